@@ -0,0 +1,217 @@
+//! Recovering circular arcs from polylines.
+//!
+//! CAD formats like DXF and the output of many toolpath generators describe curved shapes
+//! with long polylines, which are expensive to store and make cutting machines slow down at
+//! each vertex. [fit_arcs](fn.fit_arcs.html) looks for runs of consecutive points that lie on
+//! a circle and replaces them with a single arc segment, keeping the other edges as line
+//! segments.
+//!
+//! The polyline is walked greedily: from each point, the run is extended as long as all of
+//! its vertices are within the tolerance of the circle going through the first, middle and
+//! last point of the run, the run turns consistently around the center, and the edges don't
+//! move away from the arc by more than the tolerance either. Runs that are straight within
+//! the tolerance are left as lines.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # use lyon_path::{Path, Segment};
+//! # use lyon_path_builder::*;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::arc_fit::*;
+//! # fn main() {
+//! # let polyline: Vec<Point> = (0..20).map(|i| {
+//! #     let angle = i as f32 * 0.1;
+//! #     point(angle.cos() * 10.0, angle.sin() * 10.0)
+//! # }).collect();
+//! let mut builder = Path::builder();
+//! builder.move_to(polyline[0]);
+//! for segment in fit_arcs(&polyline, 0.01) {
+//!     match segment {
+//!         Segment::Line(line) => { builder.line_to(line.to); }
+//!         Segment::Arc(arc) => {
+//!             // Emit the arc, for example as a G2/G3 command, or approximate it.
+//!             arc.flattened_for_each(0.01, &mut |p| { builder.line_to(p); });
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! let path = builder.build();
+//! # let _ = path;
+//! # }
+//! ```
+
+use std::f32::consts::PI;
+
+use bezier::{Arc, LineSegment};
+use path::Segment;
+use core::math::*;
+
+// The smallest number of edges replaced by an arc, three points always fit a circle.
+const MIN_ARC_EDGES: usize = 3;
+
+/// Replaces the runs of points of a polyline that lie on a circle with arc segments.
+///
+/// Returns a sequence of connected line and arc segments going from the first to the last
+/// point of the polyline. The vertices of the polyline are within `tolerance` of the arcs
+/// that replace them, and so are the edges.
+pub fn fit_arcs(points: &[Point], tolerance: f32) -> Vec<Segment> {
+    let mut result = Vec::new();
+    let n = points.len();
+    let mut start = 0;
+    while start + 1 < n {
+        let mut best = None;
+        let mut end = start + MIN_ARC_EDGES;
+        while end < n {
+            match fit_arc(&points[start..end + 1], tolerance) {
+                Some(arc) => { best = Some((end, arc)); }
+                None => { break; }
+            }
+            end += 1;
+        }
+
+        match best {
+            Some((end, arc)) => {
+                result.push(Segment::Arc(arc));
+                start = end;
+            }
+            None => {
+                result.push(Segment::Line(LineSegment { from: points[start], to: points[start + 1] }));
+                start += 1;
+            }
+        }
+    }
+
+    return result;
+}
+
+// Returns the arc going through all of the points within the tolerance, if any.
+fn fit_arc(points: &[Point], tolerance: f32) -> Option<Arc> {
+    let first = points[0];
+    let last = points[points.len() - 1];
+
+    // Leave the straight runs alone, the circle would be huge and badly conditioned.
+    let chord = last - first;
+    let chord_length = chord.length();
+    let straight = chord_length > 0.0 && points.iter().all(|p| {
+        (chord.cross(*p - first) / chord_length).abs() <= tolerance
+    });
+    if straight {
+        return None;
+    }
+
+    let center = match circle_center(first, points[points.len() / 2], last) {
+        Some(center) => center,
+        None => { return None; }
+    };
+    let radius = (first - center).length();
+
+    let mut sweep = 0.0;
+    for i in 0..points.len() - 1 {
+        let a = points[i] - center;
+        let b = points[i + 1] - center;
+        if (b.length() - radius).abs() > tolerance {
+            return None;
+        }
+
+        // All of the edges must turn in the same direction.
+        let angle = a.cross(b).atan2(a.dot(b));
+        if angle == 0.0 || (sweep != 0.0 && (angle > 0.0) != (sweep > 0.0)) {
+            return None;
+        }
+        sweep += angle;
+
+        // The middle of the edge is where it is the furthest from the arc.
+        let middle = points[i].lerp(points[i + 1], 0.5);
+        if radius - (middle - center).length() > tolerance {
+            return None;
+        }
+    }
+
+    if sweep.abs() > 2.0 * PI {
+        return None;
+    }
+
+    let start = first - center;
+    return Some(Arc {
+        center: center,
+        radius: radius,
+        start_angle: Radians::new(start.y.atan2(start.x)),
+        sweep_angle: Radians::new(sweep),
+    });
+}
+
+// The center of the circle going through three points, None if they are aligned.
+fn circle_center(a: Point, b: Point, c: Point) -> Option<Point> {
+    let ab = b - a;
+    let ac = c - a;
+    let d = 2.0 * ab.cross(ac);
+    if d == 0.0 {
+        return None;
+    }
+
+    let ab2 = ab.square_length();
+    let ac2 = ac.square_length();
+    return Some(a + vec2(
+        (ac.y * ab2 - ab.y * ac2) / d,
+        (ab.x * ac2 - ac.x * ab2) / d,
+    ));
+}
+
+#[test]
+fn test_fit_arcs() {
+    // A quarter of a circle followed by two edges.
+    let mut points = Vec::new();
+    for i in 0..9 {
+        let angle = i as f32 * PI / 16.0;
+        points.push(point(10.0 * angle.cos(), 10.0 * angle.sin()));
+    }
+    points.push(point(-10.0, 10.0));
+    points.push(point(-20.0, 10.0));
+
+    let segments = fit_arcs(&points, 0.1);
+    assert_eq!(segments.len(), 3);
+    match segments[0] {
+        Segment::Arc(ref arc) => {
+            assert!((arc.center - point(0.0, 0.0)).length() < 0.001);
+            assert!((arc.radius - 10.0).abs() < 0.001);
+            assert!(arc.start_angle.get().abs() < 0.001);
+            assert!((arc.sweep_angle.get() - PI * 0.5).abs() < 0.001);
+        }
+        _ => { panic!("{:?}", segments[0]); }
+    }
+    match segments[1] {
+        Segment::Line(ref line) => {
+            assert_eq!(line.from, points[8]);
+            assert_eq!(line.to, points[9]);
+        }
+        _ => { panic!("{:?}", segments[1]); }
+    }
+
+    // The edges are too far from the arc with a smaller tolerance.
+    assert_eq!(fit_arcs(&points, 0.01).len(), points.len() - 1);
+}
+
+#[test]
+fn test_fit_arcs_straight() {
+    let points = [
+        point(0.0, 0.0),
+        point(1.0, 0.0),
+        point(2.0, 0.001),
+        point(3.0, 0.0),
+        point(4.0, 0.0),
+    ];
+    let segments = fit_arcs(&points, 0.01);
+    assert_eq!(segments.len(), 4);
+    for segment in &segments {
+        match *segment {
+            Segment::Line(_) => {}
+            _ => { panic!("{:?}", segment); }
+        }
+    }
+    assert!(fit_arcs(&points[..1], 0.01).is_empty());
+}
@@ -0,0 +1,428 @@
+//! Boolean operations (union, intersection, difference and xor) between the fills of two
+//! paths.
+//!
+//! This makes it possible to clip shapes on the CPU before tessellating them, instead of
+//! relying on the stencil buffer.
+//!
+//! The edges of both paths are split at their intersections, which are found with a sweep
+//! over the x axis, so that they only touch at their end points. Each of the resulting edges
+//! is then kept if the result of the operation differs on its two sides, which is determined
+//! with the winding numbers of the two paths on each side and the fill rule. The kept edges
+//! are oriented with the result on their left and chained into polygons. The classification
+//! is quadratic in the number of edges.
+//!
+//! All sub-paths are considered closed.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::FillRule;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::boolean_ops::*;
+//! # fn main() {
+//! # let mut shape = Path::builder();
+//! # shape.move_to(point(0.0, 0.0));
+//! # shape.line_to(point(10.0, 0.0));
+//! # shape.line_to(point(10.0, 10.0));
+//! # shape.close();
+//! # let shape = shape.build();
+//! # let mut clip = Path::builder();
+//! # clip.move_to(point(5.0, -5.0));
+//! # clip.line_to(point(15.0, -5.0));
+//! # clip.line_to(point(15.0, 5.0));
+//! # clip.line_to(point(5.0, 5.0));
+//! # clip.close();
+//! # let clip = clip.build();
+//! let mut builder = Path::builder();
+//! boolean_op(
+//!     shape.path_iter().flattened(0.1),
+//!     clip.path_iter().flattened(0.1),
+//!     BooleanOp::Intersection,
+//!     FillRule::NonZero,
+//!     &mut builder,
+//! );
+//! let clipped = builder.build();
+//! # let _ = clipped;
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use core::{FlattenedEvent, FillRule};
+use core::math::*;
+use path_builder::BaseBuilder;
+
+/// The operations supported by [boolean_op](fn.boolean_op.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BooleanOp {
+    /// The area covered by either path.
+    Union,
+    /// The area covered by both paths.
+    Intersection,
+    /// The area covered by the first path and not by the second one.
+    Difference,
+    /// The area covered by exactly one of the paths.
+    Xor,
+}
+
+impl BooleanOp {
+    fn apply(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            BooleanOp::Union => in_a || in_b,
+            BooleanOp::Intersection => in_a && in_b,
+            BooleanOp::Difference => in_a && !in_b,
+            BooleanOp::Xor => in_a != in_b,
+        }
+    }
+}
+
+/// Computes a boolean operation between the fills of two flattened paths and sends the
+/// result to a path builder.
+///
+/// The fill rule applies to both paths. The result only has closed sub-paths without
+/// self-intersections, with outer polygons in the positive (counter-clockwise with the y axis
+/// pointing up) orientation and holes in the opposite orientation, so it can be filled with
+/// either fill rule.
+pub fn boolean_op<A, B, Builder>(a: A, b: B, op: BooleanOp, fill_rule: FillRule, output: &mut Builder)
+where
+    A: Iterator<Item = FlattenedEvent>,
+    B: Iterator<Item = FlattenedEvent>,
+    Builder: BaseBuilder,
+{
+    for polygon in boolean_op_polygons(a, b, op, fill_rule) {
+        output.move_to(polygon[0]);
+        for &p in &polygon[1..] {
+            output.line_to(p);
+        }
+        output.close();
+    }
+}
+
+/// Same as [boolean_op](fn.boolean_op.html), returning the polygons of the result.
+pub fn boolean_op_polygons<A, B>(a: A, b: B, op: BooleanOp, fill_rule: FillRule) -> Vec<Vec<Point>>
+where
+    A: Iterator<Item = FlattenedEvent>,
+    B: Iterator<Item = FlattenedEvent>,
+{
+    let mut edges = Vec::new();
+    collect_edges(a, &mut edges);
+    let count_a = edges.len();
+    collect_edges(b, &mut edges);
+
+    // Split the edges at the intersections so that they only touch at their end points.
+    let mut splits: Vec<Vec<(f32, Point)>> = vec![Vec::new(); edges.len()];
+    for_each_candidate_pair(&edges, &mut |i, j| intersect(&edges, i, j, &mut splits));
+
+    let mut sub_edges = Vec::new();
+    for (i, &(a, b)) in edges.iter().enumerate() {
+        let points = &mut splits[i];
+        points.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap());
+        let mut from = a;
+        for &(_, p) in points.iter() {
+            if p != from && p != b {
+                sub_edges.push((from, p));
+                from = p;
+            }
+        }
+        sub_edges.push((from, b));
+    }
+
+    let (edges_a, edges_b) = edges.split_at(count_a);
+    let inside = |p: Point| {
+        op.apply(
+            is_inside(winding_number(edges_a, p), fill_rule),
+            is_inside(winding_number(edges_b, p), fill_rule),
+        )
+    };
+
+    // Keep the sub-edges that separate the inside of the result from the outside.
+    let mut boundary = Vec::new();
+    let mut seen = HashMap::new();
+    for &(a, b) in &sub_edges {
+        let v = b - a;
+        let length = v.length();
+        let mid = a.lerp(b, 0.5);
+        let left = vec2(-v.y, v.x) * (0.001 / length.max(0.000001)) * length.min(1.0);
+        let edge = match (inside(mid + left), inside(mid - left)) {
+            (true, false) => (a, b),
+            (false, true) => (b, a),
+            _ => { continue; }
+        };
+        // Overlapping edges of the two paths produce the same edge.
+        if seen.insert((key(edge.0), key(edge.1)), ()).is_none() {
+            boundary.push(edge);
+        }
+    }
+
+    return link_edges(&boundary);
+}
+
+fn is_inside(winding: i32, fill_rule: FillRule) -> bool {
+    match fill_rule {
+        FillRule::EvenOdd => winding % 2 != 0,
+        FillRule::NonZero => winding != 0,
+    }
+}
+
+// Adds the edges of the sub-paths, closing them.
+fn collect_edges<Iter>(path: Iter, edges: &mut Vec<(Point, Point)>)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let mut first = point(0.0, 0.0);
+    let mut current = point(0.0, 0.0);
+    for evt in path {
+        let to = match evt {
+            FlattenedEvent::MoveTo(to) => {
+                if current != first {
+                    edges.push((current, first));
+                }
+                first = to;
+                current = to;
+                continue;
+            }
+            FlattenedEvent::LineTo(to) => to,
+            FlattenedEvent::Close => first,
+        };
+        if to != current {
+            edges.push((current, to));
+        }
+        current = to;
+    }
+    if current != first {
+        edges.push((current, first));
+    }
+}
+
+// Invokes the callback with the pairs of edges whose ranges of x overlap.
+fn for_each_candidate_pair<F>(edges: &[(Point, Point)], call_back: &mut F)
+where
+    F: FnMut(usize, usize),
+{
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by(|&i, &j| {
+        let a = edges[i].0.x.min(edges[i].1.x);
+        let b = edges[j].0.x.min(edges[j].1.x);
+        a.partial_cmp(&b).unwrap()
+    });
+
+    let mut active: Vec<(f32, usize)> = Vec::new();
+    for &i in &order {
+        let (a, b) = edges[i];
+        let min_x = a.x.min(b.x);
+        active.retain(|&(max_x, _)| max_x >= min_x);
+        for &(_, j) in &active {
+            call_back(j.min(i), j.max(i));
+        }
+        active.push((a.x.max(b.x), i));
+    }
+}
+
+fn key(p: Point) -> (u32, u32) { (p.x.to_bits(), p.y.to_bits()) }
+
+fn intersect(edges: &[(Point, Point)], i: usize, j: usize, splits: &mut Vec<Vec<(f32, Point)>>) {
+    let (a1, b1) = edges[i];
+    let (a2, b2) = edges[j];
+    if a1.x.max(b1.x) < a2.x.min(b2.x) || a2.x.max(b2.x) < a1.x.min(b1.x) ||
+        a1.y.max(b1.y) < a2.y.min(b2.y) || a2.y.max(b2.y) < a1.y.min(b1.y) {
+        return;
+    }
+
+    let d1 = b1 - a1;
+    let d2 = b2 - a2;
+    let denom = d1.cross(d2);
+    let epsilon = 0.00001;
+
+    // The parameter of a point on an edge, if it is strictly inside of the edge.
+    let on_edge = |p: Point, a: Point, d: Vec2| -> Option<f32> {
+        let square_length = d.square_length();
+        let t = (p - a).dot(d) / square_length;
+        let distance = (p - a).cross(d).abs() / square_length.sqrt();
+        if t > epsilon && t < 1.0 - epsilon && distance < epsilon {
+            Some(t)
+        } else {
+            None
+        }
+    };
+
+    if denom.abs() < epsilon * d1.length() * d2.length() {
+        // Parallel edges, split them where they overlap.
+        for &p in &[a2, b2] {
+            if let Some(t) = on_edge(p, a1, d1) {
+                splits[i].push((t, p));
+            }
+        }
+        for &p in &[a1, b1] {
+            if let Some(t) = on_edge(p, a2, d2) {
+                splits[j].push((t, p));
+            }
+        }
+        return;
+    }
+
+    let t = (a2 - a1).cross(d2) / denom;
+    let u = (a2 - a1).cross(d1) / denom;
+    if t < -epsilon || t > 1.0 + epsilon || u < -epsilon || u > 1.0 + epsilon {
+        return;
+    }
+
+    // Snap to the end points so that the edges share the exact same points.
+    let p = if t <= epsilon {
+        a1
+    } else if t >= 1.0 - epsilon {
+        b1
+    } else if u <= epsilon {
+        a2
+    } else if u >= 1.0 - epsilon {
+        b2
+    } else {
+        a1 + d1 * t
+    };
+
+    if t > epsilon && t < 1.0 - epsilon {
+        splits[i].push((t, p));
+    }
+    if u > epsilon && u < 1.0 - epsilon {
+        splits[j].push((u, p));
+    }
+}
+
+fn winding_number(edges: &[(Point, Point)], p: Point) -> i32 {
+    let mut winding = 0;
+    for &(a, b) in edges {
+        if a.y <= p.y {
+            if b.y > p.y && (b - a).cross(p - a) > 0.0 {
+                winding += 1;
+            }
+        } else if b.y <= p.y && (b - a).cross(p - a) < 0.0 {
+            winding -= 1;
+        }
+    }
+
+    return winding;
+}
+
+// Chains the edges into closed polygons.
+fn link_edges(edges: &[(Point, Point)]) -> Vec<Vec<Point>> {
+    let mut outgoing: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (i, &(a, _)) in edges.iter().enumerate() {
+        outgoing.entry(key(a)).or_insert_with(Vec::new).push(i);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut polygons = Vec::new();
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let mut polygon = vec![edges[start].0];
+        let mut current = edges[start].1;
+        while current != edges[start].0 {
+            polygon.push(current);
+            let next = outgoing.get(&key(current))
+                .and_then(|candidates| candidates.iter().cloned().find(|&e| !used[e]));
+            match next {
+                Some(e) => {
+                    used[e] = true;
+                    current = edges[e].1;
+                }
+                None => { break; }
+            }
+        }
+
+        if polygon.len() >= 3 {
+            polygons.push(polygon);
+        }
+    }
+
+    return polygons;
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[cfg(test)]
+fn add_square(builder: &mut ::path::Builder, x: f32, y: f32, size: f32) {
+    builder.move_to(point(x, y));
+    builder.line_to(point(x + size, y));
+    builder.line_to(point(x + size, y + size));
+    builder.line_to(point(x, y + size));
+    builder.close();
+}
+
+#[cfg(test)]
+fn area(polygons: &[Vec<Point>]) -> f32 {
+    let mut area = 0.0;
+    for polygon in polygons {
+        let n = polygon.len();
+        for i in 0..n {
+            area += polygon[i].to_vector().cross(polygon[(i + 1) % n].to_vector()) * 0.5;
+        }
+    }
+
+    return area;
+}
+
+#[cfg(test)]
+fn op_area(a: &Path, b: &Path, op: BooleanOp, fill_rule: FillRule) -> f32 {
+    area(&boolean_op_polygons(a.path_iter().flattened(0.1), b.path_iter().flattened(0.1), op, fill_rule))
+}
+
+#[test]
+fn test_boolean_ops_squares() {
+    let mut builder = Path::builder();
+    add_square(&mut builder, 0.0, 0.0, 10.0);
+    let a = builder.build();
+    let mut builder = Path::builder();
+    add_square(&mut builder, 5.0, 5.0, 10.0);
+    let b = builder.build();
+
+    for &fill_rule in &[FillRule::EvenOdd, FillRule::NonZero] {
+        assert!((op_area(&a, &b, BooleanOp::Union, fill_rule) - 175.0).abs() < 0.001);
+        assert!((op_area(&a, &b, BooleanOp::Intersection, fill_rule) - 25.0).abs() < 0.001);
+        assert!((op_area(&a, &b, BooleanOp::Difference, fill_rule) - 75.0).abs() < 0.001);
+        assert!((op_area(&a, &b, BooleanOp::Xor, fill_rule) - 150.0).abs() < 0.001);
+    }
+
+    let result = boolean_op_polygons(a.path_iter().flattened(0.1), b.path_iter().flattened(0.1), BooleanOp::Union, FillRule::NonZero);
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].len(), 8);
+
+    // Squares that share an edge are merged.
+    let mut builder = Path::builder();
+    add_square(&mut builder, 10.0, 0.0, 10.0);
+    let c = builder.build();
+    let result = boolean_op_polygons(a.path_iter().flattened(0.1), c.path_iter().flattened(0.1), BooleanOp::Union, FillRule::NonZero);
+    assert_eq!(result.len(), 1);
+    assert!((area(&result) - 200.0).abs() < 0.001);
+}
+
+#[test]
+fn test_boolean_ops_fill_rules() {
+    // Two overlapping squares in the same path.
+    let mut builder = Path::builder();
+    add_square(&mut builder, 0.0, 0.0, 10.0);
+    add_square(&mut builder, 5.0, 5.0, 10.0);
+    let a = builder.build();
+    let empty = Path::new();
+
+    assert!((op_area(&a, &empty, BooleanOp::Union, FillRule::NonZero) - 175.0).abs() < 0.001);
+    assert!((op_area(&a, &empty, BooleanOp::Union, FillRule::EvenOdd) - 150.0).abs() < 0.001);
+
+    // A hole.
+    let mut builder = Path::builder();
+    add_square(&mut builder, 1.0, 1.0, 2.0);
+    let hole = builder.build();
+    assert!((op_area(&a, &hole, BooleanOp::Difference, FillRule::EvenOdd) - 146.0).abs() < 0.001);
+}
@@ -0,0 +1,394 @@
+//! Tight bounding volumes: oriented rectangles and k-DOPs.
+//!
+//! Axis-aligned bounding rectangles are very loose around rotated content. This module
+//! computes two tighter volumes, which are still cheap to test against:
+//!
+//! - [min_area_rect](fn.min_area_rect.html) finds the oriented rectangle of minimum area
+//!   that contains a set of points. One of the sides of this rectangle is always aligned with
+//!   an edge of the convex hull of the points, so the edges of the hull are visited with
+//!   rotating calipers, in linear time after the hull is computed.
+//! - [Kdop](struct.Kdop.html) (discrete oriented polytope) bounds the points between pairs of
+//!   lines in `k / 2` fixed directions. With `k = 4` it is the axis-aligned bounding
+//!   rectangle, with `k = 8` an octagon.
+//!
+//! To bound a path, [path_points](fn.path_points.html) returns the end points and control
+//! points of its segments, which contain the curves. Collecting the points of a flattened path
+//! gives tighter volumes within the flattening tolerance.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::bounds::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 2.0));
+//! # builder.quadratic_bezier_to(point(12.0, 8.0), point(4.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let cursor = point(5.0, 4.0);
+//! let points = path_points(path.iter());
+//! let obb = min_area_rect(&points).unwrap();
+//! let octagon = Kdop::new(&points, 8).unwrap();
+//! if octagon.contains(cursor) && obb.contains(cursor) {
+//!     // ...
+//! }
+//! # }
+//! ```
+
+use std::f32::consts::PI;
+use std::cmp::Ordering;
+
+use core::PathEvent;
+use core::math::*;
+
+/// Returns the end points and control points of the segments of a path.
+pub fn path_points<Iter: Iterator<Item = PathEvent>>(path: Iter) -> Vec<Point> {
+    let mut points = Vec::new();
+    for evt in path {
+        match evt {
+            PathEvent::MoveTo(to) | PathEvent::LineTo(to) => { points.push(to); }
+            PathEvent::QuadraticTo(ctrl, to) => {
+                points.push(ctrl);
+                points.push(to);
+            }
+            PathEvent::CubicTo(ctrl1, ctrl2, to) => {
+                points.push(ctrl1);
+                points.push(ctrl2);
+                points.push(to);
+            }
+            PathEvent::Close => {}
+        }
+    }
+
+    return points;
+}
+
+/// Computes the convex hull of a set of points.
+///
+/// The hull is counter-clockwise (with the y axis pointing up), starts with the point with the
+/// smallest x coordinate and doesn't contain collinear points.
+pub fn convex_hull(points: &[Point]) -> Vec<Point> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| {
+        match a.x.partial_cmp(&b.x).unwrap() {
+            Ordering::Equal => a.y.partial_cmp(&b.y).unwrap(),
+            ordering => ordering,
+        }
+    });
+    if sorted.len() < 3 {
+        sorted.dedup();
+        return sorted;
+    }
+
+    // Andrew's monotone chain.
+    let mut hull: Vec<Point> = Vec::with_capacity(sorted.len() + 1);
+    for pass in 0..2 {
+        let start = hull.len();
+        for i in 0..sorted.len() {
+            let p = if pass == 0 { sorted[i] } else { sorted[sorted.len() - 1 - i] };
+            while hull.len() >= start + 2 {
+                let a = hull[hull.len() - 2];
+                let b = hull[hull.len() - 1];
+                if (b - a).cross(p - a) > 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // The last point is the first one of the other chain.
+        hull.pop();
+    }
+    // When all of the points are equal.
+    hull.dedup();
+
+    return hull;
+}
+
+/// A rectangle with an arbitrary orientation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OrientedRect {
+    pub center: Point,
+    /// Normalized direction of the first pair of sides.
+    pub axis: Vec2,
+    /// Half of the size of the rectangle along `axis` (x) and perpendicularly to it (y).
+    pub half_extents: Vec2,
+}
+
+impl OrientedRect {
+    /// The normalized direction of the second pair of sides.
+    pub fn normal(&self) -> Vec2 { vec2(-self.axis.y, self.axis.x) }
+
+    pub fn area(&self) -> f32 { 4.0 * self.half_extents.x * self.half_extents.y }
+
+    /// The corners, counter-clockwise.
+    pub fn corners(&self) -> [Point; 4] {
+        let u = self.axis * self.half_extents.x;
+        let v = self.normal() * self.half_extents.y;
+        [
+            self.center - u - v,
+            self.center + u - v,
+            self.center + u + v,
+            self.center - u + v,
+        ]
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        let d = p - self.center;
+        d.dot(self.axis).abs() <= self.half_extents.x && d.dot(self.normal()).abs() <= self.half_extents.y
+    }
+}
+
+/// Computes the oriented rectangle of minimum area containing a set of points.
+///
+/// Returns None if there are no points.
+pub fn min_area_rect(points: &[Point]) -> Option<OrientedRect> {
+    let hull = convex_hull(points);
+    let n = hull.len();
+    if n == 0 {
+        return None;
+    }
+    if n == 1 {
+        return Some(OrientedRect { center: hull[0], axis: vec2(1.0, 0.0), half_extents: vec2(0.0, 0.0) });
+    }
+    if n == 2 {
+        let d = hull[1] - hull[0];
+        return Some(OrientedRect {
+            center: hull[0] + d * 0.5,
+            axis: d / d.length(),
+            half_extents: vec2(d.length() * 0.5, 0.0),
+        });
+    }
+
+    let edge = |i: usize| {
+        let d = hull[(i + 1) % n] - hull[i];
+        let u = d / d.length();
+        (u, vec2(-u.y, u.x))
+    };
+
+    // The extreme points along the edge (right and left) and away from it (top). They all
+    // move forward around the hull as the edge does.
+    let (u, v) = edge(0);
+    let mut right = 0;
+    let mut top = 0;
+    let mut left = 0;
+    for i in 1..n {
+        if hull[i].to_vector().dot(u) > hull[right].to_vector().dot(u) { right = i; }
+        if hull[i].to_vector().dot(v) > hull[top].to_vector().dot(v) { top = i; }
+        if hull[i].to_vector().dot(u) < hull[left].to_vector().dot(u) { left = i; }
+    }
+
+    let mut result: Option<OrientedRect> = None;
+    for i in 0..n {
+        let (u, v) = edge(i);
+        let origin = hull[i];
+        let dot_u = |j: usize| (hull[j] - origin).dot(u);
+        let dot_v = |j: usize| (hull[j] - origin).dot(v);
+        while dot_u((right + 1) % n) > dot_u(right) { right = (right + 1) % n; }
+        while dot_v((top + 1) % n) > dot_v(top) { top = (top + 1) % n; }
+        while dot_u((left + 1) % n) < dot_u(left) { left = (left + 1) % n; }
+
+        let (min_u, max_u) = (dot_u(left), dot_u(right));
+        let height = dot_v(top);
+        let rect = OrientedRect {
+            center: origin + u * ((min_u + max_u) * 0.5) + v * (height * 0.5),
+            axis: u,
+            half_extents: vec2((max_u - min_u) * 0.5, height * 0.5),
+        };
+        let smaller = match result {
+            Some(ref best) => rect.area() < best.area(),
+            None => true,
+        };
+        if smaller {
+            result = Some(rect);
+        }
+    }
+
+    return result;
+}
+
+/// A discrete oriented polytope: the intersection of slabs in `k / 2` directions evenly
+/// spread over half a turn, starting with the x axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kdop {
+    /// For each direction, the smallest projection of the points on it.
+    pub min: Vec<f32>,
+    /// For each direction, the largest projection of the points on it.
+    pub max: Vec<f32>,
+}
+
+impl Kdop {
+    /// Computes the k-DOP of a set of points.
+    ///
+    /// `k` is rounded down to an even number and at least 4. Returns None if there are no
+    /// points.
+    pub fn new(points: &[Point], k: usize) -> Option<Kdop> {
+        if points.is_empty() {
+            return None;
+        }
+
+        let num_directions = (k / 2).max(2);
+        let mut kdop = Kdop {
+            min: vec![::std::f32::MAX; num_directions],
+            max: vec![::std::f32::MIN; num_directions],
+        };
+        for j in 0..num_directions {
+            let direction = kdop.direction(j);
+            for p in points {
+                let d = p.to_vector().dot(direction);
+                kdop.min[j] = kdop.min[j].min(d);
+                kdop.max[j] = kdop.max[j].max(d);
+            }
+        }
+
+        return Some(kdop);
+    }
+
+    /// The number of bounding lines.
+    pub fn k(&self) -> usize { self.min.len() * 2 }
+
+    /// The normalized direction of a slab.
+    pub fn direction(&self, idx: usize) -> Vec2 {
+        let angle = PI * idx as f32 / self.min.len() as f32;
+        vec2(angle.cos(), angle.sin())
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        for j in 0..self.min.len() {
+            let d = p.to_vector().dot(self.direction(j));
+            if d < self.min[j] || d > self.max[j] {
+                return false;
+            }
+        }
+
+        return true;
+    }
+
+    /// Whether two k-DOPs with the same `k` overlap.
+    ///
+    /// Touching k-DOPs are considered overlapping.
+    pub fn intersects(&self, other: &Kdop) -> bool {
+        debug_assert_eq!(self.k(), other.k());
+        for j in 0..self.min.len() {
+            if self.max[j] < other.min[j] || other.max[j] < self.min[j] {
+                return false;
+            }
+        }
+
+        return true;
+    }
+
+    /// The vertices of the polygon, counter-clockwise.
+    ///
+    /// The polygon has `k` vertices, some of which can be equal when a slab only touches the
+    /// points at a corner.
+    pub fn to_polygon(&self) -> Vec<Point> {
+        let n = self.min.len();
+        // The lines are sorted by the angle of their outward normal.
+        let line = |i: usize| {
+            let normal = self.direction(i % n);
+            if i < n { (normal, self.max[i]) } else { (-normal, -self.min[i - n]) }
+        };
+
+        let mut polygon = Vec::with_capacity(2 * n);
+        for i in 0..(2 * n) {
+            let (n1, d1) = line(i);
+            let (n2, d2) = line((i + 1) % (2 * n));
+            let det = n1.cross(n2);
+            polygon.push(point(
+                (d1 * n2.y - d2 * n1.y) / det,
+                (n1.x * d2 - n2.x * d1) / det,
+            ));
+        }
+
+        return polygon;
+    }
+}
+
+#[cfg(test)]
+fn rotated_rect() -> Vec<Point> {
+    // A 4x2 rectangle rotated by 30 degrees around (5, 3), with points inside.
+    let (sin, cos) = (PI / 6.0).sin_cos();
+    let mut points = Vec::new();
+    for &(x, y) in &[(-2.0, -1.0), (0.5, 0.0), (2.0, -1.0), (2.0, 1.0), (-1.0, 0.5), (-2.0, 1.0)] {
+        points.push(point(5.0 + x * cos - y * sin, 3.0 + x * sin + y * cos));
+    }
+
+    return points;
+}
+
+#[test]
+fn test_convex_hull() {
+    let points = [
+        point(0.0, 0.0), point(1.0, 1.0), point(2.0, 0.0), point(4.0, 0.0),
+        point(4.0, 4.0), point(0.0, 4.0), point(0.0, 2.0), point(4.0, 4.0),
+    ];
+    let hull = convex_hull(&points);
+    assert_eq!(hull, vec![point(0.0, 0.0), point(4.0, 0.0), point(4.0, 4.0), point(0.0, 4.0)]);
+
+    assert_eq!(convex_hull(&[point(1.0, 1.0), point(1.0, 1.0)]), vec![point(1.0, 1.0)]);
+    assert!(convex_hull(&[]).is_empty());
+}
+
+#[test]
+fn test_min_area_rect() {
+    let points = rotated_rect();
+    let rect = min_area_rect(&points).unwrap();
+    assert!((rect.area() - 8.0).abs() < 0.001);
+    assert!((rect.center - point(5.0, 3.0)).length() < 0.001);
+    // Aligned with one of the sides.
+    let angle = rect.axis.y.atan2(rect.axis.x);
+    let angle = ((angle / (PI * 0.5)).round() * PI * 0.5 - angle).abs();
+    assert!((angle - PI / 6.0).abs() < 0.001 || (angle - PI / 3.0).abs() < 0.001);
+    for p in &points {
+        assert!((*p - rect.center).dot(rect.axis).abs() <= rect.half_extents.x + 0.001);
+        assert!((*p - rect.center).dot(rect.normal()).abs() <= rect.half_extents.y + 0.001);
+    }
+    assert!(!rect.contains(point(5.0, 5.0)));
+
+    // Much smaller than the axis-aligned rectangle.
+    let kdop = Kdop::new(&points, 4).unwrap();
+    let aabb_area = (kdop.max[0] - kdop.min[0]) * (kdop.max[1] - kdop.min[1]);
+    assert!(rect.area() < aabb_area * 0.7);
+
+    assert!(min_area_rect(&[]).is_none());
+    let segment = min_area_rect(&[point(0.0, 0.0), point(2.0, 0.0)]).unwrap();
+    assert_eq!(segment.center, point(1.0, 0.0));
+    assert_eq!(segment.area(), 0.0);
+}
+
+#[test]
+fn test_kdop() {
+    // A diamond.
+    let points = [point(0.0, -2.0), point(2.0, 0.0), point(0.0, 2.0), point(-2.0, 0.0)];
+
+    let aabb = Kdop::new(&points, 4).unwrap();
+    assert_eq!(aabb.k(), 4);
+    assert_eq!(aabb.min, vec![-2.0, -2.0]);
+    assert_eq!(aabb.max, vec![2.0, 2.0]);
+    assert!(aabb.contains(point(1.5, 1.5)));
+
+    let octagon = Kdop::new(&points, 8).unwrap();
+    assert_eq!(octagon.k(), 8);
+    assert!(!octagon.contains(point(1.5, 1.5)));
+    assert!(octagon.contains(point(0.9, 0.9)));
+
+    // The octagon is the diamond, each vertex is repeated.
+    let polygon = octagon.to_polygon();
+    assert_eq!(polygon.len(), 8);
+    for (i, p) in polygon.iter().enumerate() {
+        assert!((*p - points[((i + 1) / 2 + 1) % 4]).length() < 0.001);
+    }
+
+    let other = Kdop::new(&[point(1.5, 1.5), point(3.0, 3.0)], 8).unwrap();
+    assert!(aabb.intersects(&Kdop::new(&[point(1.5, 1.5), point(3.0, 3.0)], 4).unwrap()));
+    assert!(!octagon.intersects(&other));
+}
@@ -0,0 +1,612 @@
+//! Buffering (offsetting) the area of a path.
+//!
+//! The buffer of a shape at a positive distance is the set of points within that distance of
+//! the shape, and at a negative distance the shape shrunk by that distance. Unlike a stroke,
+//! which is a set of overlapping triangles along the edges, the buffer is returned as a
+//! clean path without self-intersections that can be used for hit testing, for geofencing
+//! or as the input of other operations.
+//!
+//! The edges of the path are offset and connected with the requested line join, then the
+//! parts of the offset curve that overlap are resolved by only keeping the boundary of the
+//! area where the winding number of the offset curve is positive. This last step is
+//! quadratic in the number of edges.
+//!
+//! Closed sub-paths are treated as polygons, and must be oriented consistently with holes
+//! in the opposite direction of the outer polygons. Open sub-paths are treated as lines,
+//! buffered on both sides with the requested line cap, and only for positive distances.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::{LineCap, LineJoin};
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::buffer::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(100.0, 0.0));
+//! # builder.line_to(point(100.0, 50.0));
+//! # let path = builder.build();
+//! let mut builder = Path::builder();
+//! buffer(path.path_iter().flattened(0.1), 10.0, LineJoin::Round, LineCap::Round, 0.1, &mut builder);
+//! let geofence = builder.build();
+//! # let _ = geofence;
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use core::{FlattenedEvent, LineCap, LineJoin};
+use core::math::*;
+use path_builder::BaseBuilder;
+
+/// Miter joins longer than this many times the distance are replaced with bevel joins
+/// (or clipped with `LineJoin::MiterClip`). Same as the default miter limit of SVG.
+pub const MITER_LIMIT: f32 = 4.0;
+
+/// Computes the buffer of a flattened path and sends it to a path builder.
+///
+/// `tolerance` is the maximum distance between the round joins and caps and the circle
+/// arcs they approximate. The result only has closed sub-paths, with outer polygons in the
+/// positive (counter-clockwise with the y axis pointing up) orientation and holes in the
+/// opposite orientation.
+pub fn buffer<Iter, Builder>(
+    path: Iter,
+    distance: f32,
+    join: LineJoin,
+    cap: LineCap,
+    tolerance: f32,
+    output: &mut Builder,
+) where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Builder: BaseBuilder,
+{
+    for polygon in buffer_polygons(path, distance, join, cap, tolerance) {
+        output.move_to(polygon[0]);
+        for &p in &polygon[1..] {
+            output.line_to(p);
+        }
+        output.close();
+    }
+}
+
+/// Same as [buffer](fn.buffer.html), returning the polygons of the result.
+pub fn buffer_polygons<Iter>(
+    path: Iter,
+    distance: f32,
+    join: LineJoin,
+    cap: LineCap,
+    tolerance: f32,
+) -> Vec<Vec<Point>>
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let (mut polygons, lines) = collect_sub_paths(path);
+    let params = Params {
+        distance: distance,
+        join: join,
+        cap: cap,
+        tolerance: tolerance.max(0.0001),
+    };
+
+    // The orientation of the path decides which polygons are holes.
+    let area: f32 = polygons.iter().map(|polygon| signed_area(polygon)).sum();
+    if area < 0.0 {
+        for polygon in &mut polygons {
+            polygon.reverse();
+        }
+    }
+
+    let mut offset_curves = Vec::new();
+    for polygon in &polygons {
+        if polygon.len() < 3 && distance <= 0.0 {
+            continue;
+        }
+        let mut curve = Vec::new();
+        offset_polygon(polygon, &[], &params, &mut curve);
+        offset_curves.push(curve);
+    }
+
+    if distance > 0.0 {
+        for line in &lines {
+            let mut curve = Vec::new();
+            offset_line(line, &params, &mut curve);
+            offset_curves.push(curve);
+        }
+    }
+
+    return resolve_positive_winding(&offset_curves);
+}
+
+struct Params {
+    distance: f32,
+    join: LineJoin,
+    cap: LineCap,
+    tolerance: f32,
+}
+
+// Offsets a polygon on the right side of its edges (outside for a counter-clockwise polygon).
+// The vertices in `caps` are the ends of a line where the polygon turns back, they get a cap
+// instead of a join.
+fn offset_polygon(polygon: &[Point], caps: &[usize], params: &Params, output: &mut Vec<Point>) {
+    let n = polygon.len();
+    let distance = params.distance;
+    for i in 0..n {
+        let p = polygon[i];
+        let d1 = (p - polygon[(i + n - 1) % n]).normalize();
+        let d2 = (polygon[(i + 1) % n] - p).normalize();
+        let n1 = vec2(d1.y, -d1.x);
+        let n2 = vec2(d2.y, -d2.x);
+        let a = p + n1 * distance;
+        let b = p + n2 * distance;
+
+        if caps.contains(&i) {
+            add_cap(p, a, b, d1, params, output);
+            continue;
+        }
+
+        let turn = d1.cross(d2);
+        if turn * distance > 0.0 || (turn == 0.0 && d1.dot(d2) < 0.0) {
+            add_join(p, a, b, d1, d2, params, output);
+        } else if turn * distance < 0.0 {
+            // The offset edges overlap, going through the vertex keeps the winding number
+            // of the overlapping part positive.
+            output.push(a);
+            output.push(p);
+            output.push(b);
+        } else {
+            output.push(a);
+        }
+    }
+}
+
+// A line is offset as the polygon that goes along the line and back.
+fn offset_line(line: &[Point], params: &Params, output: &mut Vec<Point>) {
+    let n = line.len();
+    if n == 1 {
+        let p = line[0];
+        let r = params.distance;
+        match params.cap {
+            LineCap::Butt => {}
+            LineCap::Square => {
+                output.extend_from_slice(&[
+                    p + vec2(-r, -r),
+                    p + vec2(r, -r),
+                    p + vec2(r, r),
+                    p + vec2(-r, r),
+                ]);
+            }
+            LineCap::Round => {
+                let start = p + vec2(r, 0.0);
+                add_arc(p, start, 2.0 * PI, params, output);
+            }
+        }
+        return;
+    }
+
+    let mut polygon = line.to_vec();
+    polygon.extend(line[1..n - 1].iter().rev().cloned());
+    offset_polygon(&polygon, &[0, n - 1], params, output);
+}
+
+fn add_join(p: Point, a: Point, b: Point, d1: Vec2, d2: Vec2, params: &Params, output: &mut Vec<Point>) {
+    let distance = params.distance.abs();
+    match params.join {
+        LineJoin::Round => {
+            output.push(a);
+            let (v1, v2) = (a - p, b - p);
+            let mut angle = v1.cross(v2).atan2(v1.dot(v2));
+            if params.distance > 0.0 && angle < 0.0 {
+                angle += 2.0 * PI;
+            } else if params.distance < 0.0 && angle > 0.0 {
+                angle -= 2.0 * PI;
+            }
+            add_arc(p, a, angle, params, output);
+            output.push(b);
+        }
+        LineJoin::Miter | LineJoin::MiterClip => {
+            output.push(a);
+            // The miter point is where the two offset edges meet.
+            let denom = d1.cross(d2);
+            let miter = if denom != 0.0 {
+                let t = (b - a).cross(d2) / denom;
+                Some(a + d1 * t)
+            } else {
+                None
+            };
+            match miter {
+                Some(m) if (m - p).length() <= MITER_LIMIT * distance => {
+                    output.push(m);
+                }
+                _ if params.join == LineJoin::MiterClip => {
+                    // Clip the miter with a line perpendicular to the bisector.
+                    let bisector = ((a - p) + (b - p)).normalize();
+                    let bisector = if bisector.x.is_finite() {
+                        bisector
+                    } else {
+                        d1
+                    };
+                    let limit = MITER_LIMIT * distance;
+                    let along = |from: Point, dir: Vec2| {
+                        let speed = dir.dot(bisector);
+                        if speed.abs() < 0.000001 {
+                            return from;
+                        }
+                        from + dir * ((limit - (from - p).dot(bisector)) / speed)
+                    };
+                    output.push(along(a, d1));
+                    output.push(along(b, -d2));
+                }
+                _ => {}
+            }
+            output.push(b);
+        }
+        LineJoin::Bevel => {
+            output.push(a);
+            output.push(b);
+        }
+    }
+}
+
+fn add_cap(p: Point, a: Point, b: Point, direction: Vec2, params: &Params, output: &mut Vec<Point>) {
+    match params.cap {
+        LineCap::Butt => {
+            output.push(a);
+            output.push(b);
+        }
+        LineCap::Square => {
+            let extension = direction * params.distance;
+            output.push(a + extension);
+            output.push(b + extension);
+        }
+        LineCap::Round => {
+            output.push(a);
+            add_arc(p, a, PI, params, output);
+            output.push(b);
+        }
+    }
+}
+
+// Adds the points of an arc around the center, excluding the end points.
+fn add_arc(center: Point, start: Point, angle: f32, params: &Params, output: &mut Vec<Point>) {
+    let radius = params.distance.abs();
+    let max_step = if params.tolerance >= radius {
+        PI * 0.5
+    } else {
+        2.0 * (1.0 - params.tolerance / radius).acos()
+    };
+    let num_steps = (angle.abs() / max_step).ceil().max(1.0) as u32;
+    let v = start - center;
+    for i in 1..num_steps {
+        let a = angle * i as f32 / num_steps as f32;
+        let (sin, cos) = a.sin_cos();
+        output.push(center + vec2(v.x * cos - v.y * sin, v.x * sin + v.y * cos));
+    }
+    if angle.abs() >= 2.0 * PI {
+        output.push(start);
+    }
+}
+
+// Splits the path into closed polygons and open lines, without duplicated points.
+fn collect_sub_paths<Iter>(path: Iter) -> (Vec<Vec<Point>>, Vec<Vec<Point>>)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let mut polygons = Vec::new();
+    let mut lines = Vec::new();
+    let mut current: Vec<Point> = Vec::new();
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                if !current.is_empty() {
+                    lines.push(current.clone());
+                }
+                current.clear();
+                current.push(to);
+            }
+            FlattenedEvent::LineTo(to) => {
+                if current.last() != Some(&to) {
+                    current.push(to);
+                }
+            }
+            FlattenedEvent::Close => {
+                while current.len() > 1 && current.last() == current.first() {
+                    current.pop();
+                }
+                if current.len() >= 3 {
+                    polygons.push(current.clone());
+                } else if !current.is_empty() {
+                    lines.push(current.clone());
+                }
+                current.clear();
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    return (polygons, lines);
+}
+
+fn signed_area(polygon: &[Point]) -> f32 {
+    let n = polygon.len();
+    let mut area = 0.0;
+    for i in 0..n {
+        area += polygon[i].to_vector().cross(polygon[(i + 1) % n].to_vector());
+    }
+
+    return area * 0.5;
+}
+
+fn key(p: Point) -> (u32, u32) { (p.x.to_bits(), p.y.to_bits()) }
+
+// Returns the boundary of the area where the winding number of the polygons is positive,
+// with the area on the left of the edges.
+fn resolve_positive_winding(polygons: &[Vec<Point>]) -> Vec<Vec<Point>> {
+    let mut edges = Vec::new();
+    for polygon in polygons {
+        let n = polygon.len();
+        for i in 0..n {
+            let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+            if a != b {
+                edges.push((a, b));
+            }
+        }
+    }
+
+    // Split the edges at the intersections so that they only touch at their end points.
+    let mut splits: Vec<Vec<(f32, Point)>> = vec![Vec::new(); edges.len()];
+    for i in 0..edges.len() {
+        for j in i + 1..edges.len() {
+            intersect(&edges, i, j, &mut splits);
+        }
+    }
+
+    let mut sub_edges = Vec::new();
+    for (i, &(a, b)) in edges.iter().enumerate() {
+        let points = &mut splits[i];
+        points.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap());
+        let mut from = a;
+        for &(_, p) in points.iter() {
+            if p != from && p != b {
+                sub_edges.push((from, p));
+                from = p;
+            }
+        }
+        sub_edges.push((from, b));
+    }
+
+    // Keep the sub-edges that separate a positive winding number from a non-positive one.
+    let mut boundary = Vec::new();
+    let mut seen = HashMap::new();
+    for &(a, b) in &sub_edges {
+        let v = b - a;
+        let length = v.length();
+        let mid = a.lerp(b, 0.5);
+        let left = vec2(-v.y, v.x) * (0.001 / length.max(0.000001)) * length.min(1.0);
+        let inside_left = winding_number(&edges, mid + left) > 0;
+        let inside_right = winding_number(&edges, mid - left) > 0;
+        let edge = match (inside_left, inside_right) {
+            (true, false) => (a, b),
+            (false, true) => (b, a),
+            _ => { continue; }
+        };
+        if seen.insert((key(edge.0), key(edge.1)), ()).is_none() {
+            boundary.push(edge);
+        }
+    }
+
+    return link_edges(&boundary);
+}
+
+fn intersect(edges: &[(Point, Point)], i: usize, j: usize, splits: &mut Vec<Vec<(f32, Point)>>) {
+    let (a1, b1) = edges[i];
+    let (a2, b2) = edges[j];
+    if a1.x.max(b1.x) < a2.x.min(b2.x) || a2.x.max(b2.x) < a1.x.min(b1.x) ||
+        a1.y.max(b1.y) < a2.y.min(b2.y) || a2.y.max(b2.y) < a1.y.min(b1.y) {
+        return;
+    }
+
+    let d1 = b1 - a1;
+    let d2 = b2 - a2;
+    let denom = d1.cross(d2);
+    let epsilon = 0.00001;
+
+    // The parameter of a point on an edge, if it is strictly inside of the edge.
+    let on_edge = |p: Point, a: Point, d: Vec2| -> Option<f32> {
+        let square_length = d.square_length();
+        let t = (p - a).dot(d) / square_length;
+        let distance = (p - a).cross(d).abs() / square_length.sqrt();
+        if t > epsilon && t < 1.0 - epsilon && distance < epsilon {
+            Some(t)
+        } else {
+            None
+        }
+    };
+
+    if denom.abs() < epsilon * d1.length() * d2.length() {
+        // Parallel edges, split them where they overlap.
+        for &p in &[a2, b2] {
+            if let Some(t) = on_edge(p, a1, d1) {
+                splits[i].push((t, p));
+            }
+        }
+        for &p in &[a1, b1] {
+            if let Some(t) = on_edge(p, a2, d2) {
+                splits[j].push((t, p));
+            }
+        }
+        return;
+    }
+
+    let t = (a2 - a1).cross(d2) / denom;
+    let u = (a2 - a1).cross(d1) / denom;
+    if t < -epsilon || t > 1.0 + epsilon || u < -epsilon || u > 1.0 + epsilon {
+        return;
+    }
+
+    // Snap to the end points so that the edges share the exact same points.
+    let p = if t <= epsilon {
+        a1
+    } else if t >= 1.0 - epsilon {
+        b1
+    } else if u <= epsilon {
+        a2
+    } else if u >= 1.0 - epsilon {
+        b2
+    } else {
+        a1 + d1 * t
+    };
+
+    if t > epsilon && t < 1.0 - epsilon {
+        splits[i].push((t, p));
+    }
+    if u > epsilon && u < 1.0 - epsilon {
+        splits[j].push((u, p));
+    }
+}
+
+fn winding_number(edges: &[(Point, Point)], p: Point) -> i32 {
+    let mut winding = 0;
+    for &(a, b) in edges {
+        if a.y <= p.y {
+            if b.y > p.y && (b - a).cross(p - a) > 0.0 {
+                winding += 1;
+            }
+        } else if b.y <= p.y && (b - a).cross(p - a) < 0.0 {
+            winding -= 1;
+        }
+    }
+
+    return winding;
+}
+
+// Chains the edges into closed polygons.
+fn link_edges(edges: &[(Point, Point)]) -> Vec<Vec<Point>> {
+    let mut outgoing: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+    for (i, &(a, _)) in edges.iter().enumerate() {
+        outgoing.entry(key(a)).or_insert_with(Vec::new).push(i);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut polygons = Vec::new();
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let mut polygon = vec![edges[start].0];
+        let mut current = edges[start].1;
+        while current != edges[start].0 {
+            polygon.push(current);
+            let next = outgoing.get(&key(current))
+                .and_then(|candidates| candidates.iter().cloned().find(|&e| !used[e]));
+            match next {
+                Some(e) => {
+                    used[e] = true;
+                    current = edges[e].1;
+                }
+                None => { break; }
+            }
+        }
+
+        if polygon.len() >= 3 {
+            polygons.push(polygon);
+        }
+    }
+
+    return polygons;
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[cfg(test)]
+fn square_path(min: f32, max: f32) -> Path {
+    let mut builder = Path::builder();
+    builder.move_to(point(min, min));
+    builder.line_to(point(max, min));
+    builder.line_to(point(max, max));
+    builder.line_to(point(min, max));
+    builder.close();
+    return builder.build();
+}
+
+#[cfg(test)]
+fn total_area(polygons: &[Vec<Point>]) -> f32 {
+    polygons.iter().map(|polygon| signed_area(polygon)).sum()
+}
+
+#[test]
+fn test_buffer_square() {
+    let path = square_path(0.0, 10.0);
+
+    let grown = buffer_polygons(path.path_iter().flattened(0.1), 1.0, LineJoin::Miter, LineCap::Butt, 0.1);
+    assert_eq!(grown.len(), 1);
+    assert!((total_area(&grown) - 144.0).abs() < 0.01);
+
+    let shrunk = buffer_polygons(path.path_iter().flattened(0.1), -1.0, LineJoin::Miter, LineCap::Butt, 0.1);
+    assert_eq!(shrunk.len(), 1);
+    assert!((total_area(&shrunk) - 64.0).abs() < 0.01);
+
+    let bevel = buffer_polygons(path.path_iter().flattened(0.1), 1.0, LineJoin::Bevel, LineCap::Butt, 0.1);
+    assert!((total_area(&bevel) - 142.0).abs() < 0.01);
+
+    let round = buffer_polygons(path.path_iter().flattened(0.1), 1.0, LineJoin::Round, LineCap::Butt, 0.01);
+    assert!((total_area(&round) - (140.0 + PI)).abs() < 0.1);
+
+    // Shrunk until nothing is left.
+    let empty = buffer_polygons(path.path_iter().flattened(0.1), -6.0, LineJoin::Miter, LineCap::Butt, 0.1);
+    assert!(total_area(&empty).abs() < 0.01);
+}
+
+#[test]
+fn test_buffer_merges_overlaps() {
+    // Two squares that overlap once buffered.
+    let mut builder = Path::builder();
+    for &x in &[0.0, 3.0] {
+        builder.move_to(point(x, 0.0));
+        builder.line_to(point(x + 2.0, 0.0));
+        builder.line_to(point(x + 2.0, 2.0));
+        builder.line_to(point(x, 2.0));
+        builder.close();
+    }
+    let path = builder.build();
+
+    let result = buffer_polygons(path.path_iter().flattened(0.1), 1.0, LineJoin::Miter, LineCap::Butt, 0.1);
+    assert_eq!(result.len(), 1);
+    // A 7 x 4 rectangle.
+    assert!((total_area(&result) - 28.0).abs() < 0.01);
+}
+
+#[test]
+fn test_buffer_line() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    let path = builder.build();
+
+    let butt = buffer_polygons(path.path_iter().flattened(0.1), 1.0, LineJoin::Miter, LineCap::Butt, 0.1);
+    assert!((total_area(&butt) - 20.0).abs() < 0.01);
+
+    let square = buffer_polygons(path.path_iter().flattened(0.1), 1.0, LineJoin::Miter, LineCap::Square, 0.1);
+    assert!((total_area(&square) - 24.0).abs() < 0.01);
+
+    let round = buffer_polygons(path.path_iter().flattened(0.1), 1.0, LineJoin::Miter, LineCap::Round, 0.01);
+    assert!((total_area(&round) - (20.0 + PI)).abs() < 0.1);
+
+    // Lines have no area to shrink.
+    assert!(buffer_polygons(path.path_iter().flattened(0.1), -1.0, LineJoin::Miter, LineCap::Butt, 0.1).is_empty());
+}
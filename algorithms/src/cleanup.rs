@@ -0,0 +1,304 @@
+//! Removing redundant and degenerate events from paths.
+//!
+//! Paths coming from scanned or traced images, or from geometry exported by other tools,
+//! often contain edges that are a fraction of a pixel long, long straight lines split into
+//! many collinear segments, and sub-paths that end a tiny distance away from where they
+//! started. All of these make the output of the tessellators larger, and nearly degenerate
+//! edges are the most common source of robustness problems.
+//! [cleanup_path](fn.cleanup_path.html) removes them in a single pass, before tessellating:
+//!
+//! - Edges shorter than the tolerance (including curves whose control points are all within
+//!   the tolerance of their start) are removed.
+//! - Points within the tolerance of the first point of their sub-path are fused with it.
+//! - Consecutive line segments going in the same direction are merged if the points in
+//!   between are within the tolerance of the merged segment.
+//! - A line segment going back to the start of a closed sub-path is removed since closing
+//!   the sub-path adds the same edge.
+//! - Sub-paths left without any edge are removed.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::cleanup::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(5.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 0.001));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! let mut builder = Path::builder();
+//! let stats = cleanup_path(path.iter(), 0.01, &mut builder);
+//! println!("removed {} edges", stats.removed_edges + stats.merged_edges);
+//! let path = builder.build();
+//! # let _ = path;
+//! # }
+//! ```
+
+use core::PathEvent;
+use core::math::*;
+use path_builder::PathBuilder;
+
+/// What [cleanup_path](fn.cleanup_path.html) changed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CleanupStats {
+    /// Edges removed because they were shorter than the tolerance or duplicated the edge
+    /// closing their sub-path.
+    pub removed_edges: usize,
+    /// Line segments merged into the previous one because they were collinear.
+    pub merged_edges: usize,
+    /// Points moved onto the first point of their sub-path.
+    pub fused_points: usize,
+    /// Sub-paths removed because they didn't have any edge left.
+    pub removed_sub_paths: usize,
+}
+
+/// Removes the redundant and degenerate events of a path and sends the result to a path
+/// builder. See the [module documentation](index.html).
+pub fn cleanup_path<Iter, Builder>(path: Iter, tolerance: f32, output: &mut Builder) -> CleanupStats
+where
+    Iter: Iterator<Item = PathEvent>,
+    Builder: PathBuilder,
+{
+    let mut cleanup = Cleanup {
+        output: output,
+        tolerance: tolerance,
+        stats: CleanupStats::default(),
+        first: point(0.0, 0.0),
+        current: point(0.0, 0.0),
+        line_start: point(0.0, 0.0),
+        pending: None,
+        merged_points: Vec::new(),
+        in_sub_path: false,
+        started: false,
+    };
+
+    for evt in path {
+        match evt {
+            PathEvent::MoveTo(to) => {
+                cleanup.end_sub_path(false);
+                cleanup.first = to;
+                cleanup.current = to;
+                cleanup.in_sub_path = true;
+            }
+            PathEvent::LineTo(to) => { cleanup.line_to(to); }
+            PathEvent::QuadraticTo(ctrl, to) => { cleanup.curve_to(&[ctrl], to); }
+            PathEvent::CubicTo(ctrl1, ctrl2, to) => { cleanup.curve_to(&[ctrl1, ctrl2], to); }
+            PathEvent::Close => { cleanup.end_sub_path(true); }
+        }
+    }
+    cleanup.end_sub_path(false);
+
+    return cleanup.stats;
+}
+
+struct Cleanup<'l, Builder: 'l> {
+    output: &'l mut Builder,
+    tolerance: f32,
+    stats: CleanupStats,
+    first: Point,
+    current: Point,
+    // The line segment that hasn't been sent to the output yet because the next ones may
+    // be merged into it, and the points that were removed by merging them.
+    line_start: Point,
+    pending: Option<Point>,
+    merged_points: Vec<Point>,
+    in_sub_path: bool,
+    // Whether the move_to of the current sub-path was sent to the output.
+    started: bool,
+}
+
+impl<'l, Builder: PathBuilder> Cleanup<'l, Builder> {
+    fn line_to(&mut self, to: Point) {
+        self.in_sub_path = true;
+        if self.is_degenerate(&[], to) {
+            self.stats.removed_edges += 1;
+            return;
+        }
+        let to = self.fuse(to);
+
+        if let Some(p) = self.pending {
+            if self.can_merge(p, to) {
+                self.merged_points.push(p);
+                self.pending = Some(to);
+                self.current = to;
+                self.stats.merged_edges += 1;
+                return;
+            }
+            self.flush();
+        }
+
+        self.line_start = self.current;
+        self.pending = Some(to);
+        self.current = to;
+    }
+
+    fn curve_to(&mut self, ctrl: &[Point], to: Point) {
+        self.in_sub_path = true;
+        if self.is_degenerate(ctrl, to) {
+            self.stats.removed_edges += 1;
+            return;
+        }
+        let to = self.fuse(to);
+
+        self.flush();
+        self.begin();
+        if ctrl.len() == 1 {
+            self.output.quadratic_bezier_to(ctrl[0], to);
+        } else {
+            self.output.cubic_bezier_to(ctrl[0], ctrl[1], to);
+        }
+        self.current = to;
+    }
+
+    fn end_sub_path(&mut self, close: bool) {
+        if !self.in_sub_path {
+            return;
+        }
+
+        if close && self.pending == Some(self.first) {
+            self.pending = None;
+            self.merged_points.clear();
+            self.stats.removed_edges += 1;
+        }
+        self.flush();
+
+        if !self.started {
+            self.stats.removed_sub_paths += 1;
+        } else if close {
+            self.output.close();
+        }
+
+        self.current = self.first;
+        self.in_sub_path = false;
+        self.started = false;
+    }
+
+    // An edge is degenerate if all of its points are within the tolerance of its start.
+    fn is_degenerate(&self, ctrl: &[Point], to: Point) -> bool {
+        let current = self.current;
+        let tolerance = self.tolerance;
+        return (to - current).length() <= tolerance
+            && ctrl.iter().all(|p| (*p - current).length() <= tolerance);
+    }
+
+    fn fuse(&mut self, to: Point) -> Point {
+        if to != self.first && (to - self.first).length() <= self.tolerance {
+            self.stats.fused_points += 1;
+            return self.first;
+        }
+        return to;
+    }
+
+    // Whether the pending line segment and the one from its end to `to` can be replaced
+    // with a single segment.
+    fn can_merge(&self, pending: Point, to: Point) -> bool {
+        let v = to - self.line_start;
+        let length = v.length();
+        if length == 0.0 {
+            return false;
+        }
+
+        // Don't merge segments going back and forth.
+        if (pending - self.line_start).dot(v) <= 0.0 || (to - pending).dot(v) <= 0.0 {
+            return false;
+        }
+
+        let line_start = self.line_start;
+        let tolerance = self.tolerance;
+        let close_enough = |p: &Point| (v.cross(*p - line_start) / length).abs() <= tolerance;
+        return close_enough(&pending) && self.merged_points.iter().all(|p| close_enough(p));
+    }
+
+    fn begin(&mut self) {
+        if !self.started {
+            self.output.move_to(self.first);
+            self.started = true;
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(p) = self.pending.take() {
+            self.begin();
+            self.output.line_to(p);
+            self.merged_points.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+
+#[test]
+fn test_cleanup_path() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(0.001, 0.0));
+    builder.line_to(point(5.0, 0.0));
+    builder.line_to(point(10.0, 0.001));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.line_to(point(0.0001, 0.0001));
+    builder.close();
+    // Empty sub-paths.
+    builder.move_to(point(20.0, 20.0));
+    builder.close();
+    builder.move_to(point(30.0, 30.0));
+    builder.cubic_bezier_to(point(30.0, 30.0), point(30.001, 30.0), point(30.0, 30.0));
+    // A curve with its end point close to its start isn't degenerate.
+    builder.move_to(point(40.0, 40.0));
+    builder.quadratic_bezier_to(point(50.0, 40.0), point(40.0, 40.001));
+    let path = builder.build();
+
+    let mut builder = Path::builder();
+    let stats = cleanup_path(path.iter(), 0.01, &mut builder);
+    let events: Vec<PathEvent> = builder.build().iter().collect();
+
+    assert_eq!(
+        events,
+        vec![
+            PathEvent::MoveTo(point(0.0, 0.0)),
+            PathEvent::LineTo(point(10.0, 0.001)),
+            PathEvent::LineTo(point(10.0, 10.0)),
+            PathEvent::LineTo(point(0.0, 10.0)),
+            PathEvent::Close,
+            PathEvent::MoveTo(point(40.0, 40.0)),
+            PathEvent::QuadraticTo(point(50.0, 40.0), point(40.0, 40.0)),
+        ]
+    );
+    assert_eq!(
+        stats,
+        CleanupStats {
+            removed_edges: 3,
+            merged_edges: 1,
+            fused_points: 2,
+            removed_sub_paths: 2,
+        }
+    );
+}
+
+#[test]
+fn test_cleanup_back_and_forth() {
+    // Collinear segments going back are not merged.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(5.0, 0.0));
+    let path = builder.build();
+
+    let mut builder = Path::builder();
+    let stats = cleanup_path(path.iter(), 0.01, &mut builder);
+    assert_eq!(builder.build().iter().count(), 3);
+    assert_eq!(stats, CleanupStats::default());
+}
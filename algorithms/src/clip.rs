@@ -0,0 +1,418 @@
+//! Clipping paths against an axis-aligned rectangle.
+//!
+//! This is typically used by tile-based renderers to make sure the geometry of a tile doesn't
+//! bleed into its neighbors, before tessellating it.
+//!
+//! The clipping operates on flattened paths, curves are approximated with line segments
+//! using the tolerance of the flattening iterator.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::clip::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(-10.0, -10.0));
+//! # builder.line_to(point(300.0, 20.0));
+//! # builder.line_to(point(100.0, 300.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! let tile = rect(0.0, 0.0, 256.0, 256.0);
+//! let mut builder = Path::builder();
+//! clip_path_to_rect(path.path_iter().flattened(0.1), &tile, ClipMode::Fill, &mut builder);
+//! let clipped = builder.build();
+//! # let _ = clipped;
+//! # }
+//! ```
+
+use core::FlattenedEvent;
+use core::math::*;
+use core::math_utils::clip_segment_to_rect;
+use path_builder::BaseBuilder;
+
+/// How to interpret the path that is being clipped.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClipMode {
+    /// The path describes a region to fill: each sub-path is treated as a closed polygon
+    /// and the output only contains closed sub-paths.
+    ///
+    /// Parts of the polygons outside of the rectangle are replaced with edges along the
+    /// sides of the rectangle, so the fill of the result is the intersection of the fill
+    /// of the path and the rectangle.
+    Fill,
+    /// The path describes the center line of a stroke: only the parts of the segments
+    /// within the rectangle are kept and the output is made of open sub-paths.
+    ///
+    /// To avoid cutting the stroke near the sides of the rectangle, clip against the
+    /// rectangle inflated by half of the line width.
+    Stroke,
+}
+
+/// Clips a flattened path against a rectangle and sends the result to a path builder.
+pub fn clip_path_to_rect<Iter, Builder>(
+    path: Iter,
+    rect: &Rect,
+    mode: ClipMode,
+    output: &mut Builder,
+) where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Builder: BaseBuilder,
+{
+    match mode {
+        ClipMode::Fill => clip_fill(path, rect, output),
+        ClipMode::Stroke => clip_stroke(path, rect, output),
+    }
+}
+
+fn clip_fill<Iter, Builder>(path: Iter, rect: &Rect, output: &mut Builder)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Builder: BaseBuilder,
+{
+    let mut polygon = Vec::new();
+    let mut tmp = Vec::new();
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                clip_polygon(&mut polygon, &mut tmp, rect, output);
+                polygon.push(to);
+            }
+            FlattenedEvent::LineTo(to) => {
+                polygon.push(to);
+            }
+            FlattenedEvent::Close => {
+                clip_polygon(&mut polygon, &mut tmp, rect, output);
+            }
+        }
+    }
+    clip_polygon(&mut polygon, &mut tmp, rect, output);
+}
+
+// Sutherland-Hodgman against each side of the rectangle. Clears the polygon.
+fn clip_polygon<Builder: BaseBuilder>(
+    polygon: &mut Vec<Point>,
+    tmp: &mut Vec<Point>,
+    rect: &Rect,
+    output: &mut Builder,
+) {
+    if polygon.len() < 3 {
+        polygon.clear();
+        return;
+    }
+
+    // Each side is described by an axis (0 for x, 1 for y), the coordinate of the side and
+    // whether the inside is above (true) or below that coordinate.
+    // Points exactly on a side count as inside.
+    let sides = [
+        (0, rect.min_x(), true),
+        (0, rect.max_x(), false),
+        (1, rect.min_y(), true),
+        (1, rect.max_y(), false),
+    ];
+
+    for &(axis, value, above) in &sides {
+        tmp.clear();
+        // Positive inside, negative outside.
+        let side = |p: Point| {
+            let v = if axis == 0 { p.x } else { p.y };
+            if above { v - value } else { value - v }
+        };
+
+        let mut prev = polygon[polygon.len() - 1];
+        let mut prev_side = side(prev);
+        for &p in polygon.iter() {
+            let p_side = side(p);
+            if (p_side > 0.0 && prev_side < 0.0) || (p_side < 0.0 && prev_side > 0.0) {
+                tmp.push(intersect_axis(prev, p, axis, value));
+            }
+            if p_side >= 0.0 {
+                tmp.push(p);
+            }
+            prev = p;
+            prev_side = p_side;
+        }
+
+        ::std::mem::swap(polygon, tmp);
+        if polygon.len() < 3 {
+            polygon.clear();
+            return;
+        }
+    }
+
+    output.move_to(polygon[0]);
+    for &p in &polygon[1..] {
+        output.line_to(p);
+    }
+    output.close();
+
+    polygon.clear();
+}
+
+fn intersect_axis(a: Point, b: Point, axis: u32, value: f32) -> Point {
+    if axis == 0 {
+        let t = (value - a.x) / (b.x - a.x);
+        point(value, a.y + (b.y - a.y) * t)
+    } else {
+        let t = (value - a.y) / (b.y - a.y);
+        point(a.x + (b.x - a.x) * t, value)
+    }
+}
+
+fn clip_stroke<Iter, Builder>(path: Iter, rect: &Rect, output: &mut Builder)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Builder: BaseBuilder,
+{
+    let mut first = point(0.0, 0.0);
+    let mut current = point(0.0, 0.0);
+    // Whether the output is in the middle of a sub-path that ends at the current position.
+    let mut connected = false;
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                first = to;
+                current = to;
+                connected = false;
+            }
+            FlattenedEvent::LineTo(to) => {
+                connected = clip_segment(current, to, rect, connected, output);
+                current = to;
+            }
+            FlattenedEvent::Close => {
+                clip_segment(current, first, rect, connected, output);
+                current = first;
+                connected = false;
+            }
+        }
+    }
+}
+
+// Returns whether the clipped segment reaches its end point.
+fn clip_segment<Builder: BaseBuilder>(
+    from: Point,
+    to: Point,
+    rect: &Rect,
+    connected: bool,
+    output: &mut Builder,
+) -> bool {
+    let (t0, t1) = match clip_segment_to_rect(from, to, rect) {
+        Some(range) => range,
+        None => {
+            return false;
+        }
+    };
+
+    let v = to - from;
+    if t0 > 0.0 || !connected {
+        output.move_to(from + v * t0);
+    }
+    if t1 < 1.0 {
+        output.line_to(from + v * t1);
+        return false;
+    }
+    output.line_to(to);
+
+    return true;
+}
+
+/// Clips a polygon against a convex polygon (Sutherland-Hodgman).
+///
+/// The clip polygon can be in either orientation but must be convex, the clipped polygon
+/// can be concave. The result is empty if the polygons don't overlap. As with any
+/// Sutherland-Hodgman implementation, clipping a concave polygon can produce degenerate
+/// edges along the sides of the clip polygon where the result should be split in several
+/// parts, which doesn't affect its fill.
+pub fn clip_polygon_to_convex(polygon: &[Point], clip: &[Point]) -> Vec<Point> {
+    let mut result = polygon.to_vec();
+    let mut tmp = Vec::with_capacity(polygon.len());
+    clip_to_convex(&mut result, &mut tmp, clip);
+
+    return result;
+}
+
+/// Clips each sub-path of a flattened path against a convex polygon and sends the
+/// resulting closed polygons to a path builder.
+///
+/// See [clip_polygon_to_convex](fn.clip_polygon_to_convex.html).
+pub fn clip_path_to_convex<Iter, Builder>(path: Iter, clip: &[Point], output: &mut Builder)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Builder: BaseBuilder,
+{
+    let mut polygon = Vec::new();
+    let mut tmp = Vec::new();
+    let flush = |polygon: &mut Vec<Point>, tmp: &mut Vec<Point>, output: &mut Builder| {
+        clip_to_convex(polygon, tmp, clip);
+        if polygon.len() >= 3 {
+            output.move_to(polygon[0]);
+            for &p in &polygon[1..] {
+                output.line_to(p);
+            }
+            output.close();
+        }
+        polygon.clear();
+    };
+
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                flush(&mut polygon, &mut tmp, output);
+                polygon.push(to);
+            }
+            FlattenedEvent::LineTo(to) => {
+                polygon.push(to);
+            }
+            FlattenedEvent::Close => {
+                flush(&mut polygon, &mut tmp, output);
+            }
+        }
+    }
+    flush(&mut polygon, &mut tmp, output);
+}
+
+fn clip_to_convex(polygon: &mut Vec<Point>, tmp: &mut Vec<Point>, clip: &[Point]) {
+    if polygon.len() < 3 || clip.len() < 3 {
+        polygon.clear();
+        return;
+    }
+
+    // Twice the signed area, to know which side of the clip edges is the inside.
+    let mut area = 0.0;
+    for i in 0..clip.len() {
+        let a = clip[i];
+        let b = clip[(i + 1) % clip.len()];
+        area += a.x * b.y - b.x * a.y;
+    }
+    let orientation = if area < 0.0 { -1.0 } else { 1.0 };
+
+    for i in 0..clip.len() {
+        let a = clip[i];
+        let b = clip[(i + 1) % clip.len()];
+        let edge = b - a;
+        let side = |p: Point| edge.cross(p - a) * orientation;
+
+        tmp.clear();
+        let mut prev = polygon[polygon.len() - 1];
+        let mut prev_side = side(prev);
+        for &p in polygon.iter() {
+            let p_side = side(p);
+            // Points on the clip edge count as inside, don't add them twice.
+            if (p_side > 0.0 && prev_side < 0.0) || (p_side < 0.0 && prev_side > 0.0) {
+                let t = prev_side / (prev_side - p_side);
+                tmp.push(prev + (p - prev) * t);
+            }
+            if p_side >= 0.0 {
+                tmp.push(p);
+            }
+            prev = p;
+            prev_side = p_side;
+        }
+
+        ::std::mem::swap(polygon, tmp);
+        if polygon.len() < 3 {
+            polygon.clear();
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[test]
+fn test_clip_fill() {
+    let mut builder = Path::builder();
+    builder.move_to(point(-5.0, -5.0));
+    builder.line_to(point(5.0, -5.0));
+    builder.line_to(point(5.0, 5.0));
+    builder.line_to(point(-5.0, 5.0));
+    builder.close();
+    // Entirely outside.
+    builder.move_to(point(20.0, 20.0));
+    builder.line_to(point(30.0, 20.0));
+    builder.line_to(point(30.0, 30.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut output = Path::builder();
+    clip_path_to_rect(
+        path.path_iter().flattened(0.1),
+        &rect(0.0, 0.0, 10.0, 10.0),
+        ClipMode::Fill,
+        &mut output,
+    );
+    let clipped = output.build();
+
+    assert_eq!(clipped.points().len(), 4);
+    for p in clipped.points() {
+        assert!(p.x >= 0.0 && p.x <= 5.0 && p.y >= 0.0 && p.y <= 5.0);
+    }
+    assert!(clipped.points().contains(&point(0.0, 0.0)));
+    assert!(clipped.points().contains(&point(5.0, 5.0)));
+}
+
+#[test]
+fn test_clip_stroke() {
+    let mut builder = Path::builder();
+    builder.move_to(point(-5.0, 5.0));
+    builder.line_to(point(5.0, 5.0));
+    builder.line_to(point(5.0, 15.0));
+    builder.line_to(point(8.0, 15.0));
+    builder.line_to(point(8.0, 5.0));
+    let path = builder.build();
+
+    let mut output = Path::builder();
+    clip_path_to_rect(
+        path.path_iter().flattened(0.1),
+        &rect(0.0, 0.0, 10.0, 10.0),
+        ClipMode::Stroke,
+        &mut output,
+    );
+    let clipped = output.build();
+
+    // Two open sub-paths: (0, 5) -> (5, 5) -> (5, 10) and (8, 10) -> (8, 5).
+    assert_eq!(
+        clipped.points(),
+        &[point(0.0, 5.0), point(5.0, 5.0), point(5.0, 10.0), point(8.0, 10.0), point(8.0, 5.0)]
+    );
+}
+
+#[test]
+fn test_clip_polygon_to_convex() {
+    let square = [point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)];
+    // A triangle covering the lower-left half of the square, in both orientations.
+    let triangle = [point(-10.0, 20.0), point(20.0, -10.0), point(-10.0, -10.0)];
+    let reversed = [point(-10.0, -10.0), point(20.0, -10.0), point(-10.0, 20.0)];
+
+    for clip in &[&triangle[..], &reversed[..]] {
+        let result = clip_polygon_to_convex(&square, clip);
+        assert_eq!(result.len(), 3);
+        assert!(result.contains(&point(0.0, 0.0)));
+        assert!(result.contains(&point(10.0, 0.0)));
+        assert!(result.contains(&point(0.0, 10.0)));
+    }
+
+    let far = [point(100.0, 100.0), point(110.0, 100.0), point(100.0, 110.0)];
+    assert!(clip_polygon_to_convex(&square, &far).is_empty());
+
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.close();
+    let path = builder.build();
+    let mut output = Path::builder();
+    clip_path_to_convex(path.path_iter().flattened(0.1), &square, &mut output);
+    assert_eq!(output.build().points().len(), 3);
+}
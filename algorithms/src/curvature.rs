@@ -0,0 +1,374 @@
+//! Curvature of paths as a function of the distance along them.
+//!
+//! The curvature is the inverse of the radius of the circle that best fits the path at a given
+//! point. It is signed: positive where the path turns counter-clockwise (with the y axis
+//! pointing up) and negative where it turns clockwise. Machines following a path (plotters,
+//! CNC) must slow down where the curvature is high, and labels are best placed where it is low.
+//!
+//! The curvature of each segment is computed analytically by
+//! [Segment::curvature](../../lyon_path/enum.Segment.html#method.curvature).
+//! [CurvatureProfile](struct.CurvatureProfile.html) maps the distance along the path to a
+//! segment and a parameter on that segment, using a table of arc lengths per segment, to
+//! sample the curvature along the path and find its maximum.
+//!
+//! The corners between segments are not taken into account (their curvature is infinite),
+//! only the curvature inside of the segments is.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::curvature::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.quadratic_bezier_to(point(20.0, 0.0), point(20.0, 10.0));
+//! # let path = builder.build();
+//! # let max_speed: f32 = 100.0;
+//! # let max_acceleration: f32 = 50.0;
+//! let profile = CurvatureProfile::new(path.iter(), 0.01);
+//! for sample in profile.sample(1.0) {
+//!     let speed = max_speed.min(max_acceleration / sample.curvature.abs()).sqrt();
+//!     // ...
+//! #   let _ = speed;
+//! }
+//!
+//! let sharpest = profile.max_curvature().unwrap();
+//! println!("slow down at {}", sharpest.distance);
+//! # }
+//! ```
+
+use core::PathEvent;
+use core::math::*;
+use path::{Segment, SegmentIter};
+
+// Maximum number of steps in the arc length table of a curve.
+const MAX_STEPS: usize = 1024;
+
+/// The curvature of a path at a given distance along it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CurvatureSample {
+    /// The distance along the path.
+    pub distance: f32,
+    pub position: Point,
+    /// Signed curvature, positive when the path turns counter-clockwise.
+    pub curvature: f32,
+    /// Index of the segment in the [segment iterator](../../lyon_path/struct.SegmentIter.html)
+    /// of the path.
+    pub segment: usize,
+    /// Parameter of the position on the segment.
+    pub t: f32,
+}
+
+#[derive(Clone, Debug)]
+struct MeasuredCurve {
+    segment: Segment,
+    index: usize,
+    /// Distance along the path at the start of the segment.
+    start: f32,
+    /// Parameters at regular intervals and the distance from the start of the segment.
+    table: Vec<(f32, f32)>,
+}
+
+impl MeasuredCurve {
+    fn length(&self) -> f32 { self.table[self.table.len() - 1].1 }
+
+    fn t_at(&self, distance: f32) -> f32 {
+        let mut i = 0;
+        let mut j = self.table.len() - 1;
+        while j - i > 1 {
+            let mid = (i + j) / 2;
+            if self.table[mid].1 <= distance { i = mid; } else { j = mid; }
+        }
+        let (t0, d0) = self.table[i];
+        let (t1, d1) = self.table[j];
+        if d1 <= d0 {
+            return t0;
+        }
+        let f = ((distance - d0) / (d1 - d0)).max(0.0).min(1.0);
+
+        return t0 + (t1 - t0) * f;
+    }
+
+    fn distance_at(&self, t: f32) -> f32 {
+        let steps = self.table.len() - 1;
+        let i = ((t * steps as f32) as usize).min(steps - 1);
+        let (t0, d0) = self.table[i];
+        let (t1, d1) = self.table[i + 1];
+
+        return d0 + (d1 - d0) * (t - t0) / (t1 - t0);
+    }
+
+    fn sample(&self, t: f32) -> CurvatureSample {
+        CurvatureSample {
+            distance: self.start + self.distance_at(t),
+            position: self.segment.sample(t),
+            curvature: self.segment.curvature(t),
+            segment: self.index,
+            t: t,
+        }
+    }
+
+    fn max_curvature_t(&self) -> f32 {
+        let s = match self.segment {
+            Segment::Line(_) => { return 0.0; }
+            Segment::Quadratic(ref s) => { return s.max_curvature_t(); }
+            // The curvature of arcs is constant.
+            Segment::Arc(_) => { return 0.0; }
+            Segment::Cubic(ref s) => s,
+        };
+
+        // Coarse sampling followed by a ternary search around the best sample.
+        let n = 32;
+        let mut best = 0;
+        let mut best_curvature = 0.0;
+        for i in 0..(n + 1) {
+            let k = s.curvature(i as f32 / n as f32).abs();
+            if k > best_curvature {
+                best = i;
+                best_curvature = k;
+            }
+        }
+        let mut a = (best as f32 - 1.0).max(0.0) / n as f32;
+        let mut b = (best as f32 + 1.0).min(n as f32) / n as f32;
+        for _ in 0..24 {
+            let t1 = a + (b - a) / 3.0;
+            let t2 = b - (b - a) / 3.0;
+            if s.curvature(t1).abs() < s.curvature(t2).abs() {
+                a = t1;
+            } else {
+                b = t2;
+            }
+        }
+        let t = (a + b) * 0.5;
+
+        return if s.curvature(t).abs() >= best_curvature { t } else { best as f32 / n as f32 };
+    }
+}
+
+/// Computes the curvature of a path at given distances along it.
+///
+/// Sub-paths are measured one after the other like with
+/// [PathMeasure](../measure/struct.PathMeasure.html): the gap between the end of a sub-path
+/// and the start of the next one does not count, and empty segments are ignored.
+#[derive(Clone, Debug)]
+pub struct CurvatureProfile {
+    curves: Vec<MeasuredCurve>,
+    length: f32,
+}
+
+impl CurvatureProfile {
+    /// Builds the profile from an iterator of path events.
+    ///
+    /// The curves are subdivided uniformly with a number of steps that depends on their size
+    /// and on the tolerance, which determines the precision of the arc lengths.
+    pub fn new<Iter: Iterator<Item = PathEvent>>(path: Iter, tolerance: f32) -> Self {
+        let mut profile = CurvatureProfile {
+            curves: Vec::new(),
+            length: 0.0,
+        };
+
+        for (index, segment) in SegmentIter::new(path).enumerate() {
+            let steps = match segment {
+                Segment::Line(_) => 1,
+                Segment::Quadratic(ref s) => {
+                    steps_for((s.ctrl - s.from).length() + (s.to - s.ctrl).length(), tolerance)
+                }
+                Segment::Cubic(ref s) => {
+                    steps_for(
+                        (s.ctrl1 - s.from).length() + (s.ctrl2 - s.ctrl1).length() + (s.to - s.ctrl2).length(),
+                        tolerance,
+                    )
+                }
+                Segment::Arc(ref s) => steps_for(s.length(), tolerance),
+            };
+
+            let mut table = Vec::with_capacity(steps + 1);
+            table.push((0.0, 0.0));
+            let mut previous = segment.from();
+            let mut length = 0.0;
+            for i in 1..(steps + 1) {
+                let t = i as f32 / steps as f32;
+                let p = segment.sample(t);
+                length += (p - previous).length();
+                table.push((t, length));
+                previous = p;
+            }
+            if length == 0.0 {
+                continue;
+            }
+
+            let curve = MeasuredCurve {
+                segment: segment,
+                index: index,
+                start: profile.length,
+                table: table,
+            };
+            profile.length += curve.length();
+            profile.curves.push(curve);
+        }
+
+        return profile;
+    }
+
+    /// The total length of the path.
+    pub fn length(&self) -> f32 { self.length }
+
+    pub fn is_empty(&self) -> bool { self.curves.is_empty() }
+
+    /// Returns the curvature at a distance along the path (clamped to the length of the path).
+    ///
+    /// Returns None if the path is empty.
+    pub fn sample_at(&self, distance: f32) -> Option<CurvatureSample> {
+        if self.curves.is_empty() {
+            return None;
+        }
+
+        let distance = distance.max(0.0).min(self.length);
+        let mut i = 0;
+        let mut j = self.curves.len();
+        while j - i > 1 {
+            let mid = (i + j) / 2;
+            if self.curves[mid].start <= distance { i = mid; } else { j = mid; }
+        }
+        let curve = &self.curves[i];
+        let t = curve.t_at(distance - curve.start);
+        let mut sample = curve.sample(t);
+        sample.distance = distance;
+
+        return Some(sample);
+    }
+
+    /// Returns the curvature at a distance along the path.
+    pub fn curvature_at(&self, distance: f32) -> Option<f32> {
+        self.sample_at(distance).map(|sample| sample.curvature)
+    }
+
+    /// Samples the curvature at regular intervals along the path, starting at its beginning.
+    pub fn sample(&self, spacing: f32) -> Vec<CurvatureSample> {
+        let mut samples = Vec::new();
+        if self.curves.is_empty() || spacing <= 0.0 {
+            return samples;
+        }
+
+        let n = (self.length / spacing) as usize;
+        for i in 0..(n + 1) {
+            samples.extend(self.sample_at(i as f32 * spacing));
+        }
+
+        return samples;
+    }
+
+    /// Returns the sample where the absolute value of the curvature is the highest.
+    ///
+    /// The maximum is found analytically for quadratic curves and numerically for cubic ones.
+    /// Returns None if the path is empty.
+    pub fn max_curvature(&self) -> Option<CurvatureSample> {
+        let mut result: Option<CurvatureSample> = None;
+        for curve in &self.curves {
+            let sample = curve.sample(curve.max_curvature_t());
+            let higher = match result {
+                Some(ref best) => sample.curvature.abs() > best.curvature.abs(),
+                None => true,
+            };
+            if higher {
+                result = Some(sample);
+            }
+        }
+
+        return result;
+    }
+}
+
+fn steps_for(length_bound: f32, tolerance: f32) -> usize {
+    let steps = (length_bound / tolerance).sqrt().ceil();
+    if !(steps >= 1.0) {
+        return 1;
+    }
+
+    return (steps as usize).min(MAX_STEPS);
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::{BaseBuilder, PathBuilder};
+
+#[cfg(test)]
+fn line_arc_line() -> Path {
+    // A quarter of a circle of radius 10 between two lines.
+    let k = 10.0 * 0.5522847;
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.cubic_bezier_to(point(10.0 + k, 0.0), point(20.0, 10.0 - k), point(20.0, 10.0));
+    builder.line_to(point(20.0, 20.0));
+
+    return builder.build();
+}
+
+#[test]
+fn test_curvature_profile() {
+    use std::f32::consts::PI;
+
+    let path = line_arc_line();
+    let profile = CurvatureProfile::new(path.iter(), 0.01);
+    let arc = 5.0 * PI;
+    assert!((profile.length() - (20.0 + arc)).abs() < 0.01);
+
+    assert_eq!(profile.curvature_at(5.0), Some(0.0));
+    assert_eq!(profile.curvature_at(25.0 + arc), Some(0.0));
+    assert!((profile.curvature_at(10.0 + arc * 0.5).unwrap() - 0.1).abs() < 0.003);
+
+    let middle = profile.sample_at(10.0 + arc * 0.5).unwrap();
+    assert_eq!(middle.segment, 1);
+    assert!((middle.t - 0.5).abs() < 0.01);
+
+    let samples = profile.sample(1.0);
+    assert_eq!(samples.len(), 36);
+    for sample in &samples {
+        if sample.distance > 10.5 && sample.distance < 10.0 + arc - 0.5 {
+            assert!((sample.curvature - 0.1).abs() < 0.003);
+        } else if sample.distance < 9.5 || sample.distance > 10.0 + arc + 0.5 {
+            assert_eq!(sample.curvature, 0.0);
+        }
+    }
+
+    let max = profile.max_curvature().unwrap();
+    assert_eq!(max.segment, 1);
+    assert!(max.curvature >= samples.iter().map(|s| s.curvature).fold(0.0, f32::max));
+    assert!((max.curvature - 0.1).abs() < 0.003);
+    assert!(max.distance > 10.0 && max.distance < 10.0 + arc);
+}
+
+#[test]
+fn test_curvature_quadratic() {
+    // A parabola turning clockwise, sharpest at its apex.
+    let mut builder = Path::builder();
+    builder.move_to(point(-1.0, -1.0));
+    builder.quadratic_bezier_to(point(0.0, 1.0), point(1.0, -1.0));
+    let path = builder.build();
+
+    let profile = CurvatureProfile::new(path.iter(), 0.001);
+    let max = profile.max_curvature().unwrap();
+    assert_eq!(max.t, 0.5);
+    assert_eq!(max.curvature, -2.0);
+    assert_eq!(max.position, point(0.0, 0.0));
+    assert!((max.distance - profile.length() * 0.5).abs() < 0.001);
+}
+
+#[test]
+fn test_curvature_empty() {
+    let profile = CurvatureProfile::new(Path::new().iter(), 0.01);
+    assert!(profile.is_empty());
+    assert!(profile.max_curvature().is_none());
+    assert!(profile.curvature_at(0.0).is_none());
+    assert!(profile.sample(1.0).is_empty());
+}
@@ -0,0 +1,333 @@
+//! Merging adjacent polygons into larger ones.
+//!
+//! Map data often describes a region as many polygons sharing their borders, for example
+//! the municipalities of a country. Filling all of them produces the interior edges between
+//! the polygons, and [boolean operations](../boolean_ops/index.html) are unnecessarily slow
+//! to merge them since the polygons only touch along their borders.
+//! [dissolve](fn.dissolve.html) takes advantage of this: a border shared by two polygons is
+//! made of the same edges in opposite directions (the polygons being consistently oriented),
+//! which cancel out. The remaining edges are chained into the rings of the result.
+//!
+//! Borders don't always match exactly, so the vertices closer than a tolerance are merged
+//! first. If the remaining edges still cross or overlap each other, the polygons overlap
+//! and the general union of the [boolean_ops](../boolean_ops/index.html) module is used
+//! instead.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # use lyon_path::Polygon;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::dissolve::*;
+//! # fn main() {
+//! # let square = |x: f32| Polygon {
+//! #     exterior: vec![point(x, 0.0), point(x + 1.0, 0.0), point(x + 1.0, 1.0), point(x, 1.0)],
+//! #     interiors: Vec::new(),
+//! # };
+//! # let municipalities = vec![square(0.0), square(1.0), square(2.0)];
+//! let country = dissolve(&municipalities, 0.001);
+//! # assert_eq!(country.len(), 1);
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use core::{FlattenedEvent, FillRule};
+use core::math::*;
+use path::{Polygon, polygons_from_path};
+use boolean_ops::{boolean_op_polygons, BooleanOp};
+
+/// Merges polygons into the polygons covering their union.
+///
+/// The polygons are expected not to overlap much (they can share borders), and their
+/// vertices closer than `snap_tolerance` are merged. The rings of the result are closed and
+/// oriented as described in the [polygon](../../lyon_path/polygon/index.html) module.
+pub fn dissolve(polygons: &[Polygon], snap_tolerance: f32) -> Vec<Polygon> {
+    let mut snapper = Snapper {
+        tolerance: snap_tolerance,
+        cells: HashMap::new(),
+    };
+
+    // Count the directed edges, an edge cancels out with the same edge in the other direction.
+    let mut counts: HashMap<(Key, Key), i32> = HashMap::new();
+    let mut order = Vec::new();
+    for polygon in polygons {
+        let mut polygon = polygon.clone();
+        polygon.close_rings();
+        polygon.orient();
+        for idx in 0..polygon.num_rings() {
+            let ring = polygon.ring(idx);
+            for i in 1..ring.len() {
+                let a = snapper.snap(ring[i - 1]);
+                let b = snapper.snap(ring[i]);
+                if a == b {
+                    continue;
+                }
+                let (ka, kb) = (key(a), key(b));
+                if let Some(count) = counts.get_mut(&(kb, ka)) {
+                    if *count > 0 {
+                        *count -= 1;
+                        continue;
+                    }
+                }
+                // The same edge twice in the same direction is a duplicated polygon.
+                match counts.entry((ka, kb)) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(1);
+                        order.push((a, b));
+                    }
+                    Entry::Occupied(mut entry) => {
+                        *entry.get_mut() = 1;
+                    }
+                }
+            }
+        }
+    }
+    let edges: Vec<(Point, Point)> = order.into_iter()
+        .filter(|&(a, b)| counts[&(key(a), key(b))] > 0)
+        .collect();
+
+    let rings = if has_crossings(&edges) {
+        let events = polygons.iter().flat_map(|polygon| polygon.iter()).collect::<Vec<_>>();
+        boolean_op_polygons(
+            events.into_iter(),
+            None::<FlattenedEvent>.into_iter(),
+            BooleanOp::Union,
+            FillRule::NonZero,
+        )
+    } else {
+        chain_edges(&edges)
+    };
+
+    let mut events = Vec::new();
+    for ring in &rings {
+        events.push(FlattenedEvent::MoveTo(ring[0]));
+        for &p in &ring[1..] {
+            events.push(FlattenedEvent::LineTo(p));
+        }
+        events.push(FlattenedEvent::Close);
+    }
+
+    return polygons_from_path(events.into_iter());
+}
+
+type Key = (u32, u32);
+
+fn key(p: Point) -> Key { (p.x.to_bits(), p.y.to_bits()) }
+
+// Merges the points closer than the tolerance with the first such point it was given.
+struct Snapper {
+    tolerance: f32,
+    cells: HashMap<(i64, i64), Vec<Point>>,
+}
+
+impl Snapper {
+    fn snap(&mut self, p: Point) -> Point {
+        if !(self.tolerance > 0.0) {
+            return p;
+        }
+
+        let cell = ((p.x / self.tolerance).floor() as i64, (p.y / self.tolerance).floor() as i64);
+        for dx in -1..2 {
+            for dy in -1..2 {
+                if let Some(points) = self.cells.get(&(cell.0 + dx, cell.1 + dy)) {
+                    for &q in points {
+                        if (q - p).length() <= self.tolerance {
+                            return q;
+                        }
+                    }
+                }
+            }
+        }
+        self.cells.entry(cell).or_insert(Vec::new()).push(p);
+
+        return p;
+    }
+}
+
+// Whether some edges cross, overlap, or touch another edge elsewhere than at its ends.
+// The edges are sorted along the x axis so that only the ones with overlapping ranges
+// are compared.
+fn has_crossings(edges: &[(Point, Point)]) -> bool {
+    let mut sorted: Vec<(f32, f32, usize)> = edges.iter().enumerate().map(|(i, &(a, b))| {
+        (a.x.min(b.x), a.x.max(b.x), i)
+    }).collect();
+    sorted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    for i in 0..sorted.len() {
+        let (_, max_x, e1) = sorted[i];
+        for j in i + 1..sorted.len() {
+            let (min_x, _, e2) = sorted[j];
+            if min_x > max_x {
+                break;
+            }
+            if segments_interfere(edges[e1], edges[e2]) {
+                return true;
+            }
+        }
+    }
+
+    return false;
+}
+
+fn segments_interfere((a, b): (Point, Point), (c, d): (Point, Point)) -> bool {
+    let ab = b - a;
+    let cd = d - c;
+    let shared = a == c || a == d || b == c || b == d;
+    if shared {
+        // Edges sharing an end can only overlap if they are collinear.
+        if ab.cross(cd) != 0.0 {
+            return false;
+        }
+        // They overlap if they leave the shared end in the same direction.
+        let (v1, v2) = if a == c {
+            (ab, cd)
+        } else if b == d {
+            (-ab, -cd)
+        } else if a == d {
+            (ab, -cd)
+        } else {
+            (-ab, cd)
+        };
+        return v1.dot(v2) > 0.0;
+    }
+
+    let o1 = ab.cross(c - a);
+    let o2 = ab.cross(d - a);
+    let o3 = cd.cross(a - c);
+    let o4 = cd.cross(b - c);
+    if o1 == 0.0 && o2 == 0.0 {
+        // Collinear, check whether the projections overlap.
+        let length = ab.square_length();
+        let t1 = (c - a).dot(ab) / length;
+        let t2 = (d - a).dot(ab) / length;
+        return t1.max(t2) >= 0.0 && t1.min(t2) <= 1.0;
+    }
+
+    return o1 * o2 <= 0.0 && o3 * o4 <= 0.0;
+}
+
+// Chains the edges into rings. Where several rings touch at a vertex, the edge turning the
+// most to the right is followed so that the rings don't cross themselves.
+fn chain_edges(edges: &[(Point, Point)]) -> Vec<Vec<Point>> {
+    let mut outgoing: HashMap<Key, Vec<usize>> = HashMap::new();
+    for (i, &(a, _)) in edges.iter().enumerate() {
+        outgoing.entry(key(a)).or_insert(Vec::new()).push(i);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+
+        let first = key(edges[start].0);
+        let mut ring = Vec::new();
+        let mut current = start;
+        loop {
+            used[current] = true;
+            let (a, b) = edges[current];
+            ring.push(a);
+            if key(b) == first {
+                break;
+            }
+
+            let incoming = b - a;
+            let mut next = None;
+            let mut best_angle = 0.0;
+            for &candidate in &outgoing[&key(b)] {
+                if used[candidate] {
+                    continue;
+                }
+                let d = edges[candidate].1 - edges[candidate].0;
+                let angle = incoming.cross(d).atan2(incoming.dot(d));
+                if next.is_none() || angle < best_angle {
+                    next = Some(candidate);
+                    best_angle = angle;
+                }
+            }
+
+            match next {
+                Some(edge) => { current = edge; }
+                None => { break; }
+            }
+        }
+
+        if ring.len() >= 3 {
+            rings.push(ring);
+        }
+    }
+
+    return rings;
+}
+
+#[cfg(test)]
+fn square(x: f32, y: f32, size: f32) -> Polygon {
+    Polygon::new(
+        vec![point(x, y), point(x + size, y), point(x + size, y + size), point(x, y + size)],
+        Vec::new(),
+    )
+}
+
+#[cfg(test)]
+fn area(polygon: &Polygon) -> f32 {
+    let mut area = 0.0;
+    for idx in 0..polygon.num_rings() {
+        let ring = polygon.ring(idx);
+        for i in 1..ring.len() {
+            area += ring[i - 1].to_vector().cross(ring[i].to_vector()) * 0.5;
+        }
+    }
+
+    return area;
+}
+
+#[test]
+fn test_dissolve_shared_border() {
+    let result = dissolve(&[square(0.0, 0.0, 10.0), square(10.0, 0.0, 10.0)], 0.0);
+    assert_eq!(result.len(), 1);
+    assert!(result[0].interiors.is_empty());
+    // The vertices of the dissolved border are kept.
+    assert_eq!(result[0].exterior.len(), 7);
+    assert_eq!(area(&result[0]), 200.0);
+
+    // Borders that don't exactly match.
+    let result = dissolve(&[square(0.0, 0.0, 10.0), square(10.001, 0.0, 10.0)], 0.01);
+    assert_eq!(result.len(), 1);
+    assert!((area(&result[0]) - 200.0).abs() < 0.1);
+
+    // Disjoint polygons stay separate.
+    let result = dissolve(&[square(0.0, 0.0, 10.0), square(20.0, 0.0, 10.0)], 0.0);
+    assert_eq!(result.len(), 2);
+}
+
+#[test]
+fn test_dissolve_hole() {
+    // A 3x3 grid of squares without the center one.
+    let mut squares = Vec::new();
+    for i in 0..3 {
+        for j in 0..3 {
+            if i != 1 || j != 1 {
+                squares.push(square(i as f32 * 10.0, j as f32 * 10.0, 10.0));
+            }
+        }
+    }
+
+    let result = dissolve(&squares, 0.0);
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].interiors.len(), 1);
+    assert_eq!(area(&result[0]), 800.0);
+}
+
+#[test]
+fn test_dissolve_overlap() {
+    // Overlapping polygons fall back to the general union.
+    let result = dissolve(&[square(0.0, 0.0, 10.0), square(5.0, 5.0, 10.0)], 0.0);
+    assert_eq!(result.len(), 1);
+    assert!((area(&result[0]) - 175.0).abs() < 0.01);
+}
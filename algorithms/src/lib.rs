@@ -0,0 +1,51 @@
+#![doc(html_logo_url = "https://nical.github.io/lyon-doc/lyon-logo.svg")]
+
+//! # Lyon algorithms
+//!
+//! A collection of algorithms operating on paths. The fill and stroke tessellators don't
+//! need them, but some of the higher level tessellation helpers of `lyon_tessellation`
+//! build on them (for example tiling and clipped fills use [clip](clip/index.html), and
+//! glows use [buffer](buffer/index.html)).
+//!
+//! - [measure](measure/index.html): arc length parametrization and resampling of paths.
+//! - [text_on_path](text_on_path/index.html): placing glyphs along a path.
+//! - [clip](clip/index.html): clipping paths against a rectangle or a convex polygon.
+//! - [simplify](simplify/index.html): area-based simplification of polylines and rings.
+//! - [minkowski](minkowski/index.html): Minkowski sums with convex polygons and discs.
+//! - [buffer](buffer/index.html): growing or shrinking the area of a path by a distance.
+//! - [snap](snap/index.html): snapping paths to a grid without creating self-intersections.
+//! - [sdf](sdf/index.html): rasterizing paths into signed distance fields.
+//! - [curvature](curvature/index.html): curvature as a function of the distance along a path.
+//! - [bounds](bounds/index.html): convex hulls, oriented bounding rectangles and k-DOPs.
+//! - [stroke_outline](stroke_outline/index.html): converting strokes into paths.
+//! - [boolean_ops](boolean_ops/index.html): union, intersection, difference and xor of fills.
+//! - [arc_fit](arc_fit/index.html): replacing runs of polyline points with circular arcs.
+//! - [cleanup](cleanup/index.html): removing tiny edges and merging collinear segments.
+//! - [dissolve](dissolve/index.html): merging polygons that share borders.
+
+extern crate lyon_core as core;
+extern crate lyon_bezier as bezier;
+extern crate lyon_path_builder as path_builder;
+extern crate lyon_path_iterator as path_iterator;
+extern crate lyon_path as path;
+
+pub mod measure;
+pub mod text_on_path;
+pub mod clip;
+pub mod simplify;
+pub mod minkowski;
+pub mod buffer;
+pub mod snap;
+pub mod sdf;
+pub mod curvature;
+pub mod bounds;
+pub mod stroke_outline;
+pub mod boolean_ops;
+pub mod arc_fit;
+pub mod cleanup;
+pub mod dissolve;
+
+pub use core::math;
+
+#[doc(inline)]
+pub use measure::{PathMeasure, MeasureError};
@@ -0,0 +1,572 @@
+//! Arc length parametrization of paths.
+//!
+//! [PathMeasure](struct.PathMeasure.html) approximates the path with line segments and
+//! stores the distance along the path at the start of each segment, which makes it
+//! possible to find the position and the direction of the path at a given distance
+//! from its start.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::measure::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.quadratic_bezier_to(point(20.0, 0.0), point(20.0, 10.0));
+//! # let path = builder.build();
+//! let measure = PathMeasure::new(path.path_iter().flattened(0.01));
+//! let middle = measure.sample(measure.length() * 0.5).unwrap();
+//! println!("position: {:?}, direction: {:?}", middle.position, middle.tangent);
+//!
+//! // A point every 5 units along the path.
+//! let points = resample(path.path_iter().flattened(0.01), 5.0);
+//! # let _ = points;
+//! # }
+//! ```
+//!
+//! Building the table requires flattening the path. When many paths are known in advance,
+//! the tables can be built once, for example in an asset pipeline, stored with
+//! [to_bytes](struct.PathMeasure.html#method.to_bytes) and loaded at runtime with
+//! [from_bytes](struct.PathMeasure.html#method.from_bytes):
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::measure::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # let path = builder.build();
+//! // Offline.
+//! let bytes = PathMeasure::new(path.path_iter().flattened(0.01)).to_bytes();
+//! // ... store the bytes with the other assets.
+//!
+//! // At runtime.
+//! let measure = PathMeasure::from_bytes(&bytes).unwrap();
+//! # assert_eq!(measure.length(), 10.0);
+//! # }
+//! ```
+
+use core::FlattenedEvent;
+use core::math::*;
+use path_builder::BaseBuilder;
+
+/// A line segment of the flattened path along with its position along the path.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MeasuredSegment {
+    pub from: Point,
+    pub to: Point,
+    /// Distance along the path at the start of the segment.
+    pub start: f32,
+    /// Length of the segment.
+    pub length: f32,
+}
+
+/// The result of sampling a `PathMeasure` at a given distance.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PathSample {
+    pub position: Point,
+    /// Normalized direction of the path.
+    pub tangent: Vec2,
+    /// The distance along the path (clamped to the length of the path).
+    pub distance: f32,
+}
+
+/// A problem found when loading a [PathMeasure](struct.PathMeasure.html) from segments or
+/// from bytes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MeasureError {
+    /// The bytes don't start with the expected tag.
+    InvalidHeader,
+    /// The bytes were written by an incompatible version of the format.
+    UnsupportedVersion(u32),
+    /// There are less bytes than the header announces.
+    UnexpectedEnd,
+    /// The segment at this index has a non-finite coordinate, isn't longer than zero, or
+    /// doesn't start where the previous one ends along the path.
+    InvalidSegment(usize),
+}
+
+// The first bytes of a serialized measure, followed by the version and the number of
+// segments as little-endian u32, and then each segment as six little-endian f32
+// (from, to, start and length).
+const MEASURE_TAG: &'static [u8; 4] = b"LYPM";
+const MEASURE_VERSION: u32 = 1;
+const MEASURE_HEADER_SIZE: usize = 12;
+const MEASURE_SEGMENT_SIZE: usize = 24;
+
+/// Measures distances along a flattened path.
+///
+/// Sub-paths are measured one after the other, the gap between the end of a sub-path
+/// and the start of the next one does not count. Empty segments are ignored.
+#[derive(Clone, Debug)]
+pub struct PathMeasure {
+    segments: Vec<MeasuredSegment>,
+    length: f32,
+}
+
+impl PathMeasure {
+    /// Builds the measure from an iterator of flattened events.
+    ///
+    /// The tolerance of the flattening determines the precision of the measure.
+    pub fn new<Iter: Iterator<Item = FlattenedEvent>>(it: Iter) -> Self {
+        let mut measure = PathMeasure {
+            segments: Vec::new(),
+            length: 0.0,
+        };
+
+        let mut first = point(0.0, 0.0);
+        let mut current = point(0.0, 0.0);
+        for evt in it {
+            match evt {
+                FlattenedEvent::MoveTo(to) => {
+                    first = to;
+                    current = to;
+                }
+                FlattenedEvent::LineTo(to) => {
+                    measure.add_segment(current, to);
+                    current = to;
+                }
+                FlattenedEvent::Close => {
+                    measure.add_segment(current, first);
+                    current = first;
+                }
+            }
+        }
+
+        return measure;
+    }
+
+    /// Builds the measure from segments previously obtained with
+    /// [segments](#method.segments).
+    ///
+    /// The segments must be sorted along the path, each of them starting at the distance where
+    /// the previous one ends.
+    pub fn from_segments(segments: Vec<MeasuredSegment>) -> Result<Self, MeasureError> {
+        let mut length = 0.0;
+        for (i, segment) in segments.iter().enumerate() {
+            let finite = segment.from.x.is_finite() && segment.from.y.is_finite()
+                && segment.to.x.is_finite() && segment.to.y.is_finite()
+                && segment.length.is_finite();
+            if !finite || !(segment.length > 0.0) || segment.start != length {
+                return Err(MeasureError::InvalidSegment(i));
+            }
+            length += segment.length;
+        }
+
+        return Ok(PathMeasure {
+            segments: segments,
+            length: length,
+        });
+    }
+
+    /// Serializes the measure into a compact binary format that can be loaded with
+    /// [from_bytes](#method.from_bytes), independently of the endianness of the platform.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let size = MEASURE_HEADER_SIZE + self.segments.len() * MEASURE_SEGMENT_SIZE;
+        let mut bytes = Vec::with_capacity(size);
+        bytes.extend_from_slice(MEASURE_TAG);
+        write_u32(&mut bytes, MEASURE_VERSION);
+        write_u32(&mut bytes, self.segments.len() as u32);
+        for segment in &self.segments {
+            for &value in &[
+                segment.from.x, segment.from.y,
+                segment.to.x, segment.to.y,
+                segment.start, segment.length,
+            ] {
+                write_u32(&mut bytes, value.to_bits());
+            }
+        }
+
+        return bytes;
+    }
+
+    /// Loads a measure serialized with [to_bytes](#method.to_bytes).
+    ///
+    /// This doesn't flatten anything and is much faster than building the measure from the
+    /// path. The bytes after the last segment are ignored.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MeasureError> {
+        if bytes.len() < MEASURE_HEADER_SIZE || &bytes[0..4] != &MEASURE_TAG[..] {
+            return Err(MeasureError::InvalidHeader);
+        }
+        let version = read_u32(bytes, 4);
+        if version != MEASURE_VERSION {
+            return Err(MeasureError::UnsupportedVersion(version));
+        }
+        let count = read_u32(bytes, 8) as usize;
+        if (bytes.len() - MEASURE_HEADER_SIZE) / MEASURE_SEGMENT_SIZE < count {
+            return Err(MeasureError::UnexpectedEnd);
+        }
+
+        let mut segments = Vec::with_capacity(count);
+        for i in 0..count {
+            let offset = MEASURE_HEADER_SIZE + i * MEASURE_SEGMENT_SIZE;
+            let value = |idx: usize| f32::from_bits(read_u32(bytes, offset + idx * 4));
+            segments.push(MeasuredSegment {
+                from: point(value(0), value(1)),
+                to: point(value(2), value(3)),
+                start: value(4),
+                length: value(5),
+            });
+        }
+
+        return PathMeasure::from_segments(segments);
+    }
+
+    fn add_segment(&mut self, from: Point, to: Point) {
+        let length = (to - from).length();
+        if length == 0.0 {
+            return;
+        }
+
+        self.segments.push(MeasuredSegment {
+            from: from,
+            to: to,
+            start: self.length,
+            length: length,
+        });
+        self.length += length;
+    }
+
+    /// The total length of the path.
+    pub fn length(&self) -> f32 { self.length }
+
+    pub fn segments(&self) -> &[MeasuredSegment] { &self.segments[..] }
+
+    pub fn is_empty(&self) -> bool { self.segments.is_empty() }
+
+    /// Returns the index of the segment containing the provided distance.
+    ///
+    /// The distance is expected to be between zero and the length of the path.
+    pub fn segment_index(&self, distance: f32) -> usize {
+        let mut low = 0;
+        let mut high = self.segments.len();
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            if self.segments[mid].start <= distance {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        return low;
+    }
+
+    /// Returns the position and direction of the path at a given distance from its start.
+    ///
+    /// The distance is clamped between zero and the length of the path.
+    /// Returns None if the path is empty.
+    pub fn sample(&self, distance: f32) -> Option<PathSample> {
+        if self.segments.is_empty() {
+            return None;
+        }
+
+        let distance = distance.max(0.0).min(self.length);
+        let segment = &self.segments[self.segment_index(distance)];
+        let t = ((distance - segment.start) / segment.length).min(1.0);
+        let v = segment.to - segment.from;
+
+        return Some(PathSample {
+            position: segment.from + v * t,
+            tangent: v / segment.length,
+            distance: distance,
+        });
+    }
+
+    /// Returns the position at a given distance from the start of the path.
+    pub fn position(&self, distance: f32) -> Option<Point> {
+        self.sample(distance).map(|sample| sample.position)
+    }
+
+    /// Returns the normalized direction of the path at a given distance from its start.
+    pub fn tangent(&self, distance: f32) -> Option<Vec2> {
+        self.sample(distance).map(|sample| sample.tangent)
+    }
+
+    /// Returns the positions at regular distances along the path, starting at its start.
+    ///
+    /// The last point is at the last multiple of `spacing` that is not beyond the end of the
+    /// path. Returns an empty vector if the path is empty or the spacing isn't positive.
+    pub fn resample(&self, spacing: f32) -> Vec<Point> {
+        let mut points = Vec::new();
+        if self.segments.is_empty() || spacing <= 0.0 {
+            return points;
+        }
+
+        let n = (self.length / spacing).floor() as usize;
+        points.reserve(n + 1);
+        // Walk the segments instead of searching for each distance.
+        let mut segment_idx = 0;
+        for i in 0..(n + 1) {
+            let distance = (i as f32 * spacing).min(self.length);
+            while segment_idx + 1 < self.segments.len() &&
+                self.segments[segment_idx + 1].start <= distance {
+                segment_idx += 1;
+            }
+            let segment = &self.segments[segment_idx];
+            let t = ((distance - segment.start) / segment.length).min(1.0);
+            points.push(segment.from.lerp(segment.to, t));
+        }
+
+        return points;
+    }
+}
+
+fn write_u32(bytes: &mut Vec<u8>, value: u32) {
+    for i in 0..4 {
+        bytes.push((value >> (i * 8)) as u8);
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut value = 0;
+    for i in 0..4 {
+        value |= (bytes[offset + i] as u32) << (i * 8);
+    }
+
+    return value;
+}
+
+/// Returns points at uniform arc length intervals along a flattened path.
+///
+/// The sub-paths are measured one after the other like in
+/// [PathMeasure](struct.PathMeasure.html). The precision depends on the tolerance of the
+/// flattening.
+pub fn resample<Iter>(path: Iter, spacing: f32) -> Vec<Point>
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    PathMeasure::new(path).resample(spacing)
+}
+
+/// Resamples each sub-path of a flattened path at uniform arc length intervals, and sends
+/// the result to a path builder.
+///
+/// Each sub-path starts at its original start. The end of the open sub-paths is kept so that
+/// they keep their length, the last interval being shorter. Closed sub-paths are closed
+/// after the last point.
+pub fn resample_path<Iter, Builder>(path: Iter, spacing: f32, output: &mut Builder)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Builder: BaseBuilder,
+{
+    let mut sub_path: Vec<FlattenedEvent> = Vec::new();
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(_) => {
+                resample_sub_path(&sub_path, spacing, output);
+                sub_path.clear();
+                sub_path.push(evt);
+            }
+            FlattenedEvent::LineTo(_) => {
+                sub_path.push(evt);
+            }
+            FlattenedEvent::Close => {
+                sub_path.push(evt);
+                resample_sub_path(&sub_path, spacing, output);
+                sub_path.clear();
+            }
+        }
+    }
+    resample_sub_path(&sub_path, spacing, output);
+}
+
+fn resample_sub_path<Builder: BaseBuilder>(sub_path: &[FlattenedEvent], spacing: f32, output: &mut Builder) {
+    let measure = PathMeasure::new(sub_path.iter().cloned());
+    let points = measure.resample(spacing);
+    if points.is_empty() {
+        return;
+    }
+
+    let closed = sub_path.last() == Some(&FlattenedEvent::Close);
+    let end = measure.segments()[measure.segments().len() - 1].to;
+
+    output.move_to(points[0]);
+    for &p in &points[1..] {
+        if closed && p == points[0] {
+            // Back at the start.
+            continue;
+        }
+        output.line_to(p);
+    }
+
+    if closed {
+        output.close();
+    } else if points[points.len() - 1] != end {
+        output.line_to(end);
+    }
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::PathBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[test]
+fn test_measure_polyline() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.move_to(point(100.0, 100.0));
+    builder.line_to(point(100.0, 105.0));
+    let path = builder.build();
+
+    let measure = PathMeasure::new(path.path_iter().flattened(0.1));
+    assert_eq!(measure.length(), 25.0);
+    assert_eq!(measure.segments().len(), 3);
+
+    let s = measure.sample(5.0).unwrap();
+    assert_eq!(s.position, point(5.0, 0.0));
+    assert_eq!(s.tangent, vec2(1.0, 0.0));
+
+    let s = measure.sample(15.0).unwrap();
+    assert_eq!(s.position, point(10.0, 5.0));
+    assert_eq!(s.tangent, vec2(0.0, 1.0));
+
+    // The gap between the sub-paths is not measured.
+    assert_eq!(measure.position(22.0), Some(point(100.0, 102.0)));
+
+    // Out of range distances are clamped.
+    assert_eq!(measure.position(-1.0), Some(point(0.0, 0.0)));
+    assert_eq!(measure.position(1000.0), Some(point(100.0, 105.0)));
+}
+
+#[test]
+fn test_measure_close() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(4.0, 0.0));
+    builder.line_to(point(4.0, 3.0));
+    builder.close();
+    let path = builder.build();
+
+    let measure = PathMeasure::new(path.path_iter().flattened(0.1));
+    assert_eq!(measure.length(), 12.0);
+    assert_eq!(measure.tangent(10.0), Some(vec2(-0.8, -0.6)));
+}
+
+#[test]
+fn test_measure_empty() {
+    let measure = PathMeasure::new(Path::new().path_iter().flattened(0.1));
+    assert!(measure.is_empty());
+    assert_eq!(measure.sample(0.0), None);
+}
+
+#[test]
+fn test_measure_bytes() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.quadratic_bezier_to(point(10.0, 20.0), point(20.0, 0.0));
+    builder.line_to(point(20.0, -5.0));
+    builder.close();
+    let path = builder.build();
+
+    let measure = PathMeasure::new(path.path_iter().flattened(0.01));
+    let bytes = measure.to_bytes();
+    assert_eq!(bytes.len(), MEASURE_HEADER_SIZE + measure.segments().len() * MEASURE_SEGMENT_SIZE);
+
+    let loaded = PathMeasure::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded.segments(), measure.segments());
+    assert_eq!(loaded.length(), measure.length());
+    assert_eq!(loaded.sample(12.5), measure.sample(12.5));
+
+    let empty = PathMeasure::from_bytes(&PathMeasure::new(None::<FlattenedEvent>.into_iter()).to_bytes()).unwrap();
+    assert!(empty.is_empty());
+
+    assert_eq!(PathMeasure::from_bytes(b"LYPX").err(), Some(MeasureError::InvalidHeader));
+    assert_eq!(PathMeasure::from_bytes(&bytes[..bytes.len() - 1]).err(), Some(MeasureError::UnexpectedEnd));
+    let mut future = bytes.clone();
+    future[4] = 2;
+    assert_eq!(PathMeasure::from_bytes(&future).err(), Some(MeasureError::UnsupportedVersion(2)));
+
+    // The segments must follow each other along the path.
+    let mut segments = measure.segments().to_vec();
+    segments[3].start += 1.0;
+    assert_eq!(PathMeasure::from_segments(segments).err(), Some(MeasureError::InvalidSegment(3)));
+}
+
+#[test]
+fn test_resample_line() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(4.0, 0.0));
+    builder.line_to(point(4.0, 6.0));
+    let path = builder.build();
+
+    let points = resample(path.path_iter().flattened(0.1), 2.5);
+    let expected = [point(0.0, 0.0), point(2.5, 0.0), point(4.0, 1.0), point(4.0, 3.5), point(4.0, 6.0)];
+    assert_eq!(points.len(), expected.len());
+    for (p, e) in points.iter().zip(expected.iter()) {
+        assert!((*p - *e).length() < 0.0001, "{:?} {:?}", p, e);
+    }
+    assert!(resample(path.path_iter().flattened(0.1), 0.0).is_empty());
+}
+
+#[test]
+fn test_resample_curve() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.quadratic_bezier_to(point(10.0, 20.0), point(20.0, 0.0));
+    let path = builder.build();
+
+    let measure = PathMeasure::new(path.path_iter().flattened(0.001));
+    let points = measure.resample(1.0);
+    assert_eq!(points.len(), measure.length().floor() as usize + 1);
+    // The chords are slightly shorter than the arcs.
+    for pair in points.windows(2) {
+        let d = (pair[1] - pair[0]).length();
+        assert!(d <= 1.0001 && d > 0.99);
+    }
+}
+
+#[test]
+fn test_resample_path() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    builder.move_to(point(20.0, 0.0));
+    builder.line_to(point(25.0, 0.0));
+    let path = builder.build();
+
+    let mut builder = Path::builder();
+    resample_path(path.path_iter().flattened(0.1), 3.0, &mut builder);
+    let resampled = builder.build();
+
+    let mut num_points = 0;
+    let mut closed = false;
+    for evt in resampled.path_iter().flattened(0.1) {
+        match evt {
+            FlattenedEvent::MoveTo(_) | FlattenedEvent::LineTo(_) => { num_points += 1; }
+            FlattenedEvent::Close => { closed = true; }
+        }
+    }
+    // 14 points on the square (0, 3, ..., 39), 20, 23 and the end of the line.
+    assert_eq!(num_points, 14 + 3);
+    assert!(closed);
+    assert_eq!(resampled.points()[resampled.points().len() - 1], point(25.0, 0.0));
+}
@@ -0,0 +1,318 @@
+//! Minkowski sums of paths with convex polygons and discs.
+//!
+//! The Minkowski sum of a shape A and a convex shape B is the set of the points `a + b`,
+//! in other words the area covered by B when its origin is moved over all of A. With a disc
+//! this is the dilation of A, useful to inflate hit areas or to compute the configuration
+//! space of a robot.
+//!
+//! The sum is computed as the convolution of the boundaries: each polygon of the path is
+//! turned into a single closed polygon following the edges of the path translated by the
+//! vertices of B, going around the vertices of B at the corners. Around the concave corners
+//! the convolution loops over itself, so the result must be filled with the non-zero fill
+//! rule (the winding number of the convolution is positive inside of the sum and zero
+//! outside).
+//!
+//! The sub-paths must be oriented consistently, with holes in the opposite direction of the
+//! outer polygons.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::minkowski::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! let mut builder = Path::builder();
+//! minkowski_sum_with_disc(path.path_iter().flattened(0.1), 5.0, 0.1, &mut builder);
+//! let inflated = builder.build();
+//! // The inflated path can be filled like any other path.
+//! # let _ = inflated;
+//! # }
+//! ```
+
+use std::f32::consts::PI;
+
+use core::FlattenedEvent;
+use core::math::*;
+use path_builder::BaseBuilder;
+
+/// Computes the Minkowski sum of a polygon and a convex polygon.
+///
+/// The convex polygon can be in either orientation. The result is a closed polygon to be
+/// filled with the non-zero fill rule, in the orientation of `polygon`.
+pub fn minkowski_sum_polygon(polygon: &[Point], convex: &[Point]) -> Vec<Point> {
+    let convex = counter_clockwise(convex);
+    let mut result = Vec::new();
+    if signed_area(polygon) < 0.0 {
+        let reversed: Vec<Point> = polygon.iter().rev().cloned().collect();
+        convolve(&reversed, &convex, &mut result);
+        result.reverse();
+    } else {
+        convolve(polygon, &convex, &mut result);
+    }
+
+    return result;
+}
+
+/// Computes the Minkowski sum of a flattened path and a convex polygon, and sends the
+/// result to a path builder.
+///
+/// Each sub-path is treated as a closed polygon. The result must be filled with the
+/// non-zero fill rule.
+pub fn minkowski_sum<Iter, Builder>(path: Iter, convex: &[Point], output: &mut Builder)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Builder: BaseBuilder,
+{
+    let convex = counter_clockwise(convex);
+    let mut polygons = collect_polygons(path);
+
+    // The orientation of the path decides which polygons are holes.
+    let area: f32 = polygons.iter().map(|polygon| signed_area(polygon)).sum();
+    let reversed = area < 0.0;
+    if reversed {
+        for polygon in &mut polygons {
+            polygon.reverse();
+        }
+    }
+
+    let mut result = Vec::new();
+    for polygon in &polygons {
+        result.clear();
+        convolve(polygon, &convex, &mut result);
+        if reversed {
+            result.reverse();
+        }
+        if result.len() < 3 {
+            continue;
+        }
+        output.move_to(result[0]);
+        for &p in &result[1..] {
+            output.line_to(p);
+        }
+        output.close();
+    }
+}
+
+/// Computes the Minkowski sum of a flattened path and a disc centered on the origin, which
+/// is the set of points within `radius` of the fill of the path.
+///
+/// The disc is approximated with a polygon within `tolerance`. The result must be filled
+/// with the non-zero fill rule.
+pub fn minkowski_sum_with_disc<Iter, Builder>(
+    path: Iter,
+    radius: f32,
+    tolerance: f32,
+    output: &mut Builder,
+) where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Builder: BaseBuilder,
+{
+    minkowski_sum(path, &disc_polygon(radius, tolerance), output);
+}
+
+/// A regular polygon inscribed in a circle centered on the origin, with enough vertices for
+/// its edges to be within `tolerance` of the circle.
+pub fn disc_polygon(radius: f32, tolerance: f32) -> Vec<Point> {
+    let radius = radius.abs();
+    let num_points = if tolerance <= 0.0 || tolerance >= radius {
+        if tolerance <= 0.0 { 64 } else { 4 }
+    } else {
+        let angle = (1.0 - tolerance / radius).acos();
+        ((PI / angle).ceil() as u32).max(4).min(1024)
+    };
+
+    return (0..num_points).map(|i| {
+        let angle = i as f32 * 2.0 * PI / num_points as f32;
+        point(angle.cos() * radius, angle.sin() * radius)
+    }).collect();
+}
+
+// Convolution of a counter-clockwise (positive area) polygon and a counter-clockwise
+// convex polygon.
+fn convolve(polygon: &[Point], convex: &[Point], output: &mut Vec<Point>) {
+    let n = polygon.len();
+    let m = convex.len();
+    if n < 2 || m == 0 {
+        return;
+    }
+
+    for i in 0..n {
+        let p = polygon[i];
+        let d_in = p - polygon[(i + n - 1) % n];
+        let d_out = polygon[(i + 1) % n] - p;
+
+        let start = extreme_vertex(convex, d_in);
+        let end = extreme_vertex(convex, d_out);
+        // Go around the convex polygon in the direction the path turns.
+        let forward = d_in.cross(d_out) >= 0.0;
+
+        let mut k = start;
+        output.push(p + convex[k].to_vector());
+        while k != end {
+            k = if forward { (k + 1) % m } else { (k + m - 1) % m };
+            output.push(p + convex[k].to_vector());
+        }
+    }
+}
+
+// The vertex of the convex polygon that is the furthest on the right of the direction.
+//
+// It is the vertex where the direction of the boundary of the convex polygon passes the
+// given direction, so that edges parallel to the direction are attributed consistently.
+fn extreme_vertex(convex: &[Point], direction: Vec2) -> usize {
+    let m = convex.len();
+    for k in 0..m {
+        let e_in = convex[k] - convex[(k + m - 1) % m];
+        let e_out = convex[(k + 1) % m] - convex[k];
+        if e_in.cross(direction) >= 0.0 && direction.cross(e_out) > 0.0 {
+            return k;
+        }
+    }
+
+    // Degenerate input, fall back to the furthest vertex along the normal.
+    let normal = vec2(direction.y, -direction.x);
+    let mut best = 0;
+    for k in 1..m {
+        if convex[k].to_vector().dot(normal) > convex[best].to_vector().dot(normal) {
+            best = k;
+        }
+    }
+
+    return best;
+}
+
+fn counter_clockwise(polygon: &[Point]) -> Vec<Point> {
+    if signed_area(polygon) < 0.0 {
+        return polygon.iter().rev().cloned().collect();
+    }
+
+    return polygon.to_vec();
+}
+
+fn signed_area(polygon: &[Point]) -> f32 {
+    let n = polygon.len();
+    let mut area = 0.0;
+    for i in 0..n {
+        area += polygon[i].to_vector().cross(polygon[(i + 1) % n].to_vector());
+    }
+
+    return area * 0.5;
+}
+
+fn collect_polygons<Iter: Iterator<Item = FlattenedEvent>>(path: Iter) -> Vec<Vec<Point>> {
+    let mut polygons = Vec::new();
+    let mut current = Vec::new();
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                if current.len() >= 2 {
+                    polygons.push(current.clone());
+                }
+                current.clear();
+                current.push(to);
+            }
+            FlattenedEvent::LineTo(to) => {
+                current.push(to);
+            }
+            FlattenedEvent::Close => {
+                if current.len() >= 2 {
+                    polygons.push(current.clone());
+                }
+                current.clear();
+            }
+        }
+    }
+    if current.len() >= 2 {
+        polygons.push(current);
+    }
+
+    return polygons;
+}
+
+#[cfg(test)]
+fn winding_number(polygon: &[Point], p: Point) -> i32 {
+    let n = polygon.len();
+    let mut winding = 0;
+    for i in 0..n {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % n];
+        if a.y <= p.y {
+            if b.y > p.y && (b - a).cross(p - a) > 0.0 {
+                winding += 1;
+            }
+        } else if b.y <= p.y && (b - a).cross(p - a) < 0.0 {
+            winding -= 1;
+        }
+    }
+
+    return winding;
+}
+
+#[test]
+fn test_minkowski_squares() {
+    let square = [point(0.0, 0.0), point(2.0, 0.0), point(2.0, 2.0), point(0.0, 2.0)];
+    let convex = [point(-1.0, -1.0), point(-1.0, 1.0), point(1.0, 1.0), point(1.0, -1.0)];
+
+    let sum = minkowski_sum_polygon(&square, &convex);
+    assert_eq!(sum.len(), 8);
+    assert_eq!(signed_area(&sum), 16.0);
+    assert!(sum.contains(&point(-1.0, -1.0)));
+    assert!(sum.contains(&point(3.0, 3.0)));
+
+    // The orientation of the input is preserved.
+    let reversed: Vec<Point> = square.iter().rev().cloned().collect();
+    assert_eq!(signed_area(&minkowski_sum_polygon(&reversed, &convex)), -16.0);
+}
+
+#[test]
+fn test_minkowski_concave() {
+    // An L shape.
+    let polygon = [
+        point(0.0, 0.0),
+        point(4.0, 0.0),
+        point(4.0, 1.0),
+        point(1.0, 1.0),
+        point(1.0, 4.0),
+        point(0.0, 4.0),
+    ];
+    let convex = [point(-0.5, -0.5), point(0.5, -0.5), point(0.5, 0.5), point(-0.5, 0.5)];
+    let sum = minkowski_sum_polygon(&polygon, &convex);
+
+    for &(p, inside) in &[
+        (point(2.0, 0.5), true),
+        (point(1.4, 1.4), true),
+        (point(-0.4, 4.4), true),
+        (point(1.6, 1.6), false),
+        (point(3.0, 1.6), false),
+        (point(-0.6, 2.0), false),
+    ] {
+        assert_eq!(winding_number(&sum, p) > 0, inside, "{:?}", p);
+        assert!(winding_number(&sum, p) >= 0);
+    }
+}
+
+#[test]
+fn test_disc_polygon() {
+    let disc = disc_polygon(10.0, 0.1);
+    assert!(disc.len() >= 16);
+    for p in &disc {
+        assert!((p.to_vector().length() - 10.0).abs() < 0.001);
+    }
+    // The middle of the edges is within the tolerance.
+    let mid = disc[0].lerp(disc[1], 0.5);
+    assert!(10.0 - mid.to_vector().length() <= 0.1);
+}
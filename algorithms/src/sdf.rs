@@ -0,0 +1,349 @@
+//! Rasterizing paths into signed distance fields.
+//!
+//! A signed distance field stores, for each pixel, the distance from the center of the pixel
+//! to the outline of a shape, negative inside of the shape and positive outside. Sampled with
+//! bilinear filtering, it can be rendered at many scales with sharp edges, which is why it is
+//! popular for text and icons.
+//!
+//! [path_to_sdf](fn.path_to_sdf.html) computes the distance to the actual line and curve
+//! segments of the path (not to a flattened approximation), and only uses flattening to find
+//! whether each pixel is inside of the fill. The sub-paths are implicitly closed, like when
+//! filling the path. Each pixel only looks at the segments whose bounding rectangle is closer
+//! than `max_distance` and than the closest segment found so far.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::sdf::*;
+//! # fn upload_texture(_width: u32, _height: u32, _data: &[u8]) {}
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.quadratic_bezier_to(point(10.0, 10.0), point(0.0, 10.0));
+//! # builder.close();
+//! # let glyph = builder.build();
+//! let options = SdfOptions::default().with_resolution(4.0).with_max_distance(2.0);
+//! let sdf = path_to_sdf(glyph.iter(), &options);
+//! upload_texture(sdf.width, sdf.height, &sdf.to_u8());
+//! # }
+//! ```
+
+use core::{PathEvent, FillRule};
+use core::math::*;
+use path::{SegmentIter, Segment, distance_to_rect};
+
+/// Parameters of the distance field generation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SdfOptions {
+    /// Number of pixels per unit of the path.
+    pub resolution: f32,
+
+    /// Number of pixels added around the bounding rectangle of the path.
+    pub padding: u32,
+
+    /// The distances are clamped between `-max_distance` and `max_distance` (in units of the
+    /// path). It is also the range of the 8-bit encoding.
+    pub max_distance: f32,
+
+    /// See the SVG specification.
+    pub fill_rule: FillRule,
+
+    /// Maximum distance between the curves and their flattened approximation, only used to
+    /// decide which pixels are inside and for the precision of the distance to curves.
+    pub tolerance: f32,
+
+    // To be able to add fields without making it a breaking change, add an empty private field
+    // which makes it impossible to create a SdfOptions without the calling constructor.
+    _private: (),
+}
+
+impl SdfOptions {
+    pub fn default() -> SdfOptions {
+        SdfOptions {
+            resolution: 1.0,
+            padding: 4,
+            max_distance: 4.0,
+            fill_rule: FillRule::EvenOdd,
+            tolerance: 0.01,
+            _private: (),
+        }
+    }
+
+    pub fn with_resolution(mut self, resolution: f32) -> SdfOptions {
+        self.resolution = resolution;
+        return self;
+    }
+
+    pub fn with_padding(mut self, padding: u32) -> SdfOptions {
+        self.padding = padding;
+        return self;
+    }
+
+    pub fn with_max_distance(mut self, max_distance: f32) -> SdfOptions {
+        self.max_distance = max_distance;
+        return self;
+    }
+
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> SdfOptions {
+        self.fill_rule = fill_rule;
+        return self;
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f32) -> SdfOptions {
+        self.tolerance = tolerance;
+        return self;
+    }
+}
+
+/// A grid of signed distances.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistanceField {
+    pub width: u32,
+    pub height: u32,
+    /// Position of the top-left corner of the first pixel, in the space of the path.
+    pub origin: Point,
+    /// Size of a pixel in units of the path.
+    pub pixel_size: f32,
+    pub max_distance: f32,
+    /// Row by row, negative inside of the path.
+    pub values: Vec<f32>,
+}
+
+impl DistanceField {
+    /// The signed distance at a pixel.
+    pub fn get(&self, x: u32, y: u32) -> f32 { self.values[(y * self.width + x) as usize] }
+
+    /// The position of the center of a pixel, in the space of the path.
+    pub fn pixel_center(&self, x: u32, y: u32) -> Point {
+        self.origin + vec2(x as f32 + 0.5, y as f32 + 0.5) * self.pixel_size
+    }
+
+    /// Encodes the distances in 8 bits.
+    ///
+    /// The outline is at 128, the inside of the shape is above and the outside below, with
+    /// `max_distance` mapped to 0 and `-max_distance` to 255.
+    pub fn to_u8(&self) -> Vec<u8> {
+        self.values.iter().map(|&d| {
+            let v = (0.5 - d / (2.0 * self.max_distance)) * 255.0;
+            v.round().max(0.0).min(255.0) as u8
+        }).collect()
+    }
+}
+
+/// Computes the signed distance field of a path.
+pub fn path_to_sdf<Iter>(path: Iter, options: &SdfOptions) -> DistanceField
+where
+    Iter: Iterator<Item = PathEvent>,
+{
+    let segments: Vec<Segment> = SegmentIter::new(close_sub_paths(path).into_iter()).collect();
+    let pixel_size = 1.0 / options.resolution;
+    let padding = options.padding as f32 * pixel_size;
+
+    let mut bounds: Option<Rect> = None;
+    for segment in &segments {
+        let r = segment.bounding_rect();
+        bounds = Some(match bounds {
+            Some(b) => b.union(&r),
+            None => r,
+        });
+    }
+    let bounds = match bounds {
+        Some(b) => b,
+        None => {
+            return DistanceField {
+                width: 0,
+                height: 0,
+                origin: point(0.0, 0.0),
+                pixel_size: pixel_size,
+                max_distance: options.max_distance,
+                values: Vec::new(),
+            };
+        }
+    };
+
+    let width = (bounds.size.width * options.resolution).ceil() as u32 + 2 * options.padding;
+    let height = (bounds.size.height * options.resolution).ceil() as u32 + 2 * options.padding;
+    let mut sdf = DistanceField {
+        width: width,
+        height: height,
+        origin: bounds.origin - vec2(padding, padding),
+        pixel_size: pixel_size,
+        max_distance: options.max_distance,
+        values: Vec::with_capacity((width * height) as usize),
+    };
+
+    let edges = flatten(&segments, options.tolerance);
+    let rects: Vec<Rect> = segments.iter().map(|s| s.bounding_rect()).collect();
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+
+    for y in 0..height {
+        let center_y = sdf.pixel_center(0, y).y;
+        row_crossings(&edges, center_y, &mut crossings);
+
+        let mut crossing_idx = 0;
+        let mut winding = 0;
+        for x in 0..width {
+            let position = sdf.pixel_center(x, y);
+            while crossing_idx < crossings.len() && crossings[crossing_idx].0 <= position.x {
+                winding += crossings[crossing_idx].1;
+                crossing_idx += 1;
+            }
+
+            let mut distance = options.max_distance;
+            for (segment, rect) in segments.iter().zip(rects.iter()) {
+                if distance_to_rect(position, rect) >= distance {
+                    continue;
+                }
+                let t = segment.closest_point_t(position, options.tolerance);
+                distance = distance.min((segment.sample(t) - position).length());
+            }
+
+            let inside = match options.fill_rule {
+                FillRule::EvenOdd => winding % 2 != 0,
+                FillRule::NonZero => winding != 0,
+            };
+            sdf.values.push(if inside { -distance } else { distance });
+        }
+    }
+
+    return sdf;
+}
+
+// Adds the missing close events so that the sub-paths are closed like when filling.
+fn close_sub_paths<Iter: Iterator<Item = PathEvent>>(path: Iter) -> Vec<PathEvent> {
+    let mut events = Vec::new();
+    let mut open = false;
+    for evt in path {
+        match evt {
+            PathEvent::MoveTo(_) => {
+                if open {
+                    events.push(PathEvent::Close);
+                }
+                open = false;
+            }
+            PathEvent::Close => { open = false; }
+            _ => { open = true; }
+        }
+        events.push(evt);
+    }
+    if open {
+        events.push(PathEvent::Close);
+    }
+
+    return events;
+}
+
+fn flatten(segments: &[Segment], tolerance: f32) -> Vec<(Point, Point)> {
+    let mut edges = Vec::new();
+    for segment in segments {
+        let mut from = segment.from();
+        let mut add_edge = |to: Point| {
+            edges.push((from, to));
+            from = to;
+        };
+        match *segment {
+            Segment::Line(ref s) => { add_edge(s.to); }
+            Segment::Quadratic(ref s) => { s.flattened_for_each(tolerance, &mut add_edge); }
+            Segment::Cubic(ref s) => { s.flattened_for_each(tolerance, &mut add_edge); }
+            Segment::Arc(ref s) => { s.flattened_for_each(tolerance, &mut add_edge); }
+        }
+    }
+
+    return edges;
+}
+
+// The intersections of the edges with a horizontal line, sorted by x, with the direction of
+// the edges.
+fn row_crossings(edges: &[(Point, Point)], y: f32, crossings: &mut Vec<(f32, i32)>) {
+    crossings.clear();
+    for &(a, b) in edges {
+        // Half-open so that the vertices on the line are counted once.
+        let (winding, crosses) = if a.y < b.y {
+            (1, a.y <= y && y < b.y)
+        } else {
+            (-1, b.y <= y && y < a.y)
+        };
+        if crosses {
+            crossings.push((a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y), winding));
+        }
+    }
+    crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::{BaseBuilder, PathBuilder};
+
+#[test]
+fn test_sdf_square() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(8.0, 0.0));
+    builder.line_to(point(8.0, 8.0));
+    builder.line_to(point(0.0, 8.0));
+    builder.close();
+    let path = builder.build();
+
+    let options = SdfOptions::default().with_padding(2).with_max_distance(3.0);
+    let sdf = path_to_sdf(path.iter(), &options);
+    assert_eq!((sdf.width, sdf.height), (12, 12));
+    assert_eq!(sdf.origin, point(-2.0, -2.0));
+
+    // The center of the pixel (2, 2) is at (0.5, 0.5), inside.
+    assert_eq!(sdf.get(2, 2), -0.5);
+    assert_eq!(sdf.get(5, 2), -0.5);
+    assert_eq!(sdf.get(4, 4), -2.5);
+    // Clamped.
+    assert_eq!(sdf.get(5, 5), -3.0);
+    // Outside.
+    assert_eq!(sdf.get(1, 5), 0.5);
+    assert_eq!(sdf.get(0, 5), 1.5);
+    assert_eq!(sdf.get(0, 0), (1.5f32 * 1.5 * 2.0).sqrt());
+
+    let bytes = sdf.to_u8();
+    assert!(bytes[(4 * 12 + 4) as usize] > 128);
+    assert!(bytes[(5 * 12 + 0) as usize] < 128);
+    assert_eq!(bytes[(5 * 12 + 5) as usize], 255);
+}
+
+#[test]
+fn test_sdf_curve() {
+    // A disc approximated with four quadratic curves, open to check the implicit close.
+    let r = 4.0;
+    // Control points at the intersection of the tangents.
+    let k = r * (2.0f32.sqrt() - 1.0);
+    let mut builder = Path::builder();
+    builder.move_to(point(r, 0.0));
+    builder.quadratic_bezier_to(point(r, k), point(r * 0.5f32.sqrt(), r * 0.5f32.sqrt()));
+    builder.quadratic_bezier_to(point(k, r), point(0.0, r));
+    builder.line_to(point(0.0, 0.0));
+    let path = builder.build();
+
+    let options = SdfOptions::default().with_resolution(2.0).with_padding(2).with_max_distance(10.0);
+    let sdf = path_to_sdf(path.iter(), &options);
+    for y in 0..sdf.height {
+        for x in 0..sdf.width {
+            let p = sdf.pixel_center(x, y);
+            let d = sdf.get(x, y);
+            // Where the arc is the closest part of the outline, the distance is the distance
+            // to the circle.
+            let expected = p.to_vector().length() - r;
+            if p.x > 0.0 && p.y > 0.0 && expected.abs() < p.x.min(p.y).min(1.0) {
+                assert!((d - expected).abs() < 0.1, "{:?} {} {}", p, d, expected);
+            }
+            // The implicit closing edge goes from (0, 0) to (r, 0).
+            if p.y < 0.0 && p.x > 0.5 && p.x < r - 0.5 {
+                assert!((d + p.y).abs() < 0.001);
+            }
+        }
+    }
+}
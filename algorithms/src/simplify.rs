@@ -0,0 +1,291 @@
+//! Area-based polyline simplification (Visvalingam-Whyatt).
+//!
+//! Each point forms a triangle with its two neighbors, the area of which measures how much
+//! the point contributes to the shape. The points are removed by increasing area until all
+//! remaining triangles are larger than a threshold. Compared to distance based algorithms
+//! the results tend to look more natural at small scales, which suits multi-zoom map data.
+//!
+//! When rings share borders (adjacent polygons on a map), simplifying each ring separately
+//! makes the shared borders diverge and opens gaps between the polygons.
+//! [simplify_rings](fn.simplify_rings.html) can preserve the topology by splitting the rings
+//! at the vertices where borders meet and simplifying each border once.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::simplify::*;
+//! # fn main() {
+//! # let parcels = vec![
+//! #     vec![point(0.0, 0.0), point(5.0, 0.1), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)],
+//! #     vec![point(10.0, 0.0), point(20.0, 0.0), point(20.0, 10.0), point(10.0, 10.0)],
+//! # ];
+//! let simplified = simplify_rings(&parcels, 2.0, true);
+//! # assert_eq!(simplified.len(), 2);
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use core::math::*;
+
+/// Simplifies an open polyline, the first and last points are always kept.
+///
+/// Points whose triangle with their neighbors has an area smaller than `min_area` are
+/// removed.
+pub fn simplify_polyline(points: &[Point], min_area: f32) -> Vec<Point> {
+    simplify(points, min_area, false)
+}
+
+/// Simplifies a closed ring (the last point must not repeat the first one).
+///
+/// At least three points are kept.
+pub fn simplify_ring(points: &[Point], min_area: f32) -> Vec<Point> {
+    simplify(points, min_area, true)
+}
+
+/// Simplifies a set of closed rings.
+///
+/// If `preserve_topology` is true, the portions of the rings that are shared with other
+/// rings (same sequence of points, in either direction) are simplified identically and the
+/// vertices where several borders meet are kept, so that adjacent rings stay adjacent.
+pub fn simplify_rings(
+    rings: &[Vec<Point>],
+    min_area: f32,
+    preserve_topology: bool,
+) -> Vec<Vec<Point>> {
+    if !preserve_topology {
+        return rings.iter().map(|ring| simplify_ring(ring, min_area)).collect();
+    }
+
+    let junctions = find_junctions(rings);
+
+    let mut result = Vec::with_capacity(rings.len());
+    for ring in rings {
+        let n = ring.len();
+        let first_junction = (0..n).find(|&i| junctions.get(&key(ring[i])) == Some(&true));
+        let start = match first_junction {
+            Some(idx) => idx,
+            None => {
+                result.push(simplify_ring(ring, min_area));
+                continue;
+            }
+        };
+
+        // Walk the ring from a junction and simplify each chain between two junctions.
+        let mut output = Vec::with_capacity(n);
+        let mut chain = vec![ring[start]];
+        for i in 1..n + 1 {
+            let p = ring[(start + i) % n];
+            chain.push(p);
+            if junctions.get(&key(p)) == Some(&true) {
+                let simplified = simplify_chain(&chain, min_area);
+                // The last point is the first point of the next chain.
+                output.extend_from_slice(&simplified[..simplified.len() - 1]);
+                chain.clear();
+                chain.push(p);
+            }
+        }
+
+        result.push(output);
+    }
+
+    return result;
+}
+
+// Simplifies the chain in a canonical direction so that two rings sharing the chain in
+// opposite directions get the same result.
+fn simplify_chain(chain: &[Point], min_area: f32) -> Vec<Point> {
+    if is_canonical(chain) {
+        return simplify_polyline(chain, min_area);
+    }
+
+    let reversed: Vec<Point> = chain.iter().rev().cloned().collect();
+    let mut simplified = simplify_polyline(&reversed, min_area);
+    simplified.reverse();
+
+    return simplified;
+}
+
+fn is_canonical(chain: &[Point]) -> bool {
+    let n = chain.len();
+    for i in 0..n / 2 {
+        match compare_points(chain[i], chain[n - 1 - i]) {
+            Ordering::Less => { return true; }
+            Ordering::Greater => { return false; }
+            Ordering::Equal => {}
+        }
+    }
+    return true;
+}
+
+fn compare_points(a: Point, b: Point) -> Ordering {
+    match a.x.partial_cmp(&b.x) {
+        Some(Ordering::Equal) | None => a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal),
+        Some(order) => order,
+    }
+}
+
+fn key(p: Point) -> (u32, u32) { (p.x.to_bits(), p.y.to_bits()) }
+
+// A vertex is a junction if it is shared by several rings with different neighbors, that
+// is where shared borders start or end.
+fn find_junctions(rings: &[Vec<Point>]) -> HashMap<(u32, u32), bool> {
+    // For each vertex, the unordered pair of neighbors of its first occurrence and whether
+    // it is a junction.
+    let mut vertices: HashMap<(u32, u32), ((u32, u32), (u32, u32), bool)> = HashMap::new();
+    for ring in rings {
+        let n = ring.len();
+        for i in 0..n {
+            let a = key(ring[(i + n - 1) % n]);
+            let b = key(ring[(i + 1) % n]);
+            let neighbors = if a < b { (a, b) } else { (b, a) };
+            let entry = vertices.entry(key(ring[i])).or_insert((neighbors.0, neighbors.1, false));
+            if (entry.0, entry.1) != neighbors {
+                entry.2 = true;
+            }
+        }
+    }
+
+    return vertices.into_iter().map(|(k, v)| (k, v.2)).collect();
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Candidate {
+    area: f32,
+    index: usize,
+    // Invalidates the candidate when the neighbors of the point change.
+    version: u32,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for Candidate {
+    // Reversed so that the binary heap (a max-heap) pops the smallest area first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.area.partial_cmp(&self.area).unwrap_or(Ordering::Equal)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+fn simplify(points: &[Point], min_area: f32, closed: bool) -> Vec<Point> {
+    let n = points.len();
+    let min_points = if closed { 3 } else { 2 };
+    if n <= min_points {
+        return points.to_vec();
+    }
+
+    let mut prev: Vec<usize> = (0..n).map(|i| (i + n - 1) % n).collect();
+    let mut next: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+    let mut removed = vec![false; n];
+    let mut versions = vec![0u32; n];
+    let mut remaining = n;
+
+    let removable = |i: usize| closed || (i != 0 && i != n - 1);
+    let area = |p: &[usize], nx: &[usize], i: usize| {
+        triangle_area(points[p[i]], points[i], points[nx[i]])
+    };
+
+    let mut heap = BinaryHeap::with_capacity(n);
+    for i in 0..n {
+        if removable(i) {
+            heap.push(Candidate { area: area(&prev, &next, i), index: i, version: 0 });
+        }
+    }
+
+    while let Some(candidate) = heap.pop() {
+        let i = candidate.index;
+        if removed[i] || candidate.version != versions[i] {
+            continue;
+        }
+        if candidate.area >= min_area || remaining <= min_points {
+            break;
+        }
+
+        removed[i] = true;
+        remaining -= 1;
+        let (p, nx) = (prev[i], next[i]);
+        next[p] = nx;
+        prev[nx] = p;
+
+        for &j in &[p, nx] {
+            if removable(j) {
+                versions[j] += 1;
+                // The area can't decrease, otherwise points would be removed before the
+                // point whose removal made them less significant.
+                let a = area(&prev, &next, j).max(candidate.area);
+                heap.push(Candidate { area: a, index: j, version: versions[j] });
+            }
+        }
+    }
+
+    return (0..n).filter(|&i| !removed[i]).map(|i| points[i]).collect();
+}
+
+fn triangle_area(a: Point, b: Point, c: Point) -> f32 { ((b - a).cross(c - a) * 0.5).abs() }
+
+#[test]
+fn test_simplify_polyline() {
+    let points = [
+        point(0.0, 0.0),
+        point(1.0, 0.1),
+        point(2.0, 0.0),
+        point(3.0, 5.0),
+        point(4.0, 0.0),
+    ];
+    assert_eq!(
+        simplify_polyline(&points, 1.0),
+        vec![point(0.0, 0.0), point(2.0, 0.0), point(3.0, 5.0), point(4.0, 0.0)]
+    );
+    assert_eq!(simplify_polyline(&points, 100.0), vec![point(0.0, 0.0), point(4.0, 0.0)]);
+    assert_eq!(simplify_polyline(&points, 0.0), points.to_vec());
+}
+
+#[test]
+fn test_simplify_ring() {
+    let ring = [
+        point(0.0, 0.0),
+        point(5.0, 0.01),
+        point(10.0, 0.0),
+        point(10.0, 10.0),
+        point(0.0, 10.0),
+    ];
+    assert_eq!(simplify_ring(&ring, 1.0).len(), 4);
+    assert_eq!(simplify_ring(&ring, 1000.0).len(), 3);
+}
+
+#[test]
+fn test_simplify_rings_topology() {
+    // Two squares sharing a wiggly vertical border at x = 10.
+    let border = [
+        point(10.0, 0.0),
+        point(10.1, 2.0),
+        point(9.9, 4.0),
+        point(10.1, 6.0),
+        point(9.9, 8.0),
+        point(10.0, 10.0),
+    ];
+    let mut left = vec![point(0.0, 10.0), point(0.0, 0.0)];
+    left.extend_from_slice(&border);
+    let mut right = vec![point(20.0, 0.0), point(20.0, 10.0)];
+    right.extend(border.iter().rev().cloned());
+
+    let simplified = simplify_rings(&[left, right], 0.5, true);
+
+    let mut left_border: Vec<Point> = simplified[0].iter().filter(|p| p.x > 5.0).cloned().collect();
+    let mut right_border: Vec<Point> = simplified[1].iter().filter(|p| p.x < 15.0).cloned().collect();
+    left_border.sort_by(|a, b| compare_points(*a, *b));
+    right_border.sort_by(|a, b| compare_points(*a, *b));
+    assert_eq!(left_border, right_border);
+    // The ends of the shared border are kept.
+    assert!(left_border.contains(&point(10.0, 0.0)));
+    assert!(left_border.contains(&point(10.0, 10.0)));
+    assert!(left_border.len() < border.len());
+}
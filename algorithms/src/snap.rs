@@ -0,0 +1,408 @@
+//! Snapping paths to a grid without breaking them.
+//!
+//! Rounding the coordinates of a path independently can make a vertex jump to the other
+//! side of a nearby edge, creating self-intersections, and can collapse short edges and thin
+//! parts of the shape. [snap_polygons_to_grid](fn.snap_polygons_to_grid.html) uses snap
+//! rounding instead: the pixels of the grid that contain a vertex or an intersection are
+//! "hot", and every edge that goes through a hot pixel is routed through its center. Edges
+//! that would cross after the rounding then meet at a shared vertex instead, so the output
+//! doesn't have new self-intersections.
+//!
+//! The result is then cleaned up: repeated points, edges that go back and forth and
+//! polygons that collapsed to nothing are removed.
+//!
+//! Finding the intersections is quadratic in the number of edges.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::snap::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.2, 0.3));
+//! # builder.line_to(point(10.6, 0.1));
+//! # builder.line_to(point(5.4, 8.9));
+//! # builder.close();
+//! # let path = builder.build();
+//! // Snap to the centers of the pixels.
+//! let mut builder = Path::builder();
+//! snap_path_to_grid(path.path_iter().flattened(0.1), 1.0, vec2(0.5, 0.5), &mut builder);
+//! # let _ = builder.build();
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+use std::iter::once;
+
+use core::{FlattenedEvent, PathEvent};
+use core::math::*;
+use core::math_utils::clip_segment_to_rect;
+use path_builder::BaseBuilder;
+use path::SegmentIndex;
+
+/// A polyline, closed or not.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<Point>,
+    pub closed: bool,
+}
+
+/// Snaps the points of a flattened path to a grid, and sends the result to a path builder.
+///
+/// The grid points are at `offset + (i, j) * spacing`, for example a spacing of one and an
+/// offset of `(0.5, 0.5)` snaps to the center of the pixels.
+pub fn snap_path_to_grid<Iter, Builder>(path: Iter, spacing: f32, offset: Vec2, output: &mut Builder)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Builder: BaseBuilder,
+{
+    let polylines = collect_polylines(path);
+    for polyline in snap_polygons_to_grid(&polylines, spacing, offset) {
+        output.move_to(polyline.points[0]);
+        for &p in &polyline.points[1..] {
+            output.line_to(p);
+        }
+        if polyline.closed {
+            output.close();
+        }
+    }
+}
+
+/// Snaps polylines to a grid with snap rounding. See the [module documentation](index.html).
+///
+/// Closed polylines that collapse to less than three points or to a zero area are removed,
+/// as well as open polylines that collapse to a single point.
+///
+/// The polylines are returned unchanged if the spacing isn't a positive finite number.
+pub fn snap_polygons_to_grid(polylines: &[Polyline], spacing: f32, offset: Vec2) -> Vec<Polyline> {
+    if !(spacing > 0.0 && spacing.is_finite()) {
+        return polylines.to_vec();
+    }
+
+    let grid = Grid { spacing: spacing, offset: offset };
+
+    let mut edges = Vec::new();
+    for polyline in polylines {
+        let n = polyline.points.len();
+        let num_edges = if polyline.closed { n } else { n.saturating_sub(1) };
+        for i in 0..num_edges {
+            edges.push((polyline.points[i], polyline.points[(i + 1) % n]));
+        }
+    }
+
+    // The hot pixels, identified by their center.
+    let mut hot_pixels: Vec<Point> = Vec::new();
+    for polyline in polylines {
+        for &p in &polyline.points {
+            hot_pixels.push(grid.snap(p));
+        }
+    }
+    // Only the edges with overlapping bounding rectangles can intersect.
+    let index = SegmentIndex::new(edges.iter().flat_map(|&(from, to)| {
+        once(PathEvent::MoveTo(from)).chain(once(PathEvent::LineTo(to)))
+    }));
+    for i in 0..edges.len() {
+        for j in index.segments_in_rect(&index.segments()[i].bounding_rect()) {
+            if j <= i {
+                continue;
+            }
+            if let Some(p) = intersection(edges[i], edges[j]) {
+                hot_pixels.push(grid.snap(p));
+            }
+        }
+    }
+    hot_pixels.sort_by(|a, b| total_cmp(a.x, b.x).then(total_cmp(a.y, b.y)));
+    hot_pixels.dedup();
+
+    let mut result = Vec::new();
+    for polyline in polylines {
+        let n = polyline.points.len();
+        if n == 0 {
+            continue;
+        }
+        let num_edges = if polyline.closed { n } else { n - 1 };
+        let mut points = vec![grid.snap(polyline.points[0])];
+        for i in 0..num_edges {
+            route_edge(polyline.points[i], polyline.points[(i + 1) % n], &grid, &hot_pixels, &mut points);
+        }
+
+        if polyline.closed {
+            // The last point is the first one.
+            points.pop();
+        }
+
+        cleanup(&mut points, polyline.closed);
+
+        let valid = if polyline.closed {
+            points.len() >= 3 && signed_area(&points) != 0.0
+        } else {
+            points.len() >= 2
+        };
+        if valid {
+            result.push(Polyline { points: points, closed: polyline.closed });
+        }
+    }
+
+    return result;
+}
+
+struct Grid {
+    spacing: f32,
+    offset: Vec2,
+}
+
+impl Grid {
+    fn snap(&self, p: Point) -> Point {
+        let s = self.spacing;
+        point(
+            ((p.x - self.offset.x) / s).round() * s + self.offset.x,
+            ((p.y - self.offset.y) / s).round() * s + self.offset.y,
+        )
+    }
+
+    fn pixel(&self, center: Point) -> Rect {
+        let half = self.spacing * 0.5;
+        rect(center.x - half, center.y - half, self.spacing, self.spacing)
+    }
+}
+
+// Adds the centers of the hot pixels the edge goes through, in order, and the snapped end
+// of the edge.
+fn route_edge(from: Point, to: Point, grid: &Grid, hot_pixels: &[Point], output: &mut Vec<Point>) {
+    let start = grid.snap(from);
+    let end = grid.snap(to);
+    let mut crossed: Vec<(f32, Point)> = Vec::new();
+    for &center in hot_pixels {
+        if center == start || center == end {
+            continue;
+        }
+        if let Some((t0, t1)) = clip_segment_to_rect(from, to, &grid.pixel(center)) {
+            crossed.push(((t0 + t1) * 0.5, center));
+        }
+    }
+    crossed.sort_by(|a, b| total_cmp(a.0, b.0));
+
+    for (_, center) in crossed {
+        output.push(center);
+    }
+    output.push(end);
+}
+
+// Removes repeated points and the edges that go back and forth (spikes).
+fn cleanup(points: &mut Vec<Point>, closed: bool) {
+    points.dedup();
+    if closed {
+        while points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+    }
+
+    let mut changed = true;
+    while changed && points.len() >= 3 {
+        changed = false;
+        let n = points.len();
+        let range = if closed { 0..n } else { 1..n - 1 };
+        for i in range {
+            let prev = points[(i + n - 1) % n];
+            let next = points[(i + 1) % n];
+            if prev == next {
+                // Remove the tip of the spike and one of the two copies of its base.
+                let next_idx = (i + 1) % n;
+                let (first, second) = if next_idx > i { (next_idx, i) } else { (i, next_idx) };
+                points.remove(first);
+                points.remove(second);
+                changed = true;
+                break;
+            }
+            // A point in the middle of a straight edge that goes back over itself.
+            let (v1, v2) = (points[i] - prev, next - points[i]);
+            if v1.cross(v2) == 0.0 && v1.dot(v2) < 0.0 {
+                points.remove(i);
+                changed = true;
+                break;
+            }
+        }
+        if changed {
+            points.dedup();
+            if closed {
+                while points.len() > 1 && points.first() == points.last() {
+                    points.pop();
+                }
+            }
+        }
+    }
+}
+
+// The intersection of two segments, excluding the shared end points.
+fn intersection(a: (Point, Point), b: (Point, Point)) -> Option<Point> {
+    let d1 = a.1 - a.0;
+    let d2 = b.1 - b.0;
+    let denom = d1.cross(d2);
+    if denom == 0.0 {
+        return None;
+    }
+    let t = (b.0 - a.0).cross(d2) / denom;
+    let u = (b.0 - a.0).cross(d1) / denom;
+    if t <= 0.0 || t >= 1.0 || u <= 0.0 || u >= 1.0 {
+        return None;
+    }
+
+    return Some(a.0 + d1 * t);
+}
+
+// IEEE 754 total order, so that NaN coordinates in the input can't make the sorts panic.
+fn total_cmp(a: f32, b: f32) -> Ordering {
+    let key = |v: f32| {
+        let bits = v.to_bits() as i32;
+        bits ^ (((bits >> 31) as u32) >> 1) as i32
+    };
+
+    return key(a).cmp(&key(b));
+}
+
+fn signed_area(polygon: &[Point]) -> f32 {
+    let n = polygon.len();
+    let mut area = 0.0;
+    for i in 0..n {
+        area += polygon[i].to_vector().cross(polygon[(i + 1) % n].to_vector());
+    }
+
+    return area * 0.5;
+}
+
+fn collect_polylines<Iter: Iterator<Item = FlattenedEvent>>(path: Iter) -> Vec<Polyline> {
+    let mut polylines = Vec::new();
+    let mut current = Vec::new();
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                if current.len() > 1 {
+                    polylines.push(Polyline { points: current.clone(), closed: false });
+                }
+                current.clear();
+                current.push(to);
+            }
+            FlattenedEvent::LineTo(to) => {
+                current.push(to);
+            }
+            FlattenedEvent::Close => {
+                if current.len() > 1 && current.first() == current.last() {
+                    current.pop();
+                }
+                if !current.is_empty() {
+                    polylines.push(Polyline { points: current.clone(), closed: true });
+                }
+                current.clear();
+            }
+        }
+    }
+    if current.len() > 1 {
+        polylines.push(Polyline { points: current, closed: false });
+    }
+
+    return polylines;
+}
+
+#[cfg(test)]
+fn has_crossing(polylines: &[Polyline]) -> bool {
+    let mut edges = Vec::new();
+    for polyline in polylines {
+        let n = polyline.points.len();
+        let num_edges = if polyline.closed { n } else { n - 1 };
+        for i in 0..num_edges {
+            edges.push((polyline.points[i], polyline.points[(i + 1) % n]));
+        }
+    }
+    for i in 0..edges.len() {
+        for j in i + 1..edges.len() {
+            if intersection(edges[i], edges[j]).is_some() {
+                return true;
+            }
+        }
+    }
+
+    return false;
+}
+
+#[test]
+fn test_snap_no_new_intersection() {
+    // Rounding the points independently moves (4.4, 0.47) below the edge from (0, 0) to
+    // (10, 1), which makes the edges cross.
+    let polygon = Polyline {
+        points: vec![point(0.0, 0.0), point(10.0, 1.0), point(10.0, 5.0), point(4.4, 0.47)],
+        closed: true,
+    };
+    assert!(!has_crossing(&[polygon.clone()]));
+
+    let snapped = snap_polygons_to_grid(&[polygon], 1.0, vec2(0.0, 0.0));
+    assert_eq!(snapped.len(), 1);
+    assert!(!has_crossing(&snapped));
+    // The edge is routed through the pixel of the vertex, the part of the polygon between
+    // (0, 0) and (4, 0) collapsed.
+    assert_eq!(snapped[0].points.len(), 3);
+    assert!(snapped[0].points.contains(&point(4.0, 0.0)));
+    assert!(!snapped[0].points.contains(&point(0.0, 0.0)));
+}
+
+#[test]
+fn test_snap_collapse() {
+    let small = Polyline {
+        points: vec![point(0.1, 0.1), point(0.3, 0.1), point(0.1, 0.3)],
+        closed: true,
+    };
+    let spike = Polyline {
+        points: vec![
+            point(10.0, 10.0),
+            point(20.0, 10.0),
+            point(20.2, 15.0),
+            point(20.1, 10.2),
+            point(20.0, 20.0),
+            point(10.0, 20.0),
+        ],
+        closed: true,
+    };
+    let snapped = snap_polygons_to_grid(&[small, spike], 1.0, vec2(0.0, 0.0));
+
+    // The small triangle collapsed, the spike became a segment going back and forth.
+    assert_eq!(snapped.len(), 1);
+    assert!(snapped[0].points.iter().all(|p| p.x <= 20.0));
+    assert_eq!(signed_area(&snapped[0].points), 100.0);
+}
+
+#[test]
+fn test_snap_half_pixel() {
+    let line = Polyline {
+        points: vec![point(0.2, 0.2), point(3.9, 1.1)],
+        closed: false,
+    };
+    let snapped = snap_polygons_to_grid(&[line], 1.0, vec2(0.5, 0.5));
+    assert_eq!(snapped[0].points, vec![point(0.5, 0.5), point(3.5, 1.5)]);
+}
+
+#[test]
+fn test_snap_invalid_input() {
+    let polygon = Polyline {
+        points: vec![point(0.2, 0.2), point(3.9, 1.1), point(1.0, 4.0)],
+        closed: true,
+    };
+    for &spacing in &[0.0, -1.0, ::std::f32::NAN, ::std::f32::INFINITY] {
+        let snapped = snap_polygons_to_grid(&[polygon.clone()], spacing, vec2(0.0, 0.0));
+        assert_eq!(snapped, vec![polygon.clone()]);
+    }
+
+    // Doesn't panic.
+    let nan = Polyline {
+        points: vec![point(0.0, 0.0), point(::std::f32::NAN, 1.0), point(1.0, 4.0), point(3.0, 0.0)],
+        closed: true,
+    };
+    snap_polygons_to_grid(&[nan, polygon], 1.0, vec2(0.0, 0.0));
+}
@@ -0,0 +1,486 @@
+//! Conversion of the stroke of a path into a path (stroke-to-path).
+//!
+//! The outline of the stroke is sent to a path builder, which is what exporters to vector
+//! formats (SVG, PDF, fonts) need when the target doesn't support strokes or when the stroke
+//! has to be edited as a shape. Each open sub-path produces one closed outline going along
+//! one side of the line and back along the other side, and each closed sub-path produces an
+//! outline on each side.
+//!
+//! Round joins and caps are emitted as line segments approximating their arcs within the
+//! tolerance, or, with `round_curves`, as cubic bézier curves (one per quarter of circle at
+//! most) which keeps the outline compact.
+//!
+//! The outline is not cleaned up: the inner sides of the joins go through the vertices of the
+//! path and the outlines of different parts of the stroke can overlap. It must be filled with
+//! the non-zero fill rule. See the [buffer](../buffer/index.html) module for a clean result.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_algorithms;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::LineJoin;
+//! # use lyon_core::math::*;
+//! # use lyon_algorithms::stroke_outline::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.quadratic_bezier_to(point(20.0, 0.0), point(20.0, 10.0));
+//! # let path = builder.build();
+//! let options = StrokeOutlineOptions::default()
+//!     .with_line_width(2.0)
+//!     .with_line_join(LineJoin::Round)
+//!     .with_round_curves();
+//! let mut builder = Path::builder();
+//! stroke_to_path(path.path_iter().flattened(0.1), &options, &mut builder);
+//! let outline = builder.build();
+//! # let _ = outline;
+//! # }
+//! ```
+
+use std::f32::consts::PI;
+
+use core::{FlattenedEvent, LineCap, LineJoin};
+use core::math::*;
+use path_builder::PathBuilder;
+
+/// Parameters of [stroke_to_path](fn.stroke_to_path.html).
+#[derive(Copy, Clone, Debug)]
+pub struct StrokeOutlineOptions {
+    pub line_width: f32,
+
+    /// See the SVG specification.
+    pub line_cap: LineCap,
+
+    /// See the SVG specification.
+    pub line_join: LineJoin,
+
+    /// See the SVG specification.
+    pub miter_limit: f32,
+
+    /// Maximum distance between the line segments of round joins and caps and their arcs.
+    ///
+    /// Not used with `round_curves`.
+    pub tolerance: f32,
+
+    /// Emit round joins and caps as cubic bézier curves instead of line segments.
+    pub round_curves: bool,
+
+    // To be able to add fields without making it a breaking change, add an empty private field
+    // which makes it impossible to create a StrokeOutlineOptions without calling the constructor.
+    _private: (),
+}
+
+impl StrokeOutlineOptions {
+    pub fn default() -> StrokeOutlineOptions {
+        StrokeOutlineOptions {
+            line_width: 1.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            miter_limit: 4.0,
+            tolerance: 0.1,
+            round_curves: false,
+            _private: (),
+        }
+    }
+
+    pub fn with_line_width(mut self, width: f32) -> StrokeOutlineOptions {
+        self.line_width = width;
+        return self;
+    }
+
+    pub fn with_line_cap(mut self, cap: LineCap) -> StrokeOutlineOptions {
+        self.line_cap = cap;
+        return self;
+    }
+
+    pub fn with_line_join(mut self, join: LineJoin) -> StrokeOutlineOptions {
+        self.line_join = join;
+        return self;
+    }
+
+    pub fn with_miter_limit(mut self, limit: f32) -> StrokeOutlineOptions {
+        self.miter_limit = limit;
+        return self;
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f32) -> StrokeOutlineOptions {
+        self.tolerance = tolerance;
+        return self;
+    }
+
+    pub fn with_round_curves(mut self) -> StrokeOutlineOptions {
+        self.round_curves = true;
+        return self;
+    }
+}
+
+/// Sends the outline of the stroke of a flattened path to a path builder.
+pub fn stroke_to_path<Iter, Builder>(path: Iter, options: &StrokeOutlineOptions, output: &mut Builder)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Builder: PathBuilder,
+{
+    if !(options.line_width > 0.0) {
+        return;
+    }
+
+    let mut outline = Outline {
+        output: output,
+        options: options,
+        half_width: options.line_width * 0.5,
+        started: false,
+    };
+
+    let (polygons, lines) = collect_sub_paths(path);
+    for polygon in &polygons {
+        outline.side(polygon, &[]);
+        let mut reversed = polygon.clone();
+        reversed.reverse();
+        outline.side(&reversed, &[]);
+    }
+    for line in &lines {
+        outline.line(line);
+    }
+}
+
+struct Outline<'l, Builder: 'l> {
+    output: &'l mut Builder,
+    options: &'l StrokeOutlineOptions,
+    half_width: f32,
+    started: bool,
+}
+
+impl<'l, Builder: PathBuilder> Outline<'l, Builder> {
+    // A line is outlined as the polygon that goes along the line and back.
+    fn line(&mut self, line: &[Point]) {
+        let n = line.len();
+        if n == 1 {
+            let p = line[0];
+            let r = self.half_width;
+            match self.options.line_cap {
+                LineCap::Butt => {}
+                LineCap::Square => {
+                    self.point(p + vec2(-r, -r));
+                    self.point(p + vec2(r, -r));
+                    self.point(p + vec2(r, r));
+                    self.point(p + vec2(-r, r));
+                    self.close();
+                }
+                LineCap::Round => {
+                    let start = p + vec2(r, 0.0);
+                    self.point(start);
+                    self.arc(p, start, start, 2.0 * PI);
+                    self.close();
+                }
+            }
+            return;
+        }
+
+        let mut polygon = line.to_vec();
+        polygon.extend(line[1..n - 1].iter().rev().cloned());
+        self.side(&polygon, &[0, n - 1]);
+    }
+
+    // Outlines the right side of the edges of a polygon (the outside of a counter-clockwise
+    // polygon with the y axis pointing up). The vertices in `caps` are the ends of a line
+    // where the polygon turns back, they get a cap instead of a join.
+    fn side(&mut self, polygon: &[Point], caps: &[usize]) {
+        let n = polygon.len();
+        let hw = self.half_width;
+        for i in 0..n {
+            let p = polygon[i];
+            let d1 = (p - polygon[(i + n - 1) % n]).normalize();
+            let d2 = (polygon[(i + 1) % n] - p).normalize();
+            let a = p + vec2(d1.y, -d1.x) * hw;
+            let b = p + vec2(d2.y, -d2.x) * hw;
+
+            if caps.contains(&i) {
+                self.cap(p, a, b, d1);
+                continue;
+            }
+
+            let turn = d1.cross(d2);
+            if turn > 0.0 || (turn == 0.0 && d1.dot(d2) < 0.0) {
+                self.join(p, a, b, d1, d2);
+            } else if turn < 0.0 {
+                // Inner side of the join, going through the vertex keeps the winding number
+                // of the overlapping part positive.
+                self.point(a);
+                self.point(p);
+                self.point(b);
+            } else {
+                self.point(a);
+            }
+        }
+        self.close();
+    }
+
+    fn join(&mut self, p: Point, a: Point, b: Point, d1: Vec2, d2: Vec2) {
+        let hw = self.half_width;
+        let limit = self.options.miter_limit * hw;
+        self.point(a);
+        match self.options.line_join {
+            LineJoin::Round => {
+                let (v1, v2) = (a - p, b - p);
+                let mut angle = v1.cross(v2).atan2(v1.dot(v2));
+                if angle < 0.0 {
+                    angle += 2.0 * PI;
+                }
+                self.arc(p, a, b, angle);
+                return;
+            }
+            LineJoin::Miter | LineJoin::MiterClip => {
+                // The miter point is where the two offset edges meet.
+                let denom = d1.cross(d2);
+                let miter = if denom != 0.0 {
+                    Some(a + d1 * ((b - a).cross(d2) / denom))
+                } else {
+                    None
+                };
+                match miter {
+                    Some(m) if (m - p).length() <= limit => {
+                        self.point(m);
+                    }
+                    _ if self.options.line_join == LineJoin::MiterClip => {
+                        // Clip the miter with a line perpendicular to the bisector.
+                        let bisector = ((a - p) + (b - p)).normalize();
+                        let bisector = if bisector.x.is_finite() { bisector } else { d1 };
+                        let along = |from: Point, dir: Vec2| {
+                            let speed = dir.dot(bisector);
+                            if speed.abs() < 0.000001 {
+                                return from;
+                            }
+                            from + dir * ((limit - (from - p).dot(bisector)) / speed)
+                        };
+                        let (m1, m2) = (along(a, d1), along(b, -d2));
+                        self.point(m1);
+                        self.point(m2);
+                    }
+                    _ => {}
+                }
+            }
+            LineJoin::Bevel => {}
+        }
+        self.point(b);
+    }
+
+    fn cap(&mut self, p: Point, a: Point, b: Point, direction: Vec2) {
+        match self.options.line_cap {
+            LineCap::Butt => {
+                self.point(a);
+                self.point(b);
+            }
+            LineCap::Square => {
+                let extension = direction * self.half_width;
+                self.point(a + extension);
+                self.point(b + extension);
+            }
+            LineCap::Round => {
+                self.point(a);
+                self.arc(p, a, b, PI);
+            }
+        }
+    }
+
+    fn point(&mut self, p: Point) {
+        if self.started {
+            self.output.line_to(p);
+        } else {
+            self.output.move_to(p);
+            self.started = true;
+        }
+    }
+
+    fn close(&mut self) {
+        if self.started {
+            self.output.close();
+            self.started = false;
+        }
+    }
+
+    // Adds an arc around the center from the current point, which is `from`, to `to`.
+    fn arc(&mut self, center: Point, from: Point, to: Point, angle: f32) {
+        let v = from - center;
+        let rotate = |a: f32| {
+            let (sin, cos) = a.sin_cos();
+            vec2(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+        };
+
+        if self.options.round_curves {
+            // Each piece of at most a quarter of circle is approximated with a cubic bézier
+            // curve with its control points along the tangents.
+            let num_pieces = (angle.abs() / (PI * 0.5) - 0.0001).ceil().max(1.0) as u32;
+            let step = angle / num_pieces as f32;
+            let k = 4.0 / 3.0 * (step / 4.0).tan();
+            for i in 0..num_pieces {
+                let v0 = rotate(step * i as f32);
+                let v1 = rotate(step * (i + 1) as f32);
+                let end = if i + 1 == num_pieces { to } else { center + v1 };
+                self.output.cubic_bezier_to(
+                    center + v0 + vec2(-v0.y, v0.x) * k,
+                    center + v1 - vec2(-v1.y, v1.x) * k,
+                    end,
+                );
+            }
+            return;
+        }
+
+        let radius = self.half_width;
+        let tolerance = self.options.tolerance.max(0.0001);
+        let max_step = if tolerance >= radius {
+            PI * 0.5
+        } else {
+            2.0 * (1.0 - tolerance / radius).acos()
+        };
+        let num_steps = (angle.abs() / max_step).ceil().max(1.0) as u32;
+        for i in 1..num_steps {
+            let p = center + rotate(angle * i as f32 / num_steps as f32);
+            self.output.line_to(p);
+        }
+        self.output.line_to(to);
+    }
+}
+
+// Splits the path into closed polygons and open lines, without duplicated points.
+fn collect_sub_paths<Iter>(path: Iter) -> (Vec<Vec<Point>>, Vec<Vec<Point>>)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let mut polygons = Vec::new();
+    let mut lines = Vec::new();
+    let mut current: Vec<Point> = Vec::new();
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                if !current.is_empty() {
+                    lines.push(current.clone());
+                }
+                current.clear();
+                current.push(to);
+            }
+            FlattenedEvent::LineTo(to) => {
+                if current.last() != Some(&to) {
+                    current.push(to);
+                }
+            }
+            FlattenedEvent::Close => {
+                while current.len() > 1 && current.last() == current.first() {
+                    current.pop();
+                }
+                if current.len() >= 3 {
+                    polygons.push(current.clone());
+                } else if !current.is_empty() {
+                    lines.push(current.clone());
+                }
+                current.clear();
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    return (polygons, lines);
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+#[cfg(test)]
+use core::PathEvent;
+
+#[cfg(test)]
+fn outline(path: &Path, options: &StrokeOutlineOptions) -> Vec<PathEvent> {
+    let mut builder = Path::builder();
+    stroke_to_path(path.path_iter().flattened(0.1), options, &mut builder);
+    return builder.build().iter().collect();
+}
+
+#[cfg(test)]
+fn count_curves(events: &[PathEvent]) -> usize {
+    events.iter().filter(|evt| match **evt { PathEvent::CubicTo(..) => true, _ => false }).count()
+}
+
+#[test]
+fn test_stroke_to_path_line() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    let path = builder.build();
+
+    let options = StrokeOutlineOptions::default().with_line_width(2.0);
+    assert_eq!(
+        outline(&path, &options),
+        vec![
+            PathEvent::MoveTo(point(0.0, 1.0)),
+            PathEvent::LineTo(point(0.0, -1.0)),
+            PathEvent::LineTo(point(10.0, -1.0)),
+            PathEvent::LineTo(point(10.0, 1.0)),
+            PathEvent::Close,
+        ]
+    );
+
+    // Each round cap is made of two quarters of circle.
+    let curves = outline(&path, &options.with_line_cap(LineCap::Round).with_round_curves());
+    assert_eq!(curves.len(), 7);
+    assert_eq!(count_curves(&curves), 4);
+    let k = 4.0 / 3.0 * (PI / 8.0).tan();
+    match curves[1] {
+        PathEvent::CubicTo(ctrl1, ctrl2, to) => {
+            assert!((ctrl1 - point(-k, 1.0)).length() < 0.0001);
+            assert!((ctrl2 - point(-1.0, k)).length() < 0.0001);
+            assert!((to - point(-1.0, 0.0)).length() < 0.0001);
+        }
+        _ => { panic!("{:?}", curves[1]); }
+    }
+    match curves[2] {
+        PathEvent::CubicTo(ctrl1, ctrl2, to) => {
+            assert!((ctrl1 - point(-1.0, -k)).length() < 0.0001);
+            assert!((ctrl2 - point(-k, -1.0)).length() < 0.0001);
+            assert_eq!(to, point(0.0, -1.0));
+        }
+        _ => { panic!("{:?}", curves[2]); }
+    }
+
+    // The same caps as line segments.
+    let lines = outline(&path, &options.with_line_cap(LineCap::Round).with_tolerance(0.01));
+    assert!(lines.len() > 7);
+    assert_eq!(count_curves(&lines), 0);
+}
+
+#[test]
+fn test_stroke_to_path_polygon() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    // Only the outer joins are round.
+    let options = StrokeOutlineOptions::default()
+        .with_line_width(2.0)
+        .with_line_join(LineJoin::Round)
+        .with_round_curves();
+    let events = outline(&path, &options);
+    assert_eq!(count_curves(&events), 4);
+    assert_eq!(events.iter().filter(|evt| **evt == PathEvent::Close).count(), 2);
+
+    let options = StrokeOutlineOptions::default().with_line_width(2.0);
+    let events = outline(&path, &options);
+    assert_eq!(events[0], PathEvent::MoveTo(point(-1.0, 0.0)));
+    assert_eq!(events[1], PathEvent::LineTo(point(-1.0, -1.0)));
+    assert_eq!(events[2], PathEvent::LineTo(point(0.0, -1.0)));
+}
@@ -0,0 +1,235 @@
+//! Placing glyphs along a path.
+//!
+//! Given the advance of each glyph of a run of text, [layout_on_path](fn.layout_on_path.html)
+//! computes the transform to apply to each glyph so that the text follows a path, for example
+//! to render labels along roads on a map.
+//!
+//! Glyphs are expected to be defined with their origin on the baseline, the x axis
+//! pointing in the direction of the text. Each glyph is rotated to follow the direction
+//! of the path at the middle of its advance, which looks better than using the direction at
+//! the origin of the glyph.
+
+use core::math::*;
+use measure::PathMeasure;
+
+/// What to do with the glyphs that don't fit on the path.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TextOverflow {
+    /// Glyphs that go past the end of the path are not placed.
+    Hide,
+    /// Glyphs that go past the end of the path continue in the direction of the end of
+    /// the path.
+    Extrapolate,
+    /// The advances are uniformly scaled down so that the text fits on the path.
+    Compress,
+}
+
+/// Where to start the text along the path.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TextAlignment {
+    /// The text starts at the provided distance from the start of the path.
+    Start(f32),
+    /// The text is centered on the path.
+    Center,
+    /// The text ends at the end of the path.
+    End,
+}
+
+/// Parameters of the layout.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextOnPathOptions {
+    pub alignment: TextAlignment,
+    pub overflow: TextOverflow,
+    /// Offset applied to the glyphs in the direction of the normal of the path, for example
+    /// to place the text above the path rather than on it.
+    pub baseline_offset: f32,
+
+    // To be able to add fields without making it a breaking change, add an empty private field
+    // which makes it impossible to create a TextOnPathOptions without calling the constructor.
+    _private: (),
+}
+
+impl TextOnPathOptions {
+    pub fn default() -> Self {
+        TextOnPathOptions {
+            alignment: TextAlignment::Start(0.0),
+            overflow: TextOverflow::Hide,
+            baseline_offset: 0.0,
+            _private: (),
+        }
+    }
+
+    pub fn with_alignment(mut self, alignment: TextAlignment) -> Self {
+        self.alignment = alignment;
+        return self;
+    }
+
+    pub fn with_overflow(mut self, overflow: TextOverflow) -> Self {
+        self.overflow = overflow;
+        return self;
+    }
+
+    pub fn with_baseline_offset(mut self, offset: f32) -> Self {
+        self.baseline_offset = offset;
+        return self;
+    }
+}
+
+/// The placement of a glyph along the path.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlyphPlacement {
+    /// Index of the glyph in the advances slice.
+    pub glyph: usize,
+    /// Position of the origin of the glyph.
+    pub position: Point,
+    /// Rotation of the glyph.
+    pub angle: Radians<f32>,
+    /// Transform from glyph space to path space (rotation and translation).
+    pub transform: Transform2d,
+}
+
+/// Computes the placement of a sequence of glyphs along a path.
+///
+/// `advances` contains the advance width of each glyph, in path space units.
+/// Glyphs that are hidden because of the overflow policy are not part of the result.
+pub fn layout_on_path(
+    measure: &PathMeasure,
+    advances: &[f32],
+    options: &TextOnPathOptions,
+) -> Vec<GlyphPlacement> {
+    let mut output = Vec::with_capacity(advances.len());
+    if measure.is_empty() {
+        return output;
+    }
+
+    let path_length = measure.length();
+    let mut text_length: f32 = advances.iter().sum();
+
+    let mut scale = 1.0;
+    if options.overflow == TextOverflow::Compress && text_length > path_length {
+        scale = path_length / text_length;
+        text_length = path_length;
+    }
+
+    let mut distance = match options.alignment {
+        TextAlignment::Start(offset) => offset,
+        TextAlignment::Center => (path_length - text_length) * 0.5,
+        TextAlignment::End => path_length - text_length,
+    };
+
+    for (idx, advance) in advances.iter().enumerate() {
+        let advance = advance * scale;
+        let middle = distance + advance * 0.5;
+        distance += advance;
+
+        let overflows = middle < 0.0 || middle > path_length;
+        if overflows && options.overflow == TextOverflow::Hide {
+            continue;
+        }
+
+        // Sample the path at the middle of the glyph and extrapolate along the tangent
+        // if we are past one of the ends of the path.
+        let sample = measure.sample(middle).unwrap();
+        let center = sample.position + sample.tangent * (middle - sample.distance);
+        let normal = vec2(sample.tangent.y, -sample.tangent.x);
+        let position = center - sample.tangent * (advance * 0.5) + normal * options.baseline_offset;
+
+        // The tangent is normalized so it directly gives the cosine and sine of the angle.
+        let (cos, sin) = (sample.tangent.x, sample.tangent.y);
+        let angle = rad(sin.atan2(cos));
+        let transform = Transform2d::row_major(
+            cos, sin,
+            -sin, cos,
+            position.x, position.y,
+        );
+
+        output.push(GlyphPlacement {
+            glyph: idx,
+            position: position,
+            angle: angle,
+            transform: transform,
+        });
+    }
+
+    return output;
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[cfg(test)]
+fn l_shaped_path() -> PathMeasure {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    let path = builder.build();
+
+    return PathMeasure::new(path.path_iter().flattened(0.1));
+}
+
+#[cfg(test)]
+fn assert_almost_eq(a: Point, b: Point) {
+    if (a - b).square_length() > 0.0001 {
+        panic!("expected {:?} and {:?} to be equal", a, b);
+    }
+}
+
+#[test]
+fn test_text_on_path_hide() {
+    let measure = l_shaped_path();
+    let advances = [4.0, 4.0, 4.0, 4.0, 4.0, 4.0];
+    let glyphs = layout_on_path(&measure, &advances, &TextOnPathOptions::default());
+
+    // The last glyph's middle is at 22, past the end of the path.
+    assert_eq!(glyphs.len(), 5);
+    assert_almost_eq(glyphs[0].position, point(0.0, 0.0));
+    assert_almost_eq(glyphs[1].position, point(4.0, 0.0));
+    // The fourth glyph is centered at (10, 4), on the vertical part of the path.
+    assert_almost_eq(glyphs[3].position, point(10.0, 2.0));
+    assert_almost_eq(
+        glyphs[3].transform.transform_point(&point(2.0, 0.0)),
+        point(10.0, 4.0)
+    );
+}
+
+#[test]
+fn test_text_on_path_extrapolate() {
+    let measure = l_shaped_path();
+    let advances = [4.0, 4.0, 4.0, 4.0, 4.0, 4.0];
+    let options = TextOnPathOptions::default()
+        .with_overflow(TextOverflow::Extrapolate);
+    let glyphs = layout_on_path(&measure, &advances, &options);
+
+    assert_eq!(glyphs.len(), 6);
+    assert_almost_eq(glyphs[5].position, point(10.0, 10.0));
+}
+
+#[test]
+fn test_text_on_path_compress() {
+    let measure = l_shaped_path();
+    let advances = [10.0, 10.0, 10.0, 10.0];
+    let options = TextOnPathOptions::default()
+        .with_overflow(TextOverflow::Compress);
+    let glyphs = layout_on_path(&measure, &advances, &options);
+
+    assert_eq!(glyphs.len(), 4);
+    assert_almost_eq(glyphs[1].position, point(5.0, 0.0));
+    assert_almost_eq(glyphs[3].position, point(10.0, 5.0));
+}
+
+#[test]
+fn test_text_on_path_center() {
+    let measure = l_shaped_path();
+    let advances = [2.0, 2.0];
+    let options = TextOnPathOptions::default()
+        .with_alignment(TextAlignment::Center);
+    let glyphs = layout_on_path(&measure, &advances, &options);
+
+    assert_eq!(glyphs.len(), 2);
+    assert_almost_eq(glyphs[0].position, point(8.0, 0.0));
+}
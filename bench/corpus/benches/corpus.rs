@@ -0,0 +1,106 @@
+#[macro_use]
+extern crate criterion;
+extern crate lyon;
+extern crate lyon_corpus_bench;
+
+use lyon::math::Point;
+use lyon::path_iterator::PathIterator;
+use lyon::tessellation::geometry_builder::{simple_builder, VertexBuffers, BuffersBuilder, VertexConstructor};
+use lyon::tessellation::path_fill::{FillEvents, FillTessellator, FillOptions};
+use lyon::tessellation::path_stroke::{StrokeTessellator, StrokeOptions};
+use lyon::tessellation::StrokeVertex;
+use lyon_corpus_bench::{CORPUS, parse_path};
+
+use criterion::Criterion;
+
+const TOLERANCE: f32 = 0.05;
+
+// The stroke normals are relative to a width of 1.0.
+struct StrokePositions {
+    line_width: f32,
+}
+
+impl VertexConstructor<StrokeVertex, Point> for StrokePositions {
+    fn new_vertex(&mut self, vertex: StrokeVertex) -> Point {
+        vertex.position + vertex.normal * self.line_width
+    }
+}
+
+fn parsing(c: &mut Criterion) {
+    for &(name, text) in CORPUS {
+        c.bench_function(&format!("parse/{}", name), move |b| {
+            b.iter(|| parse_path(text))
+        });
+    }
+}
+
+fn flattening(c: &mut Criterion) {
+    for &(name, text) in CORPUS {
+        let path = parse_path(text);
+        c.bench_function(&format!("flatten/{}", name), move |b| {
+            b.iter(|| path.path_iter().flattened(TOLERANCE).count())
+        });
+    }
+}
+
+// Only the tessellation of pre-built events.
+fn fill_prebuilt_events(c: &mut Criterion) {
+    for &(name, text) in CORPUS {
+        let path = parse_path(text);
+        let events = FillEvents::from_iter(path.path_iter().flattened(TOLERANCE));
+        let mut tess = FillTessellator::new();
+        let options = FillOptions::default().with_tolerance(TOLERANCE);
+        let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+        c.bench_function(&format!("fill_prebuilt_events/{}", name), move |b| {
+            b.iter(|| {
+                buffers.vertices.clear();
+                buffers.indices.clear();
+                tess.tessellate_events(&events, &options, &mut simple_builder(&mut buffers)).unwrap();
+            })
+        });
+    }
+}
+
+// Building the events from the flattened path and tessellating them.
+fn fill(c: &mut Criterion) {
+    for &(name, text) in CORPUS {
+        let path = parse_path(text);
+        let mut tess = FillTessellator::new();
+        let options = FillOptions::default().with_tolerance(TOLERANCE);
+        let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+        c.bench_function(&format!("fill/{}", name), move |b| {
+            b.iter(|| {
+                buffers.vertices.clear();
+                buffers.indices.clear();
+                tess.tessellate_path(
+                    path.path_iter().flattened(TOLERANCE),
+                    &options,
+                    &mut simple_builder(&mut buffers)
+                ).unwrap();
+            })
+        });
+    }
+}
+
+fn stroke(c: &mut Criterion) {
+    for &(name, text) in CORPUS {
+        let path = parse_path(text);
+        let mut tess = StrokeTessellator::new();
+        let options = StrokeOptions::default().with_tolerance(TOLERANCE);
+        let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+        c.bench_function(&format!("stroke/{}", name), move |b| {
+            b.iter(|| {
+                buffers.vertices.clear();
+                buffers.indices.clear();
+                tess.tessellate(
+                    path.path_iter().flattened(TOLERANCE),
+                    &options,
+                    &mut BuffersBuilder::new(&mut buffers, StrokePositions { line_width: options.line_width })
+                ).unwrap();
+            })
+        });
+    }
+}
+
+criterion_group!(benches, parsing, flattening, fill, fill_prebuilt_events, stroke);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+//! A small corpus of representative paths for benchmarking.
+//!
+//! The paths are stored as SVG path data in the `paths` directory:
+//!
+//! - glyphs: a few TrueType-like outlines made of quadratic curves, with holes.
+//! - synthetic map: a large generated polygon with thousands of jittered line segments,
+//!   islands and a lake. It mimics the shape of map data but isn't real map data.
+//! - icons: small icons made of cubic curves.
+//!
+//! Run the benchmarks with `cargo bench` from this directory.
+
+extern crate lyon;
+
+use lyon::path::Path;
+use lyon::path_builder::*;
+use lyon::svg::parser::path::PathTokenizer;
+
+/// The name and SVG path data of each path of the corpus.
+pub const CORPUS: &'static [(&'static str, &'static str)] = &[
+    ("glyph_g", include_str!("../paths/glyph_g.path")),
+    ("glyph_ampersand", include_str!("../paths/glyph_ampersand.path")),
+    ("synthetic_map", include_str!("../paths/synthetic_map.path")),
+    ("icon_gear", include_str!("../paths/icon_gear.path")),
+    ("icon_heart", include_str!("../paths/icon_heart.path")),
+];
+
+/// Parses SVG path data.
+///
+/// Panics if the path data is invalid, which is not expected for the corpus.
+pub fn parse_path(text: &str) -> Path {
+    let mut builder = SvgPathBuilder::new(Path::builder());
+
+    for item in PathTokenizer::new(text) {
+        builder.svg_event(item.unwrap());
+    }
+
+    return builder.build();
+}
+
+#[test]
+fn test_corpus_parses() {
+    use lyon::path_iterator::PathIterator;
+
+    for &(name, text) in CORPUS {
+        let path = parse_path(text);
+        assert!(path.path_iter().flattened(0.1).count() > 2, "{} is empty", name);
+    }
+}
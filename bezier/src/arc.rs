@@ -0,0 +1,187 @@
+use std::f32::consts::PI;
+
+use euclid::Radians;
+use {Point, Vec2, Rect, rect, vec2};
+
+/// A circular arc.
+///
+/// Unlike bézier curves, the curves at a constant distance of a circular arc (its offsets)
+/// are circular arcs with the same center, which the stroke tessellator takes advantage of.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Arc {
+    pub center: Point,
+    pub radius: f32,
+    pub start_angle: Radians<f32>,
+    /// Positive for counter-clockwise arcs (with the y axis pointing up).
+    pub sweep_angle: Radians<f32>,
+}
+
+impl Arc {
+    /// Sample the arc at t (expecting t between 0 and 1).
+    pub fn sample(&self, t: f32) -> Point {
+        self.center + self.sample_direction(t) * self.radius
+    }
+
+    /// The unit vector from the center to the point of the arc at t.
+    pub fn sample_direction(&self, t: f32) -> Vec2 {
+        let angle = self.start_angle.get() + self.sweep_angle.get() * t;
+        vec2(angle.cos(), angle.sin())
+    }
+
+    /// The unit tangent of the arc at t, in the direction of the arc.
+    pub fn sample_tangent(&self, t: f32) -> Vec2 {
+        let d = self.sample_direction(t);
+        let tangent = vec2(-d.y, d.x);
+        if self.sweep_angle.get() < 0.0 { -tangent } else { tangent }
+    }
+
+    pub fn from(&self) -> Point { self.sample(0.0) }
+
+    pub fn to(&self) -> Point { self.sample(1.0) }
+
+    /// Returns an inverted version of this arc where the beginning and the end points are
+    /// swapped.
+    #[must_use]
+    pub fn flip(&self) -> Self {
+        Arc {
+            center: self.center,
+            radius: self.radius,
+            start_angle: Radians::new(self.start_angle.get() + self.sweep_angle.get()),
+            sweep_angle: Radians::new(-self.sweep_angle.get()),
+        }
+    }
+
+    /// The length of the arc.
+    pub fn length(&self) -> f32 { self.radius * self.sweep_angle.get().abs() }
+
+    /// The smallest rectangle containing the arc.
+    pub fn bounding_rect(&self) -> Rect {
+        let from = self.from();
+        let to = self.to();
+        let mut min = point_min(from, to);
+        let mut max = point_max(from, to);
+
+        // The arc reaches its extrema where it crosses the axes of its circle.
+        let start = self.start_angle.get();
+        let end = start + self.sweep_angle.get();
+        let quarter = PI * 0.5;
+        let mut k = (start.min(end) / quarter).ceil();
+        let mut count = 0;
+        while k * quarter <= start.max(end) && count < 4 {
+            let angle = k * quarter;
+            let p = self.center + vec2(angle.cos(), angle.sin()) * self.radius;
+            min = point_min(min, p);
+            max = point_max(max, p);
+            k += 1.0;
+            count += 1;
+        }
+
+        return rect(min.x, min.y, max.x - min.x, max.y - min.y);
+    }
+
+    /// Returns the parameter of the point of the arc that is the closest to a position.
+    pub fn closest_point_t(&self, position: Point) -> f32 {
+        let sweep = self.sweep_angle.get();
+        let v = position - self.center;
+        if sweep == 0.0 || (v.x == 0.0 && v.y == 0.0) {
+            return 0.0;
+        }
+
+        // The angle from the start of the arc to the position, in the direction of the arc.
+        let mut offset = (v.y.atan2(v.x) - self.start_angle.get()) * sweep.signum();
+        offset -= (offset / (2.0 * PI)).floor() * 2.0 * PI;
+        let t = offset / sweep.abs();
+        if t <= 1.0 {
+            return t;
+        }
+
+        // Outside of the arc, one of the endpoints is the closest.
+        if (self.from() - position).square_length() <= (self.to() - position).square_length() {
+            return 0.0;
+        }
+
+        return 1.0;
+    }
+
+    /// The number of line segments needed to approximate the arc within a tolerance.
+    pub fn flattening_step_count(&self, tolerance: f32) -> u32 {
+        arc_step_count(self.radius, self.sweep_angle.get(), tolerance)
+    }
+
+    /// Iterates through the arc invoking a callback at each point of its flattened
+    /// approximation, excluding the first point.
+    pub fn flattened_for_each<F: FnMut(Point)>(&self, tolerance: f32, call_back: &mut F) {
+        let n = self.flattening_step_count(tolerance);
+        for i in 1..n {
+            call_back(self.sample(i as f32 / n as f32));
+        }
+        call_back(self.to());
+    }
+}
+
+fn point_min(a: Point, b: Point) -> Point { Point::new(a.x.min(b.x), a.y.min(b.y)) }
+
+fn point_max(a: Point, b: Point) -> Point { Point::new(a.x.max(b.x), a.y.max(b.y)) }
+
+/// The number of line segments needed to approximate an arc of a given radius within a
+/// tolerance.
+///
+/// The distance between a chord and its arc is `radius * (1 - cos(angle / 2))`.
+pub fn arc_step_count(radius: f32, sweep_angle: f32, tolerance: f32) -> u32 {
+    let radius = radius.abs();
+    if radius <= tolerance {
+        // The chords can't be further than the radius.
+        return (sweep_angle.abs() / PI).ceil().max(1.0) as u32;
+    }
+
+    let max_step = 2.0 * (1.0 - tolerance / radius).acos();
+    return (sweep_angle.abs() / max_step).ceil().max(1.0) as u32;
+}
+
+#[test]
+fn test_arc_flattening() {
+    let arc = Arc {
+        center: Point::new(1.0, 2.0),
+        radius: 10.0,
+        start_angle: Radians::new(0.0),
+        sweep_angle: Radians::new(PI),
+    };
+    assert!((arc.to() - Point::new(-9.0, 2.0)).length() < 0.0001);
+    assert!((arc.sample_tangent(0.0) - vec2(0.0, 1.0)).length() < 0.0001);
+    assert!((arc.flip().sample_tangent(0.0) - vec2(0.0, 1.0)).length() < 0.0001);
+
+    let tolerance = 0.01;
+    let mut previous = arc.from();
+    let mut count = 0;
+    arc.flattened_for_each(tolerance, &mut |p| {
+        // The middle of each chord is within the tolerance of the arc.
+        let middle = previous.lerp(p, 0.5);
+        assert!(arc.radius - (middle - arc.center).length() <= tolerance * 1.01);
+        previous = p;
+        count += 1;
+    });
+    assert_eq!(count, arc.flattening_step_count(tolerance));
+    assert_eq!(previous, arc.to());
+
+    // A bigger radius needs more segments.
+    assert!(arc_step_count(20.0, PI, tolerance) > arc_step_count(10.0, PI, tolerance));
+}
+
+#[test]
+fn test_arc_queries() {
+    let arc = Arc {
+        center: Point::new(0.0, 0.0),
+        radius: 10.0,
+        start_angle: Radians::new(-PI * 0.25),
+        sweep_angle: Radians::new(PI),
+    };
+    let r = arc.bounding_rect();
+    assert!((r.min_x() - arc.to().x).abs() < 0.0001);
+    assert!((r.max_x() - 10.0).abs() < 0.0001);
+    assert!((r.min_y() - arc.from().y).abs() < 0.0001);
+    assert!((r.max_y() - 10.0).abs() < 0.0001);
+
+    assert!((arc.closest_point_t(Point::new(0.0, 20.0)) - 0.75).abs() < 0.0001);
+    assert_eq!(arc.closest_point_t(Point::new(1.0, -20.0)), 0.0);
+    assert!((arc.flip().closest_point_t(Point::new(0.0, 20.0)) - 0.25).abs() < 0.0001);
+}
@@ -0,0 +1,54 @@
+use Point;
+
+// Searches the parameter of the point of a curve that is the closest to a position.
+//
+// The curve is sampled uniformly, then the parameter is refined around each local minimum
+// of the distance with a ternary search, until the step along the curve is below the
+// tolerance. `length_bound` is an upper bound of the length of the curve (for example the
+// length of its control polygon).
+pub fn closest_t<F: Fn(f32) -> Point>(
+    sample: F,
+    position: Point,
+    num_samples: u32,
+    length_bound: f32,
+    tolerance: f32,
+) -> f32 {
+    let square_distance = |t: f32| (sample(t) - position).square_length();
+
+    let n = num_samples.max(2);
+    let distances: Vec<f32> = (0..(n + 1)).map(|i| square_distance(i as f32 / n as f32)).collect();
+
+    let mut best_t = 0.0;
+    let mut best = distances[0];
+    for i in 0..(n + 1) as usize {
+        let is_local_min = (i == 0 || distances[i] <= distances[i - 1]) &&
+            (i == n as usize || distances[i] <= distances[i + 1]);
+        if !is_local_min {
+            continue;
+        }
+
+        let mut a = (i as f32 - 1.0).max(0.0) / n as f32;
+        let mut b = (i as f32 + 1.0).min(n as f32) / n as f32;
+        let mut iterations = 0;
+        while (b - a) * length_bound > tolerance && iterations < 64 {
+            let t1 = a + (b - a) / 3.0;
+            let t2 = b - (b - a) / 3.0;
+            if square_distance(t1) < square_distance(t2) {
+                b = t2;
+            } else {
+                a = t1;
+            }
+            iterations += 1;
+        }
+
+        for &t in &[a, b, (a + b) * 0.5] {
+            let d = square_distance(t);
+            if d < best {
+                best = d;
+                best_t = t;
+            }
+        }
+    }
+
+    return best_t;
+}
@@ -1,6 +1,12 @@
-use {Point, Rect, rect, Transform2D};
+use {Point, Vec2, Rect, rect, Transform2D};
 use up_to_two::UpToTwo;
+use closest_point::closest_t;
 use flatten_cubic::{flatten_cubic_bezier, find_cubic_bezier_inflection_points};
+use flatten_cubic::{no_inflection_flattening_step, no_inflection_flattening_error};
+use flatten_uniform::flatten_cubic_uniform;
+use flatten_curvature::flatten_cubic_by_angle;
+use rect_clip::cubic_rect_intervals;
+use std::ops::Range;
 pub use flatten_cubic::CubicFlatteningIter;
 pub use cubic_to_quadratic::cubic_to_quadratic;
 
@@ -31,6 +37,34 @@ impl CubicBezierSegment {
             self.to.to_vector() * t3;
     }
 
+    /// Sample the derivative of the curve at t (expecting t between 0 and 1).
+    pub fn derivative(&self, t: f32) -> Vec2 {
+        let one_t = 1.0 - t;
+        return ((self.ctrl1 - self.from) * one_t * one_t +
+            (self.ctrl2 - self.ctrl1) * 2.0 * one_t * t +
+            (self.to - self.ctrl2) * t * t) * 3.0;
+    }
+
+    /// Sample the second derivative of the curve at t (expecting t between 0 and 1).
+    pub fn second_derivative(&self, t: f32) -> Vec2 {
+        let a = self.ctrl2 - self.ctrl1 * 2.0 + self.from.to_vector();
+        let b = self.to - self.ctrl2 * 2.0 + self.ctrl1.to_vector();
+        return (a * (1.0 - t) + b * t) * 6.0;
+    }
+
+    /// Signed curvature of the curve at t (expecting t between 0 and 1), positive when the
+    /// curve turns counter-clockwise (with the y axis pointing up).
+    ///
+    /// Returns zero where the derivative is zero.
+    pub fn curvature(&self, t: f32) -> f32 {
+        let d1 = self.derivative(t);
+        let length = d1.length();
+        if length == 0.0 {
+            return 0.0;
+        }
+        return d1.cross(self.second_derivative(t)) / (length * length * length);
+    }
+
     /// Split this curve into two sub-curves.
     pub fn split(&self, t: f32) -> (CubicBezierSegment, CubicBezierSegment) {
         let ctrl1a = self.from + (self.ctrl1 - self.from) * t;
@@ -104,6 +138,62 @@ impl CubicBezierSegment {
         flatten_cubic_bezier(*self, tolerance, call_back);
     }
 
+    /// Find the interval of the begining of the curve that can be approximated with a
+    /// line segment, using the same criterion as `flattened_for_each` and `flattening_iter`.
+    ///
+    /// The curve is expected not to have inflection points (see `find_inflection_points`),
+    /// the flattening splits the curve at its inflection points and applies this step to each
+    /// part. Returns 1.0 if the whole curve is within the tolerance of its chord.
+    pub fn flattening_step(&self, tolerance: f32) -> f32 {
+        no_inflection_flattening_step(self, tolerance)
+    }
+
+    /// Returns the estimate of the distance between the curve and the line segment from its
+    /// start to the point at `t` that the flattening uses, that is the tolerance for which
+    /// `flattening_step` would return `t`.
+    ///
+    /// Like `flattening_step`, this is only meaningful for curves without inflection points.
+    pub fn flattening_error(&self, t: f32) -> f32 {
+        no_inflection_flattening_error(self, t)
+    }
+
+    /// Returns true if the flattening would approximate the whole curve with a single line
+    /// segment for this tolerance.
+    ///
+    /// Custom flatteners (for example subdividing curves on the GPU) can use this to stop
+    /// subdividing where lyon would, and produce the same points at the boundaries.
+    pub fn is_flat_enough(&self, tolerance: f32) -> bool {
+        if self.find_inflection_points().iter().any(|&t| t > 0.0) {
+            return false;
+        }
+
+        return self.flattening_step(tolerance) >= 1.0;
+    }
+
+    /// Iterates through the curve invoking a callback at each point, using uniformly
+    /// distributed segments.
+    ///
+    /// This generates more points than `flattened_for_each` but can be faster, in particular
+    /// with the `simd` feature enabled.
+    pub fn uniform_flattened_for_each<F: FnMut(Point)>(&self, tolerance: f32, call_back: &mut F) {
+        flatten_cubic_uniform(self, tolerance, call_back);
+    }
+
+    /// Iterates through the curve invoking a callback at each point, with segments along
+    /// which the direction of the curve changes by at most `max_angle` (in radians).
+    ///
+    /// See [flatten_cubic_by_angle](fn.flatten_cubic_by_angle.html).
+    pub fn angle_flattened_for_each<F: FnMut(Point)>(&self, max_angle: f32, call_back: &mut F) {
+        flatten_cubic_by_angle(self, max_angle, call_back);
+    }
+
+    /// Returns the intervals of the parameter over which the curve is inside of a rectangle.
+    ///
+    /// See [cubic_rect_intervals](fn.cubic_rect_intervals.html).
+    pub fn inside_rect_intervals(&self, rect: &Rect) -> Vec<Range<f32>> {
+        cubic_rect_intervals(self, rect)
+    }
+
     /// Compute the length of the segment using a flattened approximation.
     pub fn compute_length(&self, tolerance: f32) -> f32 {
         let mut start = self.from;
@@ -115,6 +205,20 @@ impl CubicBezierSegment {
         return len;
     }
 
+    /// Returns the parameter of the point of the curve that is the closest to a position,
+    /// within `tolerance`.
+    pub fn closest_point_t(&self, position: Point, tolerance: f32) -> f32 {
+        let length_bound = (self.ctrl1 - self.from).length() +
+            (self.ctrl2 - self.ctrl1).length() +
+            (self.to - self.ctrl2).length();
+        closest_t(|t| self.sample(t), position, 24, length_bound, tolerance)
+    }
+
+    /// Returns the point of the curve that is the closest to a position, within `tolerance`.
+    pub fn closest_point(&self, position: Point, tolerance: f32) -> Point {
+        self.sample(self.closest_point_t(position, tolerance))
+    }
+
     pub fn find_inflection_points(&self) -> UpToTwo<f32> {
         find_cubic_bezier_inflection_points(self)
     }
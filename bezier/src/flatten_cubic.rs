@@ -155,7 +155,9 @@ fn flatten_cubic_no_inflection<F: FnMut(Point)>(
     call_back(end);
 }
 
-fn no_inflection_flattening_step(bezier: &CubicBezierSegment, tolerance: f32) -> f32 {
+// The parameter up to which the curve is within the tolerance of the chord from its start,
+// assuming that it doesn't have inflection points. See CubicBezierSegment::flattening_step.
+pub fn no_inflection_flattening_step(bezier: &CubicBezierSegment, tolerance: f32) -> f32 {
     let v1 = bezier.ctrl1 - bezier.from;
     let v2 = bezier.ctrl2 - bezier.from;
 
@@ -180,6 +182,21 @@ fn no_inflection_flattening_step(bezier: &CubicBezierSegment, tolerance: f32) ->
     return t;
 }
 
+// The inverse of no_inflection_flattening_step: the estimated distance between the curve and
+// the chord from its start to the point at t.
+pub fn no_inflection_flattening_error(bezier: &CubicBezierSegment, t: f32) -> f32 {
+    let v1 = bezier.ctrl1 - bezier.from;
+    let v2 = bezier.ctrl2 - bezier.from;
+
+    let v1_cross_v2 = v2.x * v1.y - v2.y * v1.x;
+    let h = v1.x.hypot(v1.y);
+    if v1_cross_v2 * h == 0.0 {
+        return 0.0;
+    }
+
+    return 0.75 * (v1_cross_v2 / h).abs() * t * t;
+}
+
 // Find the inflection points of a cubic bezier curve.
 pub fn find_cubic_bezier_inflection_points(bezier: &CubicBezierSegment) -> UpToTwo<f32> {
     // Find inflection points.
@@ -341,3 +358,51 @@ fn test_issue_19() {
 
     assert!(iter_points.len() > 1);
 }
+
+#[test]
+fn test_flattening_step_and_error() {
+    let curve = CubicBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl1: Point::new(10.0, 0.0),
+        ctrl2: Point::new(20.0, 10.0),
+        to: Point::new(20.0, 20.0),
+    };
+    assert!(curve.find_inflection_points().is_empty());
+
+    // The error of a step is the tolerance it was computed for.
+    for &tolerance in &[0.01, 0.1, 0.5] {
+        let t = curve.flattening_step(tolerance);
+        assert!(t < 1.0);
+        assert!((curve.flattening_error(t) - tolerance).abs() < tolerance * 0.001);
+        assert!(!curve.is_flat_enough(tolerance));
+    }
+    assert!(curve.is_flat_enough(100.0));
+
+    // The flattened curve has one segment per step.
+    let mut num_points = 0;
+    let mut remaining = curve;
+    while !remaining.is_flat_enough(0.1) {
+        remaining = remaining.after_split(remaining.flattening_step(0.1));
+        num_points += 1;
+    }
+    assert_eq!(num_points + 1, curve.flattening_iter(0.1).count());
+
+    // Straight lines are flat.
+    let line = CubicBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl1: Point::new(1.0, 0.0),
+        ctrl2: Point::new(2.0, 0.0),
+        to: Point::new(3.0, 0.0),
+    };
+    assert!(line.is_flat_enough(0.0001));
+    assert_eq!(line.flattening_error(1.0), 0.0);
+
+    // Curves with an inflection point are never flat enough as a whole.
+    let s_curve = CubicBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl1: Point::new(1.0, 1.0),
+        ctrl2: Point::new(2.0, -1.0),
+        to: Point::new(3.0, 0.0),
+    };
+    assert!(!s_curve.is_flat_enough(100.0));
+}
@@ -0,0 +1,187 @@
+//! Flattening driven by the curvature.
+//!
+//! The flattening algorithms used by `flattened_for_each` keep the distance between the curve
+//! and its approximation under a tolerance threshold, which places points along gentle curves
+//! of large shapes and few points in tight turns of small ones. For plotters and other
+//! machines the angle between consecutive segments matters more than the distance: the
+//! algorithm implemented here instead limits how much the direction of the curve changes over
+//! each segment. Near-straight sections are approximated with few long segments whatever
+//! their size, and points are concentrated where the curvature is high.
+//!
+//! The direction of the curve turns at a rate of `|B' x B''| / |B'|²` per unit of the curve
+//! parameter. Each step is sized so that the highest rate sampled over the step times the
+//! length of the step is at most the maximum angle.
+
+use {Point, Vec2};
+use {QuadraticBezierSegment, CubicBezierSegment};
+
+/// Smallest step in the parameter space, to bound the number of points near cusps where the
+/// curve turns instantly.
+const MIN_STEP: f32 = 1.0 / 4096.0;
+
+/// Largest step in the parameter space, so that a bend between two straight parts isn't
+/// skipped.
+const MAX_STEP: f32 = 0.25;
+
+/// Flattens a quadratic bézier curve so that the direction changes by at most `max_angle`
+/// (in radians) along each segment.
+///
+/// The callback is invoked for each point after the start of the curve. The last point is
+/// exactly the endpoint of the curve.
+pub fn flatten_quadratic_by_angle<F: FnMut(Point)>(
+    curve: &QuadraticBezierSegment,
+    max_angle: f32,
+    call_back: &mut F,
+) {
+    let second_derivative = curve.second_derivative();
+    flatten_by_angle(
+        &|t| (curve.derivative(t), second_derivative),
+        max_angle,
+        &mut |t| call_back(curve.sample(t)),
+    );
+    call_back(curve.to);
+}
+
+/// Flattens a cubic bézier curve so that the direction changes by at most `max_angle`
+/// (in radians) along each segment.
+///
+/// The callback is invoked for each point after the start of the curve. The last point is
+/// exactly the endpoint of the curve.
+pub fn flatten_cubic_by_angle<F: FnMut(Point)>(
+    curve: &CubicBezierSegment,
+    max_angle: f32,
+    call_back: &mut F,
+) {
+    flatten_by_angle(
+        &|t| (curve.derivative(t), curve.second_derivative(t)),
+        max_angle,
+        &mut |t| call_back(curve.sample(t)),
+    );
+    call_back(curve.to);
+}
+
+// Invokes the callback with the parameters of the points between the endpoints.
+fn flatten_by_angle<D, F>(derivatives: &D, max_angle: f32, call_back: &mut F)
+where
+    D: Fn(f32) -> (Vec2, Vec2),
+    F: FnMut(f32),
+{
+    let turning_rate = |t: f32| {
+        let (d1, d2) = derivatives(t);
+        let square_length = d1.square_length();
+        if square_length == 0.0 {
+            return ::std::f32::INFINITY;
+        }
+        d1.cross(d2).abs() / square_length
+    };
+
+    let step_for_rate = |rate: f32| {
+        // Also catches NaN.
+        if !(rate > 0.0) {
+            return MAX_STEP;
+        }
+        (max_angle / rate).max(MIN_STEP).min(MAX_STEP)
+    };
+
+    let mut t = 0.0;
+    loop {
+        let mut dt = step_for_rate(turning_rate(t));
+        // The curvature can increase within the step, shrink the step until the rates at its
+        // middle and end agree with it.
+        loop {
+            let rate = turning_rate(t)
+                .max(turning_rate((t + dt * 0.5).min(1.0)))
+                .max(turning_rate((t + dt).min(1.0)));
+            let next_dt = step_for_rate(rate);
+            if next_dt >= dt * 0.99 {
+                break;
+            }
+            dt = next_dt;
+        }
+
+        t += dt;
+        if t >= 1.0 - MIN_STEP {
+            return;
+        }
+        call_back(t);
+    }
+}
+
+#[cfg(test)]
+fn max_turn(from: Point, points: &[Point]) -> f32 {
+    let mut max = 0.0;
+    let mut previous = from;
+    let mut previous_direction: Option<Vec2> = None;
+    for &p in points {
+        let direction = (p - previous).normalize();
+        if let Some(d) = previous_direction {
+            let angle = d.cross(direction).atan2(d.dot(direction)).abs();
+            if angle > max {
+                max = angle;
+            }
+        }
+        previous_direction = Some(direction);
+        previous = p;
+    }
+
+    return max;
+}
+
+#[test]
+fn test_flatten_quadratic_by_angle() {
+    let max_angle = 0.1;
+
+    // A tight turn of 90 degrees.
+    let tight = QuadraticBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl: Point::new(10.0, 0.0),
+        to: Point::new(10.0, 10.0),
+    };
+    let mut points = Vec::new();
+    flatten_quadratic_by_angle(&tight, max_angle, &mut |p| points.push(p));
+    assert_eq!(*points.last().unwrap(), tight.to);
+    assert!(points.len() >= 16);
+    assert!(max_turn(tight.from, &points) <= max_angle * 1.5);
+
+    // A long and gentle curve only needs a few segments, even though it would need many of
+    // them with a small distance tolerance.
+    let gentle = QuadraticBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl: Point::new(500.0, 10.0),
+        to: Point::new(1000.0, 0.0),
+    };
+    let mut points = Vec::new();
+    flatten_quadratic_by_angle(&gentle, max_angle, &mut |p| points.push(p));
+    assert!(points.len() <= 4);
+    let mut count = 0;
+    gentle.flattened_for_each(0.01, &mut |_| { count += 1; });
+    assert!(count > points.len());
+}
+
+#[test]
+fn test_flatten_cubic_by_angle() {
+    let max_angle = 0.1;
+
+    // An S-shaped curve.
+    let curve = CubicBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl1: Point::new(10.0, 0.0),
+        ctrl2: Point::new(0.0, 10.0),
+        to: Point::new(10.0, 10.0),
+    };
+    let mut points = Vec::new();
+    flatten_cubic_by_angle(&curve, max_angle, &mut |p| points.push(p));
+    assert_eq!(*points.last().unwrap(), curve.to);
+    assert!(max_turn(curve.from, &points) <= max_angle * 1.5);
+
+    // A straight line.
+    let line = CubicBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl1: Point::new(1.0, 0.0),
+        ctrl2: Point::new(2.0, 0.0),
+        to: Point::new(3.0, 0.0),
+    };
+    let mut points = Vec::new();
+    flatten_cubic_by_angle(&line, max_angle, &mut |p| points.push(p));
+    assert_eq!(points.len(), 4);
+}
@@ -0,0 +1,297 @@
+//! Flattening with uniformly distributed segments.
+//!
+//! The adaptive flattening algorithms used by `flattened_for_each` compute each step from
+//! the previous one which makes them hard to parallelize. The algorithm implemented here
+//! instead computes upfront how many evenly spaced (in the parameter space) segments are
+//! needed to stay within the tolerance threshold, after which all of the points can be
+//! evaluated independently.
+//!
+//! This produces more segments than the adaptive approach for a given tolerance but the
+//! points are cheap to evaluate. With the `simd` feature enabled, the points are evaluated
+//! 4 or 8 at a time using SSE or AVX instructions when the CPU supports them (detected at
+//! runtime).
+
+use {Point, Vec2, vec2};
+use {QuadraticBezierSegment, CubicBezierSegment};
+
+/// Upper bound for the number of segments, to avoid pathological cases with very small
+/// or invalid tolerance thresholds.
+const MAX_SEGMENTS: u32 = 1 << 16;
+
+/// Number of points evaluated per batch.
+const CHUNK_SIZE: usize = 64;
+
+/// Polynomial form of a curve: `a * t³ + b * t² + c * t + d`.
+struct Polynomial {
+    a: Vec2,
+    b: Vec2,
+    c: Vec2,
+    d: Vec2,
+}
+
+impl Polynomial {
+    fn quadratic(curve: &QuadraticBezierSegment) -> Self {
+        let p0 = curve.from.to_vector();
+        let p1 = curve.ctrl.to_vector();
+        let p2 = curve.to.to_vector();
+        Polynomial {
+            a: vec2(0.0, 0.0),
+            b: p0 - p1 * 2.0 + p2,
+            c: (p1 - p0) * 2.0,
+            d: p0,
+        }
+    }
+
+    fn cubic(curve: &CubicBezierSegment) -> Self {
+        let p0 = curve.from.to_vector();
+        let p1 = curve.ctrl1.to_vector();
+        let p2 = curve.ctrl2.to_vector();
+        let p3 = curve.to.to_vector();
+        Polynomial {
+            a: p3 - p0 + (p1 - p2) * 3.0,
+            b: (p0 + p2) * 3.0 - p1 * 6.0,
+            c: (p1 - p0) * 3.0,
+            d: p0,
+        }
+    }
+}
+
+/// Returns the number of segments needed to flatten a quadratic bézier curve uniformly.
+pub fn quadratic_uniform_segment_count(curve: &QuadraticBezierSegment, tolerance: f32) -> u32 {
+    // The distance between the curve and a chord of parameter length h is bounded by
+    // h² * max|B''| / 8, and |B''| = 2 * |from - 2 * ctrl + to|.
+    let dd = (curve.from.to_vector() - curve.ctrl.to_vector() * 2.0 + curve.to.to_vector()).length();
+    return segment_count(dd * 0.25, tolerance);
+}
+
+/// Returns the number of segments needed to flatten a cubic bézier curve uniformly.
+pub fn cubic_uniform_segment_count(curve: &CubicBezierSegment, tolerance: f32) -> u32 {
+    // Same bound as above with |B''| <= 6 * max(|dd1|, |dd2|) (Wang's formula).
+    let from = curve.from.to_vector();
+    let ctrl1 = curve.ctrl1.to_vector();
+    let ctrl2 = curve.ctrl2.to_vector();
+    let to = curve.to.to_vector();
+    let dd1 = (from - ctrl1 * 2.0 + ctrl2).length();
+    let dd2 = (ctrl1 - ctrl2 * 2.0 + to).length();
+    return segment_count(dd1.max(dd2) * 0.75, tolerance);
+}
+
+fn segment_count(error_bound: f32, tolerance: f32) -> u32 {
+    let n = (error_bound / tolerance).sqrt().ceil();
+    // Also catches NaN.
+    if !(n >= 1.0) {
+        return 1;
+    }
+
+    return n.min(MAX_SEGMENTS as f32) as u32;
+}
+
+/// Flattens a quadratic bézier curve with uniformly distributed segments.
+///
+/// The callback is invoked for each point after the start of the curve. The last point is
+/// exactly the endpoint of the curve.
+pub fn flatten_quadratic_uniform<F: FnMut(Point)>(
+    curve: &QuadraticBezierSegment,
+    tolerance: f32,
+    call_back: &mut F,
+) {
+    let n = quadratic_uniform_segment_count(curve, tolerance);
+    evaluate(&Polynomial::quadratic(curve), n, call_back);
+    call_back(curve.to);
+}
+
+/// Flattens a cubic bézier curve with uniformly distributed segments.
+///
+/// The callback is invoked for each point after the start of the curve. The last point is
+/// exactly the endpoint of the curve.
+pub fn flatten_cubic_uniform<F: FnMut(Point)>(
+    curve: &CubicBezierSegment,
+    tolerance: f32,
+    call_back: &mut F,
+) {
+    let n = cubic_uniform_segment_count(curve, tolerance);
+    evaluate(&Polynomial::cubic(curve), n, call_back);
+    call_back(curve.to);
+}
+
+type ChunkFn = unsafe fn(&Polynomial, u32, f32, &mut [f32; CHUNK_SIZE], &mut [f32; CHUNK_SIZE]);
+
+// Evaluates the points at t = i / n for i in 1..n (the last point is not evaluated).
+fn evaluate<F: FnMut(Point)>(poly: &Polynomial, n: u32, call_back: &mut F) {
+    let chunk_fn = select_chunk_fn();
+    let step = 1.0 / n as f32;
+    let mut xs = [0.0; CHUNK_SIZE];
+    let mut ys = [0.0; CHUNK_SIZE];
+    let mut first = 1;
+    while first < n {
+        let count = ::std::cmp::min((n - first) as usize, CHUNK_SIZE);
+        unsafe {
+            chunk_fn(poly, first, step, &mut xs, &mut ys);
+        }
+        for i in 0..count {
+            call_back(Point::new(xs[i], ys[i]));
+        }
+        first += count as u32;
+    }
+}
+
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+fn select_chunk_fn() -> ChunkFn {
+    if is_x86_feature_detected!("avx") {
+        return simd::evaluate_chunk_avx;
+    }
+    if is_x86_feature_detected!("sse") {
+        return simd::evaluate_chunk_sse;
+    }
+
+    return evaluate_chunk_scalar;
+}
+
+#[cfg(not(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64"))))]
+fn select_chunk_fn() -> ChunkFn { evaluate_chunk_scalar }
+
+// Evaluates CHUNK_SIZE points starting at t = first * step.
+unsafe fn evaluate_chunk_scalar(
+    poly: &Polynomial,
+    first: u32,
+    step: f32,
+    xs: &mut [f32; CHUNK_SIZE],
+    ys: &mut [f32; CHUNK_SIZE],
+) {
+    for i in 0..CHUNK_SIZE {
+        let t = (first + i as u32) as f32 * step;
+        xs[i] = ((poly.a.x * t + poly.b.x) * t + poly.c.x) * t + poly.d.x;
+        ys[i] = ((poly.a.y * t + poly.b.y) * t + poly.c.y) * t + poly.d.y;
+    }
+}
+
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+mod simd {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    use super::{Polynomial, CHUNK_SIZE};
+
+    #[target_feature(enable = "sse")]
+    pub unsafe fn evaluate_chunk_sse(
+        poly: &Polynomial,
+        first: u32,
+        step: f32,
+        xs: &mut [f32; CHUNK_SIZE],
+        ys: &mut [f32; CHUNK_SIZE],
+    ) {
+        let ax = _mm_set1_ps(poly.a.x);
+        let bx = _mm_set1_ps(poly.b.x);
+        let cx = _mm_set1_ps(poly.c.x);
+        let dx = _mm_set1_ps(poly.d.x);
+        let ay = _mm_set1_ps(poly.a.y);
+        let by = _mm_set1_ps(poly.b.y);
+        let cy = _mm_set1_ps(poly.c.y);
+        let dy = _mm_set1_ps(poly.d.y);
+        let lanes = _mm_set_ps(3.0, 2.0, 1.0, 0.0);
+        let step = _mm_set1_ps(step);
+
+        let mut i = 0;
+        while i < CHUNK_SIZE {
+            let idx = _mm_add_ps(_mm_set1_ps((first + i as u32) as f32), lanes);
+            let t = _mm_mul_ps(idx, step);
+            let x = _mm_add_ps(_mm_mul_ps(_mm_add_ps(_mm_mul_ps(_mm_add_ps(_mm_mul_ps(ax, t), bx), t), cx), t), dx);
+            let y = _mm_add_ps(_mm_mul_ps(_mm_add_ps(_mm_mul_ps(_mm_add_ps(_mm_mul_ps(ay, t), by), t), cy), t), dy);
+            _mm_storeu_ps(xs.as_mut_ptr().offset(i as isize), x);
+            _mm_storeu_ps(ys.as_mut_ptr().offset(i as isize), y);
+            i += 4;
+        }
+    }
+
+    #[target_feature(enable = "avx")]
+    pub unsafe fn evaluate_chunk_avx(
+        poly: &Polynomial,
+        first: u32,
+        step: f32,
+        xs: &mut [f32; CHUNK_SIZE],
+        ys: &mut [f32; CHUNK_SIZE],
+    ) {
+        let ax = _mm256_set1_ps(poly.a.x);
+        let bx = _mm256_set1_ps(poly.b.x);
+        let cx = _mm256_set1_ps(poly.c.x);
+        let dx = _mm256_set1_ps(poly.d.x);
+        let ay = _mm256_set1_ps(poly.a.y);
+        let by = _mm256_set1_ps(poly.b.y);
+        let cy = _mm256_set1_ps(poly.c.y);
+        let dy = _mm256_set1_ps(poly.d.y);
+        let lanes = _mm256_set_ps(7.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0, 0.0);
+        let step = _mm256_set1_ps(step);
+
+        let mut i = 0;
+        while i < CHUNK_SIZE {
+            let idx = _mm256_add_ps(_mm256_set1_ps((first + i as u32) as f32), lanes);
+            let t = _mm256_mul_ps(idx, step);
+            let x = _mm256_add_ps(_mm256_mul_ps(_mm256_add_ps(_mm256_mul_ps(_mm256_add_ps(_mm256_mul_ps(ax, t), bx), t), cx), t), dx);
+            let y = _mm256_add_ps(_mm256_mul_ps(_mm256_add_ps(_mm256_mul_ps(_mm256_add_ps(_mm256_mul_ps(ay, t), by), t), cy), t), dy);
+            _mm256_storeu_ps(xs.as_mut_ptr().offset(i as isize), x);
+            _mm256_storeu_ps(ys.as_mut_ptr().offset(i as isize), y);
+            i += 8;
+        }
+    }
+}
+
+#[test]
+fn test_uniform_matches_sampling() {
+    let curve = CubicBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl1: Point::new(10.0, 30.0),
+        ctrl2: Point::new(50.0, -20.0),
+        to: Point::new(100.0, 10.0),
+    };
+
+    let n = cubic_uniform_segment_count(&curve, 0.01);
+    assert!(n > CHUNK_SIZE as u32);
+
+    let mut points = Vec::new();
+    flatten_cubic_uniform(&curve, 0.01, &mut |p| { points.push(p); });
+
+    assert_eq!(points.len(), n as usize);
+    assert_eq!(*points.last().unwrap(), curve.to);
+    for (i, p) in points.iter().enumerate() {
+        let expected = curve.sample((i + 1) as f32 / n as f32);
+        assert!((*p - expected).length() < 0.001);
+    }
+}
+
+#[test]
+fn test_uniform_tolerance() {
+    let curve = QuadraticBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl: Point::new(50.0, 100.0),
+        to: Point::new(100.0, 0.0),
+    };
+
+    for &tolerance in &[1.0, 0.1, 0.01] {
+        let n = quadratic_uniform_segment_count(&curve, tolerance);
+        let mut prev = curve.from;
+        let mut i = 0;
+        flatten_quadratic_uniform(&curve, tolerance, &mut |p| {
+            // The middle of each segment is the furthest from the curve for a parabola.
+            let mid = curve.sample((i as f32 + 0.5) / n as f32);
+            let chord_mid = prev + (p - prev) * 0.5;
+            assert!((mid - chord_mid).length() <= tolerance * 1.01);
+            prev = p;
+            i += 1;
+        });
+        assert_eq!(i, n);
+    }
+}
+
+#[test]
+fn test_uniform_straight_line() {
+    let curve = QuadraticBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl: Point::new(1.0, 1.0),
+        to: Point::new(2.0, 2.0),
+    };
+    let mut points = Vec::new();
+    flatten_quadratic_uniform(&curve, 0.1, &mut |p| { points.push(p); });
+    assert_eq!(points, vec![Point::new(2.0, 2.0)]);
+}
@@ -67,12 +67,17 @@ extern crate arrayvec;
 extern crate euclid;
 
 mod flatten_cubic;
+mod flatten_uniform;
+mod flatten_curvature;
 mod cubic_to_quadratic;
 mod up_to_two;
 mod quadratic_bezier;
 mod cubic_bezier;
 mod triangle;
 mod line;
+mod closest_point;
+mod arc;
+mod rect_clip;
 
 pub use cubic_to_quadratic::cubic_to_quadratic;
 pub use up_to_two::UpToTwo;
@@ -96,6 +101,11 @@ pub use quadratic_bezier::{QuadraticBezierSegment, QuadraticFlatteningIter};
 pub use cubic_bezier::{CubicBezierSegment, CubicFlatteningIter};
 pub use triangle::{Triangle};
 pub use line::{LineSegment};
+pub use arc::{Arc, arc_step_count};
+pub use flatten_uniform::{flatten_quadratic_uniform, flatten_cubic_uniform};
+pub use flatten_uniform::{quadratic_uniform_segment_count, cubic_uniform_segment_count};
+pub use flatten_curvature::{flatten_quadratic_by_angle, flatten_cubic_by_angle};
+pub use rect_clip::{quadratic_rect_intervals, cubic_rect_intervals};
 
 use euclid::vec2;
 use euclid::rect;
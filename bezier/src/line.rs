@@ -35,6 +35,22 @@ impl LineSegment {
         LineSegment { from: self.to, to: self.from }
     }
 
+    /// Returns the parameter of the point of the segment that is the closest to a position.
+    pub fn closest_point_t(&self, position: Point) -> f32 {
+        let v = self.to - self.from;
+        let square_length = v.square_length();
+        if square_length == 0.0 {
+            return 0.0;
+        }
+
+        return ((position - self.from).dot(v) / square_length).max(0.0).min(1.0);
+    }
+
+    /// Returns the point of the segment that is the closest to a position.
+    pub fn closest_point(&self, position: Point) -> Point {
+        self.sample(self.closest_point_t(position))
+    }
+
     /// Split this curve into two sub-segments.
     #[inline]
     pub fn split(&self, t: f32) -> (Self, Self) {
@@ -286,3 +302,16 @@ fn bounding_rect() {
         assert_eq!(ls.bounding_rect(), r);
     }
 }
+
+#[test]
+fn closest_point() {
+    let l = LineSegment {
+        from: point(0.0, 0.0),
+        to: point(10.0, 0.0),
+    };
+
+    assert_eq!(l.closest_point_t(point(2.0, 5.0)), 0.2);
+    assert_eq!(l.closest_point(point(2.0, -5.0)), point(2.0, 0.0));
+    assert_eq!(l.closest_point(point(-3.0, 1.0)), point(0.0, 0.0));
+    assert_eq!(l.closest_point(point(13.0, 1.0)), point(10.0, 0.0));
+}
@@ -1,5 +1,10 @@
 use {CubicBezierSegment};
-use {Point, Rect, rect, Triangle, Transform2D};
+use {Point, Vec2, Rect, rect, Triangle, Transform2D};
+use flatten_uniform::flatten_quadratic_uniform;
+use flatten_curvature::flatten_quadratic_by_angle;
+use rect_clip::quadratic_rect_intervals;
+use std::ops::Range;
+use closest_point::closest_t;
 use std::mem::swap;
 
 /// A 2d curve segment defined by three points: the beginning of the segment, a control
@@ -23,6 +28,42 @@ impl QuadraticBezierSegment {
         return self.from * one_t2 + self.ctrl.to_vector() * 2.0 * one_t * t + self.to.to_vector() * t2;
     }
 
+    /// Sample the derivative of the curve at t (expecting t between 0 and 1).
+    pub fn derivative(&self, t: f32) -> Vec2 {
+        ((self.ctrl - self.from) * (1.0 - t) + (self.to - self.ctrl) * t) * 2.0
+    }
+
+    /// The second derivative of the curve (which is constant).
+    pub fn second_derivative(&self) -> Vec2 {
+        (self.to - self.ctrl * 2.0 + self.from.to_vector()) * 2.0
+    }
+
+    /// Signed curvature of the curve at t (expecting t between 0 and 1), positive when the
+    /// curve turns counter-clockwise (with the y axis pointing up).
+    ///
+    /// Returns zero where the derivative is zero.
+    pub fn curvature(&self, t: f32) -> f32 {
+        let d1 = self.derivative(t);
+        let length = d1.length();
+        if length == 0.0 {
+            return 0.0;
+        }
+        return d1.cross(self.second_derivative()) / (length * length * length);
+    }
+
+    /// Returns the parameter of the point of maximum curvature, where the derivative is
+    /// the shortest.
+    pub fn max_curvature_t(&self) -> f32 {
+        let a = self.ctrl - self.from;
+        let b = self.to - self.ctrl - a;
+        let bb = b.dot(b);
+        if bb == 0.0 {
+            // A line.
+            return 0.0;
+        }
+        return (-a.dot(b) / bb).max(0.0).min(1.0);
+    }
+
     /// Sample the x coordinate of the curve at t (expecting t between 0 and 1).
     pub fn sample_x(&self, t: f32) -> f32 {
         let t2 = t * t;
@@ -214,6 +255,30 @@ impl QuadraticBezierSegment {
         QuadraticFlatteningIter::new(*self, tolerance)
     }
 
+    /// Iterates through the curve invoking a callback at each point, using uniformly
+    /// distributed segments.
+    ///
+    /// This generates more points than `flattened_for_each` but can be faster, in particular
+    /// with the `simd` feature enabled.
+    pub fn uniform_flattened_for_each<F: FnMut(Point)>(&self, tolerance: f32, call_back: &mut F) {
+        flatten_quadratic_uniform(self, tolerance, call_back);
+    }
+
+    /// Iterates through the curve invoking a callback at each point, with segments along
+    /// which the direction of the curve changes by at most `max_angle` (in radians).
+    ///
+    /// See [flatten_quadratic_by_angle](fn.flatten_quadratic_by_angle.html).
+    pub fn angle_flattened_for_each<F: FnMut(Point)>(&self, max_angle: f32, call_back: &mut F) {
+        flatten_quadratic_by_angle(self, max_angle, call_back);
+    }
+
+    /// Returns the intervals of the parameter over which the curve is inside of a rectangle.
+    ///
+    /// See [quadratic_rect_intervals](fn.quadratic_rect_intervals.html).
+    pub fn inside_rect_intervals(&self, rect: &Rect) -> Vec<Range<f32>> {
+        quadratic_rect_intervals(self, rect)
+    }
+
     /// Compute the length of the segment using a flattened approximation.
     pub fn compute_length(&self, tolerance: f32) -> f32 {
         let mut start = self.from;
@@ -225,6 +290,18 @@ impl QuadraticBezierSegment {
         return len;
     }
 
+    /// Returns the parameter of the point of the curve that is the closest to a position,
+    /// within `tolerance`.
+    pub fn closest_point_t(&self, position: Point, tolerance: f32) -> f32 {
+        let length_bound = (self.ctrl - self.from).length() + (self.to - self.ctrl).length();
+        closest_t(|t| self.sample(t), position, 16, length_bound, tolerance)
+    }
+
+    /// Returns the point of the curve that is the closest to a position, within `tolerance`.
+    pub fn closest_point(&self, position: Point, tolerance: f32) -> Point {
+        self.sample(self.closest_point_t(position, tolerance))
+    }
+
     /// Returns a triangle containing this curve segment.
     pub fn bounding_triangle(&self) -> Triangle {
         Triangle {
@@ -409,3 +486,40 @@ fn length_straight_line() {
     }.compute_length(0.01);
     assert_eq!(len, 2.0);
 }
+
+#[test]
+fn closest_point_on_quadratic_bezier_segment() {
+    let a = QuadraticBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl: Point::new(1.0, 1.0),
+        to: Point::new(2.0, 0.0),
+    };
+
+    // The top of the curve.
+    assert!((a.closest_point_t(Point::new(1.0, 3.0), 0.0001) - 0.5).abs() < 0.001);
+    assert!((a.closest_point(Point::new(1.0, 3.0), 0.0001) - Point::new(1.0, 0.5)).length() < 0.001);
+    // Beyond the ends.
+    assert_eq!(a.closest_point_t(Point::new(-1.0, -1.0), 0.0001), 0.0);
+    assert_eq!(a.closest_point_t(Point::new(3.0, -1.0), 0.0001), 1.0);
+}
+
+#[test]
+fn curvature_of_parabola() {
+    // y = x² for x between -1 and 1.
+    let a = QuadraticBezierSegment {
+        from: Point::new(-1.0, 1.0),
+        ctrl: Point::new(0.0, -1.0),
+        to: Point::new(1.0, 1.0),
+    };
+
+    assert_eq!(a.derivative(0.5), Vec2::new(2.0, 0.0));
+    assert_eq!(a.curvature(0.5), 2.0);
+    assert_eq!(a.max_curvature_t(), 0.5);
+    // The curvature of y = x² at x = 1 is 2 / (1 + 4)^(3/2).
+    assert!((a.curvature(1.0) - 2.0 / 5.0f32.powf(1.5)).abs() < 0.0001);
+    // Turning the other way.
+    assert!((a.to_cubic().curvature(0.5) - 2.0).abs() < 0.0001);
+    let mut b = a;
+    b.flip();
+    assert_eq!(b.curvature(0.5), -2.0);
+}
@@ -0,0 +1,240 @@
+//! Intersection of curves with axis-aligned rectangles.
+//!
+//! Tiled renderers need to know which parts of a curve fall in each tile. Rather than
+//! flattening the curve and clipping the resulting polyline, the functions of this module
+//! compute the parameters at which the curve crosses the sides of the rectangle by solving
+//! the polynomial equation of each coordinate. The curve is inside of the rectangle over
+//! some of the intervals between consecutive crossings, which is checked at the middle of
+//! each interval.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_bezier;
+//! # use lyon_bezier::{CubicBezierSegment, Point, Size, Rect};
+//! # fn add_to_tile(_tile: &Rect, _curve: &CubicBezierSegment) {}
+//! # fn main() {
+//! # let point = |x, y| Point::new(x, y);
+//! # let tile_rect = Rect::new(point(0.0, 0.0), Size::new(10.0, 10.0));
+//! # let tile = tile_rect;
+//! # let curve = CubicBezierSegment {
+//! #     from: point(-5.0, 5.0),
+//! #     ctrl1: point(5.0, 20.0),
+//! #     ctrl2: point(5.0, -10.0),
+//! #     to: point(15.0, 5.0),
+//! # };
+//! for range in curve.inside_rect_intervals(&tile_rect) {
+//!     let clipped = curve
+//!         .after_split(range.start)
+//!         .before_split((range.end - range.start) / (1.0 - range.start));
+//!     add_to_tile(&tile, &clipped);
+//! }
+//! # }
+//! ```
+
+use std::f32::consts::PI;
+use std::ops::Range;
+
+use {Point, Rect};
+use {QuadraticBezierSegment, CubicBezierSegment};
+
+/// Returns the intervals of the parameter of a quadratic bézier curve over which the curve
+/// is inside of a rectangle, sorted in increasing order.
+///
+/// The edges of the rectangle are considered inside.
+pub fn quadratic_rect_intervals(curve: &QuadraticBezierSegment, rect: &Rect) -> Vec<Range<f32>> {
+    let mut crossings = Vec::new();
+    {
+        let mut add_axis = |p0: f32, p1: f32, p2: f32, min: f32, max: f32| {
+            let a = p0 - 2.0 * p1 + p2;
+            let b = 2.0 * (p1 - p0);
+            for &value in &[min, max] {
+                solve_quadratic(a, b, p0 - value, &mut |t| crossings.push(t));
+            }
+        };
+        add_axis(curve.from.x, curve.ctrl.x, curve.to.x, rect.min_x(), rect.max_x());
+        add_axis(curve.from.y, curve.ctrl.y, curve.to.y, rect.min_y(), rect.max_y());
+    }
+
+    return inside_intervals(crossings, rect, &|t| curve.sample(t));
+}
+
+/// Returns the intervals of the parameter of a cubic bézier curve over which the curve
+/// is inside of a rectangle, sorted in increasing order.
+///
+/// The edges of the rectangle are considered inside.
+pub fn cubic_rect_intervals(curve: &CubicBezierSegment, rect: &Rect) -> Vec<Range<f32>> {
+    let mut crossings = Vec::new();
+    {
+        let mut add_axis = |p0: f32, p1: f32, p2: f32, p3: f32, min: f32, max: f32| {
+            let a = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+            let b = 3.0 * p0 - 6.0 * p1 + 3.0 * p2;
+            let c = 3.0 * (p1 - p0);
+            for &value in &[min, max] {
+                solve_cubic(a, b, c, p0 - value, &mut |t| crossings.push(t));
+            }
+        };
+        add_axis(curve.from.x, curve.ctrl1.x, curve.ctrl2.x, curve.to.x, rect.min_x(), rect.max_x());
+        add_axis(curve.from.y, curve.ctrl1.y, curve.ctrl2.y, curve.to.y, rect.min_y(), rect.max_y());
+    }
+
+    return inside_intervals(crossings, rect, &|t| curve.sample(t));
+}
+
+// Splits the parameter space at the crossings and keeps the intervals that are inside of the
+// rectangle, merging the adjacent ones.
+fn inside_intervals<F>(mut crossings: Vec<f32>, rect: &Rect, sample: &F) -> Vec<Range<f32>>
+where
+    F: Fn(f32) -> Point,
+{
+    crossings.retain(|&t| t > 0.0 && t < 1.0);
+    crossings.push(0.0);
+    crossings.push(1.0);
+    crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    crossings.dedup();
+
+    let mut intervals: Vec<Range<f32>> = Vec::new();
+    for i in 1..crossings.len() {
+        let start = crossings[i - 1];
+        let end = crossings[i];
+        let p = sample((start + end) * 0.5);
+        let inside = p.x >= rect.min_x() && p.x <= rect.max_x()
+            && p.y >= rect.min_y() && p.y <= rect.max_y();
+        if !inside {
+            continue;
+        }
+
+        if let Some(last) = intervals.last_mut() {
+            if last.end == start {
+                last.end = end;
+                continue;
+            }
+        }
+        intervals.push(start..end);
+    }
+
+    return intervals;
+}
+
+// Invokes the callback with the real roots of `a * t² + b * t + c`.
+fn solve_quadratic<F: FnMut(f32)>(a: f32, b: f32, c: f32, call_back: &mut F) {
+    if a.abs() <= 1e-6 * (b.abs() + c.abs()) {
+        if b != 0.0 {
+            call_back(-c / b);
+        }
+        return;
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return;
+    }
+    if discriminant == 0.0 {
+        call_back(-b / (2.0 * a));
+        return;
+    }
+
+    // Avoids the cancellation between b and the square root of the discriminant.
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    call_back(q / a);
+    if q != 0.0 {
+        call_back(c / q);
+    }
+}
+
+// Invokes the callback with the real roots of `a * t³ + b * t² + c * t + d`.
+fn solve_cubic<F: FnMut(f32)>(a: f32, b: f32, c: f32, d: f32, call_back: &mut F) {
+    if a.abs() <= 1e-6 * (b.abs() + c.abs() + d.abs()) {
+        solve_quadratic(b, c, d, call_back);
+        return;
+    }
+
+    // Substituting t = x - b / 3 gives the depressed cubic x³ + p * x + q.
+    let b = b / a;
+    let c = c / a;
+    let d = d / a;
+    let offset = -b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+
+    if discriminant > 0.0 {
+        let sqrt = discriminant.sqrt();
+        call_back((-q / 2.0 + sqrt).cbrt() + (-q / 2.0 - sqrt).cbrt() + offset);
+    } else if p == 0.0 {
+        call_back(offset);
+    } else {
+        // Three real roots.
+        let r = 2.0 * (-p / 3.0).sqrt();
+        let cos = (3.0 * q / (p * r)).max(-1.0).min(1.0);
+        let angle = cos.acos() / 3.0;
+        for k in 0..3 {
+            call_back(r * (angle - 2.0 * PI * k as f32 / 3.0).cos() + offset);
+        }
+    }
+}
+
+#[cfg(test)]
+use euclid::rect;
+
+#[cfg(test)]
+fn assert_intervals(intervals: &[Range<f32>], expected: &[(f32, f32)]) {
+    assert_eq!(intervals.len(), expected.len(), "{:?}", intervals);
+    for (interval, &(start, end)) in intervals.iter().zip(expected.iter()) {
+        assert!((interval.start - start).abs() < 0.001, "{:?}", intervals);
+        assert!((interval.end - end).abs() < 0.001, "{:?}", intervals);
+    }
+}
+
+#[test]
+fn test_quadratic_rect_intervals() {
+    // x = 20 * t, y = 40 * t * (1 - t)
+    let curve = QuadraticBezierSegment {
+        from: Point::new(0.0, 0.0),
+        ctrl: Point::new(10.0, 20.0),
+        to: Point::new(20.0, 0.0),
+    };
+
+    assert_intervals(
+        &quadratic_rect_intervals(&curve, &rect(0.0, -1.0, 20.0, 8.5)),
+        &[(0.0, 0.25), (0.75, 1.0)],
+    );
+    assert_intervals(
+        &quadratic_rect_intervals(&curve, &rect(-1.0, -1.0, 30.0, 30.0)),
+        &[(0.0, 1.0)],
+    );
+    assert_intervals(
+        &quadratic_rect_intervals(&curve, &rect(5.0, 11.0, 10.0, 10.0)),
+        &[],
+    );
+
+    // Same results with the equivalent cubic curve.
+    assert_intervals(
+        &curve.to_cubic().inside_rect_intervals(&rect(0.0, -1.0, 20.0, 8.5)),
+        &[(0.0, 0.25), (0.75, 1.0)],
+    );
+}
+
+#[test]
+fn test_cubic_rect_intervals() {
+    // x = 30 * t, y = 100 * (t - 0.2) * (t - 0.5) * (t - 0.8)
+    let curve = CubicBezierSegment {
+        from: Point::new(0.0, -8.0),
+        ctrl1: Point::new(10.0, 14.0),
+        ctrl2: Point::new(20.0, -14.0),
+        to: Point::new(30.0, 8.0),
+    };
+
+    assert_intervals(
+        &cubic_rect_intervals(&curve, &rect(-1.0, 0.0, 32.0, 20.0)),
+        &[(0.2, 0.5), (0.8, 1.0)],
+    );
+    assert_intervals(
+        &cubic_rect_intervals(&curve, &rect(-1.0, -20.0, 32.0, 20.0)),
+        &[(0.0, 0.2), (0.5, 0.8)],
+    );
+    assert_intervals(
+        &cubic_rect_intervals(&curve, &rect(3.0, -20.0, 12.0, 40.0)),
+        &[(0.1, 0.5)],
+    );
+}
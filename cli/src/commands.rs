@@ -1,12 +1,26 @@
 use std::io;
+use lyon::tessellation::LineCap;
+
+/// How the result of a tessellation is written.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// The vertices and indices in a human readable form.
+    Text,
+    /// A Wavefront OBJ mesh.
+    Obj,
+    /// An SVG document showing the triangles, useful to look at tessellation bugs.
+    Svg,
+}
 
 pub struct TessellateCmd {
     pub input: String,
     pub output: Box<io::Write>,
     pub fill: bool,
     pub stroke: Option<f32>,
+    pub line_cap: LineCap,
     pub tolerance: f32,
     pub count: bool,
+    pub format: OutputFormat,
 }
 
 pub struct FlattenCmd {
@@ -16,3 +30,23 @@ pub struct FlattenCmd {
     pub count: bool,
 }
 
+
+pub struct DebugFillCmd {
+    pub input: String,
+    pub output: Box<io::Write>,
+    pub tolerance: f32,
+}
+
+pub struct FuzzCmd {
+    pub output: Box<io::Write>,
+    pub fill: bool,
+    pub stroke: bool,
+    pub tolerance: f32,
+    /// Run until a failure is found if `None`.
+    pub iterations: Option<u64>,
+    pub max_sub_paths: u32,
+    pub max_points: u32,
+    pub curves: bool,
+    /// Seed of the random number generator, picked randomly if `None`.
+    pub seed: Option<u32>,
+}
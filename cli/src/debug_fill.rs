@@ -0,0 +1,188 @@
+use commands::DebugFillCmd;
+use lyon::math::*;
+use lyon::svg::parser;
+use lyon::path::Path;
+use lyon::path_builder::*;
+use lyon::path_iterator::*;
+use lyon::events::FlattenedEvent;
+use lyon::tessellation::geometry_builder::{VertexBuffers, BuffersBuilder, VertexConstructor};
+use lyon::tessellation::path_fill::*;
+use lyon::tessellation::FillVertex;
+use std::io;
+
+#[derive(Debug)]
+pub enum DebugError {
+    Io(io::Error),
+    Parse,
+}
+
+impl ::std::convert::From<::std::io::Error> for DebugError {
+    fn from(err: io::Error) -> Self { DebugError::Io(err) }
+}
+
+/// Tessellates the fill of a path and writes an HTML page that shows the state of the
+/// sweep line after each vertex.
+pub fn debug_fill(mut cmd: DebugFillCmd) -> Result<(), DebugError> {
+    let mut builder = SvgPathBuilder::new(Path::builder());
+
+    for item in parser::path::PathTokenizer::new(&cmd.input) {
+        if let Ok(event) = item {
+            builder.svg_event(event)
+        } else {
+            return Err(DebugError::Parse);
+        }
+    }
+
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+    let mut tess = FillTessellator::new();
+    tess.record_snapshots(true);
+    // The snapshots are interesting in particular when the tessellation fails.
+    let result = tess.tessellate_path(
+        path.path_iter().flattened(cmd.tolerance),
+        &FillOptions::default().with_tolerance(cmd.tolerance),
+        &mut BuffersBuilder::new(&mut buffers, Positions)
+    );
+
+    let mut polylines: Vec<Vec<Point>> = Vec::new();
+    for evt in path.path_iter().flattened(cmd.tolerance) {
+        match evt {
+            FlattenedEvent::MoveTo(to) => { polylines.push(vec![to]); }
+            FlattenedEvent::LineTo(to) => {
+                if let Some(polyline) = polylines.last_mut() {
+                    polyline.push(to);
+                }
+            }
+            FlattenedEvent::Close => {
+                if let Some(polyline) = polylines.last_mut() {
+                    let first = polyline[0];
+                    polyline.push(first);
+                }
+            }
+        }
+    }
+
+    let mut min = point(0.0, 0.0);
+    let mut max = point(0.0, 0.0);
+    let mut first = true;
+    for p in polylines.iter().flat_map(|p| p.iter()) {
+        if first {
+            min = *p;
+            max = *p;
+            first = false;
+        }
+        min.x = min.x.min(p.x);
+        min.y = min.y.min(p.y);
+        max.x = max.x.max(p.x);
+        max.y = max.y.max(p.y);
+    }
+    let size = (max.x - min.x).max(max.y - min.y).max(1.0);
+    let margin = size * 0.05;
+    let lw = size * 0.003;
+
+    let out = &mut *cmd.output;
+    try!{ writeln!(out, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">") };
+    try!{ writeln!(out, "<title>Fill tessellation debugger</title>") };
+    try!{ writeln!(out, "<style>svg {{ width: 90vmin; height: 90vmin; border: 1px solid #ccc; }} .frame {{ display: none; }}</style>") };
+    try!{ writeln!(out, "</head>\n<body>") };
+    try!{ writeln!(out,
+        "<div><button onclick=\"show(current - 1)\">previous</button> \
+         <button onclick=\"show(current + 1)\">next</button> \
+         <input id=\"slider\" type=\"range\" min=\"0\" max=\"{}\" value=\"0\" oninput=\"show(parseInt(this.value))\"> \
+         <span id=\"caption\"></span></div>",
+        tess.snapshots().len().max(1) - 1
+    ) };
+    if let Err(ref err) = result {
+        try!{ writeln!(out, "<p style=\"color: red\">Tessellation failed: {:?}</p>", err) };
+    }
+
+    for (idx, snapshot) in tess.snapshots().iter().enumerate() {
+        try!{ writeln!(out,
+            "<div class=\"frame\" data-caption=\"step {} at ({}, {}), {} span(s), {} triangle(s)\">",
+            idx, snapshot.position.x, snapshot.position.y, snapshot.spans.len(), snapshot.triangle_count
+        ) };
+        try!{ writeln!(out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
+            min.x - margin, min.y - margin,
+            max.x - min.x + 2.0 * margin, max.y - min.y + 2.0 * margin
+        ) };
+
+        // Triangles emitted so far.
+        try!{ writeln!(out, "<g fill=\"rgba(50, 100, 200, 0.3)\" stroke=\"rgb(50, 100, 200)\" stroke-width=\"{}\">", lw * 0.5) };
+        for triangle in buffers.indices.chunks(3).take(snapshot.triangle_count) {
+            let a = buffers.vertices[triangle[0] as usize];
+            let b = buffers.vertices[triangle[1] as usize];
+            let c = buffers.vertices[triangle[2] as usize];
+            try!{ writeln!(out, "<path d=\"M {} {} L {} {} L {} {} Z\"/>", a.x, a.y, b.x, b.y, c.x, c.y) };
+        }
+        try!{ writeln!(out, "</g>") };
+
+        // The outline of the path.
+        try!{ writeln!(out, "<g fill=\"none\" stroke=\"#aaa\" stroke-width=\"{}\">", lw) };
+        for polyline in &polylines {
+            try!{ write!(out, "<polyline points=\"") };
+            for p in polyline {
+                try!{ write!(out, "{},{} ", p.x, p.y) };
+            }
+            try!{ writeln!(out, "\"/>") };
+        }
+        try!{ writeln!(out, "</g>") };
+
+        // The sweep line.
+        try!{ writeln!(out,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"orange\" stroke-width=\"{}\"/>",
+            min.x - margin, snapshot.position.y, max.x + margin, snapshot.position.y, lw
+        ) };
+
+        // The spans, left edges in green and right edges in red. Merge edges are dashed.
+        for span in &snapshot.spans {
+            for &(edge, color) in &[(span.left, "green"), (span.right, "red")] {
+                let dash = if edge.merge { format!(" stroke-dasharray=\"{} {}\"", lw * 3.0, lw * 3.0) } else { String::new() };
+                try!{ writeln!(out,
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\"{}/>",
+                    edge.upper.x, edge.upper.y, edge.lower.x, edge.lower.y, color, lw * 2.0, dash
+                ) };
+            }
+        }
+
+        for p in &snapshot.pending_intersections {
+            try!{ writeln!(out, "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"purple\"/>", p.x, p.y, lw * 3.0) };
+        }
+
+        try!{ writeln!(out,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"black\"/>",
+            snapshot.position.x, snapshot.position.y, lw * 4.0
+        ) };
+
+        try!{ writeln!(out, "</svg>\n</div>") };
+    }
+
+    try!{ writeln!(out, "<script>
+var frames = document.getElementsByClassName('frame');
+var current = 0;
+function show(idx) {{
+    if (idx < 0 || idx >= frames.length) {{ return; }}
+    frames[current].style.display = 'none';
+    current = idx;
+    frames[current].style.display = 'block';
+    document.getElementById('slider').value = current;
+    document.getElementById('caption').textContent = frames[current].dataset.caption;
+}}
+document.addEventListener('keydown', function(e) {{
+    if (e.key == 'ArrowLeft') {{ show(current - 1); }}
+    if (e.key == 'ArrowRight') {{ show(current + 1); }}
+}});
+if (frames.length > 0) {{ frames[0].style.display = 'block'; show(0); }}
+</script>
+</body>
+</html>") };
+
+    Ok(())
+}
+
+struct Positions;
+
+impl VertexConstructor<FillVertex, Point> for Positions {
+    fn new_vertex(&mut self, vertex: FillVertex) -> Point { vertex.position }
+}
@@ -0,0 +1,178 @@
+use commands::FuzzCmd;
+use lyon::math::*;
+use lyon::path::Path;
+use lyon::path_builder::*;
+use lyon::path_iterator::*;
+use lyon::tessellation::geometry_builder::{VertexBuffers, BuffersBuilder, VertexConstructor};
+use lyon::tessellation::path_fill::*;
+use lyon::tessellation::path_stroke::*;
+use lyon::tessellation::{FillVertex, StrokeVertex};
+use lyon::extra::debugging::find_reduced_test_case;
+use lyon::extra::testing::{random_path, RandomPathOptions};
+use rand::{Rng, SeedableRng, XorShiftRng, thread_rng};
+use std::io;
+
+#[derive(Debug)]
+pub enum FuzzError {
+    Io(io::Error),
+    /// A failing path was found (and reduced).
+    Failure(Path),
+}
+
+impl ::std::convert::From<::std::io::Error> for FuzzError {
+    fn from(err: io::Error) -> Self { FuzzError::Io(err) }
+}
+
+/// Generates random paths, tessellates them and checks the output.
+///
+/// When a path makes the tessellators panic or produce invalid geometry, it is reduced
+/// to a smaller path that still fails and printed as a rust test case.
+pub fn run(mut cmd: FuzzCmd) -> Result<(), FuzzError> {
+    let seed = match cmd.seed {
+        Some(seed) => seed,
+        None => thread_rng().gen(),
+    };
+    try!{ writeln!(&mut *cmd.output, "seed: {}", seed) };
+    // The xorshift generator must not be seeded with zeros only.
+    let mut rng = XorShiftRng::from_seed([0x193a6754, 0xa8a7d469, 0x97830e05, seed]);
+
+    let mut i: u64 = 0;
+    loop {
+        if let Some(max) = cmd.iterations {
+            if i >= max {
+                break;
+            }
+        }
+
+        let path = generate_path(&mut rng, &cmd);
+
+        let failed = check(&path, &cmd);
+
+        if failed {
+            try!{ writeln!(&mut *cmd.output, "failure after {} iterations, reducing...", i) };
+            let options = cmd_options(&cmd);
+            let reduced = find_reduced_test_case(path.as_slice(), &move |path: Path| {
+                // The reduction catches panics, no need to do it here.
+                !check_unchecked(&path, &options)
+            });
+
+            return Err(FuzzError::Failure(reduced));
+        }
+
+        if i != 0 && i % 1000 == 0 {
+            try!{ writeln!(&mut *cmd.output, "{} paths tested", i) };
+        }
+
+        i += 1;
+    }
+
+    try!{ writeln!(&mut *cmd.output, "{} paths tested without failure", i) };
+
+    Ok(())
+}
+
+/// The parts of the command that the reduction callback needs (the latter must not hold
+/// on to the output stream).
+#[derive(Copy, Clone, Debug)]
+struct Options {
+    fill: bool,
+    stroke: bool,
+    tolerance: f32,
+}
+
+fn cmd_options(cmd: &FuzzCmd) -> Options {
+    Options {
+        fill: cmd.fill,
+        stroke: cmd.stroke,
+        tolerance: cmd.tolerance,
+    }
+}
+
+/// Returns true if the path fails.
+fn check(path: &Path, cmd: &FuzzCmd) -> bool {
+    let options = cmd_options(cmd);
+    let path = path.clone();
+    ::std::panic::catch_unwind(move || !check_unchecked(&path, &options)).unwrap_or(true)
+}
+
+/// Returns true if the tessellation succeeded and produced valid geometry.
+fn check_unchecked(path: &Path, options: &Options) -> bool {
+    if options.fill {
+        let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+        let result = FillTessellator::new().tessellate_path(
+            path.path_iter().flattened(options.tolerance),
+            &FillOptions::default().with_tolerance(options.tolerance),
+            &mut BuffersBuilder::new(&mut buffers, FillPosition)
+        );
+
+        if result.is_err() || !is_valid(&buffers) {
+            return false;
+        }
+    }
+
+    if options.stroke {
+        let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+        let result = StrokeTessellator::new().tessellate(
+            path.path_iter().flattened(options.tolerance),
+            &StrokeOptions::default().with_tolerance(options.tolerance),
+            &mut BuffersBuilder::new(&mut buffers, StrokePosition)
+        );
+
+        if result.is_err() || !is_valid(&buffers) {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn is_valid(buffers: &VertexBuffers<Point>) -> bool {
+    if buffers.indices.len() % 3 != 0 {
+        return false;
+    }
+
+    for v in &buffers.vertices {
+        if !v.x.is_finite() || !v.y.is_finite() {
+            return false;
+        }
+    }
+
+    let num_vertices = buffers.vertices.len();
+    for &idx in &buffers.indices {
+        if idx as usize >= num_vertices {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn generate_path<R: Rng>(rng: &mut R, cmd: &FuzzCmd) -> Path {
+    let options = RandomPathOptions {
+        max_sub_paths: cmd.max_sub_paths,
+        max_edges: cmd.max_points,
+        curves: cmd.curves,
+        ..RandomPathOptions::default()
+    };
+    let path = random_path(rng, &options);
+
+    // The reduction works on polygons, so the curves are flattened up-front.
+    let mut builder = Path::builder().flattened(cmd.tolerance);
+    for evt in path.path_iter() {
+        builder.path_event(evt);
+    }
+
+    builder.build()
+}
+
+struct FillPosition;
+
+impl VertexConstructor<FillVertex, Point> for FillPosition {
+    fn new_vertex(&mut self, vertex: FillVertex) -> Point { vertex.position }
+}
+
+struct StrokePosition;
+
+impl VertexConstructor<StrokeVertex, Point> for StrokePosition {
+    fn new_vertex(&mut self, vertex: StrokeVertex) -> Point { vertex.position + vertex.normal }
+}
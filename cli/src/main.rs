@@ -1,12 +1,16 @@
 extern crate clap;
 extern crate lyon;
+extern crate rand;
 
 mod commands;
 mod tessellate;
 mod flatten;
+mod debug_fill;
+mod fuzzing;
 
 use clap::*;
 use commands::*;
+use lyon::tessellation::LineCap;
 
 use std::fs::File;
 use std::io::{Read, Write, stdout, stderr};
@@ -37,11 +41,25 @@ fn main() {
                 .value_name("TOLERANCE")
                 .takes_value(true)
             )
+            .arg(Arg::with_name("LINE_CAP")
+                .long("line-cap")
+                .help("Sets the line cap of the stroke (butt by default)")
+                .value_name("LINE_CAP")
+                .takes_value(true)
+                .possible_values(&["butt", "square", "round"])
+            )
             .arg(Arg::with_name("COUNT")
                 .short("c")
                 .long("count")
                 .help("Prints the number of triangles and vertices")
             )
+            .arg(Arg::with_name("FORMAT")
+                .long("format")
+                .help("Sets the output format (text by default)")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(&["text", "obj", "svg"])
+            )
         )
         .subcommand(SubCommand::with_name("flatten")
             .about("Flattens a path")
@@ -58,6 +76,65 @@ fn main() {
                 .help("Prints the number of vertices")
             )
         )
+        .subcommand(SubCommand::with_name("debug-fill")
+            .about("Writes an HTML page showing the fill tessellation step by step")
+            .arg(Arg::with_name("TOLERANCE")
+                .short("t")
+                .long("tolerance")
+                .help("Sets the tolerance threshold for flattening (0.5 by default)")
+                .value_name("TOLERANCE")
+                .takes_value(true)
+            )
+        )
+        .subcommand(SubCommand::with_name("fuzz")
+            .about("Tessellates random paths in order to find bugs")
+            .arg(Arg::with_name("FILL")
+                .short("f")
+                .long("fill")
+                .help("Fills the paths")
+            )
+            .arg(Arg::with_name("STROKE")
+                .short("s")
+                .long("stroke")
+                .help("Strokes the paths")
+            )
+            .arg(Arg::with_name("TOLERANCE")
+                .short("t")
+                .long("tolerance")
+                .help("Sets the tolerance threshold for flattening (0.5 by default)")
+                .value_name("TOLERANCE")
+                .takes_value(true)
+            )
+            .arg(Arg::with_name("ITERATIONS")
+                .short("n")
+                .long("iterations")
+                .help("Stops after a given number of paths (runs until a failure by default)")
+                .value_name("ITERATIONS")
+                .takes_value(true)
+            )
+            .arg(Arg::with_name("MAX_SUB_PATHS")
+                .long("max-sub-paths")
+                .help("Sets the maximum number of sub-paths per path (3 by default)")
+                .value_name("MAX_SUB_PATHS")
+                .takes_value(true)
+            )
+            .arg(Arg::with_name("MAX_POINTS")
+                .long("max-points")
+                .help("Sets the maximum number of edges per sub-path (20 by default)")
+                .value_name("MAX_POINTS")
+                .takes_value(true)
+            )
+            .arg(Arg::with_name("CURVES")
+                .long("curves")
+                .help("Generates curves in addition to line segments")
+            )
+            .arg(Arg::with_name("SEED")
+                .long("seed")
+                .help("Sets the seed of the random paths, to reproduce a previous run")
+                .value_name("SEED")
+                .takes_value(true)
+            )
+        )
         .arg(Arg::with_name("PATH")
             .value_name("PATH")
             .help("An SVG path")
@@ -111,8 +188,10 @@ fn main() {
             output: output,
             fill: fill_cmd || (!fill_cmd && !stroke_cmd.is_some()),
             stroke: stroke_cmd,
+            line_cap: get_line_cap(&tess_matches),
             tolerance: get_tolerance(&tess_matches),
             count: tess_matches.is_present("COUNT"),
+            format: get_format(&tess_matches),
         };
 
         tessellate::tessellate(cmd).unwrap();
@@ -126,6 +205,34 @@ fn main() {
         };
 
         flatten::flatten(cmd).unwrap();
+
+    } else if let Some(debug_matches) = matches.subcommand_matches("debug-fill") {
+        let cmd = DebugFillCmd {
+            input: input_buffer,
+            output: output,
+            tolerance: get_tolerance(&debug_matches),
+        };
+
+        debug_fill::debug_fill(cmd).unwrap();
+
+    } else if let Some(fuzz_matches) = matches.subcommand_matches("fuzz") {
+        let fill_cmd = fuzz_matches.is_present("FILL");
+        let stroke_cmd = fuzz_matches.is_present("STROKE");
+        let cmd = FuzzCmd {
+            output: output,
+            fill: fill_cmd || !stroke_cmd,
+            stroke: stroke_cmd,
+            tolerance: get_tolerance(&fuzz_matches),
+            iterations: fuzz_matches.value_of("ITERATIONS").and_then(|s| s.parse().ok()),
+            max_sub_paths: get_u32(&fuzz_matches, "MAX_SUB_PATHS", 3),
+            max_points: get_u32(&fuzz_matches, "MAX_POINTS", 20),
+            curves: fuzz_matches.is_present("CURVES"),
+            seed: fuzz_matches.value_of("SEED").and_then(|s| s.parse().ok()),
+        };
+
+        if let Err(fuzzing::FuzzError::Failure(_)) = fuzzing::run(cmd) {
+            ::std::process::exit(1);
+        }
     }
 }
 
@@ -137,6 +244,29 @@ fn get_tolerance(matches: &ArgMatches) -> f32 {
     return default;
 }
 
+fn get_u32(matches: &ArgMatches, name: &str, default: u32) -> u32 {
+    if let Some(value_str) = matches.value_of(name) {
+        return value_str.parse().unwrap_or(default).max(1);
+    }
+    return default;
+}
+
+fn get_line_cap(matches: &ArgMatches) -> LineCap {
+    match matches.value_of("LINE_CAP") {
+        Some("square") => LineCap::Square,
+        Some("round") => LineCap::Round,
+        _ => LineCap::Butt,
+    }
+}
+
+fn get_format(matches: &ArgMatches) -> OutputFormat {
+    match matches.value_of("FORMAT") {
+        Some("obj") => OutputFormat::Obj,
+        Some("svg") => OutputFormat::Svg,
+        _ => OutputFormat::Text,
+    }
+}
+
 fn get_stroke(matches: &ArgMatches) -> Option<f32> {
     if let Some(stroke_str) = matches.value_of("STROKE") {
         if let Ok(val) = stroke_str.parse() {
@@ -1,4 +1,4 @@
-use commands::TessellateCmd;
+use commands::{TessellateCmd, OutputFormat};
 use lyon::math::*;
 use lyon::svg::parser;
 use lyon::path::Path;
@@ -40,7 +40,7 @@ pub fn tessellate(mut cmd: TessellateCmd) -> Result<(), TessError> {
     if cmd.fill {
         if FillTessellator::new().tessellate_path(
             path.path_iter().flattened(cmd.tolerance),
-            &FillOptions::default(),
+            &FillOptions::default().with_tolerance(cmd.tolerance),
             &mut BuffersBuilder::new(&mut buffers, ApplyNormal)
         ).is_err() {
             return Err(TessError::Fill);
@@ -50,7 +50,9 @@ pub fn tessellate(mut cmd: TessellateCmd) -> Result<(), TessError> {
     if let Some(width) = cmd.stroke {
         if StrokeTessellator::new().tessellate(
             path.path_iter().flattened(cmd.tolerance),
-            &StrokeOptions::default(),
+            &StrokeOptions::default()
+                .with_tolerance(cmd.tolerance)
+                .with_line_cap(cmd.line_cap),
             &mut BuffersBuilder::new(&mut buffers, StrokeWidth(width))
         ).is_err() {
             return Err(TessError::Stroke);
@@ -65,27 +67,94 @@ pub fn tessellate(mut cmd: TessellateCmd) -> Result<(), TessError> {
         return Ok(());
     }
 
-    try!{ write!(&mut *cmd.output, "vertices: [") };
+    match cmd.format {
+        OutputFormat::Text => write_text(&buffers, &mut *cmd.output),
+        OutputFormat::Obj => write_obj(&buffers, &mut *cmd.output),
+        OutputFormat::Svg => write_svg(&buffers, &mut *cmd.output),
+    }
+}
+
+fn write_text(buffers: &VertexBuffers<Point>, output: &mut io::Write) -> Result<(), TessError> {
+    try!{ write!(output, "vertices: [") };
     let mut is_first = true;
-    for vertex in buffers.vertices {
+    for vertex in &buffers.vertices {
         if !is_first {
-            try!{ write!(&mut *cmd.output, ", ") };
+            try!{ write!(output, ", ") };
         }
-        try!{ write!(&mut *cmd.output, "({}, {})", vertex.x, vertex.y) };
+        try!{ write!(output, "({}, {})", vertex.x, vertex.y) };
         is_first = false;
     }
-    try!{ writeln!(&mut *cmd.output, "]") };
+    try!{ writeln!(output, "]") };
 
-    try!{ write!(&mut *cmd.output, "indices: [") };
+    try!{ write!(output, "indices: [") };
     let mut is_first = true;
-    for index in buffers.indices {
+    for index in &buffers.indices {
         if !is_first {
-            try!{ write!(&mut *cmd.output, ", ") };
+            try!{ write!(output, ", ") };
         }
-        try!{ write!(&mut *cmd.output, "{}", index) };
+        try!{ write!(output, "{}", index) };
         is_first = false;
     }
-    try!{ writeln!(&mut *cmd.output, "]") };
+    try!{ writeln!(output, "]") };
+
+    Ok(())
+}
+
+fn write_obj(buffers: &VertexBuffers<Point>, output: &mut io::Write) -> Result<(), TessError> {
+    for vertex in &buffers.vertices {
+        try!{ writeln!(output, "v {} {} 0", vertex.x, vertex.y) };
+    }
+    // OBJ indices start at 1.
+    for triangle in buffers.indices.chunks(3) {
+        try!{ writeln!(output, "f {} {} {}", triangle[0] + 1, triangle[1] + 1, triangle[2] + 1) };
+    }
+
+    Ok(())
+}
+
+fn write_svg(buffers: &VertexBuffers<Point>, output: &mut io::Write) -> Result<(), TessError> {
+    let mut min = point(0.0, 0.0);
+    let mut max = point(0.0, 0.0);
+    if let Some(first) = buffers.vertices.first() {
+        min = *first;
+        max = *first;
+    }
+    for v in &buffers.vertices {
+        min.x = min.x.min(v.x);
+        min.y = min.y.min(v.y);
+        max.x = max.x.max(v.x);
+        max.y = max.y.max(v.y);
+    }
+
+    let size = (max.x - min.x).max(max.y - min.y).max(1.0);
+    let margin = size * 0.05;
+    let line_width = size * 0.002;
+
+    try!{ writeln!(output,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
+        min.x - margin, min.y - margin,
+        max.x - min.x + 2.0 * margin, max.y - min.y + 2.0 * margin
+    ) };
+    try!{ writeln!(output,
+        "  <g fill=\"rgba(50, 100, 200, 0.3)\" stroke=\"black\" stroke-width=\"{}\" stroke-linejoin=\"round\">",
+        line_width
+    ) };
+    for triangle in buffers.indices.chunks(3) {
+        let a = buffers.vertices[triangle[0] as usize];
+        let b = buffers.vertices[triangle[1] as usize];
+        let c = buffers.vertices[triangle[2] as usize];
+        try!{ writeln!(output,
+            "    <path d=\"M {} {} L {} {} L {} {} Z\"/>",
+            a.x, a.y, b.x, b.y, c.x, c.y
+        ) };
+    }
+    try!{ writeln!(output, "  </g>") };
+    try!{ writeln!(output, "  <g fill=\"red\">") };
+    for v in &buffers.vertices {
+        try!{ writeln!(output, "    <circle cx=\"{}\" cy=\"{}\" r=\"{}\"/>", v.x, v.y, line_width * 2.0) };
+    }
+    try!{ writeln!(output, "  </g>") };
+    try!{ writeln!(output, "</svg>") };
 
     Ok(())
 }
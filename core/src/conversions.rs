@@ -0,0 +1,209 @@
+//! Conversions between lyon's math types and other math libraries.
+//!
+//! Each library is behind a feature of the same name (`mint`, `cgmath` and `nalgebra`).
+//!
+//! lyon's math types are provided by euclid, so the coherence rules don't let us implement
+//! `From` between them and the types of another crate. Instead, the `Convert` trait is
+//! implemented in both directions:
+//!
+//! ```
+//! # #[cfg(feature = "mint")]
+//! # extern crate mint;
+//! # extern crate lyon_core;
+//! # #[cfg(feature = "mint")]
+//! # fn example() {
+//! use lyon_core::conversions::Convert;
+//! use lyon_core::math::{Point, point};
+//!
+//! let p: mint::Point2<f32> = point(1.0, 2.0).convert();
+//! let q: Point = p.convert();
+//! # assert_eq!(q, point(1.0, 2.0));
+//! # }
+//! # fn main() {
+//! # #[cfg(feature = "mint")]
+//! # example();
+//! # }
+//! ```
+//!
+//! Transforms follow euclid's conventions: `Transform2d` multiplies row vectors, its
+//! `m31` and `m32` members are the translation.
+
+use math::{Point, Vec2, Transform2d, point, vec2};
+
+/// Converts a value into an equivalent type from another math library.
+pub trait Convert<T> {
+    fn convert(self) -> T;
+}
+
+#[cfg(feature = "mint")]
+mod mint_conversions {
+    use super::*;
+    use mint;
+
+    impl Convert<mint::Point2<f32>> for Point {
+        fn convert(self) -> mint::Point2<f32> { mint::Point2 { x: self.x, y: self.y } }
+    }
+
+    impl Convert<Point> for mint::Point2<f32> {
+        fn convert(self) -> Point { point(self.x, self.y) }
+    }
+
+    impl Convert<mint::Vector2<f32>> for Vec2 {
+        fn convert(self) -> mint::Vector2<f32> { mint::Vector2 { x: self.x, y: self.y } }
+    }
+
+    impl Convert<Vec2> for mint::Vector2<f32> {
+        fn convert(self) -> Vec2 { vec2(self.x, self.y) }
+    }
+
+    impl Convert<mint::RowMatrix2x3<f32>> for Transform2d {
+        fn convert(self) -> mint::RowMatrix2x3<f32> {
+            mint::RowMatrix2x3 {
+                x: mint::Vector2 { x: self.m11, y: self.m12 },
+                y: mint::Vector2 { x: self.m21, y: self.m22 },
+                z: mint::Vector2 { x: self.m31, y: self.m32 },
+            }
+        }
+    }
+
+    impl Convert<Transform2d> for mint::RowMatrix2x3<f32> {
+        fn convert(self) -> Transform2d {
+            Transform2d::row_major(
+                self.x.x, self.x.y,
+                self.y.x, self.y.y,
+                self.z.x, self.z.y,
+            )
+        }
+    }
+}
+
+#[cfg(feature = "cgmath")]
+mod cgmath_conversions {
+    use super::*;
+    use cgmath;
+
+    impl Convert<cgmath::Point2<f32>> for Point {
+        fn convert(self) -> cgmath::Point2<f32> { cgmath::Point2::new(self.x, self.y) }
+    }
+
+    impl Convert<Point> for cgmath::Point2<f32> {
+        fn convert(self) -> Point { point(self.x, self.y) }
+    }
+
+    impl Convert<cgmath::Vector2<f32>> for Vec2 {
+        fn convert(self) -> cgmath::Vector2<f32> { cgmath::Vector2::new(self.x, self.y) }
+    }
+
+    impl Convert<Vec2> for cgmath::Vector2<f32> {
+        fn convert(self) -> Vec2 { vec2(self.x, self.y) }
+    }
+
+    // cgmath matrices are column-major and multiply column vectors.
+    impl Convert<cgmath::Matrix3<f32>> for Transform2d {
+        fn convert(self) -> cgmath::Matrix3<f32> {
+            cgmath::Matrix3::new(
+                self.m11, self.m12, 0.0,
+                self.m21, self.m22, 0.0,
+                self.m31, self.m32, 1.0,
+            )
+        }
+    }
+
+    impl Convert<Transform2d> for cgmath::Matrix3<f32> {
+        fn convert(self) -> Transform2d {
+            Transform2d::row_major(
+                self.x.x, self.x.y,
+                self.y.x, self.y.y,
+                self.z.x, self.z.y,
+            )
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_conversions {
+    use super::*;
+    use nalgebra;
+
+    impl Convert<nalgebra::Point2<f32>> for Point {
+        fn convert(self) -> nalgebra::Point2<f32> { nalgebra::Point2::new(self.x, self.y) }
+    }
+
+    impl Convert<Point> for nalgebra::Point2<f32> {
+        fn convert(self) -> Point { point(self.x, self.y) }
+    }
+
+    impl Convert<nalgebra::Vector2<f32>> for Vec2 {
+        fn convert(self) -> nalgebra::Vector2<f32> { nalgebra::Vector2::new(self.x, self.y) }
+    }
+
+    impl Convert<Vec2> for nalgebra::Vector2<f32> {
+        fn convert(self) -> Vec2 { vec2(self.x, self.y) }
+    }
+
+    // nalgebra's constructor takes the components in row-major order and the matrices
+    // multiply column vectors.
+    impl Convert<nalgebra::Matrix3<f32>> for Transform2d {
+        fn convert(self) -> nalgebra::Matrix3<f32> {
+            nalgebra::Matrix3::new(
+                self.m11, self.m21, self.m31,
+                self.m12, self.m22, self.m32,
+                0.0, 0.0, 1.0,
+            )
+        }
+    }
+
+    impl Convert<Transform2d> for nalgebra::Matrix3<f32> {
+        fn convert(self) -> Transform2d {
+            Transform2d::row_major(
+                self[(0, 0)], self[(1, 0)],
+                self[(0, 1)], self[(1, 1)],
+                self[(0, 2)], self[(1, 2)],
+            )
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+#[test]
+fn test_mint_round_trip() {
+    use mint;
+
+    let p: mint::Point2<f32> = point(1.0, 2.0).convert();
+    assert_eq!((p.x, p.y), (1.0, 2.0));
+    let p: Point = p.convert();
+    assert_eq!(p, point(1.0, 2.0));
+
+    let t = Transform2d::create_translation(3.0, 4.0);
+    let m: mint::RowMatrix2x3<f32> = t.convert();
+    assert_eq!((m.z.x, m.z.y), (3.0, 4.0));
+    let t2: Transform2d = m.convert();
+    assert_eq!(t, t2);
+}
+
+#[cfg(feature = "cgmath")]
+#[test]
+fn test_cgmath_transform() {
+    use cgmath;
+    use cgmath::Transform;
+
+    let t = Transform2d::create_translation(3.0, 4.0);
+    let m: cgmath::Matrix3<f32> = t.convert();
+    let p = m.transform_point(cgmath::Point2::new(1.0, 1.0));
+    assert_eq!((p.x, p.y), (4.0, 5.0));
+    let t2: Transform2d = m.convert();
+    assert_eq!(t, t2);
+}
+
+#[cfg(feature = "nalgebra")]
+#[test]
+fn test_nalgebra_transform() {
+    use nalgebra;
+
+    let t = Transform2d::create_translation(3.0, 4.0);
+    let m: nalgebra::Matrix3<f32> = t.convert();
+    let p = m * nalgebra::Vector3::new(1.0, 1.0, 1.0);
+    assert_eq!((p.x, p.y), (4.0, 5.0));
+    let t2: Transform2d = m.convert();
+    assert_eq!(t, t2);
+}
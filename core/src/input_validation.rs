@@ -0,0 +1,178 @@
+//! Detecting invalid coordinates in the input of the builders and tessellators.
+//!
+//! A NaN or infinite coordinate, or a coordinate that is too large to be represented by the
+//! fixed point numbers of the tessellators, usually doesn't fail where it is introduced but
+//! much later (deep in the sweep of the fill tessellator for example), which makes the cause
+//! hard to find. The helpers of this module check the coordinates of each event as it is
+//! produced and identify the first invalid event by its index.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_core;
+//! # use lyon_core::FlattenedEvent;
+//! # use lyon_core::math::point;
+//! # use lyon_core::input_validation::*;
+//! # fn main() {
+//! let path = vec![
+//!     FlattenedEvent::MoveTo(point(0.0, 0.0)),
+//!     FlattenedEvent::LineTo(point(1.0e6, 0.0)),
+//!     FlattenedEvent::LineTo(point(0.0, 1.0)),
+//!     FlattenedEvent::Close,
+//! ];
+//! let mut events = ValidatingIter::new(path.into_iter(), MAX_FIXED_POINT_COORDINATE);
+//! let polygon: Vec<FlattenedEvent> = (&mut events).collect();
+//! if let Some(error) = events.error() {
+//!     println!("invalid coordinates in event {}: {:?}", error.event, error.kind);
+//! }
+//! # assert_eq!(polygon.len(), 1);
+//! # assert_eq!(events.error().unwrap().event, 1);
+//! # }
+//! ```
+
+use math::Point;
+use events::{PathEvent, FlattenedEvent};
+
+/// The largest coordinate that can be represented by the 16.16 fixed point numbers used
+/// by the tessellators.
+pub const MAX_FIXED_POINT_COORDINATE: f32 = 32767.0;
+
+/// What is wrong with a coordinate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum InvalidCoordinate {
+    NaN,
+    Infinite,
+    /// The magnitude of the coordinate is larger than the allowed maximum.
+    TooLarge,
+}
+
+/// An event with an invalid coordinate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InputError {
+    /// The index of the event in the sequence of events (starting at zero).
+    pub event: usize,
+    pub kind: InvalidCoordinate,
+}
+
+/// Checks that the coordinates of a point are finite and their magnitude is at most
+/// `max_magnitude`.
+pub fn validate_point(p: Point, max_magnitude: f32) -> Result<(), InvalidCoordinate> {
+    for &v in &[p.x, p.y] {
+        if v.is_nan() {
+            return Err(InvalidCoordinate::NaN);
+        }
+        if v.is_infinite() {
+            return Err(InvalidCoordinate::Infinite);
+        }
+        if v > max_magnitude || v < -max_magnitude {
+            return Err(InvalidCoordinate::TooLarge);
+        }
+    }
+
+    return Ok(());
+}
+
+/// Checks the points of a path event (including the control points).
+pub fn validate_path_event(event: &PathEvent, max_magnitude: f32) -> Result<(), InvalidCoordinate> {
+    match *event {
+        PathEvent::MoveTo(to) | PathEvent::LineTo(to) => validate_point(to, max_magnitude),
+        PathEvent::QuadraticTo(ctrl, to) => {
+            try!{ validate_point(ctrl, max_magnitude) };
+            validate_point(to, max_magnitude)
+        }
+        PathEvent::CubicTo(ctrl1, ctrl2, to) => {
+            try!{ validate_point(ctrl1, max_magnitude) };
+            try!{ validate_point(ctrl2, max_magnitude) };
+            validate_point(to, max_magnitude)
+        }
+        PathEvent::Close => Ok(()),
+    }
+}
+
+/// Checks the point of a flattened event.
+pub fn validate_flattened_event(event: &FlattenedEvent, max_magnitude: f32) -> Result<(), InvalidCoordinate> {
+    match *event {
+        FlattenedEvent::MoveTo(to) | FlattenedEvent::LineTo(to) => validate_point(to, max_magnitude),
+        FlattenedEvent::Close => Ok(()),
+    }
+}
+
+/// An iterator adapter that stops at the first flattened event with invalid coordinates.
+///
+/// The error can be retrieved with `error` once the iteration is done, iterating over
+/// `&mut ValidatingIter` makes it possible to hand the events over to a consumer and check
+/// the error afterwards.
+pub struct ValidatingIter<Iter> {
+    iter: Iter,
+    max_magnitude: f32,
+    index: usize,
+    error: Option<InputError>,
+}
+
+impl<Iter> ValidatingIter<Iter> {
+    pub fn new(iter: Iter, max_magnitude: f32) -> Self {
+        ValidatingIter {
+            iter: iter,
+            max_magnitude: max_magnitude,
+            index: 0,
+            error: None,
+        }
+    }
+
+    /// The first invalid event, if any.
+    pub fn error(&self) -> Option<InputError> { self.error }
+}
+
+impl<Iter: Iterator<Item = FlattenedEvent>> Iterator for ValidatingIter<Iter> {
+    type Item = FlattenedEvent;
+    fn next(&mut self) -> Option<FlattenedEvent> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        let event = match self.iter.next() {
+            Some(event) => event,
+            None => { return None; }
+        };
+        if let Err(kind) = validate_flattened_event(&event, self.max_magnitude) {
+            self.error = Some(InputError { event: self.index, kind: kind });
+            return None;
+        }
+        self.index += 1;
+
+        return Some(event);
+    }
+}
+
+#[cfg(test)]
+use math::point;
+
+#[test]
+fn test_validate_point() {
+    assert_eq!(validate_point(point(1.0, -2.0), 10.0), Ok(()));
+    assert_eq!(validate_point(point(::std::f32::NAN, 0.0), 10.0), Err(InvalidCoordinate::NaN));
+    assert_eq!(validate_point(point(0.0, -::std::f32::INFINITY), 10.0), Err(InvalidCoordinate::Infinite));
+    assert_eq!(validate_point(point(0.0, -11.0), 10.0), Err(InvalidCoordinate::TooLarge));
+}
+
+#[test]
+fn test_validating_iter() {
+    let events = [
+        FlattenedEvent::MoveTo(point(0.0, 0.0)),
+        FlattenedEvent::LineTo(point(1.0, 0.0)),
+        FlattenedEvent::Close,
+        FlattenedEvent::MoveTo(point(0.0, ::std::f32::NAN)),
+        FlattenedEvent::LineTo(point(1.0, 0.0)),
+    ];
+
+    let mut iter = ValidatingIter::new(events.iter().cloned(), MAX_FIXED_POINT_COORDINATE);
+    assert_eq!((&mut iter).count(), 3);
+    assert_eq!(iter.error(), Some(InputError { event: 3, kind: InvalidCoordinate::NaN }));
+
+    let mut iter = ValidatingIter::new(events[0..3].iter().cloned(), MAX_FIXED_POINT_COORDINATE);
+    assert_eq!((&mut iter).count(), 3);
+    assert_eq!(iter.error(), None);
+
+    let curve = PathEvent::CubicTo(point(0.0, 0.0), point(1.0e6, 0.0), point(1.0, 1.0));
+    assert_eq!(validate_path_event(&curve, MAX_FIXED_POINT_COORDINATE), Err(InvalidCoordinate::TooLarge));
+}
@@ -1,7 +1,8 @@
 use euclid;
 use fixed;
 
-pub use euclid::{Point2D, Vector2D, TypedPoint2D, Radians};
+pub use euclid::{Point2D, Vector2D, TypedPoint2D, TypedVector2D, TypedRect, TypedTransform2D};
+pub use euclid::{Radians, UnknownUnit};
 
 pub type Point = euclid::Point2D<f32>;
 pub type IntPoint = euclid::Point2D<i32>;
@@ -29,6 +30,13 @@ pub type IntVec3 = euclid::Vector3D<i32>;
 pub type Mat4 = euclid::Transform3D<f32>;
 pub type Transform2d = euclid::Transform2D<f32>;
 
+// Unit-tagged versions of the types above, to keep coordinates in different spaces
+// (for example local and device space) from being mixed up.
+pub type TypedPoint<Unit> = TypedPoint2D<f32, Unit>;
+pub type TypedVec2<Unit> = TypedVector2D<f32, Unit>;
+pub type TypedRectangle<Unit> = TypedRect<f32, Unit>;
+pub type TypedTransform<Src, Dst> = TypedTransform2D<f32, Src, Dst>;
+
 pub use euclid::{vec2, rect};
 pub use euclid::point2 as point;
 pub use euclid::size2 as size;
@@ -106,62 +106,73 @@ pub fn line_intersection(a1: Point, a2: Point, b1: Point, b2: Point) -> Option<P
     );
 }
 
+/// Returns the intersection of the segments `a1 b1` and `a2 b2` if it is strictly inside of
+/// both segments.
+///
+/// The points are converted to the fixed point numbers of the tessellators and the
+/// intersection is classified with exact integer arithmetic, like in the sweep of the fill
+/// tessellator, instead of comparing the parameters with an epsilon. If the segments are
+/// collinear and overlap, an endpoint of one segment that is strictly inside of the other
+/// one is returned.
 pub fn segment_intersection(a1: Vec2, b1: Vec2, a2: Vec2, b2: Vec2) -> Option<Vec2> {
-    let v1 = b1 - a1;
-    let v2 = b2 - a2;
-    if fuzzy_eq(v2, vec2(0.0, 0.0)) {
+    // The coordinates are 32 bits integers so the products below can't overflow.
+    let raw = |v: Vec2| (fixed(v.x).raw() as i128, fixed(v.y).raw() as i128);
+    let (a1x, a1y) = raw(a1);
+    let (b1x, b1y) = raw(b1);
+    let (a2x, a2y) = raw(a2);
+    let (b2x, b2y) = raw(b2);
+    let (v1x, v1y) = (b1x - a1x, b1y - a1y);
+    let (v2x, v2y) = (b2x - a2x, b2y - a2y);
+    if v2x == 0 && v2y == 0 {
         return None;
     }
 
-    let v1_cross_v2 = v1.cross(v2);
-    let a2_a1_cross_v1 = (a2 - a1).cross(v1);
-
-    if v1_cross_v2 == 0.0 {
-        if a2_a1_cross_v1 == 0.0 {
-
-            let v1_sqr_len = v1.square_length();
-            // check if a2 is between a1 and b1
-            let v1_dot_a2a1 = v1.dot(a2 - a1);
-            if v1_dot_a2a1 > 0.0 && v1_dot_a2a1 < v1_sqr_len {
-                return Some(a2);
-            }
-
-            // check if b2 is between a1 and b1
-            let v1_dot_b2a1 = v1.dot(b2 - a1);
-            if v1_dot_b2a1 > 0.0 && v1_dot_b2a1 < v1_sqr_len {
-                return Some(b2);
-            }
-
-            let v2_sqr_len = v2.square_length();
-            // check if a1 is between a2 and b2
-            let v2_dot_a1a2 = v2.dot(a1 - a2);
-            if v2_dot_a1a2 > 0.0 && v2_dot_a1a2 < v2_sqr_len {
-                return Some(a1);
-            }
-
-            // check if b1 is between a2 and b2
-            let v2_dot_b1a2 = v2.dot(b1 - a2);
-            if v2_dot_b1a2 > 0.0 && v2_dot_b1a2 < v2_sqr_len {
-                return Some(b1);
-            }
+    let (dx, dy) = (a2x - a1x, a2y - a1y);
+    let v1_cross_v2 = v1x * v2y - v1y * v2x;
+    let a2_a1_cross_v1 = dx * v1y - dy * v1x;
 
+    if v1_cross_v2 == 0 {
+        if a2_a1_cross_v1 != 0 {
+            // Parallel.
             return None;
         }
 
+        if strictly_inside((a2x, a2y), (a1x, a1y), (v1x, v1y)) {
+            return Some(a2);
+        }
+        if strictly_inside((b2x, b2y), (a1x, a1y), (v1x, v1y)) {
+            return Some(b2);
+        }
+        if strictly_inside((a1x, a1y), (a2x, a2y), (v2x, v2y)) {
+            return Some(a1);
+        }
+        if strictly_inside((b1x, b1y), (a2x, a2y), (v2x, v2y)) {
+            return Some(b1);
+        }
+
         return None;
     }
 
-    let t = (a2 - a1).cross(v2) / v1_cross_v2;
-    let u = a2_a1_cross_v1 / v1_cross_v2;
+    // The parameters on each segment are t / denom and u / denom.
+    let sign = if v1_cross_v2 > 0 { 1 } else { -1 };
+    let denom = v1_cross_v2 * sign;
+    let t = (dx * v2y - dy * v2x) * sign;
+    let u = a2_a1_cross_v1 * sign;
 
-    // TODO :(
-    if t > 0.00001 && t < 0.9999 && u > 0.00001 && u < 0.9999 {
-        return Some(a1 + (v1 * t));
+    if t > 0 && t < denom && u > 0 && u < denom {
+        return Some(a1 + (b1 - a1) * (t as f64 / denom as f64) as f32);
     }
 
     return None;
 }
 
+// Returns true if the point, known to be on the line of the segment, is strictly between
+// the endpoints of the segment.
+fn strictly_inside(p: (i128, i128), from: (i128, i128), v: (i128, i128)) -> bool {
+    let dot = (p.0 - from.0) * v.0 + (p.1 - from.1) * v.1;
+    return dot > 0 && dot < v.0 * v.0 + v.1 * v.1;
+}
+
 #[test]
 fn test_segment_intersection() {
 
@@ -214,6 +225,23 @@ fn test_segment_intersection() {
         vec2(0.0, 0.0), vec2(1.0, 0.0),
         vec2(0.0, 1.0), vec2(1.0, 1.0)
     ).is_none());
+
+    // Close to an endpoint but not on it.
+    let i = segment_intersection(
+        vec2(0.0, 0.0), vec2(1000.0, 0.0),
+        vec2(999.95, -1.0), vec2(999.95, 1.0)
+    ).unwrap();
+    assert!((i.x - 999.95).abs() < 0.001 && i.y == 0.0);
+
+    // Touching at an endpoint.
+    assert!(segment_intersection(
+        vec2(0.0, 0.0), vec2(1.0, 0.0),
+        vec2(1.0, 0.0), vec2(1.0, 1.0)
+    ).is_none());
+    assert!(segment_intersection(
+        vec2(0.0, 0.0), vec2(2.0, 0.0),
+        vec2(1.0, 0.0), vec2(1.0, 1.0)
+    ).is_none());
 }
 
 pub fn line_horizontal_intersection(a: Vec2, b: Vec2, y: f32) -> f32 {
@@ -321,3 +349,220 @@ fn test_compute_normal() {
         assert_almost_eq(compute_normal(vec2(f, 0.0), vec2(f * f, 0.0)), vec2(0.0, -1.0));
     }
 }
+
+/// Cohen-Sutherland outcode bit set when a point is on the left of a rectangle.
+pub const OUTCODE_LEFT: u8 = 1;
+/// Cohen-Sutherland outcode bit set when a point is on the right of a rectangle.
+pub const OUTCODE_RIGHT: u8 = 2;
+/// Cohen-Sutherland outcode bit set when a point is above a rectangle (smaller y).
+pub const OUTCODE_TOP: u8 = 4;
+/// Cohen-Sutherland outcode bit set when a point is below a rectangle (greater y).
+pub const OUTCODE_BOTTOM: u8 = 8;
+
+/// Computes the Cohen-Sutherland outcode of a point with respect to a rectangle.
+///
+/// The outcode is zero if the point is inside of the rectangle (edges included).
+pub fn rect_outcode(p: Point, rect: &Rect) -> u8 {
+    let mut code = 0;
+    if p.x < rect.min_x() {
+        code |= OUTCODE_LEFT;
+    } else if p.x > rect.max_x() {
+        code |= OUTCODE_RIGHT;
+    }
+    if p.y < rect.min_y() {
+        code |= OUTCODE_TOP;
+    } else if p.y > rect.max_y() {
+        code |= OUTCODE_BOTTOM;
+    }
+    return code;
+}
+
+/// Returns true if the segment is trivially outside of the rectangle, that is if both
+/// endpoints are on the same outer side of it.
+///
+/// This is a cheap test for culling, some segments that don't touch the rectangle (around
+/// its corners) are not rejected.
+pub fn segment_trivially_outside_rect(from: Point, to: Point, rect: &Rect) -> bool {
+    rect_outcode(from, rect) & rect_outcode(to, rect) != 0
+}
+
+/// Clips a segment against a rectangle (edges included).
+///
+/// Returns the range of the segment's parameter `t` (between 0 and 1) that is inside of the
+/// rectangle, or `None` if the segment doesn't touch the rectangle. The clipped segment goes
+/// from `from + (to - from) * t0` to `from + (to - from) * t1`.
+///
+/// Uses Cohen-Sutherland outcodes to accept or reject the trivial cases and the
+/// Liang-Barsky algorithm otherwise.
+pub fn clip_segment_to_rect(from: Point, to: Point, rect: &Rect) -> Option<(f32, f32)> {
+    let code_from = rect_outcode(from, rect);
+    let code_to = rect_outcode(to, rect);
+    if code_from | code_to == 0 {
+        return Some((0.0, 1.0));
+    }
+    if code_from & code_to != 0 {
+        return None;
+    }
+
+    let v = to - from;
+    let mut t0 = 0.0;
+    let mut t1 = 1.0;
+    let checks = [
+        (-v.x, from.x - rect.min_x()),
+        (v.x, rect.max_x() - from.x),
+        (-v.y, from.y - rect.min_y()),
+        (v.y, rect.max_y() - from.y),
+    ];
+    for &(p, q) in &checks {
+        if p == 0.0 {
+            // Parallel to this side.
+            if q < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let t = q / p;
+        if p < 0.0 {
+            if t > t1 {
+                return None;
+            }
+            if t > t0 {
+                t0 = t;
+            }
+        } else {
+            if t < t0 {
+                return None;
+            }
+            if t < t1 {
+                t1 = t;
+            }
+        }
+    }
+
+    return Some((t0, t1));
+}
+
+/// Returns true if the rectangles are equal within a tolerance, that is if each of their
+/// sides are at most `tolerance` away from the corresponding side of the other.
+pub fn fuzzy_eq_rect(a: &Rect, b: &Rect, tolerance: f32) -> bool {
+    (a.min_x() - b.min_x()).abs() <= tolerance
+        && (a.min_y() - b.min_y()).abs() <= tolerance
+        && (a.max_x() - b.max_x()).abs() <= tolerance
+        && (a.max_y() - b.max_y()).abs() <= tolerance
+}
+
+/// Returns true if the segment from `a_from` to `a_to` is equal to the segment from `b_from`
+/// to `b_to` within a tolerance.
+///
+/// The direction of the segments matters, use `fuzzy_eq_segment(a_from, a_to, b_to, b_from)`
+/// as well to ignore it.
+pub fn fuzzy_eq_segment(a_from: Point, a_to: Point, b_from: Point, b_to: Point, tolerance: f32) -> bool {
+    (a_from - b_from).length() <= tolerance && (a_to - b_to).length() <= tolerance
+}
+
+/// Returns true if the segment is inside of the rectangle grown by `margin` on each side.
+///
+/// A positive margin is permissive: segments along the sides of the rectangle, or slightly
+/// outside of it because of rounding errors, are contained. A negative margin is
+/// conservative: a segment that is contained can't touch the sides of the rectangle even
+/// with rounding errors, for example to skip clipping it. Since the rectangle is convex, only
+/// the ends of the segment need to be tested.
+pub fn rect_contains_segment(rect: &Rect, from: Point, to: Point, margin: f32) -> bool {
+    rect_contains_point_with_margin(rect, from, margin)
+        && rect_contains_point_with_margin(rect, to, margin)
+}
+
+/// Returns true if the rectangles overlap once `a` is grown by `margin` on each side.
+///
+/// Rectangles that only touch overlap. A negative margin requires the rectangles to overlap
+/// by more than its absolute value.
+pub fn rects_overlap_with_margin(a: &Rect, b: &Rect, margin: f32) -> bool {
+    a.min_x() - margin <= b.max_x()
+        && b.min_x() <= a.max_x() + margin
+        && a.min_y() - margin <= b.max_y()
+        && b.min_y() <= a.max_y() + margin
+}
+
+fn rect_contains_point_with_margin(rect: &Rect, p: Point, margin: f32) -> bool {
+    p.x >= rect.min_x() - margin
+        && p.x <= rect.max_x() + margin
+        && p.y >= rect.min_y() - margin
+        && p.y <= rect.max_y() + margin
+}
+
+#[test]
+fn test_rect_outcode() {
+    let r = rect(0.0, 0.0, 10.0, 10.0);
+    assert_eq!(rect_outcode(point(5.0, 5.0), &r), 0);
+    assert_eq!(rect_outcode(point(10.0, 0.0), &r), 0);
+    assert_eq!(rect_outcode(point(-1.0, 5.0), &r), OUTCODE_LEFT);
+    assert_eq!(rect_outcode(point(11.0, -1.0), &r), OUTCODE_RIGHT | OUTCODE_TOP);
+    assert_eq!(rect_outcode(point(5.0, 11.0), &r), OUTCODE_BOTTOM);
+
+    assert!(segment_trivially_outside_rect(point(-1.0, 0.0), point(-1.0, 10.0), &r));
+    assert!(!segment_trivially_outside_rect(point(-1.0, 5.0), point(11.0, 5.0), &r));
+}
+
+#[test]
+fn test_clip_segment_to_rect() {
+    let r = rect(0.0, 0.0, 10.0, 10.0);
+    assert_eq!(clip_segment_to_rect(point(1.0, 1.0), point(9.0, 9.0), &r), Some((0.0, 1.0)));
+    assert_eq!(clip_segment_to_rect(point(-10.0, 5.0), point(10.0, 5.0), &r), Some((0.5, 1.0)));
+    assert_eq!(clip_segment_to_rect(point(5.0, -10.0), point(5.0, 30.0), &r), Some((0.25, 0.5)));
+    assert_eq!(clip_segment_to_rect(point(-5.0, -5.0), point(15.0, 15.0), &r), Some((0.25, 0.75)));
+    // Outside on the same side.
+    assert_eq!(clip_segment_to_rect(point(-5.0, 0.0), point(-1.0, 10.0), &r), None);
+    // Passes next to a corner.
+    assert_eq!(clip_segment_to_rect(point(-5.0, 4.0), point(4.0, -5.0), &r), None);
+}
+
+#[test]
+fn test_fuzzy_eq_rect_and_segment() {
+    let r = rect(0.0, 0.0, 10.0, 10.0);
+    assert!(fuzzy_eq_rect(&r, &rect(0.001, -0.001, 10.0, 10.001), 0.01));
+    assert!(!fuzzy_eq_rect(&r, &rect(0.0, 0.0, 10.1, 10.0), 0.01));
+
+    let (a, b) = (point(0.0, 0.0), point(5.0, 5.0));
+    assert!(fuzzy_eq_segment(a, b, point(0.0, 0.001), point(5.001, 5.0), 0.01));
+    assert!(!fuzzy_eq_segment(a, b, b, a, 0.01));
+}
+
+#[test]
+fn test_rect_contains_segment() {
+    let r = rect(0.0, 0.0, 10.0, 10.0);
+
+    // Along a side.
+    let (from, to) = (point(0.0, 0.0), point(10.0, 0.0));
+    assert!(rect_contains_segment(&r, from, to, 0.0));
+    assert!(!rect_contains_segment(&r, from, to, -0.0001));
+
+    // Slightly outside because of a rounding error.
+    let (from, to) = (point(-0.00001, 5.0), point(5.0, 5.0));
+    assert!(!rect_contains_segment(&r, from, to, 0.0));
+    assert!(rect_contains_segment(&r, from, to, 0.001));
+
+    // Well inside.
+    let (from, to) = (point(1.0, 1.0), point(9.0, 5.0));
+    assert!(rect_contains_segment(&r, from, to, 0.0));
+    assert!(rect_contains_segment(&r, from, to, -0.5));
+    assert!(!rect_contains_segment(&r, from, to, -1.5));
+
+    // Crossing the rectangle.
+    assert!(!rect_contains_segment(&r, point(-5.0, 5.0), point(15.0, 5.0), 0.1));
+}
+
+#[test]
+fn test_rects_overlap_with_margin() {
+    let a = rect(0.0, 0.0, 10.0, 10.0);
+    // Touching.
+    assert!(rects_overlap_with_margin(&a, &rect(10.0, 0.0, 5.0, 5.0), 0.0));
+    assert!(!rects_overlap_with_margin(&a, &rect(10.0, 0.0, 5.0, 5.0), -0.001));
+    // Close.
+    let b = rect(10.5, 10.5, 5.0, 5.0);
+    assert!(!rects_overlap_with_margin(&a, &b, 0.0));
+    assert!(rects_overlap_with_margin(&a, &b, 1.0));
+    // Overlapping by one unit.
+    let c = rect(9.0, 2.0, 5.0, 5.0);
+    assert!(rects_overlap_with_margin(&a, &c, -0.5));
+    assert!(!rects_overlap_with_margin(&a, &c, -1.5));
+}
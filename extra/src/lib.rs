@@ -7,8 +7,11 @@ extern crate lyon_core as core;
 extern crate lyon_path as path;
 extern crate lyon_path_builder as path_builder;
 extern crate lyon_path_iterator as path_iterator;
+extern crate rand;
 
 pub mod rust_logo;
 //pub mod triangle_rasterizer;
 pub mod debugging;
 pub mod image;
+pub mod scanline_rasterizer;
+pub mod testing;
@@ -0,0 +1,329 @@
+//! A reference rasterizer for differential testing of the tessellators.
+//!
+//! The rasterizer evaluates the fill rule independently at each sample position, without any
+//! of the clever tricks that make real rasterizers fast, so that it is easy to convince
+//! oneself that it is correct. Rasterizing a path with [rasterize_path](fn.rasterize_path.html)
+//! and the tessellation of the same path with
+//! [rasterize_triangles](fn.rasterize_triangles.html), then comparing the two coverage
+//! bitmaps with [compare](fn.compare.html) gives a ground truth for the tessellators.
+//!
+//! Samples are placed on a regular grid of `samples x samples` positions in each pixel, and
+//! the coverage of a pixel is the fraction of its samples that are inside of the shape.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_extra;
+//! # extern crate lyon_core;
+//! # use lyon_core::{FlattenedEvent, FillRule};
+//! # use lyon_core::math::*;
+//! # use lyon_extra::scanline_rasterizer::*;
+//! # fn main() {
+//! # let path = vec![
+//! #     FlattenedEvent::MoveTo(point(8.0, 8.0)),
+//! #     FlattenedEvent::LineTo(point(56.0, 8.0)),
+//! #     FlattenedEvent::LineTo(point(56.0, 56.0)),
+//! #     FlattenedEvent::LineTo(point(8.0, 56.0)),
+//! #     FlattenedEvent::Close,
+//! # ];
+//! // The vertices and indices produced by a tessellator for the same path.
+//! let vertices = [point(8.0, 8.0), point(56.0, 8.0), point(56.0, 56.0), point(8.0, 56.0)];
+//! let indices = [0, 1, 2, 0, 2, 3];
+//!
+//! let reference = rasterize_path(path.into_iter(), FillRule::EvenOdd, 64, 64, 4);
+//! let result = rasterize_triangles(&vertices, &indices, 64, 64, 4);
+//! let diff = compare(&reference, &result);
+//! assert!(diff.max_difference <= 0.25);
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+
+use core::{FlattenedEvent, FillRule};
+use core::math::*;
+
+/// A grayscale image where each pixel stores the fraction of its area covered by a shape.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverageBitmap {
+    pub width: usize,
+    pub height: usize,
+    /// Coverage values between 0.0 and 1.0, row by row.
+    pub pixels: Vec<f32>,
+}
+
+impl CoverageBitmap {
+    pub fn new(width: usize, height: usize) -> Self {
+        CoverageBitmap {
+            width: width,
+            height: height,
+            pixels: vec![0.0; width * height],
+        }
+    }
+
+    pub fn coverage(&self, x: usize, y: usize) -> f32 { self.pixels[x + y * self.width] }
+
+    /// Writes the bitmap as an ascii-art string, useful to look at a failing test.
+    pub fn to_ascii(&self) -> String {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = self.coverage(x, y);
+                s.push(if c == 0.0 { '.' } else if c < 1.0 { '+' } else { '#' });
+            }
+            s.push('\n');
+        }
+        return s;
+    }
+}
+
+/// The result of comparing two coverage bitmaps.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BitmapDiff {
+    /// The largest per-pixel coverage difference.
+    pub max_difference: f32,
+    /// The number of pixels that don't have the same coverage.
+    pub differing_pixels: usize,
+    /// The sum of the absolute coverage differences.
+    pub total_difference: f32,
+}
+
+/// Rasterizes a flattened path, sub-paths being implicitly closed.
+pub fn rasterize_path<Iter>(
+    path: Iter,
+    fill_rule: FillRule,
+    width: usize,
+    height: usize,
+    samples: usize,
+) -> CoverageBitmap
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let edges = collect_edges(path);
+    let mut bitmap = CoverageBitmap::new(width, height);
+    let sample_weight = 1.0 / (samples * samples) as f32;
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+
+    for row in 0..height * samples {
+        let y = sample_position(row, samples);
+
+        // The winding number of a sample is the sum of the directions of the edges
+        // on its left.
+        crossings.clear();
+        for &(from, to) in &edges {
+            // Half-open interval so that a vertex shared by two edges is counted once.
+            let (winding, min_y, max_y) = if from.y < to.y {
+                (1, from.y, to.y)
+            } else {
+                (-1, to.y, from.y)
+            };
+            if y < min_y || y >= max_y {
+                continue;
+            }
+            let t = (y - from.y) / (to.y - from.y);
+            crossings.push((from.x + (to.x - from.x) * t, winding));
+        }
+        crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        for column in 0..width * samples {
+            let x = sample_position(column, samples);
+            let mut winding = 0;
+            for &(crossing_x, w) in &crossings {
+                if crossing_x > x {
+                    break;
+                }
+                winding += w;
+            }
+
+            let inside = match fill_rule {
+                FillRule::EvenOdd => winding % 2 != 0,
+                FillRule::NonZero => winding != 0,
+            };
+            if inside {
+                bitmap.pixels[column / samples + (row / samples) * width] += sample_weight;
+            }
+        }
+    }
+
+    return bitmap;
+}
+
+/// Rasterizes triangles, typically the output of a tessellator.
+///
+/// A sample is covered if it is inside of at least one triangle, so overlapping triangles
+/// don't increase the coverage (but they can hide a bug, see
+/// `lyon_tessellation::validation` to check for overlaps through the total area).
+pub fn rasterize_triangles(
+    vertices: &[Point],
+    indices: &[u16],
+    width: usize,
+    height: usize,
+    samples: usize,
+) -> CoverageBitmap {
+    let mut covered = vec![false; width * samples * height * samples];
+    let stride = width * samples;
+
+    for triangle in indices.chunks(3) {
+        if triangle.len() < 3 {
+            break;
+        }
+        let edges = [
+            (vertices[triangle[0] as usize], vertices[triangle[1] as usize]),
+            (vertices[triangle[1] as usize], vertices[triangle[2] as usize]),
+            (vertices[triangle[2] as usize], vertices[triangle[0] as usize]),
+        ];
+
+        for row in 0..height * samples {
+            let y = sample_position(row, samples);
+            let mut min_x = ::std::f32::MAX;
+            let mut max_x = ::std::f32::MIN;
+            for &(from, to) in &edges {
+                let (min_y, max_y) = if from.y < to.y { (from.y, to.y) } else { (to.y, from.y) };
+                if y < min_y || y >= max_y {
+                    continue;
+                }
+                let t = (y - from.y) / (to.y - from.y);
+                let x = from.x + (to.x - from.x) * t;
+                min_x = min_x.min(x);
+                max_x = max_x.max(x);
+            }
+
+            if min_x > max_x {
+                continue;
+            }
+
+            // Same convention as rasterize_path: the left edge is inclusive and the right
+            // edge exclusive.
+            for column in 0..width * samples {
+                let x = sample_position(column, samples);
+                if x >= min_x && x < max_x {
+                    covered[column + row * stride] = true;
+                }
+            }
+        }
+    }
+
+    let mut bitmap = CoverageBitmap::new(width, height);
+    let sample_weight = 1.0 / (samples * samples) as f32;
+    for row in 0..height * samples {
+        for column in 0..width * samples {
+            if covered[column + row * stride] {
+                bitmap.pixels[column / samples + (row / samples) * width] += sample_weight;
+            }
+        }
+    }
+
+    return bitmap;
+}
+
+/// Compares two coverage bitmaps of the same size.
+pub fn compare(a: &CoverageBitmap, b: &CoverageBitmap) -> BitmapDiff {
+    assert_eq!(a.width, b.width);
+    assert_eq!(a.height, b.height);
+
+    let mut diff = BitmapDiff {
+        max_difference: 0.0,
+        differing_pixels: 0,
+        total_difference: 0.0,
+    };
+
+    for (pa, pb) in a.pixels.iter().zip(b.pixels.iter()) {
+        let d = (pa - pb).abs();
+        // Coverage is accumulated in floating point, ignore tiny differences.
+        if d > 0.0001 {
+            diff.differing_pixels += 1;
+            diff.total_difference += d;
+            diff.max_difference = diff.max_difference.max(d);
+        }
+    }
+
+    return diff;
+}
+
+fn sample_position(i: usize, samples: usize) -> f32 { (i as f32 + 0.5) / samples as f32 }
+
+fn collect_edges<Iter: Iterator<Item = FlattenedEvent>>(path: Iter) -> Vec<(Point, Point)> {
+    let mut edges = Vec::new();
+    let mut first = point(0.0, 0.0);
+    let mut current = first;
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                if current != first {
+                    edges.push((current, first));
+                }
+                first = to;
+                current = to;
+            }
+            FlattenedEvent::LineTo(to) => {
+                edges.push((current, to));
+                current = to;
+            }
+            FlattenedEvent::Close => {
+                edges.push((current, first));
+                current = first;
+            }
+        }
+    }
+    if current != first {
+        edges.push((current, first));
+    }
+
+    return edges;
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[test]
+fn test_rasterize_square() {
+    let mut builder = Path::builder();
+    builder.move_to(point(1.0, 1.0));
+    builder.line_to(point(3.0, 1.0));
+    builder.line_to(point(3.0, 3.5));
+    builder.line_to(point(1.0, 3.5));
+    builder.close();
+    let path = builder.build();
+
+    let bitmap = rasterize_path(path.path_iter().flattened(0.1), FillRule::EvenOdd, 4, 4, 4);
+    assert_eq!(bitmap.coverage(0, 0), 0.0);
+    assert_eq!(bitmap.coverage(1, 1), 1.0);
+    assert_eq!(bitmap.coverage(2, 2), 1.0);
+    assert_eq!(bitmap.coverage(2, 3), 0.5);
+    assert_eq!(bitmap.coverage(3, 2), 0.0);
+
+    let vertices = [point(1.0, 1.0), point(3.0, 1.0), point(3.0, 3.5), point(1.0, 3.5)];
+    let indices = [0, 1, 2, 0, 2, 3];
+    let triangles = rasterize_triangles(&vertices, &indices, 4, 4, 4);
+    assert_eq!(compare(&bitmap, &triangles).differing_pixels, 0);
+
+    // Missing a triangle.
+    let triangles = rasterize_triangles(&vertices, &indices[..3], 4, 4, 4);
+    let diff = compare(&bitmap, &triangles);
+    assert!(diff.differing_pixels > 0);
+    assert!(diff.max_difference > 0.4);
+}
+
+#[test]
+fn test_rasterize_fill_rules() {
+    // Two nested squares with the same winding.
+    let mut builder = Path::builder();
+    for &(min, max) in &[(0.0, 4.0), (1.0, 3.0)] {
+        builder.move_to(point(min, min));
+        builder.line_to(point(max, min));
+        builder.line_to(point(max, max));
+        builder.line_to(point(min, max));
+        builder.close();
+    }
+    let path = builder.build();
+
+    let even_odd = rasterize_path(path.path_iter().flattened(0.1), FillRule::EvenOdd, 4, 4, 2);
+    let non_zero = rasterize_path(path.path_iter().flattened(0.1), FillRule::NonZero, 4, 4, 2);
+    assert_eq!(even_odd.coverage(0, 0), 1.0);
+    assert_eq!(even_odd.coverage(1, 1), 0.0);
+    assert_eq!(non_zero.coverage(1, 1), 1.0);
+    assert_eq!(compare(&even_odd, &non_zero).differing_pixels, 4);
+}
@@ -0,0 +1,273 @@
+//! Path generators for property testing.
+//!
+//! These are the inputs lyon uses to stress its own tessellators. They are exposed so that
+//! code built on top of lyon can run its pipeline against the same shapes.
+//!
+//! The random generators take the random number generator as a parameter, use a seeded one
+//! (for example `rand::XorShiftRng::from_seed`) to get reproducible test cases.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_extra;
+//! # extern crate lyon_path;
+//! # extern crate rand;
+//! # use rand::{SeedableRng, XorShiftRng};
+//! # use lyon_path::Path;
+//! # use lyon_extra::testing::*;
+//! # fn check_my_pipeline(_path: &Path) {}
+//! # fn main() {
+//! let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+//! for _ in 0..1000 {
+//!     let path = random_path(&mut rng, &RandomPathOptions::default());
+//!     check_my_pipeline(&path);
+//! }
+//! # }
+//! ```
+
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use core::math::*;
+use path::Path;
+use path_builder::{BaseBuilder, PathBuilder};
+
+/// Parameters for [random_path](fn.random_path.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RandomPathOptions {
+    /// The area in which the points are generated.
+    pub bounds: Rect,
+    /// Maximum number of sub-paths (at least one is generated).
+    pub max_sub_paths: u32,
+    /// Maximum number of edges per sub-path (at least two are generated).
+    pub max_edges: u32,
+    /// Whether to generate quadratic and cubic bézier segments in addition to line segments.
+    pub curves: bool,
+    /// If set, coordinates are snapped to a grid with this spacing, which makes overlapping
+    /// edges and coincident vertices a lot more likely.
+    pub grid: Option<f32>,
+}
+
+impl RandomPathOptions {
+    pub fn default() -> Self {
+        RandomPathOptions {
+            bounds: rect(0.0, 0.0, 100.0, 100.0),
+            max_sub_paths: 3,
+            max_edges: 20,
+            curves: false,
+            grid: Some(0.25),
+        }
+    }
+}
+
+/// Generates a random closed path, usually with lots of self-intersections.
+pub fn random_path<R: Rng>(rng: &mut R, options: &RandomPathOptions) -> Path {
+    let mut builder = Path::builder();
+
+    let num_sub_paths = rng.gen_range(1, options.max_sub_paths.max(1) + 1);
+    for _ in 0..num_sub_paths {
+        builder.move_to(random_point(rng, options));
+        let num_edges = rng.gen_range(2, options.max_edges.max(2) + 1);
+        for _ in 0..num_edges {
+            if options.curves {
+                match rng.gen_range(0, 3) {
+                    0 => {
+                        let ctrl = random_point(rng, options);
+                        let to = random_point(rng, options);
+                        builder.quadratic_bezier_to(ctrl, to);
+                        continue;
+                    }
+                    1 => {
+                        let ctrl1 = random_point(rng, options);
+                        let ctrl2 = random_point(rng, options);
+                        let to = random_point(rng, options);
+                        builder.cubic_bezier_to(ctrl1, ctrl2, to);
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            builder.line_to(random_point(rng, options));
+        }
+        builder.close();
+    }
+
+    return builder.build();
+}
+
+/// Generates a simple (not self-intersecting) random polygon by sorting random points
+/// around a center.
+pub fn random_simple_polygon<R: Rng>(rng: &mut R, center: Point, radius: f32, num_points: u32) -> Path {
+    let mut angles: Vec<f32> = (0..num_points.max(3)).map(|_| rng.gen_range(0.0, 2.0 * PI)).collect();
+    angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let mut builder = Path::builder();
+    for (i, &angle) in angles.iter().enumerate() {
+        let r = rng.gen_range(radius * 0.1, radius);
+        let p = center + vec2(angle.cos(), angle.sin()) * r;
+        if i == 0 {
+            builder.move_to(p);
+        } else {
+            builder.line_to(p);
+        }
+    }
+    builder.close();
+
+    return builder.build();
+}
+
+/// A star shape with `num_branches` branches.
+///
+/// The points alternate between the inner and outer radius, the first one being on the
+/// outer radius, straight above the center.
+pub fn star(center: Point, inner_radius: f32, outer_radius: f32, num_branches: u32) -> Path {
+    let num_points = num_branches.max(2) * 2;
+    let mut builder = Path::builder();
+    for i in 0..num_points {
+        let angle = i as f32 * 2.0 * PI / num_points as f32 - PI * 0.5;
+        let r = if i % 2 == 0 { outer_radius } else { inner_radius };
+        let p = center + vec2(angle.cos(), angle.sin()) * r;
+        if i == 0 {
+            builder.move_to(p);
+        } else {
+            builder.line_to(p);
+        }
+    }
+    builder.close();
+
+    return builder.build();
+}
+
+/// Concentric regular polygons, the largest one having a radius of `radius`.
+///
+/// If `alternate_winding` is true, every other ring goes in the opposite direction so that
+/// the rings alternate between filled and empty with both fill rules. Otherwise they all
+/// have the same winding, which only produces holes with the even-odd fill rule.
+pub fn nested_rings(
+    center: Point,
+    radius: f32,
+    num_rings: u32,
+    points_per_ring: u32,
+    alternate_winding: bool,
+) -> Path {
+    let points_per_ring = points_per_ring.max(3);
+    let mut builder = Path::builder();
+    for ring in 0..num_rings {
+        let r = radius * (num_rings - ring) as f32 / num_rings as f32;
+        let reverse = alternate_winding && ring % 2 == 1;
+        for i in 0..points_per_ring {
+            let mut angle = i as f32 * 2.0 * PI / points_per_ring as f32;
+            if reverse {
+                angle = -angle;
+            }
+            let p = center + vec2(angle.cos(), angle.sin()) * r;
+            if i == 0 {
+                builder.move_to(p);
+            } else {
+                builder.line_to(p);
+            }
+        }
+        builder.close();
+    }
+
+    return builder.build();
+}
+
+/// Generates adversarial shapes that are hard to handle robustly: nearly colinear points,
+/// vertices that are almost (or exactly) on other edges, duplicated vertices, overlapping
+/// edges and very thin spikes.
+///
+/// The generated paths live within `bounds`.
+pub fn near_degenerate<R: Rng>(rng: &mut R, bounds: Rect, num_edges: u32) -> Path {
+    let options = RandomPathOptions {
+        bounds: bounds,
+        grid: None,
+        ..RandomPathOptions::default()
+    };
+    let epsilon = bounds.size.width.max(bounds.size.height) * 0.000001;
+
+    let mut builder = Path::builder();
+    let first = random_point(rng, &options);
+    builder.move_to(first);
+    let mut points = vec![first];
+
+    for _ in 0..num_edges.max(2) {
+        let prev = points[points.len() - 1];
+        let p = match rng.gen_range(0, 5) {
+            // Almost on the line between two previous points.
+            0 if points.len() >= 2 => {
+                let a = points[rng.gen_range(0, points.len())];
+                let b = points[rng.gen_range(0, points.len())];
+                let t = rng.gen_range(-0.5, 1.5);
+                let offset = vec2(rng.gen_range(-epsilon, epsilon), rng.gen_range(-epsilon, epsilon));
+                a + (b - a) * t + offset
+            }
+            // A previous point, exactly.
+            1 => points[rng.gen_range(0, points.len())],
+            // Very close to a previous point.
+            2 => {
+                let offset = vec2(rng.gen_range(-epsilon, epsilon), rng.gen_range(-epsilon, epsilon));
+                points[rng.gen_range(0, points.len())] + offset
+            }
+            // A thin spike: same y as the previous point.
+            3 => point(rng.gen_range(bounds.min_x(), bounds.max_x()), prev.y),
+            _ => random_point(rng, &options),
+        };
+        builder.line_to(p);
+        points.push(p);
+    }
+    builder.close();
+
+    return builder.build();
+}
+
+fn random_point<R: Rng>(rng: &mut R, options: &RandomPathOptions) -> Point {
+    let b = options.bounds;
+    let mut p = point(rng.gen_range(b.min_x(), b.max_x()), rng.gen_range(b.min_y(), b.max_y()));
+    if let Some(grid) = options.grid {
+        p.x = (p.x / grid).round() * grid;
+        p.y = (p.y / grid).round() * grid;
+    }
+
+    return p;
+}
+
+#[cfg(test)]
+use rand::{SeedableRng, XorShiftRng};
+
+#[test]
+fn test_random_path() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let options = RandomPathOptions::default();
+    for _ in 0..100 {
+        let path = random_path(&mut rng, &options);
+        for p in path.points() {
+            assert!(options.bounds.contains(p) || p.x == 100.0 || p.y == 100.0);
+            assert_eq!((p.x * 4.0).fract(), 0.0);
+        }
+    }
+
+    // Same seed, same paths.
+    let mut rng1 = XorShiftRng::from_seed([5, 6, 7, 8]);
+    let mut rng2 = XorShiftRng::from_seed([5, 6, 7, 8]);
+    let a = random_path(&mut rng1, &options);
+    let b = random_path(&mut rng2, &options);
+    assert_eq!(a.points(), b.points());
+}
+
+#[test]
+fn test_shapes() {
+    let path = star(point(0.0, 0.0), 5.0, 10.0, 5);
+    assert_eq!(path.points().len(), 10);
+    assert!((path.points()[0] - point(0.0, -10.0)).length() < 0.0001);
+
+    let path = nested_rings(point(0.0, 0.0), 10.0, 3, 8, true);
+    assert_eq!(path.points().len(), 24);
+
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let path = random_simple_polygon(&mut rng, point(0.0, 0.0), 10.0, 12);
+    assert_eq!(path.points().len(), 12);
+    let path = near_degenerate(&mut rng, rect(0.0, 0.0, 10.0, 10.0), 20);
+    assert_eq!(path.points().len(), 21);
+}
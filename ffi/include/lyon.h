@@ -0,0 +1,113 @@
+/* This file is generated by cbindgen from lyon_ffi's sources. Do not edit. */
+
+#ifndef LYON_FFI_H
+#define LYON_FFI_H
+
+#include <stdint.h>
+#include <stdlib.h>
+#include <stdbool.h>
+
+typedef enum {
+    LyonStatus_Ok = 0,
+    LyonStatus_InvalidArgument = 1,
+    LyonStatus_TessellationError = 2,
+    LyonStatus_InternalError = 3,
+} LyonStatus;
+
+typedef enum {
+    LyonFillRule_EvenOdd = 0,
+    LyonFillRule_NonZero = 1,
+} LyonFillRule;
+
+typedef enum {
+    LyonLineCap_Butt = 0,
+    LyonLineCap_Square = 1,
+    LyonLineCap_Round = 2,
+} LyonLineCap;
+
+typedef enum {
+    LyonLineJoin_Miter = 0,
+    LyonLineJoin_MiterClip = 1,
+    LyonLineJoin_Round = 2,
+    LyonLineJoin_Bevel = 3,
+} LyonLineJoin;
+
+typedef struct LyonGeometry LyonGeometry;
+
+typedef struct LyonPath LyonPath;
+
+typedef struct LyonPathBuilder LyonPathBuilder;
+
+typedef struct {
+    float tolerance;
+    LyonFillRule fill_rule;
+} LyonFillOptions;
+
+typedef struct {
+    float tolerance;
+    float line_width;
+    LyonLineCap line_cap;
+    LyonLineJoin line_join;
+    float miter_limit;
+} LyonStrokeOptions;
+
+typedef struct {
+    float x;
+    float y;
+} LyonVertex;
+
+LyonFillOptions lyon_fill_options_default(void);
+
+void lyon_geometry_clear(LyonGeometry *geometry);
+
+void lyon_geometry_delete(LyonGeometry *geometry);
+
+uint32_t lyon_geometry_index_count(const LyonGeometry *geometry);
+
+const uint16_t *lyon_geometry_indices(const LyonGeometry *geometry);
+
+LyonGeometry *lyon_geometry_new(void);
+
+uint32_t lyon_geometry_vertex_count(const LyonGeometry *geometry);
+
+const LyonVertex *lyon_geometry_vertices(const LyonGeometry *geometry);
+
+LyonPath *lyon_path_builder_build(LyonPathBuilder *builder);
+
+void lyon_path_builder_close(LyonPathBuilder *builder);
+
+void lyon_path_builder_cubic_bezier_to(LyonPathBuilder *builder,
+                                       float ctrl1_x,
+                                       float ctrl1_y,
+                                       float ctrl2_x,
+                                       float ctrl2_y,
+                                       float x,
+                                       float y);
+
+void lyon_path_builder_delete(LyonPathBuilder *builder);
+
+void lyon_path_builder_line_to(LyonPathBuilder *builder, float x, float y);
+
+void lyon_path_builder_move_to(LyonPathBuilder *builder, float x, float y);
+
+LyonPathBuilder *lyon_path_builder_new(void);
+
+void lyon_path_builder_quadratic_bezier_to(LyonPathBuilder *builder,
+                                           float ctrl_x,
+                                           float ctrl_y,
+                                           float x,
+                                           float y);
+
+void lyon_path_delete(LyonPath *path);
+
+LyonStrokeOptions lyon_stroke_options_default(void);
+
+LyonStatus lyon_tessellate_fill(const LyonPath *path,
+                                const LyonFillOptions *options,
+                                LyonGeometry *output);
+
+LyonStatus lyon_tessellate_stroke(const LyonPath *path,
+                                  const LyonStrokeOptions *options,
+                                  LyonGeometry *output);
+
+#endif /* LYON_FFI_H */
@@ -0,0 +1,476 @@
+//! # Lyon C bindings
+//!
+//! Exposes path building, fill and stroke tessellation through a C ABI.
+//!
+//! The API is organized around three opaque handles:
+//!
+//! - `LyonPathBuilder`, created with `lyon_path_builder_new`,
+//! - `LyonPath`, created by consuming a builder with `lyon_path_builder_build`,
+//! - `LyonGeometry`, which receives the output of the tessellators as flat vertex and
+//!   index arrays (see `lyon_geometry_vertices` and `lyon_geometry_indices`).
+//!
+//! Every handle must be released with its corresponding `*_delete` function.
+//!
+//! The C header (`include/lyon.h`) is generated with cbindgen and checked in. It must be
+//! regenerated when the API changes, from the `ffi` directory:
+//!
+//! ```sh
+//! cbindgen --config cbindgen.toml --crate lyon_ffi --output include/lyon.h
+//! ```
+//!
+//! Panics are caught at the boundary, a tessellation that panics returns
+//! `LyonStatus_InternalError` and leaves the geometry as it was before the call.
+//!
+//! ```c
+//! LyonPathBuilder* builder = lyon_path_builder_new();
+//! lyon_path_builder_move_to(builder, 0.0, 0.0);
+//! lyon_path_builder_line_to(builder, 10.0, 0.0);
+//! lyon_path_builder_line_to(builder, 10.0, 10.0);
+//! lyon_path_builder_close(builder);
+//! LyonPath* path = lyon_path_builder_build(builder);
+//!
+//! LyonGeometry* geom = lyon_geometry_new();
+//! LyonFillOptions options = lyon_fill_options_default();
+//! if (lyon_tessellate_fill(path, &options, geom) == LyonStatus_Ok) {
+//!     upload(lyon_geometry_vertices(geom), lyon_geometry_vertex_count(geom),
+//!            lyon_geometry_indices(geom), lyon_geometry_index_count(geom));
+//! }
+//!
+//! lyon_geometry_delete(geom);
+//! lyon_path_delete(path);
+//! ```
+
+extern crate lyon;
+
+use lyon::math::*;
+use lyon::path::{Path, Builder};
+use lyon::path_builder::*;
+use lyon::path_iterator::*;
+use lyon::tessellation::geometry_builder::{VertexBuffers, BuffersBuilder, VertexConstructor};
+use lyon::tessellation::path_fill::{FillTessellator, FillOptions};
+use lyon::tessellation::path_stroke::{StrokeTessellator, StrokeOptions, LineCap, LineJoin};
+use lyon::tessellation::{FillVertex, StrokeVertex};
+
+use std::ptr;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Opaque handle to a path builder.
+pub struct LyonPathBuilder {
+    builder: Builder,
+}
+
+/// Opaque handle to an immutable path.
+pub struct LyonPath {
+    path: Path,
+}
+
+/// Opaque handle to the vertex and index buffers produced by the tessellators.
+pub struct LyonGeometry {
+    buffers: VertexBuffers<LyonVertex>,
+}
+
+/// The vertex layout written in `LyonGeometry`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LyonVertex {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LyonStatus {
+    Ok = 0,
+    /// One of the parameters is a null pointer.
+    InvalidArgument = 1,
+    /// The tessellator could not process the path.
+    TessellationError = 2,
+    /// The tessellator panicked, which is a bug in lyon.
+    InternalError = 3,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LyonFillRule {
+    EvenOdd = 0,
+    NonZero = 1,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LyonLineCap {
+    Butt = 0,
+    Square = 1,
+    Round = 2,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LyonLineJoin {
+    Miter = 0,
+    MiterClip = 1,
+    Round = 2,
+    Bevel = 3,
+}
+
+/// Parameters of the fill tessellator.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LyonFillOptions {
+    pub tolerance: f32,
+    pub fill_rule: LyonFillRule,
+}
+
+/// Parameters of the stroke tessellator.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LyonStrokeOptions {
+    pub tolerance: f32,
+    pub line_width: f32,
+    pub line_cap: LyonLineCap,
+    pub line_join: LyonLineJoin,
+    pub miter_limit: f32,
+}
+
+impl LyonFillOptions {
+    fn to_fill_options(&self) -> FillOptions {
+        let options = match self.fill_rule {
+            LyonFillRule::EvenOdd => FillOptions::even_odd(),
+            LyonFillRule::NonZero => FillOptions::non_zero(),
+        };
+
+        return options.with_tolerance(self.tolerance);
+    }
+}
+
+impl LyonStrokeOptions {
+    fn to_stroke_options(&self) -> StrokeOptions {
+        let cap = match self.line_cap {
+            LyonLineCap::Butt => LineCap::Butt,
+            LyonLineCap::Square => LineCap::Square,
+            LyonLineCap::Round => LineCap::Round,
+        };
+        let join = match self.line_join {
+            LyonLineJoin::Miter => LineJoin::Miter,
+            LyonLineJoin::MiterClip => LineJoin::MiterClip,
+            LyonLineJoin::Round => LineJoin::Round,
+            LyonLineJoin::Bevel => LineJoin::Bevel,
+        };
+
+        return StrokeOptions::default()
+            .with_tolerance(self.tolerance)
+            .with_line_width(self.line_width)
+            .with_line_cap(cap)
+            .with_line_join(join)
+            .with_miter_limit(self.miter_limit);
+    }
+}
+
+struct FillCtor;
+
+impl VertexConstructor<FillVertex, LyonVertex> for FillCtor {
+    fn new_vertex(&mut self, vertex: FillVertex) -> LyonVertex {
+        LyonVertex { x: vertex.position.x, y: vertex.position.y }
+    }
+}
+
+// The normals of the stroke vertices are relative to a width of 1.0.
+struct StrokeCtor {
+    line_width: f32,
+}
+
+impl VertexConstructor<StrokeVertex, LyonVertex> for StrokeCtor {
+    fn new_vertex(&mut self, vertex: StrokeVertex) -> LyonVertex {
+        let pos = vertex.position + vertex.normal * self.line_width;
+        LyonVertex { x: pos.x, y: pos.y }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn lyon_fill_options_default() -> LyonFillOptions {
+    LyonFillOptions {
+        tolerance: 0.1,
+        fill_rule: LyonFillRule::EvenOdd,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn lyon_stroke_options_default() -> LyonStrokeOptions {
+    LyonStrokeOptions {
+        tolerance: 0.1,
+        line_width: 1.0,
+        line_cap: LyonLineCap::Butt,
+        line_join: LyonLineJoin::Miter,
+        miter_limit: 10.0,
+    }
+}
+
+/// Creates a path builder. Must be consumed with `lyon_path_builder_build` or released
+/// with `lyon_path_builder_delete`.
+#[no_mangle]
+pub extern "C" fn lyon_path_builder_new() -> *mut LyonPathBuilder {
+    Box::into_raw(Box::new(LyonPathBuilder { builder: Path::builder() }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lyon_path_builder_delete(builder: *mut LyonPathBuilder) {
+    if !builder.is_null() {
+        drop(Box::from_raw(builder));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lyon_path_builder_move_to(builder: *mut LyonPathBuilder, x: f32, y: f32) {
+    if let Some(b) = builder.as_mut() {
+        b.builder.move_to(point(x, y));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lyon_path_builder_line_to(builder: *mut LyonPathBuilder, x: f32, y: f32) {
+    if let Some(b) = builder.as_mut() {
+        b.builder.line_to(point(x, y));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lyon_path_builder_quadratic_bezier_to(
+    builder: *mut LyonPathBuilder,
+    ctrl_x: f32, ctrl_y: f32,
+    x: f32, y: f32,
+) {
+    if let Some(b) = builder.as_mut() {
+        b.builder.quadratic_bezier_to(point(ctrl_x, ctrl_y), point(x, y));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lyon_path_builder_cubic_bezier_to(
+    builder: *mut LyonPathBuilder,
+    ctrl1_x: f32, ctrl1_y: f32,
+    ctrl2_x: f32, ctrl2_y: f32,
+    x: f32, y: f32,
+) {
+    if let Some(b) = builder.as_mut() {
+        b.builder.cubic_bezier_to(point(ctrl1_x, ctrl1_y), point(ctrl2_x, ctrl2_y), point(x, y));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lyon_path_builder_close(builder: *mut LyonPathBuilder) {
+    if let Some(b) = builder.as_mut() {
+        b.builder.close();
+    }
+}
+
+/// Consumes the builder and returns the path. The builder handle must not be used after
+/// this call.
+#[no_mangle]
+pub unsafe extern "C" fn lyon_path_builder_build(builder: *mut LyonPathBuilder) -> *mut LyonPath {
+    if builder.is_null() {
+        return ptr::null_mut();
+    }
+    let builder = *Box::from_raw(builder);
+
+    return Box::into_raw(Box::new(LyonPath { path: builder.builder.build() }));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lyon_path_delete(path: *mut LyonPath) {
+    if !path.is_null() {
+        drop(Box::from_raw(path));
+    }
+}
+
+/// Creates empty vertex and index buffers to pass to the tessellation functions.
+#[no_mangle]
+pub extern "C" fn lyon_geometry_new() -> *mut LyonGeometry {
+    Box::into_raw(Box::new(LyonGeometry { buffers: VertexBuffers::new() }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lyon_geometry_delete(geometry: *mut LyonGeometry) {
+    if !geometry.is_null() {
+        drop(Box::from_raw(geometry));
+    }
+}
+
+/// Removes the content of the buffers while retaining the allocated memory.
+#[no_mangle]
+pub unsafe extern "C" fn lyon_geometry_clear(geometry: *mut LyonGeometry) {
+    if let Some(geom) = geometry.as_mut() {
+        geom.buffers.vertices.clear();
+        geom.buffers.indices.clear();
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lyon_geometry_vertex_count(geometry: *const LyonGeometry) -> u32 {
+    match geometry.as_ref() {
+        Some(geom) => geom.buffers.vertices.len() as u32,
+        None => 0,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn lyon_geometry_index_count(geometry: *const LyonGeometry) -> u32 {
+    match geometry.as_ref() {
+        Some(geom) => geom.buffers.indices.len() as u32,
+        None => 0,
+    }
+}
+
+/// Returns a pointer to the vertices. It is invalidated by the next call that modifies
+/// the geometry.
+#[no_mangle]
+pub unsafe extern "C" fn lyon_geometry_vertices(geometry: *const LyonGeometry) -> *const LyonVertex {
+    match geometry.as_ref() {
+        Some(geom) => geom.buffers.vertices.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Returns a pointer to the indices. It is invalidated by the next call that modifies
+/// the geometry.
+#[no_mangle]
+pub unsafe extern "C" fn lyon_geometry_indices(geometry: *const LyonGeometry) -> *const u16 {
+    match geometry.as_ref() {
+        Some(geom) => geom.buffers.indices.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Tessellates the fill of a path and appends the result to the geometry.
+#[no_mangle]
+pub unsafe extern "C" fn lyon_tessellate_fill(
+    path: *const LyonPath,
+    options: *const LyonFillOptions,
+    output: *mut LyonGeometry,
+) -> LyonStatus {
+    let (path, options, geom) = match (path.as_ref(), options.as_ref(), output.as_mut()) {
+        (Some(p), Some(o), Some(g)) => (p, o, g),
+        _ => { return LyonStatus::InvalidArgument; }
+    };
+
+    return catch_tessellation_panic(geom, |buffers| {
+        FillTessellator::new().tessellate_path(
+            path.path.path_iter().flattened(options.tolerance),
+            &options.to_fill_options(),
+            &mut BuffersBuilder::new(buffers, FillCtor)
+        ).is_ok()
+    });
+}
+
+/// Tessellates the stroke of a path and appends the result to the geometry.
+#[no_mangle]
+pub unsafe extern "C" fn lyon_tessellate_stroke(
+    path: *const LyonPath,
+    options: *const LyonStrokeOptions,
+    output: *mut LyonGeometry,
+) -> LyonStatus {
+    let (path, options, geom) = match (path.as_ref(), options.as_ref(), output.as_mut()) {
+        (Some(p), Some(o), Some(g)) => (p, o, g),
+        _ => { return LyonStatus::InvalidArgument; }
+    };
+
+    return catch_tessellation_panic(geom, |buffers| {
+        StrokeTessellator::new().tessellate(
+            path.path.path_iter().flattened(options.tolerance),
+            &options.to_stroke_options(),
+            &mut BuffersBuilder::new(buffers, StrokeCtor { line_width: options.line_width })
+        ).is_ok()
+    });
+}
+
+// Unwinding across the C boundary is undefined behavior, so panics are turned into an error
+// and the partial output of the tessellation is removed.
+fn catch_tessellation_panic<F>(geom: &mut LyonGeometry, tessellate: F) -> LyonStatus
+where
+    F: FnOnce(&mut VertexBuffers<LyonVertex>) -> bool,
+{
+    let num_vertices = geom.buffers.vertices.len();
+    let num_indices = geom.buffers.indices.len();
+    let buffers = &mut geom.buffers;
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| tessellate(&mut *buffers)));
+
+    return match result {
+        Ok(true) => LyonStatus::Ok,
+        Ok(false) => LyonStatus::TessellationError,
+        Err(_) => {
+            buffers.vertices.truncate(num_vertices);
+            buffers.indices.truncate(num_indices);
+            LyonStatus::InternalError
+        }
+    };
+}
+
+#[cfg(test)]
+unsafe fn build_square() -> *mut LyonPath {
+    let builder = lyon_path_builder_new();
+    lyon_path_builder_move_to(builder, 0.0, 0.0);
+    lyon_path_builder_line_to(builder, 1.0, 0.0);
+    lyon_path_builder_line_to(builder, 1.0, 1.0);
+    lyon_path_builder_line_to(builder, 0.0, 1.0);
+    lyon_path_builder_close(builder);
+    return lyon_path_builder_build(builder);
+}
+
+#[test]
+fn test_fill_square() {
+    unsafe {
+        let path = build_square();
+        let geom = lyon_geometry_new();
+        let options = lyon_fill_options_default();
+
+        assert_eq!(lyon_tessellate_fill(path, &options, geom), LyonStatus::Ok);
+        assert_eq!(lyon_geometry_vertex_count(geom), 4);
+        assert_eq!(lyon_geometry_index_count(geom), 6);
+
+        lyon_geometry_clear(geom);
+        assert_eq!(lyon_geometry_vertex_count(geom), 0);
+
+        lyon_geometry_delete(geom);
+        lyon_path_delete(path);
+    }
+}
+
+#[test]
+fn test_stroke_square() {
+    unsafe {
+        let path = build_square();
+        let geom = lyon_geometry_new();
+        let mut options = lyon_stroke_options_default();
+        options.line_width = 4.0;
+
+        assert_eq!(lyon_tessellate_stroke(path, &options, geom), LyonStatus::Ok);
+        assert!(lyon_geometry_index_count(geom) > 0);
+        assert_eq!(lyon_geometry_index_count(geom) % 3, 0);
+
+        // The stroke extends by half of the width on each side of the square's outline.
+        let vertices = ::std::slice::from_raw_parts(
+            lyon_geometry_vertices(geom),
+            lyon_geometry_vertex_count(geom) as usize,
+        );
+        let min_x = vertices.iter().fold(0.0f32, |m, v| m.min(v.x));
+        let max_x = vertices.iter().fold(0.0f32, |m, v| m.max(v.x));
+        assert!((min_x + 2.0).abs() < 0.0001);
+        assert!((max_x - 3.0).abs() < 0.0001);
+
+        lyon_geometry_delete(geom);
+        lyon_path_delete(path);
+    }
+}
+
+#[test]
+fn test_null_arguments() {
+    unsafe {
+        let options = lyon_fill_options_default();
+        assert_eq!(
+            lyon_tessellate_fill(ptr::null(), &options, ptr::null_mut()),
+            LyonStatus::InvalidArgument
+        );
+        assert!(lyon_path_builder_build(ptr::null_mut()).is_null());
+        assert!(lyon_geometry_vertices(ptr::null()).is_null());
+    }
+}
@@ -40,9 +40,22 @@
 //!
 
 extern crate lyon_core as core;
+extern crate lyon_bezier as bezier;
 extern crate lyon_path_builder as path_builder;
 extern crate lyon_path_iterator as path_iterator;
 
 mod path;
+mod typed;
+mod quantized;
+mod queries;
+mod segment_index;
+mod polygon;
+mod overlap;
 
 pub use path::*;
+pub use typed::*;
+pub use quantized::*;
+pub use queries::*;
+pub use segment_index::*;
+pub use polygon::*;
+pub use overlap::*;
@@ -0,0 +1,306 @@
+//! Overlap tests between the fills of two paths.
+//!
+//! These answer yes or no questions (do two shapes collide, is a shape inside of another one)
+//! without computing the boolean operation itself.
+//!
+//! The paths are flattened with a given tolerance and their sub-paths are implicitly closed.
+//! The fills use the even-odd rule. The edges of the two paths are tested against each other
+//! with a sweep over the x axis, so that only the edges whose bounding rectangles overlap are
+//! compared. When the outlines don't cross, one point per sub-path is enough to tell whether
+//! a path is inside of the other one.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_core;
+//! # use lyon_path::*;
+//! # use lyon_path_builder::*;
+//! # use lyon_core::math::*;
+//! # fn main() {
+//! # let square = |x: f32, y: f32| {
+//! #     let mut builder = Path::builder();
+//! #     builder.move_to(point(x, y));
+//! #     builder.line_to(point(x + 10.0, y));
+//! #     builder.line_to(point(x + 10.0, y + 10.0));
+//! #     builder.line_to(point(x, y + 10.0));
+//! #     builder.close();
+//! #     builder.build()
+//! # };
+//! # let player = square(0.0, 0.0);
+//! # let wall = square(5.0, 5.0);
+//! if paths_intersect(player.iter(), wall.iter(), 0.1) {
+//!     // ...
+//! }
+//! # }
+//! ```
+
+use bezier::{LineSegment, QuadraticBezierSegment, CubicBezierSegment};
+use core::PathEvent;
+use core::math::*;
+use core::math_utils::rects_overlap_with_margin;
+
+/// Returns true if the fills of two paths overlap or if their outlines touch.
+pub fn paths_intersect<A, B>(a: A, b: B, tolerance: f32) -> bool
+where
+    A: Iterator<Item = PathEvent>,
+    B: Iterator<Item = PathEvent>,
+{
+    let a = FlattenedRings::new(a, tolerance);
+    let b = FlattenedRings::new(b, tolerance);
+    if a.edges.is_empty() || b.edges.is_empty() || !rects_overlap_with_margin(&a.bounds, &b.bounds, 0.0) {
+        return false;
+    }
+
+    if outlines_touch(&a.edges, &b.edges) {
+        return true;
+    }
+
+    return a.ring_starts.iter().any(|&p| b.fill_contains(p)) ||
+        b.ring_starts.iter().any(|&p| a.fill_contains(p));
+}
+
+/// Returns true if the fill of `b` is strictly inside of the fill of `a`.
+///
+/// Outlines that touch are not considered inside of each other. An empty path is not inside
+/// of any path.
+pub fn path_contains_path<A, B>(a: A, b: B, tolerance: f32) -> bool
+where
+    A: Iterator<Item = PathEvent>,
+    B: Iterator<Item = PathEvent>,
+{
+    let a = FlattenedRings::new(a, tolerance);
+    let b = FlattenedRings::new(b, tolerance);
+    if a.edges.is_empty() || b.edges.is_empty() || !rect_contains_rect(&a.bounds, &b.bounds) {
+        return false;
+    }
+
+    if outlines_touch(&a.edges, &b.edges) {
+        return false;
+    }
+
+    // Without crossings, b is inside of a unless a has a hole (or any other sub-path) inside
+    // of b.
+    return b.ring_starts.iter().all(|&p| a.fill_contains(p)) &&
+        !a.ring_starts.iter().any(|&p| b.fill_contains(p));
+}
+
+struct FlattenedRings {
+    edges: Vec<LineSegment>,
+    ring_starts: Vec<Point>,
+    bounds: Rect,
+}
+
+impl FlattenedRings {
+    fn new<Iter: Iterator<Item = PathEvent>>(path: Iter, tolerance: f32) -> Self {
+        let mut rings = FlattenedRings {
+            edges: Vec::new(),
+            ring_starts: Vec::new(),
+            bounds: Rect::zero(),
+        };
+
+        let mut first = point(0.0, 0.0);
+        let mut current = point(0.0, 0.0);
+        let mut edges = Vec::new();
+        for evt in path {
+            match evt {
+                PathEvent::MoveTo(to) => {
+                    // Close the previous sub-path.
+                    add_edge(&mut edges, &mut current, first);
+                    first = to;
+                    current = to;
+                    rings.ring_starts.push(to);
+                }
+                PathEvent::LineTo(to) => {
+                    add_edge(&mut edges, &mut current, to);
+                }
+                PathEvent::QuadraticTo(ctrl, to) => {
+                    QuadraticBezierSegment { from: current, ctrl: ctrl, to: to }
+                        .flattened_for_each(tolerance, &mut |p| { add_edge(&mut edges, &mut current, p); });
+                }
+                PathEvent::CubicTo(ctrl1, ctrl2, to) => {
+                    CubicBezierSegment { from: current, ctrl1: ctrl1, ctrl2: ctrl2, to: to }
+                        .flattened_for_each(tolerance, &mut |p| { add_edge(&mut edges, &mut current, p); });
+                }
+                PathEvent::Close => {
+                    add_edge(&mut edges, &mut current, first);
+                }
+            }
+        }
+        add_edge(&mut edges, &mut current, first);
+        rings.edges = edges;
+
+        rings.edges.retain(|edge| edge.from != edge.to);
+
+        // Rect::union ignores empty rectangles, which horizontal and vertical edges have.
+        let mut min: Point = point(::std::f32::MAX, ::std::f32::MAX);
+        let mut max: Point = point(::std::f32::MIN, ::std::f32::MIN);
+        for edge in &rings.edges {
+            for p in &[edge.from, edge.to] {
+                min = point(min.x.min(p.x), min.y.min(p.y));
+                max = point(max.x.max(p.x), max.y.max(p.y));
+            }
+        }
+        if !rings.edges.is_empty() {
+            rings.bounds = rect(min.x, min.y, max.x - min.x, max.y - min.y);
+        }
+
+        return rings;
+    }
+
+    // Even-odd point in polygon test with half-open edges.
+    fn fill_contains(&self, p: Point) -> bool {
+        let mut inside = false;
+        for edge in &self.edges {
+            let (a, b) = (edge.from, edge.to);
+            if (a.y > p.y) != (b.y > p.y) {
+                let x = a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y);
+                if x > p.x {
+                    inside = !inside;
+                }
+            }
+        }
+
+        return inside;
+    }
+}
+
+fn add_edge(edges: &mut Vec<LineSegment>, current: &mut Point, to: Point) {
+    edges.push(LineSegment { from: *current, to: to });
+    *current = to;
+}
+
+fn rect_contains_rect(a: &Rect, b: &Rect) -> bool {
+    a.min_x() <= b.min_x() && b.max_x() <= a.max_x() && a.min_y() <= b.min_y() && b.max_y() <= a.max_y()
+}
+
+// Sweep and prune over the x axis.
+fn outlines_touch(a: &[LineSegment], b: &[LineSegment]) -> bool {
+    let mut events: Vec<(f32, f32, bool, usize)> = Vec::with_capacity(a.len() + b.len());
+    for (i, edge) in a.iter().enumerate() {
+        events.push((edge.from.x.min(edge.to.x), edge.from.x.max(edge.to.x), true, i));
+    }
+    for (i, edge) in b.iter().enumerate() {
+        events.push((edge.from.x.min(edge.to.x), edge.from.x.max(edge.to.x), false, i));
+    }
+    events.sort_by(|e1, e2| e1.0.partial_cmp(&e2.0).unwrap());
+
+    let mut active_a: Vec<(f32, usize)> = Vec::new();
+    let mut active_b: Vec<(f32, usize)> = Vec::new();
+    for &(min_x, max_x, in_a, i) in &events {
+        active_a.retain(|&(end, _)| end >= min_x);
+        active_b.retain(|&(end, _)| end >= min_x);
+        let (edge, others, other_edges) = if in_a {
+            (&a[i], &active_b, b)
+        } else {
+            (&b[i], &active_a, a)
+        };
+        for &(_, j) in others {
+            if segments_touch(edge, &other_edges[j]) {
+                return true;
+            }
+        }
+        if in_a {
+            active_a.push((max_x, i));
+        } else {
+            active_b.push((max_x, i));
+        }
+    }
+
+    return false;
+}
+
+// Intersection test that includes the end points and overlapping colinear segments.
+fn segments_touch(s1: &LineSegment, s2: &LineSegment) -> bool {
+    let orient = |a: Point, b: Point, c: Point| (b - a).cross(c - a);
+    let within = |s: &LineSegment, p: Point| {
+        p.x >= s.from.x.min(s.to.x) && p.x <= s.from.x.max(s.to.x) &&
+            p.y >= s.from.y.min(s.to.y) && p.y <= s.from.y.max(s.to.y)
+    };
+
+    let d1 = orient(s2.from, s2.to, s1.from);
+    let d2 = orient(s2.from, s2.to, s1.to);
+    let d3 = orient(s1.from, s1.to, s2.from);
+    let d4 = orient(s1.from, s1.to, s2.to);
+
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0)) &&
+        ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0)) {
+        return true;
+    }
+
+    return (d1 == 0.0 && within(s2, s1.from)) ||
+        (d2 == 0.0 && within(s2, s1.to)) ||
+        (d3 == 0.0 && within(s1, s2.from)) ||
+        (d4 == 0.0 && within(s1, s2.to));
+}
+
+#[cfg(test)]
+use path::{Path, Builder};
+#[cfg(test)]
+use path_builder::{BaseBuilder, PathBuilder};
+
+#[cfg(test)]
+fn square(builder: &mut Builder, x: f32, y: f32, size: f32) {
+    builder.move_to(point(x, y));
+    builder.line_to(point(x + size, y));
+    builder.line_to(point(x + size, y + size));
+    builder.line_to(point(x, y + size));
+    builder.close();
+}
+
+#[cfg(test)]
+fn squares(squares: &[(f32, f32, f32)]) -> Path {
+    let mut builder = Path::builder();
+    for &(x, y, size) in squares {
+        square(&mut builder, x, y, size);
+    }
+
+    return builder.build();
+}
+
+#[test]
+fn test_paths_intersect() {
+    let a = squares(&[(0.0, 0.0, 10.0)]);
+
+    // Crossing outlines.
+    assert!(paths_intersect(a.iter(), squares(&[(5.0, 5.0, 10.0)]).iter(), 0.1));
+    // Disjoint.
+    assert!(!paths_intersect(a.iter(), squares(&[(11.0, 0.0, 10.0)]).iter(), 0.1));
+    assert!(!paths_intersect(a.iter(), squares(&[(5.0, 11.0, 1.0)]).iter(), 0.1));
+    // Touching.
+    assert!(paths_intersect(a.iter(), squares(&[(10.0, 2.0, 3.0)]).iter(), 0.1));
+    // Inside of each other.
+    assert!(paths_intersect(a.iter(), squares(&[(2.0, 2.0, 1.0)]).iter(), 0.1));
+    assert!(paths_intersect(squares(&[(2.0, 2.0, 1.0)]).iter(), a.iter(), 0.1));
+    // In a hole.
+    let ring = squares(&[(0.0, 0.0, 10.0), (2.0, 2.0, 6.0)]);
+    assert!(!paths_intersect(ring.iter(), squares(&[(3.0, 3.0, 1.0)]).iter(), 0.1));
+    assert!(paths_intersect(ring.iter(), squares(&[(1.0, 3.0, 0.5)]).iter(), 0.1));
+    // Empty paths don't intersect anything.
+    assert!(!paths_intersect(a.iter(), Path::new().iter(), 0.1));
+
+    // A curve bulging into a square, with a sub-path that isn't closed.
+    let mut builder = Path::builder();
+    builder.move_to(point(-5.0, 0.0));
+    builder.quadratic_bezier_to(point(10.0, 5.0), point(-5.0, 10.0));
+    let bulge = builder.build();
+    assert!(paths_intersect(bulge.iter(), a.iter(), 0.1));
+    assert!(!paths_intersect(bulge.iter(), squares(&[(3.0, 0.0, 10.0)]).iter(), 0.1));
+}
+
+#[test]
+fn test_path_contains_path() {
+    let a = squares(&[(0.0, 0.0, 10.0)]);
+    assert!(path_contains_path(a.iter(), squares(&[(2.0, 2.0, 1.0)]).iter(), 0.1));
+    assert!(!path_contains_path(squares(&[(2.0, 2.0, 1.0)]).iter(), a.iter(), 0.1));
+    assert!(!path_contains_path(a.iter(), squares(&[(5.0, 5.0, 10.0)]).iter(), 0.1));
+    assert!(!path_contains_path(a.iter(), squares(&[(0.0, 2.0, 1.0)]).iter(), 0.1));
+
+    // A hole of a inside of b.
+    let ring = squares(&[(0.0, 0.0, 10.0), (4.0, 4.0, 2.0)]);
+    assert!(!path_contains_path(ring.iter(), squares(&[(3.0, 3.0, 4.0)]).iter(), 0.1));
+    assert!(path_contains_path(ring.iter(), squares(&[(1.0, 1.0, 2.0)]).iter(), 0.1));
+    // b is also made of several sub-paths.
+    assert!(path_contains_path(a.iter(), squares(&[(1.0, 1.0, 2.0), (6.0, 6.0, 2.0)]).iter(), 0.1));
+}
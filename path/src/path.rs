@@ -1,7 +1,7 @@
 use path_builder::{BaseBuilder, PathBuilder, SvgPathBuilder, FlatteningBuilder};
-use path_iterator::PathStateIter;
+use path_iterator::{PathStateIter, PathIterator};
 
-use core::PathEvent;
+use core::{PathEvent, FlattenedEvent};
 use core::math::*;
 
 use std::iter::IntoIterator;
@@ -64,11 +64,21 @@ impl Path {
 
     pub fn path_iter(&self) -> PathStateIter<PathIter> { PathStateIter::new(self.iter()) }
 
+    /// Iterates over the path in reverse order, see [ReversedPathIter](struct.ReversedPathIter.html).
+    pub fn reversed_iter(&self) -> ReversedPathIter { ReversedPathIter::new(&self.points[..], &self.verbs[..]) }
+
     pub fn points(&self) -> &[Point] { &self.points[..] }
 
     pub fn mut_points(&mut self) -> &mut [Point] { &mut self.points[..] }
 
     pub fn verbs(&self) -> &[Verb] { &self.verbs[..] }
+
+    /// Flattens the path and returns a loop of points per sub-path.
+    ///
+    /// See [PathSlice::flattened_outline](struct.PathSlice.html#method.flattened_outline).
+    pub fn flattened_outline(&self, tolerance: f32) -> Vec<Vec<Point>> {
+        self.as_slice().flattened_outline(tolerance)
+    }
 }
 
 impl<'l> IntoIterator for &'l Path {
@@ -91,9 +101,80 @@ impl<'l> PathSlice<'l> {
 
     pub fn path_iter(&self) -> PathStateIter<PathIter> { PathStateIter::new(self.iter()) }
 
+    /// Iterates over the path in reverse order, see [ReversedPathIter](struct.ReversedPathIter.html).
+    pub fn reversed_iter(&self) -> ReversedPathIter { ReversedPathIter::new(self.points, self.verbs) }
+
     pub fn points(&self) -> &[Point] { self.points }
 
     pub fn verbs(&self) -> &[Verb] { self.verbs }
+
+    /// Flattens the path and returns a loop of points per sub-path.
+    ///
+    /// The curves are approximated with line segments within the tolerance. Each sub-path is
+    /// a loop, whether it is closed or not, and the first point is not repeated at the end.
+    /// Repeated points and points that don't change the shape of the loop (in the middle of a
+    /// straight line or at the tip of a zero-width spike) are removed, and the loops that are
+    /// left with less than three points are dropped.
+    pub fn flattened_outline(&self, tolerance: f32) -> Vec<Vec<Point>> {
+        let mut outline = Vec::new();
+        let mut current: Vec<Point> = Vec::new();
+        for evt in self.path_iter().flattened(tolerance) {
+            match evt {
+                FlattenedEvent::MoveTo(to) => {
+                    end_loop(&mut current, &mut outline);
+                    current.push(to);
+                }
+                FlattenedEvent::LineTo(to) => {
+                    current.push(to);
+                }
+                FlattenedEvent::Close => {
+                    end_loop(&mut current, &mut outline);
+                }
+            }
+        }
+        end_loop(&mut current, &mut outline);
+
+        return outline;
+    }
+}
+
+fn end_loop(current: &mut Vec<Point>, outline: &mut Vec<Vec<Point>>) {
+    let mut points: Vec<Point> = Vec::with_capacity(current.len());
+    for &p in current.iter() {
+        // Removing a point can make the previous one redundant.
+        loop {
+            let n = points.len();
+            if n > 0 && points[n - 1] == p {
+                break;
+            }
+            if n > 1 && (points[n - 1] - points[n - 2]).cross(p - points[n - 1]) == 0.0 {
+                points.pop();
+                continue;
+            }
+            points.push(p);
+            break;
+        }
+    }
+    current.clear();
+
+    // Same thing around the start of the loop.
+    loop {
+        let n = points.len();
+        if n < 3 {
+            return;
+        }
+        if points[n - 1] == points[0] {
+            points.pop();
+        } else if (points[n - 1] - points[n - 2]).cross(points[0] - points[n - 1]) == 0.0 {
+            points.pop();
+        } else if (points[0] - points[n - 1]).cross(points[1] - points[0]) == 0.0 {
+            points.remove(0);
+        } else {
+            break;
+        }
+    }
+
+    outline.push(points);
 }
 
 //impl<'l> IntoIterator for PathSlice<'l> {
@@ -256,6 +337,168 @@ impl<'l> Iterator for PathIter<'l> {
     }
 }
 
+/// An iterator over the events of a path in reverse order.
+///
+/// The sub-paths are visited from the last one to the first one, and each of them starts
+/// with a `MoveTo` event at its last point. The control points of the curves are swapped
+/// so that the curves keep their shape, and closed sub-paths are still closed, which also
+/// reverses their closing edge. The path is not copied.
+#[derive(Clone, Debug)]
+pub struct ReversedPathIter<'l> {
+    // The part of the path that hasn't been visited yet.
+    points: &'l [Point],
+    verbs: &'l [Verb],
+    // The remaining segments of the current sub-path.
+    segment_points: &'l [Point],
+    segment_verbs: &'l [Verb],
+    start: Point,
+    close: bool,
+}
+
+impl<'l> ReversedPathIter<'l> {
+    pub fn new(points: &'l [Point], verbs: &'l [Verb]) -> Self {
+        ReversedPathIter {
+            points: points,
+            verbs: verbs,
+            segment_points: &[],
+            segment_verbs: &[],
+            start: point(0.0, 0.0),
+            close: false,
+        }
+    }
+}
+
+impl<'l> Iterator for ReversedPathIter<'l> {
+    type Item = PathEvent;
+    fn next(&mut self) -> Option<PathEvent> {
+        if let Some((&verb, verbs)) = self.segment_verbs.split_last() {
+            self.segment_verbs = verbs;
+            let split = self.segment_points.len() - verb_point_count(verb);
+            let p = &self.segment_points[split..];
+            self.segment_points = &self.segment_points[..split];
+            // The end of the previous segment.
+            let to = self.segment_points.last().cloned().unwrap_or(self.start);
+            return Some(match verb {
+                Verb::LineTo => PathEvent::LineTo(to),
+                Verb::QuadraticTo => PathEvent::QuadraticTo(p[0], to),
+                _ => PathEvent::CubicTo(p[1], p[0], to),
+            });
+        }
+
+        if self.close {
+            self.close = false;
+            return Some(PathEvent::Close);
+        }
+
+        // Find the segments of the next sub-path.
+        let mut end = self.verbs.len();
+        if end == 0 {
+            return None;
+        }
+        self.close = self.verbs[end - 1] == Verb::Close;
+        if self.close {
+            end -= 1;
+        }
+        let mut begin = end;
+        let mut point_count = 0;
+        while begin > 0 {
+            match self.verbs[begin - 1] {
+                Verb::MoveTo | Verb::Close => { break; }
+                verb => { point_count += verb_point_count(verb); }
+            }
+            begin -= 1;
+        }
+
+        let point_begin = self.points.len() - point_count;
+        self.segment_verbs = &self.verbs[begin..end];
+        self.segment_points = &self.points[point_begin..];
+        if begin > 0 && self.verbs[begin - 1] == Verb::MoveTo {
+            self.start = self.points[point_begin - 1];
+            self.verbs = &self.verbs[..(begin - 1)];
+            self.points = &self.points[..(point_begin - 1)];
+        } else {
+            self.verbs = &self.verbs[..begin];
+            self.points = &self.points[..point_begin];
+            self.start = implicit_start(self.points, self.verbs);
+        }
+
+        return Some(PathEvent::MoveTo(self.segment_points.last().cloned().unwrap_or(self.start)));
+    }
+}
+
+fn verb_point_count(verb: Verb) -> usize {
+    match verb {
+        Verb::MoveTo | Verb::LineTo => 1,
+        Verb::QuadraticTo => 2,
+        Verb::CubicTo => 3,
+        Verb::Close => 0,
+    }
+}
+
+// The start of a sub-path that doesn't begin with a MoveTo event, which is the start of the
+// sub-path before it.
+fn implicit_start(points: &[Point], verbs: &[Verb]) -> Point {
+    let mut p = points.len();
+    for &verb in verbs.iter().rev() {
+        if verb == Verb::MoveTo {
+            return points[p - 1];
+        }
+        p -= verb_point_count(verb);
+    }
+
+    return point(0.0, 0.0);
+}
+
+#[test]
+fn test_reversed_iter() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.quadratic_bezier_to(point(2.0, 0.0), point(2.0, 1.0));
+    builder.cubic_bezier_to(point(3.0, 1.0), point(3.0, 2.0), point(2.0, 2.0));
+    builder.close();
+    builder.move_to(point(10.0, 0.0));
+    builder.line_to(point(11.0, 0.0));
+    builder.move_to(point(20.0, 0.0));
+    let path = builder.build();
+
+    let events: Vec<PathEvent> = path.reversed_iter().collect();
+    assert_eq!(
+        events,
+        vec![
+            PathEvent::MoveTo(point(20.0, 0.0)),
+            PathEvent::MoveTo(point(11.0, 0.0)),
+            PathEvent::LineTo(point(10.0, 0.0)),
+            PathEvent::MoveTo(point(2.0, 2.0)),
+            PathEvent::CubicTo(point(3.0, 2.0), point(3.0, 1.0), point(2.0, 1.0)),
+            PathEvent::QuadraticTo(point(2.0, 0.0), point(1.0, 0.0)),
+            PathEvent::LineTo(point(0.0, 0.0)),
+            PathEvent::Close,
+        ]
+    );
+
+    // Sub-paths that don't start with a MoveTo event.
+    let mut builder = Path::builder();
+    builder.line_to(point(1.0, 0.0));
+    builder.close();
+    builder.line_to(point(5.0, 5.0));
+    let path = builder.build();
+
+    let events: Vec<PathEvent> = path.as_slice().reversed_iter().collect();
+    assert_eq!(
+        events,
+        vec![
+            PathEvent::MoveTo(point(5.0, 5.0)),
+            PathEvent::LineTo(point(0.0, 0.0)),
+            PathEvent::MoveTo(point(1.0, 0.0)),
+            PathEvent::LineTo(point(0.0, 0.0)),
+            PathEvent::Close,
+        ]
+    );
+
+    assert!(Path::new().reversed_iter().next().is_none());
+}
+
 #[test]
 fn test_path_builder_1() {
 
@@ -524,3 +767,189 @@ fn test_path_builder_close_empty() {
     let _ = builder.build();
 }
 */
+
+#[test]
+fn test_validating_builder() {
+    use path_builder::ValidatingBuilder;
+    use core::input_validation::{InputError, InvalidCoordinate, MAX_FIXED_POINT_COORDINATE};
+
+    let mut builder = ValidatingBuilder::new(Path::builder(), MAX_FIXED_POINT_COORDINATE);
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.close();
+    builder.move_to(point(10.0, 0.0));
+    builder.quadratic_bezier_to(point(1.0e9, 0.0), point(11.0, 1.0));
+    builder.line_to(point(::std::f32::NAN, 0.0));
+    assert_eq!(builder.error(), Some(InputError { event: 4, kind: InvalidCoordinate::TooLarge }));
+    assert_eq!(builder.try_build().err(), Some(InputError { event: 4, kind: InvalidCoordinate::TooLarge }));
+
+    let mut builder = ValidatingBuilder::new(Path::builder(), MAX_FIXED_POINT_COORDINATE);
+    builder.move_to(point(0.0, 0.0));
+    builder.cubic_bezier_to(point(1.0, 0.0), point(1.0, 1.0), point(0.0, 1.0));
+    let path = builder.try_build().unwrap();
+    assert_eq!(path.points().len(), 4);
+}
+
+#[test]
+fn test_policy_builder() {
+    use path_builder::{PolicyBuilder, BuilderPolicies, MissingMoveTo};
+
+    // Canvas: the first line_to only moves, and the next sub-path starts where the closed
+    // one started.
+    let mut builder = PolicyBuilder::new(Path::builder(), BuilderPolicies::canvas());
+    builder.line_to(point(1.0, 1.0));
+    builder.line_to(point(2.0, 1.0));
+    builder.close();
+    builder.line_to(point(3.0, 3.0));
+    let path = builder.build();
+    let events: Vec<PathEvent> = path.iter().collect();
+    assert_eq!(events, vec![
+        PathEvent::MoveTo(point(1.0, 1.0)),
+        PathEvent::LineTo(point(2.0, 1.0)),
+        PathEvent::Close,
+        PathEvent::MoveTo(point(1.0, 1.0)),
+        PathEvent::LineTo(point(3.0, 3.0)),
+    ]);
+
+    // SVG: segments before the first move_to are dropped.
+    let mut builder = Path::builder().with_policies(BuilderPolicies::svg());
+    builder.line_to(point(1.0, 1.0));
+    builder.quadratic_bezier_to(point(1.0, 1.0), point(2.0, 2.0));
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    assert_eq!(builder.rejected_event(), Some(0));
+    let events: Vec<PathEvent> = builder.build().iter().collect();
+    assert_eq!(events, vec![PathEvent::MoveTo(point(0.0, 0.0)), PathEvent::LineTo(point(1.0, 0.0))]);
+
+    // Sub-paths starting at the origin and closed automatically.
+    let policies = BuilderPolicies::default()
+        .with_missing_move_to(MissingMoveTo::Origin)
+        .with_auto_close();
+    let mut builder = PolicyBuilder::new(Path::builder(), policies);
+    builder.line_to(point(1.0, 0.0));
+    builder.move_to(point(5.0, 5.0));
+    builder.line_to(point(6.0, 5.0));
+    let events: Vec<PathEvent> = builder.build().iter().collect();
+    assert_eq!(events, vec![
+        PathEvent::MoveTo(point(0.0, 0.0)),
+        PathEvent::LineTo(point(1.0, 0.0)),
+        PathEvent::Close,
+        PathEvent::MoveTo(point(5.0, 5.0)),
+        PathEvent::LineTo(point(6.0, 5.0)),
+        PathEvent::Close,
+    ]);
+}
+
+#[test]
+fn test_subdividing_builder() {
+    let mut builder = Path::builder().subdivided(1.0);
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(0.0, 1.0));
+    builder.line_to(point(2.0, 1.0));
+    builder.line_to(point(2.0, 0.0));
+    builder.close();
+    let events: Vec<PathEvent> = builder.build().iter().collect();
+    assert_eq!(events, vec![
+        PathEvent::MoveTo(point(0.0, 0.0)),
+        PathEvent::LineTo(point(0.0, 1.0)),
+        PathEvent::LineTo(point(1.0, 1.0)),
+        PathEvent::LineTo(point(2.0, 1.0)),
+        PathEvent::LineTo(point(2.0, 0.0)),
+        // The closing segment is subdivided too.
+        PathEvent::LineTo(point(1.0, 0.0)),
+        PathEvent::LineTo(point(0.0, 0.0)),
+        PathEvent::Close,
+    ]);
+
+    let mut builder = Path::builder().subdivided(1.0);
+    builder.move_to(point(0.0, 0.0));
+    builder.quadratic_bezier_to(point(2.0, 0.0), point(2.0, 2.0));
+    let events: Vec<PathEvent> = builder.build().iter().collect();
+    // The control polygon of the curve is 4 long.
+    assert!(events.len() >= 5);
+    let mut from = point(0.0, 0.0);
+    for event in &events[1..] {
+        match *event {
+            PathEvent::QuadraticTo(ctrl, to) => {
+                assert!((ctrl - from).length() + (to - ctrl).length() <= 1.0);
+                from = to;
+            }
+            _ => { panic!("unexpected event {:?}", event); }
+        }
+    }
+    assert_eq!(from, point(2.0, 2.0));
+}
+
+#[test]
+fn test_warped_iter() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    // Bends the horizontal lines into parabolas and keeps the vertical ones straight.
+    let bend = |v: Vec2| vec2(v.x, v.y + 0.1 * v.x * v.x);
+    let events: Vec<FlattenedEvent> = path.path_iter().warped(&bend, 0.1).collect();
+
+    // Each parabola is split in 6 pieces: 4 where it is flatter and 2 twice as long where
+    // it is steeper.
+    assert_eq!(events.len(), 15);
+    assert_eq!(events[0], FlattenedEvent::MoveTo(point(0.0, 0.0)));
+    assert_eq!(events[6], FlattenedEvent::LineTo(point(10.0, 10.0)));
+    assert_eq!(events[7], FlattenedEvent::LineTo(point(10.0, 20.0)));
+    assert_eq!(events[13], FlattenedEvent::LineTo(point(0.0, 10.0)));
+    assert_eq!(events[14], FlattenedEvent::Close);
+    for evt in &events[1..7] {
+        if let FlattenedEvent::LineTo(p) = *evt {
+            assert!((p.y - 0.1 * p.x * p.x).abs() < 0.001);
+        } else {
+            panic!("unexpected event {:?}", evt);
+        }
+    }
+
+    // Lines stay lines with an affine transformation.
+    let events: Vec<FlattenedEvent> = path.path_iter().warped(|v: Vec2| v * 2.0, 0.1).collect();
+    assert_eq!(events, vec![
+        FlattenedEvent::MoveTo(point(0.0, 0.0)),
+        FlattenedEvent::LineTo(point(20.0, 0.0)),
+        FlattenedEvent::LineTo(point(20.0, 20.0)),
+        FlattenedEvent::LineTo(point(0.0, 20.0)),
+        FlattenedEvent::Close,
+    ]);
+}
+
+#[test]
+fn test_flattened_outline() {
+    let mut builder = Path::builder();
+    // A square with a repeated point, a point in the middle of a side, a spike and the
+    // first point repeated at the end.
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.line_to(point(2.0, 0.0));
+    builder.line_to(point(2.0, 2.0));
+    builder.line_to(point(3.0, 2.0));
+    builder.line_to(point(2.0, 2.0));
+    builder.line_to(point(0.0, 2.0));
+    builder.line_to(point(0.0, 0.0));
+    builder.close();
+    // Degenerate sub-paths.
+    builder.move_to(point(5.0, 5.0));
+    builder.line_to(point(6.0, 5.0));
+    builder.line_to(point(7.0, 5.0));
+    builder.move_to(point(8.0, 8.0));
+    // An open curved sub-path.
+    builder.move_to(point(10.0, 0.0));
+    builder.quadratic_bezier_to(point(20.0, 0.0), point(20.0, 10.0));
+    let path = builder.build();
+
+    let outline = path.flattened_outline(0.1);
+    assert_eq!(outline.len(), 2);
+    assert_eq!(outline[0], vec![point(0.0, 0.0), point(2.0, 0.0), point(2.0, 2.0), point(0.0, 2.0)]);
+    assert!(outline[1].len() > 3);
+    assert_eq!(outline[1][0], point(10.0, 0.0));
+    assert_eq!(*outline[1].last().unwrap(), point(20.0, 10.0));
+}
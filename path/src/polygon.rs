@@ -0,0 +1,392 @@
+//! Polygons with holes.
+//!
+//! Geographic formats such as GeoJSON and WKB describe areas as polygons made of an exterior
+//! ring and a list of interior rings (the holes), with only straight edges. [Polygon](struct.Polygon.html)
+//! keeps this structure, which is lost when the rings are flattened into a sequence of path
+//! events.
+//!
+//! Like in these formats, the rings are explicitly closed (the last point is equal to the first
+//! one), the exterior ring is counter-clockwise and the interior rings are clockwise (with the
+//! y axis pointing up). [validate](struct.Polygon.html#method.validate) checks these rules and
+//! [close_rings](struct.Polygon.html#method.close_rings) and
+//! [orient](struct.Polygon.html#method.orient) fix them.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_iterator;
+//! # extern crate lyon_core;
+//! # use lyon_path::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # fn main() {
+//! # let exterior = vec![point(0.0, 0.0), point(0.0, 10.0), point(10.0, 10.0), point(10.0, 0.0)];
+//! # let holes = vec![vec![point(2.0, 2.0), point(4.0, 2.0), point(4.0, 4.0), point(2.0, 4.0)]];
+//! let mut polygon = Polygon::new(exterior, holes);
+//! polygon.close_rings();
+//! polygon.orient();
+//! assert_eq!(polygon.validate(), Ok(()));
+//!
+//! let path = polygon.to_path();
+//!
+//! // Back from a path, with the holes attached to the exterior ring they are in.
+//! let polygons = polygons_from_path(path.path_iter().flattened(0.05));
+//! # assert_eq!(polygons.len(), 1);
+//! # }
+//! ```
+
+use core::FlattenedEvent;
+use core::math::*;
+use path_builder::BaseBuilder;
+
+use path::Path;
+
+/// An area bounded by an exterior ring, minus the areas bounded by its interior rings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polygon {
+    pub exterior: Vec<Point>,
+    pub interiors: Vec<Vec<Point>>,
+}
+
+/// A problem found by [Polygon::validate](struct.Polygon.html#method.validate).
+///
+/// The rings are identified by an index, zero being the exterior ring and `i + 1` the interior
+/// ring `i`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PolygonError {
+    /// The ring has less than three distinct points (four including the closing point).
+    TooFewPoints { ring: usize },
+    /// The last point of the ring is not equal to the first one.
+    UnclosedRing { ring: usize },
+    /// The exterior ring is not counter-clockwise or an interior ring is not clockwise.
+    WrongOrientation { ring: usize },
+}
+
+impl Polygon {
+    pub fn new(exterior: Vec<Point>, interiors: Vec<Vec<Point>>) -> Self {
+        Polygon {
+            exterior: exterior,
+            interiors: interiors,
+        }
+    }
+
+    /// The number of rings, including the exterior ring.
+    pub fn num_rings(&self) -> usize { self.interiors.len() + 1 }
+
+    /// The ring at an index, zero being the exterior ring and `i + 1` the interior ring `i`.
+    pub fn ring(&self, idx: usize) -> &[Point] {
+        if idx == 0 { &self.exterior } else { &self.interiors[idx - 1] }
+    }
+
+    /// Checks that the rings are closed, have at least three distinct points and are in
+    /// the expected orientation. Returns the first problem found.
+    pub fn validate(&self) -> Result<(), PolygonError> {
+        for idx in 0..self.num_rings() {
+            let ring = self.ring(idx);
+            if ring.len() < 4 {
+                return Err(PolygonError::TooFewPoints { ring: idx });
+            }
+            if ring[0] != ring[ring.len() - 1] {
+                return Err(PolygonError::UnclosedRing { ring: idx });
+            }
+            let area = signed_area(ring);
+            if (idx == 0 && area <= 0.0) || (idx > 0 && area >= 0.0) {
+                return Err(PolygonError::WrongOrientation { ring: idx });
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Repeats the first point at the end of the rings that aren't explicitly closed.
+    pub fn close_rings(&mut self) {
+        close_ring(&mut self.exterior);
+        for ring in &mut self.interiors {
+            close_ring(ring);
+        }
+    }
+
+    /// Reverses the rings that are not in the expected orientation.
+    pub fn orient(&mut self) {
+        if signed_area(&self.exterior) < 0.0 {
+            self.exterior.reverse();
+        }
+        for ring in &mut self.interiors {
+            if signed_area(ring) > 0.0 {
+                ring.reverse();
+            }
+        }
+    }
+
+    /// Iterates over the rings as flattened path events, each ring being a closed sub-path.
+    ///
+    /// The closing point of the rings is not repeated. This can be given directly to the fill
+    /// tessellator.
+    pub fn iter(&self) -> PolygonIter {
+        PolygonIter {
+            polygon: self,
+            ring: 0,
+            point: 0,
+        }
+    }
+
+    /// Adds the rings to a path builder, each ring being a closed sub-path.
+    pub fn build_path<Builder: BaseBuilder>(&self, builder: &mut Builder) {
+        for evt in self.iter() {
+            match evt {
+                FlattenedEvent::MoveTo(to) => { builder.move_to(to); }
+                FlattenedEvent::LineTo(to) => { builder.line_to(to); }
+                FlattenedEvent::Close => { builder.close(); }
+            }
+        }
+    }
+
+    /// Converts the polygon into a path.
+    pub fn to_path(&self) -> Path {
+        let mut builder = Path::builder();
+        self.build_path(&mut builder);
+
+        return builder.build();
+    }
+}
+
+/// An iterator over the rings of a polygon, see [Polygon::iter](struct.Polygon.html#method.iter).
+#[derive(Clone, Debug)]
+pub struct PolygonIter<'l> {
+    polygon: &'l Polygon,
+    ring: usize,
+    point: usize,
+}
+
+impl<'l> Iterator for PolygonIter<'l> {
+    type Item = FlattenedEvent;
+    fn next(&mut self) -> Option<FlattenedEvent> {
+        loop {
+            if self.ring >= self.polygon.num_rings() {
+                return None;
+            }
+
+            let ring = self.polygon.ring(self.ring);
+            let mut len = ring.len();
+            if len > 1 && ring[0] == ring[len - 1] {
+                len -= 1;
+            }
+            if len == 0 {
+                self.ring += 1;
+                continue;
+            }
+
+            let idx = self.point;
+            self.point += 1;
+            if idx == 0 {
+                return Some(FlattenedEvent::MoveTo(ring[0]));
+            }
+            if idx < len {
+                return Some(FlattenedEvent::LineTo(ring[idx]));
+            }
+
+            self.ring += 1;
+            self.point = 0;
+            return Some(FlattenedEvent::Close);
+        }
+    }
+}
+
+/// Converts a flattened path into polygons.
+///
+/// Each sub-path is a ring (closed or not). The rings are assumed not to cross each other and
+/// are nested with the even-odd rule: the rings that are inside of an even number of other
+/// rings are exterior rings, and the others are holes of the smallest ring they are inside of.
+/// The polygons are in the order of their exterior ring in the path, and their rings are
+/// closed and oriented. Rings with less than three points are ignored.
+pub fn polygons_from_path<Iter>(path: Iter) -> Vec<Polygon>
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let mut rings: Vec<Vec<Point>> = Vec::new();
+    let mut current: Vec<Point> = Vec::new();
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                add_ring(&mut current, &mut rings);
+                current.push(to);
+            }
+            FlattenedEvent::LineTo(to) => {
+                current.push(to);
+            }
+            FlattenedEvent::Close => {
+                add_ring(&mut current, &mut rings);
+            }
+        }
+    }
+    add_ring(&mut current, &mut rings);
+
+    // For each ring, the smallest ring that contains it and the number of rings containing it.
+    let areas: Vec<f32> = rings.iter().map(|ring| signed_area(ring).abs()).collect();
+    let mut parents: Vec<Option<usize>> = Vec::with_capacity(rings.len());
+    let mut depths: Vec<usize> = Vec::with_capacity(rings.len());
+    for i in 0..rings.len() {
+        let mut parent = None;
+        let mut depth = 0;
+        for j in 0..rings.len() {
+            if i == j || areas[j] < areas[i] || !ring_contains(&rings[j], rings[i][0]) {
+                continue;
+            }
+            depth += 1;
+            parent = match parent {
+                Some(p) if areas[p] <= areas[j] => Some(p),
+                _ => Some(j),
+            };
+        }
+        parents.push(parent);
+        depths.push(depth);
+    }
+
+    let mut polygons = Vec::new();
+    let mut polygon_of_ring: Vec<Option<usize>> = vec![None; rings.len()];
+    for i in 0..rings.len() {
+        if depths[i] % 2 == 0 {
+            polygon_of_ring[i] = Some(polygons.len());
+            polygons.push(Polygon::new(rings[i].clone(), Vec::new()));
+        }
+    }
+    for i in 0..rings.len() {
+        if depths[i] % 2 == 1 {
+            if let Some(polygon) = parents[i].and_then(|p| polygon_of_ring[p]) {
+                polygons[polygon].interiors.push(rings[i].clone());
+            }
+        }
+    }
+
+    for polygon in &mut polygons {
+        polygon.close_rings();
+        polygon.orient();
+    }
+
+    return polygons;
+}
+
+fn add_ring(current: &mut Vec<Point>, rings: &mut Vec<Vec<Point>>) {
+    if current.len() > 1 && current[0] == current[current.len() - 1] {
+        current.pop();
+    }
+    if current.len() >= 3 {
+        rings.push(current.clone());
+    }
+    current.clear();
+}
+
+fn close_ring(ring: &mut Vec<Point>) {
+    if !ring.is_empty() && ring[0] != ring[ring.len() - 1] {
+        let first = ring[0];
+        ring.push(first);
+    }
+}
+
+// Works with or without the closing point.
+fn signed_area(ring: &[Point]) -> f32 {
+    let n = ring.len();
+    let mut area = 0.0;
+    for i in 0..n {
+        area += ring[i].to_vector().cross(ring[(i + 1) % n].to_vector());
+    }
+
+    return area * 0.5;
+}
+
+// Even-odd point in polygon test.
+fn ring_contains(ring: &[Point], p: Point) -> bool {
+    let n = ring.len();
+    let mut inside = false;
+    for i in 0..n {
+        let a = ring[i];
+        let b = ring[(i + 1) % n];
+        if (a.y <= p.y) != (b.y <= p.y) {
+            let x = a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y);
+            if x > p.x {
+                inside = !inside;
+            }
+        }
+    }
+
+    return inside;
+}
+
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[cfg(test)]
+fn square(x: f32, y: f32, size: f32) -> Vec<Point> {
+    vec![point(x, y), point(x + size, y), point(x + size, y + size), point(x, y + size)]
+}
+
+#[test]
+fn test_polygon_validate() {
+    let mut polygon = Polygon::new(square(0.0, 0.0, 10.0), vec![square(2.0, 2.0, 2.0)]);
+    assert_eq!(polygon.validate(), Err(PolygonError::UnclosedRing { ring: 0 }));
+
+    polygon.close_rings();
+    assert_eq!(polygon.exterior.len(), 5);
+    assert_eq!(polygon.validate(), Err(PolygonError::WrongOrientation { ring: 1 }));
+
+    polygon.orient();
+    assert_eq!(polygon.validate(), Ok(()));
+    assert_eq!(polygon.exterior[0], polygon.exterior[4]);
+
+    polygon.interiors.push(vec![point(5.0, 5.0), point(6.0, 5.0), point(5.0, 5.0)]);
+    assert_eq!(polygon.validate(), Err(PolygonError::TooFewPoints { ring: 2 }));
+}
+
+#[test]
+fn test_polygon_iter() {
+    let mut polygon = Polygon::new(square(0.0, 0.0, 10.0), vec![square(2.0, 2.0, 2.0), Vec::new()]);
+    polygon.close_rings();
+
+    let events: Vec<FlattenedEvent> = polygon.iter().collect();
+    // The empty ring is skipped.
+    assert_eq!(events.len(), 10);
+    assert_eq!(events[0], FlattenedEvent::MoveTo(point(0.0, 0.0)));
+    assert_eq!(events[3], FlattenedEvent::LineTo(point(0.0, 10.0)));
+    assert_eq!(events[4], FlattenedEvent::Close);
+    assert_eq!(events[5], FlattenedEvent::MoveTo(point(2.0, 2.0)));
+    assert_eq!(events[9], FlattenedEvent::Close);
+
+    let path = polygon.to_path();
+    assert_eq!(path.points().len(), 8);
+}
+
+#[test]
+fn test_polygons_from_path() {
+    // A square with a hole containing an island, and a separate square, in arbitrary
+    // orientations.
+    let mut island = square(4.0, 4.0, 2.0);
+    island.reverse();
+    let mut builder = Path::builder();
+    for ring in &[square(0.0, 0.0, 10.0), square(2.0, 2.0, 6.0), island, square(20.0, 0.0, 5.0)] {
+        builder.move_to(ring[0]);
+        for &p in &ring[1..] {
+            builder.line_to(p);
+        }
+        builder.close();
+    }
+    let path = builder.build();
+
+    let polygons = polygons_from_path(path.path_iter().flattened(0.05));
+
+    assert_eq!(polygons.len(), 3);
+    assert_eq!(polygons[0].interiors.len(), 1);
+    assert_eq!(polygons[0].interiors[0][0], point(2.0, 2.0));
+    assert_eq!(polygons[1].exterior[0], point(4.0, 6.0));
+    assert!(polygons[1].interiors.is_empty());
+    assert!(polygons[2].interiors.is_empty());
+    for polygon in &polygons {
+        assert_eq!(polygon.validate(), Ok(()));
+    }
+
+    // Round trip.
+    let path = polygons[0].to_path();
+    let back = polygons_from_path(polygons[0].iter());
+    assert_eq!(back, vec![polygons[0].clone()]);
+    assert_eq!(path.points().len(), 8);
+}
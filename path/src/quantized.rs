@@ -0,0 +1,510 @@
+use path::{Path, Verb};
+use path_builder::{BaseBuilder, PathBuilder};
+use path_iterator::PathStateIter;
+
+use core::PathEvent;
+use core::math::*;
+
+/// A compact read-only path with coordinates quantized to 16 bits.
+///
+/// The points are stored as 16 bits offsets relative to the bounding box of the path,
+/// which divides the memory used by the points by four compared to a [Path](struct.Path.html).
+/// The verbs and points are stored in boxed slices so that no memory is wasted on spare
+/// capacity.
+///
+/// The quantization error is at most `precision() / 2` on each axis, `precision()` being the
+/// largest dimension of the bounding box divided by 65535. This makes it well suited to store
+/// large amounts of small paths such as map features or glyphs.
+#[derive(Clone, Debug)]
+pub struct QuantizedPath {
+    verbs: Box<[Verb]>,
+    points: Box<[[u16; 2]]>,
+    origin: Point,
+    scale: f32,
+}
+
+impl QuantizedPath {
+    /// Quantizes a path.
+    pub fn from_path(path: &Path) -> QuantizedPath {
+        QuantizedPath::from_slices(path.points(), path.verbs())
+    }
+
+    pub fn from_slices(points: &[Point], verbs: &[Verb]) -> QuantizedPath {
+        let mut min = point(0.0, 0.0);
+        let mut max = point(0.0, 0.0);
+        if let Some(first) = points.first() {
+            min = *first;
+            max = *first;
+        }
+        for p in points {
+            min.x = min.x.min(p.x);
+            min.y = min.y.min(p.y);
+            max.x = max.x.max(p.x);
+            max.y = max.y.max(p.y);
+        }
+
+        let extent = (max.x - min.x).max(max.y - min.y);
+        let scale = if extent > 0.0 { extent / (::std::u16::MAX as f32) } else { 1.0 };
+        let inv_scale = 1.0 / scale;
+
+        let quantized_points: Vec<[u16; 2]> = points.iter().map(|p| {
+            [
+                ((p.x - min.x) * inv_scale).round().min(::std::u16::MAX as f32) as u16,
+                ((p.y - min.y) * inv_scale).round().min(::std::u16::MAX as f32) as u16,
+            ]
+        }).collect();
+
+        QuantizedPath {
+            verbs: verbs.to_vec().into_boxed_slice(),
+            points: quantized_points.into_boxed_slice(),
+            origin: min,
+            scale: scale,
+        }
+    }
+
+    /// The distance between two consecutive values of the quantization grid.
+    pub fn precision(&self) -> f32 { self.scale }
+
+    pub fn verbs(&self) -> &[Verb] { &self.verbs[..] }
+
+    pub fn num_points(&self) -> usize { self.points.len() }
+
+    /// Returns the dequantized point at a given index.
+    pub fn point(&self, idx: usize) -> Point { self.dequantize(self.points[idx]) }
+
+    pub fn iter(&self) -> QuantizedPathIter {
+        QuantizedPathIter {
+            path: self,
+            verb: 0,
+            point: 0,
+        }
+    }
+
+    pub fn path_iter(&self) -> PathStateIter<QuantizedPathIter> { PathStateIter::new(self.iter()) }
+
+    /// Converts back into a regular path.
+    pub fn to_path(&self) -> Path {
+        let mut builder = Path::builder();
+        for evt in self.iter() {
+            match evt {
+                PathEvent::MoveTo(to) => { builder.move_to(to); }
+                PathEvent::LineTo(to) => { builder.line_to(to); }
+                PathEvent::QuadraticTo(ctrl, to) => { builder.quadratic_bezier_to(ctrl, to); }
+                PathEvent::CubicTo(ctrl1, ctrl2, to) => { builder.cubic_bezier_to(ctrl1, ctrl2, to); }
+                PathEvent::Close => { builder.close(); }
+            }
+        }
+
+        return builder.build();
+    }
+
+    /// The approximate amount of memory used by the path, in bytes.
+    pub fn memory_size(&self) -> usize {
+        ::std::mem::size_of::<Self>() +
+            self.verbs.len() * ::std::mem::size_of::<Verb>() +
+            self.points.len() * ::std::mem::size_of::<[u16; 2]>()
+    }
+
+    #[inline]
+    fn dequantize(&self, p: [u16; 2]) -> Point {
+        point(
+            self.origin.x + p[0] as f32 * self.scale,
+            self.origin.y + p[1] as f32 * self.scale,
+        )
+    }
+}
+
+impl<'l> IntoIterator for &'l QuantizedPath {
+    type Item = PathEvent;
+    type IntoIter = QuantizedPathIter<'l>;
+
+    fn into_iter(self) -> QuantizedPathIter<'l> { self.iter() }
+}
+
+/// An iterator of `PathEvent` over a `QuantizedPath`.
+#[derive(Clone, Debug)]
+pub struct QuantizedPathIter<'l> {
+    path: &'l QuantizedPath,
+    verb: usize,
+    point: usize,
+}
+
+impl<'l> QuantizedPathIter<'l> {
+    fn next_point(&mut self) -> Point {
+        let p = self.path.point(self.point);
+        self.point += 1;
+        return p;
+    }
+}
+
+impl<'l> Iterator for QuantizedPathIter<'l> {
+    type Item = PathEvent;
+    fn next(&mut self) -> Option<PathEvent> {
+        if self.verb >= self.path.verbs.len() {
+            return None;
+        }
+
+        let verb = self.path.verbs[self.verb];
+        self.verb += 1;
+
+        return Some(match verb {
+            Verb::MoveTo => PathEvent::MoveTo(self.next_point()),
+            Verb::LineTo => PathEvent::LineTo(self.next_point()),
+            Verb::QuadraticTo => {
+                let ctrl = self.next_point();
+                let to = self.next_point();
+                PathEvent::QuadraticTo(ctrl, to)
+            }
+            Verb::CubicTo => {
+                let ctrl1 = self.next_point();
+                let ctrl2 = self.next_point();
+                let to = self.next_point();
+                PathEvent::CubicTo(ctrl1, ctrl2, to)
+            }
+            Verb::Close => PathEvent::Close,
+        });
+    }
+}
+
+const SNORM_MAX: f32 = ::std::i16::MAX as f32;
+
+/// A path which coordinates are stored as normalized 16 bits integers within a declared
+/// bounding rectangle.
+///
+/// `-32767` maps to the minimum and `32767` to the maximum of the rectangle on each axis
+/// (`-32768` is treated as `-32767`). Unlike [QuantizedPath](struct.QuantizedPath.html),
+/// the rectangle is provided by the user rather than computed from the points, which
+/// matches how vector tiles deliver their geometry: the coordinates can be stored as they
+/// are and are only converted to floating point when the path is iterated over.
+///
+/// Points outside of the rectangle are clamped to its edges.
+#[derive(Clone, Debug)]
+pub struct NormalizedPath {
+    verbs: Vec<Verb>,
+    points: Vec<[i16; 2]>,
+    bounds: Rect,
+}
+
+impl NormalizedPath {
+    /// Creates a [NormalizedBuilder](struct.NormalizedBuilder.html) quantizing points within
+    /// the provided rectangle.
+    pub fn builder(bounds: Rect) -> NormalizedBuilder { NormalizedBuilder::new(bounds) }
+
+    /// Creates a path from already quantized coordinates.
+    ///
+    /// The number of points must match the verbs.
+    pub fn from_raw(bounds: Rect, verbs: Vec<Verb>, points: Vec<[i16; 2]>) -> NormalizedPath {
+        debug_assert_eq!(
+            verbs.iter().map(|verb| match *verb {
+                Verb::MoveTo | Verb::LineTo => 1,
+                Verb::QuadraticTo => 2,
+                Verb::CubicTo => 3,
+                Verb::Close => 0,
+            }).sum::<usize>(),
+            points.len()
+        );
+
+        NormalizedPath {
+            verbs: verbs,
+            points: points,
+            bounds: bounds,
+        }
+    }
+
+    /// The rectangle that the coordinates are relative to.
+    pub fn bounds(&self) -> &Rect { &self.bounds }
+
+    /// The distance between two consecutive values of the quantization grid on each axis.
+    pub fn precision(&self) -> Vec2 {
+        vec2(self.bounds.size.width, self.bounds.size.height) / (2.0 * SNORM_MAX)
+    }
+
+    pub fn verbs(&self) -> &[Verb] { &self.verbs[..] }
+
+    /// The quantized coordinates.
+    pub fn raw_points(&self) -> &[[i16; 2]] { &self.points[..] }
+
+    pub fn num_points(&self) -> usize { self.points.len() }
+
+    /// Returns the dequantized point at a given index.
+    pub fn point(&self, idx: usize) -> Point { dequantize_snorm(&self.bounds, self.points[idx]) }
+
+    pub fn iter(&self) -> NormalizedPathIter {
+        NormalizedPathIter {
+            path: self,
+            verb: 0,
+            point: 0,
+        }
+    }
+
+    pub fn path_iter(&self) -> PathStateIter<NormalizedPathIter> { PathStateIter::new(self.iter()) }
+
+    /// Converts into a regular path.
+    pub fn to_path(&self) -> Path {
+        let mut builder = Path::builder();
+        for evt in self.iter() {
+            match evt {
+                PathEvent::MoveTo(to) => { builder.move_to(to); }
+                PathEvent::LineTo(to) => { builder.line_to(to); }
+                PathEvent::QuadraticTo(ctrl, to) => { builder.quadratic_bezier_to(ctrl, to); }
+                PathEvent::CubicTo(ctrl1, ctrl2, to) => { builder.cubic_bezier_to(ctrl1, ctrl2, to); }
+                PathEvent::Close => { builder.close(); }
+            }
+        }
+
+        return builder.build();
+    }
+
+    /// The approximate amount of memory used by the path, in bytes.
+    pub fn memory_size(&self) -> usize {
+        ::std::mem::size_of::<Self>() +
+            self.verbs.len() * ::std::mem::size_of::<Verb>() +
+            self.points.len() * ::std::mem::size_of::<[i16; 2]>()
+    }
+}
+
+impl<'l> IntoIterator for &'l NormalizedPath {
+    type Item = PathEvent;
+    type IntoIter = NormalizedPathIter<'l>;
+
+    fn into_iter(self) -> NormalizedPathIter<'l> { self.iter() }
+}
+
+fn quantize_snorm(bounds: &Rect, p: Point) -> [i16; 2] {
+    fn axis(v: f32, min: f32, size: f32) -> i16 {
+        if !(size > 0.0) {
+            return 0;
+        }
+        let n = ((v - min) / size * 2.0 - 1.0).max(-1.0).min(1.0);
+        return (n * SNORM_MAX).round() as i16;
+    }
+
+    [
+        axis(p.x, bounds.origin.x, bounds.size.width),
+        axis(p.y, bounds.origin.y, bounds.size.height),
+    ]
+}
+
+#[inline]
+fn dequantize_snorm(bounds: &Rect, p: [i16; 2]) -> Point {
+    let nx = (p[0] as f32).max(-SNORM_MAX) / SNORM_MAX;
+    let ny = (p[1] as f32).max(-SNORM_MAX) / SNORM_MAX;
+    point(
+        bounds.origin.x + (nx + 1.0) * 0.5 * bounds.size.width,
+        bounds.origin.y + (ny + 1.0) * 0.5 * bounds.size.height,
+    )
+}
+
+/// Builds a [NormalizedPath](struct.NormalizedPath.html), quantizing the points as they
+/// are added.
+pub struct NormalizedBuilder {
+    path: NormalizedPath,
+    current_position: Point,
+    first_position: Point,
+}
+
+impl NormalizedBuilder {
+    pub fn new(bounds: Rect) -> Self {
+        NormalizedBuilder {
+            path: NormalizedPath {
+                verbs: Vec::new(),
+                points: Vec::new(),
+                bounds: bounds,
+            },
+            current_position: point(0.0, 0.0),
+            first_position: point(0.0, 0.0),
+        }
+    }
+
+    fn push_point(&mut self, p: Point) {
+        let q = quantize_snorm(&self.path.bounds, p);
+        self.path.points.push(q);
+    }
+}
+
+impl BaseBuilder for NormalizedBuilder {
+    type PathType = NormalizedPath;
+
+    fn move_to(&mut self, to: Point) {
+        self.push_point(to);
+        self.path.verbs.push(Verb::MoveTo);
+        self.first_position = to;
+        self.current_position = to;
+    }
+
+    fn line_to(&mut self, to: Point) {
+        self.push_point(to);
+        self.path.verbs.push(Verb::LineTo);
+        self.current_position = to;
+    }
+
+    fn close(&mut self) {
+        self.path.verbs.push(Verb::Close);
+        self.current_position = self.first_position;
+    }
+
+    fn current_position(&self) -> Point { self.current_position }
+
+    fn build(self) -> NormalizedPath { self.path }
+
+    fn build_and_reset(&mut self) -> NormalizedPath {
+        self.current_position = point(0.0, 0.0);
+        self.first_position = point(0.0, 0.0);
+        let bounds = self.path.bounds;
+        return ::std::mem::replace(&mut self.path, NormalizedPath {
+            verbs: Vec::new(),
+            points: Vec::new(),
+            bounds: bounds,
+        });
+    }
+}
+
+impl PathBuilder for NormalizedBuilder {
+    fn quadratic_bezier_to(&mut self, ctrl: Point, to: Point) {
+        self.push_point(ctrl);
+        self.push_point(to);
+        self.path.verbs.push(Verb::QuadraticTo);
+        self.current_position = to;
+    }
+
+    fn cubic_bezier_to(&mut self, ctrl1: Point, ctrl2: Point, to: Point) {
+        self.push_point(ctrl1);
+        self.push_point(ctrl2);
+        self.push_point(to);
+        self.path.verbs.push(Verb::CubicTo);
+        self.current_position = to;
+    }
+}
+
+/// An iterator of `PathEvent` over a `NormalizedPath`, converting the coordinates lazily.
+#[derive(Clone, Debug)]
+pub struct NormalizedPathIter<'l> {
+    path: &'l NormalizedPath,
+    verb: usize,
+    point: usize,
+}
+
+impl<'l> NormalizedPathIter<'l> {
+    fn next_point(&mut self) -> Point {
+        let p = self.path.point(self.point);
+        self.point += 1;
+        return p;
+    }
+}
+
+impl<'l> Iterator for NormalizedPathIter<'l> {
+    type Item = PathEvent;
+    fn next(&mut self) -> Option<PathEvent> {
+        if self.verb >= self.path.verbs.len() {
+            return None;
+        }
+
+        let verb = self.path.verbs[self.verb];
+        self.verb += 1;
+
+        return Some(match verb {
+            Verb::MoveTo => PathEvent::MoveTo(self.next_point()),
+            Verb::LineTo => PathEvent::LineTo(self.next_point()),
+            Verb::QuadraticTo => {
+                let ctrl = self.next_point();
+                let to = self.next_point();
+                PathEvent::QuadraticTo(ctrl, to)
+            }
+            Verb::CubicTo => {
+                let ctrl1 = self.next_point();
+                let ctrl2 = self.next_point();
+                let to = self.next_point();
+                PathEvent::CubicTo(ctrl1, ctrl2, to)
+            }
+            Verb::Close => PathEvent::Close,
+        });
+    }
+}
+
+#[test]
+fn test_quantized_path() {
+    let mut builder = Path::builder();
+    builder.move_to(point(-100.0, 20.0));
+    builder.line_to(point(300.0, 20.5));
+    builder.quadratic_bezier_to(point(250.0, 100.0), point(100.0, 150.0));
+    builder.cubic_bezier_to(point(50.0, 120.0), point(-50.0, 130.0), point(-100.0, 100.0));
+    builder.close();
+    let path = builder.build();
+
+    let quantized = QuantizedPath::from_path(&path);
+    assert!(quantized.precision() <= 400.0 / 65535.0 + 0.0001);
+    let path_size = ::std::mem::size_of::<Path>() +
+        path.points().len() * ::std::mem::size_of::<Point>() +
+        path.verbs().len() * ::std::mem::size_of::<Verb>();
+    assert!(quantized.memory_size() < path_size);
+
+    let max_error = quantized.precision() * 0.5 + 0.0001;
+    let mut n = 0;
+    for (a, b) in path.iter().zip(quantized.iter()) {
+        match (a, b) {
+            (PathEvent::MoveTo(a), PathEvent::MoveTo(b)) |
+            (PathEvent::LineTo(a), PathEvent::LineTo(b)) => {
+                assert!((a.x - b.x).abs() <= max_error && (a.y - b.y).abs() <= max_error);
+            }
+            (PathEvent::QuadraticTo(_, a), PathEvent::QuadraticTo(_, b)) |
+            (PathEvent::CubicTo(_, _, a), PathEvent::CubicTo(_, _, b)) => {
+                assert!((a.x - b.x).abs() <= max_error && (a.y - b.y).abs() <= max_error);
+            }
+            (PathEvent::Close, PathEvent::Close) => {}
+            _ => { panic!("mismatched events {:?} {:?}", a, b); }
+        }
+        n += 1;
+    }
+    assert_eq!(n, 5);
+    assert_eq!(quantized.to_path().verbs(), path.verbs());
+}
+
+#[test]
+fn test_quantized_empty_path() {
+    let quantized = QuantizedPath::from_path(&Path::new());
+    assert_eq!(quantized.iter().next(), None);
+}
+
+#[test]
+fn test_normalized_path() {
+    // A 4096 units tile.
+    let bounds = rect(0.0, 0.0, 4096.0, 4096.0);
+    let mut builder = NormalizedPath::builder(bounds);
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(4096.0, 0.0));
+    builder.quadratic_bezier_to(point(4096.0, 2048.0), point(2048.0, 4096.0));
+    builder.line_to(point(5000.0, -10.0));
+    builder.close();
+    let path = builder.build();
+
+    assert_eq!(path.raw_points()[0], [-32767, -32767]);
+    assert_eq!(path.raw_points()[1], [32767, -32767]);
+    assert_eq!(path.raw_points()[2], [32767, 0]);
+    // Clamped to the bounds.
+    assert_eq!(path.raw_points()[4], [32767, -32767]);
+
+    let events: Vec<PathEvent> = path.iter().collect();
+    assert_eq!(events[0], PathEvent::MoveTo(point(0.0, 0.0)));
+    assert_eq!(events[1], PathEvent::LineTo(point(4096.0, 0.0)));
+    assert_eq!(events[3], PathEvent::LineTo(point(4096.0, 0.0)));
+    assert_eq!(events[4], PathEvent::Close);
+    assert_eq!(events.len(), 5);
+    match events[2] {
+        PathEvent::QuadraticTo(ctrl, to) => {
+            let max_error = path.precision().x * 0.5 + 0.0001;
+            assert!((ctrl.x - 4096.0).abs() <= max_error && (ctrl.y - 2048.0).abs() <= max_error);
+            assert!((to.x - 2048.0).abs() <= max_error && (to.y - 4096.0).abs() <= max_error);
+        }
+        _ => { panic!("unexpected event {:?}", events[2]); }
+    }
+
+    // The raw data can be used as is.
+    let raw = NormalizedPath::from_raw(
+        bounds,
+        path.verbs().to_vec(),
+        path.raw_points().to_vec(),
+    );
+    assert_eq!(raw.to_path().points(), path.to_path().points());
+    assert_eq!(NormalizedPath::from_raw(bounds, vec![Verb::MoveTo], vec![[::std::i16::MIN, 0]]).point(0).x, 0.0);
+    assert!(path.memory_size() < ::std::mem::size_of::<NormalizedPath>() + 5 * 4 + 5 * ::std::mem::size_of::<Point>());
+}
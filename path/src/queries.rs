@@ -0,0 +1,777 @@
+//! Geometric queries on paths.
+
+use std::f32::consts::PI;
+
+use bezier::{LineSegment, QuadraticBezierSegment, CubicBezierSegment, Arc};
+use core::{PathEvent, SvgEvent, PathState};
+use path_builder::{BaseBuilder, SvgBuilder};
+use core::math::*;
+use core::ArcFlags;
+
+use path::{Path, PathIter, Verb};
+
+/// A line or curve segment of a path.
+#[derive(Copy, Clone, Debug)]
+pub enum Segment {
+    Line(LineSegment),
+    Quadratic(QuadraticBezierSegment),
+    Cubic(CubicBezierSegment),
+    Arc(Arc),
+}
+
+impl Segment {
+    pub fn from(&self) -> Point {
+        match *self {
+            Segment::Line(ref s) => s.from,
+            Segment::Quadratic(ref s) => s.from,
+            Segment::Cubic(ref s) => s.from,
+            Segment::Arc(ref s) => s.from(),
+        }
+    }
+
+    pub fn to(&self) -> Point {
+        match *self {
+            Segment::Line(ref s) => s.to,
+            Segment::Quadratic(ref s) => s.to,
+            Segment::Cubic(ref s) => s.to,
+            Segment::Arc(ref s) => s.to(),
+        }
+    }
+
+    /// Sample the segment at t (expecting t between 0 and 1).
+    pub fn sample(&self, t: f32) -> Point {
+        match *self {
+            Segment::Line(ref s) => s.sample(t),
+            Segment::Quadratic(ref s) => s.sample(t),
+            Segment::Cubic(ref s) => s.sample(t),
+            Segment::Arc(ref s) => s.sample(t),
+        }
+    }
+
+    /// Sample the derivative of the segment at t (expecting t between 0 and 1).
+    pub fn derivative(&self, t: f32) -> Vec2 {
+        match *self {
+            Segment::Line(ref s) => s.to_vector(),
+            Segment::Quadratic(ref s) => s.derivative(t),
+            Segment::Cubic(ref s) => s.derivative(t),
+            Segment::Arc(ref s) => s.sample_tangent(t) * s.length(),
+        }
+    }
+
+    /// Signed curvature of the segment at t (expecting t between 0 and 1), positive when the
+    /// segment turns counter-clockwise (with the y axis pointing up).
+    pub fn curvature(&self, t: f32) -> f32 {
+        match *self {
+            Segment::Line(_) => 0.0,
+            Segment::Quadratic(ref s) => s.curvature(t),
+            Segment::Cubic(ref s) => s.curvature(t),
+            Segment::Arc(ref s) => if s.sweep_angle.get() < 0.0 { -1.0 / s.radius } else { 1.0 / s.radius },
+        }
+    }
+
+    /// The signed area of the region between the segment and the origin, positive when the
+    /// segment turns counter-clockwise around the origin (with the y axis pointing up).
+    ///
+    /// This is the term of the shoelace formula extended to curves: summing it over the
+    /// segments of a closed sub-path gives the signed area of the sub-path.
+    pub fn signed_area(&self) -> f32 {
+        fn cross(a: Point, b: Point) -> f32 { a.to_vector().cross(b.to_vector()) }
+        match *self {
+            Segment::Line(ref s) => cross(s.from, s.to) * 0.5,
+            Segment::Quadratic(ref s) => {
+                (2.0 * (cross(s.from, s.ctrl) + cross(s.ctrl, s.to)) + cross(s.from, s.to)) / 6.0
+            }
+            Segment::Cubic(ref s) => {
+                (6.0 * cross(s.from, s.ctrl1)
+                    + 3.0 * cross(s.from, s.ctrl2)
+                    + cross(s.from, s.to)
+                    + 3.0 * cross(s.ctrl1, s.ctrl2)
+                    + 3.0 * cross(s.ctrl1, s.to)
+                    + 6.0 * cross(s.ctrl2, s.to)) / 20.0
+            }
+            Segment::Arc(ref s) => {
+                // The circular sector plus the triangle between the center, the origin and
+                // the chord.
+                let center = s.center.to_vector();
+                (s.radius * s.radius * s.sweep_angle.get()
+                    + center.cross(s.to() - s.from())) * 0.5
+            }
+        }
+    }
+
+    /// A rectangle containing the segment (and its control points).
+    pub fn bounding_rect(&self) -> Rect {
+        match *self {
+            Segment::Line(ref s) => s.bounding_rect(),
+            Segment::Quadratic(ref s) => s.bounding_rect(),
+            Segment::Cubic(ref s) => s.bounding_rect(),
+            Segment::Arc(ref s) => s.bounding_rect(),
+        }
+    }
+
+    /// Returns the parameter of the point of the segment that is the closest to a position.
+    ///
+    /// The tolerance only applies to bézier curves, the closest point of lines and arcs is exact.
+    pub fn closest_point_t(&self, position: Point, tolerance: f32) -> f32 {
+        match *self {
+            Segment::Line(ref s) => s.closest_point_t(position),
+            Segment::Quadratic(ref s) => s.closest_point_t(position, tolerance),
+            Segment::Cubic(ref s) => s.closest_point_t(position, tolerance),
+            Segment::Arc(ref s) => s.closest_point_t(position),
+        }
+    }
+
+    /// Adds the intersections of a ray with the segment to `output`, with the distance from
+    /// `origin` and the position of each intersection.
+    ///
+    /// `direction` is expected to be normalized. Curves are flattened with the given tolerance.
+    /// The end point of the segment is excluded so that a ray going through the junction of two
+    /// consecutive segments only produces one intersection.
+    pub fn intersect_ray(&self, origin: Point, direction: Vec2, tolerance: f32, output: &mut Vec<(f32, Point)>) {
+        // The segment is inside of the convex hull of its control points (or of the bounding
+        // rectangle of arcs).
+        let (points, n) = match *self {
+            Segment::Line(ref s) => ([s.from, s.to, s.to, s.to], 2),
+            Segment::Quadratic(ref s) => ([s.from, s.ctrl, s.to, s.to], 3),
+            Segment::Cubic(ref s) => ([s.from, s.ctrl1, s.ctrl2, s.to], 4),
+            Segment::Arc(ref s) => {
+                let r = s.bounding_rect();
+                ([r.origin, r.top_right(), r.bottom_right(), r.bottom_left()], 4)
+            }
+        };
+        let mut above = false;
+        let mut below = false;
+        let mut ahead = false;
+        for p in &points[0..n] {
+            let side = direction.cross(*p - origin);
+            above |= side >= 0.0;
+            below |= side <= 0.0;
+            ahead |= direction.dot(*p - origin) >= 0.0;
+        }
+        if !above || !below || !ahead {
+            return;
+        }
+
+        let mut from = self.from();
+        let mut add_edge = |to: Point| {
+            intersect_ray_edge(from, to, origin, direction, output);
+            from = to;
+        };
+        match *self {
+            Segment::Line(ref s) => { add_edge(s.to); }
+            Segment::Quadratic(ref s) => { s.flattened_for_each(tolerance, &mut add_edge); }
+            Segment::Cubic(ref s) => { s.flattened_for_each(tolerance, &mut add_edge); }
+            Segment::Arc(ref s) => { s.flattened_for_each(tolerance, &mut add_edge); }
+        }
+    }
+}
+
+/// An iterator over the segments of a sequence of path events.
+///
+/// Closing a sub-path produces a line segment back to its start, even if it is empty.
+#[derive(Clone, Debug)]
+pub struct SegmentIter<Iter> {
+    events: Iter,
+    first: Point,
+    current: Point,
+}
+
+impl<Iter: Iterator<Item = PathEvent>> SegmentIter<Iter> {
+    pub fn new(events: Iter) -> Self {
+        SegmentIter {
+            events: events,
+            first: point(0.0, 0.0),
+            current: point(0.0, 0.0),
+        }
+    }
+}
+
+impl<Iter: Iterator<Item = PathEvent>> Iterator for SegmentIter<Iter> {
+    type Item = Segment;
+    fn next(&mut self) -> Option<Segment> {
+        loop {
+            let from = self.current;
+            let segment = match self.events.next() {
+                Some(PathEvent::MoveTo(to)) => {
+                    self.first = to;
+                    self.current = to;
+                    continue;
+                }
+                Some(PathEvent::LineTo(to)) => {
+                    self.current = to;
+                    Segment::Line(LineSegment { from: from, to: to })
+                }
+                Some(PathEvent::QuadraticTo(ctrl, to)) => {
+                    self.current = to;
+                    Segment::Quadratic(QuadraticBezierSegment { from: from, ctrl: ctrl, to: to })
+                }
+                Some(PathEvent::CubicTo(ctrl1, ctrl2, to)) => {
+                    self.current = to;
+                    Segment::Cubic(CubicBezierSegment { from: from, ctrl1: ctrl1, ctrl2: ctrl2, to: to })
+                }
+                Some(PathEvent::Close) => {
+                    self.current = self.first;
+                    Segment::Line(LineSegment { from: from, to: self.first })
+                }
+                None => { return None; }
+            };
+
+            return Some(segment);
+        }
+    }
+}
+
+/// An iterator over the segments of a sequence of SVG events.
+///
+/// The relative, horizontal, vertical and smooth events are resolved into absolute segments.
+/// Circular arcs produce arc segments while elliptic arcs, which the
+/// [Arc](../lyon_bezier/struct.Arc.html) segment can't represent, are approximated with cubic
+/// bézier segments. As with [SegmentIter](struct.SegmentIter.html), closing a sub-path produces
+/// a line segment back to its start.
+pub struct SvgSegmentIter<Iter> {
+    events: Iter,
+    state: PathState,
+    // The remaining cubic bézier segments of an elliptic arc, in reverse order.
+    pending: Vec<Segment>,
+}
+
+impl<Iter: Iterator<Item = SvgEvent>> SvgSegmentIter<Iter> {
+    pub fn new(events: Iter) -> Self {
+        SvgSegmentIter {
+            events: events,
+            state: PathState::new(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<Iter: Iterator<Item = SvgEvent>> Iterator for SvgSegmentIter<Iter> {
+    type Item = Segment;
+    fn next(&mut self) -> Option<Segment> {
+        loop {
+            if let Some(segment) = self.pending.pop() {
+                return Some(segment);
+            }
+
+            let event = match self.events.next() {
+                Some(event) => event,
+                None => { return None; }
+            };
+
+            let from = self.state.current;
+            let first = self.state.first;
+            let segment = match event {
+                SvgEvent::ArcTo(to, radii, x_rotation, flags) => {
+                    arc_segment(from, to, radii, x_rotation, flags, &mut self.pending)
+                }
+                SvgEvent::RelativeArcTo(to, radii, x_rotation, flags) => {
+                    let to = self.state.from_relative(to);
+                    arc_segment(from, to, radii, x_rotation, flags, &mut self.pending)
+                }
+                _ => match self.state.svg_to_path_event(event) {
+                    PathEvent::MoveTo(_) => None,
+                    PathEvent::LineTo(to) => {
+                        Some(Segment::Line(LineSegment { from: from, to: to }))
+                    }
+                    PathEvent::QuadraticTo(ctrl, to) => {
+                        Some(Segment::Quadratic(QuadraticBezierSegment { from: from, ctrl: ctrl, to: to }))
+                    }
+                    PathEvent::CubicTo(ctrl1, ctrl2, to) => {
+                        Some(Segment::Cubic(CubicBezierSegment { from: from, ctrl1: ctrl1, ctrl2: ctrl2, to: to }))
+                    }
+                    PathEvent::Close => {
+                        Some(Segment::Line(LineSegment { from: from, to: first }))
+                    }
+                },
+            };
+            self.state.svg_event(event);
+
+            if let Some(segment) = segment {
+                return Some(segment);
+            }
+        }
+    }
+}
+
+// Converts an SVG arc into an arc segment if it is circular. Elliptic arcs are approximated
+// with cubic bézier segments that are added to `pending` in reverse order, and the first one
+// is returned.
+fn arc_segment(
+    from: Point,
+    to: Point,
+    radii: Vec2,
+    x_rotation: Radians<f32>,
+    flags: ArcFlags,
+    pending: &mut Vec<Segment>,
+) -> Option<Segment> {
+    if from == to {
+        return None;
+    }
+
+    let rx = radii.x.abs();
+    let ry = radii.y.abs();
+    if rx == 0.0 || ry == 0.0 {
+        return Some(Segment::Line(LineSegment { from: from, to: to }));
+    }
+
+    if rx != ry {
+        let mut builder = Path::builder().with_svg();
+        builder.move_to(from);
+        builder.arc_to(to, radii, x_rotation, flags);
+        pending.extend(builder.build().segments());
+        pending.reverse();
+        return pending.pop();
+    }
+
+    // The radius is scaled up if it is too small for the arc to reach the end point.
+    let chord = to - from;
+    let half_length = chord.length() * 0.5;
+    let radius = rx.max(half_length);
+    let direction = chord / (half_length * 2.0);
+    let normal = vec2(-direction.y, direction.x);
+    let height = (radius * radius - half_length * half_length).max(0.0).sqrt();
+    let side = if flags.large_arc != flags.sweep { 1.0 } else { -1.0 };
+    let center = from + chord * 0.5 + normal * height * side;
+
+    let start_angle = (from.y - center.y).atan2(from.x - center.x);
+    let end_angle = (to.y - center.y).atan2(to.x - center.x);
+    let mut sweep_angle = end_angle - start_angle;
+    if flags.sweep && sweep_angle < 0.0 {
+        sweep_angle += 2.0 * PI;
+    } else if !flags.sweep && sweep_angle > 0.0 {
+        sweep_angle -= 2.0 * PI;
+    }
+
+    return Some(Segment::Arc(Arc {
+        center: center,
+        radius: radius,
+        start_angle: Radians::new(start_angle),
+        sweep_angle: Radians::new(sweep_angle),
+    }));
+}
+
+/// The orientation of a sub-path.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Winding {
+    /// Counter-clockwise with the y axis pointing up (clockwise on screen, with the y axis
+    /// pointing down). The signed area is positive.
+    CounterClockwise,
+    /// Clockwise with the y axis pointing up. The signed area is negative.
+    Clockwise,
+    /// The signed area is zero, for example if the sub-path is empty or flat.
+    Degenerate,
+}
+
+/// The result of [Path::closest_point](struct.Path.html#method.closest_point).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClosestPoint {
+    pub distance: f32,
+    pub point: Point,
+    /// Index of the segment in the [segment iterator](struct.SegmentIter.html) of the path.
+    pub segment: usize,
+    /// Parameter of the point on the segment.
+    pub t: f32,
+}
+
+impl Path {
+    /// Iterates over the line and curve segments of the path.
+    pub fn segments(&self) -> SegmentIter<PathIter> { SegmentIter::new(self.iter()) }
+
+    /// The number of sub-paths, each of them starting with a `MoveTo` event.
+    pub fn num_sub_paths(&self) -> usize {
+        self.verbs().iter().filter(|verb| **verb == Verb::MoveTo).count()
+    }
+
+    /// The signed area of a sub-path, positive if it is counter-clockwise (with the y axis
+    /// pointing up).
+    ///
+    /// The area of the curves is computed exactly rather than from a flattened
+    /// approximation. Sub-paths that aren't closed are implicitly closed with a line segment.
+    ///
+    /// Panics if the index is not smaller than `num_sub_paths()`.
+    pub fn sub_path_signed_area(&self, index: usize) -> f32 {
+        let mut sub_path = None;
+        let mut num_sub_paths = 0;
+        let mut first = point(0.0, 0.0);
+        let mut current = point(0.0, 0.0);
+        let mut area = 0.0;
+        for evt in self.iter() {
+            let segment = match evt {
+                PathEvent::MoveTo(to) => {
+                    if sub_path == Some(index) {
+                        break;
+                    }
+                    sub_path = Some(num_sub_paths);
+                    num_sub_paths += 1;
+                    first = to;
+                    current = to;
+                    continue;
+                }
+                _ if sub_path != Some(index) => { continue; }
+                PathEvent::LineTo(to) => {
+                    Segment::Line(LineSegment { from: current, to: to })
+                }
+                PathEvent::QuadraticTo(ctrl, to) => {
+                    Segment::Quadratic(QuadraticBezierSegment { from: current, ctrl: ctrl, to: to })
+                }
+                PathEvent::CubicTo(ctrl1, ctrl2, to) => {
+                    Segment::Cubic(CubicBezierSegment { from: current, ctrl1: ctrl1, ctrl2: ctrl2, to: to })
+                }
+                PathEvent::Close => {
+                    Segment::Line(LineSegment { from: current, to: first })
+                }
+            };
+            area += segment.signed_area();
+            current = segment.to();
+        }
+
+        assert!(sub_path == Some(index), "sub-path {} out of range", index);
+
+        // Implicitly close the sub-path.
+        area += Segment::Line(LineSegment { from: current, to: first }).signed_area();
+
+        return area;
+    }
+
+    /// The orientation of a sub-path, from the sign of its
+    /// [signed area](#method.sub_path_signed_area).
+    ///
+    /// Useful to tell holes apart from the outer contours, for example in font outlines.
+    ///
+    /// Panics if the index is not smaller than `num_sub_paths()`.
+    pub fn sub_path_orientation(&self, index: usize) -> Winding {
+        let area = self.sub_path_signed_area(index);
+        if area > 0.0 {
+            return Winding::CounterClockwise;
+        }
+        if area < 0.0 {
+            return Winding::Clockwise;
+        }
+        return Winding::Degenerate;
+    }
+
+    /// Finds the point of the path that is the closest to a position.
+    ///
+    /// The segments are visited by increasing distance to their bounding rectangle, and the
+    /// search stops as soon as the remaining segments can't be closer than the closest point
+    /// found so far. The position of the closest point on curves is within `tolerance`.
+    ///
+    /// Returns None if the path doesn't have any segment.
+    pub fn closest_point(&self, position: Point, tolerance: f32) -> Option<ClosestPoint> {
+        let mut candidates: Vec<(f32, usize, Segment)> = self.segments().enumerate().map(|(i, segment)| {
+            (distance_to_rect(position, &segment.bounding_rect()), i, segment)
+        }).collect();
+        candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let mut result: Option<ClosestPoint> = None;
+        for &(lower_bound, i, ref segment) in &candidates {
+            if let Some(ref closest) = result {
+                if lower_bound > closest.distance {
+                    break;
+                }
+            }
+
+            let t = segment.closest_point_t(position, tolerance);
+            let p = segment.sample(t);
+            let distance = (p - position).length();
+            let closer = match result {
+                Some(ref closest) => distance < closest.distance,
+                None => true,
+            };
+            if closer {
+                result = Some(ClosestPoint { distance: distance, point: p, segment: i, t: t });
+            }
+        }
+
+        return result;
+    }
+}
+
+/// The distance between a position and a rectangle, zero if the position is inside.
+pub fn distance_to_rect(position: Point, rect: &Rect) -> f32 {
+    let dx = (rect.min_x() - position.x).max(position.x - rect.max_x()).max(0.0);
+    let dy = (rect.min_y() - position.y).max(position.y - rect.max_y()).max(0.0);
+
+    return (dx * dx + dy * dy).sqrt();
+}
+
+/// Computes the intersections of a ray with the line and curve segments of a path.
+///
+/// Returns the distance from `origin` and the position of each intersection, sorted by
+/// increasing distance. The curves are flattened with the given tolerance, and only the
+/// segments whose control points are on both sides of the ray are looked at. The sub-paths
+/// are not implicitly closed, and segments that are parallel to the ray don't produce any
+/// intersection.
+pub fn intersect_ray<Iter>(path: Iter, origin: Point, direction: Vec2, tolerance: f32) -> Vec<(f32, Point)>
+where
+    Iter: Iterator<Item = PathEvent>,
+{
+    let mut result = Vec::new();
+    let length = direction.length();
+    if length == 0.0 {
+        return result;
+    }
+    let direction = direction / length;
+
+    let mut first = point(0.0, 0.0);
+    let mut current = point(0.0, 0.0);
+    let mut open = false;
+    for evt in path {
+        let segment = match evt {
+            PathEvent::MoveTo(to) => {
+                if open {
+                    intersect_ray_end(current, first, origin, direction, &mut result);
+                }
+                first = to;
+                current = to;
+                open = false;
+                continue;
+            }
+            PathEvent::LineTo(to) => {
+                open = true;
+                Segment::Line(LineSegment { from: current, to: to })
+            }
+            PathEvent::QuadraticTo(ctrl, to) => {
+                open = true;
+                Segment::Quadratic(QuadraticBezierSegment { from: current, ctrl: ctrl, to: to })
+            }
+            PathEvent::CubicTo(ctrl1, ctrl2, to) => {
+                open = true;
+                Segment::Cubic(CubicBezierSegment { from: current, ctrl1: ctrl1, ctrl2: ctrl2, to: to })
+            }
+            PathEvent::Close => {
+                open = false;
+                Segment::Line(LineSegment { from: current, to: first })
+            }
+        };
+        current = segment.to();
+        segment.intersect_ray(origin, direction, tolerance, &mut result);
+    }
+    if open {
+        intersect_ray_end(current, first, origin, direction, &mut result);
+    }
+
+    result.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    return result;
+}
+
+fn intersect_ray_edge(a: Point, b: Point, origin: Point, direction: Vec2, output: &mut Vec<(f32, Point)>) {
+    let e = b - a;
+    let denom = direction.cross(e);
+    if denom == 0.0 {
+        return;
+    }
+    let w = a - origin;
+    let distance = w.cross(e) / denom;
+    let u = w.cross(direction) / denom;
+    if distance >= 0.0 && u >= 0.0 && u < 1.0 {
+        output.push((distance, a + e * u));
+    }
+}
+
+// The end of an open sub-path isn't covered by the half-open edges.
+fn intersect_ray_end(end: Point, first: Point, origin: Point, direction: Vec2, output: &mut Vec<(f32, Point)>) {
+    let w = end - origin;
+    if end != first && direction.cross(w) == 0.0 && direction.dot(w) >= 0.0 {
+        output.push((w.length(), end));
+    }
+}
+
+#[cfg(test)]
+use path_builder::PathBuilder;
+
+#[test]
+fn test_segments() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.quadratic_bezier_to(point(2.0, 0.0), point(2.0, 1.0));
+    builder.close();
+    builder.move_to(point(10.0, 0.0));
+    builder.cubic_bezier_to(point(11.0, 0.0), point(12.0, 1.0), point(12.0, 2.0));
+    let path = builder.build();
+
+    let segments: Vec<Segment> = path.segments().collect();
+    assert_eq!(segments.len(), 4);
+    assert_eq!((segments[2].from(), segments[2].to()), (point(2.0, 1.0), point(0.0, 0.0)));
+    assert_eq!(segments[3].from(), point(10.0, 0.0));
+}
+
+#[test]
+fn test_svg_segments() {
+    let flags = ArcFlags { large_arc: false, sweep: true };
+    let events = vec![
+        SvgEvent::MoveTo(point(0.0, 0.0)),
+        SvgEvent::RelativeLineTo(vec2(10.0, 0.0)),
+        // The radius is too small and gets scaled up to 5.
+        SvgEvent::ArcTo(point(20.0, 0.0), vec2(2.0, 2.0), Radians::new(0.0), flags),
+        SvgEvent::HorizontalLineTo(30.0),
+        SvgEvent::RelativeArcTo(vec2(10.0, 0.0), vec2(10.0, 5.0), Radians::new(0.0), flags),
+        SvgEvent::Close,
+    ];
+    let segments: Vec<Segment> = SvgSegmentIter::new(events.into_iter()).collect();
+
+    match segments[0] {
+        Segment::Line(s) => { assert_eq!((s.from, s.to), (point(0.0, 0.0), point(10.0, 0.0))); }
+        _ => { panic!("{:?}", segments[0]); }
+    }
+    match segments[1] {
+        Segment::Arc(s) => {
+            assert_eq!(s.center, point(15.0, 0.0));
+            assert_eq!(s.radius, 5.0);
+            assert_eq!(s.sweep_angle.get(), PI);
+            assert!((s.to() - point(20.0, 0.0)).length() < 0.0001);
+            assert!(segments[1].curvature(0.5) > 0.0);
+        }
+        _ => { panic!("{:?}", segments[1]); }
+    }
+    assert_eq!(segments[2].from(), point(20.0, 0.0));
+    assert_eq!(segments[2].to(), point(30.0, 0.0));
+
+    // The elliptic arc is approximated with cubic bézier segments.
+    let n = segments.len();
+    assert!(n > 4);
+    for segment in &segments[3..(n - 1)] {
+        match *segment {
+            Segment::Cubic(_) => {}
+            _ => { panic!("{:?}", segment); }
+        }
+    }
+    assert!((segments[n - 2].to() - point(40.0, 0.0)).length() < 0.01);
+    assert_eq!(segments[n - 1].to(), point(0.0, 0.0));
+}
+
+#[test]
+fn test_closest_point() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.quadratic_bezier_to(point(20.0, 0.0), point(20.0, 10.0));
+    builder.move_to(point(0.0, 20.0));
+    builder.line_to(point(20.0, 20.0));
+    let path = builder.build();
+
+    let closest = path.closest_point(point(5.0, 3.0), 0.01).unwrap();
+    assert_eq!(closest.segment, 0);
+    assert_eq!(closest.point, point(5.0, 0.0));
+    assert_eq!(closest.distance, 3.0);
+    assert_eq!(closest.t, 0.5);
+
+    let closest = path.closest_point(point(5.0, 18.0), 0.01).unwrap();
+    assert_eq!(closest.segment, 2);
+    assert_eq!(closest.distance, 2.0);
+
+    // On the curve.
+    let closest = path.closest_point(point(20.0, 10.0), 0.001).unwrap();
+    assert_eq!(closest.segment, 1);
+    assert!(closest.distance < 0.01);
+    assert!(closest.t > 0.99);
+
+    assert!(Path::new().closest_point(point(0.0, 0.0), 0.01).is_none());
+}
+
+#[test]
+fn test_intersect_ray_lines() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    // The direction doesn't need to be normalized.
+    let hits = intersect_ray(path.iter(), point(-5.0, 5.0), vec2(2.0, 0.0), 0.01);
+    assert_eq!(hits, vec![(5.0, point(0.0, 5.0)), (15.0, point(10.0, 5.0))]);
+
+    // From the inside.
+    let hits = intersect_ray(path.iter(), point(5.0, 3.0), vec2(0.0, -1.0), 0.01);
+    assert_eq!(hits, vec![(3.0, point(5.0, 0.0))]);
+
+    // Pointing away.
+    assert!(intersect_ray(path.iter(), point(20.0, 5.0), vec2(1.0, 0.0), 0.01).is_empty());
+    assert!(intersect_ray(path.iter(), point(-5.0, 5.0), vec2(0.0, 0.0), 0.01).is_empty());
+}
+
+#[test]
+fn test_intersect_ray_curves() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.quadratic_bezier_to(point(5.0, 10.0), point(10.0, 0.0));
+    builder.move_to(point(0.0, 20.0));
+    builder.cubic_bezier_to(point(0.0, 30.0), point(10.0, 30.0), point(10.0, 20.0));
+    let path = builder.build();
+
+    // The top of the quadratic curve is at (5.0, 5.0).
+    let hits = intersect_ray(path.iter(), point(5.0, -5.0), vec2(0.0, 1.0), 0.01);
+    assert_eq!(hits.len(), 2);
+    assert!((hits[0].0 - 10.0).abs() < 0.05);
+    assert!((hits[0].1 - point(5.0, 5.0)).length() < 0.05);
+    // The top of the cubic curve is at (5.0, 27.5).
+    assert!((hits[1].0 - 32.5).abs() < 0.05);
+
+    // The quadratic curve is at y = 2 for x = 10 * t with 20 * t * (1 - t) = 2.
+    let hits = intersect_ray(path.iter(), point(-1.0, 2.0), vec2(1.0, 0.0), 0.01);
+    assert_eq!(hits.len(), 2);
+    let t0 = (1.0 - 0.6f32.sqrt()) * 0.5;
+    let t1 = (1.0 + 0.6f32.sqrt()) * 0.5;
+    assert!((hits[0].0 - (1.0 + 10.0 * t0)).abs() < 0.05);
+    assert!((hits[1].0 - (1.0 + 10.0 * t1)).abs() < 0.05);
+
+    // Above the quadratic curve and below the cubic one.
+    assert!(intersect_ray(path.iter(), point(-1.0, 10.0), vec2(1.0, 0.0), 0.01).is_empty());
+}
+
+#[test]
+fn test_sub_path_orientation() {
+    let curve = QuadraticBezierSegment {
+        from: point(0.0, 20.0),
+        ctrl: point(5.0, 30.0),
+        to: point(10.0, 20.0),
+    };
+    let cubic = curve.to_cubic();
+
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    // A hole.
+    builder.move_to(point(2.0, 2.0));
+    builder.line_to(point(2.0, 8.0));
+    builder.line_to(point(8.0, 8.0));
+    builder.line_to(point(8.0, 2.0));
+    builder.close();
+    // Open sub-paths are implicitly closed.
+    builder.move_to(curve.from);
+    builder.quadratic_bezier_to(curve.ctrl, curve.to);
+    builder.move_to(cubic.from);
+    builder.cubic_bezier_to(cubic.ctrl1, cubic.ctrl2, cubic.to);
+    builder.move_to(point(20.0, 20.0));
+    let path = builder.build();
+
+    assert_eq!(path.num_sub_paths(), 5);
+    assert_eq!(path.sub_path_signed_area(0), 100.0);
+    assert_eq!(path.sub_path_signed_area(1), -36.0);
+    // Two thirds of the triangle formed by the control points.
+    assert!((path.sub_path_signed_area(2) + 100.0 / 3.0).abs() < 0.001);
+    assert!((path.sub_path_signed_area(3) + 100.0 / 3.0).abs() < 0.001);
+
+    assert_eq!(path.sub_path_orientation(0), Winding::CounterClockwise);
+    assert_eq!(path.sub_path_orientation(1), Winding::Clockwise);
+    assert_eq!(path.sub_path_orientation(2), Winding::Clockwise);
+    assert_eq!(path.sub_path_orientation(3), Winding::Clockwise);
+    assert_eq!(path.sub_path_orientation(4), Winding::Degenerate);
+
+    // A half disk.
+    let arc = Segment::Arc(Arc {
+        center: point(10.0, 5.0),
+        radius: 2.0,
+        start_angle: Radians::new(0.0),
+        sweep_angle: Radians::new(PI),
+    });
+    let chord = Segment::Line(LineSegment { from: arc.to(), to: arc.from() });
+    assert!((arc.signed_area() + chord.signed_area() - 2.0 * PI).abs() < 0.001);
+}
@@ -0,0 +1,410 @@
+//! A bounding volume hierarchy over the segments of a path.
+//!
+//! [SegmentIndex](struct.SegmentIndex.html) is built once from a path and speeds up repeated
+//! queries against it (closest point, ray intersections and rectangle overlap), which is useful
+//! when a large drawing is hit-tested many times per frame. The segments are grouped in small
+//! leaves and recursively split in two along the longest side of their bounding rectangle.
+//!
+//! The queries return the same results as the functions that work directly on the path, the
+//! indices of the segments being the ones of the [segment iterator](struct.SegmentIter.html).
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_core;
+//! # use lyon_path::*;
+//! # use lyon_path_builder::*;
+//! # use lyon_core::math::*;
+//! # fn highlight(_segment: usize) {}
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(100.0, 0.0));
+//! # builder.quadratic_bezier_to(point(100.0, 100.0), point(0.0, 100.0));
+//! # let drawing = builder.build();
+//! # let cursors = vec![point(50.0, 2.0), point(50.0, 50.0)];
+//! let index = SegmentIndex::new(drawing.iter());
+//! for cursor in cursors {
+//!     if let Some(closest) = index.closest_point(cursor, 0.01) {
+//!         if closest.distance < 5.0 {
+//!             highlight(closest.segment);
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::f32;
+
+use bezier::{LineSegment, QuadraticBezierSegment, CubicBezierSegment};
+use core::PathEvent;
+use core::math::*;
+use core::math_utils::rects_overlap_with_margin;
+
+use queries::{Segment, ClosestPoint, distance_to_rect};
+
+const MAX_SEGMENTS_PER_LEAF: usize = 4;
+
+#[derive(Copy, Clone, Debug)]
+struct Node {
+    rect: Rect,
+    // For leaves, the range of the segments in `order`. Otherwise `count` is zero, the first
+    // child is the next node and `first` is the index of the second child.
+    first: u32,
+    count: u32,
+}
+
+/// A spatial index over the line and curve segments of a path.
+#[derive(Clone, Debug)]
+pub struct SegmentIndex {
+    segments: Vec<Segment>,
+    rects: Vec<Rect>,
+    order: Vec<u32>,
+    nodes: Vec<Node>,
+    // The last point of the sub-paths that aren't closed.
+    open_ends: Vec<Point>,
+}
+
+impl SegmentIndex {
+    /// Builds the index from a sequence of path events.
+    pub fn new<Iter: Iterator<Item = PathEvent>>(path: Iter) -> Self {
+        let mut segments = Vec::new();
+        let mut open_ends = Vec::new();
+        let mut first = point(0.0, 0.0);
+        let mut current = point(0.0, 0.0);
+        let mut open = false;
+        for evt in path {
+            let segment = match evt {
+                PathEvent::MoveTo(to) => {
+                    if open && current != first {
+                        open_ends.push(current);
+                    }
+                    first = to;
+                    current = to;
+                    open = false;
+                    continue;
+                }
+                PathEvent::LineTo(to) => {
+                    open = true;
+                    Segment::Line(LineSegment { from: current, to: to })
+                }
+                PathEvent::QuadraticTo(ctrl, to) => {
+                    open = true;
+                    Segment::Quadratic(QuadraticBezierSegment { from: current, ctrl: ctrl, to: to })
+                }
+                PathEvent::CubicTo(ctrl1, ctrl2, to) => {
+                    open = true;
+                    Segment::Cubic(CubicBezierSegment { from: current, ctrl1: ctrl1, ctrl2: ctrl2, to: to })
+                }
+                PathEvent::Close => {
+                    open = false;
+                    Segment::Line(LineSegment { from: current, to: first })
+                }
+            };
+            current = segment.to();
+            segments.push(segment);
+        }
+        if open && current != first {
+            open_ends.push(current);
+        }
+
+        let rects: Vec<Rect> = segments.iter().map(|s| s.bounding_rect()).collect();
+        let mut index = SegmentIndex {
+            order: (0..segments.len() as u32).collect(),
+            segments: segments,
+            rects: rects,
+            nodes: Vec::new(),
+            open_ends: open_ends,
+        };
+        if !index.segments.is_empty() {
+            let n = index.segments.len();
+            index.build(0, n);
+        }
+
+        return index;
+    }
+
+    /// The segments of the path, in the order of the segment iterator.
+    pub fn segments(&self) -> &[Segment] { &self.segments }
+
+    /// A rectangle containing the whole path, or None if the path doesn't have any segment.
+    pub fn bounding_rect(&self) -> Option<Rect> { self.nodes.first().map(|node| node.rect) }
+
+    /// Finds the point of the path that is the closest to a position.
+    ///
+    /// Equivalent to [Path::closest_point](struct.Path.html#method.closest_point).
+    pub fn closest_point(&self, position: Point, tolerance: f32) -> Option<ClosestPoint> {
+        let mut result: Option<ClosestPoint> = None;
+        if self.nodes.is_empty() {
+            return result;
+        }
+
+        let mut stack = vec![(0, distance_to_rect(position, &self.nodes[0].rect))];
+        while let Some((node_idx, lower_bound)) = stack.pop() {
+            if let Some(ref closest) = result {
+                if lower_bound > closest.distance {
+                    continue;
+                }
+            }
+
+            let node = self.nodes[node_idx];
+            if node.count == 0 {
+                // Visit the closest child first.
+                let a = node_idx + 1;
+                let b = node.first as usize;
+                let da = distance_to_rect(position, &self.nodes[a].rect);
+                let db = distance_to_rect(position, &self.nodes[b].rect);
+                if da < db {
+                    stack.push((b, db));
+                    stack.push((a, da));
+                } else {
+                    stack.push((a, da));
+                    stack.push((b, db));
+                }
+                continue;
+            }
+
+            for &i in self.leaf(&node) {
+                let i = i as usize;
+                if let Some(ref closest) = result {
+                    if distance_to_rect(position, &self.rects[i]) > closest.distance {
+                        continue;
+                    }
+                }
+                let segment = &self.segments[i];
+                let t = segment.closest_point_t(position, tolerance);
+                let p = segment.sample(t);
+                let distance = (p - position).length();
+                let closer = match result {
+                    Some(ref closest) => distance < closest.distance
+                        || (distance == closest.distance && i < closest.segment),
+                    None => true,
+                };
+                if closer {
+                    result = Some(ClosestPoint { distance: distance, point: p, segment: i, t: t });
+                }
+            }
+        }
+
+        return result;
+    }
+
+    /// Computes the intersections of a ray with the path, sorted by increasing distance.
+    ///
+    /// Equivalent to [intersect_ray](fn.intersect_ray.html).
+    pub fn intersect_ray(&self, origin: Point, direction: Vec2, tolerance: f32) -> Vec<(f32, Point)> {
+        let mut result = Vec::new();
+        let length = direction.length();
+        if length == 0.0 || self.nodes.is_empty() {
+            return result;
+        }
+        let direction = direction / length;
+
+        let mut stack = vec![0];
+        while let Some(node_idx) = stack.pop() {
+            let node = self.nodes[node_idx];
+            if !ray_intersects_rect(origin, direction, &node.rect) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.first as usize);
+                stack.push(node_idx + 1);
+                continue;
+            }
+            for &i in self.leaf(&node) {
+                self.segments[i as usize].intersect_ray(origin, direction, tolerance, &mut result);
+            }
+        }
+
+        for &end in &self.open_ends {
+            let w = end - origin;
+            if direction.cross(w) == 0.0 && direction.dot(w) >= 0.0 {
+                result.push((w.length(), end));
+            }
+        }
+
+        result.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        return result;
+    }
+
+    /// Returns the indices of the segments whose bounding rectangle overlaps a rectangle,
+    /// in increasing order.
+    ///
+    /// Rectangles that only touch are considered overlapping.
+    pub fn segments_in_rect(&self, rect: &Rect) -> Vec<usize> {
+        let mut result = Vec::new();
+        if self.nodes.is_empty() {
+            return result;
+        }
+
+        let mut stack = vec![0];
+        while let Some(node_idx) = stack.pop() {
+            let node = self.nodes[node_idx];
+            if !rects_overlap_with_margin(&node.rect, rect, 0.0) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.first as usize);
+                stack.push(node_idx + 1);
+                continue;
+            }
+            for &i in self.leaf(&node) {
+                if rects_overlap_with_margin(&self.rects[i as usize], rect, 0.0) {
+                    result.push(i as usize);
+                }
+            }
+        }
+
+        result.sort();
+
+        return result;
+    }
+
+    fn leaf(&self, node: &Node) -> &[u32] {
+        &self.order[node.first as usize..(node.first + node.count) as usize]
+    }
+
+    // Adds the node containing order[start..end] and its children, depth first.
+    fn build(&mut self, start: usize, end: usize) {
+        let mut rect = self.rects[self.order[start] as usize];
+        for &i in &self.order[start + 1..end] {
+            rect = rect.union(&self.rects[i as usize]);
+        }
+
+        let node_idx = self.nodes.len();
+        if end - start <= MAX_SEGMENTS_PER_LEAF {
+            self.nodes.push(Node { rect: rect, first: start as u32, count: (end - start) as u32 });
+            return;
+        }
+        self.nodes.push(Node { rect: rect, first: 0, count: 0 });
+
+        // Split at the median of the centers along the longest side.
+        let horizontal = rect.size.width >= rect.size.height;
+        {
+            let rects = &self.rects;
+            let key = |i: &u32| {
+                let r = &rects[*i as usize];
+                if horizontal { r.min_x() + r.max_x() } else { r.min_y() + r.max_y() }
+            };
+            self.order[start..end].sort_by(|a, b| key(a).partial_cmp(&key(b)).unwrap());
+        }
+        let mid = (start + end) / 2;
+        self.build(start, mid);
+        self.nodes[node_idx].first = self.nodes.len() as u32;
+        self.build(mid, end);
+    }
+}
+
+// Slab test, `direction` being normalized.
+fn ray_intersects_rect(origin: Point, direction: Vec2, rect: &Rect) -> bool {
+    let mut t_min: f32 = 0.0;
+    let mut t_max = f32::MAX;
+    let axes = [
+        (origin.x, direction.x, rect.min_x(), rect.max_x()),
+        (origin.y, direction.y, rect.min_y(), rect.max_y()),
+    ];
+    for &(o, d, min, max) in &axes {
+        if d == 0.0 {
+            if o < min || o > max {
+                return false;
+            }
+            continue;
+        }
+        let t0 = (min - o) / d;
+        let t1 = (max - o) / d;
+        t_min = t_min.max(t0.min(t1));
+        t_max = t_max.min(t0.max(t1));
+        if t_min > t_max {
+            return false;
+        }
+    }
+
+    return true;
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use queries::intersect_ray;
+#[cfg(test)]
+use path_builder::{BaseBuilder, PathBuilder};
+
+#[cfg(test)]
+fn grid_of_circles() -> Path {
+    // 10x10 circles approximated with four quadratic curves, the last row isn't closed.
+    let r = 4.0;
+    let k = r * (2.0f32.sqrt() - 1.0);
+    let mut builder = Path::builder();
+    for y in 0..10 {
+        for x in 0..10 {
+            let c = point(x as f32 * 10.0, y as f32 * 10.0);
+            builder.move_to(c + vec2(r, 0.0));
+            builder.quadratic_bezier_to(c + vec2(r, r), c + vec2(0.0, r));
+            builder.quadratic_bezier_to(c + vec2(-r, r), c + vec2(-r, 0.0));
+            builder.quadratic_bezier_to(c + vec2(-r, -r), c + vec2(0.0, -r));
+            builder.line_to(c + vec2(k, -r));
+            builder.line_to(c + vec2(r, -k));
+            if y < 9 {
+                builder.close();
+            }
+        }
+    }
+
+    return builder.build();
+}
+
+#[test]
+fn test_segment_index_closest_point() {
+    let path = grid_of_circles();
+    let index = SegmentIndex::new(path.iter());
+    assert_eq!(index.segments().len(), path.segments().count());
+    assert_eq!(index.bounding_rect(), Some(rect(-4.0, -4.0, 98.0, 98.0)));
+
+    for &(x, y) in &[(2.1, -1.3), (13.7, 28.4), (43.3, 67.1), (-20.0, 51.7), (91.2, 99.0), (200.0, -3.0)] {
+        let position = point(x, y);
+        let expected = path.closest_point(position, 0.01).unwrap();
+        let closest = index.closest_point(position, 0.01).unwrap();
+        assert_eq!(closest.distance, expected.distance);
+        assert_eq!(closest.point, expected.point);
+    }
+
+    assert!(SegmentIndex::new(Path::new().iter()).closest_point(point(0.0, 0.0), 0.01).is_none());
+}
+
+#[test]
+fn test_segment_index_intersect_ray() {
+    let path = grid_of_circles();
+    let index = SegmentIndex::new(path.iter());
+
+    let rays = [
+        (point(-10.0, 1.0), vec2(1.0, 0.0)),
+        (point(33.0, 120.0), vec2(0.0, -1.0)),
+        (point(-10.0, -10.0), vec2(1.0, 1.3)),
+        (point(50.0, 50.0), vec2(-1.0, 0.2)),
+        (point(-10.0, 50.0), vec2(-1.0, 0.0)),
+    ];
+    for &(origin, direction) in &rays {
+        let expected = intersect_ray(path.iter(), origin, direction, 0.01);
+        assert_eq!(index.intersect_ray(origin, direction, 0.01), expected);
+    }
+
+    // Through the middle of the first row.
+    assert_eq!(index.intersect_ray(point(-10.0, 1.0), vec2(1.0, 0.0), 0.01).len(), 20);
+}
+
+#[test]
+fn test_segments_in_rect() {
+    let path = grid_of_circles();
+    let index = SegmentIndex::new(path.iter());
+
+    // Only the two curves at the bottom of the circle centered on (20.0, 10.0), which has six
+    // segments as do the eleven circles before it.
+    assert_eq!(index.segments_in_rect(&rect(17.0, 13.0, 6.0, 0.5)), vec![72, 73]);
+
+    let expected: Vec<usize> = (0..index.segments().len()).collect();
+    assert_eq!(index.segments_in_rect(&rect(-100.0, -100.0, 300.0, 300.0)), expected);
+    assert!(index.segments_in_rect(&rect(4.5, 4.5, 1.0, 1.0)).is_empty());
+}
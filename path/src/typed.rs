@@ -0,0 +1,140 @@
+//! Paths tagged with a unit type.
+//!
+//! `TypedPath<Unit>` wraps a `Path` whose coordinates live in a given space. Builder methods
+//! take `TypedPoint2D<f32, Unit>` and going from a space to another requires a
+//! `TypedTransform2D<f32, Src, Dst>`, so mixing up local and device space coordinates
+//! becomes a compile error.
+
+use path::{Path, Builder, PathIter};
+use path_builder::{BaseBuilder, PathBuilder};
+use path_iterator::PathStateIter;
+
+use core::math::*;
+
+use std::marker::PhantomData;
+
+/// A path which coordinates are expressed in `Unit`.
+pub struct TypedPath<Unit> {
+    path: Path,
+    _unit: PhantomData<Unit>,
+}
+
+impl<Unit> TypedPath<Unit> {
+    /// Creates a [TypedBuilder](struct.TypedBuilder.html) to create a path.
+    pub fn builder() -> TypedBuilder<Unit> { TypedBuilder::new() }
+
+    /// Tags an untyped path with a unit.
+    pub fn from_untyped(path: Path) -> Self {
+        TypedPath {
+            path: path,
+            _unit: PhantomData,
+        }
+    }
+
+    /// Drops the unit.
+    pub fn to_untyped(self) -> Path { self.path }
+
+    pub fn as_untyped(&self) -> &Path { &self.path }
+
+    pub fn iter(&self) -> PathIter { self.path.iter() }
+
+    pub fn path_iter(&self) -> PathStateIter<PathIter> { self.path.path_iter() }
+
+    pub fn num_points(&self) -> usize { self.path.points().len() }
+
+    pub fn point(&self, idx: usize) -> TypedPoint<Unit> {
+        TypedPoint::from_untyped(&self.path.points()[idx])
+    }
+
+    /// Applies a transform to all of the points of the path and returns the result in
+    /// the destination space.
+    pub fn transform<Dst>(&self, transform: &TypedTransform<Unit, Dst>) -> TypedPath<Dst> {
+        let mut path = self.path.clone();
+        for p in path.mut_points() {
+            *p = transform.transform_point(&TypedPoint::from_untyped(p)).to_untyped();
+        }
+
+        return TypedPath::from_untyped(path);
+    }
+}
+
+impl<Unit> Clone for TypedPath<Unit> {
+    fn clone(&self) -> Self { TypedPath::from_untyped(self.path.clone()) }
+}
+
+/// Builds a `TypedPath<Unit>`.
+///
+/// This is a thin wrapper around [Builder](struct.Builder.html) which methods take points
+/// in `Unit` space.
+pub struct TypedBuilder<Unit> {
+    builder: Builder,
+    _unit: PhantomData<Unit>,
+}
+
+impl<Unit> TypedBuilder<Unit> {
+    pub fn new() -> Self { TypedBuilder::with_capacity(128) }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        TypedBuilder {
+            builder: Builder::with_capacity(cap),
+            _unit: PhantomData,
+        }
+    }
+
+    pub fn move_to(&mut self, to: TypedPoint<Unit>) { self.builder.move_to(to.to_untyped()); }
+
+    pub fn line_to(&mut self, to: TypedPoint<Unit>) { self.builder.line_to(to.to_untyped()); }
+
+    pub fn quadratic_bezier_to(&mut self, ctrl: TypedPoint<Unit>, to: TypedPoint<Unit>) {
+        self.builder.quadratic_bezier_to(ctrl.to_untyped(), to.to_untyped());
+    }
+
+    pub fn cubic_bezier_to(
+        &mut self,
+        ctrl1: TypedPoint<Unit>,
+        ctrl2: TypedPoint<Unit>,
+        to: TypedPoint<Unit>,
+    ) {
+        self.builder.cubic_bezier_to(ctrl1.to_untyped(), ctrl2.to_untyped(), to.to_untyped());
+    }
+
+    pub fn close(&mut self) { self.builder.close(); }
+
+    pub fn current_position(&self) -> TypedPoint<Unit> {
+        TypedPoint::from_untyped(&self.builder.current_position())
+    }
+
+    pub fn build(self) -> TypedPath<Unit> { TypedPath::from_untyped(self.builder.build()) }
+
+    pub fn build_and_reset(&mut self) -> TypedPath<Unit> {
+        TypedPath::from_untyped(self.builder.build_and_reset())
+    }
+
+    /// Gives access to the untyped builder, for example to use the
+    /// [path_builder](https://docs.rs/lyon_path_builder) adapters.
+    pub fn untyped_builder(&mut self) -> &mut Builder { &mut self.builder }
+}
+
+#[cfg(test)]
+struct LocalSpace;
+#[cfg(test)]
+struct DeviceSpace;
+
+#[test]
+fn test_typed_path_transform() {
+    let mut builder: TypedBuilder<LocalSpace> = TypedPath::builder();
+    builder.move_to(TypedPoint::new(0.0, 0.0));
+    builder.line_to(TypedPoint::new(1.0, 0.0));
+    builder.quadratic_bezier_to(TypedPoint::new(2.0, 0.0), TypedPoint::new(2.0, 1.0));
+    builder.close();
+    let local = builder.build();
+
+    let to_device: TypedTransform<LocalSpace, DeviceSpace> =
+        TypedTransform::create_translation(10.0, 5.0);
+    let device: TypedPath<DeviceSpace> = local.transform(&to_device);
+
+    assert_eq!(device.num_points(), 4);
+    assert_eq!(device.point(0), TypedPoint::new(10.0, 5.0));
+    assert_eq!(device.point(3), TypedPoint::new(12.0, 6.0));
+    assert_eq!(local.point(3), TypedPoint::new(2.0, 1.0));
+}
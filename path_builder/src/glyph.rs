@@ -0,0 +1,150 @@
+use core::math::*;
+use path_builder::PathBuilder;
+
+#[cfg(feature = "ttf-parser")]
+use ttf_parser;
+
+/// Adapter receiving glyph outlines from font parsers and forwarding them to a
+/// `PathBuilder`.
+///
+/// Font parsers like `ttf-parser` and `rusttype` describe glyph outlines through
+/// `move_to`, `line_to`, `quad_to`, `curve_to` and `close` callbacks taking scalar
+/// coordinates. This adapter implements these callbacks and applies a transform to go
+/// from font units to the path's coordinate space (for example to scale the glyph
+/// to a font size and to flip the y axis, since font outlines are y-up).
+///
+/// With the `ttf-parser` feature, it implements `ttf_parser::OutlineBuilder` (which is
+/// also what `rusttype` uses), so a glyph can be turned into a path by passing a
+/// `GlyphBuilder` wrapping the path builder to `Face::outline_glyph` and calling `build`
+/// on it.
+pub struct GlyphBuilder<Builder> {
+    builder: Builder,
+    transform: Transform2d,
+}
+
+impl<Builder: PathBuilder> GlyphBuilder<Builder> {
+    pub fn new(builder: Builder) -> Self {
+        GlyphBuilder::with_transform(builder, Transform2d::identity())
+    }
+
+    pub fn with_transform(builder: Builder, transform: Transform2d) -> Self {
+        GlyphBuilder {
+            builder: builder,
+            transform: transform,
+        }
+    }
+
+    /// Scales font units to the requested font size, flips the y axis and translates the
+    /// glyph so that its origin (on the baseline) is at the provided position.
+    pub fn with_font_size(builder: Builder, units_per_em: f32, font_size: f32, origin: Point) -> Self {
+        let scale = font_size / units_per_em;
+        let transform = Transform2d::create_scale(scale, -scale)
+            .post_translate(origin.to_vector());
+
+        GlyphBuilder::with_transform(builder, transform)
+    }
+
+    pub fn set_transform(&mut self, transform: Transform2d) { self.transform = transform; }
+
+    pub fn move_to(&mut self, x: f32, y: f32) {
+        let to = self.point(x, y);
+        self.builder.move_to(to);
+    }
+
+    pub fn line_to(&mut self, x: f32, y: f32) {
+        let to = self.point(x, y);
+        self.builder.line_to(to);
+    }
+
+    pub fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let ctrl = self.point(x1, y1);
+        let to = self.point(x, y);
+        self.builder.quadratic_bezier_to(ctrl, to);
+    }
+
+    pub fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let ctrl1 = self.point(x1, y1);
+        let ctrl2 = self.point(x2, y2);
+        let to = self.point(x, y);
+        self.builder.cubic_bezier_to(ctrl1, ctrl2, to);
+    }
+
+    pub fn close(&mut self) { self.builder.close(); }
+
+    /// Builds the path object.
+    pub fn build(self) -> Builder::PathType { self.builder.build() }
+
+    /// Returns the underlying builder, for example to add more glyphs to the same path
+    /// with a different transform.
+    pub fn into_inner(self) -> Builder { self.builder }
+
+    fn point(&self, x: f32, y: f32) -> Point { self.transform.transform_point(&point(x, y)) }
+}
+
+#[cfg(feature = "ttf-parser")]
+impl<Builder: PathBuilder> ttf_parser::OutlineBuilder for GlyphBuilder<Builder> {
+    fn move_to(&mut self, x: f32, y: f32) { GlyphBuilder::move_to(self, x, y); }
+
+    fn line_to(&mut self, x: f32, y: f32) { GlyphBuilder::line_to(self, x, y); }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        GlyphBuilder::quad_to(self, x1, y1, x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        GlyphBuilder::curve_to(self, x1, y1, x2, y2, x, y);
+    }
+
+    fn close(&mut self) { GlyphBuilder::close(self); }
+}
+
+#[cfg(test)]
+use core::PathEvent;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+
+#[cfg(test)]
+struct Recorder {
+    events: Vec<PathEvent>,
+    current: Point,
+}
+
+#[cfg(test)]
+impl BaseBuilder for Recorder {
+    type PathType = Vec<PathEvent>;
+    fn move_to(&mut self, to: Point) { self.events.push(PathEvent::MoveTo(to)); self.current = to; }
+    fn line_to(&mut self, to: Point) { self.events.push(PathEvent::LineTo(to)); self.current = to; }
+    fn close(&mut self) { self.events.push(PathEvent::Close); }
+    fn current_position(&self) -> Point { self.current }
+    fn build(self) -> Vec<PathEvent> { self.events }
+    fn build_and_reset(&mut self) -> Vec<PathEvent> { ::std::mem::replace(&mut self.events, Vec::new()) }
+}
+
+#[cfg(test)]
+impl PathBuilder for Recorder {
+    fn quadratic_bezier_to(&mut self, ctrl: Point, to: Point) {
+        self.events.push(PathEvent::QuadraticTo(ctrl, to));
+        self.current = to;
+    }
+    fn cubic_bezier_to(&mut self, ctrl1: Point, ctrl2: Point, to: Point) {
+        self.events.push(PathEvent::CubicTo(ctrl1, ctrl2, to));
+        self.current = to;
+    }
+}
+
+#[test]
+fn test_glyph_builder_font_size() {
+    let recorder = Recorder { events: Vec::new(), current: point(0.0, 0.0) };
+    // 1000 units per em rendered at 10px with the baseline at y = 20.
+    let mut glyph = GlyphBuilder::with_font_size(recorder, 1000.0, 10.0, point(5.0, 20.0));
+    glyph.move_to(0.0, 0.0);
+    glyph.line_to(100.0, 0.0);
+    glyph.quad_to(100.0, 500.0, 0.0, 700.0);
+    glyph.close();
+
+    let events = glyph.build();
+    assert_eq!(events[0], PathEvent::MoveTo(point(5.0, 20.0)));
+    assert_eq!(events[1], PathEvent::LineTo(point(6.0, 20.0)));
+    assert_eq!(events[2], PathEvent::QuadraticTo(point(6.0, 15.0), point(5.0, 13.0)));
+    assert_eq!(events[3], PathEvent::Close);
+}
@@ -77,14 +77,23 @@
 //! let path = builder.build();
 //! ```
 //!
+//! To densify a path before moving its points with a non-linear transformation, use the
+//! subdivided method, and to follow the conventions of a graphics API for sub-paths that
+//! are not explicitly started or closed, use the with_policies method (see
+//! [BuilderPolicies](struct.BuilderPolicies.html)).
+//!
 
 extern crate lyon_core as core;
 extern crate lyon_bezier as bezier;
+#[cfg(feature = "ttf-parser")]
+extern crate ttf_parser;
 
 mod path_builder;
 mod arc;
+mod glyph;
 
 #[doc(inline)]
 pub use path_builder::*;
+pub use glyph::GlyphBuilder;
 
 pub use core::*;
@@ -1,4 +1,5 @@
 use core::{PathEvent, FlattenedEvent, SvgEvent, ArcFlags};
+use core::input_validation::{InputError, validate_point};
 use core::math::*;
 use bezier::{CubicBezierSegment, QuadraticBezierSegment};
 use arc::arc_to_cubic_beziers;
@@ -47,6 +48,17 @@ pub trait BaseBuilder: ::std::marker::Sized {
     fn flattened(self, tolerance: f32) -> FlatteningBuilder<Self> {
         FlatteningBuilder::new(self, tolerance)
     }
+
+    /// Returns a builder that subdivides the segments that are longer than a maximum length.
+    fn subdivided(self, max_length: f32) -> SubdividingBuilder<Self> {
+        SubdividingBuilder::new(self, max_length)
+    }
+
+    /// Returns a builder that applies conventions about closing sub-paths and starting them
+    /// without a `move_to`.
+    fn with_policies(self, policies: BuilderPolicies) -> PolicyBuilder<Self> {
+        PolicyBuilder::new(self, policies)
+    }
 }
 
 /// The main path building interface. More elaborate interfaces are built on top
@@ -366,6 +378,425 @@ impl<Builder: BaseBuilder> FlatteningBuilder<Builder> {
     pub fn set_tolerance(&mut self, tolerance: f32) { self.tolerance = tolerance }
 }
 
+/// A builder that subdivides the segments that are longer than a maximum length.
+///
+/// Lines are split into equal parts. Curves are split in half until the length of their
+/// control polygon, which is never shorter than the curve, is below the maximum length. The
+/// wrapped builder receives the same shape with denser points, which is needed before moving
+/// the points with a non-linear transformation (for example a map projection), where long
+/// segments would otherwise cut corners.
+pub struct SubdividingBuilder<Builder> {
+    builder: Builder,
+    max_length: f32,
+    first_position: Point,
+}
+
+impl<Builder: BaseBuilder> SubdividingBuilder<Builder> {
+    pub fn new(builder: Builder, max_length: f32) -> SubdividingBuilder<Builder> {
+        assert!(max_length > 0.0);
+        SubdividingBuilder {
+            builder: builder,
+            max_length: max_length,
+            first_position: point(0.0, 0.0),
+        }
+    }
+
+    pub fn set_max_length(&mut self, max_length: f32) {
+        assert!(max_length > 0.0);
+        self.max_length = max_length;
+    }
+}
+
+impl<Builder: PathBuilder> SubdividingBuilder<Builder> {
+    fn subdivide_quadratic(&mut self, curve: &QuadraticBezierSegment) {
+        let polygon_length = (curve.ctrl - curve.from).length() + (curve.to - curve.ctrl).length();
+        // Written this way so that NaNs don't subdivide forever.
+        if !(polygon_length > self.max_length) {
+            self.builder.quadratic_bezier_to(curve.ctrl, curve.to);
+            return;
+        }
+
+        let (a, b) = curve.split(0.5);
+        self.subdivide_quadratic(&a);
+        self.subdivide_quadratic(&b);
+    }
+
+    fn subdivide_cubic(&mut self, curve: &CubicBezierSegment) {
+        let polygon_length = (curve.ctrl1 - curve.from).length()
+            + (curve.ctrl2 - curve.ctrl1).length()
+            + (curve.to - curve.ctrl2).length();
+        if !(polygon_length > self.max_length) {
+            self.builder.cubic_bezier_to(curve.ctrl1, curve.ctrl2, curve.to);
+            return;
+        }
+
+        let (a, b) = curve.split(0.5);
+        self.subdivide_cubic(&a);
+        self.subdivide_cubic(&b);
+    }
+}
+
+impl<Builder: BaseBuilder> BaseBuilder for SubdividingBuilder<Builder> {
+    type PathType = Builder::PathType;
+
+    fn move_to(&mut self, to: Point) {
+        self.first_position = to;
+        self.builder.move_to(to);
+    }
+
+    fn line_to(&mut self, to: Point) {
+        let from = self.builder.current_position();
+        let n = ((to - from).length() / self.max_length).ceil();
+        if n > 1.0 {
+            let count = n as u32;
+            for i in 1..count {
+                self.builder.line_to(from.lerp(to, i as f32 / n));
+            }
+        }
+        self.builder.line_to(to);
+    }
+
+    fn close(&mut self) {
+        // The closing segment can be long as well.
+        let first = self.first_position;
+        if self.builder.current_position() != first {
+            self.line_to(first);
+        }
+        self.builder.close();
+    }
+
+    fn current_position(&self) -> Point { self.builder.current_position() }
+
+    fn build(self) -> Builder::PathType { self.builder.build() }
+
+    fn build_and_reset(&mut self) -> Builder::PathType {
+        self.first_position = point(0.0, 0.0);
+        self.builder.build_and_reset()
+    }
+}
+
+impl<Builder: PathBuilder> PathBuilder for SubdividingBuilder<Builder> {
+    fn quadratic_bezier_to(&mut self, ctrl: Point, to: Point) {
+        let curve = QuadraticBezierSegment {
+            from: self.current_position(),
+            ctrl: ctrl,
+            to: to,
+        };
+        self.subdivide_quadratic(&curve);
+    }
+
+    fn cubic_bezier_to(&mut self, ctrl1: Point, ctrl2: Point, to: Point) {
+        let curve = CubicBezierSegment {
+            from: self.current_position(),
+            ctrl1: ctrl1,
+            ctrl2: ctrl2,
+            to: to,
+        };
+        self.subdivide_cubic(&curve);
+    }
+}
+
+/// A builder that checks the coordinates of the events before forwarding them.
+///
+/// Events with a NaN or infinite coordinate, or a coordinate larger than the maximum
+/// magnitude, are not forwarded. The first of them is recorded, identified by the index
+/// of the event (each call to the builder being an event).
+pub struct ValidatingBuilder<Builder> {
+    builder: Builder,
+    max_magnitude: f32,
+    event_count: usize,
+    error: Option<InputError>,
+}
+
+impl<Builder: BaseBuilder> ValidatingBuilder<Builder> {
+    pub fn new(builder: Builder, max_magnitude: f32) -> ValidatingBuilder<Builder> {
+        ValidatingBuilder {
+            builder: builder,
+            max_magnitude: max_magnitude,
+            event_count: 0,
+            error: None,
+        }
+    }
+
+    /// The first invalid event, if any.
+    pub fn error(&self) -> Option<InputError> { self.error }
+
+    /// Builds the path if all of the events were valid.
+    pub fn try_build(self) -> Result<Builder::PathType, InputError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        return Ok(self.builder.build());
+    }
+
+    // Returns true if the points of the next event are valid.
+    fn check(&mut self, points: &[Point]) -> bool {
+        let event = self.event_count;
+        self.event_count += 1;
+        for p in points {
+            if let Err(kind) = validate_point(*p, self.max_magnitude) {
+                if self.error.is_none() {
+                    self.error = Some(InputError { event: event, kind: kind });
+                }
+                return false;
+            }
+        }
+
+        return true;
+    }
+}
+
+impl<Builder: BaseBuilder> BaseBuilder for ValidatingBuilder<Builder> {
+    type PathType = Builder::PathType;
+
+    fn move_to(&mut self, to: Point) {
+        if self.check(&[to]) {
+            self.builder.move_to(to);
+        }
+    }
+
+    fn line_to(&mut self, to: Point) {
+        if self.check(&[to]) {
+            self.builder.line_to(to);
+        }
+    }
+
+    fn close(&mut self) {
+        self.event_count += 1;
+        self.builder.close();
+    }
+
+    fn current_position(&self) -> Point { self.builder.current_position() }
+
+    fn build(self) -> Builder::PathType { self.builder.build() }
+
+    fn build_and_reset(&mut self) -> Builder::PathType {
+        self.event_count = 0;
+        self.error = None;
+        self.builder.build_and_reset()
+    }
+}
+
+impl<Builder: PathBuilder> PathBuilder for ValidatingBuilder<Builder> {
+    fn quadratic_bezier_to(&mut self, ctrl: Point, to: Point) {
+        if self.check(&[ctrl, to]) {
+            self.builder.quadratic_bezier_to(ctrl, to);
+        }
+    }
+
+    fn cubic_bezier_to(&mut self, ctrl1: Point, ctrl2: Point, to: Point) {
+        if self.check(&[ctrl1, ctrl2, to]) {
+            self.builder.cubic_bezier_to(ctrl1, ctrl2, to);
+        }
+    }
+}
+
+/// What to do with the segments that are added before any `move_to`.
+///
+/// After a `close`, all policies except `Forward` start the next sub-path at the start of the
+/// closed one (like SVG and canvas do).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MissingMoveTo {
+    /// Forward the events as they are and let the wrapped builder decide.
+    Forward,
+    /// Start the sub-path at the origin.
+    Origin,
+    /// The first event acts as a `move_to` to its first point (like the HTML canvas): a
+    /// `line_to` only moves, and curves start at their first control point.
+    FirstPoint,
+    /// Drop the events until the next `move_to` (the SVG path syntax requires a `moveto`
+    /// first). The first dropped event is recorded.
+    Reject,
+}
+
+/// The conventions applied by a [PolicyBuilder](struct.PolicyBuilder.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BuilderPolicies {
+    /// Close the sub-paths that are left open by a `move_to` or at the end of the path.
+    pub auto_close: bool,
+
+    pub missing_move_to: MissingMoveTo,
+
+    // To be able to add fields without making it a breaking change, add an empty private field
+    // which makes it impossible to create a BuilderPolicies without the calling constructor.
+    _private: (),
+}
+
+impl BuilderPolicies {
+    /// Forward everything as is.
+    pub fn default() -> BuilderPolicies {
+        BuilderPolicies {
+            auto_close: false,
+            missing_move_to: MissingMoveTo::Forward,
+            _private: (),
+        }
+    }
+
+    /// The conventions of SVG path data.
+    pub fn svg() -> BuilderPolicies {
+        BuilderPolicies::default().with_missing_move_to(MissingMoveTo::Reject)
+    }
+
+    /// The conventions of the HTML canvas path API.
+    pub fn canvas() -> BuilderPolicies {
+        BuilderPolicies::default().with_missing_move_to(MissingMoveTo::FirstPoint)
+    }
+
+    pub fn with_auto_close(mut self) -> BuilderPolicies {
+        self.auto_close = true;
+        return self;
+    }
+
+    pub fn with_missing_move_to(mut self, policy: MissingMoveTo) -> BuilderPolicies {
+        self.missing_move_to = policy;
+        return self;
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum SubPathState {
+    // No move_to yet.
+    Empty,
+    Building,
+    Closed,
+}
+
+/// A builder that applies configurable conventions about closing sub-paths and about the
+/// segments that are added before any `move_to`.
+///
+/// Graphics APIs don't agree on these conventions, which causes subtle differences when
+/// porting drawing code from one to another. See [BuilderPolicies](struct.BuilderPolicies.html).
+pub struct PolicyBuilder<Builder> {
+    builder: Builder,
+    policies: BuilderPolicies,
+    state: SubPathState,
+    has_segments: bool,
+    event_count: usize,
+    rejected_event: Option<usize>,
+}
+
+impl<Builder: BaseBuilder> PolicyBuilder<Builder> {
+    pub fn new(builder: Builder, policies: BuilderPolicies) -> PolicyBuilder<Builder> {
+        PolicyBuilder {
+            builder: builder,
+            policies: policies,
+            state: SubPathState::Empty,
+            has_segments: false,
+            event_count: 0,
+            rejected_event: None,
+        }
+    }
+
+    pub fn policies(&self) -> &BuilderPolicies { &self.policies }
+
+    /// The index of the first event dropped by `MissingMoveTo::Reject`, if any.
+    pub fn rejected_event(&self) -> Option<usize> { self.rejected_event }
+
+    // Makes sure a sub-path is started before a segment. Returns None if the segment must be
+    // dropped, and Some(true) if a sub-path was started at the first point of the segment.
+    fn begin_segment(&mut self, first_point: Point) -> Option<bool> {
+        let event = self.event_count;
+        self.event_count += 1;
+        let mut moved_to_first_point = false;
+        match (self.state, self.policies.missing_move_to) {
+            (SubPathState::Building, _) | (_, MissingMoveTo::Forward) => {}
+            (SubPathState::Closed, _) => {
+                let start = self.builder.current_position();
+                self.builder.move_to(start);
+            }
+            (SubPathState::Empty, MissingMoveTo::Origin) => {
+                self.builder.move_to(point(0.0, 0.0));
+            }
+            (SubPathState::Empty, MissingMoveTo::FirstPoint) => {
+                self.builder.move_to(first_point);
+                moved_to_first_point = true;
+            }
+            (SubPathState::Empty, MissingMoveTo::Reject) => {
+                if self.rejected_event.is_none() {
+                    self.rejected_event = Some(event);
+                }
+                return None;
+            }
+        }
+        self.state = SubPathState::Building;
+
+        return Some(moved_to_first_point);
+    }
+
+    fn auto_close(&mut self) {
+        if self.policies.auto_close && self.state == SubPathState::Building && self.has_segments {
+            self.builder.close();
+        }
+    }
+}
+
+impl<Builder: BaseBuilder> BaseBuilder for PolicyBuilder<Builder> {
+    type PathType = Builder::PathType;
+
+    fn move_to(&mut self, to: Point) {
+        self.event_count += 1;
+        self.auto_close();
+        self.builder.move_to(to);
+        self.state = SubPathState::Building;
+        self.has_segments = false;
+    }
+
+    fn line_to(&mut self, to: Point) {
+        match self.begin_segment(to) {
+            Some(false) => {
+                self.builder.line_to(to);
+                self.has_segments = true;
+            }
+            // Only moved to the end of the segment.
+            Some(true) | None => {}
+        }
+    }
+
+    fn close(&mut self) {
+        self.event_count += 1;
+        if self.state == SubPathState::Empty && self.policies.missing_move_to != MissingMoveTo::Forward {
+            // Nothing to close.
+            return;
+        }
+        self.builder.close();
+        self.state = SubPathState::Closed;
+        self.has_segments = false;
+    }
+
+    fn current_position(&self) -> Point { self.builder.current_position() }
+
+    fn build(mut self) -> Builder::PathType {
+        self.auto_close();
+        self.builder.build()
+    }
+
+    fn build_and_reset(&mut self) -> Builder::PathType {
+        self.auto_close();
+        self.state = SubPathState::Empty;
+        self.has_segments = false;
+        self.event_count = 0;
+        self.rejected_event = None;
+        self.builder.build_and_reset()
+    }
+}
+
+impl<Builder: PathBuilder> PathBuilder for PolicyBuilder<Builder> {
+    fn quadratic_bezier_to(&mut self, ctrl: Point, to: Point) {
+        if self.begin_segment(ctrl).is_some() {
+            self.builder.quadratic_bezier_to(ctrl, to);
+            self.has_segments = true;
+        }
+    }
+
+    fn cubic_bezier_to(&mut self, ctrl1: Point, ctrl2: Point, to: Point) {
+        if self.begin_segment(ctrl1).is_some() {
+            self.builder.cubic_bezier_to(ctrl1, ctrl2, to);
+            self.has_segments = true;
+        }
+    }
+}
+
 impl<Builder: BaseBuilder> PolygonBuilder for Builder {
     fn polygon(&mut self, points: &[Point]) {
         assert!(!points.is_empty());
@@ -26,6 +26,27 @@ pub trait PathIterator: Iterator<Item = PathEvent> + Sized {
         FlatteningIter::new(tolerance, self)
     }
 
+    /// Returns an iterator that turns curves into line segments along which the direction
+    /// of the curves changes by at most `max_angle` (in radians).
+    ///
+    /// This places more points where the curvature is high and fewer where the curves are
+    /// almost straight, regardless of their size.
+    fn angle_flattened(self, max_angle: f32) -> AngleFlatteningIter<Self> {
+        AngleFlatteningIter::new(max_angle, self)
+    }
+
+    /// Returns an iterator that applies a non-linear transformation to the path.
+    ///
+    /// Straight lines generally don't stay straight through such a transformation, so lines
+    /// and curves are subdivided until the yielded line segments are within `tolerance` of the
+    /// transformed segments.
+    fn warped<F>(self, warp: F, tolerance: f32) -> WarpingIter<Self, F>
+    where
+        F: Fn(Vec2) -> Vec2,
+    {
+        WarpingIter::new(warp, tolerance, self)
+    }
+
     /// Returns an iterator of SVG events.
     fn svg_iter(self) -> iter::Map<Self, fn(PathEvent) -> SvgEvent> { self.map(path_to_svg_event) }
 }
@@ -241,6 +262,224 @@ where
     }
 }
 
+/// An iterator that consumes a PathIterator and yields FlattenedEvents, with a maximum angle
+/// per segment instead of a distance tolerance.
+pub struct AngleFlatteningIter<Iter> {
+    it: Iter,
+    // The points of the current curve, in reverse order.
+    points: Vec<Point>,
+    max_angle: f32,
+}
+
+impl<Iter: PathIterator> AngleFlatteningIter<Iter> {
+    /// Create the iterator.
+    pub fn new(max_angle: f32, it: Iter) -> Self {
+        AngleFlatteningIter {
+            it: it,
+            points: Vec::new(),
+            max_angle: max_angle,
+        }
+    }
+}
+
+impl<Iter> FlattenedIterator for AngleFlatteningIter<Iter>
+where
+    Iter: PathIterator,
+{
+    fn get_state(&self) -> &PathState { self.it.get_state() }
+}
+
+impl<Iter> Iterator for AngleFlatteningIter<Iter>
+where
+    Iter: PathIterator,
+{
+    type Item = FlattenedEvent;
+    fn next(&mut self) -> Option<FlattenedEvent> {
+        if let Some(point) = self.points.pop() {
+            return Some(FlattenedEvent::LineTo(point));
+        }
+
+        let current = self.get_state().current;
+        let max_angle = self.max_angle;
+        match self.it.next() {
+            Some(PathEvent::MoveTo(to)) => Some(FlattenedEvent::MoveTo(to)),
+            Some(PathEvent::LineTo(to)) => Some(FlattenedEvent::LineTo(to)),
+            Some(PathEvent::Close) => Some(FlattenedEvent::Close),
+            Some(PathEvent::QuadraticTo(ctrl, to)) => {
+                {
+                    let points = &mut self.points;
+                    QuadraticBezierSegment {
+                        from: current,
+                        ctrl: ctrl,
+                        to: to,
+                    }.angle_flattened_for_each(max_angle, &mut |p| points.push(p));
+                }
+                self.points.reverse();
+                return self.next();
+            }
+            Some(PathEvent::CubicTo(ctrl1, ctrl2, to)) => {
+                {
+                    let points = &mut self.points;
+                    CubicBezierSegment {
+                        from: current,
+                        ctrl1: ctrl1,
+                        ctrl2: ctrl2,
+                        to: to,
+                    }.angle_flattened_for_each(max_angle, &mut |p| points.push(p));
+                }
+                self.points.reverse();
+                return self.next();
+            }
+            None => None,
+        }
+    }
+}
+
+/// An iterator that consumes a PathIterator, applies a non-linear transformation to it and
+/// yields FlattenedEvents.
+///
+/// Each segment is split in half until the transformed points at a quarter, half and three
+/// quarters of each piece are within the tolerance of the line segment between the transformed
+/// ends of the piece (with a limit of 2^16 pieces per segment).
+pub struct WarpingIter<Iter, F> {
+    it: Iter,
+    warp: F,
+    tolerance: f32,
+    // The events of the current segment, in reverse order.
+    events: Vec<FlattenedEvent>,
+    // The state of the transformed path.
+    state: PathState,
+}
+
+impl<Iter: PathIterator, F: Fn(Vec2) -> Vec2> WarpingIter<Iter, F> {
+    /// Create the iterator.
+    pub fn new(warp: F, tolerance: f32, it: Iter) -> Self {
+        WarpingIter {
+            it: it,
+            warp: warp,
+            tolerance: tolerance,
+            events: Vec::new(),
+            state: PathState::new(),
+        }
+    }
+
+    fn warp_segment<S: Fn(f32) -> Point>(&mut self, sample: &S) {
+        let warp = &self.warp;
+        let from = (0.0, warp_point(warp, sample(0.0)));
+        let to = (1.0, warp_point(warp, sample(1.0)));
+        let events = &mut self.events;
+        warp_subdivide(sample, warp, from, to, self.tolerance, 0, &mut |p| {
+            events.push(FlattenedEvent::LineTo(p))
+        });
+    }
+}
+
+impl<Iter, F> FlattenedIterator for WarpingIter<Iter, F>
+where
+    Iter: PathIterator,
+    F: Fn(Vec2) -> Vec2,
+{
+    fn get_state(&self) -> &PathState { &self.state }
+}
+
+impl<Iter, F> Iterator for WarpingIter<Iter, F>
+where
+    Iter: PathIterator,
+    F: Fn(Vec2) -> Vec2,
+{
+    type Item = FlattenedEvent;
+    fn next(&mut self) -> Option<FlattenedEvent> {
+        if let Some(evt) = self.events.pop() {
+            self.state.flattened_event(evt);
+            return Some(evt);
+        }
+
+        let current = self.it.get_state().current;
+        match self.it.next() {
+            Some(PathEvent::MoveTo(to)) => {
+                let evt = FlattenedEvent::MoveTo(warp_point(&self.warp, to));
+                self.state.flattened_event(evt);
+                return Some(evt);
+            }
+            Some(PathEvent::LineTo(to)) => {
+                self.warp_segment(&|t| current.lerp(to, t));
+            }
+            Some(PathEvent::QuadraticTo(ctrl, to)) => {
+                let curve = QuadraticBezierSegment { from: current, ctrl: ctrl, to: to };
+                self.warp_segment(&|t| curve.sample(t));
+            }
+            Some(PathEvent::CubicTo(ctrl1, ctrl2, to)) => {
+                let curve = CubicBezierSegment { from: current, ctrl1: ctrl1, ctrl2: ctrl2, to: to };
+                self.warp_segment(&|t| curve.sample(t));
+            }
+            Some(PathEvent::Close) => {
+                // The closing segment is transformed like the others, and the close event
+                // replaces its last point.
+                let first = self.it.get_state().first;
+                if current != first {
+                    self.warp_segment(&|t| current.lerp(first, t));
+                    self.events.pop();
+                }
+                self.events.push(FlattenedEvent::Close);
+            }
+            None => {
+                return None;
+            }
+        }
+        self.events.reverse();
+
+        return self.next();
+    }
+}
+
+fn warp_point<F: Fn(Vec2) -> Vec2>(warp: &F, p: Point) -> Point { warp(p.to_vector()).to_point() }
+
+// Calls the callback with the transformed points of the sample function from the parameter
+// of `from` (excluded) to the one of `to` (included).
+fn warp_subdivide<S, F, C>(
+    sample: &S,
+    warp: &F,
+    from: (f32, Point),
+    to: (f32, Point),
+    tolerance: f32,
+    depth: u32,
+    call_back: &mut C,
+)
+where
+    S: Fn(f32) -> Point,
+    F: Fn(Vec2) -> Vec2,
+    C: FnMut(Point),
+{
+    let (t0, p0) = from;
+    let (t1, p1) = to;
+    let dt = t1 - t0;
+    let mid = (t0 + dt * 0.5, warp_point(warp, sample(t0 + dt * 0.5)));
+
+    if depth < 16 {
+        let flat = distance_to_segment(mid.1, p0, p1) <= tolerance
+            && distance_to_segment(warp_point(warp, sample(t0 + dt * 0.25)), p0, p1) <= tolerance
+            && distance_to_segment(warp_point(warp, sample(t0 + dt * 0.75)), p0, p1) <= tolerance;
+        if !flat {
+            warp_subdivide(sample, warp, from, mid, tolerance, depth + 1, call_back);
+            warp_subdivide(sample, warp, mid, to, tolerance, depth + 1, call_back);
+            return;
+        }
+    }
+
+    call_back(p1);
+}
+
+fn distance_to_segment(p: Point, a: Point, b: Point) -> f32 {
+    let ab = b - a;
+    let sq_length = ab.square_length();
+    if sq_length == 0.0 {
+        return (p - a).length();
+    }
+    let t = ((p - a).dot(ab) / sq_length).max(0.0).min(1.0);
+
+    return (p - (a + ab * t)).length();
+}
+
 /// An adapater iterator that implements SvgIterator on top of an Iterator<Item=SvgEvent>.
 pub struct PathStateSvgIter<Iter> {
     it: Iter,
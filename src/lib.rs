@@ -177,6 +177,8 @@
 //!   algorithm that is used by `lyon_path_iterator` and `lyon_path_builder`.
 //! - The `lyon_svg` crate contains utilities to interface with SVG. At the moment it is mostly
 //!   a collection of wrappers around the excellent `svgparser` crate.
+//! - The `lyon_algorithms` crate contains algorithms operating on paths that are not required
+//!   by the tessellators, like measuring the length of a path or placing glyphs along it.
 //! - The `lyon_core` crate contains internal details that are useful to all other lyon crates
 //!   (except `lyon_bezier`). It is reexported by all crates and you should not have to interact
 //!   directly with it.
@@ -195,6 +197,7 @@ pub extern crate lyon_tessellation;
 pub extern crate lyon_bezier;
 pub extern crate lyon_extra;
 pub extern crate lyon_svg;
+pub extern crate lyon_algorithms;
 //pub extern crate lyon_renderer;
 
 pub use lyon_core::*;
@@ -206,4 +209,5 @@ pub use lyon_path_iterator as path_iterator;
 pub use lyon_bezier as bezier;
 pub use lyon_extra as extra;
 pub use lyon_svg as svg;
+pub use lyon_algorithms as algorithms;
 //pub use lyon_renderer as renderer;
@@ -5,6 +5,8 @@
 use core::FlattenedEvent;
 use geometry_builder::{GeometryBuilder, Count, VertexId};
 use path_stroke::{StrokeOptions, StrokeTessellator};
+use path_fill::{FillTessellator, FillOptions, FillResult};
+use path::Polygon;
 use math_utils::compute_normal;
 use math::*;
 use {FillVertex, StrokeVertex, Side};
@@ -399,39 +401,11 @@ pub fn fill_rounded_rectangle<Output: GeometryBuilder<FillVertex>>(
 ) -> Count {
     output.begin_geometry();
 
-    let w = rect.size.width;
-    let h = rect.size.height;
     let x_min = rect.min_x();
     let y_min = rect.min_y();
     let x_max = rect.max_x();
     let y_max = rect.max_y();
-    let min_wh = w.min(h);
-    let mut tl = radii.top_left.abs().min(min_wh);
-    let mut tr = radii.top_right.abs().min(min_wh);
-    let mut bl = radii.bottom_left.abs().min(min_wh);
-    let mut br = radii.bottom_right.abs().min(min_wh);
-
-    // clamp border radii if they don't fit in the rectangle.
-    if tl + tr > w {
-        let x = (tl + tr - w) * 0.5;
-        tl -= x;
-        tr -= x;
-    }
-    if bl + br > w {
-        let x = (bl + br - w) * 0.5;
-        bl -= x;
-        br -= x;
-    }
-    if tr + br > h {
-        let x = (tr + br - h) * 0.5;
-        tr -= x;
-        br -= x;
-    }
-    if tl + bl > h {
-        let x = (tl + bl - h) * 0.5;
-        tl -= x;
-        bl -= x;
-    }
+    let (tl, tr, bl, br) = clamped_border_radii(rect, radii);
 
     // top
     let p1 = point(x_min + tl, y_min);
@@ -560,6 +534,80 @@ fn fill_border_radius<Output: GeometryBuilder<FillVertex>>(
     );
 }
 
+// Returns the top-left, top-right, bottom-left and bottom-right radii, reduced so that
+// they fit in the rectangle.
+fn clamped_border_radii(rect: &Rect, radii: &BorderRadii) -> (f32, f32, f32, f32) {
+    let w = rect.size.width;
+    let h = rect.size.height;
+    let min_wh = w.min(h);
+    let mut tl = radii.top_left.abs().min(min_wh);
+    let mut tr = radii.top_right.abs().min(min_wh);
+    let mut bl = radii.bottom_left.abs().min(min_wh);
+    let mut br = radii.bottom_right.abs().min(min_wh);
+
+    // clamp border radii if they don't fit in the rectangle.
+    if tl + tr > w {
+        let x = (tl + tr - w) * 0.5;
+        tl -= x;
+        tr -= x;
+    }
+    if bl + br > w {
+        let x = (bl + br - w) * 0.5;
+        bl -= x;
+        br -= x;
+    }
+    if tr + br > h {
+        let x = (tr + br - h) * 0.5;
+        tr -= x;
+        br -= x;
+    }
+    if tl + bl > h {
+        let x = (tl + bl - h) * 0.5;
+        tl -= x;
+        bl -= x;
+    }
+
+    return (tl, tr, bl, br);
+}
+
+/// Returns the signed distance between a point and the outline of an axis-aligned rounded
+/// rectangle.
+///
+/// The distance is negative inside of the shape and positive outside. The radii are clamped
+/// the same way as in `fill_rounded_rectangle`, and the top corners are the ones at the
+/// minimum y coordinate.
+pub fn rounded_rectangle_signed_distance(rect: &Rect, radii: &BorderRadii, p: Point) -> f32 {
+    let (tl, tr, bl, br) = clamped_border_radii(rect, radii);
+
+    let half_size = vec2(rect.size.width * 0.5, rect.size.height * 0.5);
+    let center = rect.origin + half_size;
+    let d = p - center;
+
+    // Each quadrant of the rectangle only depends on the radius of its corner.
+    let radius = match (d.x < 0.0, d.y < 0.0) {
+        (true, true) => tl,
+        (false, true) => tr,
+        (true, false) => bl,
+        (false, false) => br,
+    };
+
+    let q: Vec2 = vec2(
+        d.x.abs() - half_size.x + radius,
+        d.y.abs() - half_size.y + radius,
+    );
+    let outside = q.max(vec2(0.0, 0.0)).length();
+    let inside = q.x.max(q.y).min(0.0);
+
+    return outside + inside - radius;
+}
+
+/// Returns whether a point is inside of an axis-aligned rounded rectangle.
+///
+/// Points on the outline are considered inside.
+pub fn rounded_rectangle_contains(rect: &Rect, radii: &BorderRadii, p: Point) -> bool {
+    rounded_rectangle_signed_distance(rect, radii, p) <= 0.0
+}
+
 /// Tessellate the stroke of an axis-aligned rounded rectangle.
 pub fn stroke_rounded_rectangle<Output: GeometryBuilder<StrokeVertex>>(
     _rect: &Rect,
@@ -676,6 +724,16 @@ where
     return output.end_geometry();
 }
 
+/// Tessellate a polygon with holes.
+///
+/// The rings are sent to the fill tessellator as they are, without flattening.
+pub fn fill_polygon<Output>(polygon: &Polygon, options: &FillOptions, output: &mut Output) -> FillResult
+where
+    Output: GeometryBuilder<FillVertex>,
+{
+    return FillTessellator::new().tessellate_path(polygon.iter(), options, output);
+}
+
 /// Tessellate the stroke of a shape that is discribed by an iterator of points
 /// (convenient when tessellating a shape that is represented as a slice `&[Point]`).
 pub fn stroke_polyline<Iter, Output>(it: Iter, is_closed: bool, output: &mut Output) -> Count
@@ -797,3 +855,62 @@ fn test_polyline_events_closed() {
     assert_eq!(evts.next(), Some(FlattenedEvent::LineTo(point(5.0, 2.0))));
     assert_eq!(evts.next(), Some(FlattenedEvent::Close));
 }
+
+#[test]
+fn test_fill_polygon() {
+    use geometry_builder::{VertexBuffers, simple_builder};
+
+    let mut polygon = Polygon::new(
+        vec![point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)],
+        vec![vec![point(4.0, 4.0), point(4.0, 6.0), point(6.0, 6.0), point(6.0, 4.0)]],
+    );
+    polygon.close_rings();
+
+    let mut buffers: VertexBuffers<FillVertex> = VertexBuffers::new();
+    fill_polygon(&polygon, &FillOptions::default(), &mut simple_builder(&mut buffers)).unwrap();
+
+    // The hole is not covered.
+    let mut area = 0.0;
+    for t in buffers.indices.chunks(3) {
+        let a = buffers.vertices[t[0] as usize].position;
+        let b = buffers.vertices[t[1] as usize].position;
+        let c = buffers.vertices[t[2] as usize].position;
+        area += ((b - a).cross(c - a) * 0.5).abs();
+    }
+    assert!((area - 96.0).abs() < 0.001);
+}
+
+#[test]
+fn test_rounded_rectangle_distance() {
+    let r = rect(0.0, 0.0, 100.0, 50.0);
+    let radii = BorderRadii::new(10.0, 0.0, 20.0, 5.0);
+
+    let check = |p: Point, expected: f32| {
+        let d = rounded_rectangle_signed_distance(&r, &radii, p);
+        assert!((d - expected).abs() < 0.001, "{:?}: {} != {}", p, d, expected);
+        assert_eq!(rounded_rectangle_contains(&r, &radii, p), expected <= 0.0);
+    };
+
+    // Inside, closest to a straight side.
+    check(point(50.0, 20.0), -20.0);
+    check(point(95.0, 25.0), -5.0);
+    // Outside of a straight side.
+    check(point(50.0, -3.0), 3.0);
+    // Sharp top right corner.
+    check(point(103.0, -4.0), 5.0);
+    check(point(99.0, 1.0), -1.0);
+    // The top left corner is rounded: its tip is outside of the shape.
+    check(point(0.0, 0.0), 2.0f32.sqrt() * 10.0 - 10.0);
+    check(point(1.0, 1.0), 2.0f32.sqrt() * 9.0 - 10.0);
+    check(point(10.0, 0.0), 0.0);
+    // Bottom left corner.
+    check(point(20.0 - 12.0, 30.0 + 16.0), 0.0);
+    check(point(20.0 - 24.0, 30.0 + 32.0), 20.0);
+
+    // The radii are clamped to fit in the rectangle.
+    let big = BorderRadii::new_all_same(100.0);
+    let d = rounded_rectangle_signed_distance(&r, &big, point(0.0, 0.0));
+    assert!((d - (2.0f32.sqrt() * 25.0 - 25.0)).abs() < 0.001);
+    assert!(rounded_rectangle_contains(&r, &big, point(50.0, 0.0)));
+    assert!(!rounded_rectangle_contains(&r, &big, point(1.0, 1.0)));
+}
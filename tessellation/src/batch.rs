@@ -0,0 +1,409 @@
+//! Tessellating many shapes into shared buffers.
+//!
+//! Renderers usually put many shapes in the same vertex and index buffers to reduce the number
+//! of draw calls, and need to know which part of the buffers belongs to each shape.
+//! [tessellate_batch](fn.tessellate_batch.html) fill-tessellates a sequence of paths into a
+//! single geometry builder with the same `FillTessellator`, so that its allocations are reused
+//! between the shapes, and returns the range of vertices and indices of each shape.
+//!
+//! [tessellate_batch_to_buffers](fn.tessellate_batch_to_buffers.html) produces the same
+//! layout in new buffers. With the `parallel` feature enabled, the shapes are tessellated in
+//! parallel using rayon and the results are concatenated.
+//!
+//! [tessellate_sub_paths](fn.tessellate_sub_paths.html) applies the same idea to the sub-paths
+//! of a single path, keeping the holes with the sub-path they belong to.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::batch::*;
+//! # fn set_color(_color: [f32; 4]) {}
+//! # fn draw(_indices: std::ops::Range<u32>) {}
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let paths = vec![path.clone(), path];
+//! # let colors = vec![[1.0, 0.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]];
+//! let options = FillOptions::default();
+//! let shapes: Vec<(&Path, &FillOptions)> = paths.iter().map(|path| (path, &options)).collect();
+//! let mut buffers: VertexBuffers<FillVertex> = VertexBuffers::new();
+//! let batch = tessellate_batch(shapes.iter().cloned(), &mut simple_builder(&mut buffers)).unwrap();
+//! for (shape, range) in batch.shapes.iter().enumerate() {
+//!     set_color(colors[shape]);
+//!     draw(range.indices.clone());
+//! }
+//! # }
+//! ```
+
+use std::ops::Range;
+
+use math::Point;
+use core::FlattenedEvent;
+use path::Path;
+use path_iterator::PathIterator;
+use geometry_builder::{GeometryBuilder, Count, VertexBuffers, Index, simple_builder};
+use path_fill::{FillTessellator, FillOptions, FillError};
+use FillVertex as Vertex;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The part of the geometry that belongs to a shape.
+///
+/// The ranges are relative to the geometry produced by the batch, which starts at the end of
+/// the buffers when the output is a `BuffersBuilder`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShapeRange {
+    pub vertices: Range<u32>,
+    pub indices: Range<u32>,
+}
+
+/// The result of the tessellation of a batch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Batch {
+    /// The ranges of each shape, in the order of the input.
+    pub shapes: Vec<ShapeRange>,
+    /// The number of vertices and indices of the whole batch.
+    pub count: Count,
+}
+
+/// Fill-tessellates a sequence of paths into a geometry builder.
+///
+/// Each shape is a separate geometry from the point of view of the builder (between a
+/// `begin_geometry` and an `end_geometry`). The tessellation stops at the first error.
+pub fn tessellate_batch<'l, Iter, Output>(shapes: Iter, output: &mut Output) -> Result<Batch, FillError>
+where
+    Iter: Iterator<Item = (&'l Path, &'l FillOptions)>,
+    Output: GeometryBuilder<Vertex>,
+{
+    let mut tessellator = FillTessellator::new();
+    let mut batch = Batch {
+        shapes: Vec::new(),
+        count: Count { vertices: 0, indices: 0 },
+    };
+
+    for (path, options) in shapes {
+        let count = try!{
+            tessellator.tessellate_path(path.path_iter().flattened(options.tolerance), options, output)
+        };
+        batch.shapes.push(ShapeRange {
+            vertices: batch.count.vertices..batch.count.vertices + count.vertices,
+            indices: batch.count.indices..batch.count.indices + count.indices,
+        });
+        batch.count = batch.count + count;
+    }
+
+    return Ok(batch);
+}
+
+/// Fill-tessellates paths into new buffers.
+///
+/// The indices of the buffers are absolute (they don't need to be offset by the start of the
+/// vertex range of their shape).
+pub fn tessellate_batch_to_buffers(
+    shapes: &[(&Path, &FillOptions)],
+) -> Result<(VertexBuffers<Vertex>, Batch), FillError> {
+    let tessellate = |&(path, options): &(&Path, &FillOptions)| -> Result<VertexBuffers<Vertex>, FillError> {
+        let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+        let mut tessellator = FillTessellator::new();
+        try!{
+            tessellator.tessellate_path(
+                path.path_iter().flattened(options.tolerance),
+                options,
+                &mut simple_builder(&mut buffers),
+            )
+        };
+
+        return Ok(buffers);
+    };
+
+    #[cfg(feature = "parallel")]
+    let results: Vec<Result<VertexBuffers<Vertex>, FillError>> = shapes.par_iter().map(tessellate).collect();
+    #[cfg(not(feature = "parallel"))]
+    let results: Vec<Result<VertexBuffers<Vertex>, FillError>> = shapes.iter().map(tessellate).collect();
+
+    let mut output: VertexBuffers<Vertex> = VertexBuffers::new();
+    let mut batch = Batch {
+        shapes: Vec::with_capacity(shapes.len()),
+        count: Count { vertices: 0, indices: 0 },
+    };
+    for result in results {
+        let buffers = try!{ result };
+        let first_vertex = output.vertices.len() as u32;
+        let first_index = output.indices.len() as u32;
+        output.vertices.extend_from_slice(&buffers.vertices);
+        output.indices.extend(buffers.indices.iter().map(|&i| i + first_vertex as Index));
+        batch.shapes.push(ShapeRange {
+            vertices: first_vertex..output.vertices.len() as u32,
+            indices: first_index..output.indices.len() as u32,
+        });
+    }
+    batch.count = Count {
+        vertices: output.vertices.len() as u32,
+        indices: output.indices.len() as u32,
+    };
+
+    return Ok((output, batch));
+}
+
+/// Fill-tessellates a path and returns the range of vertices and indices of each of its
+/// sub-paths, in the order of the input.
+///
+/// The sub-paths are grouped with the even-odd rule: each sub-path that isn't a hole is
+/// tessellated together with the holes directly inside of it, so that the holes are preserved,
+/// and a hole gets the ranges of the sub-path that contains it. The groups are separate
+/// geometries from the point of view of the builder, like the shapes of
+/// [tessellate_batch](fn.tessellate_batch.html).
+///
+/// Sub-paths are assumed not to cross each other; the parts where they do are not filled the
+/// same way as when tessellating the whole path at once.
+pub fn tessellate_sub_paths<Iter, Output>(
+    path: Iter,
+    options: &FillOptions,
+    output: &mut Output,
+) -> Result<Batch, FillError>
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Output: GeometryBuilder<Vertex>,
+{
+    let rings = collect_rings(path);
+
+    // The ring that directly contains each ring, if any.
+    let mut parents: Vec<Option<usize>> = Vec::with_capacity(rings.len());
+    let mut depths: Vec<usize> = Vec::with_capacity(rings.len());
+    for (i, ring) in rings.iter().enumerate() {
+        let mut parent: Option<usize> = None;
+        let mut depth = 0;
+        if let Some(&p) = ring.first() {
+            for (j, other) in rings.iter().enumerate() {
+                if i != j && ring_contains(other, p) {
+                    depth += 1;
+                    // The direct parent is the smallest of the containing rings.
+                    parent = match parent {
+                        Some(k) if area(&rings[k]) <= area(other) => Some(k),
+                        _ => Some(j),
+                    };
+                }
+            }
+        }
+        parents.push(parent);
+        depths.push(depth);
+    }
+
+    let mut tessellator = FillTessellator::new();
+    let mut batch = Batch {
+        shapes: vec![ShapeRange { vertices: 0..0, indices: 0..0 }; rings.len()],
+        count: Count { vertices: 0, indices: 0 },
+    };
+
+    for i in 0..rings.len() {
+        if depths[i] % 2 == 1 {
+            continue;
+        }
+
+        let group: Vec<usize> = (0..rings.len()).filter(|&j| {
+            j == i || (depths[j] % 2 == 1 && parents[j] == Some(i))
+        }).collect();
+
+        let mut events = Vec::new();
+        for &j in &group {
+            events.push(FlattenedEvent::MoveTo(rings[j][0]));
+            for &p in &rings[j][1..] {
+                events.push(FlattenedEvent::LineTo(p));
+            }
+            events.push(FlattenedEvent::Close);
+        }
+
+        let count = try!{ tessellator.tessellate_path(events.into_iter(), options, output) };
+        let range = ShapeRange {
+            vertices: batch.count.vertices..batch.count.vertices + count.vertices,
+            indices: batch.count.indices..batch.count.indices + count.indices,
+        };
+        for &j in &group {
+            batch.shapes[j] = range.clone();
+        }
+        batch.count = batch.count + count;
+    }
+
+    return Ok(batch);
+}
+
+fn collect_rings<Iter: Iterator<Item = FlattenedEvent>>(path: Iter) -> Vec<Vec<Point>> {
+    let mut rings = Vec::new();
+    let mut ring: Vec<Point> = Vec::new();
+    // Edges after a close start a new sub-path at the same position.
+    let mut first = Point::new(0.0, 0.0);
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                if !ring.is_empty() {
+                    rings.push(ring);
+                }
+                ring = vec![to];
+                first = to;
+            }
+            FlattenedEvent::LineTo(to) => {
+                if ring.is_empty() {
+                    ring.push(first);
+                }
+                ring.push(to);
+            }
+            FlattenedEvent::Close => {
+                if !ring.is_empty() {
+                    rings.push(ring);
+                    ring = Vec::new();
+                }
+            }
+        }
+    }
+    if !ring.is_empty() {
+        rings.push(ring);
+    }
+
+    return rings;
+}
+
+fn area(ring: &[Point]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+        area += a.x * b.y - b.x * a.y;
+    }
+
+    return (area * 0.5).abs();
+}
+
+// Even-odd point in polygon test.
+fn ring_contains(ring: &[Point], p: Point) -> bool {
+    let mut inside = false;
+    for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+        if (a.y > p.y) != (b.y > p.y) {
+            let x = a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y);
+            if x > p.x {
+                inside = !inside;
+            }
+        }
+    }
+
+    return inside;
+}
+
+#[cfg(test)]
+use math::point;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+
+#[cfg(test)]
+fn test_shapes() -> Vec<Path> {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.line_to(point(1.0, 1.0));
+    builder.close();
+    let triangle = builder.build();
+
+    let mut builder = Path::builder();
+    builder.move_to(point(10.0, 0.0));
+    builder.line_to(point(12.0, 0.0));
+    builder.line_to(point(12.0, 2.0));
+    builder.line_to(point(10.0, 2.0));
+    builder.close();
+    let square = builder.build();
+
+    return vec![triangle, square];
+}
+
+#[test]
+fn test_tessellate_batch() {
+    let paths = test_shapes();
+    let options = FillOptions::default();
+    let shapes: Vec<(&Path, &FillOptions)> = paths.iter().map(|path| (path, &options)).collect();
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    let batch = tessellate_batch(shapes.iter().cloned(), &mut simple_builder(&mut buffers)).unwrap();
+
+    assert_eq!(batch.shapes.len(), 2);
+    assert_eq!(batch.shapes[0], ShapeRange { vertices: 0..3, indices: 0..3 });
+    assert_eq!(batch.shapes[1], ShapeRange { vertices: 3..7, indices: 3..9 });
+    assert_eq!(batch.count, Count { vertices: 7, indices: 9 });
+
+    // Each shape's indices refer to its own vertices.
+    for range in &batch.shapes {
+        for &i in &buffers.indices[range.indices.start as usize..range.indices.end as usize] {
+            assert!(range.vertices.start <= i as u32 && (i as u32) < range.vertices.end);
+        }
+    }
+
+    let (buffers2, batch2) = tessellate_batch_to_buffers(&shapes).unwrap();
+    assert_eq!(batch2, batch);
+    assert_eq!(buffers2.indices, buffers.indices);
+}
+
+#[test]
+fn test_tessellate_sub_paths() {
+    let mut builder = Path::builder();
+    // A square with a hole.
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    builder.move_to(point(2.0, 2.0));
+    builder.line_to(point(8.0, 2.0));
+    builder.line_to(point(8.0, 8.0));
+    builder.line_to(point(2.0, 8.0));
+    builder.close();
+    // An island in the hole.
+    builder.move_to(point(4.0, 4.0));
+    builder.line_to(point(6.0, 4.0));
+    builder.line_to(point(6.0, 6.0));
+    builder.line_to(point(4.0, 6.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    let batch = tessellate_sub_paths(
+        path.path_iter().flattened(0.1),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    assert_eq!(batch.shapes.len(), 3);
+    assert_eq!(batch.shapes[1], batch.shapes[0]);
+    assert_eq!(batch.shapes[0].vertices, 0..8);
+    assert_eq!(batch.shapes[2].vertices, 8..12);
+    assert_eq!(batch.shapes[2].indices.start, batch.shapes[0].indices.end);
+    assert_eq!(batch.shapes[2].indices.end - batch.shapes[2].indices.start, 6);
+    assert_eq!(batch.count.indices as usize, buffers.indices.len());
+
+    // The area of each range.
+    let range_area = |range: &ShapeRange| {
+        let mut total = 0.0;
+        for triangle in buffers.indices[range.indices.start as usize..range.indices.end as usize].chunks(3) {
+            let a = buffers.vertices[triangle[0] as usize].position;
+            let b = buffers.vertices[triangle[1] as usize].position;
+            let c = buffers.vertices[triangle[2] as usize].position;
+            total += ((b - a).cross(c - a) * 0.5).abs();
+        }
+        total
+    };
+    assert_eq!(range_area(&batch.shapes[0]), 64.0);
+    assert_eq!(range_area(&batch.shapes[2]), 4.0);
+}
@@ -0,0 +1,199 @@
+//! Fill tessellation telling the triangles along curves apart from the others.
+//!
+//! The fill tessellator works on flattened paths and doesn't know which edges approximate
+//! curves. [fill_path_classified](fn.fill_path_classified.html) flattens the curves itself,
+//! remembers the edges they produced, and gives each triangle of the tessellation to a
+//! [ClassifiedGeometryBuilder](../geometry_builder/trait.ClassifiedGeometryBuilder.html)
+//! along with its [source](../geometry_builder/enum.TriangleSource.html): the triangles that
+//! have an edge along a flattened curve are `TriangleSource::Curve` and the others are
+//! `TriangleSource::Interior`. A renderer can then evaluate the curves on the GPU, or apply
+//! any other special shading, in the curve triangles only.
+//!
+//! The fill tessellator doesn't produce anti-aliasing fringes yet (see
+//! `FillOptions::vertex_aa`), so `TriangleSource::Fringe` is never used here.
+//!
+//! The edges of a curve that are split by an intersection with another edge are not
+//! recognized, the triangles along the split parts are classified as interior.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::classified_fill::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let mut buffers: VertexBuffers<FillVertex> = VertexBuffers::new();
+//! let mut sources = Vec::new();
+//! fill_path_classified(
+//!     path.iter(),
+//!     &FillOptions::default(),
+//!     &mut TriangleSourceRecorder::new(&mut simple_builder(&mut buffers), &mut sources),
+//! ).unwrap();
+//! for (triangle, source) in buffers.indices.chunks(3).zip(sources.iter()) {
+//!     // ...
+//! #   let _ = (triangle, source);
+//! }
+//! # }
+//! ```
+
+use std::collections::HashSet;
+
+use math::*;
+use core::{PathEvent, FlattenedEvent};
+use bezier::{QuadraticBezierSegment, CubicBezierSegment};
+use geometry_builder::{ClassifiedGeometryBuilder, TriangleSource, VertexBuffers, VertexId, simple_builder};
+use path_fill::{FillTessellator, FillOptions, FillResult};
+use FillVertex as Vertex;
+
+/// Fill-tessellates a path, flattening its curves with the tolerance of the options and
+/// classifying the triangles. See the [module documentation](index.html).
+pub fn fill_path_classified<Iter, Output>(
+    path: Iter,
+    options: &FillOptions,
+    output: &mut Output,
+) -> FillResult
+where
+    Iter: Iterator<Item = PathEvent>,
+    Output: ClassifiedGeometryBuilder<Vertex>,
+{
+    let mut events = Vec::new();
+    let mut curve_edges = HashSet::new();
+    let mut first = point(0.0, 0.0);
+    let mut current = point(0.0, 0.0);
+    for evt in path {
+        match evt {
+            PathEvent::MoveTo(to) => {
+                events.push(FlattenedEvent::MoveTo(to));
+                first = to;
+                current = to;
+            }
+            PathEvent::LineTo(to) => {
+                events.push(FlattenedEvent::LineTo(to));
+                current = to;
+            }
+            PathEvent::QuadraticTo(ctrl, to) => {
+                let curve = QuadraticBezierSegment { from: current, ctrl: ctrl, to: to };
+                curve.flattened_for_each(options.tolerance, &mut |p| {
+                    curve_edges.insert(edge_key(current, p));
+                    events.push(FlattenedEvent::LineTo(p));
+                    current = p;
+                });
+            }
+            PathEvent::CubicTo(ctrl1, ctrl2, to) => {
+                let curve = CubicBezierSegment { from: current, ctrl1: ctrl1, ctrl2: ctrl2, to: to };
+                curve.flattened_for_each(options.tolerance, &mut |p| {
+                    curve_edges.insert(edge_key(current, p));
+                    events.push(FlattenedEvent::LineTo(p));
+                    current = p;
+                });
+            }
+            PathEvent::Close => {
+                events.push(FlattenedEvent::Close);
+                current = first;
+            }
+        }
+    }
+
+    let mut mesh: VertexBuffers<Vertex> = VertexBuffers::new();
+    try!{ FillTessellator::new().tessellate_path(events.into_iter(), options, &mut simple_builder(&mut mesh)) };
+
+    output.begin_geometry();
+    for &vertex in &mesh.vertices {
+        output.add_vertex(vertex);
+    }
+    for triangle in mesh.indices.chunks(3) {
+        let p = |i: usize| mesh.vertices[triangle[i] as usize].position;
+        let along_curve = (0..3).any(|i| curve_edges.contains(&edge_key(p(i), p((i + 1) % 3))));
+        output.add_classified_triangle(
+            VertexId(triangle[0]),
+            VertexId(triangle[1]),
+            VertexId(triangle[2]),
+            if along_curve { TriangleSource::Curve } else { TriangleSource::Interior },
+        );
+    }
+
+    return Ok(output.end_geometry());
+}
+
+// The positions are rounded the same way as the tessellator does, so that the vertices of
+// the output can be matched with the flattened curves.
+fn point_key(p: Point) -> (u32, u32) {
+    (fixed(p.x).to_f32().to_bits(), fixed(p.y).to_f32().to_bits())
+}
+
+fn edge_key(a: Point, b: Point) -> ((u32, u32), (u32, u32)) {
+    let (a, b) = (point_key(a), point_key(b));
+    if a < b { (a, b) } else { (b, a) }
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::{BaseBuilder, PathBuilder};
+#[cfg(test)]
+use geometry_builder::TriangleSourceRecorder;
+
+#[test]
+fn test_fill_path_classified() {
+    // A square with a bump on its top side.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.quadratic_bezier_to(point(5.0, -5.0), point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    let mut sources = Vec::new();
+    fill_path_classified(
+        path.iter(),
+        &FillOptions::default().with_tolerance(0.1),
+        &mut TriangleSourceRecorder::new(&mut simple_builder(&mut buffers), &mut sources),
+    ).unwrap();
+
+    assert_eq!(sources.len() * 3, buffers.indices.len());
+    assert!(sources.contains(&TriangleSource::Curve));
+    assert!(sources.contains(&TriangleSource::Interior));
+    for (triangle, source) in buffers.indices.chunks(3).zip(sources.iter()) {
+        // All of the edges of the curve have a vertex above the square.
+        let above = triangle.iter().any(|&i| buffers.vertices[i as usize].position.y < 0.0);
+        if !above {
+            assert_eq!(*source, TriangleSource::Interior);
+        }
+        assert!(*source != TriangleSource::Fringe);
+    }
+
+    // Without curves, all of the triangles are interior.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    let mut sources = Vec::new();
+    fill_path_classified(
+        path.iter(),
+        &FillOptions::default(),
+        &mut TriangleSourceRecorder::new(&mut simple_builder(&mut buffers), &mut sources),
+    ).unwrap();
+    assert_eq!(sources, vec![TriangleSource::Interior]);
+}
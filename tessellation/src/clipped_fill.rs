@@ -0,0 +1,354 @@
+//! Fill tessellation of a path restricted to the fill of a clip path.
+//!
+//! This is useful to render shapes through non-rectangular viewports or masks (a circular
+//! minimap for example) without stencil tricks or a separate boolean operation.
+//!
+//! Both paths are fill-tessellated and decomposed into convex polygons (see the
+//! [convex_decomposition](../convex_decomposition/index.html) module), then each convex
+//! polygon of the path is clipped against the overlapping convex polygons of the clip path.
+//! The convex polygons of the clip path are bucketed in a grid, so each polygon of the path is
+//! only clipped against the ones near it. The intersection of two convex polygons being
+//! convex, the results are triangulated as fans. The vertices closer than a hundredth of the
+//! tolerance are merged, so that the adjacent pieces share their vertices instead of leaving
+//! cracks where rounding errors put the intersections at slightly different positions.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::clipped_fill::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let map = path;
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(5.0, 5.0));
+//! # builder.line_to(point(15.0, 5.0));
+//! # builder.line_to(point(15.0, 15.0));
+//! # builder.line_to(point(5.0, 15.0));
+//! # builder.close();
+//! # let viewport = builder.build();
+//! # let mut buffers: VertexBuffers<FillVertex> = VertexBuffers::new();
+//! let count = fill_clipped(
+//!     map.path_iter().flattened(0.1),
+//!     viewport.path_iter().flattened(0.1),
+//!     &FillOptions::default(),
+//!     &mut simple_builder(&mut buffers),
+//! ).unwrap();
+//! # assert!(count.indices > 0);
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use math::*;
+use core::FlattenedEvent;
+use core::math_utils::rects_overlap_with_margin;
+use algorithms::clip::clip_polygon_to_convex;
+use geometry_builder::{GeometryBuilder, Count, VertexId};
+use convex_decomposition::fill_convex_polygons;
+use path_fill::{FillOptions, FillError};
+use tiling::TileGrid;
+use FillVertex as Vertex;
+
+/// Fill-tessellates the intersection of the fills of `path` and `clip`.
+///
+/// The fill rule and tolerance of `options` apply to both paths. The normals of the produced
+/// vertices are not computed (they are nil vectors).
+pub fn fill_clipped<PathIter, ClipIter, Output>(
+    path: PathIter,
+    clip: ClipIter,
+    options: &FillOptions,
+    output: &mut Output,
+) -> Result<Count, FillError>
+where
+    PathIter: Iterator<Item = FlattenedEvent>,
+    ClipIter: Iterator<Item = FlattenedEvent>,
+    Output: GeometryBuilder<Vertex>,
+{
+    let polygons = try!{ fill_convex_polygons(path, options, ::std::usize::MAX) };
+    let clip_polygons = try!{ fill_convex_polygons(clip, options, ::std::usize::MAX) };
+
+    output.begin_geometry();
+
+    if polygons.is_empty() || clip_polygons.is_empty() {
+        return Ok(output.end_geometry());
+    }
+
+    let clip_index = ClipIndex::new(&clip_polygons);
+    let mut vertices = VertexMerger::new(options.tolerance * MERGE_DISTANCE_FACTOR);
+    let mut candidates = Vec::new();
+
+    for polygon in &polygons {
+        clip_index.candidates(&polygon_rect(polygon), &mut candidates);
+        for &idx in &candidates {
+            let clipped = clip_polygon_to_convex(polygon, &clip_polygons[idx]);
+            if clipped.len() < 3 {
+                continue;
+            }
+
+            // Merging the vertices can collapse some of the edges, only keep the distinct ones.
+            let mut fan: Vec<(VertexId, Point)> = Vec::with_capacity(clipped.len());
+            for &p in &clipped {
+                let vertex = vertices.add(p, output);
+                let is_new = fan.last().map(|v| v.0) != Some(vertex.0)
+                    && fan.first().map(|v| v.0) != Some(vertex.0);
+                if is_new {
+                    fan.push(vertex);
+                }
+            }
+
+            for i in 2..fan.len() {
+                let (first, a) = fan[0];
+                let (previous, b) = fan[i - 1];
+                let (current, c) = fan[i];
+                // Skip the triangles that have no area, which clipping along the sides of the
+                // clip polygon can produce.
+                if (b - a).cross(c - a) != 0.0 {
+                    output.add_triangle(first, previous, current);
+                }
+            }
+        }
+    }
+
+    return Ok(output.end_geometry());
+}
+
+/// Vertices closer than this fraction of the tolerance are merged.
+const MERGE_DISTANCE_FACTOR: f32 = 0.01;
+
+/// Buckets the convex pieces of the clip path in a grid so that each piece of the path is
+/// only clipped against the pieces that are near it.
+struct ClipIndex {
+    grid: TileGrid,
+    rects: Vec<Rect>,
+    cells: Vec<Vec<usize>>,
+}
+
+impl ClipIndex {
+    fn new(polygons: &[Vec<Point>]) -> Self {
+        let rects: Vec<Rect> = polygons.iter().map(|polygon| polygon_rect(polygon)).collect();
+        let mut bounds = rects[0];
+        for r in &rects[1..] {
+            bounds = bounds.union(r);
+        }
+
+        // About one piece per cell.
+        let n = (rects.len() as f32).sqrt().ceil() as u32;
+        let tile_size = size(
+            (bounds.size.width / n as f32).max(::std::f32::MIN_POSITIVE),
+            (bounds.size.height / n as f32).max(::std::f32::MIN_POSITIVE),
+        );
+        let grid = TileGrid::new(bounds.origin, tile_size, n, n);
+
+        let mut cells = vec![Vec::new(); (n * n) as usize];
+        for (idx, r) in rects.iter().enumerate() {
+            let (columns, rows) = grid.tiles_in_rect(r);
+            for y in rows {
+                for x in columns.clone() {
+                    cells[(y * n + x) as usize].push(idx);
+                }
+            }
+        }
+
+        return ClipIndex { grid: grid, rects: rects, cells: cells };
+    }
+
+    /// Writes the indices of the pieces which bounding rectangle overlaps `rect`, in
+    /// increasing order and without duplicates.
+    fn candidates(&self, rect: &Rect, output: &mut Vec<usize>) {
+        output.clear();
+        let (columns, rows) = self.grid.tiles_in_rect(rect);
+        for y in rows {
+            for x in columns.clone() {
+                for &idx in &self.cells[(y * self.grid.columns + x) as usize] {
+                    if rects_overlap_with_margin(rect, &self.rects[idx], 0.0) {
+                        output.push(idx);
+                    }
+                }
+            }
+        }
+        output.sort();
+        output.dedup();
+    }
+}
+
+/// Shares the vertices that are closer than a merge distance.
+///
+/// The intersections of the edges are computed once per clipped pair of pieces, and
+/// rounding errors place them at slightly different positions depending on the pair. Adding
+/// them as separate vertices would leave cracks and T-junctions between adjacent pieces.
+struct VertexMerger {
+    distance: f32,
+    cells: HashMap<(i32, i32), Vec<(VertexId, Point)>>,
+}
+
+impl VertexMerger {
+    fn new(distance: f32) -> Self {
+        VertexMerger {
+            distance: distance.max(::std::f32::MIN_POSITIVE),
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Returns the vertex merged with `p` and its position, adding it if needed.
+    fn add<Output>(&mut self, p: Point, output: &mut Output) -> (VertexId, Point)
+    where
+        Output: GeometryBuilder<Vertex>,
+    {
+        let cx = (p.x / self.distance).floor() as i32;
+        let cy = (p.y / self.distance).floor() as i32;
+        for y in (cy - 1)..(cy + 2) {
+            for x in (cx - 1)..(cx + 2) {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    for &(id, position) in cell {
+                        if (position - p).square_length() <= self.distance * self.distance {
+                            return (id, position);
+                        }
+                    }
+                }
+            }
+        }
+
+        let id = output.add_vertex(Vertex { position: p, normal: vec2(0.0, 0.0) });
+        self.cells.entry((cx, cy)).or_insert_with(Vec::new).push((id, p));
+
+        return (id, p);
+    }
+}
+
+fn polygon_rect(polygon: &[Point]) -> Rect {
+    let mut min = polygon[0];
+    let mut max = polygon[0];
+    for p in &polygon[1..] {
+        min = point(min.x.min(p.x), min.y.min(p.y));
+        max = point(max.x.max(p.x), max.y.max(p.y));
+    }
+
+    return rect(min.x, min.y, max.x - min.x, max.y - min.y);
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+#[cfg(test)]
+use geometry_builder::{VertexBuffers, simple_builder};
+
+#[cfg(test)]
+fn add_square(builder: &mut ::path::Builder, x: f32, y: f32, size: f32) {
+    builder.move_to(point(x, y));
+    builder.line_to(point(x + size, y));
+    builder.line_to(point(x + size, y + size));
+    builder.line_to(point(x, y + size));
+    builder.close();
+}
+
+#[cfg(test)]
+fn fill_clipped_buffers(path: &Path, clip: &Path) -> VertexBuffers<Vertex> {
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    fill_clipped(
+        path.path_iter().flattened(0.1),
+        clip.path_iter().flattened(0.1),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    return buffers;
+}
+
+#[cfg(test)]
+fn clipped_area(path: &Path, clip: &Path) -> f32 {
+    let buffers = fill_clipped_buffers(path, clip);
+    let mut area = 0.0;
+    for triangle in buffers.indices.chunks(3) {
+        let a = buffers.vertices[triangle[0] as usize].position;
+        let b = buffers.vertices[triangle[1] as usize].position;
+        let c = buffers.vertices[triangle[2] as usize].position;
+        area += ((b - a).cross(c - a) * 0.5).abs();
+    }
+
+    return area;
+}
+
+#[test]
+fn test_fill_clipped() {
+    // A square with a hole.
+    let mut builder = Path::builder();
+    add_square(&mut builder, 0.0, 0.0, 10.0);
+    add_square(&mut builder, 3.0, 3.0, 4.0);
+    let path = builder.build();
+
+    let mut builder = Path::builder();
+    add_square(&mut builder, 5.0, 5.0, 10.0);
+    let clip = builder.build();
+    assert!((clipped_area(&path, &clip) - 21.0).abs() < 0.001);
+    assert!((clipped_area(&clip, &path) - 21.0).abs() < 0.001);
+
+    // The clip path is entirely in the hole.
+    let mut builder = Path::builder();
+    add_square(&mut builder, 4.0, 4.0, 2.0);
+    let clip = builder.build();
+    assert_eq!(clipped_area(&path, &clip), 0.0);
+
+    // The clip path has a hole too.
+    let mut builder = Path::builder();
+    add_square(&mut builder, -5.0, -5.0, 20.0);
+    add_square(&mut builder, 0.0, 0.0, 5.0);
+    let clip = builder.build();
+    assert!((clipped_area(&path, &clip) - 63.0).abs() < 0.001);
+}
+
+#[test]
+fn test_fill_clipped_shared_edges() {
+    // The edges of the result that aren't shared by two triangles form its outline. Cracks or
+    // T-junctions between the clipped pieces would add to its length.
+    let mut builder = Path::builder();
+    add_square(&mut builder, 0.0, 0.0, 10.0);
+    add_square(&mut builder, 3.0, 3.0, 4.0);
+    let path = builder.build();
+
+    let mut builder = Path::builder();
+    add_square(&mut builder, 5.0, 5.0, 10.0);
+    add_square(&mut builder, 8.0, 8.0, 1.0);
+    let clip = builder.build();
+
+    let buffers = fill_clipped_buffers(&path, &clip);
+    let mut edges: HashMap<(u16, u16), u32> = HashMap::new();
+    for triangle in buffers.indices.chunks(3) {
+        for i in 0..3 {
+            let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+            *edges.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+        }
+    }
+
+    let mut outline = 0.0;
+    for (&(a, b), &count) in &edges {
+        assert!(count <= 2);
+        if count == 1 {
+            let a = buffers.vertices[a as usize].position;
+            let b = buffers.vertices[b as usize].position;
+            outline += (b - a).length();
+        }
+    }
+
+    assert!((outline - 24.0).abs() < 0.001);
+    assert!((clipped_area(&path, &clip) - 20.0).abs() < 0.001);
+}
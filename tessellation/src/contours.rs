@@ -0,0 +1,274 @@
+//! Extracting the boundary of the fill tessellation.
+//!
+//! The fill tessellator resolves the self-intersections of the path and applies the fill
+//! rule, so the boundary of its output is a clean version of the path: simple loops that
+//! don't cross each other, with the parts excluded by the fill rule removed. This module
+//! extracts these loops as polylines, for example to generate the side walls of an
+//! [extrusion](../extrusion/index.html), to draw outline effects or to export the cleaned
+//! geometry.
+//!
+//! The outer boundaries are counter-clockwise and the holes are clockwise (with the y axis
+//! pointing up). When several loops touch at a vertex, they are kept separate.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::contours::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! let contours = fill_contours(path.path_iter().flattened(0.05), &FillOptions::even_odd()).unwrap();
+//! for contour in &contours {
+//!     if contour.is_hole {
+//!         // ...
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::PI;
+
+use math::*;
+use core::FlattenedEvent;
+use geometry_builder::{VertexBuffers, BuffersBuilder, TypedPositions, Index};
+use path_fill::{FillTessellator, FillOptions, FillError};
+
+/// A closed loop of the boundary of a fill.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contour {
+    /// The points of the loop, the last point is implicitly connected to the first one.
+    pub points: Vec<Point>,
+    /// Whether the loop is the boundary of a hole (clockwise) rather than an outer boundary
+    /// (counter-clockwise).
+    pub is_hole: bool,
+}
+
+/// Fill-tessellates a path and returns the loops of the boundary of the fill.
+pub fn fill_contours<Iter>(path: Iter, options: &FillOptions) -> Result<Vec<Contour>, FillError>
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let mut fill: VertexBuffers<Point> = VertexBuffers::new();
+    try!{
+        FillTessellator::new().tessellate_path(
+            path,
+            options,
+            &mut BuffersBuilder::new(&mut fill, TypedPositions::new()),
+        )
+    };
+
+    return Ok(mesh_contours(&fill.vertices, &fill.indices));
+}
+
+/// Returns the loops of the boundary of a triangle mesh, such as the output of the fill
+/// tessellator.
+pub fn mesh_contours(vertices: &[Point], indices: &[Index]) -> Vec<Contour> {
+    return boundary_loops(vertices, indices).iter().map(|boundary| {
+        let points: Vec<Point> = boundary.iter().map(|&i| vertices[i as usize]).collect();
+        let is_hole = signed_area(&points) < 0.0;
+        Contour { points: points, is_hole: is_hole }
+    }).collect();
+}
+
+/// Returns the loops of the boundary of a triangle mesh as vertex indices.
+///
+/// The boundary is made of the edges that belong to a single triangle. The orientation of
+/// the triangles doesn't matter, the loops are oriented so that the mesh is on their left.
+pub fn boundary_loops(vertices: &[Point], indices: &[Index]) -> Vec<Vec<Index>> {
+    // The edges, oriented so that their triangle is on the left.
+    let mut edges: HashMap<(Index, Index), (Index, Index, u32)> = HashMap::new();
+    for triangle in indices.chunks(3) {
+        let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+        let area = (vertices[b as usize] - vertices[a as usize])
+            .cross(vertices[c as usize] - vertices[a as usize]);
+        let t = if area > 0.0 {
+            [a, b, c]
+        } else if area < 0.0 {
+            [a, c, b]
+        } else {
+            continue;
+        };
+        for i in 0..3 {
+            let (from, to) = (t[i], t[(i + 1) % 3]);
+            let key = if from < to { (from, to) } else { (to, from) };
+            edges.entry(key).or_insert((from, to, 0)).2 += 1;
+        }
+    }
+
+    let mut boundary: Vec<(Index, Index)> = edges.values()
+        .filter(|&&(_, _, count)| count == 1)
+        .map(|&(from, to, _)| (from, to))
+        .collect();
+    // Keep the output deterministic.
+    boundary.sort();
+
+    let mut outgoing: HashMap<Index, Vec<Index>> = HashMap::new();
+    for &(from, to) in &boundary {
+        outgoing.entry(from).or_insert_with(Vec::new).push(to);
+    }
+
+    let mut used: HashSet<(Index, Index)> = HashSet::new();
+    let mut loops = Vec::new();
+    for &(first, second) in &boundary {
+        if used.contains(&(first, second)) {
+            continue;
+        }
+        used.insert((first, second));
+
+        let mut current_loop = vec![first];
+        let (mut previous, mut current) = (first, second);
+        while current != first {
+            current_loop.push(current);
+            let next = match next_edge(vertices, &outgoing, &used, previous, current) {
+                Some(next) => next,
+                None => { break; }
+            };
+            used.insert((current, next));
+            previous = current;
+            current = next;
+        }
+
+        if current_loop.len() >= 3 {
+            loops.push(current_loop);
+        }
+    }
+
+    return loops;
+}
+
+// At a vertex where several loops meet, takes the sharpest turn to the right so that the
+// loops don't cross each other.
+fn next_edge(
+    vertices: &[Point],
+    outgoing: &HashMap<Index, Vec<Index>>,
+    used: &HashSet<(Index, Index)>,
+    previous: Index,
+    current: Index,
+) -> Option<Index> {
+    let candidates = match outgoing.get(&current) {
+        Some(candidates) => candidates,
+        None => { return None; }
+    };
+
+    let position = vertices[current as usize];
+    let back = vertices[previous as usize] - position;
+    let mut best = None;
+    let mut best_angle = 0.0;
+    for &next in candidates {
+        if used.contains(&(current, next)) {
+            continue;
+        }
+        let d = vertices[next as usize] - position;
+        // The clockwise angle from the incoming edge to the candidate.
+        let mut angle = d.cross(back).atan2(d.dot(back));
+        if angle <= 0.0 {
+            angle += 2.0 * PI;
+        }
+        if best.is_none() || angle < best_angle {
+            best = Some(next);
+            best_angle = angle;
+        }
+    }
+
+    return best;
+}
+
+fn signed_area(polygon: &[Point]) -> f32 {
+    let n = polygon.len();
+    let mut area = 0.0;
+    for i in 0..n {
+        area += polygon[i].to_vector().cross(polygon[(i + 1) % n].to_vector());
+    }
+
+    return area * 0.5;
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_iterator::PathIterator;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+
+#[test]
+fn test_contours_with_hole() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    builder.move_to(point(4.0, 4.0));
+    builder.line_to(point(6.0, 4.0));
+    builder.line_to(point(6.0, 6.0));
+    builder.line_to(point(4.0, 6.0));
+    builder.close();
+    let path = builder.build();
+
+    let contours = fill_contours(path.path_iter().flattened(0.05), &FillOptions::even_odd()).unwrap();
+    assert_eq!(contours.len(), 2);
+
+    let outer = contours.iter().find(|c| !c.is_hole).unwrap();
+    let hole = contours.iter().find(|c| c.is_hole).unwrap();
+    assert_eq!(outer.points.len(), 4);
+    assert_eq!(hole.points.len(), 4);
+    assert_eq!(signed_area(&outer.points), 100.0);
+    assert_eq!(signed_area(&hole.points), -4.0);
+    for p in &hole.points {
+        assert!(p.x == 4.0 || p.x == 6.0);
+        assert!(p.y == 4.0 || p.y == 6.0);
+    }
+}
+
+#[test]
+fn test_contours_self_intersection() {
+    // A bow tie, the two triangles touch at the intersection.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(2.0, 2.0));
+    builder.line_to(point(2.0, 0.0));
+    builder.line_to(point(0.0, 2.0));
+    builder.close();
+    let path = builder.build();
+
+    let contours = fill_contours(path.path_iter().flattened(0.05), &FillOptions::even_odd()).unwrap();
+    assert_eq!(contours.len(), 2);
+    for contour in &contours {
+        assert!(!contour.is_hole);
+        assert_eq!(contour.points.len(), 3);
+        assert!((signed_area(&contour.points) - 1.0).abs() < 0.01);
+        assert!(contour.points.iter().any(|p| (*p - point(1.0, 1.0)).length() < 0.01));
+    }
+}
+
+#[test]
+fn test_boundary_loops_touching() {
+    // Two squares sharing a corner, the loops must not be merged.
+    let vertices = [
+        point(0.0, 0.0), point(1.0, 0.0), point(1.0, 1.0), point(0.0, 1.0),
+        point(2.0, 1.0), point(2.0, 2.0), point(1.0, 2.0),
+    ];
+    let indices = [0, 1, 2, 0, 2, 3, 2, 4, 5, 2, 5, 6];
+    let loops = boundary_loops(&vertices, &indices);
+    assert_eq!(loops.len(), 2);
+    for boundary in &loops {
+        assert_eq!(boundary.len(), 4);
+    }
+}
@@ -0,0 +1,266 @@
+//! Decomposition of fill regions into convex polygons.
+//!
+//! Physics engines usually require collision shapes to be convex, and often limit the number
+//! of vertices of each shape (8 in Box2D). Rather than a triangle soup, this module produces a
+//! small set of convex polygons covering the fill of a path.
+//!
+//! The decomposition uses the Hertel-Mehlhorn algorithm: starting from a triangulation, the
+//! edges shared by two polygons are removed one after the other as long as the union of the
+//! two polygons is convex (and small enough). The result has at most four times as many
+//! polygons as the optimal decomposition.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::convex_decomposition::*;
+//! # struct World;
+//! # impl World { fn add_fixture(&mut self, _body: u32, _polygon: &[Point]) {} }
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let mut world = World;
+//! # let body = 0;
+//! let polygons = fill_convex_polygons(
+//!     path.path_iter().flattened(0.05),
+//!     &FillOptions::default(),
+//!     8,
+//! ).unwrap();
+//!
+//! for polygon in &polygons {
+//!     world.add_fixture(body, &polygon[..]);
+//! }
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use math::*;
+use core::FlattenedEvent;
+use geometry_builder::{VertexBuffers, Index, simple_builder};
+use path_fill::{FillTessellator, FillOptions, FillError};
+use FillVertex as Vertex;
+
+/// Merges the triangles of a triangulation into convex polygons of at most `max_vertices`
+/// vertices.
+///
+/// Each group of three indices in `indices` is a triangle of the vertices in `positions`.
+/// The triangles must not overlap, and neighbor triangles must share the indices of the
+/// vertices of their common edge. Triangles that have no area are ignored.
+///
+/// The polygons are returned as lists of vertex indices, counter-clockwise (with the y axis
+/// pointing up).
+pub fn convex_decomposition(positions: &[Point], indices: &[Index], max_vertices: usize) -> Vec<Vec<Index>> {
+    let mut polygons: Vec<Option<Vec<Index>>> = Vec::with_capacity(indices.len() / 3);
+    // The polygon on the left side of each directed edge.
+    let mut owners: HashMap<(Index, Index), usize> = HashMap::new();
+
+    for triangle in indices.chunks(3) {
+        if triangle.len() < 3 {
+            break;
+        }
+        let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+        let area = orientation(positions[a as usize], positions[b as usize], positions[c as usize]);
+        let vertices = if area > 0.0 {
+            vec![a, b, c]
+        } else if area < 0.0 {
+            vec![a, c, b]
+        } else {
+            continue;
+        };
+        for i in 0..3 {
+            owners.insert((vertices[i], vertices[(i + 1) % 3]), polygons.len());
+        }
+        polygons.push(Some(vertices));
+    }
+
+    // The diagonals, in the order of the triangles so that the result doesn't depend on the
+    // iteration order of the hash map.
+    let mut diagonals = Vec::new();
+    for polygon in &polygons {
+        let polygon = polygon.as_ref().unwrap();
+        for i in 0..3 {
+            let (a, b) = (polygon[i], polygon[(i + 1) % 3]);
+            if a < b && owners.contains_key(&(b, a)) {
+                diagonals.push((a, b));
+            }
+        }
+    }
+
+    for (a, b) in diagonals {
+        let (p1, p2) = match (owners.get(&(a, b)), owners.get(&(b, a))) {
+            (Some(&p1), Some(&p2)) if p1 != p2 => (p1, p2),
+            _ => { continue; }
+        };
+
+        let merged = {
+            let first = polygons[p1].as_ref().unwrap();
+            let second = polygons[p2].as_ref().unwrap();
+            if first.len() + second.len() - 2 > max_vertices {
+                continue;
+            }
+
+            // The first polygon from b to a, followed by the second one between a and b.
+            let i = first.iter().position(|&v| v == b).unwrap();
+            let j = second.iter().position(|&v| v == a).unwrap();
+            let mut merged = Vec::with_capacity(first.len() + second.len() - 2);
+            for k in 0..first.len() {
+                merged.push(first[(i + k) % first.len()]);
+            }
+            for k in 1..(second.len() - 1) {
+                merged.push(second[(j + k) % second.len()]);
+            }
+
+            // Only the angles at a and b change.
+            let convex = {
+                let n = merged.len();
+                let convex_at = |idx: usize| {
+                    orientation(
+                        positions[merged[(idx + n - 1) % n] as usize],
+                        positions[merged[idx] as usize],
+                        positions[merged[(idx + 1) % n] as usize],
+                    ) >= 0.0
+                };
+                convex_at(0) && convex_at(first.len() - 1)
+            };
+            if !convex {
+                continue;
+            }
+
+            merged
+        };
+
+        owners.remove(&(a, b));
+        owners.remove(&(b, a));
+        {
+            let second = polygons[p2].as_ref().unwrap();
+            for k in 0..second.len() {
+                let edge = (second[k], second[(k + 1) % second.len()]);
+                if edge != (b, a) {
+                    owners.insert(edge, p1);
+                }
+            }
+        }
+        polygons[p1] = Some(merged);
+        polygons[p2] = None;
+    }
+
+    return polygons.into_iter().filter_map(|polygon| polygon).collect();
+}
+
+/// Tessellates a path with the fill tessellator and merges the triangles into convex polygons
+/// of at most `max_vertices` vertices.
+///
+/// See [convex_decomposition](fn.convex_decomposition.html).
+pub fn fill_convex_polygons<Iter>(
+    path: Iter,
+    options: &FillOptions,
+    max_vertices: usize,
+) -> Result<Vec<Vec<Point>>, FillError>
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    try!{ FillTessellator::new().tessellate_path(path, options, &mut simple_builder(&mut buffers)) };
+
+    let positions: Vec<Point> = buffers.vertices.iter().map(|v| v.position).collect();
+    let polygons = convex_decomposition(&positions, &buffers.indices, max_vertices);
+
+    return Ok(polygons.iter().map(|polygon| {
+        polygon.iter().map(|&idx| positions[idx as usize]).collect()
+    }).collect());
+}
+
+fn orientation(a: Point, b: Point, c: Point) -> f32 { (b - a).cross(c - b) }
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[cfg(test)]
+fn check_convex_polygons(polygons: &[Vec<Point>], max_vertices: usize) -> f32 {
+    let mut area = 0.0;
+    for polygon in polygons {
+        let n = polygon.len();
+        assert!(n >= 3 && n <= max_vertices);
+        for i in 0..n {
+            assert!(orientation(polygon[i], polygon[(i + 1) % n], polygon[(i + 2) % n]) >= 0.0);
+            area += (polygon[i].x * polygon[(i + 1) % n].y - polygon[(i + 1) % n].x * polygon[i].y) * 0.5;
+        }
+    }
+
+    return area;
+}
+
+#[cfg(test)]
+fn polygon_path(points: &[Point]) -> Path {
+    let mut builder = Path::builder();
+    builder.move_to(points[0]);
+    for p in &points[1..] {
+        builder.line_to(*p);
+    }
+    builder.close();
+
+    return builder.build();
+}
+
+#[test]
+fn test_convex_decomposition() {
+    let square = polygon_path(&[point(0.0, 0.0), point(1.0, 0.0), point(1.0, 1.0), point(0.0, 1.0)]);
+    let polygons = fill_convex_polygons(square.path_iter().flattened(0.1), &FillOptions::default(), 8).unwrap();
+    assert_eq!(polygons.len(), 1);
+    assert_eq!(check_convex_polygons(&polygons, 8), 1.0);
+
+    // An L shape has one reflex vertex, so it needs two or three polygons.
+    let l_shape = polygon_path(&[
+        point(0.0, 0.0),
+        point(2.0, 0.0),
+        point(2.0, 1.0),
+        point(1.0, 1.0),
+        point(1.0, 2.0),
+        point(0.0, 2.0),
+    ]);
+    let polygons = fill_convex_polygons(l_shape.path_iter().flattened(0.1), &FillOptions::default(), 8).unwrap();
+    assert!(polygons.len() >= 2 && polygons.len() <= 3);
+    assert_eq!(check_convex_polygons(&polygons, 8), 3.0);
+}
+
+#[test]
+fn test_convex_decomposition_max_vertices() {
+    let octagon = polygon_path(&[
+        point(1.0, 0.0),
+        point(2.0, 0.0),
+        point(3.0, 1.0),
+        point(3.0, 2.0),
+        point(2.0, 3.0),
+        point(1.0, 3.0),
+        point(0.0, 2.0),
+        point(0.0, 1.0),
+    ]);
+
+    let polygons = fill_convex_polygons(octagon.path_iter().flattened(0.1), &FillOptions::default(), 8).unwrap();
+    assert_eq!(polygons.len(), 1);
+    assert_eq!(check_convex_polygons(&polygons, 8), 7.0);
+
+    let polygons = fill_convex_polygons(octagon.path_iter().flattened(0.1), &FillOptions::default(), 4).unwrap();
+    assert!(polygons.len() > 1);
+    assert_eq!(check_convex_polygons(&polygons, 4), 7.0);
+}
@@ -0,0 +1,201 @@
+//! Fill tessellation with analytic anti-aliasing attributes.
+//!
+//! Rather than extruding a fringe of triangles around the shape or relying on multisampling,
+//! the renderer can compute the coverage of each pixel from its distance to the boundary of
+//! the fill. This module produces triangles that carry, at each vertex, the distance to each
+//! of the three edges of their triangle that are on the boundary of the fill. These distances
+//! are linear over the triangle, so after interpolation the fragment shader has the exact
+//! distance of the pixel center to the boundary edges of its triangle, and
+//! [coverage](fn.coverage.html) turns them into a weight.
+//!
+//! The vertices can't be shared between triangles since their attributes depend on the
+//! triangle. The geometry isn't extruded either, so the pixels along the boundary are at most
+//! half covered.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::coverage::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! let mesh = fill_with_coverage(path.path_iter().flattened(0.1), &FillOptions::default()).unwrap();
+//! // In the fragment shader:
+//! // alpha *= coverage(v_edge_distances, pixel_size);
+//! # let _ = mesh;
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use math::*;
+use core::FlattenedEvent;
+use geometry_builder::{VertexBuffers, BuffersBuilder, TypedPositions, Index};
+use path_fill::{FillTessellator, FillOptions, FillError};
+
+/// The distance used for the edges of a triangle that are inside of the fill.
+///
+/// It is large enough not to affect the coverage while keeping the interpolation precise.
+pub const INTERIOR_EDGE_DISTANCE: f32 = 1.0e6;
+
+/// Vertex produced by [fill_with_coverage](fn.fill_with_coverage.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CoverageVertex {
+    pub position: Point,
+    /// The distance from the vertex to the line of each edge of its triangle, the edge `i`
+    /// going from the vertex `i` to the vertex `i + 1` of the triangle.
+    ///
+    /// Edges that are not on the boundary of the fill have `INTERIOR_EDGE_DISTANCE`.
+    pub edge_distances: [f32; 3],
+}
+
+/// Fill-tessellates a path into triangles with edge distances for analytic anti-aliasing.
+pub fn fill_with_coverage<Iter>(path: Iter, options: &FillOptions) -> Result<VertexBuffers<CoverageVertex>, FillError>
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let mut fill: VertexBuffers<Point> = VertexBuffers::new();
+    try!{
+        FillTessellator::new().tessellate_path(
+            path,
+            options,
+            &mut BuffersBuilder::new(&mut fill, TypedPositions::new()),
+        )
+    };
+
+    return Ok(coverage_mesh(&fill.vertices, &fill.indices));
+}
+
+/// Computes the edge distances of a triangle mesh, such as the output of the fill
+/// tessellator.
+///
+/// The boundary of the mesh is made of the edges that belong to a single triangle.
+/// Triangles that have no area are removed.
+pub fn coverage_mesh(vertices: &[Point], indices: &[Index]) -> VertexBuffers<CoverageVertex> {
+    let edge_key = |a: Index, b: Index| if a < b { (a, b) } else { (b, a) };
+
+    let mut edges: HashMap<(Index, Index), u32> = HashMap::new();
+    for triangle in indices.chunks(3) {
+        for i in 0..3 {
+            *edges.entry(edge_key(triangle[i], triangle[(i + 1) % 3])).or_insert(0) += 1;
+        }
+    }
+
+    let mut output: VertexBuffers<CoverageVertex> = VertexBuffers::with_capacity(indices.len(), indices.len());
+    for triangle in indices.chunks(3) {
+        let p = [
+            vertices[triangle[0] as usize],
+            vertices[triangle[1] as usize],
+            vertices[triangle[2] as usize],
+        ];
+        let double_area = (p[1] - p[0]).cross(p[2] - p[0]).abs();
+        if double_area == 0.0 {
+            continue;
+        }
+
+        let mut distances = [[INTERIOR_EDGE_DISTANCE; 3]; 3];
+        for i in 0..3 {
+            if edges[&edge_key(triangle[i], triangle[(i + 1) % 3])] != 1 {
+                continue;
+            }
+            // Only the opposite vertex is away from the edge, at the height of the triangle.
+            let length = (p[(i + 1) % 3] - p[i]).length();
+            for v in 0..3 {
+                distances[v][i] = 0.0;
+            }
+            distances[(i + 2) % 3][i] = double_area / length;
+        }
+
+        let first = output.vertices.len() as Index;
+        for v in 0..3 {
+            output.vertices.push(CoverageVertex { position: p[v], edge_distances: distances[v] });
+            output.indices.push(first + v as Index);
+        }
+    }
+
+    return output;
+}
+
+/// The coverage of a pixel, given the interpolated edge distances at its center.
+///
+/// This is the computation the fragment shader is expected to do: each boundary edge covers
+/// the pixel linearly over the width of a pixel centered on the edge.
+pub fn coverage(edge_distances: [f32; 3], pixel_size: f32) -> f32 {
+    let mut coverage = 1.0;
+    for &d in &edge_distances {
+        coverage *= (d / pixel_size + 0.5).max(0.0).min(1.0);
+    }
+
+    return coverage;
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[test]
+fn test_fill_with_coverage() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    let mesh = fill_with_coverage(path.path_iter().flattened(0.1), &FillOptions::default()).unwrap();
+    assert_eq!(mesh.vertices.len(), 6);
+    assert_eq!(mesh.indices.len(), 6);
+
+    let mut boundary_edges = 0;
+    for triangle in mesh.vertices.chunks(3) {
+        for i in 0..3 {
+            let distances = [
+                triangle[0].edge_distances[i],
+                triangle[1].edge_distances[i],
+                triangle[2].edge_distances[i],
+            ];
+            if distances[0] == INTERIOR_EDGE_DISTANCE {
+                assert_eq!(distances, [INTERIOR_EDGE_DISTANCE; 3]);
+                continue;
+            }
+            boundary_edges += 1;
+            assert_eq!(distances[i], 0.0);
+            assert_eq!(distances[(i + 1) % 3], 0.0);
+            assert_eq!(distances[(i + 2) % 3], 10.0);
+        }
+
+        // The centroid is far from the boundary.
+        let mut center = [0.0; 3];
+        for v in triangle {
+            for i in 0..3 {
+                center[i] += v.edge_distances[i] / 3.0;
+            }
+        }
+        assert_eq!(coverage(center, 1.0), 1.0);
+    }
+    assert_eq!(boundary_edges, 4);
+
+    assert_eq!(coverage([0.0, 5.0, INTERIOR_EDGE_DISTANCE], 1.0), 0.5);
+    assert_eq!(coverage([0.25, 0.25, INTERIOR_EDGE_DISTANCE], 1.0), 0.5625);
+    assert_eq!(coverage([0.0, 0.0, 0.0], 2.0), 0.125);
+}
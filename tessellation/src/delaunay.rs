@@ -0,0 +1,790 @@
+//! Improving the shape of the triangles of a tessellation.
+//!
+//! The sweep line of the fill tessellator produces monotone polygons which are triangulated
+//! with fans of long and thin triangles. This is fine for flat color fills but looks bad when
+//! the vertices carry data that is interpolated across the triangles (lighting, elevation,
+//! gradients).
+//!
+//! [make_delaunay](fn.make_delaunay.html) turns a triangulation into a constrained Delaunay
+//! triangulation by flipping the edges shared by two triangles until no vertex is inside of
+//! the circumcircle of a neighbor triangle. Flipping the diagonal of a convex quadrilateral
+//! doesn't change the area covered by the two triangles, so the outline of the shape (the
+//! edges that belong to a single triangle) is preserved.
+//!
+//! The fill tessellator runs this pass itself with `FillOptions::with_delaunay`.
+//!
+//! [refine](fn.refine.html) goes further and inserts vertices (Ruppert's algorithm) until
+//! the triangles meet quality bounds on their area and angles, which is what meshes used
+//! for simulations or smooth interpolation need. See `FillOptions::with_refinement`.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::delaunay::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+//! # FillTessellator::new().tessellate_path(
+//! #     path.path_iter().flattened(0.05),
+//! #     &FillOptions::default(),
+//! #     &mut BuffersBuilder::new(&mut buffers, TypedPositions::new()),
+//! # ).unwrap();
+//! # let positions = buffers.vertices.clone();
+//! make_delaunay(&positions, &mut buffers.indices);
+//! # }
+//! ```
+
+use std::collections::{BinaryHeap, HashMap};
+use std::f32::consts::PI;
+use std::usize;
+
+use math::*;
+use geometry_builder::Index;
+
+const NONE: usize = usize::MAX;
+
+/// Flips the interior edges of a triangle list until it satisfies the Delaunay criterion.
+///
+/// Each group of three indices in `indices` is a triangle of the vertices in `positions`.
+/// The triangles keep their orientation. Edges that are shared by more than two triangles
+/// are never flipped.
+///
+/// Returns the number of flipped edges.
+pub fn make_delaunay(positions: &[Point], indices: &mut [Index]) -> usize {
+    let mut mesh = Mesh::new(indices);
+    let stack = mesh.interior_edges();
+
+    return mesh.flip_edges(positions, indices, stack, &mut Vec::new());
+}
+
+// The two triangles on each side of the edges of a triangle list.
+struct Mesh {
+    // Edges that belong to a single triangle have NONE as second triangle, and non-manifold
+    // edges (shared by more than two triangles) are (NONE, NONE).
+    edges: HashMap<(Index, Index), (usize, usize)>,
+}
+
+impl Mesh {
+    fn new(indices: &[Index]) -> Self {
+        let mut mesh = Mesh { edges: HashMap::new() };
+        for t in 0..indices.len() / 3 {
+            mesh.add_triangle(indices, t);
+        }
+
+        return mesh;
+    }
+
+    fn add_triangle(&mut self, indices: &[Index], t: usize) {
+        for i in 0..3 {
+            let key = edge_key(indices[t * 3 + i], indices[t * 3 + (i + 1) % 3]);
+            let entry = self.edges.entry(key).or_insert((t, NONE));
+            if entry.0 == t || entry.0 == NONE {
+                continue;
+            }
+            if entry.1 == NONE {
+                entry.1 = t;
+            } else {
+                *entry = (NONE, NONE);
+            }
+        }
+    }
+
+    fn remove_triangle(&mut self, indices: &[Index], t: usize) {
+        for i in 0..3 {
+            let key = edge_key(indices[t * 3 + i], indices[t * 3 + (i + 1) % 3]);
+            let empty = match self.edges.get_mut(&key) {
+                Some(entry) => {
+                    if entry.0 == t {
+                        entry.0 = entry.1;
+                        entry.1 = NONE;
+                    } else if entry.1 == t {
+                        entry.1 = NONE;
+                    }
+                    entry.0 == NONE && entry.1 == NONE
+                }
+                None => false,
+            };
+            if empty {
+                self.edges.remove(&key);
+            }
+        }
+    }
+
+    // The edges shared by two triangles, sorted because the iteration order of hash maps
+    // changes from a run to another.
+    fn interior_edges(&self) -> Vec<(Index, Index)> {
+        let mut edges: Vec<(Index, Index)> = self.edges
+            .iter()
+            .filter(|&(_, &(t1, t2))| t1 != NONE && t2 != NONE)
+            .map(|(&key, _)| key)
+            .collect();
+        edges.sort();
+
+        return edges;
+    }
+
+    fn is_boundary(&self, edge: (Index, Index)) -> bool {
+        match self.edges.get(&edge) {
+            Some(&(t1, t2)) => t1 != NONE && t2 == NONE,
+            None => false,
+        }
+    }
+
+    // The triangle on the other side of an edge of `t`.
+    fn neighbor(&self, edge: (Index, Index), t: usize) -> Option<usize> {
+        match self.edges.get(&edge) {
+            Some(&(t1, t2)) if t1 == t && t2 != NONE => Some(t2),
+            Some(&(t1, t2)) if t2 == t && t1 != NONE => Some(t1),
+            _ => None,
+        }
+    }
+
+    // Flips the edges of the stack, and the edges around the flipped ones, that don't
+    // satisfy the Delaunay criterion. The flipped triangles are added to `changed`.
+    fn flip_edges(
+        &mut self,
+        positions: &[Point],
+        indices: &mut [Index],
+        mut stack: Vec<(Index, Index)>,
+        changed: &mut Vec<usize>,
+    ) -> usize {
+        // Flipping terminates in exact arithmetic, this guards against rounding errors making
+        // a pair of triangles flip back and forth.
+        let mut remaining_iterations = (stack.len() + 1) * 32;
+        let mut num_flips = 0;
+
+        while let Some(edge) = stack.pop() {
+            if remaining_iterations == 0 {
+                break;
+            }
+            remaining_iterations -= 1;
+
+            let (t1, t2) = match self.edges.get(&edge) {
+                Some(&(t1, t2)) if t1 != NONE && t2 != NONE => (t1, t2),
+                _ => { continue; }
+            };
+
+            let (a, b) = edge;
+            let c = opposite_vertex(&indices[t1 * 3..t1 * 3 + 3], a, b);
+            let d = opposite_vertex(&indices[t2 * 3..t2 * 3 + 3], a, b);
+            let (pa, pb) = (positions[a as usize], positions[b as usize]);
+            let (pc, pd) = (positions[c as usize], positions[d as usize]);
+
+            // The quadrilateral must be strictly convex for the flip to be valid.
+            let convex = orient(pc, pd, pa) * orient(pc, pd, pb) < 0.0 &&
+                orient(pa, pb, pc) * orient(pa, pb, pd) < 0.0;
+            if !convex || !in_circumcircle(pa, pb, pc, pd) {
+                continue;
+            }
+
+            let clockwise = orient(
+                positions[indices[t1 * 3] as usize],
+                positions[indices[t1 * 3 + 1] as usize],
+                positions[indices[t1 * 3 + 2] as usize],
+            ) < 0.0;
+
+            set_triangle(positions, indices, t1, [c, a, d], clockwise);
+            set_triangle(positions, indices, t2, [d, b, c], clockwise);
+
+            self.edges.remove(&edge);
+            self.edges.insert(edge_key(c, d), (t1, t2));
+            // (a, d) moved from t2 to t1 and (b, c) from t1 to t2.
+            replace_triangle(&mut self.edges, edge_key(a, d), t2, t1);
+            replace_triangle(&mut self.edges, edge_key(b, c), t1, t2);
+
+            stack.push(edge_key(a, c));
+            stack.push(edge_key(a, d));
+            stack.push(edge_key(b, c));
+            stack.push(edge_key(b, d));
+
+            changed.push(t1);
+            changed.push(t2);
+            num_flips += 1;
+        }
+
+        return num_flips;
+    }
+}
+
+/// Quality bounds for [refine](fn.refine.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RefineOptions {
+    /// Triangles with a larger area are subdivided.
+    pub max_area: f32,
+
+    /// Triangles with a smaller angle (in radians) are subdivided.
+    ///
+    /// The refinement is only guaranteed to terminate for angles up to about 20 degrees,
+    /// larger values are clamped to 30 degrees.
+    pub min_angle: f32,
+
+    /// The maximum number of vertices the refinement can add. Small angles of the outline
+    /// can't be improved and make the refinement add vertices until it reaches this limit.
+    pub max_vertices: u32,
+}
+
+impl RefineOptions {
+    pub fn new(max_area: f32, min_angle: f32) -> Self {
+        RefineOptions {
+            max_area: max_area,
+            min_angle: min_angle,
+            max_vertices: 4096,
+        }
+    }
+
+    pub fn with_max_vertices(mut self, max_vertices: u32) -> Self {
+        self.max_vertices = max_vertices;
+        return self;
+    }
+}
+
+/// Inserts vertices into a triangulation until its triangles satisfy the quality bounds.
+///
+/// The triangulation is first made Delaunay. The bad triangles (too large or with a too
+/// small angle) are then split, the largest first, by inserting their circumcenter, or the
+/// middle of an edge of the outline if the circumcenter is too close to it, and flipping the
+/// edges around the new vertex to restore the Delaunay property. The outline of the shape is
+/// preserved.
+///
+/// `add_vertex` is called with the position of each new vertex and returns its index.
+/// `positions` is indexed by the vertex indices and is extended as needed.
+///
+/// Returns the number of added vertices.
+pub fn refine(
+    positions: &mut Vec<Point>,
+    indices: &mut Vec<Index>,
+    options: &RefineOptions,
+    add_vertex: &mut FnMut(Point) -> Index,
+) -> u32 {
+    let min_angle = options.min_angle.max(0.0).min(PI / 6.0);
+    // Comparing cosines rather than angles avoids `acos`, which isn't computed the same way
+    // on all platforms.
+    let max_cosine = cos_series(min_angle);
+    let mut added = 0;
+
+    let mut mesh = Mesh::new(indices);
+    let stack = mesh.interior_edges();
+    mesh.flip_edges(positions, indices, stack, &mut Vec::new());
+
+    // The bad triangles, largest first. Triangles are modified in place so an entry is
+    // ignored if the area of its triangle changed since it was added.
+    let mut queue = BinaryHeap::new();
+    for t in 0..indices.len() / 3 {
+        if let Some(key) = bad_triangle_key(positions, indices, t, options.max_area, max_cosine) {
+            queue.push((key, t));
+        }
+    }
+
+    while added < options.max_vertices && positions.len() < Index::max_value() as usize {
+        let bad = match queue.pop() {
+            Some((key, t)) => {
+                if bad_triangle_key(positions, indices, t, options.max_area, max_cosine) != Some(key) {
+                    continue;
+                }
+                t
+            }
+            None => { break; }
+        };
+
+        let (a, b, c) = triangle_positions(positions, indices, bad);
+        let center = circumcenter(a, b, c);
+
+        let split = match locate(&mesh, positions, indices, bad, center) {
+            Location::Inside(t) => {
+                match encroached_edge(&mesh, positions, indices, t, center) {
+                    Some(edge) => Split::Edge(edge, None),
+                    None => Split::Triangle(t),
+                }
+            }
+            Location::OnEdge(t, edge) => {
+                match encroached_edge(&mesh, positions, indices, t, center) {
+                    Some(edge) => Split::Edge(edge, None),
+                    None => Split::Edge(edge, Some(center)),
+                }
+            }
+            Location::Outside(edge) => Split::Edge(edge, None),
+            Location::Unknown => Split::Edge(longest_edge(positions, indices, bad), None),
+        };
+
+        let mut stack = Vec::new();
+        let mut changed = Vec::new();
+        match split {
+            Split::Edge((ea, eb), position) => {
+                let edge = edge_key(ea, eb);
+                if mesh.edges.get(&edge).map_or(true, |&(t1, _)| t1 == NONE) {
+                    // Non-manifold edges can't be split.
+                    continue;
+                }
+                let position = position.unwrap_or(positions[ea as usize].lerp(positions[eb as usize], 0.5));
+                let id = new_vertex(positions, position, add_vertex);
+                split_edge(&mut mesh, indices, edge, id, &mut stack, &mut changed);
+            }
+            Split::Triangle(t) => {
+                let id = new_vertex(positions, center, add_vertex);
+                split_triangle(&mut mesh, indices, t, id, &mut stack, &mut changed);
+            }
+        }
+        added += 1;
+
+        mesh.flip_edges(positions, indices, stack, &mut changed);
+
+        changed.sort();
+        changed.dedup();
+        for t in changed {
+            if let Some(key) = bad_triangle_key(positions, indices, t, options.max_area, max_cosine) {
+                queue.push((key, t));
+            }
+        }
+    }
+
+    return added;
+}
+
+enum Split {
+    // At the given position on the edge, or in its middle.
+    Edge((Index, Index), Option<Point>),
+    Triangle(usize),
+}
+
+enum Location {
+    // Strictly inside of a triangle.
+    Inside(usize),
+    // On an edge of a triangle.
+    OnEdge(usize, (Index, Index)),
+    // Outside of the shape, behind an edge of the outline.
+    Outside((Index, Index)),
+    // On a vertex, or the walk failed.
+    Unknown,
+}
+
+fn new_vertex(positions: &mut Vec<Point>, position: Point, add_vertex: &mut FnMut(Point) -> Index) -> Index {
+    let id = add_vertex(position);
+    if id as usize >= positions.len() {
+        positions.resize(id as usize + 1, point(0.0, 0.0));
+    }
+    positions[id as usize] = position;
+
+    return id;
+}
+
+fn triangle_positions(positions: &[Point], indices: &[Index], t: usize) -> (Point, Point, Point) {
+    (
+        positions[indices[t * 3] as usize],
+        positions[indices[t * 3 + 1] as usize],
+        positions[indices[t * 3 + 2] as usize],
+    )
+}
+
+// The priority of a bad triangle in the refinement queue, None if the triangle is good.
+fn bad_triangle_key(positions: &[Point], indices: &[Index], t: usize, max_area: f32, max_cosine: f32) -> Option<u32> {
+    let (a, b, c) = triangle_positions(positions, indices, t);
+    let area = (orient(a, b, c) * 0.5).abs() as f32;
+    if area == 0.0 || !(area > max_area || largest_cosine(a, b, c) > max_cosine) {
+        return None;
+    }
+
+    // The bits of positive floats are ordered like the floats.
+    return Some(area.to_bits());
+}
+
+// The cosine of the smallest angle of a triangle.
+fn largest_cosine(a: Point, b: Point, c: Point) -> f32 {
+    let cosine = |p: Point, q: Point, r: Point| {
+        (q - p).normalize().dot((r - p).normalize())
+    };
+
+    cosine(a, b, c).max(cosine(b, c, a)).max(cosine(c, a, b))
+}
+
+// Taylor series of the cosine, which only uses correctly rounded operations and is precise
+// for angles up to PI / 2.
+fn cos_series(angle: f32) -> f32 {
+    let x2 = angle as f64 * angle as f64;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for i in 1..9 {
+        term *= -x2 / ((2 * i - 1) * (2 * i)) as f64;
+        sum += term;
+    }
+
+    return sum as f32;
+}
+
+#[cfg(test)]
+fn smallest_angle(a: Point, b: Point, c: Point) -> f32 {
+    let angle = |p: Point, q: Point, r: Point| {
+        let (u, v) = ((q - p).normalize(), (r - p).normalize());
+        u.dot(v).max(-1.0).min(1.0).acos()
+    };
+
+    angle(a, b, c).min(angle(b, c, a)).min(angle(c, a, b))
+}
+
+fn circumcenter(a: Point, b: Point, c: Point) -> Point {
+    let (bx, by) = (b.x as f64 - a.x as f64, b.y as f64 - a.y as f64);
+    let (cx, cy) = (c.x as f64 - a.x as f64, c.y as f64 - a.y as f64);
+    let d = 2.0 * (bx * cy - by * cx);
+    let b2 = bx * bx + by * by;
+    let c2 = cx * cx + cy * cy;
+    let x = (cy * b2 - by * c2) / d;
+    let y = (bx * c2 - cx * b2) / d;
+
+    point(a.x + x as f32, a.y + y as f32)
+}
+
+// Walks from the triangle `start` towards the position, crossing the edges that separate
+// them.
+fn locate(mesh: &Mesh, positions: &[Point], indices: &[Index], start: usize, position: Point) -> Location {
+    let mut t = start;
+    // The walk terminates in a Delaunay triangulation, this guards against rounding errors.
+    'walk: for _ in 0..indices.len() / 3 {
+        let (a, b, c) = triangle_positions(positions, indices, t);
+        let sign = orient(a, b, c);
+        if sign == 0.0 {
+            return Location::Unknown;
+        }
+        let mut on_edges = Vec::new();
+        for i in 0..3 {
+            let (p, q) = (indices[t * 3 + i], indices[t * 3 + (i + 1) % 3]);
+            let side = orient(positions[p as usize], positions[q as usize], position) * sign;
+            if side < 0.0 {
+                let edge = edge_key(p, q);
+                match mesh.neighbor(edge, t) {
+                    Some(next) => {
+                        t = next;
+                        continue 'walk;
+                    }
+                    None if mesh.is_boundary(edge) => { return Location::Outside(edge); }
+                    None => { return Location::Unknown; }
+                }
+            }
+            if side == 0.0 {
+                on_edges.push(edge_key(p, q));
+            }
+        }
+
+        return match on_edges.len() {
+            0 => Location::Inside(t),
+            1 => Location::OnEdge(t, on_edges[0]),
+            _ => Location::Unknown,
+        };
+    }
+
+    return Location::Unknown;
+}
+
+// An edge of the outline that the position encroaches, if any.
+//
+// The encroached edges are on the outline of the triangles whose circumcircle contains the
+// position, so only the triangles around the one that contains it are visited.
+fn encroached_edge(
+    mesh: &Mesh,
+    positions: &[Point],
+    indices: &[Index],
+    t: usize,
+    position: Point,
+) -> Option<(Index, Index)> {
+    let mut visited = vec![t];
+    let mut i = 0;
+    while i < visited.len() {
+        let t = visited[i];
+        i += 1;
+        for j in 0..3 {
+            let edge = edge_key(indices[t * 3 + j], indices[t * 3 + (j + 1) % 3]);
+            if mesh.is_boundary(edge) {
+                let (pa, pb) = (positions[edge.0 as usize], positions[edge.1 as usize]);
+                let mid = pa.lerp(pb, 0.5);
+                if (position - mid).length() < (pb - pa).length() * 0.5 {
+                    return Some(edge);
+                }
+                continue;
+            }
+            if let Some(next) = mesh.neighbor(edge, t) {
+                let (a, b, c) = triangle_positions(positions, indices, next);
+                if !visited.contains(&next) && in_circumcircle(a, b, c, position) {
+                    visited.push(next);
+                }
+            }
+        }
+    }
+
+    return None;
+}
+
+fn longest_edge(positions: &[Point], indices: &[Index], t: usize) -> (Index, Index) {
+    let triangle = &indices[t * 3..t * 3 + 3];
+    let mut longest = (triangle[0], triangle[1]);
+    let mut longest_length = 0.0;
+    for i in 0..3 {
+        let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+        let length = (positions[b as usize] - positions[a as usize]).square_length();
+        if length > longest_length {
+            longest = (a, b);
+            longest_length = length;
+        }
+    }
+
+    return longest;
+}
+
+// Splits the triangles on both sides of an edge, the new vertex is on the edge so the
+// orientation of the triangles is preserved.
+fn split_edge(
+    mesh: &mut Mesh,
+    indices: &mut Vec<Index>,
+    edge: (Index, Index),
+    vertex: Index,
+    stack: &mut Vec<(Index, Index)>,
+    changed: &mut Vec<usize>,
+) {
+    let (t1, t2) = mesh.edges[&edge];
+    for &t in &[t1, t2] {
+        if t == NONE {
+            continue;
+        }
+        mesh.remove_triangle(indices, t);
+        for i in 0..3 {
+            let p = indices[t * 3 + i];
+            let q = indices[t * 3 + (i + 1) % 3];
+            let r = indices[t * 3 + (i + 2) % 3];
+            if edge_key(p, q) == edge {
+                let new_triangle = indices.len() / 3;
+                indices[t * 3 + (i + 1) % 3] = vertex;
+                indices.extend_from_slice(&[vertex, q, r]);
+                mesh.add_triangle(indices, t);
+                mesh.add_triangle(indices, new_triangle);
+                stack.push(edge_key(r, p));
+                stack.push(edge_key(q, r));
+                changed.push(t);
+                changed.push(new_triangle);
+                break;
+            }
+        }
+    }
+}
+
+fn split_triangle(
+    mesh: &mut Mesh,
+    indices: &mut Vec<Index>,
+    t: usize,
+    vertex: Index,
+    stack: &mut Vec<(Index, Index)>,
+    changed: &mut Vec<usize>,
+) {
+    let (p, q, r) = (indices[t * 3], indices[t * 3 + 1], indices[t * 3 + 2]);
+    let new_triangle = indices.len() / 3;
+    mesh.remove_triangle(indices, t);
+    indices[t * 3 + 2] = vertex;
+    indices.extend_from_slice(&[q, r, vertex, r, p, vertex]);
+    for &t in &[t, new_triangle, new_triangle + 1] {
+        mesh.add_triangle(indices, t);
+        changed.push(t);
+    }
+    stack.push(edge_key(p, q));
+    stack.push(edge_key(q, r));
+    stack.push(edge_key(r, p));
+}
+
+fn edge_key(a: Index, b: Index) -> (Index, Index) { if a < b { (a, b) } else { (b, a) } }
+
+fn opposite_vertex(triangle: &[Index], a: Index, b: Index) -> Index {
+    for &v in triangle {
+        if v != a && v != b {
+            return v;
+        }
+    }
+    return triangle[0];
+}
+
+fn replace_triangle(
+    edges: &mut HashMap<(Index, Index), (usize, usize)>,
+    key: (Index, Index),
+    from: usize,
+    to: usize,
+) {
+    if let Some(entry) = edges.get_mut(&key) {
+        if entry.0 == from {
+            entry.0 = to;
+        } else if entry.1 == from {
+            entry.1 = to;
+        }
+    }
+}
+
+fn set_triangle(
+    positions: &[Point],
+    indices: &mut [Index],
+    t: usize,
+    mut triangle: [Index; 3],
+    clockwise: bool,
+) {
+    let is_clockwise = orient(
+        positions[triangle[0] as usize],
+        positions[triangle[1] as usize],
+        positions[triangle[2] as usize],
+    ) < 0.0;
+    if is_clockwise != clockwise {
+        triangle.swap(1, 2);
+    }
+    indices[t * 3..t * 3 + 3].copy_from_slice(&triangle);
+}
+
+fn orient(a: Point, b: Point, c: Point) -> f64 {
+    let (ax, ay) = (a.x as f64, a.y as f64);
+    (b.x as f64 - ax) * (c.y as f64 - ay) - (b.y as f64 - ay) * (c.x as f64 - ax)
+}
+
+// Whether d is strictly inside of the circumcircle of the triangle (a, b, c).
+fn in_circumcircle(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let (dx, dy) = (d.x as f64, d.y as f64);
+    let row = |p: Point| {
+        let (x, y) = (p.x as f64 - dx, p.y as f64 - dy);
+        (x, y, x * x + y * y)
+    };
+    let (ax, ay, a2) = row(a);
+    let (bx, by, b2) = row(b);
+    let (cx, cy, c2) = row(c);
+
+    let det = ax * (by * c2 - b2 * cy) - ay * (bx * c2 - b2 * cx) + a2 * (bx * cy - by * cx);
+
+    // The sign of the determinant depends on the orientation of the triangle.
+    let det = if orient(a, b, c) < 0.0 { -det } else { det };
+
+    // Co-circular points are left alone so that rounding errors don't cause endless flips.
+    let scale = (a2 + b2 + c2) * (a2 + b2 + c2);
+    return det > scale * 1e-10;
+}
+
+#[cfg(test)]
+fn has_edge(indices: &[Index], a: Index, b: Index) -> bool {
+    indices.chunks(3).any(|t| t.contains(&a) && t.contains(&b))
+}
+
+#[test]
+fn test_flip_kite() {
+    // A thin kite split along its long diagonal.
+    let positions = [point(0.0, 0.0), point(4.0, -1.0), point(8.0, 0.0), point(4.0, 1.0)];
+    let mut indices = [0, 1, 2, 0, 2, 3];
+
+    assert_eq!(make_delaunay(&positions, &mut indices), 1);
+    assert!(has_edge(&indices, 1, 3));
+    assert!(!has_edge(&indices, 0, 2));
+    // The orientation is preserved.
+    for t in indices.chunks(3) {
+        let area = orient(positions[t[0] as usize], positions[t[1] as usize], positions[t[2] as usize]);
+        assert!(area > 0.0);
+    }
+
+    // Already Delaunay.
+    assert_eq!(make_delaunay(&positions, &mut indices), 0);
+}
+
+#[test]
+fn test_no_flip_concave() {
+    // The quadrilateral is not convex, flipping would create overlapping triangles.
+    let positions = [point(0.0, 0.0), point(4.0, -1.0), point(1.0, 0.0), point(4.0, 1.0)];
+    let mut indices = [0, 1, 2, 0, 2, 3];
+
+    assert_eq!(make_delaunay(&positions, &mut indices), 0);
+    assert_eq!(indices, [0, 1, 2, 0, 2, 3]);
+}
+
+#[cfg(test)]
+fn total_area(positions: &[Point], indices: &[Index]) -> f32 {
+    (0..indices.len() / 3).map(|t| {
+        let (a, b, c) = triangle_positions(positions, indices, t);
+        (orient(a, b, c) * 0.5).abs() as f32
+    }).sum()
+}
+
+#[test]
+fn test_refine_area() {
+    let mut positions = vec![point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)];
+    let mut indices = vec![0, 1, 2, 0, 2, 3];
+
+    let added = {
+        let mut count = positions.len() as Index;
+        let mut add_vertex = |_| { count += 1; count - 1 };
+        refine(&mut positions, &mut indices, &RefineOptions::new(5.0, 0.0), &mut add_vertex)
+    };
+
+    assert!(added > 0);
+    assert_eq!(positions.len(), 4 + added as usize);
+    assert!((total_area(&positions, &indices) - 100.0).abs() < 0.01);
+    for t in 0..indices.len() / 3 {
+        let (a, b, c) = triangle_positions(&positions, &indices, t);
+        assert!((orient(a, b, c) * 0.5).abs() <= 5.0);
+        // The orientation is preserved.
+        assert!(orient(a, b, c) > 0.0);
+    }
+}
+
+#[test]
+fn test_refine_angle() {
+    // A thin rectangle.
+    let mut positions = vec![point(0.0, 0.0), point(10.0, 0.0), point(10.0, 1.0), point(0.0, 1.0)];
+    let mut indices = vec![0, 1, 2, 0, 2, 3];
+    let min_angle = 20.0 * PI / 180.0;
+
+    {
+        let mut count = positions.len() as Index;
+        let mut add_vertex = |_| { count += 1; count - 1 };
+        refine(&mut positions, &mut indices, &RefineOptions::new(1000.0, min_angle), &mut add_vertex);
+    }
+
+    assert!((total_area(&positions, &indices) - 10.0).abs() < 0.01);
+    for t in 0..indices.len() / 3 {
+        let (a, b, c) = triangle_positions(&positions, &indices, t);
+        assert!(smallest_angle(a, b, c) >= min_angle * 0.99);
+    }
+}
+
+#[test]
+fn test_refine_is_delaunay() {
+    // An L shape, the refinement only flips the edges around the new vertices.
+    let mut positions = vec![
+        point(0.0, 0.0), point(20.0, 0.0), point(20.0, 5.0),
+        point(5.0, 5.0), point(5.0, 20.0), point(0.0, 20.0),
+    ];
+    let mut indices = vec![0, 1, 2, 0, 2, 3, 0, 3, 4, 0, 4, 5];
+
+    let added = {
+        let mut count = positions.len() as Index;
+        let mut add_vertex = |_| { count += 1; count - 1 };
+        refine(&mut positions, &mut indices, &RefineOptions::new(2.0, 20.0 * PI / 180.0), &mut add_vertex)
+    };
+
+    assert!(added > 50);
+    assert!((total_area(&positions, &indices) - 175.0).abs() < 0.01);
+    assert_eq!(make_delaunay(&positions, &mut indices), 0);
+    for t in 0..indices.len() / 3 {
+        let (a, b, c) = triangle_positions(&positions, &indices, t);
+        assert!(orient(a, b, c) > 0.0);
+        assert!((orient(a, b, c) * 0.5).abs() <= 2.0);
+    }
+}
+
+#[test]
+fn test_cos_series() {
+    for i in 0..17 {
+        let angle = i as f32 * PI / 32.0;
+        assert!((cos_series(angle) - angle.cos()).abs() < 0.000001);
+    }
+}
@@ -0,0 +1,428 @@
+//! Ear clipping triangulation of polygons with holes.
+//!
+//! An alternative to the sweep line algorithm of the
+//! [FillTessellator](../path_fill/struct.FillTessellator.html), selected with
+//! `FillOptions::with_algorithm(FillAlgorithm::EarClipping)`. It is quadratic in the number of
+//! vertices but has very little setup cost, which makes it faster for small polygons, and it
+//! is simple enough to be verified by hand, which makes it a good reference to compare the
+//! output of the fill tessellator with.
+//!
+//! Each sub-path is a ring. The rings are nested with the even-odd rule: a ring inside of an
+//! even number of rings is the outline of a polygon and a ring inside of an odd number of
+//! rings is a hole of the smallest polygon that contains it. Each hole is connected to the
+//! outline of its polygon with a bridge (two overlapping edges going back and forth), from
+//! its right-most vertex to a vertex of the outline that is visible from it, which turns the
+//! polygon into a single ring. Ears (triangles formed by three consecutive vertices that
+//! don't contain any other vertex) are then cut from the ring until only one triangle is
+//! left.
+//!
+//! The input must not intersect itself. Self-intersecting rings still produce a
+//! triangulation but it doesn't cover the fill of the path.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::ear_clipping::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! let mut buffers: VertexBuffers<FillVertex> = VertexBuffers::new();
+//! let count = ear_clipping_fill(
+//!     path.path_iter().flattened(0.05),
+//!     &mut simple_builder(&mut buffers),
+//! );
+//! # assert_eq!(count.indices, 6);
+//! # }
+//! ```
+
+use std::mem::replace;
+
+use math::*;
+use core::FlattenedEvent;
+use geometry_builder::{GeometryBuilder, Count, VertexId};
+use FillVertex as Vertex;
+
+/// Triangulates the fill of a flattened path with the ear clipping algorithm, using the
+/// even-odd fill rule.
+///
+/// The vertices of the path are added to the output once each, except for the consecutive
+/// duplicates and the rings that have less than three vertices or no area, which are skipped.
+pub fn ear_clipping_fill<Iter, Output>(path: Iter, output: &mut Output) -> Count
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Output: GeometryBuilder<Vertex>,
+{
+    output.begin_geometry();
+
+    let rings = collect_rings(path);
+
+    // Number of rings containing each ring.
+    let depths: Vec<usize> = (0..rings.len()).map(|i| {
+        (0..rings.len()).filter(|&j| j != i && ring_contains(&rings[j], rings[i][0])).count()
+    }).collect();
+
+    for i in 0..rings.len() {
+        if depths[i] % 2 == 1 {
+            continue;
+        }
+
+        let mut polygon = add_ring(&rings[i], true, output);
+
+        let mut holes: Vec<Vec<(Point, VertexId)>> = Vec::new();
+        for j in 0..rings.len() {
+            if depths[j] == depths[i] + 1 && ring_contains(&rings[i], rings[j][0]) {
+                holes.push(add_ring(&rings[j], false, output));
+            }
+        }
+
+        // The holes are bridged from right to left so that the bridges don't cross the
+        // holes that aren't bridged yet.
+        holes.sort_by(|a, b| {
+            max_x(b).partial_cmp(&max_x(a)).unwrap_or(::std::cmp::Ordering::Equal)
+        });
+        for hole in &holes {
+            bridge_hole(&mut polygon, hole);
+        }
+
+        clip_ears(&polygon, output);
+    }
+
+    return output.end_geometry();
+}
+
+fn collect_rings<Iter: Iterator<Item = FlattenedEvent>>(path: Iter) -> Vec<Vec<Point>> {
+    let mut rings = Vec::new();
+    let mut ring = Vec::new();
+    for event in path {
+        match event {
+            FlattenedEvent::MoveTo(to) => {
+                end_ring(&mut rings, &mut ring);
+                ring.push(to);
+            }
+            FlattenedEvent::LineTo(to) => {
+                if ring.last() != Some(&to) {
+                    ring.push(to);
+                }
+            }
+            FlattenedEvent::Close => {
+                end_ring(&mut rings, &mut ring);
+            }
+        }
+    }
+    end_ring(&mut rings, &mut ring);
+
+    return rings;
+}
+
+fn end_ring(rings: &mut Vec<Vec<Point>>, ring: &mut Vec<Point>) {
+    while ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    let ring = replace(ring, Vec::new());
+    if ring.len() >= 3 && signed_area(&ring) != 0.0 {
+        rings.push(ring);
+    }
+}
+
+// Positive if the ring turns counter-clockwise (with the y axis pointing up).
+fn signed_area(ring: &[Point]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..ring.len() {
+        let a = ring[i];
+        let b = ring[(i + 1) % ring.len()];
+        area += a.x * b.y - b.x * a.y;
+    }
+
+    return area * 0.5;
+}
+
+// Even-odd point in polygon test with half-open edges.
+fn ring_contains(ring: &[Point], p: Point) -> bool {
+    let mut inside = false;
+    for i in 0..ring.len() {
+        let a = ring[i];
+        let b = ring[(i + 1) % ring.len()];
+        if (a.y > p.y) != (b.y > p.y) {
+            let x = a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y);
+            if x > p.x {
+                inside = !inside;
+            }
+        }
+    }
+
+    return inside;
+}
+
+// Adds the vertices of a ring to the output, counter-clockwise for outlines and clockwise
+// for holes.
+fn add_ring<Output>(ring: &[Point], outline: bool, output: &mut Output) -> Vec<(Point, VertexId)>
+where
+    Output: GeometryBuilder<Vertex>,
+{
+    let mut result: Vec<(Point, VertexId)> = ring.iter().map(|&p| {
+        (p, output.add_vertex(Vertex { position: p, normal: vec2(0.0, 0.0) }))
+    }).collect();
+    if (signed_area(ring) > 0.0) != outline {
+        result.reverse();
+    }
+
+    return result;
+}
+
+fn max_x(ring: &[(Point, VertexId)]) -> f32 {
+    ring.iter().fold(-::std::f32::INFINITY, |x, v| x.max(v.0.x))
+}
+
+fn cross(a: Point, b: Point, c: Point) -> f32 { (b - a).cross(c - b) }
+
+// Whether the triangle abc (counter-clockwise) contains p, including its edges.
+fn triangle_contains(a: Point, b: Point, c: Point, p: Point) -> bool {
+    (b - a).cross(p - a) >= 0.0 && (c - b).cross(p - b) >= 0.0 && (a - c).cross(p - c) >= 0.0
+}
+
+// Connects a hole to the polygon with a bridge from its right-most vertex.
+fn bridge_hole(polygon: &mut Vec<(Point, VertexId)>, hole: &[(Point, VertexId)]) {
+    let mut m = 0;
+    for i in 1..hole.len() {
+        if hole[i].0.x > hole[m].0.x {
+            m = i;
+        }
+    }
+    let mp = hole[m].0;
+
+    // The closest edge of the polygon that a ray from the vertex towards +x crosses.
+    let n = polygon.len();
+    let mut closest: Option<(f32, usize)> = None;
+    for k in 0..n {
+        let a = polygon[k].0;
+        let b = polygon[(k + 1) % n].0;
+        if a.y == b.y || (a.y < mp.y && b.y < mp.y) || (a.y > mp.y && b.y > mp.y) {
+            continue;
+        }
+        let x = a.x + (mp.y - a.y) * (b.x - a.x) / (b.y - a.y);
+        if x >= mp.x && closest.map(|(best, _)| x < best).unwrap_or(true) {
+            closest = Some((x, k));
+        }
+    }
+    let (x, k) = match closest {
+        Some(c) => c,
+        // The hole isn't inside of the polygon.
+        None => { return; }
+    };
+    let intersection = point(x, mp.y);
+
+    // The end of the edge with the largest x is visible from the hole unless another vertex
+    // is in the way, in which case the vertex in the way that makes the smallest angle with
+    // the ray is.
+    let mut p = if polygon[k].0.x > polygon[(k + 1) % n].0.x { k } else { (k + 1) % n };
+    let pp = polygon[p].0;
+    if pp != intersection {
+        let (a, b, c) = if (intersection - mp).cross(pp - mp) >= 0.0 {
+            (mp, intersection, pp)
+        } else {
+            (mp, pp, intersection)
+        };
+        let mut best_cos = -1.0;
+        for i in 0..n {
+            let v = polygon[i].0;
+            if i == p || v == mp || !triangle_contains(a, b, c, v) {
+                continue;
+            }
+            let reflex = cross(polygon[(i + n - 1) % n].0, v, polygon[(i + 1) % n].0) < 0.0;
+            if !reflex {
+                continue;
+            }
+            let d = v - mp;
+            let cos = d.x / d.length();
+            if cos > best_cos {
+                best_cos = cos;
+                p = i;
+            }
+        }
+    }
+
+    // A vertex can appear several times after previous bridges, use the occurrence whose
+    // interior angle contains the hole.
+    let target = polygon[p].0;
+    for i in 0..n {
+        if polygon[i].0 == target && sector_contains(
+            polygon[(i + n - 1) % n].0,
+            target,
+            polygon[(i + 1) % n].0,
+            mp,
+        ) {
+            p = i;
+            break;
+        }
+    }
+
+    let mut bridged = Vec::with_capacity(n + hole.len() + 2);
+    bridged.extend_from_slice(&polygon[..(p + 1)]);
+    for i in 0..(hole.len() + 1) {
+        bridged.push(hole[(m + i) % hole.len()]);
+    }
+    bridged.push(polygon[p]);
+    bridged.extend_from_slice(&polygon[(p + 1)..]);
+    *polygon = bridged;
+}
+
+// Whether the interior angle at `v` of a counter-clockwise ring contains `p`.
+fn sector_contains(prev: Point, v: Point, next: Point, p: Point) -> bool {
+    let left_of_in = (v - prev).cross(p - v) >= 0.0;
+    let left_of_out = (next - v).cross(p - v) >= 0.0;
+    if cross(prev, v, next) >= 0.0 {
+        left_of_in && left_of_out
+    } else {
+        left_of_in || left_of_out
+    }
+}
+
+fn clip_ears<Output>(polygon: &[(Point, VertexId)], output: &mut Output)
+where
+    Output: GeometryBuilder<Vertex>,
+{
+    let n = polygon.len();
+    if n < 3 {
+        return;
+    }
+
+    let mut prev: Vec<usize> = (0..n).map(|i| (i + n - 1) % n).collect();
+    let mut next: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+    let mut remaining = n;
+    let mut i = 0;
+    // Number of vertices visited since the last ear was cut. After a full turn without
+    // finding an ear (the input intersects itself), the conditions are relaxed so that the
+    // loop always terminates.
+    let mut stalled = 0;
+    while remaining > 3 {
+        let (a, b, c) = (prev[i], i, next[i]);
+        let (pa, pb, pc) = (polygon[a].0, polygon[b].0, polygon[c].0);
+        let turn = cross(pa, pb, pc);
+
+        let is_ear = if stalled >= 2 * remaining {
+            true
+        } else if turn < 0.0 {
+            false
+        } else if stalled >= remaining {
+            true
+        } else {
+            // A flat vertex can always be removed, a convex one if the triangle doesn't
+            // contain another vertex of the ring.
+            let mut empty = true;
+            if turn > 0.0 {
+                let mut j = next[c];
+                while j != a {
+                    let p = polygon[j].0;
+                    if p != pa && p != pb && p != pc && triangle_contains(pa, pb, pc, p) {
+                        empty = false;
+                        break;
+                    }
+                    j = next[j];
+                }
+            }
+            empty
+        };
+
+        if is_ear {
+            if turn != 0.0 {
+                output.add_triangle(polygon[a].1, polygon[b].1, polygon[c].1);
+            }
+            next[a] = c;
+            prev[c] = a;
+            remaining -= 1;
+            stalled = 0;
+            i = a;
+        } else {
+            stalled += 1;
+            i = c;
+        }
+    }
+
+    let (a, b, c) = (prev[i], i, next[i]);
+    if cross(polygon[a].0, polygon[b].0, polygon[c].0) != 0.0 {
+        output.add_triangle(polygon[a].1, polygon[b].1, polygon[c].1);
+    }
+}
+
+#[cfg(test)]
+use geometry_builder::{VertexBuffers, simple_builder};
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[cfg(test)]
+fn triangles_area(buffers: &VertexBuffers<Vertex>) -> f32 {
+    let mut area = 0.0;
+    for triangle in buffers.indices.chunks(3) {
+        let a = buffers.vertices[triangle[0] as usize].position;
+        let b = buffers.vertices[triangle[1] as usize].position;
+        let c = buffers.vertices[triangle[2] as usize].position;
+        let triangle_area = (b - a).cross(c - a) * 0.5;
+        // All triangles are counter-clockwise.
+        assert!(triangle_area > 0.0);
+        area += triangle_area;
+    }
+
+    return area;
+}
+
+#[test]
+fn test_ear_clipping_concave() {
+    // An "L" shape with a flat vertex.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(2.0, 0.0));
+    builder.line_to(point(4.0, 0.0));
+    builder.line_to(point(4.0, 1.0));
+    builder.line_to(point(1.0, 1.0));
+    builder.line_to(point(1.0, 3.0));
+    builder.line_to(point(0.0, 3.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    let count = ear_clipping_fill(path.path_iter().flattened(0.1), &mut simple_builder(&mut buffers));
+    assert_eq!(count.vertices, 7);
+    assert_eq!(count.indices, 12);
+    assert_eq!(triangles_area(&buffers), 6.0);
+}
+
+#[test]
+fn test_ear_clipping_holes() {
+    // A square with two square holes, one of them containing an island, all of them
+    // turning in the same direction.
+    let mut builder = Path::builder();
+    for &(x, y, size) in &[(0.0, 0.0, 10.0), (1.0, 1.0, 3.0), (6.0, 1.0, 3.0), (7.0, 2.0, 1.0)] {
+        builder.move_to(point(x, y));
+        builder.line_to(point(x + size, y));
+        builder.line_to(point(x + size, y + size));
+        builder.line_to(point(x, y + size));
+        builder.close();
+    }
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    let count = ear_clipping_fill(path.path_iter().flattened(0.1), &mut simple_builder(&mut buffers));
+    assert_eq!(count.vertices, 16);
+    assert_eq!(count.indices as usize, buffers.indices.len());
+    assert_eq!(triangles_area(&buffers), 100.0 - 9.0 - 9.0 + 1.0);
+}
@@ -0,0 +1,312 @@
+//! Extruding filled paths into 3D prisms.
+//!
+//! [extrude_path](fn.extrude_path.html) fill-tessellates a path and turns the result into a
+//! closed 3D mesh: the fill becomes the top cap at the given height, a copy of it facing down
+//! becomes the bottom cap at z = 0, and the boundary of the fill is extruded into side walls.
+//! This is typically how the buildings of 3D maps are generated from their footprints.
+//!
+//! The side walls are built from the [boundary loops](../contours/index.html) of the fill
+//! tessellation, so they follow the boundary of the fill after the intersections of the path
+//! have been resolved, including the holes.
+//!
+//! The top cap and the bottom cap share their vertices between triangles, while each wall
+//! has its own four vertices so that the corners of the prism have sharp normals.
+//!
+//! The triangles are counter-clockwise when looking at them from the outside of the prism,
+//! with the z axis pointing up and the y axis of the path pointing up.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::extrusion::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let footprint = path;
+//! let mut buffers: VertexBuffers<ExtrudedVertex> = VertexBuffers::new();
+//! extrude_path(
+//!     footprint.path_iter().flattened(0.05),
+//!     10.0,
+//!     &FillOptions::default(),
+//!     &mut simple_builder(&mut buffers),
+//! ).unwrap();
+//!
+//! // With a sloped roof.
+//! extrude_path_with_heights(
+//!     footprint.path_iter().flattened(0.05),
+//!     &|p| 10.0 + p.x * 0.1,
+//!     &FillOptions::default(),
+//!     &mut simple_builder(&mut buffers),
+//! ).unwrap();
+//! # }
+//! ```
+
+use math::*;
+use core::FlattenedEvent;
+use geometry_builder::{GeometryBuilder, VertexBuffers, BuffersBuilder, TypedPositions, VertexId, Index};
+use path_fill::{FillTessellator, FillOptions, FillResult};
+use contours::boundary_loops;
+
+/// Vertex produced by the extrusion.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ExtrudedVertex {
+    pub position: Vec3,
+    /// Normalized normal of the surface at this vertex, pointing outside of the prism.
+    pub normal: Vec3,
+}
+
+/// Extrudes a path from z = 0 to z = `height`.
+pub fn extrude_path<Iter, Output>(
+    path: Iter,
+    height: f32,
+    options: &FillOptions,
+    output: &mut Output,
+) -> FillResult
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Output: GeometryBuilder<ExtrudedVertex>,
+{
+    extrude_path_with_heights(path, &|_| height, options, output)
+}
+
+/// Extrudes a path from z = 0 to the height returned by `height` at each vertex of the
+/// top cap.
+///
+/// The normals of the top cap are averaged between the triangles around each vertex.
+/// Heights should be positive, the bottom and the top caps would cross otherwise.
+pub fn extrude_path_with_heights<Iter, Output>(
+    path: Iter,
+    height: &Fn(Point) -> f32,
+    options: &FillOptions,
+    output: &mut Output,
+) -> FillResult
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Output: GeometryBuilder<ExtrudedVertex>,
+{
+    let mut fill: VertexBuffers<Point> = VertexBuffers::new();
+    try!{
+        FillTessellator::new().tessellate_path(
+            path,
+            options,
+            &mut BuffersBuilder::new(&mut fill, TypedPositions::new()),
+        )
+    };
+
+    let mut triangles: Vec<[Index; 3]> = Vec::with_capacity(fill.indices.len() / 3);
+    for triangle in fill.indices.chunks(3) {
+        let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+        let (pa, pb, pc) = (fill.vertices[a as usize], fill.vertices[b as usize], fill.vertices[c as usize]);
+        let area = (pb - pa).cross(pc - pa);
+        if area > 0.0 {
+            triangles.push([a, b, c]);
+        } else if area < 0.0 {
+            triangles.push([a, c, b]);
+        }
+    }
+
+    let top: Vec<Vec3> = fill.vertices.iter().map(|&p| Vec3::new(p.x, p.y, height(p))).collect();
+    let top_normals = smooth_normals(&top, &triangles);
+
+    output.begin_geometry();
+
+    if top.is_empty() {
+        return Ok(output.end_geometry());
+    }
+
+    // Top cap.
+    let top_offset = output.add_vertex(ExtrudedVertex { position: top[0], normal: top_normals[0] }).0;
+    for i in 1..top.len() {
+        output.add_vertex(ExtrudedVertex { position: top[i], normal: top_normals[i] });
+    }
+    for t in &triangles {
+        output.add_triangle(
+            VertexId(top_offset + t[0]),
+            VertexId(top_offset + t[1]),
+            VertexId(top_offset + t[2]),
+        );
+    }
+
+    // Bottom cap.
+    let down = Vec3::new(0.0, 0.0, -1.0);
+    let bottom_offset = top_offset + top.len() as Index;
+    for &p in &fill.vertices {
+        output.add_vertex(ExtrudedVertex { position: Vec3::new(p.x, p.y, 0.0), normal: down });
+    }
+    for t in &triangles {
+        output.add_triangle(
+            VertexId(bottom_offset + t[0]),
+            VertexId(bottom_offset + t[2]),
+            VertexId(bottom_offset + t[1]),
+        );
+    }
+
+    // Side walls.
+    for boundary in boundary_loops(&fill.vertices, &fill.indices) {
+        for i in 0..boundary.len() {
+            let (a, b) = (boundary[i], boundary[(i + 1) % boundary.len()]);
+            let (pa, pb) = (fill.vertices[a as usize], fill.vertices[b as usize]);
+            let d = pb - pa;
+            let length = d.length();
+            if length == 0.0 {
+                continue;
+            }
+            // The inside of the fill is on the left of the boundary.
+            let normal = Vec3::new(d.y / length, -d.x / length, 0.0);
+            let a0 = output.add_vertex(ExtrudedVertex { position: Vec3::new(pa.x, pa.y, 0.0), normal: normal });
+            let b0 = output.add_vertex(ExtrudedVertex { position: Vec3::new(pb.x, pb.y, 0.0), normal: normal });
+            let b1 = output.add_vertex(ExtrudedVertex { position: top[b as usize], normal: normal });
+            let a1 = output.add_vertex(ExtrudedVertex { position: top[a as usize], normal: normal });
+            output.add_triangle(a0, b0, b1);
+            output.add_triangle(a0, b1, a1);
+        }
+    }
+
+    return Ok(output.end_geometry());
+}
+
+fn smooth_normals(positions: &[Vec3], triangles: &[[Index; 3]]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::new(0.0, 0.0, 0.0); positions.len()];
+    for t in triangles {
+        let (a, b, c) = (positions[t[0] as usize], positions[t[1] as usize], positions[t[2] as usize]);
+        // Weighted by the area of the triangle.
+        let n = (b - a).cross(c - a);
+        for &i in t {
+            normals[i as usize] = normals[i as usize] + n;
+        }
+    }
+
+    return normals.iter().map(|&n| {
+        let length = n.square_length().sqrt();
+        if length == 0.0 { Vec3::new(0.0, 0.0, 1.0) } else { n / length }
+    }).collect();
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_iterator::PathIterator;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use geometry_builder::simple_builder;
+
+#[cfg(test)]
+fn square_path(size: f32) -> Path {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(size, 0.0));
+    builder.line_to(point(size, size));
+    builder.line_to(point(0.0, size));
+    builder.close();
+
+    return builder.build();
+}
+
+#[test]
+fn test_extrude_square() {
+    let path = square_path(2.0);
+    let mut buffers: VertexBuffers<ExtrudedVertex> = VertexBuffers::new();
+    extrude_path(
+        path.path_iter().flattened(0.05),
+        3.0,
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    // Four vertices per cap and per wall.
+    assert_eq!(buffers.vertices.len(), 4 + 4 + 4 * 4);
+    assert_eq!(buffers.indices.len(), (2 + 2 + 4 * 2) * 3);
+
+    let center = Vec3::new(1.0, 1.0, 1.5);
+    for v in &buffers.vertices {
+        assert!(v.position.z == 0.0 || v.position.z == 3.0);
+        assert!((v.normal.square_length() - 1.0).abs() < 0.0001);
+        // All the normals point outside.
+        assert!(v.normal.dot(v.position - center) > 0.0);
+    }
+
+    // The triangles face the same way as the normals of their vertices.
+    for t in buffers.indices.chunks(3) {
+        let (a, b, c) = (
+            buffers.vertices[t[0] as usize],
+            buffers.vertices[t[1] as usize],
+            buffers.vertices[t[2] as usize],
+        );
+        let n = (b.position - a.position).cross(c.position - a.position);
+        assert!(n.dot(a.normal) > 0.0);
+    }
+}
+
+#[test]
+fn test_extrude_with_hole() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    builder.move_to(point(4.0, 4.0));
+    builder.line_to(point(4.0, 6.0));
+    builder.line_to(point(6.0, 6.0));
+    builder.line_to(point(6.0, 4.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<ExtrudedVertex> = VertexBuffers::new();
+    extrude_path(
+        path.path_iter().flattened(0.05),
+        1.0,
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    // Eight walls, the ones of the hole face its center.
+    let walls: Vec<&ExtrudedVertex> = buffers.vertices.iter().filter(|v| v.normal.z == 0.0).collect();
+    assert_eq!(walls.len(), 8 * 4);
+    for v in walls {
+        let p: Vec2 = vec2(v.position.x - 5.0, v.position.y - 5.0);
+        let n: Vec2 = vec2(v.normal.x, v.normal.y);
+        if p.x.abs() <= 1.0 && p.y.abs() <= 1.0 {
+            assert!(n.dot(p) < 0.0);
+        } else {
+            assert!(n.dot(p) > 0.0);
+        }
+    }
+}
+
+#[test]
+fn test_extrude_with_heights() {
+    let path = square_path(1.0);
+    let mut buffers: VertexBuffers<ExtrudedVertex> = VertexBuffers::new();
+    extrude_path_with_heights(
+        path.path_iter().flattened(0.05),
+        &|p| 1.0 + p.x,
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    for v in &buffers.vertices[0..4] {
+        assert_eq!(v.position.z, 1.0 + v.position.x);
+        // The roof slopes down towards negative x.
+        assert!(v.normal.x < 0.0);
+        assert!(v.normal.z > 0.0);
+        assert!(v.normal.y.abs() < 0.0001);
+    }
+}
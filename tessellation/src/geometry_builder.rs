@@ -35,6 +35,14 @@
 //!   This separates the construction of vertex values from the assembly of the vertex buffers.
 //!   Another, simpler example of vertex constructor is the [Identity](struct.Identity.html)
 //!   constructor which just returns its input, untransformed.
+//! * The struct [PayloadBuilder](struct.PayloadBuilder.html) which attaches a caller-provided
+//!   payload (a z-index, an object id, etc.) to the vertices before passing them to another
+//!   geometry builder.
+//! * The struct [QuantizingBuilder](struct.QuantizingBuilder.html) which gives the positions
+//!   of the vertices as integers on a grid, for vertex formats that store them as `i16`.
+//! * The struct [TriangleSourceRecorder](struct.TriangleSourceRecorder.html) which records
+//!   the [source](enum.TriangleSource.html) of each triangle given to the
+//!   [ClassifiedGeometryBuilder](trait.ClassifiedGeometryBuilder.html) trait.
 //!
 //! Geometry builders are a practical way to add one last step to the tessellation pipeline,
 //! such as applying a transform or clipping the geometry.
@@ -223,6 +231,9 @@
 use std::marker::PhantomData;
 use std::ops::Add;
 
+use {FillVertex, StrokeVertex};
+use math::{Point, TypedPoint};
+
 pub type Index = u16;
 
 /// A virtual vertex offset in a geometry.
@@ -278,9 +289,86 @@ pub trait BezierGeometryBuilder<Input>: GeometryBuilder<Input> {
     fn add_quadratic_bezier(&mut self, from: VertexId, to: VertexId, ctrl: Input);
 }
 
+/// Where a triangle of a fill tessellation comes from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TriangleSource {
+    /// The triangle only touches line segments of the path, or none at all.
+    Interior,
+    /// The triangle has an edge along the flattening of a curve.
+    Curve,
+    /// The triangle is part of an anti-aliasing fringe.
+    Fringe,
+}
+
+/// An extension to GeometryBuilder that is told where each triangle comes from.
+///
+/// Renderers can use this to shade some of the triangles differently, for example to
+/// evaluate curves on the GPU only in the triangles along them.
+pub trait ClassifiedGeometryBuilder<Input>: GeometryBuilder<Input> {
+    /// Same as `add_triangle`, with the source of the triangle.
+    ///
+    /// This method can only be called between begin_geometry and end_geometry.
+    fn add_classified_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId, source: TriangleSource);
+}
+
+/// A geometry builder that records the source of each triangle in a vector before
+/// forwarding the triangles to another geometry builder.
+///
+/// The sources are in the order the triangles were added, triangles added with
+/// `add_triangle` being `TriangleSource::Interior`.
+pub struct TriangleSourceRecorder<'l, Builder: 'l> {
+    builder: &'l mut Builder,
+    sources: &'l mut Vec<TriangleSource>,
+    first_source: usize,
+}
+
+impl<'l, Builder: 'l> TriangleSourceRecorder<'l, Builder> {
+    pub fn new(builder: &'l mut Builder, sources: &'l mut Vec<TriangleSource>) -> Self {
+        TriangleSourceRecorder {
+            builder: builder,
+            sources: sources,
+            first_source: 0,
+        }
+    }
+}
+
+impl<'l, Input, Builder> GeometryBuilder<Input> for TriangleSourceRecorder<'l, Builder>
+where
+    Builder: 'l + GeometryBuilder<Input>,
+{
+    fn begin_geometry(&mut self) {
+        self.first_source = self.sources.len();
+        self.builder.begin_geometry();
+    }
+
+    fn end_geometry(&mut self) -> Count { self.builder.end_geometry() }
+
+    fn add_vertex(&mut self, vertex: Input) -> VertexId { self.builder.add_vertex(vertex) }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.add_classified_triangle(a, b, c, TriangleSource::Interior);
+    }
+
+    fn abort_geometry(&mut self) {
+        self.sources.truncate(self.first_source);
+        self.builder.abort_geometry();
+    }
+}
+
+impl<'l, Input, Builder> ClassifiedGeometryBuilder<Input> for TriangleSourceRecorder<'l, Builder>
+where
+    Builder: 'l + GeometryBuilder<Input>,
+{
+    fn add_classified_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId, source: TriangleSource) {
+        self.sources.push(source);
+        self.builder.add_triangle(a, b, c);
+    }
+}
+
 /// Structure that holds the vertex and index data.
 ///
 /// Usually writen into though temporary BuffersBuilder objects.
+#[derive(Clone, Debug)]
 pub struct VertexBuffers<VertexType> {
     pub vertices: Vec<VertexType>,
     pub indices: Vec<Index>,
@@ -357,6 +445,41 @@ impl<T> VertexConstructor<T, T> for Identity {
     fn new_vertex(&mut self, input: T) -> T { input }
 }
 
+/// A vertex constructor that outputs the positions of the vertices tagged with a unit.
+///
+/// Stroke vertices are extruded along their normal, which is relative to a width of 1.0, by
+/// the stroke width.
+pub struct TypedPositions<Unit> {
+    pub stroke_width: f32,
+    _unit: PhantomData<Unit>,
+}
+
+impl<Unit> TypedPositions<Unit> {
+    pub fn new() -> Self {
+        TypedPositions {
+            stroke_width: 1.0,
+            _unit: PhantomData,
+        }
+    }
+
+    pub fn with_stroke_width(mut self, width: f32) -> Self {
+        self.stroke_width = width;
+        return self;
+    }
+}
+
+impl<Unit> VertexConstructor<FillVertex, TypedPoint<Unit>> for TypedPositions<Unit> {
+    fn new_vertex(&mut self, vertex: FillVertex) -> TypedPoint<Unit> {
+        TypedPoint::from_untyped(&vertex.position)
+    }
+}
+
+impl<Unit> VertexConstructor<StrokeVertex, TypedPoint<Unit>> for TypedPositions<Unit> {
+    fn new_vertex(&mut self, vertex: StrokeVertex) -> TypedPoint<Unit> {
+        TypedPoint::from_untyped(&(vertex.position + vertex.normal * self.stroke_width))
+    }
+}
+
 /// A BuffersBuilder that takes the actual vertex type as input.
 pub type SimpleBuffersBuilder<'l, VertexType> = BuffersBuilder<'l,
                                                                VertexType,
@@ -377,6 +500,226 @@ pub fn simple_builder<'l, VertexType>(buffers: &'l mut VertexBuffers<VertexType>
     }
 }
 
+/// A vertex along with a payload provided by the caller of the tessellator, for example a
+/// z-index or the id of the object the vertex belongs to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WithPayload<Input, Payload> {
+    pub vertex: Input,
+    pub payload: Payload,
+}
+
+/// A geometry builder that attaches a payload to every vertex before forwarding it to
+/// another geometry builder.
+///
+/// This is useful to batch many shapes in the same buffers while keeping track of which
+/// vertices belong to which shape, without having to look at the vertex and index ranges.
+///
+/// ```
+/// # extern crate lyon_tessellation;
+/// # extern crate lyon_core;
+/// # extern crate lyon_path;
+/// # extern crate lyon_path_builder;
+/// # extern crate lyon_path_iterator;
+/// # use lyon_path::Path;
+/// # use lyon_path_builder::*;
+/// # use lyon_path_iterator::*;
+/// # use lyon_core::math::*;
+/// # use lyon_tessellation::*;
+/// # use lyon_tessellation::geometry_builder::*;
+/// # #[derive(Copy, Clone, Debug)]
+/// # struct MyVertex { position: Point, object_id: u32 }
+/// # fn main() {
+/// # let mut builder = Path::builder();
+/// # builder.move_to(point(0.0, 0.0));
+/// # builder.line_to(point(10.0, 0.0));
+/// # builder.line_to(point(10.0, 10.0));
+/// # builder.line_to(point(0.0, 10.0));
+/// # builder.close();
+/// # let path = builder.build();
+/// # let paths = vec![path.clone(), path];
+/// # let mut tessellator = FillTessellator::new();
+/// # let mut buffers: VertexBuffers<MyVertex> = VertexBuffers::new();
+/// struct WithObjectId;
+/// impl VertexConstructor<WithPayload<FillVertex, u32>, MyVertex> for WithObjectId {
+///     fn new_vertex(&mut self, v: WithPayload<FillVertex, u32>) -> MyVertex {
+///         MyVertex { position: v.vertex.position, object_id: v.payload }
+///     }
+/// }
+///
+/// let mut output = BuffersBuilder::new(&mut buffers, WithObjectId);
+/// for (id, path) in paths.iter().enumerate() {
+///     tessellator.tessellate_path(
+///         path.path_iter().flattened(0.05),
+///         &FillOptions::default(),
+///         &mut with_payload(&mut output, id as u32),
+///     ).unwrap();
+/// }
+/// # }
+/// ```
+pub struct PayloadBuilder<'l, Builder: 'l, Payload> {
+    builder: &'l mut Builder,
+    payload: Payload,
+}
+
+impl<'l, Builder: 'l, Payload> PayloadBuilder<'l, Builder, Payload> {
+    pub fn new(builder: &'l mut Builder, payload: Payload) -> Self {
+        PayloadBuilder {
+            builder: builder,
+            payload: payload,
+        }
+    }
+
+    /// Changes the payload of the vertices added from now on.
+    pub fn set_payload(&mut self, payload: Payload) { self.payload = payload; }
+
+    pub fn payload(&self) -> &Payload { &self.payload }
+}
+
+/// Creates a PayloadBuilder.
+pub fn with_payload<'l, Builder, Payload>(
+    builder: &'l mut Builder,
+    payload: Payload,
+) -> PayloadBuilder<'l, Builder, Payload> {
+    PayloadBuilder::new(builder, payload)
+}
+
+impl<'l, Input, Payload, Builder> GeometryBuilder<Input> for PayloadBuilder<'l, Builder, Payload>
+where
+    Payload: Clone,
+    Builder: 'l + GeometryBuilder<WithPayload<Input, Payload>>,
+{
+    fn begin_geometry(&mut self) { self.builder.begin_geometry(); }
+
+    fn end_geometry(&mut self) -> Count { self.builder.end_geometry() }
+
+    fn add_vertex(&mut self, vertex: Input) -> VertexId {
+        self.builder.add_vertex(WithPayload { vertex: vertex, payload: self.payload.clone() })
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.builder.add_triangle(a, b, c);
+    }
+
+    fn abort_geometry(&mut self) { self.builder.abort_geometry(); }
+}
+
+impl<'l, Input, Payload, Builder> BezierGeometryBuilder<Input> for PayloadBuilder<'l, Builder, Payload>
+where
+    Payload: Clone,
+    Builder: 'l + BezierGeometryBuilder<WithPayload<Input, Payload>>,
+{
+    fn add_quadratic_bezier(&mut self, from: VertexId, to: VertexId, ctrl: Input) {
+        self.builder.add_quadratic_bezier(from, to, WithPayload { vertex: ctrl, payload: self.payload.clone() });
+    }
+}
+
+impl<'l, Input, Payload, Builder> ClassifiedGeometryBuilder<Input> for PayloadBuilder<'l, Builder, Payload>
+where
+    Payload: Clone,
+    Builder: 'l + ClassifiedGeometryBuilder<WithPayload<Input, Payload>>,
+{
+    fn add_classified_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId, source: TriangleSource) {
+        self.builder.add_classified_triangle(a, b, c, source);
+    }
+}
+
+/// A vertex along with its position quantized to a grid, see
+/// [QuantizingBuilder](struct.QuantizingBuilder.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Quantized<Input> {
+    pub vertex: Input,
+    pub position: [i16; 2],
+}
+
+/// Rounds a point to the nearest point of a grid of `1 / 2^fractional_bits` units, in units
+/// of the grid. Coordinates that don't fit in an `i16` are clamped.
+pub fn quantize(p: Point, fractional_bits: u32) -> [i16; 2] {
+    let scale = (1u32 << fractional_bits) as f32;
+    let round = |v: f32| {
+        // Halves are rounded up like the fixed point grid of the fill tessellator does.
+        let v = (v * scale + 0.5).floor();
+        v.max(i16::min_value() as f32).min(i16::max_value() as f32) as i16
+    };
+
+    return [round(p.x), round(p.y)];
+}
+
+/// A geometry builder that quantizes the positions of the vertices before forwarding them to
+/// another geometry builder.
+///
+/// Quantizing the vertices after the tessellation moves them independently of the triangles
+/// around them, which creates cracks and overlaps where a vertex ends up on the wrong side of
+/// an edge. The input of the tessellator should be snapped to the same grid beforehand, which
+/// `FillTessellator::tessellate_path_quantized` does. The vertices the fill tessellator adds
+/// at the intersections of the edges are then the only ones that are rounded.
+///
+/// Stroke vertices are extruded along their normal, which is relative to a width of 1.0, by
+/// the stroke width before being quantized.
+pub struct QuantizingBuilder<'l, Builder: 'l> {
+    builder: &'l mut Builder,
+    fractional_bits: u32,
+    stroke_width: f32,
+}
+
+impl<'l, Builder: 'l> QuantizingBuilder<'l, Builder> {
+    pub fn new(builder: &'l mut Builder, fractional_bits: u32) -> Self {
+        assert!(fractional_bits <= 16);
+        QuantizingBuilder {
+            builder: builder,
+            fractional_bits: fractional_bits,
+            stroke_width: 1.0,
+        }
+    }
+
+    pub fn with_stroke_width(mut self, width: f32) -> Self {
+        self.stroke_width = width;
+        return self;
+    }
+
+    pub fn fractional_bits(&self) -> u32 { self.fractional_bits }
+}
+
+impl<'l, Builder> GeometryBuilder<FillVertex> for QuantizingBuilder<'l, Builder>
+where
+    Builder: 'l + GeometryBuilder<Quantized<FillVertex>>,
+{
+    fn begin_geometry(&mut self) { self.builder.begin_geometry(); }
+
+    fn end_geometry(&mut self) -> Count { self.builder.end_geometry() }
+
+    fn add_vertex(&mut self, vertex: FillVertex) -> VertexId {
+        let position = quantize(vertex.position, self.fractional_bits);
+        self.builder.add_vertex(Quantized { vertex: vertex, position: position })
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.builder.add_triangle(a, b, c);
+    }
+
+    fn abort_geometry(&mut self) { self.builder.abort_geometry(); }
+}
+
+impl<'l, Builder> GeometryBuilder<StrokeVertex> for QuantizingBuilder<'l, Builder>
+where
+    Builder: 'l + GeometryBuilder<Quantized<StrokeVertex>>,
+{
+    fn begin_geometry(&mut self) { self.builder.begin_geometry(); }
+
+    fn end_geometry(&mut self) -> Count { self.builder.end_geometry() }
+
+    fn add_vertex(&mut self, vertex: StrokeVertex) -> VertexId {
+        let extruded = vertex.position + vertex.normal * self.stroke_width;
+        let position = quantize(extruded, self.fractional_bits);
+        self.builder.add_vertex(Quantized { vertex: vertex, position: position })
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.builder.add_triangle(a, b, c);
+    }
+
+    fn abort_geometry(&mut self) { self.builder.abort_geometry(); }
+}
+
 /// Number of vertices and indices added during the tessellation.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Count {
@@ -441,6 +784,18 @@ where
     }
 }
 
+// The buffers don't have room for the sources, which are dropped.
+impl<'l, VertexType, Input, Ctor> ClassifiedGeometryBuilder<Input>
+    for BuffersBuilder<'l, VertexType, Input, Ctor>
+where
+    VertexType: 'l + Clone,
+    Ctor: VertexConstructor<Input, VertexType>,
+{
+    fn add_classified_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId, _source: TriangleSource) {
+        self.add_triangle(a, b, c);
+    }
+}
+
 #[test]
 fn test_simple_quad() {
     #[derive(Copy, Clone, PartialEq, Debug)]
@@ -555,3 +910,143 @@ fn test_simple_quad() {
     );
     assert_eq!(&buffers.indices[..], &[0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7]);
 }
+
+#[test]
+fn test_typed_positions() {
+    use path::TypedPath;
+    use path_fill::{FillTessellator, FillOptions};
+    use path_iterator::PathIterator;
+
+    struct WorldSpace;
+
+    let mut builder: ::path::TypedBuilder<WorldSpace> = TypedPath::builder();
+    builder.move_to(TypedPoint::new(0.0, 0.0));
+    builder.line_to(TypedPoint::new(1.0, 0.0));
+    builder.line_to(TypedPoint::new(1.0, 1.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<TypedPoint<WorldSpace>> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default(),
+        &mut BuffersBuilder::new(&mut buffers, TypedPositions::new())
+    ).unwrap();
+
+    assert_eq!(buffers.vertices.len(), 3);
+    assert!(buffers.vertices.contains(&TypedPoint::new(1.0, 1.0)));
+}
+
+#[test]
+fn test_typed_stroke_positions() {
+    use path::TypedPath;
+    use path_stroke::{StrokeTessellator, StrokeOptions};
+    use path_iterator::PathIterator;
+
+    struct WorldSpace;
+
+    let mut builder: ::path::TypedBuilder<WorldSpace> = TypedPath::builder();
+    builder.move_to(TypedPoint::new(0.0, 0.0));
+    builder.line_to(TypedPoint::new(10.0, 0.0));
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<TypedPoint<WorldSpace>> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate(
+        path.path_iter().flattened(0.05),
+        &StrokeOptions::default().with_line_width(4.0),
+        &mut BuffersBuilder::new(&mut buffers, TypedPositions::new().with_stroke_width(4.0))
+    ).unwrap();
+
+    // The sides of the stroke are half of the width away from the line.
+    assert_eq!(buffers.vertices.len(), 4);
+    for p in &buffers.vertices {
+        assert!((p.y.abs() - 2.0).abs() < 0.0001);
+    }
+}
+
+#[test]
+fn test_payload() {
+    use path::Path;
+    use path_builder::BaseBuilder;
+    use path_fill::{FillTessellator, FillOptions};
+    use path_iterator::PathIterator;
+    use math::point;
+
+    struct WithObjectId;
+    impl VertexConstructor<WithPayload<FillVertex, u32>, (f32, f32, u32)> for WithObjectId {
+        fn new_vertex(&mut self, v: WithPayload<FillVertex, u32>) -> (f32, f32, u32) {
+            (v.vertex.position.x, v.vertex.position.y, v.payload)
+        }
+    }
+
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.line_to(point(1.0, 1.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<(f32, f32, u32)> = VertexBuffers::new();
+    {
+        let mut output = BuffersBuilder::new(&mut buffers, WithObjectId);
+        let mut tessellator = FillTessellator::new();
+        for id in 0..3 {
+            tessellator.tessellate_path(
+                path.path_iter().flattened(0.05),
+                &FillOptions::default(),
+                &mut with_payload(&mut output, id),
+            ).unwrap();
+        }
+    }
+
+    assert_eq!(buffers.vertices.len(), 9);
+    for (i, v) in buffers.vertices.iter().enumerate() {
+        assert_eq!(v.2, i as u32 / 3);
+    }
+    // The indices of each shape refer to its own vertices.
+    for (i, triangle) in buffers.indices.chunks(3).enumerate() {
+        for &index in triangle {
+            assert_eq!(buffers.vertices[index as usize].2, i as u32);
+        }
+    }
+}
+
+#[test]
+fn test_quantize() {
+    use math::point;
+
+    assert_eq!(quantize(point(1.5, -2.0), 4), [24, -32]);
+    assert_eq!(quantize(point(1.0 / 32.0, -1.0 / 32.0), 4), [1, 0]);
+    assert_eq!(quantize(point(0.3, 0.3), 0), [0, 0]);
+    assert_eq!(quantize(point(1.0e6, -1.0e6), 4), [i16::max_value(), i16::min_value()]);
+}
+
+#[test]
+fn test_quantizing_builder() {
+    use path::Path;
+    use path_builder::BaseBuilder;
+    use path_stroke::{StrokeTessellator, StrokeOptions};
+    use path_iterator::PathIterator;
+    use math::point;
+
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<Quantized<StrokeVertex>> = VertexBuffers::new();
+    {
+        let mut output = simple_builder(&mut buffers);
+        StrokeTessellator::new().tessellate(
+            path.path_iter().flattened(0.05),
+            &StrokeOptions::default().with_line_width(2.0),
+            &mut QuantizingBuilder::new(&mut output, 4).with_stroke_width(2.0),
+        ).unwrap();
+    }
+
+    assert!(!buffers.indices.is_empty());
+    for v in &buffers.vertices {
+        assert!(v.position[1] == 16 || v.position[1] == -16);
+        assert_eq!(v.position[0] as f32, v.vertex.position.x * 16.0);
+    }
+}
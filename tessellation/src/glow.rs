@@ -0,0 +1,269 @@
+//! Geometry for soft outlines, glows and shadows.
+//!
+//! A glow can be rendered without any blur pass by filling a few bands around a shape and
+//! letting the GPU interpolate an intensity stored in the vertices. [glow](fn.glow.html)
+//! buffers the path at the distances of a list of rings, and fills the band between each
+//! pair of consecutive rings. The vertices of the output get the value of the ring they lie
+//! on, so the value is interpolated across each band.
+//!
+//! The inside of the first ring is not filled: the shape itself is usually rendered
+//! separately, and starting the rings at a negative distance makes the glow bleed inside.
+//! More rings make the falloff smoother, for example to approximate a gaussian curve.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::glow::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! // A glow fading from opaque on the shape to transparent 8 units away.
+//! let rings = glow_rings(0.0, 8.0, 4, 1.0, 0.0);
+//! let mut buffers: VertexBuffers<GlowVertex> = VertexBuffers::new();
+//! glow(path.path_iter().flattened(0.05), &rings, 0.05, &mut simple_builder(&mut buffers)).unwrap();
+//! # }
+//! ```
+
+use math::*;
+use core::{FlattenedEvent, LineCap, LineJoin};
+use algorithms::buffer::buffer_polygons;
+use geometry_builder::{GeometryBuilder, VertexBuffers, BuffersBuilder, TypedPositions};
+use path_fill::{FillTessellator, FillOptions, FillResult};
+
+/// An offset of the shape and the value of the vertices along it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlowRing {
+    /// The distance to the shape, positive outside of it.
+    pub distance: f32,
+    /// The value of the vertices on this ring, typically an opacity.
+    pub value: f32,
+}
+
+/// The vertex produced by [glow](fn.glow.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlowVertex {
+    pub position: Point,
+    /// The value interpolated between the values of the two rings around the vertex.
+    pub value: f32,
+}
+
+/// Returns `count + 1` rings evenly spaced between two distances, with values linearly
+/// interpolated between `start_value` and `end_value`.
+pub fn glow_rings(
+    start_distance: f32,
+    end_distance: f32,
+    count: u32,
+    start_value: f32,
+    end_value: f32,
+) -> Vec<GlowRing> {
+    let count = count.max(1);
+    return (0..count + 1).map(|i| {
+        let t = i as f32 / count as f32;
+        GlowRing {
+            distance: start_distance + (end_distance - start_distance) * t,
+            value: start_value + (end_value - start_value) * t,
+        }
+    }).collect();
+}
+
+/// Fills the bands between consecutive rings around a path, the rings being sorted by
+/// increasing distance.
+///
+/// Closed sub-paths are treated as polygons and open sub-paths as lines, as with
+/// [buffer](../../lyon_algorithms/buffer/fn.buffer.html). The corners and the ends of lines
+/// are rounded, `tolerance` being the maximum distance between the round parts and the
+/// circle arcs they approximate.
+pub fn glow<Iter, Output>(
+    path: Iter,
+    rings: &[GlowRing],
+    tolerance: f32,
+    output: &mut Output,
+) -> FillResult
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Output: GeometryBuilder<GlowVertex>,
+{
+    let events: Vec<FlattenedEvent> = path.collect();
+    let polygons: Vec<Vec<Vec<Point>>> = rings.iter().map(|ring| {
+        if ring.distance == 0.0 {
+            closed_polygons(&events)
+        } else {
+            buffer_polygons(
+                events.iter().cloned(),
+                ring.distance,
+                LineJoin::Round,
+                LineCap::Round,
+                tolerance,
+            )
+        }
+    }).collect();
+
+    let mut tessellator = FillTessellator::new();
+    let options = FillOptions::even_odd();
+
+    output.begin_geometry();
+    for i in 1..rings.len() {
+        let (inner, outer) = (&polygons[i - 1], &polygons[i]);
+
+        // The inner polygons are inside of the outer ones, so the even-odd fill rule
+        // only fills the band between them.
+        let mut band_events = Vec::new();
+        add_polygon_events(inner, &mut band_events);
+        add_polygon_events(outer, &mut band_events);
+        let mut band: VertexBuffers<Point> = VertexBuffers::new();
+        try!{
+            tessellator.tessellate_path(
+                band_events.into_iter(),
+                &options,
+                &mut BuffersBuilder::new(&mut band, TypedPositions::new()),
+            )
+        };
+
+        let ids: Vec<_> = band.vertices.iter().map(|&position| {
+            // The vertices are on one of the rings, except where the tessellator added
+            // intersections.
+            let d_inner = distance_to_polygons(inner, position);
+            let d_outer = distance_to_polygons(outer, position);
+            let t = if d_inner + d_outer > 0.0 { d_inner / (d_inner + d_outer) } else { 0.0 };
+            output.add_vertex(GlowVertex {
+                position: position,
+                value: rings[i - 1].value + (rings[i].value - rings[i - 1].value) * t,
+            })
+        }).collect();
+
+        for triangle in band.indices.chunks(3) {
+            output.add_triangle(
+                ids[triangle[0] as usize],
+                ids[triangle[1] as usize],
+                ids[triangle[2] as usize],
+            );
+        }
+    }
+
+    return Ok(output.end_geometry());
+}
+
+// The sub-paths with at least three points, open ones don't have an area at distance zero.
+fn closed_polygons(events: &[FlattenedEvent]) -> Vec<Vec<Point>> {
+    let mut polygons = Vec::new();
+    let mut current = Vec::new();
+    for evt in events {
+        match *evt {
+            FlattenedEvent::MoveTo(to) => {
+                current.clear();
+                current.push(to);
+            }
+            FlattenedEvent::LineTo(to) => { current.push(to); }
+            FlattenedEvent::Close => {
+                if current.len() >= 3 {
+                    polygons.push(current.clone());
+                }
+                current.truncate(1);
+            }
+        }
+    }
+
+    return polygons;
+}
+
+fn add_polygon_events(polygons: &[Vec<Point>], output: &mut Vec<FlattenedEvent>) {
+    for polygon in polygons {
+        output.push(FlattenedEvent::MoveTo(polygon[0]));
+        for &p in &polygon[1..] {
+            output.push(FlattenedEvent::LineTo(p));
+        }
+        output.push(FlattenedEvent::Close);
+    }
+}
+
+fn distance_to_polygons(polygons: &[Vec<Point>], p: Point) -> f32 {
+    let mut result = ::std::f32::MAX;
+    for polygon in polygons {
+        let n = polygon.len();
+        for j in 0..n {
+            let (a, b) = (polygon[j], polygon[(j + 1) % n]);
+            let ab = b - a;
+            let square_length = ab.square_length();
+            let t = if square_length > 0.0 {
+                ((p - a).dot(ab) / square_length).max(0.0).min(1.0)
+            } else {
+                0.0
+            };
+            result = result.min((a + ab * t - p).length());
+        }
+    }
+
+    return result;
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_iterator::PathIterator;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use geometry_builder::simple_builder;
+
+#[test]
+fn test_glow_rings() {
+    let rings = glow_rings(0.0, 4.0, 2, 1.0, 0.0);
+    assert_eq!(
+        rings,
+        vec![
+            GlowRing { distance: 0.0, value: 1.0 },
+            GlowRing { distance: 2.0, value: 0.5 },
+            GlowRing { distance: 4.0, value: 0.0 },
+        ]
+    );
+}
+
+#[test]
+fn test_glow_square() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    let rings = glow_rings(0.0, 4.0, 2, 1.0, 0.0);
+    let mut buffers: VertexBuffers<GlowVertex> = VertexBuffers::new();
+    glow(path.path_iter().flattened(0.05), &rings, 0.01, &mut simple_builder(&mut buffers)).unwrap();
+
+    // The vertices are on the rings.
+    for v in &buffers.vertices {
+        let dx = (v.position.x - 5.0).abs() - 5.0;
+        let dy = (v.position.y - 5.0).abs() - 5.0;
+        let distance = Vec2::new(dx.max(0.0), dy.max(0.0)).length() + dx.max(dy).min(0.0);
+        assert!((v.value - (1.0 - distance / 4.0)).abs() < 0.01, "{:?}", v);
+    }
+
+    // The bands cover the buffer of the square minus the square.
+    let mut area = 0.0;
+    for triangle in buffers.indices.chunks(3) {
+        let a = buffers.vertices[triangle[0] as usize].position;
+        let b = buffers.vertices[triangle[1] as usize].position;
+        let c = buffers.vertices[triangle[2] as usize].position;
+        area += ((b - a).cross(c - a) * 0.5).abs();
+    }
+    let expected = 4.0 * 10.0 * 4.0 + ::std::f32::consts::PI * 16.0;
+    assert!((area - expected).abs() < expected * 0.01, "{}", area);
+}
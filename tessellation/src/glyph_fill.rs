@@ -0,0 +1,211 @@
+//! Fill tessellation specialized for glyph outlines.
+//!
+//! TrueType glyph contours only contain line segments and quadratic bézier curves, have
+//! few vertices and are tessellated in large numbers when rendering text. The general
+//! purpose path pipeline (path object, iterator adapters and fill tessellator) has a
+//! non-negligible per-call overhead in this situation.
+//!
+//! [GlyphTessellator](struct.GlyphTessellator.html) keeps all of its temporary buffers
+//! between calls so that it does not allocate once warmed up, and splits quadratic curves
+//! at their y extremum before flattening them so that each flattened curve is y-monotone,
+//! which avoids creating clusters of nearly-coincident vertices around the extrema.
+//!
+//! [GlyphCache](struct.GlyphCache.html) stores the tessellated geometry of each glyph
+//! so that glyphs that are used many times (most of them in a paragraph) are only
+//! tessellated once.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+
+use FillVertex;
+use math::*;
+use core::{PathEvent, FlattenedEvent};
+use bezier::{QuadraticBezierSegment, CubicBezierSegment};
+use geometry_builder::{GeometryBuilder, VertexBuffers, BuffersBuilder, TypedPositions};
+use path_fill::{FillTessellator, FillEvents, FillOptions, FillResult, FillError};
+
+/// A fill tessellator for glyph outlines.
+///
+/// Cubic bézier curves are supported (for CFF fonts) but are flattened without the
+/// monotonic split.
+pub struct GlyphTessellator {
+    tessellator: FillTessellator,
+    events: FillEvents,
+    flattened: Vec<FlattenedEvent>,
+    options: FillOptions,
+}
+
+impl GlyphTessellator {
+    pub fn new() -> Self { GlyphTessellator::with_tolerance(FillOptions::default().tolerance) }
+
+    pub fn with_tolerance(tolerance: f32) -> Self {
+        GlyphTessellator {
+            tessellator: FillTessellator::new(),
+            events: FillEvents::new(),
+            flattened: Vec::with_capacity(128),
+            options: FillOptions::default().with_tolerance(tolerance),
+        }
+    }
+
+    pub fn set_tolerance(&mut self, tolerance: f32) { self.options.tolerance = tolerance; }
+
+    pub fn tolerance(&self) -> f32 { self.options.tolerance }
+
+    /// Tessellates the fill of a glyph outline.
+    pub fn tessellate_glyph<Iter, Output>(&mut self, outline: Iter, output: &mut Output) -> FillResult
+    where
+        Iter: Iterator<Item = PathEvent>,
+        Output: GeometryBuilder<FillVertex>,
+    {
+        self.flattened.clear();
+        flatten_glyph(outline, self.options.tolerance, &mut self.flattened);
+
+        self.events.set_path_iter(self.flattened.iter().cloned());
+
+        return self.tessellator.tessellate_events(&self.events, &self.options, output);
+    }
+}
+
+fn flatten_glyph<Iter>(outline: Iter, tolerance: f32, output: &mut Vec<FlattenedEvent>)
+where
+    Iter: Iterator<Item = PathEvent>,
+{
+    let mut current = point(0.0, 0.0);
+    for evt in outline {
+        match evt {
+            PathEvent::MoveTo(to) => {
+                output.push(FlattenedEvent::MoveTo(to));
+                current = to;
+            }
+            PathEvent::LineTo(to) => {
+                output.push(FlattenedEvent::LineTo(to));
+                current = to;
+            }
+            PathEvent::QuadraticTo(ctrl, to) => {
+                let curve = QuadraticBezierSegment { from: current, ctrl: ctrl, to: to };
+                let mut add_point = |p: Point| { output.push(FlattenedEvent::LineTo(p)); };
+                if let Some(t) = curve.find_y_inflection() {
+                    let (a, b) = curve.split(t);
+                    a.flattened_for_each(tolerance, &mut add_point);
+                    b.flattened_for_each(tolerance, &mut add_point);
+                } else {
+                    curve.flattened_for_each(tolerance, &mut add_point);
+                }
+                current = to;
+            }
+            PathEvent::CubicTo(ctrl1, ctrl2, to) => {
+                let curve = CubicBezierSegment { from: current, ctrl1: ctrl1, ctrl2: ctrl2, to: to };
+                curve.flattened_for_each(tolerance, &mut |p| { output.push(FlattenedEvent::LineTo(p)); });
+                current = to;
+            }
+            PathEvent::Close => {
+                output.push(FlattenedEvent::Close);
+            }
+        }
+    }
+}
+
+/// Stores the tessellation of glyphs, identified by a user-defined key.
+///
+/// The key typically contains the font, the glyph id and the font size (or a font size
+/// bucket, since the tolerance of the tessellation depends on the size at which the glyph
+/// is rendered). The geometry is stored in the glyph's coordinate space.
+pub struct GlyphCache<Key> {
+    glyphs: HashMap<Key, VertexBuffers<Point>>,
+    tessellator: GlyphTessellator,
+}
+
+impl<Key: Hash + Eq> GlyphCache<Key> {
+    pub fn new(tolerance: f32) -> Self {
+        GlyphCache {
+            glyphs: HashMap::new(),
+            tessellator: GlyphTessellator::with_tolerance(tolerance),
+        }
+    }
+
+    /// Returns the geometry of a glyph, tessellating it if it isn't in the cache.
+    ///
+    /// The outline callback is only invoked if the glyph needs to be tessellated.
+    pub fn get_or_tessellate<Iter, F>(&mut self, key: Key, outline: F) -> Result<&VertexBuffers<Point>, FillError>
+    where
+        Iter: Iterator<Item = PathEvent>,
+        F: FnOnce() -> Iter,
+    {
+        match self.glyphs.entry(key) {
+            Entry::Occupied(entry) => {
+                return Ok(entry.into_mut());
+            }
+            Entry::Vacant(entry) => {
+                let mut buffers = VertexBuffers::with_capacity(32, 64);
+                try!{
+                    self.tessellator.tessellate_glyph(
+                        outline(),
+                        &mut BuffersBuilder::new(&mut buffers, TypedPositions::new())
+                    )
+                };
+                return Ok(entry.insert(buffers));
+            }
+        }
+    }
+
+    pub fn get(&self, key: &Key) -> Option<&VertexBuffers<Point>> { self.glyphs.get(key) }
+
+    pub fn contains(&self, key: &Key) -> bool { self.glyphs.contains_key(key) }
+
+    pub fn remove(&mut self, key: &Key) -> Option<VertexBuffers<Point>> { self.glyphs.remove(key) }
+
+    pub fn clear(&mut self) { self.glyphs.clear(); }
+
+    pub fn len(&self) -> usize { self.glyphs.len() }
+}
+
+#[cfg(test)]
+fn glyph_o() -> Vec<PathEvent> {
+    // A crude "o" made of two contours of quadratic curves.
+    let mut outline = Vec::new();
+    for &(r, dir) in &[(10.0, 1.0), (6.0, -1.0)] {
+        outline.push(PathEvent::MoveTo(point(r, 0.0)));
+        outline.push(PathEvent::QuadraticTo(point(r, r * dir), point(0.0, r * dir)));
+        outline.push(PathEvent::QuadraticTo(point(-r, r * dir), point(-r, 0.0)));
+        outline.push(PathEvent::QuadraticTo(point(-r, -r * dir), point(0.0, -r * dir)));
+        outline.push(PathEvent::QuadraticTo(point(r, -r * dir), point(r, 0.0)));
+        outline.push(PathEvent::Close);
+    }
+    return outline;
+}
+
+#[test]
+fn test_glyph_tessellator() {
+    let mut tessellator = GlyphTessellator::with_tolerance(0.1);
+    let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+
+    for _ in 0..3 {
+        buffers.vertices.clear();
+        buffers.indices.clear();
+        tessellator.tessellate_glyph(
+            glyph_o().into_iter(),
+            &mut BuffersBuilder::new(&mut buffers, TypedPositions::new())
+        ).unwrap();
+
+        assert!(buffers.indices.len() > 0);
+        assert_eq!(buffers.indices.len() % 3, 0);
+    }
+}
+
+#[test]
+fn test_glyph_cache() {
+    let mut cache: GlyphCache<u32> = GlyphCache::new(0.1);
+    let mut calls = 0;
+
+    let num_indices = cache.get_or_tessellate(42, || { calls += 1; glyph_o().into_iter() })
+        .unwrap().indices.len();
+    assert!(num_indices > 0);
+
+    let again = cache.get_or_tessellate(42, || { calls += 1; glyph_o().into_iter() })
+        .unwrap().indices.len();
+    assert_eq!(again, num_indices);
+    assert_eq!(calls, 1);
+    assert_eq!(cache.len(), 1);
+    assert!(cache.get(&1).is_none());
+}
@@ -0,0 +1,452 @@
+//! Filling paths with gradients using vertex colors.
+//!
+//! Simple rendering pipelines that only interpolate vertex attributes can't evaluate a
+//! gradient in a fragment shader. [fill_with_gradient](fn.fill_with_gradient.html)
+//! tessellates a path, evaluates the gradient at each vertex and subdivides the triangles
+//! until the linear interpolation of the vertex colors across each triangle is within a
+//! tolerance of the gradient.
+//!
+//! Linear gradients with two stops don't need any subdivision. Additional stops and radial
+//! gradients add vertices along the edges where the gradient isn't linear.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::gradient::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! let gradient = Gradient::linear(
+//!     point(0.0, 0.0),
+//!     point(100.0, 0.0),
+//!     vec![
+//!         GradientStop { offset: 0.0, color: [1.0, 0.0, 0.0, 1.0] },
+//!         GradientStop { offset: 1.0, color: [0.0, 0.0, 1.0, 1.0] },
+//!     ],
+//! );
+//! let mut buffers: VertexBuffers<GradientVertex> = VertexBuffers::new();
+//! fill_with_gradient(
+//!     path.path_iter().flattened(0.05),
+//!     &gradient,
+//!     1.0 / 255.0,
+//!     &FillOptions::default(),
+//!     &mut simple_builder(&mut buffers),
+//! ).unwrap();
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use math::*;
+use core::FlattenedEvent;
+use geometry_builder::{GeometryBuilder, VertexBuffers, BuffersBuilder, TypedPositions, VertexId, Index};
+use path_fill::{FillTessellator, FillOptions, FillResult};
+
+// Each pass halves the longest edges that need it, so this is plenty.
+const MAX_SUBDIVISIONS: u32 = 12;
+
+/// A color and its position along the gradient, between 0.0 and 1.0.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GradientStop {
+    pub offset: f32,
+    /// Red, green, blue and alpha.
+    pub color: [f32; 4],
+}
+
+/// The geometry of a gradient.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GradientKind {
+    /// The offset varies from 0.0 at `start` to 1.0 at `end`, along the line between them.
+    Linear { start: Point, end: Point },
+    /// The offset varies from 0.0 at `center` to 1.0 at `radius`.
+    Radial { center: Point, radius: f32 },
+}
+
+/// A gradient, padded with the colors of the first and last stops.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    /// Sorted by offset.
+    pub stops: Vec<GradientStop>,
+}
+
+impl Gradient {
+    pub fn linear(start: Point, end: Point, stops: Vec<GradientStop>) -> Self {
+        Gradient {
+            kind: GradientKind::Linear { start: start, end: end },
+            stops: stops,
+        }
+    }
+
+    pub fn radial(center: Point, radius: f32, stops: Vec<GradientStop>) -> Self {
+        Gradient {
+            kind: GradientKind::Radial { center: center, radius: radius },
+            stops: stops,
+        }
+    }
+
+    /// The color of the gradient at a given position.
+    pub fn color_at(&self, position: Point) -> [f32; 4] {
+        let offset = match self.kind {
+            GradientKind::Linear { start, end } => {
+                let v = end - start;
+                let square_length = v.square_length();
+                if square_length == 0.0 { 0.0 } else { (position - start).dot(v) / square_length }
+            }
+            GradientKind::Radial { center, radius } => {
+                if radius == 0.0 { 1.0 } else { (position - center).length() / radius }
+            }
+        };
+
+        return self.color_at_offset(offset);
+    }
+
+    /// The color of the gradient at an offset.
+    pub fn color_at_offset(&self, offset: f32) -> [f32; 4] {
+        let stops = &self.stops[..];
+        if stops.is_empty() {
+            return [0.0; 4];
+        }
+        if offset <= stops[0].offset {
+            return stops[0].color;
+        }
+        for i in 1..stops.len() {
+            let (a, b) = (stops[i - 1], stops[i]);
+            if offset < b.offset {
+                let t = (offset - a.offset) / (b.offset - a.offset);
+                return lerp_color(a.color, b.color, t);
+            }
+        }
+
+        return stops[stops.len() - 1].color;
+    }
+}
+
+/// The vertex produced by [fill_with_gradient](fn.fill_with_gradient.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GradientVertex {
+    pub position: Point,
+    pub color: [f32; 4],
+}
+
+/// Fill-tessellates a path and assigns the colors of a gradient to the vertices.
+///
+/// The triangles are subdivided until the colors interpolated across them differ from the
+/// gradient by at most `color_tolerance` (on each channel, colors being between 0.0 and
+/// 1.0), or until the geometry reaches the maximum number of vertices.
+pub fn fill_with_gradient<Iter, Output>(
+    path: Iter,
+    gradient: &Gradient,
+    color_tolerance: f32,
+    options: &FillOptions,
+    output: &mut Output,
+) -> FillResult
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Output: GeometryBuilder<GradientVertex>,
+{
+    let mut mesh: VertexBuffers<Point> = VertexBuffers::new();
+    try!{
+        FillTessellator::new().tessellate_path(
+            path,
+            options,
+            &mut BuffersBuilder::new(&mut mesh, TypedPositions::new()),
+        )
+    };
+
+    subdivide(&mut mesh, gradient, color_tolerance);
+
+    output.begin_geometry();
+    for &position in &mesh.vertices {
+        output.add_vertex(GradientVertex { position: position, color: gradient.color_at(position) });
+    }
+    for triangle in mesh.indices.chunks(3) {
+        output.add_triangle(VertexId(triangle[0]), VertexId(triangle[1]), VertexId(triangle[2]));
+    }
+
+    return Ok(output.end_geometry());
+}
+
+// Splits the edges along which the gradient isn't linear enough, without creating
+// T-junctions: the triangles are split in two, three or four depending on how many of their
+// edges are split.
+fn subdivide(mesh: &mut VertexBuffers<Point>, gradient: &Gradient, tolerance: f32) {
+    let mut full = false;
+    for _ in 0..MAX_SUBDIVISIONS {
+        let mut midpoints: HashMap<(Index, Index), Index> = HashMap::new();
+        for triangle in mesh.indices.chunks(3) {
+            if full {
+                break;
+            }
+            let corners = [triangle[0], triangle[1], triangle[2]];
+            let mut split_any = false;
+            for i in 0..3 {
+                let (a, b) = (corners[i], corners[(i + 1) % 3]);
+                let key = edge_key(a, b);
+                if !midpoints.contains_key(&key) {
+                    let (pa, pb) = (mesh.vertices[a as usize], mesh.vertices[b as usize]);
+                    if edge_error(gradient, pa, pb) > tolerance {
+                        full = !add_midpoint(&mut mesh.vertices, &mut midpoints, key);
+                    }
+                }
+                split_any |= midpoints.contains_key(&key);
+            }
+
+            // The edges can be fine while the inside of the triangle isn't.
+            if !split_any && !full && triangle_error(gradient, &mesh.vertices, &corners) > tolerance {
+                let key = longest_edge(&mesh.vertices, &corners);
+                full = !add_midpoint(&mut mesh.vertices, &mut midpoints, key);
+            }
+        }
+
+        if midpoints.is_empty() {
+            return;
+        }
+
+        let mut indices = Vec::with_capacity(mesh.indices.len() * 2);
+        for triangle in mesh.indices.chunks(3) {
+            split_triangle([triangle[0], triangle[1], triangle[2]], &midpoints, &mut indices);
+        }
+        mesh.indices = indices;
+
+        if full {
+            return;
+        }
+    }
+}
+
+fn add_midpoint(
+    vertices: &mut Vec<Point>,
+    midpoints: &mut HashMap<(Index, Index), Index>,
+    key: (Index, Index),
+) -> bool {
+    if vertices.len() >= Index::max_value() as usize {
+        return false;
+    }
+    let mid = vertices[key.0 as usize].lerp(vertices[key.1 as usize], 0.5);
+    midpoints.insert(key, vertices.len() as Index);
+    vertices.push(mid);
+
+    return true;
+}
+
+fn split_triangle(corners: [Index; 3], midpoints: &HashMap<(Index, Index), Index>, output: &mut Vec<Index>) {
+    let mid = |i: usize| midpoints.get(&edge_key(corners[i], corners[(i + 1) % 3])).cloned();
+    let mids = [mid(0), mid(1), mid(2)];
+    let count = mids.iter().filter(|m| m.is_some()).count();
+
+    // Rotate the corners so that the split edges come first, keeping the orientation.
+    let mut rotation = 0;
+    for r in 0..3 {
+        let first_split = mids[r].is_some();
+        let last_split = mids[(r + 2) % 3].is_some();
+        if (count == 1 && first_split) || (count == 2 && !last_split) {
+            rotation = r;
+            break;
+        }
+    }
+    let a = corners[rotation];
+    let b = corners[(rotation + 1) % 3];
+    let c = corners[(rotation + 2) % 3];
+    let ab = mids[rotation];
+    let bc = mids[(rotation + 1) % 3];
+    let ca = mids[(rotation + 2) % 3];
+
+    match count {
+        0 => output.extend_from_slice(&[a, b, c]),
+        1 => {
+            let ab = ab.unwrap();
+            output.extend_from_slice(&[a, ab, c, ab, b, c]);
+        }
+        2 => {
+            let (ab, bc) = (ab.unwrap(), bc.unwrap());
+            output.extend_from_slice(&[ab, b, bc, a, ab, bc, a, bc, c]);
+        }
+        _ => {
+            let (ab, bc, ca) = (ab.unwrap(), bc.unwrap(), ca.unwrap());
+            output.extend_from_slice(&[a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+        }
+    }
+}
+
+fn edge_key(a: Index, b: Index) -> (Index, Index) { if a < b { (a, b) } else { (b, a) } }
+
+fn longest_edge(vertices: &[Point], corners: &[Index; 3]) -> (Index, Index) {
+    let mut longest = (corners[0], corners[1]);
+    let mut longest_length = -1.0;
+    for i in 0..3 {
+        let (a, b) = (corners[i], corners[(i + 1) % 3]);
+        let length = (vertices[b as usize] - vertices[a as usize]).square_length();
+        if length > longest_length {
+            longest = edge_key(a, b);
+            longest_length = length;
+        }
+    }
+
+    return longest;
+}
+
+// The largest difference between the gradient and the interpolated colors, sampled along
+// the edge.
+fn edge_error(gradient: &Gradient, a: Point, b: Point) -> f32 {
+    let (ca, cb) = (gradient.color_at(a), gradient.color_at(b));
+    let mut error: f32 = 0.0;
+    for &t in &[0.25, 0.5, 0.75] {
+        let expected = gradient.color_at(a.lerp(b, t));
+        error = error.max(color_difference(expected, lerp_color(ca, cb, t)));
+    }
+
+    return error;
+}
+
+fn triangle_error(gradient: &Gradient, vertices: &[Point], corners: &[Index; 3]) -> f32 {
+    let p: Vec<Point> = corners.iter().map(|&i| vertices[i as usize]).collect();
+    let centroid = point((p[0].x + p[1].x + p[2].x) / 3.0, (p[0].y + p[1].y + p[2].y) / 3.0);
+    let colors: Vec<[f32; 4]> = p.iter().map(|&v| gradient.color_at(v)).collect();
+    let mut average = [0.0; 4];
+    for i in 0..4 {
+        average[i] = (colors[0][i] + colors[1][i] + colors[2][i]) / 3.0;
+    }
+
+    return color_difference(gradient.color_at(centroid), average);
+}
+
+fn lerp_color(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+        a[3] + (b[3] - a[3]) * t,
+    ]
+}
+
+fn color_difference(a: [f32; 4], b: [f32; 4]) -> f32 {
+    (0..4).map(|i| (a[i] - b[i]).abs()).fold(0.0, f32::max)
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_iterator::PathIterator;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use geometry_builder::simple_builder;
+
+#[cfg(test)]
+fn rectangle(w: f32, h: f32) -> Path {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(w, 0.0));
+    builder.line_to(point(w, h));
+    builder.line_to(point(0.0, h));
+    builder.close();
+    return builder.build();
+}
+
+#[cfg(test)]
+fn max_centroid_error(buffers: &VertexBuffers<GradientVertex>, gradient: &Gradient) -> f32 {
+    let mut error: f32 = 0.0;
+    for triangle in buffers.indices.chunks(3) {
+        let v: Vec<GradientVertex> = triangle.iter().map(|&i| buffers.vertices[i as usize]).collect();
+        let centroid = point(
+            (v[0].position.x + v[1].position.x + v[2].position.x) / 3.0,
+            (v[0].position.y + v[1].position.y + v[2].position.y) / 3.0,
+        );
+        let mut average = [0.0; 4];
+        for i in 0..4 {
+            average[i] = (v[0].color[i] + v[1].color[i] + v[2].color[i]) / 3.0;
+        }
+        error = error.max(color_difference(gradient.color_at(centroid), average));
+    }
+
+    return error;
+}
+
+#[test]
+fn test_linear_gradient() {
+    let path = rectangle(100.0, 10.0);
+    let black = [0.0, 0.0, 0.0, 1.0];
+    let white = [1.0, 1.0, 1.0, 1.0];
+    let red = [1.0, 0.0, 0.0, 1.0];
+
+    // Two stops, the interpolation is exact.
+    let gradient = Gradient::linear(
+        point(0.0, 0.0),
+        point(100.0, 0.0),
+        vec![GradientStop { offset: 0.0, color: black }, GradientStop { offset: 1.0, color: white }],
+    );
+    let mut buffers: VertexBuffers<GradientVertex> = VertexBuffers::new();
+    fill_with_gradient(
+        path.path_iter().flattened(0.05),
+        &gradient,
+        0.01,
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+    assert_eq!(buffers.vertices.len(), 4);
+    for v in &buffers.vertices {
+        assert_eq!(v.color[0], v.position.x / 100.0);
+    }
+
+    // A third stop in the middle.
+    let gradient = Gradient::linear(
+        point(0.0, 0.0),
+        point(100.0, 0.0),
+        vec![
+            GradientStop { offset: 0.0, color: black },
+            GradientStop { offset: 0.5, color: red },
+            GradientStop { offset: 1.0, color: white },
+        ],
+    );
+    let mut buffers: VertexBuffers<GradientVertex> = VertexBuffers::new();
+    fill_with_gradient(
+        path.path_iter().flattened(0.05),
+        &gradient,
+        0.01,
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+    assert!(buffers.vertices.len() > 4);
+    assert!(max_centroid_error(&buffers, &gradient) <= 0.02);
+}
+
+#[test]
+fn test_radial_gradient() {
+    let path = rectangle(10.0, 10.0);
+    let gradient = Gradient::radial(
+        point(5.0, 5.0),
+        5.0,
+        vec![
+            GradientStop { offset: 0.0, color: [1.0, 1.0, 1.0, 1.0] },
+            GradientStop { offset: 1.0, color: [0.0, 0.0, 1.0, 1.0] },
+        ],
+    );
+    let mut buffers: VertexBuffers<GradientVertex> = VertexBuffers::new();
+    fill_with_gradient(
+        path.path_iter().flattened(0.05),
+        &gradient,
+        0.02,
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    assert!(buffers.vertices.len() > 20);
+    assert!(max_centroid_error(&buffers, &gradient) <= 0.04);
+}
@@ -0,0 +1,379 @@
+//! Filling paths with hatch lines.
+//!
+//! Technical drawings, engravings and pen plotters fill shapes with parallel lines instead of
+//! solid colors. [hatch_segments](fn.hatch_segments.html) computes the parts of a set of
+//! parallel lines that are inside of the fill of a path, using the same fill rules as the
+//! fill tessellator. The lines are at a given angle and spacing, optionally crossed with a
+//! second set of lines at a right angle (cross-hatching).
+//!
+//! The segments can be sent to a path builder with [hatch_path](fn.hatch_path.html), to be
+//! stroked or plotted, or tessellated directly into thin quads with
+//! [hatch_quads](fn.hatch_quads.html).
+//!
+//! Each hatch line is intersected with the edges of the path, sorted along the line and
+//! walked while keeping track of the winding number, like the spans of a scan line fill.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use std::f32::consts::PI;
+//! # use lyon_tessellation::hatching::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! let options = HatchingOptions::default()
+//!     .with_angle(PI / 4.0)
+//!     .with_spacing(2.0)
+//!     .with_cross_hatch();
+//!
+//! let mut builder = Path::builder();
+//! hatch_path(path.path_iter().flattened(0.05), &options, &mut builder);
+//! let hatches = builder.build();
+//! # let _ = hatches;
+//! # }
+//! ```
+
+use std::f32::consts::PI;
+
+use math::*;
+use core::{FlattenedEvent, FillRule};
+use path_builder::BaseBuilder;
+use geometry_builder::{GeometryBuilder, Count};
+use FillVertex as Vertex;
+
+/// Parameters for the hatching.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HatchingOptions {
+    /// Angle of the lines in radians, zero being horizontal lines.
+    pub angle: f32,
+
+    /// Distance between the lines.
+    pub spacing: f32,
+
+    /// Distance of the first line from the origin, perpendicularly to the lines. Changing the
+    /// offset shifts the lines without changing the spacing.
+    pub offset: f32,
+
+    /// If true, a second set of lines at a right angle is added.
+    pub cross_hatch: bool,
+
+    /// See the SVG specification.
+    pub fill_rule: FillRule,
+
+    // To be able to add fields without making it a breaking change, add an empty private field
+    // which makes it impossible to create a HatchingOptions without the calling constructor.
+    _private: (),
+}
+
+impl HatchingOptions {
+    pub fn default() -> HatchingOptions {
+        HatchingOptions {
+            angle: 0.0,
+            spacing: 1.0,
+            offset: 0.0,
+            cross_hatch: false,
+            fill_rule: FillRule::EvenOdd,
+            _private: (),
+        }
+    }
+
+    pub fn with_angle(mut self, angle: f32) -> HatchingOptions {
+        self.angle = angle;
+        return self;
+    }
+
+    pub fn with_spacing(mut self, spacing: f32) -> HatchingOptions {
+        self.spacing = spacing;
+        return self;
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> HatchingOptions {
+        self.offset = offset;
+        return self;
+    }
+
+    pub fn with_cross_hatch(mut self) -> HatchingOptions {
+        self.cross_hatch = true;
+        return self;
+    }
+
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> HatchingOptions {
+        self.fill_rule = fill_rule;
+        return self;
+    }
+}
+
+/// A segment of a hatch line.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HatchSegment {
+    pub from: Point,
+    pub to: Point,
+}
+
+/// Computes the segments of the hatch lines that are inside of the fill of a flattened path.
+///
+/// The segments are sorted line by line and along each line. With cross-hatching, the
+/// segments of the second set of lines come after the first ones.
+pub fn hatch_segments<Iter>(path: Iter, options: &HatchingOptions) -> Vec<HatchSegment>
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let polygons = collect_polygons(path);
+    let mut segments = Vec::new();
+    if options.spacing <= 0.0 {
+        return segments;
+    }
+
+    hatch_polygons(&polygons, options.angle, options, &mut segments);
+    if options.cross_hatch {
+        hatch_polygons(&polygons, options.angle + PI * 0.5, options, &mut segments);
+    }
+
+    return segments;
+}
+
+/// Sends the hatch segments to a path builder, each segment being a separate sub-path.
+pub fn hatch_path<Iter, Builder>(path: Iter, options: &HatchingOptions, output: &mut Builder)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Builder: BaseBuilder,
+{
+    for segment in hatch_segments(path, options) {
+        output.move_to(segment.from);
+        output.line_to(segment.to);
+    }
+}
+
+/// Tessellates the hatch segments into quads of width `line_width`.
+///
+/// The normals of the vertices point away from the segment, with a length of one.
+pub fn hatch_quads<Iter, Output>(
+    path: Iter,
+    options: &HatchingOptions,
+    line_width: f32,
+    output: &mut Output,
+) -> Count
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+    Output: GeometryBuilder<Vertex>,
+{
+    output.begin_geometry();
+    for segment in hatch_segments(path, options) {
+        let d = segment.to - segment.from;
+        let length = d.length();
+        if length == 0.0 {
+            continue;
+        }
+        let n = vec2(-d.y, d.x) / length;
+        let offset = n * line_width * 0.5;
+        let a = output.add_vertex(Vertex { position: segment.from + offset, normal: n });
+        let b = output.add_vertex(Vertex { position: segment.from - offset, normal: -n });
+        let c = output.add_vertex(Vertex { position: segment.to - offset, normal: -n });
+        let d = output.add_vertex(Vertex { position: segment.to + offset, normal: n });
+        output.add_triangle(a, b, c);
+        output.add_triangle(a, c, d);
+    }
+
+    return output.end_geometry();
+}
+
+// Hatches with horizontal lines in a space rotated by `-angle`.
+fn hatch_polygons(
+    polygons: &[Vec<Point>],
+    angle: f32,
+    options: &HatchingOptions,
+    output: &mut Vec<HatchSegment>,
+) {
+    let (sin, cos) = angle.sin_cos();
+    let to_hatch_space = |p: Point| -> Point { point(p.x * cos + p.y * sin, -p.x * sin + p.y * cos) };
+    let from_hatch_space = |p: Point| point(p.x * cos - p.y * sin, p.x * sin + p.y * cos);
+
+    let mut edges = Vec::new();
+    let mut min_y = ::std::f32::MAX;
+    let mut max_y = ::std::f32::MIN;
+    for polygon in polygons {
+        let n = polygon.len();
+        for i in 0..n {
+            let a = to_hatch_space(polygon[i]);
+            let b = to_hatch_space(polygon[(i + 1) % n]);
+            if a.y == b.y {
+                continue;
+            }
+            min_y = min_y.min(a.y).min(b.y);
+            max_y = max_y.max(a.y).max(b.y);
+            edges.push((a, b));
+        }
+    }
+    if edges.is_empty() {
+        return;
+    }
+
+    let first_line = ((min_y - options.offset) / options.spacing).ceil() as i64;
+    let last_line = ((max_y - options.offset) / options.spacing).floor() as i64;
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+    for line in first_line..(last_line + 1) {
+        let y = options.offset + line as f32 * options.spacing;
+
+        crossings.clear();
+        for &(a, b) in &edges {
+            // Half-open so that the vertices on the line are counted once.
+            let (winding, crosses) = if a.y < b.y {
+                (1, a.y <= y && y < b.y)
+            } else {
+                (-1, b.y <= y && y < a.y)
+            };
+            if crosses {
+                let x = a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y);
+                crossings.push((x, winding));
+            }
+        }
+        crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let mut winding = 0;
+        let mut start = 0.0;
+        for &(x, w) in &crossings {
+            let was_inside = is_inside(winding, options.fill_rule);
+            winding += w;
+            let inside = is_inside(winding, options.fill_rule);
+            if inside && !was_inside {
+                start = x;
+            } else if was_inside && !inside && x > start {
+                output.push(HatchSegment {
+                    from: from_hatch_space(point(start, y)),
+                    to: from_hatch_space(point(x, y)),
+                });
+            }
+        }
+    }
+}
+
+fn is_inside(winding: i32, fill_rule: FillRule) -> bool {
+    match fill_rule {
+        FillRule::EvenOdd => winding % 2 != 0,
+        FillRule::NonZero => winding != 0,
+    }
+}
+
+fn collect_polygons<Iter: Iterator<Item = FlattenedEvent>>(path: Iter) -> Vec<Vec<Point>> {
+    let mut polygons = Vec::new();
+    let mut current = Vec::new();
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                if current.len() >= 2 {
+                    polygons.push(current.clone());
+                }
+                current.clear();
+                current.push(to);
+            }
+            FlattenedEvent::LineTo(to) => {
+                current.push(to);
+            }
+            FlattenedEvent::Close => {
+                if current.len() >= 2 {
+                    polygons.push(current.clone());
+                }
+                current.clear();
+            }
+        }
+    }
+    if current.len() >= 2 {
+        polygons.push(current);
+    }
+
+    return polygons;
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_iterator::PathIterator;
+#[cfg(test)]
+use geometry_builder::{VertexBuffers, simple_builder};
+
+#[cfg(test)]
+fn square_with_hole() -> Path {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    builder.move_to(point(4.0, 4.0));
+    builder.line_to(point(6.0, 4.0));
+    builder.line_to(point(6.0, 6.0));
+    builder.line_to(point(4.0, 6.0));
+    builder.close();
+
+    return builder.build();
+}
+
+#[test]
+fn test_horizontal_hatching() {
+    let path = square_with_hole();
+    let options = HatchingOptions::default().with_spacing(2.0).with_offset(1.0);
+    let segments = hatch_segments(path.path_iter().flattened(0.05), &options);
+
+    // Lines at y = 1, 3, 5, 7 and 9, the one at y = 5 goes through the hole.
+    assert_eq!(segments.len(), 6);
+    assert_eq!(segments[0], HatchSegment { from: point(0.0, 1.0), to: point(10.0, 1.0) });
+    assert_eq!(segments[2], HatchSegment { from: point(0.0, 5.0), to: point(4.0, 5.0) });
+    assert_eq!(segments[3], HatchSegment { from: point(6.0, 5.0), to: point(10.0, 5.0) });
+    assert_eq!(segments[5], HatchSegment { from: point(0.0, 9.0), to: point(10.0, 9.0) });
+
+    // With the non-zero rule, the hole has the same orientation and is filled.
+    let non_zero = options.with_fill_rule(FillRule::NonZero);
+    assert_eq!(hatch_segments(path.path_iter().flattened(0.05), &non_zero).len(), 5);
+}
+
+#[test]
+fn test_cross_hatching() {
+    let path = square_with_hole();
+    let options = HatchingOptions::default()
+        .with_angle(PI * 0.5)
+        .with_spacing(2.0)
+        .with_offset(1.0)
+        .with_cross_hatch();
+    let segments = hatch_segments(path.path_iter().flattened(0.05), &options);
+    assert_eq!(segments.len(), 12);
+
+    for segment in &segments[0..6] {
+        // Vertical lines.
+        assert!((segment.from.x - segment.to.x).abs() < 0.0001);
+    }
+    for segment in &segments[6..12] {
+        assert!((segment.from.y - segment.to.y).abs() < 0.0001);
+    }
+    let total_length: f32 = segments.iter().map(|s| (s.to - s.from).length()).sum();
+    assert!((total_length - 2.0 * (4.0 * 10.0 + 8.0)).abs() < 0.001);
+}
+
+#[test]
+fn test_hatch_quads() {
+    let path = square_with_hole();
+    let options = HatchingOptions::default().with_spacing(2.0).with_offset(1.0);
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    let count = hatch_quads(path.path_iter().flattened(0.05), &options, 0.5, &mut simple_builder(&mut buffers));
+
+    assert_eq!(count, Count { vertices: 6 * 4, indices: 6 * 6 });
+    for v in &buffers.vertices {
+        let distance_to_line = ((v.position.y - 1.0) / 2.0).fract().abs() * 2.0;
+        assert!((distance_to_line - 0.25).abs() < 0.0001 || (distance_to_line - 1.75).abs() < 0.0001);
+    }
+}
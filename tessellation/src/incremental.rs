@@ -0,0 +1,267 @@
+//! Adding paths to an already tessellated scene.
+//!
+//! Drawing applications typically add one shape at a time to a canvas, and re-tessellating
+//! the whole canvas each time doesn't scale. The sweep line of the fill tessellator is empty
+//! at the end of a tessellation, so there is no sweep state to resume from. What can be
+//! retained is the sorted events and the output of the parts of the scene that a new path
+//! doesn't affect.
+//!
+//! [IncrementalFill](struct.IncrementalFill.html) keeps the scene as a list of groups of
+//! paths. Paths whose bounding rectangles don't overlap can't affect the fill of each other,
+//! so a new path is only tessellated together with the groups that it overlaps. The events of
+//! these groups are kept sorted and merged with the events of the new path in linear time
+//! instead of being sorted again. The other groups are left untouched, so only the geometry
+//! of the last group needs to be uploaded again.
+//!
+//! Overlapping paths are tessellated together and combined with the fill rule, exactly as if
+//! the whole scene was a single path.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::incremental::*;
+//! # fn upload(_geometry: &VertexBuffers<FillVertex>) {}
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let user_strokes = vec![path];
+//! # let options = FillOptions::default();
+//! let mut canvas = IncrementalFill::new();
+//! for stroke in user_strokes {
+//!     canvas.append(stroke.path_iter().flattened(0.1), &options).unwrap();
+//!     upload(canvas.groups().last().unwrap().geometry());
+//! }
+//! # }
+//! ```
+
+use math::*;
+use core::FlattenedEvent;
+use core::input_validation::{ValidatingIter, MAX_FIXED_POINT_COORDINATE};
+use core::math_utils::rects_overlap_with_margin;
+use geometry_builder::{VertexBuffers, Index, simple_builder};
+use path_fill::{FillTessellator, FillOptions, FillError, FillEvents, MAX_FIXED_POINT_GRID};
+use FillVertex as Vertex;
+
+/// A set of overlapping paths tessellated together.
+pub struct FillGroup {
+    events: FillEvents,
+    bounds: Rect,
+    geometry: VertexBuffers<Vertex>,
+}
+
+impl FillGroup {
+    /// The smallest rectangle containing the paths of the group.
+    pub fn bounding_rect(&self) -> Rect { self.bounds }
+
+    /// The tessellation of the paths of the group.
+    pub fn geometry(&self) -> &VertexBuffers<Vertex> { &self.geometry }
+}
+
+/// A fill tessellation that paths can be added to, see the module documentation.
+pub struct IncrementalFill {
+    tessellator: FillTessellator,
+    groups: Vec<FillGroup>,
+}
+
+impl IncrementalFill {
+    pub fn new() -> Self {
+        IncrementalFill {
+            tessellator: FillTessellator::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    /// Adds a path to the scene and tessellates it with the groups that it overlaps.
+    ///
+    /// The resulting group is moved to the end of the list of groups, the order of the other
+    /// groups is preserved. The options should be the same for all of the paths. If the
+    /// tessellation fails, the scene is left unchanged.
+    pub fn append<Iter>(&mut self, path: Iter, options: &FillOptions) -> Result<(), FillError>
+    where
+        Iter: Iterator<Item = FlattenedEvent>,
+    {
+        if let Some(fractional_bits) = options.fixed_point_grid {
+            if fractional_bits > MAX_FIXED_POINT_GRID {
+                return Err(FillError::InvalidFixedPointGrid(fractional_bits));
+            }
+        }
+
+        let mut events = FillEvents::new();
+        if options.validate_input {
+            let mut validating = ValidatingIter::new(path, MAX_FIXED_POINT_COORDINATE);
+            set_events(&mut events, &mut validating, options);
+            if let Some(error) = validating.error() {
+                return Err(FillError::InvalidInput(error));
+            }
+        } else {
+            set_events(&mut events, path, options);
+        }
+
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        // Merging groups makes the rectangle grow, which can make it overlap more groups.
+        let mut bounds = events.bounding_rect();
+        let mut merged = vec![false; self.groups.len()];
+        loop {
+            let mut changed = false;
+            for (i, group) in self.groups.iter().enumerate() {
+                if !merged[i] && rects_overlap_with_margin(&bounds, &group.bounds, 0.0) {
+                    merged[i] = true;
+                    bounds = bounds.union(&group.bounds);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        for (i, group) in self.groups.iter().enumerate() {
+            if merged[i] {
+                events.append(&group.events);
+            }
+        }
+
+        let mut geometry: VertexBuffers<Vertex> = VertexBuffers::new();
+        try!{ self.tessellator.tessellate_events(&events, options, &mut simple_builder(&mut geometry)) };
+
+        let mut i = 0;
+        self.groups.retain(|_| {
+            i += 1;
+            !merged[i - 1]
+        });
+        self.groups.push(FillGroup {
+            bounds: events.bounding_rect(),
+            events: events,
+            geometry: geometry,
+        });
+
+        return Ok(());
+    }
+
+    /// The groups of paths of the scene.
+    pub fn groups(&self) -> &[FillGroup] { &self.groups }
+
+    /// Removes all of the paths.
+    pub fn clear(&mut self) { self.groups.clear(); }
+
+    /// Copies the geometry of all of the groups into a single set of buffers.
+    pub fn build_buffers(&self) -> VertexBuffers<Vertex> {
+        let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+        for group in &self.groups {
+            let offset = buffers.vertices.len() as Index;
+            buffers.vertices.extend_from_slice(&group.geometry.vertices);
+            buffers.indices.extend(group.geometry.indices.iter().map(|&i| i + offset));
+        }
+
+        return buffers;
+    }
+}
+
+fn set_events<Iter>(events: &mut FillEvents, path: Iter, options: &FillOptions)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    if let Some(fractional_bits) = options.fixed_point_grid {
+        events.set_path_iter_on_grid(path, fractional_bits);
+    } else {
+        events.set_path_iter(path);
+    }
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[cfg(test)]
+fn square(x: f32, y: f32, size: f32) -> Path {
+    let mut builder = Path::builder();
+    builder.move_to(point(x, y));
+    builder.line_to(point(x + size, y));
+    builder.line_to(point(x + size, y + size));
+    builder.line_to(point(x, y + size));
+    builder.close();
+
+    return builder.build();
+}
+
+#[cfg(test)]
+fn area(buffers: &VertexBuffers<Vertex>) -> f32 {
+    let mut area = 0.0;
+    for triangle in buffers.indices.chunks(3) {
+        let a = buffers.vertices[triangle[0] as usize].position;
+        let b = buffers.vertices[triangle[1] as usize].position;
+        let c = buffers.vertices[triangle[2] as usize].position;
+        area += ((b - a).cross(c - a) * 0.5).abs();
+    }
+
+    return area;
+}
+
+#[test]
+fn test_incremental_fill() {
+    let options = FillOptions::default();
+    let mut scene = IncrementalFill::new();
+
+    scene.append(square(0.0, 0.0, 10.0).path_iter().flattened(0.1), &options).unwrap();
+    scene.append(square(20.0, 0.0, 10.0).path_iter().flattened(0.1), &options).unwrap();
+    assert_eq!(scene.groups().len(), 2);
+
+    // Overlaps the first square, the intersection is a hole with the even-odd rule.
+    scene.append(square(5.0, 5.0, 10.0).path_iter().flattened(0.1), &options).unwrap();
+    assert_eq!(scene.groups().len(), 2);
+    assert_eq!(scene.groups()[0].bounding_rect(), rect(20.0, 0.0, 10.0, 10.0));
+    assert_eq!(scene.groups()[1].bounding_rect(), rect(0.0, 0.0, 15.0, 15.0));
+    assert_eq!(area(scene.groups()[0].geometry()), 100.0);
+    assert_eq!(area(scene.groups()[1].geometry()), 150.0);
+
+    // Overlaps both groups.
+    scene.append(square(12.0, 12.0, 10.0).path_iter().flattened(0.1), &options).unwrap();
+    assert_eq!(scene.groups().len(), 1);
+
+    // Same result as tessellating everything at once.
+    let mut builder = Path::builder();
+    for &(x, y) in &[(0.0, 0.0), (20.0, 0.0), (5.0, 5.0), (12.0, 12.0)] {
+        builder.move_to(point(x, y));
+        builder.line_to(point(x + 10.0, y));
+        builder.line_to(point(x + 10.0, y + 10.0));
+        builder.line_to(point(x, y + 10.0));
+        builder.close();
+    }
+    let all = builder.build();
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        all.path_iter().flattened(0.1),
+        &options,
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+    assert_eq!(area(&scene.build_buffers()), area(&buffers));
+
+    // Empty paths don't change anything.
+    scene.append(Path::new().path_iter().flattened(0.1), &options).unwrap();
+    assert_eq!(scene.groups().len(), 1);
+
+    scene.clear();
+    assert!(scene.groups().is_empty());
+}
@@ -0,0 +1,225 @@
+//! Tessellating a path once for many instanced draws.
+//!
+//! Scenes with many copies of the same shape, like the markers of a map, don't need to
+//! tessellate and upload the shape for each copy: the geometry can be drawn once per copy
+//! with GPU instancing, each instance having its own transform.
+//! [fill_instanced](fn.fill_instanced.html) and [stroke_instanced](fn.stroke_instanced.html)
+//! tessellate a path once and return the geometry along with the transforms of the
+//! instances, in the order they were given and in a layout that can be uploaded directly as
+//! a per-instance vertex buffer.
+//!
+//! The transforms are not applied to the geometry, the vertex shader is expected to do it:
+//!
+//! ```glsl
+//! in vec2 a_position;
+//! in vec4 a_transform_x; // GpuInstance::x
+//! in vec4 a_transform_y; // GpuInstance::y
+//!
+//! void main() {
+//!     vec3 p = vec3(a_position, 1.0);
+//!     vec2 transformed = vec2(dot(a_transform_x.xyz, p), dot(a_transform_y.xyz, p));
+//!     // ...
+//! }
+//! ```
+//!
+//! Since all of the instances share the geometry, the tolerance of the options is divided by
+//! the largest scale of the transforms, so that the geometry is precise enough for all of
+//! them.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::instancing::*;
+//! # fn upload(_vertices: &[FillVertex], _indices: &[Index], _instances: &[GpuInstance]) {}
+//! # fn draw_instanced(_index_count: usize, _instance_count: usize) {}
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let marker_path = path;
+//! # let markers: Vec<Point> = vec![point(0.0, 0.0), point(100.0, 50.0)];
+//! let transforms: Vec<Transform2d> = markers.iter()
+//!     .map(|marker| Transform2d::create_translation(marker.x, marker.y))
+//!     .collect();
+//! let instanced = fill_instanced(&marker_path, &FillOptions::default(), &transforms).unwrap();
+//! upload(&instanced.geometry.vertices, &instanced.geometry.indices, &instanced.instances);
+//! draw_instanced(instanced.geometry.indices.len(), instanced.instances.len());
+//! # }
+//! ```
+
+use math::*;
+use path::Path;
+use path_iterator::PathIterator;
+use geometry_builder::{VertexBuffers, simple_builder};
+use path_fill::{FillTessellator, FillOptions, FillError};
+use path_stroke::{StrokeTessellator, StrokeOptions};
+use {FillVertex, StrokeVertex};
+
+/// The per-instance data of an instanced draw: the rows of a 2x3 transform, padded to four
+/// components.
+///
+/// The transformed position of a point `p` is `(dot(x.xyz, (p, 1)), dot(y.xyz, (p, 1)))`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GpuInstance {
+    pub x: [f32; 4],
+    pub y: [f32; 4],
+}
+
+impl GpuInstance {
+    pub fn from_transform(transform: &Transform2d) -> Self {
+        GpuInstance {
+            x: [transform.m11, transform.m21, transform.m31, 0.0],
+            y: [transform.m12, transform.m22, transform.m32, 0.0],
+        }
+    }
+
+    pub fn to_transform(&self) -> Transform2d {
+        Transform2d::row_major(
+            self.x[0], self.y[0],
+            self.x[1], self.y[1],
+            self.x[2], self.y[2],
+        )
+    }
+}
+
+/// The geometry shared by the instances, and the instances.
+#[derive(Clone, Debug)]
+pub struct InstancedGeometry<Vertex> {
+    pub geometry: VertexBuffers<Vertex>,
+    pub instances: Vec<GpuInstance>,
+}
+
+/// Fill-tessellates a path once for all of the transforms.
+pub fn fill_instanced(
+    path: &Path,
+    options: &FillOptions,
+    transforms: &[Transform2d],
+) -> Result<InstancedGeometry<FillVertex>, FillError> {
+    let mut options = options.clone();
+    options.tolerance = instance_tolerance(options.tolerance, transforms);
+
+    let mut geometry: VertexBuffers<FillVertex> = VertexBuffers::new();
+    try!{
+        FillTessellator::new().tessellate_path(
+            path.path_iter().flattened(options.tolerance),
+            &options,
+            &mut simple_builder(&mut geometry),
+        )
+    };
+
+    return Ok(InstancedGeometry {
+        geometry: geometry,
+        instances: transforms.iter().map(GpuInstance::from_transform).collect(),
+    });
+}
+
+/// Stroke-tessellates a path once for all of the transforms.
+///
+/// The normals of the vertices are in the space of the path. They must be transformed along
+/// with the positions (without the translation) for the width of the stroke to scale with
+/// the instances, or left alone for the width to stay the same.
+pub fn stroke_instanced(
+    path: &Path,
+    options: &StrokeOptions,
+    transforms: &[Transform2d],
+) -> Result<InstancedGeometry<StrokeVertex>, ()> {
+    let mut options = *options;
+    options.tolerance = instance_tolerance(options.tolerance, transforms);
+
+    let mut geometry: VertexBuffers<StrokeVertex> = VertexBuffers::new();
+    try!{
+        StrokeTessellator::new().tessellate(
+            path.path_iter().flattened(options.tolerance),
+            &options,
+            &mut simple_builder(&mut geometry),
+        )
+    };
+
+    return Ok(InstancedGeometry {
+        geometry: geometry,
+        instances: transforms.iter().map(GpuInstance::from_transform).collect(),
+    });
+}
+
+// The tolerance divided by the largest scale of the transforms, which is the largest
+// singular value of their linear part. Scales of at most one keep the tolerance.
+fn instance_tolerance(tolerance: f32, transforms: &[Transform2d]) -> f32 {
+    let mut max_scale: f32 = 1.0;
+    for t in transforms {
+        let s = t.m11 * t.m11 + t.m12 * t.m12 + t.m21 * t.m21 + t.m22 * t.m22;
+        let det = t.m11 * t.m22 - t.m12 * t.m21;
+        let scale = ((s + (s * s - 4.0 * det * det).max(0.0).sqrt()) * 0.5).sqrt();
+        if scale.is_finite() {
+            max_scale = max_scale.max(scale);
+        }
+    }
+
+    return tolerance / max_scale;
+}
+
+#[cfg(test)]
+use path_builder::{BaseBuilder, PathBuilder};
+
+#[test]
+fn test_gpu_instance() {
+    let transform = Transform2d::row_major(1.0, 2.0, 3.0, 4.0, 5.0, 6.0);
+    let instance = GpuInstance::from_transform(&transform);
+    assert_eq!(instance.to_transform(), transform);
+
+    // The layout matches the way euclid transforms points.
+    let p = point(7.0, 8.0);
+    let expected = transform.transform_point(&p);
+    let x = instance.x[0] * p.x + instance.x[1] * p.y + instance.x[2];
+    let y = instance.y[0] * p.x + instance.y[1] * p.y + instance.y[2];
+    assert_eq!(point(x, y), expected);
+}
+
+#[test]
+fn test_fill_instanced() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.quadratic_bezier_to(point(5.0, -5.0), point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    let small = [Transform2d::create_translation(10.0, 0.0), Transform2d::create_translation(0.0, 10.0)];
+    let instanced = fill_instanced(&path, &FillOptions::default(), &small).unwrap();
+    assert_eq!(instanced.instances.len(), 2);
+    assert_eq!(instanced.instances[1], GpuInstance::from_transform(&small[1]));
+    assert!(!instanced.geometry.indices.is_empty());
+
+    // The geometry is more detailed when an instance is scaled up.
+    let large = [Transform2d::create_translation(10.0, 0.0), Transform2d::create_scale(10.0, 10.0)];
+    let detailed = fill_instanced(&path, &FillOptions::default(), &large).unwrap();
+    assert!(detailed.geometry.vertices.len() > instanced.geometry.vertices.len());
+
+    let stroked = stroke_instanced(&path, &StrokeOptions::default(), &small).unwrap();
+    assert_eq!(stroked.instances, instanced.instances);
+    assert!(!stroked.geometry.indices.is_empty());
+}
+
+#[test]
+fn test_instance_tolerance() {
+    assert_eq!(instance_tolerance(0.1, &[]), 0.1);
+    assert_eq!(instance_tolerance(0.1, &[Transform2d::create_scale(0.5, 0.5)]), 0.1);
+    let rotated = Transform2d::create_rotation(Radians::new(1.0)).post_scale(4.0, 4.0);
+    assert!((instance_tolerance(1.0, &[rotated]) - 0.25).abs() < 0.0001);
+    assert!((instance_tolerance(1.0, &[Transform2d::create_scale(1.0, 8.0)]) - 0.125).abs() < 0.0001);
+}
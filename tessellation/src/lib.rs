@@ -168,11 +168,11 @@
 extern crate lyon_core as core;
 extern crate lyon_path_builder as path_builder;
 extern crate lyon_bezier as bezier;
-
-#[cfg(test)]
 extern crate lyon_path as path;
-#[cfg(test)]
 extern crate lyon_path_iterator as path_iterator;
+extern crate lyon_algorithms as algorithms;
+#[cfg(feature = "parallel")]
+extern crate rayon;
 #[cfg(test)]
 extern crate lyon_extra as extra;
 
@@ -180,6 +180,28 @@ pub mod basic_shapes;
 pub mod path_fill;
 pub mod path_stroke;
 pub mod geometry_builder;
+pub mod glyph_fill;
+pub mod lod_cache;
+pub mod validation;
+pub mod tiling;
+pub mod delaunay;
+pub mod gradient;
+pub mod extrusion;
+pub mod contours;
+pub mod morph;
+pub mod batch;
+pub mod hatching;
+pub mod ear_clipping;
+pub mod convex_decomposition;
+pub mod clipped_fill;
+pub mod incremental;
+pub mod coverage;
+pub mod glow;
+pub mod classified_fill;
+pub mod instancing;
+pub mod monotone_partition;
+
+mod sweep_line;
 
 pub use core::*;
 
@@ -0,0 +1,347 @@
+//! Caching fill tessellations at several levels of detail.
+//!
+//! When zooming in and out of a document, the tolerance threshold needed to render a path
+//! without visible artifacts changes continuously. Re-tessellating everything at each zoom
+//! step is expensive, while always using the finest tolerance wastes a lot of memory and
+//! vertex processing when zoomed out.
+//!
+//! [LodCache](struct.LodCache.html) rounds the requested tolerance down to a power of two
+//! (the level of detail), lazily tessellates each path at the levels that are requested and
+//! keeps the result until the memory budget is exceeded, at which point the least recently
+//! used tessellations are evicted.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use std::collections::HashMap;
+//! # use lyon_tessellation::lod_cache::*;
+//! # fn draw(_geometry: &VertexBuffers<Point>) {}
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let mut paths = HashMap::new();
+//! # paths.insert(0u32, path);
+//! # let zoom = 2.0;
+//! let mut cache = LodCache::new(64 * 1024 * 1024);
+//!
+//! // The tolerance in path space is the tolerance in screen space divided by the zoom.
+//! let tolerance = 0.25 / zoom;
+//! for (id, path) in &paths {
+//!     let geometry = cache.get_or_tessellate(id, tolerance, |t| path.path_iter().flattened(t)).unwrap();
+//!     draw(geometry);
+//! }
+//! # }
+//! ```
+
+use std::cmp;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem::size_of;
+
+use math::*;
+use core::FlattenedEvent;
+use geometry_builder::{VertexBuffers, BuffersBuilder, TypedPositions, Index};
+use path_fill::{FillTessellator, FillOptions, FillError};
+
+struct LodEntry {
+    level: i32,
+    geometry: VertexBuffers<Point>,
+    last_used: u64,
+}
+
+/// Stores the fill tessellation of paths at several levels of detail.
+pub struct LodCache<Key> {
+    entries: HashMap<Key, Vec<LodEntry>>,
+    tessellator: FillTessellator,
+    options: FillOptions,
+    memory_budget: usize,
+    memory_usage: usize,
+    clock: u64,
+}
+
+/// The finest level of detail, with a tolerance of `2^-16`.
+pub const MIN_LOD_LEVEL: i32 = -16;
+
+/// The coarsest level of detail, with a tolerance of `2^16`.
+pub const MAX_LOD_LEVEL: i32 = 16;
+
+/// Returns the level of detail to use for a given tolerance threshold.
+///
+/// The tolerance of a level is `2^level`, and the level is chosen so that its tolerance
+/// is lower or equal to the requested one. The level is clamped between `MIN_LOD_LEVEL` and
+/// `MAX_LOD_LEVEL`, NaN and tolerances that aren't positive give the finest level.
+pub fn lod_level(tolerance: f32) -> i32 {
+    if !(tolerance > 0.0) {
+        return MIN_LOD_LEVEL;
+    }
+
+    return tolerance.log2().floor().max(MIN_LOD_LEVEL as f32).min(MAX_LOD_LEVEL as f32) as i32;
+}
+
+/// Returns the tolerance threshold used to tessellate a given level of detail, clamped
+/// between `MIN_LOD_LEVEL` and `MAX_LOD_LEVEL`.
+pub fn lod_tolerance(level: i32) -> f32 {
+    2.0f32.powi(cmp::max(MIN_LOD_LEVEL, cmp::min(level, MAX_LOD_LEVEL)))
+}
+
+impl<Key: Hash + Eq + Clone> LodCache<Key> {
+    /// Creates a cache which tries to keep the size of the stored geometry below
+    /// `memory_budget` bytes.
+    pub fn new(memory_budget: usize) -> Self {
+        LodCache {
+            entries: HashMap::new(),
+            tessellator: FillTessellator::new(),
+            options: FillOptions::default(),
+            memory_budget: memory_budget,
+            memory_usage: 0,
+            clock: 0,
+        }
+    }
+
+    /// Returns the geometry of a path at the level of detail corresponding to the provided
+    /// tolerance, tessellating it if it isn't in the cache.
+    ///
+    /// The path callback receives the tolerance of the level of detail and is only invoked if
+    /// the path needs to be tessellated.
+    ///
+    /// If a single tessellation exceeds the memory budget, it is stored anyway after evicting
+    /// everything else.
+    pub fn get_or_tessellate<Iter, F>(
+        &mut self,
+        key: &Key,
+        tolerance: f32,
+        path: F,
+    ) -> Result<&VertexBuffers<Point>, FillError>
+    where
+        Iter: Iterator<Item = FlattenedEvent>,
+        F: FnOnce(f32) -> Iter,
+    {
+        self.clock += 1;
+        let level = lod_level(tolerance);
+
+        let cached = match self.entries.get(key) {
+            Some(lods) => lods.iter().any(|lod| lod.level == level),
+            None => false,
+        };
+
+        if !cached {
+            let tolerance = lod_tolerance(level);
+            self.options.tolerance = tolerance;
+            let mut geometry = VertexBuffers::new();
+            try!{
+                self.tessellator.tessellate_path(
+                    path(tolerance),
+                    &self.options,
+                    &mut BuffersBuilder::new(&mut geometry, TypedPositions::new())
+                )
+            };
+
+            let size = geometry_size(&geometry);
+            self.evict(size);
+            self.memory_usage += size;
+
+            self.entries.entry(key.clone()).or_insert_with(Vec::new).push(LodEntry {
+                level: level,
+                geometry: geometry,
+                last_used: 0,
+            });
+        }
+
+        let clock = self.clock;
+        let lod = self.entries.get_mut(key).unwrap().iter_mut().find(|lod| lod.level == level).unwrap();
+        lod.last_used = clock;
+
+        return Ok(&lod.geometry);
+    }
+
+    /// Returns the cached geometry that is the most appropriate for the provided tolerance,
+    /// without tessellating anything.
+    ///
+    /// This is the least precise level that is at least as precise as requested if any,
+    /// otherwise the most precise level available.
+    pub fn get_closest(&self, key: &Key, tolerance: f32) -> Option<&VertexBuffers<Point>> {
+        let level = lod_level(tolerance);
+        let lods = match self.entries.get(key) {
+            Some(lods) => lods,
+            None => { return None; }
+        };
+
+        let mut best: Option<&LodEntry> = None;
+        for lod in lods {
+            best = match best {
+                None => Some(lod),
+                Some(b) => {
+                    let better = if lod.level <= level {
+                        b.level > level || lod.level > b.level
+                    } else {
+                        b.level > level && lod.level < b.level
+                    };
+                    if better { Some(lod) } else { Some(b) }
+                }
+            };
+        }
+
+        return best.map(|lod| &lod.geometry);
+    }
+
+    /// Removes all levels of detail of a path.
+    pub fn remove(&mut self, key: &Key) {
+        if let Some(lods) = self.entries.remove(key) {
+            for lod in &lods {
+                self.memory_usage -= geometry_size(&lod.geometry);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.memory_usage = 0;
+    }
+
+    /// The size in bytes of the stored geometry.
+    pub fn memory_usage(&self) -> usize { self.memory_usage }
+
+    pub fn memory_budget(&self) -> usize { self.memory_budget }
+
+    /// Changes the memory budget, evicting the least recently used geometry if needed.
+    pub fn set_memory_budget(&mut self, budget: usize) {
+        self.memory_budget = budget;
+        self.evict(0);
+    }
+
+    /// The number of paths that have at least one level of detail in the cache.
+    pub fn len(&self) -> usize { self.entries.len() }
+
+    // Evicts the least recently used levels of detail until `additional_size` bytes
+    // fit in the budget.
+    fn evict(&mut self, additional_size: usize) {
+        while self.memory_usage + additional_size > self.memory_budget && !self.entries.is_empty() {
+            let mut oldest: Option<(Key, usize, u64)> = None;
+            for (key, lods) in &self.entries {
+                for (idx, lod) in lods.iter().enumerate() {
+                    let older = match oldest {
+                        Some((_, _, last_used)) => lod.last_used < last_used,
+                        None => true,
+                    };
+                    if older {
+                        oldest = Some((key.clone(), idx, lod.last_used));
+                    }
+                }
+            }
+
+            let (key, idx, _) = oldest.unwrap();
+            let remove_key = {
+                let lods = self.entries.get_mut(&key).unwrap();
+                let lod = lods.swap_remove(idx);
+                self.memory_usage -= geometry_size(&lod.geometry);
+                lods.is_empty()
+            };
+            if remove_key {
+                self.entries.remove(&key);
+            }
+        }
+    }
+}
+
+fn geometry_size(geometry: &VertexBuffers<Point>) -> usize {
+    geometry.vertices.len() * size_of::<Point>() + geometry.indices.len() * size_of::<Index>()
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+#[cfg(test)]
+use path_builder::PathBuilder;
+
+#[cfg(test)]
+fn circle_path() -> Path {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, -100.0));
+    builder.quadratic_bezier_to(point(100.0, -100.0), point(100.0, 0.0));
+    builder.quadratic_bezier_to(point(100.0, 100.0), point(0.0, 100.0));
+    builder.quadratic_bezier_to(point(-100.0, 100.0), point(-100.0, 0.0));
+    builder.quadratic_bezier_to(point(-100.0, -100.0), point(0.0, -100.0));
+    builder.close();
+    return builder.build();
+}
+
+#[test]
+fn test_lod_levels() {
+    assert_eq!(lod_level(1.0), 0);
+    assert_eq!(lod_level(0.3), -2);
+    assert_eq!(lod_level(5.0), 2);
+    assert_eq!(lod_tolerance(-2), 0.25);
+
+    assert_eq!(lod_level(0.0), MIN_LOD_LEVEL);
+    assert_eq!(lod_level(-1.0), MIN_LOD_LEVEL);
+    assert_eq!(lod_level(::std::f32::NAN), MIN_LOD_LEVEL);
+    assert_eq!(lod_level(1.0e-30), MIN_LOD_LEVEL);
+    assert_eq!(lod_level(::std::f32::INFINITY), MAX_LOD_LEVEL);
+    assert_eq!(lod_tolerance(i32::min_value()), lod_tolerance(MIN_LOD_LEVEL));
+    assert!(lod_tolerance(MIN_LOD_LEVEL) > 0.0);
+}
+
+#[test]
+fn test_lod_cache() {
+    let path = circle_path();
+    let mut cache: LodCache<u32> = LodCache::new(1024 * 1024);
+    let mut calls = 0;
+
+    let coarse = cache.get_or_tessellate(&0, 4.0, |t| { calls += 1; path.path_iter().flattened(t) })
+        .unwrap().indices.len();
+    let fine = cache.get_or_tessellate(&0, 0.01, |t| { calls += 1; path.path_iter().flattened(t) })
+        .unwrap().indices.len();
+    assert!(fine > coarse);
+
+    // Same level of detail as 4.0.
+    cache.get_or_tessellate(&0, 5.0, |t| { calls += 1; path.path_iter().flattened(t) }).unwrap();
+    assert_eq!(calls, 2);
+
+    // 1.0 is not cached, the closest more precise level is 0.01.
+    assert_eq!(cache.get_closest(&0, 1.0).unwrap().indices.len(), fine);
+    // Nothing is coarser than 100 so we get the coarsest level.
+    assert_eq!(cache.get_closest(&0, 100.0).unwrap().indices.len(), coarse);
+    assert!(cache.get_closest(&1, 1.0).is_none());
+}
+
+#[test]
+fn test_lod_cache_eviction() {
+    let path = circle_path();
+    let mut cache: LodCache<u32> = LodCache::new(1024 * 1024);
+
+    cache.get_or_tessellate(&0, 1.0, |t| path.path_iter().flattened(t)).unwrap();
+    let size = cache.memory_usage();
+    assert!(size > 0);
+
+    // Room for two tessellations.
+    cache.set_memory_budget(size * 2 + size / 2);
+    cache.get_or_tessellate(&1, 1.0, |t| path.path_iter().flattened(t)).unwrap();
+    cache.get_or_tessellate(&0, 1.0, |t| path.path_iter().flattened(t)).unwrap();
+    cache.get_or_tessellate(&2, 1.0, |t| path.path_iter().flattened(t)).unwrap();
+
+    // Path 1 was the least recently used.
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get_closest(&0, 1.0).is_some());
+    assert!(cache.get_closest(&1, 1.0).is_none());
+    assert!(cache.get_closest(&2, 1.0).is_some());
+    assert_eq!(cache.memory_usage(), size * 2);
+}
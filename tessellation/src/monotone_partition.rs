@@ -0,0 +1,435 @@
+//! Partition of polygons into y-monotone pieces.
+//!
+//! The fill tessellator splits the fill into y-monotone polygons and triangulates them on the
+//! fly. Renderers that rasterize the monotone polygons directly (for example span by span,
+//! since each horizontal line crosses a y-monotone polygon in a single interval) only need
+//! the decomposition, which this module produces without triangulating.
+//!
+//! The partition is computed with a sweep line going along increasing y that adds a diagonal
+//! below each vertex where the boundary splits and above each vertex where it merges. Holes
+//! are supported, the rings being nested with the even-odd rule. The result is a list of
+//! polygons given as indices into the input points, so that the attributes of the vertices
+//! can be looked up by the renderer.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::monotone_partition::*;
+//! # fn rasterize_monotone(_points: &[Point], _polygon: &[usize]) {}
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! let partition = monotone_partition_path(path.path_iter().flattened(0.05));
+//!
+//! for polygon in &partition.polygons {
+//!     rasterize_monotone(&partition.points, &polygon[..]);
+//! }
+//! # }
+//! ```
+
+use std::f32::consts::PI;
+use std::ops::Range;
+use std::collections::HashSet;
+use std::usize;
+
+use math::*;
+use core::FlattenedEvent;
+
+/// The points of a path and their partition into y-monotone polygons.
+#[derive(Clone, Debug)]
+pub struct MonotonePartition {
+    pub points: Vec<Point>,
+    pub polygons: Vec<Vec<usize>>,
+}
+
+/// Partitions the fill of a path into y-monotone polygons, with the even-odd fill rule.
+///
+/// The points of the result are the end points of the path's events, in order.
+pub fn monotone_partition_path<Iter: Iterator<Item = FlattenedEvent>>(path: Iter) -> MonotonePartition {
+    let mut points = Vec::new();
+    let mut rings = Vec::new();
+    let mut ring_start = 0;
+    for event in path {
+        match event {
+            FlattenedEvent::MoveTo(to) => {
+                rings.push(ring_start..points.len());
+                ring_start = points.len();
+                points.push(to);
+            }
+            FlattenedEvent::LineTo(to) => {
+                points.push(to);
+            }
+            FlattenedEvent::Close => {
+                rings.push(ring_start..points.len());
+                ring_start = points.len();
+            }
+        }
+    }
+    rings.push(ring_start..points.len());
+
+    let polygons = monotone_partition(&points, &rings);
+
+    return MonotonePartition {
+        points: points,
+        polygons: polygons,
+    };
+}
+
+/// Partitions polygons into y-monotone polygons.
+///
+/// Each range of `rings` is a closed ring of `points`, the rings are nested with the even-odd
+/// rule and can be oriented either way. The rings must not cross each other or themselves.
+/// Consecutive duplicate points are skipped and rings with less than three distinct points
+/// are ignored.
+///
+/// The polygons are returned as lists of indices into `points`, counter-clockwise (with the
+/// y axis pointing up).
+pub fn monotone_partition(points: &[Point], rings: &[Range<usize>]) -> Vec<Vec<usize>> {
+    let mut ring_vertices: Vec<Vec<usize>> = Vec::new();
+    for ring in rings {
+        let mut vertices: Vec<usize> = Vec::new();
+        for i in ring.clone() {
+            if vertices.last().map(|&prev| points[prev]) != Some(points[i]) {
+                vertices.push(i);
+            }
+        }
+        while vertices.len() > 1 && points[vertices[0]] == points[*vertices.last().unwrap()] {
+            vertices.pop();
+        }
+        if vertices.len() >= 3 {
+            ring_vertices.push(vertices);
+        }
+    }
+
+    // The algorithm is written as usual with the sweep line going down (with the y axis
+    // pointing up), so it works with the points mirrored along the x axis.
+    let q: Vec<Point> = points.iter().map(|p| point(p.x, -p.y)).collect();
+
+    // Orient the outlines counter-clockwise and the holes clockwise, so that the interior is
+    // on the left of the edges.
+    let mut next = vec![usize::MAX; points.len()];
+    let mut prev = vec![usize::MAX; points.len()];
+    for (r, vertices) in ring_vertices.iter().enumerate() {
+        let mut depth = 0;
+        for (other, other_vertices) in ring_vertices.iter().enumerate() {
+            if other != r && ring_contains(&q, other_vertices, q[vertices[0]]) {
+                depth += 1;
+            }
+        }
+        let counter_clockwise = signed_area(&q, vertices) > 0.0;
+        let reverse = counter_clockwise != (depth % 2 == 0);
+        let n = vertices.len();
+        for i in 0..n {
+            let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+            let (a, b) = if reverse { (b, a) } else { (a, b) };
+            next[a] = b;
+            prev[b] = a;
+        }
+    }
+
+    let mut events: Vec<usize> = ring_vertices.iter().flat_map(|v| v.iter().cloned()).collect();
+    events.sort_by(|&a, &b| {
+        if is_above(&q, a, b) {
+            ::std::cmp::Ordering::Less
+        } else if is_above(&q, b, a) {
+            ::std::cmp::Ordering::Greater
+        } else {
+            ::std::cmp::Ordering::Equal
+        }
+    });
+
+    let mut kinds = vec![VertexKind::Regular; points.len()];
+    for &v in &events {
+        let (p, n) = (prev[v], next[v]);
+        let convex = (q[v] - q[p]).cross(q[n] - q[v]) > 0.0;
+        kinds[v] = match (is_above(&q, p, v), is_above(&q, n, v)) {
+            (false, false) => if convex { VertexKind::Start } else { VertexKind::Split },
+            (true, true) => if convex { VertexKind::End } else { VertexKind::Merge },
+            _ => VertexKind::Regular,
+        };
+    }
+
+    // The sweep status contains the edges (identified by the index of their first vertex)
+    // that have the interior of the polygon on their right.
+    let mut status: Vec<usize> = Vec::new();
+    let mut helper = vec![usize::MAX; points.len()];
+    let mut diagonals: Vec<(usize, usize)> = Vec::new();
+    for &v in &events {
+        let p = prev[v];
+        match kinds[v] {
+            VertexKind::Start => {
+                status.push(v);
+                helper[v] = v;
+            }
+            VertexKind::End => {
+                if kinds[helper[p]] == VertexKind::Merge {
+                    diagonals.push((v, helper[p]));
+                }
+                status.retain(|&e| e != p);
+            }
+            VertexKind::Split => {
+                if let Some(left) = left_edge(&q, &next, &status, v) {
+                    diagonals.push((v, helper[left]));
+                    helper[left] = v;
+                }
+                status.push(v);
+                helper[v] = v;
+            }
+            VertexKind::Merge => {
+                if kinds[helper[p]] == VertexKind::Merge {
+                    diagonals.push((v, helper[p]));
+                }
+                status.retain(|&e| e != p);
+                if let Some(left) = left_edge(&q, &next, &status, v) {
+                    if kinds[helper[left]] == VertexKind::Merge {
+                        diagonals.push((v, helper[left]));
+                    }
+                    helper[left] = v;
+                }
+            }
+            VertexKind::Regular => {
+                if is_above(&q, p, v) {
+                    // The interior is on the right of the vertex.
+                    if kinds[helper[p]] == VertexKind::Merge {
+                        diagonals.push((v, helper[p]));
+                    }
+                    status.retain(|&e| e != p);
+                    status.push(v);
+                    helper[v] = v;
+                } else if let Some(left) = left_edge(&q, &next, &status, v) {
+                    if kinds[helper[left]] == VertexKind::Merge {
+                        diagonals.push((v, helper[left]));
+                    }
+                    helper[left] = v;
+                }
+            }
+        }
+    }
+
+    return split_faces(&q, &events, &next, &diagonals);
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum VertexKind {
+    Start,
+    End,
+    Split,
+    Merge,
+    Regular,
+}
+
+// Whether a is visited before b by the sweep line.
+fn is_above(q: &[Point], a: usize, b: usize) -> bool {
+    q[a].y > q[b].y || (q[a].y == q[b].y && q[a].x < q[b].x)
+}
+
+// The edge of the status that is the closest to the left of the vertex.
+fn left_edge(q: &[Point], next: &[usize], status: &[usize], v: usize) -> Option<usize> {
+    let p = q[v];
+    let mut result = None;
+    let mut best_x = 0.0;
+    for &e in status {
+        let (a, b) = (q[e], q[next[e]]);
+        let x = if a.y == b.y {
+            a.x.max(b.x)
+        } else {
+            a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y)
+        };
+        if x <= p.x && (result.is_none() || x > best_x) {
+            result = Some(e);
+            best_x = x;
+        }
+    }
+
+    return result;
+}
+
+// Walks the faces on the left of the edges and diagonals. At each vertex the walk takes the
+// first edge clockwise from the one it arrived with, which keeps the face on its left.
+fn split_faces(q: &[Point], vertices: &[usize], next: &[usize], diagonals: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); q.len()];
+    for &v in vertices {
+        outgoing[v].push(next[v]);
+    }
+    for &(a, b) in diagonals {
+        outgoing[a].push(b);
+        outgoing[b].push(a);
+    }
+
+    let mut used: HashSet<(usize, usize)> = HashSet::new();
+    let mut faces = Vec::new();
+    for &start in vertices {
+        for &first in &outgoing[start] {
+            if used.contains(&(start, first)) {
+                continue;
+            }
+
+            let mut face = Vec::new();
+            let (mut a, mut b) = (start, first);
+            loop {
+                used.insert((a, b));
+                face.push(a);
+
+                let back = q[a] - q[b];
+                let mut best = a;
+                let mut best_angle = 0.0;
+                for &c in &outgoing[b] {
+                    let d = q[c] - q[b];
+                    let mut angle = d.cross(back).atan2(d.dot(back));
+                    if angle <= 0.0 {
+                        angle += 2.0 * PI;
+                    }
+                    if best_angle == 0.0 || angle < best_angle {
+                        best = c;
+                        best_angle = angle;
+                    }
+                }
+
+                a = b;
+                b = best;
+                if (a, b) == (start, first) || used.contains(&(a, b)) {
+                    break;
+                }
+            }
+
+            // Counter-clockwise when mirrored back.
+            face.reverse();
+            faces.push(face);
+        }
+    }
+
+    return faces;
+}
+
+// Positive if the ring turns counter-clockwise (with the y axis pointing up).
+fn signed_area(q: &[Point], ring: &[usize]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..ring.len() {
+        let a = q[ring[i]];
+        let b = q[ring[(i + 1) % ring.len()]];
+        area += a.x * b.y - b.x * a.y;
+    }
+
+    return area * 0.5;
+}
+
+fn ring_contains(q: &[Point], ring: &[usize], p: Point) -> bool {
+    let mut inside = false;
+    for i in 0..ring.len() {
+        let a = q[ring[i]];
+        let b = q[ring[(i + 1) % ring.len()]];
+        if (a.y > p.y) != (b.y > p.y) {
+            let x = a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y);
+            if x > p.x {
+                inside = !inside;
+            }
+        }
+    }
+
+    return inside;
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+
+#[cfg(test)]
+fn check_partition(points: &[Point], polygons: &[Vec<usize>], expected_area: f32) {
+    let mut total_area = 0.0;
+    for polygon in polygons {
+        let mut area = 0.0;
+        // A y-monotone polygon goes down then up, so the direction along the y axis
+        // changes at most twice.
+        let mut changes = 0;
+        let mut last_direction = 0.0;
+        let n = polygon.len();
+        for i in 0..(2 * n) {
+            let a = points[polygon[i % n]];
+            let b = points[polygon[(i + 1) % n]];
+            if i < n {
+                area += a.x * b.y - b.x * a.y;
+            }
+            let direction = (b.y - a.y).signum();
+            if b.y != a.y {
+                if last_direction != 0.0 && direction != last_direction && i >= n {
+                    changes += 1;
+                }
+                last_direction = direction;
+            }
+        }
+        assert!(changes <= 2, "{:?} is not monotone", polygon);
+        assert!(area > 0.0);
+        total_area += area * 0.5;
+    }
+    assert_eq!(total_area, expected_area);
+}
+
+#[test]
+fn test_monotone_partition_notch() {
+    // A square with a notch coming up from the bottom edge.
+    let points = [
+        point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(6.0, 10.0),
+        point(5.0, 5.0), point(4.0, 10.0), point(0.0, 10.0),
+    ];
+    let polygons = monotone_partition(&points, &[0..7]);
+    assert_eq!(polygons.len(), 2);
+    check_partition(&points, &polygons, 95.0);
+
+    // The orientation of the input doesn't matter.
+    let reversed: Vec<Point> = points.iter().rev().cloned().collect();
+    let polygons = monotone_partition(&reversed, &[0..7]);
+    assert_eq!(polygons.len(), 2);
+    check_partition(&reversed, &polygons, 95.0);
+
+    // A convex polygon is left as is.
+    let polygons = monotone_partition(&points[..3], &[0..3]);
+    assert_eq!(polygons.len(), 1);
+    check_partition(&points, &polygons, 50.0);
+}
+
+#[test]
+fn test_monotone_partition_hole() {
+    let points = [
+        point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0),
+        point(4.0, 4.0), point(6.0, 4.0), point(6.0, 6.0), point(4.0, 6.0),
+    ];
+    let polygons = monotone_partition(&points, &[0..4, 4..8]);
+    assert_eq!(polygons.len(), 2);
+    check_partition(&points, &polygons, 96.0);
+}
+
+#[test]
+fn test_monotone_partition_path() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(5.0, 5.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    let path = builder.build();
+
+    let partition = monotone_partition_path(path.path_iter().flattened(0.1));
+    assert_eq!(partition.points.len(), 5);
+    assert_eq!(partition.polygons.len(), 1);
+    check_partition(&partition.points, &partition.polygons, 75.0);
+}
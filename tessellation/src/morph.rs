@@ -0,0 +1,413 @@
+//! Tessellating animated morphs between paths.
+//!
+//! Two paths are compatible if they are made of the same sequence of verbs, for example two
+//! versions of an icon drawn with the same number of segments. Interpolating their points
+//! gives the intermediate frames of a morph between them.
+//!
+//! [MorphTessellator](struct.MorphTessellator.html) interpolates and fill-tessellates the
+//! frames. When topology reuse is enabled, it remembers the triangles of the last frame in
+//! terms of the points of the path, and reuses them for the next frames as long as:
+//!
+//! - the flattened path has the same number of points,
+//! - the points are still in the same order along the sweep line of the fill tessellator,
+//! - none of the triangles is flipped or degenerate.
+//!
+//! In this case the fill tessellator would go through the same events and produce the same
+//! monotone polygons, so only the positions of the vertices need to be updated. The triangles
+//! are computed again as soon as one of the conditions fails. The cache isn't used when the
+//! fill tessellator adds vertices (for example at self-intersections), and it doesn't detect
+//! parts of the shape moving over each other without changing the order of the events, which
+//! is why it is an option.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::morph::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let from = path.clone();
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(20.0, 0.0));
+//! # builder.line_to(point(20.0, 5.0));
+//! # builder.line_to(point(0.0, 5.0));
+//! # builder.close();
+//! # let to = builder.build();
+//! let mut tessellator = MorphTessellator::new().with_topology_reuse(true);
+//! for frame in 0..60 {
+//!     let mut buffers: VertexBuffers<FillVertex> = VertexBuffers::new();
+//!     tessellator.tessellate_morph(
+//!         &from,
+//!         &to,
+//!         frame as f32 / 59.0,
+//!         &FillOptions::default(),
+//!         &mut simple_builder(&mut buffers),
+//!     ).unwrap();
+//!     // ...
+//! }
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use math::*;
+use core::{FlattenedEvent, FillRule};
+use path::Path;
+use path_iterator::PathIterator;
+use geometry_builder::{GeometryBuilder, Count, VertexBuffers, BuffersBuilder, TypedPositions, VertexId, Index};
+use path_fill::{FillTessellator, FillOptions, FillError};
+use FillVertex as Vertex;
+
+/// The morph tessellator's error enumeration.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MorphError {
+    /// The two paths don't have the same verbs.
+    IncompatiblePaths,
+    Fill(FillError),
+}
+
+pub type MorphResult = Result<Count, MorphError>;
+
+/// Interpolates between two compatible paths, returns `None` if the paths don't have the
+/// same verbs.
+pub fn interpolate_paths(from: &Path, to: &Path, t: f32) -> Option<Path> {
+    if from.verbs() != to.verbs() || from.points().len() != to.points().len() {
+        return None;
+    }
+
+    let mut path = from.clone();
+    for (p, &q) in path.mut_points().iter_mut().zip(to.points().iter()) {
+        *p = p.lerp(q, t);
+    }
+
+    return Some(path);
+}
+
+/// Interpolates and fill-tessellates the frames of a morph between two paths.
+///
+/// See the [module documentation](index.html).
+pub struct MorphTessellator {
+    tessellator: FillTessellator,
+    reuse_topology: bool,
+    topology: Option<Topology>,
+    reused: bool,
+    events: Vec<FlattenedEvent>,
+    points: Vec<Point>,
+    fill: VertexBuffers<Point>,
+}
+
+// The result of the last tessellation in terms of the points of the flattened path.
+struct Topology {
+    fill_rule: FillRule,
+    tolerance: f32,
+    num_points: usize,
+    // The points in the order of the sweep line.
+    order: Vec<usize>,
+    // The point of each vertex of the output.
+    vertices: Vec<usize>,
+    // Indices in `vertices`, counter-clockwise.
+    triangles: Vec<[Index; 3]>,
+}
+
+impl MorphTessellator {
+    pub fn new() -> Self {
+        MorphTessellator {
+            tessellator: FillTessellator::new(),
+            reuse_topology: false,
+            topology: None,
+            reused: false,
+            events: Vec::new(),
+            points: Vec::new(),
+            fill: VertexBuffers::new(),
+        }
+    }
+
+    /// Reuse the triangles of the previous frame when the topology of the fill didn't change.
+    pub fn with_topology_reuse(mut self, reuse: bool) -> Self {
+        self.reuse_topology = reuse;
+        if !reuse {
+            self.topology = None;
+        }
+        return self;
+    }
+
+    /// Whether the last frame reused the triangles of the previous one.
+    pub fn reused_topology(&self) -> bool { self.reused }
+
+    /// Forgets the triangles of the previous frame.
+    pub fn invalidate(&mut self) {
+        self.topology = None;
+    }
+
+    /// Interpolates between `from` and `to` and fill-tessellates the result.
+    pub fn tessellate_morph<Output>(
+        &mut self,
+        from: &Path,
+        to: &Path,
+        t: f32,
+        options: &FillOptions,
+        output: &mut Output,
+    ) -> MorphResult
+    where
+        Output: GeometryBuilder<Vertex>,
+    {
+        let path = match interpolate_paths(from, to, t) {
+            Some(path) => path,
+            None => { return Err(MorphError::IncompatiblePaths); }
+        };
+
+        self.events.clear();
+        self.points.clear();
+        for evt in path.path_iter().flattened(options.tolerance) {
+            match evt {
+                FlattenedEvent::MoveTo(p) | FlattenedEvent::LineTo(p) => { self.points.push(p); }
+                FlattenedEvent::Close => {}
+            }
+            self.events.push(evt);
+        }
+
+        self.reused = false;
+        if self.reuse_topology && self.can_reuse(options) {
+            self.reused = true;
+            return Ok(self.emit_cached(output));
+        }
+
+        self.fill.vertices.clear();
+        self.fill.indices.clear();
+        let result = self.tessellator.tessellate_path(
+            self.events.iter().cloned(),
+            options,
+            &mut BuffersBuilder::new(&mut self.fill, TypedPositions::new()),
+        );
+        if let Err(e) = result {
+            self.topology = None;
+            return Err(MorphError::Fill(e));
+        }
+
+        self.topology = if self.reuse_topology { self.build_topology(options) } else { None };
+
+        output.begin_geometry();
+        for &position in &self.fill.vertices {
+            output.add_vertex(Vertex { position: position, normal: vec2(0.0, 0.0) });
+        }
+        for triangle in self.fill.indices.chunks(3) {
+            output.add_triangle(VertexId(triangle[0]), VertexId(triangle[1]), VertexId(triangle[2]));
+        }
+
+        return Ok(output.end_geometry());
+    }
+
+    fn can_reuse(&self, options: &FillOptions) -> bool {
+        let topology = match self.topology {
+            Some(ref topology) => topology,
+            None => { return false; }
+        };
+
+        if topology.num_points != self.points.len() ||
+            topology.fill_rule != options.fill_rule ||
+            topology.tolerance != options.tolerance {
+            return false;
+        }
+
+        for pair in topology.order.windows(2) {
+            if compare_positions(self.points[pair[0]], self.points[pair[1]]) != Ordering::Less {
+                return false;
+            }
+        }
+
+        for triangle in &topology.triangles {
+            let a = self.points[topology.vertices[triangle[0] as usize]];
+            let b = self.points[topology.vertices[triangle[1] as usize]];
+            let c = self.points[topology.vertices[triangle[2] as usize]];
+            if (b - a).cross(c - a) <= 0.0 {
+                return false;
+            }
+        }
+
+        return true;
+    }
+
+    fn emit_cached<Output: GeometryBuilder<Vertex>>(&self, output: &mut Output) -> Count {
+        let topology = self.topology.as_ref().unwrap();
+        output.begin_geometry();
+        for &point_idx in &topology.vertices {
+            output.add_vertex(Vertex { position: self.points[point_idx], normal: vec2(0.0, 0.0) });
+        }
+        for triangle in &topology.triangles {
+            output.add_triangle(VertexId(triangle[0]), VertexId(triangle[1]), VertexId(triangle[2]));
+        }
+
+        return output.end_geometry();
+    }
+
+    // Maps the output of the fill tessellator back to the points of the path, fails if the
+    // tessellator added or merged vertices.
+    fn build_topology(&self, options: &FillOptions) -> Option<Topology> {
+        if options.fixed_point_grid.is_some() {
+            return None;
+        }
+
+        let mut points_by_position: HashMap<(u32, u32), usize> = HashMap::new();
+        for (i, &p) in self.points.iter().enumerate() {
+            if points_by_position.insert(key(snap(p)), i).is_some() {
+                // Two points at the same position.
+                return None;
+            }
+        }
+
+        let mut vertices = Vec::with_capacity(self.fill.vertices.len());
+        for &position in &self.fill.vertices {
+            match points_by_position.get(&key(position)) {
+                Some(&i) => { vertices.push(i); }
+                None => { return None; }
+            }
+        }
+
+        let mut triangles = Vec::with_capacity(self.fill.indices.len() / 3);
+        for triangle in self.fill.indices.chunks(3) {
+            let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+            let (pa, pb, pc) = (
+                self.points[vertices[a as usize]],
+                self.points[vertices[b as usize]],
+                self.points[vertices[c as usize]],
+            );
+            let area = (pb - pa).cross(pc - pa);
+            if area > 0.0 {
+                triangles.push([a, b, c]);
+            } else if area < 0.0 {
+                triangles.push([a, c, b]);
+            } else {
+                return None;
+            }
+        }
+
+        let mut order: Vec<usize> = (0..self.points.len()).collect();
+        order.sort_by(|&a, &b| compare_positions(self.points[a], self.points[b]));
+
+        return Some(Topology {
+            fill_rule: options.fill_rule,
+            tolerance: options.tolerance,
+            num_points: self.points.len(),
+            order: order,
+            vertices: vertices,
+            triangles: triangles,
+        });
+    }
+}
+
+// The order of the events of the fill tessellator.
+fn compare_positions(a: Point, b: Point) -> Ordering {
+    if a.y != b.y {
+        return a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal);
+    }
+
+    return a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal);
+}
+
+// The position of a point after going through the fixed point representation of the fill
+// tessellator.
+fn snap(p: Point) -> Point { point(fixed(p.x).to_f32(), fixed(p.y).to_f32()) }
+
+fn key(p: Point) -> (u32, u32) { (p.x.to_bits(), p.y.to_bits()) }
+
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use geometry_builder::simple_builder;
+
+#[cfg(test)]
+fn quad(a: Point, b: Point, c: Point, d: Point) -> Path {
+    let mut builder = Path::builder();
+    builder.move_to(a);
+    builder.line_to(b);
+    builder.line_to(c);
+    builder.line_to(d);
+    builder.close();
+
+    return builder.build();
+}
+
+#[test]
+fn test_interpolate_paths() {
+    let from = quad(point(0.0, 0.0), point(2.0, 0.0), point(2.0, 2.0), point(0.0, 2.0));
+    let to = quad(point(0.0, 0.0), point(4.0, 0.0), point(4.0, 4.0), point(0.0, 4.0));
+    let middle = interpolate_paths(&from, &to, 0.5).unwrap();
+    assert_eq!(middle.points(), &[point(0.0, 0.0), point(3.0, 0.0), point(3.0, 3.0), point(0.0, 3.0)]);
+
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.line_to(point(1.0, 1.0));
+    builder.close();
+    assert!(interpolate_paths(&from, &builder.build(), 0.5).is_none());
+}
+
+#[test]
+fn test_morph_reuse() {
+    let from = quad(point(0.0, 0.0), point(4.0, 0.5), point(4.0, 4.0), point(0.0, 3.0));
+    let to = quad(point(0.0, 0.0), point(6.0, 0.5), point(6.0, 6.0), point(0.0, 5.0));
+    let mut tessellator = MorphTessellator::new().with_topology_reuse(true);
+
+    let mut frames = Vec::new();
+    for i in 0..4 {
+        let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+        tessellator.tessellate_morph(
+            &from,
+            &to,
+            i as f32 / 3.0,
+            &FillOptions::default(),
+            &mut simple_builder(&mut buffers),
+        ).unwrap();
+        // Only the first frame is tessellated.
+        assert_eq!(tessellator.reused_topology(), i > 0);
+        frames.push(buffers);
+    }
+
+    // The reused frames match a full tessellation.
+    let mut expected: VertexBuffers<Vertex> = VertexBuffers::new();
+    let last = interpolate_paths(&from, &to, 1.0).unwrap();
+    FillTessellator::new().tessellate_path(
+        last.path_iter().flattened(0.1),
+        &FillOptions::default(),
+        &mut simple_builder(&mut expected),
+    ).unwrap();
+    let mut positions: Vec<(f32, f32)> = frames[3].vertices.iter().map(|v| (v.position.x, v.position.y)).collect();
+    let mut expected_positions: Vec<(f32, f32)> = expected.vertices.iter().map(|v| (v.position.x, v.position.y)).collect();
+    positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    expected_positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(positions, expected_positions);
+    assert_eq!(frames[3].indices.len(), expected.indices.len());
+}
+
+#[test]
+fn test_morph_topology_change() {
+    // The second point moves above the fourth one, which changes the order of the events.
+    let from = quad(point(0.0, 0.0), point(4.0, 1.0), point(4.0, 4.0), point(0.0, 3.0));
+    let to = quad(point(0.0, 0.0), point(4.0, 5.0), point(4.0, 8.0), point(0.0, 3.0));
+    let mut tessellator = MorphTessellator::new().with_topology_reuse(true);
+
+    for &(t, reused) in &[(0.0, false), (0.1, true), (1.0, false)] {
+        let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+        tessellator.tessellate_morph(&from, &to, t, &FillOptions::default(), &mut simple_builder(&mut buffers)).unwrap();
+        assert_eq!(tessellator.reused_topology(), reused);
+        assert_eq!(buffers.indices.len(), 6);
+    }
+}
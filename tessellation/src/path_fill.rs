@@ -37,6 +37,11 @@
 //! to learn more about how the tessellator's algorithm works. The source code also contains
 //! inline documentation for the adventurous who want to delve into more details.
 //!
+//! The output only depends on the input and the options, and is bitwise identical on all
+//! platforms: the sweep line orders and intersects the edges with exact integer arithmetic
+//! on the fixed point coordinates, and the remaining floating point code only uses operations
+//! that IEEE 754 requires to be correctly rounded (no fused multiply-add, no trigonometry).
+//!
 //! # Examples
 //!
 //! ```
@@ -115,18 +120,24 @@
 // It's super slow right now.
 //
 
-use std::f32::consts::PI;
 use std::mem::{replace, swap};
 use std::cmp::{PartialOrd, Ordering};
 use std::cmp;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 
 use FillVertex as Vertex;
 use Side;
 use math::*;
-use geometry_builder::{GeometryBuilder, Count, VertexId};
+use geometry_builder::{GeometryBuilder, Count, VertexId, Index, Quantized, QuantizingBuilder};
+use delaunay::{make_delaunay, refine, RefineOptions};
+use ear_clipping::ear_clipping_fill;
+use sweep_line::{SweepLine, XExtent};
 use core::FlattenedEvent;
-use math_utils::{directed_angle, directed_angle2};
+use core::input_validation::{InputError, ValidatingIter, MAX_FIXED_POINT_COORDINATE};
 
+#[cfg(test)]
+use std::f32::consts::PI;
 #[cfg(test)]
 use geometry_builder::{VertexBuffers, simple_builder};
 #[cfg(test)]
@@ -138,7 +149,7 @@ use path_builder::BaseBuilder;
 #[cfg(test)]
 use extra::rust_logo::build_logo_path;
 
-#[cfg(debug)]
+#[cfg(feature = "logging")]
 macro_rules! tess_log {
     ($obj:ident, $fmt:expr) => (
         if $obj.log {
@@ -152,21 +163,74 @@ macro_rules! tess_log {
     );
 }
 
-#[cfg(not(debug))]
+#[cfg(not(feature = "logging"))]
 macro_rules! tess_log {
     ($obj:ident, $fmt:expr) => ();
     ($obj:ident, $fmt:expr, $($arg:tt)*) => ();
 }
 
+#[cfg(feature = "tracing")]
+macro_rules! tess_trace {
+    ($obj:ident, $evt:expr) => (
+        if let Some(ref mut tracer) = $obj.tracer {
+            tracer(&$evt);
+        }
+    );
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! tess_trace {
+    ($obj:ident, $evt:expr) => ();
+}
+
 /// The fill tessellator's result type.
 pub type FillResult = Result<Count, FillError>;
 
 /// The fill tessellator's error enumeration.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum FillError {
     Unknown,
+    /// An event of the path has a NaN, infinite or too large coordinate, see
+    /// `FillOptions::validate_input`.
+    InvalidInput(InputError),
+    /// The tessellation was aborted with a `CancellationToken` or a progress callback.
+    Cancelled,
+    /// `FillOptions::fixed_point_grid` has more fractional bits than the tessellator's fixed
+    /// point numbers (see `MAX_FIXED_POINT_GRID`).
+    InvalidFixedPointGrid(u32),
+}
+
+/// A handle to abort a tessellation, for example from another thread.
+///
+/// See `FillTessellator::set_cancellation_token`.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self { CancellationToken::default() }
+
+    /// Requests the tessellations using this token to stop as soon as possible.
+    pub fn cancel(&self) { self.cancelled.store(true, AtomicOrdering::Relaxed); }
+
+    pub fn is_cancelled(&self) -> bool { self.cancelled.load(AtomicOrdering::Relaxed) }
+}
+
+/// The progress of a tessellation, see `FillTessellator::set_progress_callback`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FillProgress {
+    /// The number of vertices processed by the sweep line so far.
+    pub vertices: usize,
+    /// The number of edges of the input that the sweep line went past.
+    pub edges: usize,
+    /// The number of edges of the input.
+    pub total_edges: usize,
 }
 
+// The number of vertices processed between two calls to the progress callback.
+const PROGRESS_INTERVAL: usize = 256;
+
 #[derive(Copy, Clone, Debug)]
 struct Edge {
     upper: TessPoint,
@@ -177,7 +241,6 @@ struct Edge {
 struct EdgeBelow {
     // The upper vertex is the current vertex, we don't need to store it.
     lower: TessPoint,
-    angle: f32,
 }
 
 /// A Context object that can tessellate fill operations for complex paths.
@@ -187,32 +250,214 @@ struct EdgeBelow {
 /// or if evertything can be implemented with the same algorithm.
 pub struct FillTessellator {
     events: FillEvents,
-    sweep_line: Vec<Span>,
-    monotone_tessellators: Vec<MonotoneTessellator>,
+    sweep_line: SweepLine<Span>,
+    // Monotone tessellators that are not in use, kept around so that their buffers can be
+    // reused by the next spans instead of being reallocated for each of them.
+    monotone_pool: Vec<MonotoneTessellator>,
     intersections: Vec<Edge>,
     below: Vec<EdgeBelow>,
     previous_position: TessPoint,
     error: Option<FillError>,
     log: bool,
+    record_snapshots: bool,
+    snapshots: Vec<SweepSnapshot>,
+    triangle_count: usize,
+    #[cfg(feature = "tracing")]
+    tracer: Option<Box<FnMut(&TraceEvent) + Send>>,
+    cancellation_token: Option<CancellationToken>,
+    progress_callback: Option<Box<FnMut(&FillProgress) -> bool + Send>>,
+    // With FillOptions::delaunay, the triangles are kept until the end of the tessellation
+    // to be post-processed.
+    delaunay: bool,
+    refine: Option<RefineOptions>,
+    delaunay_positions: Vec<Point>,
+    delaunay_indices: Vec<Index>,
     pub _handle_intersections: bool,
 }
 
+/// The type of a vertex event, see `TraceEvent`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum VertexEventType {
+    Start,
+    End,
+    Split,
+    Merge,
+    Left,
+    Right,
+    /// An interior point with no edge, see `FillEvents::add_steiner_points`.
+    Steiner,
+}
+
+/// A record of what the fill tessellator is doing, see `FillTessellator::set_tracer`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceEvent {
+    /// The sweep line reached a vertex.
+    Vertex { position: Point, id: VertexId },
+    /// A vertex was classified while being processed (a vertex can produce several events).
+    VertexEvent { position: Point, event_type: VertexEventType },
+    /// An edge was added to the sweep line.
+    EdgeAdded { upper: Point, lower: Point },
+    /// An edge was removed from the sweep line.
+    EdgeRemoved { upper: Point, lower: Point },
+    /// Two edges intersect, the intersection will be processed as a vertex.
+    Intersection { position: Point },
+    /// A triangle was sent to the geometry builder.
+    Triangle(VertexId, VertexId, VertexId),
+    /// The tessellation failed.
+    Error(FillError),
+}
+
+/// An edge of the sweep line, see `SweepSnapshot`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SweepEdge {
+    pub upper: Point,
+    pub lower: Point,
+    /// Whether the edge ends at a merge vertex that hasn't been connected yet (in which case
+    /// the edge is not an actual edge of the path).
+    pub merge: bool,
+}
+
+/// A span of the sweep line (the interior of the shape between two edges),
+/// see `SweepSnapshot`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SweepSpan {
+    pub left: SweepEdge,
+    pub right: SweepEdge,
+}
+
+/// The state of the fill tessellator after processing a vertex.
+///
+/// Only useful for debugging the tessellator, see `FillTessellator::record_snapshots`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepSnapshot {
+    /// Position of the vertex that was processed.
+    pub position: Point,
+    /// The spans of the sweep line, from left to right.
+    pub spans: Vec<SweepSpan>,
+    /// The upper position of the edges produced by intersections, not processed yet.
+    pub pending_intersections: Vec<Point>,
+    /// The number of triangles that have been sent to the geometry builder so far.
+    pub triangle_count: usize,
+}
+
+/// The memory used internally by the fill tessellator.
+///
+/// The fill tessellator keeps its buffers between tessellations so that it stops
+/// allocating once its buffers are large enough for the paths it tessellates.
+/// This struct makes it possible to keep the buffers when the tessellator itself
+/// does not outlive a tessellation, for example to share them between several
+/// short-lived tessellators or to warm them up ahead of time.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate lyon_tessellation;
+/// # extern crate lyon_core;
+/// # extern crate lyon_path;
+/// # extern crate lyon_path_builder;
+/// # extern crate lyon_path_iterator;
+/// # use lyon_path::Path;
+/// # use lyon_path_builder::*;
+/// # use lyon_path_iterator::*;
+/// # use lyon_core::math::*;
+/// # use lyon_tessellation::*;
+/// # use lyon_tessellation::geometry_builder::*;
+/// # fn main() {
+/// # let mut builder = Path::builder();
+/// # builder.move_to(point(0.0, 0.0));
+/// # builder.line_to(point(10.0, 0.0));
+/// # builder.line_to(point(10.0, 10.0));
+/// # builder.line_to(point(0.0, 10.0));
+/// # builder.close();
+/// # let path = builder.build();
+/// # let paths = vec![path];
+/// # let options = FillOptions::default();
+/// # let mut buffers: VertexBuffers<FillVertex> = VertexBuffers::new();
+/// # let mut output = simple_builder(&mut buffers);
+/// let mut allocations = FillTessellatorAllocations::new();
+/// for path in &paths {
+///     let mut tessellator = FillTessellator::with_allocations(allocations);
+///     tessellator.tessellate_path(path.path_iter().flattened(0.1), &options, &mut output).unwrap();
+///     allocations = tessellator.into_allocations();
+/// }
+/// # }
+/// ```
+pub struct FillTessellatorAllocations {
+    events: FillEvents,
+    sweep_line: SweepLine<Span>,
+    monotone_pool: Vec<MonotoneTessellator>,
+    intersections: Vec<Edge>,
+    below: Vec<EdgeBelow>,
+}
+
+impl FillTessellatorAllocations {
+    pub fn new() -> Self {
+        FillTessellatorAllocations {
+            events: FillEvents::new(),
+            sweep_line: SweepLine::with_capacity(16),
+            monotone_pool: Vec::with_capacity(16),
+            intersections: Vec::with_capacity(8),
+            below: Vec::with_capacity(8),
+        }
+    }
+
+    /// Reserve memory for paths with up to `num_edges` edges and `num_spans` spans
+    /// simultaneously on the sweep line.
+    pub fn reserve(&mut self, num_edges: usize, num_spans: usize) {
+        self.events.edges.reserve(num_edges);
+        self.events.vertices.reserve(num_edges);
+        self.sweep_line.reserve(num_spans);
+        self.monotone_pool.reserve(num_spans);
+        while self.monotone_pool.len() < num_spans {
+            self.monotone_pool.push(MonotoneTessellator::begin(point(0.0, 0.0), VertexId(0)));
+        }
+    }
+}
+
 impl FillTessellator {
     /// Constructor.
     pub fn new() -> FillTessellator {
+        FillTessellator::with_allocations(FillTessellatorAllocations::new())
+    }
+
+    /// Create a tessellator that uses previously allocated buffers.
+    pub fn with_allocations(allocations: FillTessellatorAllocations) -> FillTessellator {
         FillTessellator {
-            events: FillEvents::new(),
-            sweep_line: Vec::with_capacity(16),
-            monotone_tessellators: Vec::with_capacity(16),
-            below: Vec::with_capacity(8),
-            intersections: Vec::with_capacity(8),
+            events: allocations.events,
+            sweep_line: allocations.sweep_line,
+            monotone_pool: allocations.monotone_pool,
+            below: allocations.below,
+            intersections: allocations.intersections,
             previous_position: TessPoint::new(FixedPoint32::min_val(), FixedPoint32::min_val()),
             error: None,
             log: false,
+            record_snapshots: false,
+            snapshots: Vec::new(),
+            triangle_count: 0,
+            #[cfg(feature = "tracing")]
+            tracer: None,
+            cancellation_token: None,
+            progress_callback: None,
+            delaunay: false,
+            refine: None,
+            delaunay_positions: Vec::new(),
+            delaunay_indices: Vec::new(),
             _handle_intersections: true,
         }
     }
 
+    /// Destroy the tessellator and return its buffers so that they can be reused.
+    pub fn into_allocations(mut self) -> FillTessellatorAllocations {
+        self.reset();
+        FillTessellatorAllocations {
+            events: self.events,
+            sweep_line: self.sweep_line,
+            monotone_pool: self.monotone_pool,
+            intersections: self.intersections,
+            below: self.below,
+        }
+    }
+
     /// Compute the tessellation from a path iterator.
     pub fn tessellate_path<Iter, Output>(
         &mut self,
@@ -224,9 +469,82 @@ impl FillTessellator {
         Iter: Iterator<Item = FlattenedEvent>,
         Output: GeometryBuilder<Vertex>,
     {
+        self.tessellate_path_with_steiner_points(it, &[], options, output)
+    }
+
+    /// Compute the tessellation from a path iterator, with the positions of the vertices
+    /// given as integers on a grid of `1 / 2^fractional_bits` units.
+    ///
+    /// The path is snapped to the grid before the tessellation so that the triangles are
+    /// consistent with the quantized positions (see
+    /// [QuantizingBuilder](../geometry_builder/struct.QuantizingBuilder.html)). This overrides
+    /// the `fixed_point_grid` and `algorithm` of the options.
+    pub fn tessellate_path_quantized<Iter, Output>(
+        &mut self,
+        it: Iter,
+        options: &FillOptions,
+        fractional_bits: u32,
+        output: &mut Output,
+    ) -> FillResult
+    where
+        Iter: Iterator<Item = FlattenedEvent>,
+        Output: GeometryBuilder<Quantized<Vertex>>,
+    {
+        let options = options.clone()
+            .with_fixed_point_grid(fractional_bits)
+            .with_algorithm(FillAlgorithm::Sweep);
+
+        self.tessellate_path(it, &options, &mut QuantizingBuilder::new(output, fractional_bits))
+    }
+
+    /// Compute the tessellation from a path iterator, with additional interior vertices.
+    ///
+    /// See `FillEvents::add_steiner_points`.
+    pub fn tessellate_path_with_steiner_points<Iter, Output>(
+        &mut self,
+        it: Iter,
+        steiner_points: &[Point],
+        options: &FillOptions,
+        output: &mut Output,
+    ) -> FillResult
+    where
+        Iter: Iterator<Item = FlattenedEvent>,
+        Output: GeometryBuilder<Vertex>,
+    {
+        if options.algorithm == FillAlgorithm::EarClipping {
+            if options.validate_input {
+                let mut validating = ValidatingIter::new(it, MAX_FIXED_POINT_COORDINATE);
+                let path: Vec<FlattenedEvent> = validating.by_ref().collect();
+                if let Some(error) = validating.error() {
+                    return Err(FillError::InvalidInput(error));
+                }
+                return Ok(ear_clipping_fill(path.into_iter(), output));
+            }
+            return Ok(ear_clipping_fill(it, output));
+        }
+
+        if let Some(fractional_bits) = options.fixed_point_grid {
+            if fractional_bits > MAX_FIXED_POINT_GRID {
+                return Err(FillError::InvalidFixedPointGrid(fractional_bits));
+            }
+        }
+
         let mut events = replace(&mut self.events, FillEvents::new());
         events.clear();
-        events.set_path_iter(it);
+        let mut input_error = None;
+        if options.validate_input {
+            let mut validating = ValidatingIter::new(it, MAX_FIXED_POINT_COORDINATE);
+            set_path_events(&mut events, &mut validating, options);
+            input_error = validating.error();
+        } else {
+            set_path_events(&mut events, it, options);
+        }
+        if let Some(error) = input_error {
+            events.clear();
+            self.events = events;
+            return Err(FillError::InvalidInput(error));
+        }
+        events.add_steiner_points(steiner_points);
         let result = self.tessellate_events(&events, options, output);
         self.events = events;
         return result;
@@ -243,14 +561,19 @@ impl FillTessellator {
     where
         Output: GeometryBuilder<Vertex>,
     {
-        if options.vertex_aa {
-            println!("warning: Vertex-aa is not supported yet.");
-        }
+        if cfg!(feature = "logging") {
+            if options.vertex_aa {
+                println!("warning: Vertex-aa is not supported yet.");
+            }
 
-        if options.fill_rule != FillRule::EvenOdd {
-            println!("warning: Fill rule {:?} is not supported yet.", options.fill_rule);
+            if options.fill_rule != FillRule::EvenOdd {
+                println!("warning: Fill rule {:?} is not supported yet.", options.fill_rule);
+            }
         }
 
+        self.delaunay = options.delaunay || options.refine.is_some();
+        self.refine = options.refine;
+
         self.begin_tessellation(output);
 
         self.tessellator_loop(&events, output);
@@ -263,23 +586,95 @@ impl FillTessellator {
             return Err(err);
         }
 
+        if self.delaunay {
+            self.flush_delaunay(output);
+        }
+
         let res = self.end_tessellation(output);
         self.reset();
         return Ok(res);
     }
 
+    /// Record the state of the sweep line after each processed vertex, for debugging purposes.
+    ///
+    /// The snapshots of the last tessellation can be retrieved with `snapshots`.
+    pub fn record_snapshots(&mut self, enabled: bool) { self.record_snapshots = enabled; }
+
+    /// The snapshots recorded during the last tessellation, if enabled with `record_snapshots`.
+    pub fn snapshots(&self) -> &[SweepSnapshot] { &self.snapshots[..] }
+
     /// Enable some verbose logging during the tessellation, for debugging purposes.
+    ///
+    /// Has no effect if the `logging` feature is disabled.
     pub fn enable_logging(&mut self) { self.log = true; }
 
+    /// Set a callback that receives a structured record of each step of the tessellation,
+    /// for example to collect diagnostics on machines where a debugger can't be attached.
+    ///
+    /// Has no effect if the `tracing` feature is disabled.
+    #[cfg(feature = "tracing")]
+    pub fn set_tracer(&mut self, tracer: Option<Box<FnMut(&TraceEvent) + Send>>) { self.tracer = tracer; }
+
+    /// Set a callback that receives a structured record of each step of the tessellation,
+    /// for example to collect diagnostics on machines where a debugger can't be attached.
+    ///
+    /// Has no effect if the `tracing` feature is disabled.
+    #[cfg(not(feature = "tracing"))]
+    pub fn set_tracer(&mut self, _tracer: Option<Box<FnMut(&TraceEvent) + Send>>) {}
+
+    /// Set a token that aborts the tessellation with `FillError::Cancelled` when it is
+    /// cancelled.
+    ///
+    /// The token is checked before each vertex of the sweep line, including the intersections,
+    /// and the output is discarded with `GeometryBuilder::abort_geometry`.
+    pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.cancellation_token = token;
+    }
+
+    /// Set a callback that is called every few hundred vertices with the progress of the
+    /// tessellation.
+    ///
+    /// Returning false from the callback aborts the tessellation with `FillError::Cancelled`.
+    pub fn set_progress_callback(&mut self, callback: Option<Box<FnMut(&FillProgress) -> bool + Send>>) {
+        self.progress_callback = callback;
+    }
+
+    fn is_cancelled(&self) -> bool {
+        match self.cancellation_token {
+            Some(ref token) => token.is_cancelled(),
+            None => false,
+        }
+    }
+
+    // Returns false if the tessellation must stop.
+    fn report_progress(&mut self, progress: &FillProgress) -> bool {
+        if let Some(ref mut callback) = self.progress_callback {
+            if !callback(progress) {
+                return false;
+            }
+        }
+
+        return true;
+    }
+
     fn reset(&mut self) {
+        // Move the remaining tessellators to the pool (there can be some if an error occured).
+        while let Some(span) = self.sweep_line.pop() {
+            let mut tess = span.tess;
+            tess.triangles.clear();
+            self.monotone_pool.push(tess);
+        }
         self.sweep_line.clear();
-        self.monotone_tessellators.clear();
+        self.intersections.clear();
         self.below.clear();
+        self.delaunay_positions.clear();
+        self.delaunay_indices.clear();
     }
 
     fn begin_tessellation<Output: GeometryBuilder<Vertex>>(&mut self, output: &mut Output) {
+        self.snapshots.clear();
+        self.triangle_count = 0;
         debug_assert!(self.sweep_line.is_empty());
-        debug_assert!(self.monotone_tessellators.is_empty());
         debug_assert!(self.below.is_empty());
         output.begin_geometry();
     }
@@ -289,11 +684,32 @@ impl FillTessellator {
         output: &mut Output,
     ) -> Count {
         debug_assert!(self.sweep_line.is_empty());
-        debug_assert!(self.monotone_tessellators.is_empty());
         debug_assert!(self.below.is_empty());
         return output.end_geometry();
     }
 
+    fn flush_delaunay<Output: GeometryBuilder<Vertex>>(&mut self, output: &mut Output) {
+        if let Some(refine_options) = self.refine {
+            let mut add_vertex = |position: Point| {
+                output.add_vertex(Vertex { position: position, normal: vec2(0.0, 0.0) }).0
+            };
+            refine(
+                &mut self.delaunay_positions,
+                &mut self.delaunay_indices,
+                &refine_options,
+                &mut add_vertex,
+            );
+        } else {
+            make_delaunay(&self.delaunay_positions, &mut self.delaunay_indices);
+        }
+
+        for triangle in self.delaunay_indices.chunks(3) {
+            let (a, b, c) = (VertexId(triangle[0]), VertexId(triangle[1]), VertexId(triangle[2]));
+            tess_trace!(self, TraceEvent::Triangle(a, b, c));
+            output.add_triangle(a, b, c);
+        }
+    }
+
     fn tessellator_loop<Output: GeometryBuilder<Vertex>>(
         &mut self,
         events: &FillEvents,
@@ -305,6 +721,11 @@ impl FillTessellator {
         let mut vertex_iter = events.vertices.iter();
         let mut next_edge = edge_iter.next();
         let mut next_vertex = vertex_iter.next();
+        let mut progress = FillProgress {
+            vertices: 0,
+            edges: 0,
+            total_edges: events.edges.len(),
+        };
         loop {
             if self.error.is_some() {
                 return;
@@ -321,14 +742,9 @@ impl FillTessellator {
             while let Some(edge) = next_edge {
                 if edge.upper == current_position {
                     next_edge = edge_iter.next();
+                    progress.edges += 1;
                     if edge.lower != current_position {
-                        self.below
-                            .push(
-                                EdgeBelow {
-                                    lower: edge.lower,
-                                    angle: compute_angle(edge.lower - edge.upper),
-                                }
-                            );
+                        self.below.push(EdgeBelow { lower: edge.lower });
                     }
                     pending_events = true;
                     tess_log!(self, " edge at {:?} -> {:?}", edge.upper, edge.lower);
@@ -360,13 +776,7 @@ impl FillTessellator {
                     let inter = self.intersections.remove(0);
 
                     if inter.lower != current_position {
-                        self.below
-                            .push(
-                                EdgeBelow {
-                                    lower: inter.lower,
-                                    angle: compute_angle(inter.lower - current_position),
-                                }
-                            );
+                        self.below.push(EdgeBelow { lower: inter.lower });
                     }
 
                     pending_events = true;
@@ -379,9 +789,23 @@ impl FillTessellator {
             }
 
             if pending_events {
+                progress.vertices += 1;
+                if self.is_cancelled() {
+                    self.error(FillError::Cancelled);
+                    return;
+                }
+                if progress.vertices % PROGRESS_INTERVAL == 0 && !self.report_progress(&progress) {
+                    self.error(FillError::Cancelled);
+                    return;
+                }
+
                 let num_intersections = self.intersections.len();
                 self.process_vertex(current_position, output);
 
+                if self.record_snapshots {
+                    self.take_snapshot(current_position);
+                }
+
                 if num_intersections != self.intersections.len() {
                     // We found an intersection durign process_vertex, it has been added
                     // to self.intersections.
@@ -429,10 +853,19 @@ impl FillTessellator {
                 normal: vec2(0.0, 0.0),
             }
         );
+        tess_trace!(self, TraceEvent::Vertex { position: vec2_position, id: id });
+
+        if self.delaunay {
+            let idx = id.0 as usize;
+            if idx >= self.delaunay_positions.len() {
+                self.delaunay_positions.resize(idx + 1, point(0.0, 0.0));
+            }
+            self.delaunay_positions[idx] = vec2_position;
+        }
 
         // Walk the sweep line to determine where we are with respect to the
         // existing spans.
-        let mut start_span = 0;
+        let mut start_span = self.sweep_line.len();
 
 
         // Go through the sweep line to find the first edge that ends at the current
@@ -446,71 +879,63 @@ impl FillTessellator {
         };
         let mut status = E::Out;
 
-        for span in &mut self.sweep_line {
-            if !span.left.merge && span.left.lower == current_position {
-                status = E::LeftEdge;
-                break;
-            }
+        // The spans that are entirely on the left of the current position can't contain it
+        // or end at it, the sweep line skips them without testing their edges.
+        let on_the_right = |_: FixedPoint32, max_x: FixedPoint32| max_x >= current_position.x;
+        let mut next_span = self.sweep_line.find(0, &on_the_right);
+        while let Some(span_idx) = next_span {
+            let (left, right) = {
+                let span = self.sweep_line.get(span_idx);
+                (span.left, span.right)
+            };
 
-            if test_span_touches(&span.left, current_position) {
+            let found = if !left.merge && left.lower == current_position {
+                Some(E::LeftEdge)
+            } else if test_span_touches(&left, current_position) {
                 // The current point is on an edge we need to split the edge into the part
                 // above and the part below. See test_point_on_edge_left for an example of
                 // geometry that can lead to this scenario.
 
                 // Split the edge.
-                self.below
-                    .push(
-                        EdgeBelow {
-                            lower: span.left.lower,
-                            angle: compute_angle(span.left.lower - current_position),
-                        }
-                    );
-                span.left.lower = current_position;
-
-                status = E::LeftEdge;
-                break;
-            }
-
-            if test_span_side(&span.left, current_position) {
-                status = E::Out;
-                break;
-            }
+                self.below.push(EdgeBelow { lower: left.lower });
+                self.sweep_line.update(span_idx, |span| { span.left.lower = current_position; });
 
-            if !span.right.merge && span.right.lower == current_position {
+                Some(E::LeftEdge)
+            } else if test_span_side(&left, current_position) {
+                Some(E::Out)
+            } else if !right.merge && right.lower == current_position {
                 // TODO: this can lead to incorrect results if the next span also touches.
-                status = E::RightEdge;
-                break;
-            }
-
-            if test_span_touches(&span.right, current_position) {
+                Some(E::RightEdge)
+            } else if test_span_touches(&right, current_position) {
                 // Same situation as above, the current point is on an edge (but this
                 // time it is the right side instead of the left side of a span).
 
                 // Split the edge.
-                self.below
-                    .push(
-                        EdgeBelow {
-                            lower: span.right.lower,
-                            angle: compute_angle(span.right.lower - current_position),
-                        }
-                    );
-                span.right.lower = current_position;
+                self.below.push(EdgeBelow { lower: right.lower });
+                self.sweep_line.update(span_idx, |span| { span.right.lower = current_position; });
 
-                status = E::RightEdge;
-                break;
-            }
+                Some(E::RightEdge)
+            } else if test_span_side(&right, current_position) {
+                Some(E::In)
+            } else {
+                None
+            };
 
-            if test_span_side(&span.right, current_position) {
-                status = E::In;
+            if let Some(found) = found {
+                status = found;
+                start_span = span_idx;
                 break;
             }
 
-            start_span += 1;
+            next_span = self.sweep_line.find(span_idx + 1, &on_the_right);
         }
 
-        self.below.sort_by(|a, b| a.angle.partial_cmp(&b.angle).unwrap_or(Ordering::Equal));
+        self.below.sort_by(|a, b| {
+            compare_directions(a.lower - current_position, b.lower - current_position)
+                .then(compare_positions(a.lower, b.lower))
+        });
 
-        if self.log {
+        if cfg!(feature = "logging") && self.log {
             self.log_sl(current_position, start_span);
         }
 
@@ -540,6 +965,10 @@ impl FillTessellator {
                     //   ....\
                     //
                     tess_log!(self, "(right event) {}", start_span);
+                    tess_trace!(self, TraceEvent::VertexEvent {
+                        position: vec2_position,
+                        event_type: VertexEventType::Right,
+                    });
 
                     let edge_to = self.below[0].lower;
                     self.insert_edge(start_span, Side::Right, current_position, edge_to, id);
@@ -556,8 +985,14 @@ impl FillTessellator {
         }
 
         // Count the number of remaining edges above the sweep line that end at
-        // the current position.
-        for span in &self.sweep_line[span_idx..] {
+        // the current position. Only the spans that contain the current position
+        // horizontally can have edges that end at it.
+        let contains = |min_x: FixedPoint32, max_x: FixedPoint32| {
+            min_x <= current_position.x && max_x >= current_position.x
+        };
+        let mut next_span = self.sweep_line.find(span_idx, &contains);
+        while let Some(idx) = next_span {
+            let span = self.sweep_line.get(idx);
             let left = !span.left.merge && span.left.lower == current_position;
             let right = !span.right.merge && span.right.lower == current_position;
             // Here it is tempting to assume that we can only have end events
@@ -580,6 +1015,8 @@ impl FillTessellator {
                 span.left.lower,
                 span.left.merge
             );
+
+            next_span = self.sweep_line.find(idx + 1, &contains);
         }
 
         // Pairs of edges that end at the current position form "end events".
@@ -592,6 +1029,10 @@ impl FillTessellator {
             //     x
             //
             tess_log!(self, "(end event) {}", span_idx);
+            tess_trace!(self, TraceEvent::VertexEvent {
+                position: vec2_position,
+                event_type: VertexEventType::End,
+            });
 
             self.resolve_merge_vertices(span_idx, current_position, id, output);
             self.end_span(span_idx, current_position, id, output);
@@ -607,6 +1048,10 @@ impl FillTessellator {
             // .....x.....
             //
             tess_log!(self, "(merge event) {}", start_span);
+            tess_trace!(self, TraceEvent::VertexEvent {
+                position: vec2_position,
+                event_type: VertexEventType::Merge,
+            });
 
             debug_assert!(above_count == 1);
             self.merge_event(current_position, id, start_span, output)
@@ -624,6 +1069,10 @@ impl FillTessellator {
 
             let vertex_below = self.below[self.below.len() - 1].lower;
             tess_log!(self, "(left event) {}    -> {:?}", span_idx, vertex_below);
+            tess_trace!(self, TraceEvent::VertexEvent {
+                position: vec2_position,
+                event_type: VertexEventType::Left,
+            });
             self.insert_edge(span_idx, Side::Left, current_position, vertex_below, id);
 
             below_count -= 1;
@@ -646,6 +1095,10 @@ impl FillTessellator {
                 // .../   \...
                 //
                 tess_log!(self, "(split event) {}", start_span);
+                tess_trace!(self, TraceEvent::VertexEvent {
+                    position: vec2_position,
+                    event_type: VertexEventType::Split,
+                });
 
                 let left = self.below[0].clone();
                 let right = self.below[below_count - 1].clone();
@@ -662,6 +1115,10 @@ impl FillTessellator {
                 //    /...\
                 //
                 tess_log!(self, "(start event) {}", span_idx);
+                tess_trace!(self, TraceEvent::VertexEvent {
+                    position: vec2_position,
+                    event_type: VertexEventType::Start,
+                });
 
                 let l = self.below[below_idx].lower;
                 let r = self.below[below_idx + 1].lower;
@@ -675,20 +1132,27 @@ impl FillTessellator {
                 };
 
                 // Look whether the two edges are colinear:
-                if self.below[below_idx].angle != self.below[below_idx + 1].angle {
+                let colinear =
+                    compare_directions(l - current_position, r - current_position) == Ordering::Equal;
+                if !colinear {
                     // In most cases (not colinear):
                     self.check_intersections(&mut left_edge);
                     self.check_intersections(&mut right_edge);
+                    tess_trace!(self, TraceEvent::EdgeAdded {
+                        upper: vec2_position,
+                        lower: to_f32_point(left_edge.lower),
+                    });
+                    tess_trace!(self, TraceEvent::EdgeAdded {
+                        upper: vec2_position,
+                        lower: to_f32_point(right_edge.lower),
+                    });
+                    let vec2_position = to_f32_point(current_position);
+                    let tess = self.begin_monotone_tessellator(vec2_position, id);
                     self.sweep_line
                         .insert(
                             span_idx,
-                            Span::begin(current_position, id, left_edge.lower, right_edge.lower),
+                            Span::begin(current_position, id, left_edge.lower, right_edge.lower, tess),
                         );
-                    let vec2_position = to_f32_point(current_position);
-                    self.monotone_tessellators.insert(
-                        span_idx,
-                        MonotoneTessellator::begin(vec2_position, id),
-                    );
                 } else {
                     // If the two edges are colinear we "postpone" the beginning of this span
                     // since at this level there is nothing to fill in a zero-area span.
@@ -716,6 +1180,22 @@ impl FillTessellator {
             }
         }
 
+        if status == E::In && self.below.is_empty() {
+            // Steiner point, there is no edge above or below the current vertex.
+            //
+            // .....:.....
+            // .....x.....
+            // ...........
+            //
+            tess_log!(self, "(steiner point) {}", start_span);
+            tess_trace!(self, TraceEvent::VertexEvent {
+                position: vec2_position,
+                event_type: VertexEventType::Steiner,
+            });
+
+            self.steiner_event(start_span, current_position, id, output);
+        }
+
         self.debug_check_sl(current_position);
 
         self.below.clear();
@@ -731,12 +1211,12 @@ impl FillTessellator {
         id: VertexId,
         output: &mut Output,
     ) {
-        while self.sweep_line[span_idx].right.merge {
+        while self.sweep_line.get(span_idx).right.merge {
             //     \ /
             //  \   x   <-- merge vertex
             //   \ :
             //    x   <-- current vertex
-            self.sweep_line[span_idx + 1].set_lower_vertex(current, Side::Left);
+            self.sweep_line.update(span_idx + 1, |span| span.set_lower_vertex(current, Side::Left));
             self.end_span(span_idx, current, id, output);
         }
     }
@@ -751,7 +1231,7 @@ impl FillTessellator {
         output: &mut Output,
     ) {
         // Look whether the span shares a merge vertex with the previous one
-        if self.sweep_line[span_idx].left.merge {
+        if self.sweep_line.get(span_idx).left.merge {
             let left_span = span_idx - 1;
             let right_span = span_idx;
             //            \ /
@@ -771,25 +1251,61 @@ impl FillTessellator {
             //    / :r2
             // ll/   x   <-- current split vertex
             //  left/ \right
-            let ll = self.sweep_line[span_idx].left;
+            let ll = self.sweep_line.get(span_idx).left;
             let r2 = Edge {
                 upper: ll.upper,
                 lower: current,
             };
 
-            self.sweep_line.insert(span_idx, Span::begin(ll.upper, ll.upper_id, ll.lower, current));
             let vec2_position = to_f32_point(ll.upper);
-            self.monotone_tessellators
-                .insert(span_idx, MonotoneTessellator::begin(vec2_position, ll.upper_id));
-            self.sweep_line[span_idx + 1].left.upper = r2.upper;
-            self.sweep_line[span_idx + 1].left.lower = r2.lower;
-            self.sweep_line[span_idx + 1].left.merge = false;
+            let tess = self.begin_monotone_tessellator(vec2_position, ll.upper_id);
+            self.sweep_line.insert(span_idx, Span::begin(ll.upper, ll.upper_id, ll.lower, current, tess));
+            self.sweep_line.update(span_idx + 1, |span| {
+                span.left.upper = r2.upper;
+                span.left.lower = r2.lower;
+                span.left.merge = false;
+            });
 
             self.insert_edge(span_idx, Side::Right, current, left.lower, id);
             self.insert_edge(span_idx + 1, Side::Left, current, right.lower, id);
         }
     }
 
+    // A Steiner point splits the span that contains it in two like a split vertex, and then
+    // becomes the merge vertex of the two spans, so that it gets connected to the next
+    // vertex below.
+    fn steiner_event<Output: GeometryBuilder<Vertex>>(
+        &mut self,
+        span_idx: usize,
+        current: TessPoint,
+        id: VertexId,
+        output: &mut Output,
+    ) {
+        if self.sweep_line.get(span_idx).left.merge {
+            //            \ /
+            //             x   <-- merge vertex
+            //  left_span  :  righ_span
+            //             x   <-- current steiner point
+            self.merge_event(current, id, span_idx - 1, output);
+        } else {
+            //      /
+            //     x
+            //    / :
+            // ll/   x   <-- current steiner point
+            let ll = self.sweep_line.get(span_idx).left;
+            let vec2_position = to_f32_point(ll.upper);
+            let tess = self.begin_monotone_tessellator(vec2_position, ll.upper_id);
+            self.sweep_line.insert(span_idx, Span::begin(ll.upper, ll.upper_id, ll.lower, current, tess));
+            self.sweep_line.update(span_idx + 1, |span| {
+                span.left.upper = ll.upper;
+                span.left.lower = current;
+                span.left.merge = false;
+            });
+
+            self.merge_event(current, id, span_idx, output);
+        }
+    }
+
     fn merge_event<Output: GeometryBuilder<Vertex>>(
         &mut self,
         position: TessPoint,
@@ -809,11 +1325,15 @@ impl FillTessellator {
 
         let vec2_position = to_f32_point(position);
 
-        self.sweep_line[left_span].merge_vertex(position, id, Side::Right);
-        self.monotone_tessellators[left_span].vertex(vec2_position, id, Side::Right);
+        self.sweep_line.update(left_span, |span| {
+            span.merge_vertex(position, id, Side::Right);
+            span.tess.vertex(vec2_position, id, Side::Right);
+        });
 
-        self.sweep_line[right_span].merge_vertex(position, id, Side::Left);
-        self.monotone_tessellators[right_span].vertex(vec2_position, id, Side::Left);
+        self.sweep_line.update(right_span, |span| {
+            span.merge_vertex(position, id, Side::Left);
+            span.tess.vertex(vec2_position, id, Side::Left);
+        });
     }
 
     fn insert_edge(
@@ -831,12 +1351,22 @@ impl FillTessellator {
             upper: upper,
             lower: lower,
         };
-        self.sweep_line[span_idx].mut_edge(side).merge = true;
+        tess_trace!(self, TraceEvent::EdgeRemoved {
+            upper: to_f32_point(self.sweep_line.get(span_idx).side_edge(side).upper),
+            lower: to_f32_point(self.sweep_line.get(span_idx).side_edge(side).lower),
+        });
+        self.sweep_line.update(span_idx, |span| { span.mut_edge(side).merge = true; });
         self.check_intersections(&mut edge);
-        // This sets the merge flag to false.
-        self.sweep_line[span_idx].edge(edge, id, side);
+        tess_trace!(self, TraceEvent::EdgeAdded {
+            upper: to_f32_point(edge.upper),
+            lower: to_f32_point(edge.lower),
+        });
         let vec2_position = to_f32_point(edge.upper);
-        self.monotone_tessellators[span_idx].vertex(vec2_position, id, side);
+        self.sweep_line.update(span_idx, |span| {
+            // This sets the merge flag to false.
+            span.edge(edge, id, side);
+            span.tess.vertex(vec2_position, id, side);
+        });
 
     }
 
@@ -862,31 +1392,43 @@ impl FillTessellator {
 
         let original_edge = *edge;
         let mut intersection = None;
-        let mut span_idx = 0;
 
-        for span in &mut self.sweep_line {
+        // Only the spans that overlap the edge horizontally can intersect it, same as the
+        // early-out in segment_intersection but for both sides of the spans at once.
+        let mut next_span = {
+            let (edge_min_x, edge_max_x) = edge.upper.x.min_max(edge.lower.x);
+            self.sweep_line.find(0, &|min_x: FixedPoint32, max_x: FixedPoint32| {
+                max_x > edge_min_x && min_x < edge_max_x
+            })
+        };
+
+        while let Some(span_idx) = next_span {
+            let (left, right) = {
+                let span = self.sweep_line.get(span_idx);
+                (span.left, span.right)
+            };
 
             // Test for an intersection against the span's left edge.
-            if !span.left.merge {
+            if !left.merge {
                 match segment_intersection(
                     edge.upper,
                     edge.lower,
-                    span.left.upper,
-                    span.left.lower,
+                    left.upper,
+                    left.lower,
                 ) {
                     Some(position) => {
                         tess_log!(self, " -- found an intersection at {:?}
                                         |    {:?}->{:?} x {:?}->{:?}",
                             position,
                             original_edge.upper, original_edge.lower,
-                            span.left.upper, span.left.lower,
+                            left.upper, left.lower,
                         );
 
                         intersection = Some(
                             (Intersection {
                                  point: position,
                                  lower1: original_edge.lower,
-                                 lower2: Some(span.left.lower),
+                                 lower2: Some(left.lower),
                              },
 
                              span_idx,
@@ -901,25 +1443,25 @@ impl FillTessellator {
             }
 
             // Same thing for the span's right edge.
-            if !span.right.merge {
+            if !right.merge {
                 match segment_intersection(
                     edge.upper,
                     edge.lower,
-                    span.right.upper,
-                    span.right.lower,
+                    right.upper,
+                    right.lower,
                 ) {
                     Some(position) => {
                         tess_log!(self, " -- found an intersection at {:?}
                                         |    {:?}->{:?} x {:?}->{:?}",
                             position,
                             original_edge.upper, original_edge.lower,
-                            span.right.upper, span.right.lower,
+                            right.upper, right.lower,
                         );
                         intersection = Some(
                             (Intersection {
                                  point: position,
                                  lower1: original_edge.lower,
-                                 lower2: Some(span.right.lower),
+                                 lower2: Some(right.lower),
                              },
                              span_idx,
                              Side::Right)
@@ -930,7 +1472,12 @@ impl FillTessellator {
                 }
             }
 
-            span_idx += 1;
+            // The edge is shortened when an intersection is found, which can rule out
+            // more of the remaining spans.
+            let (edge_min_x, edge_max_x) = edge.upper.x.min_max(edge.lower.x);
+            next_span = self.sweep_line.find(span_idx + 1, &|min_x: FixedPoint32, max_x: FixedPoint32| {
+                max_x > edge_min_x && min_x < edge_max_x
+            });
         }
 
         if let Some((mut evt, span_idx, side)) = intersection {
@@ -975,10 +1522,13 @@ impl FillTessellator {
                 span_idx,
                 side,
                 evt.point,
-                self.sweep_line[span_idx].mut_edge(side).lower
+                self.sweep_line.get(span_idx).side_edge(side).lower
             );
 
-            self.sweep_line[span_idx].mut_edge(side).lower = evt.point;
+            tess_trace!(self, TraceEvent::Intersection { position: to_f32_point(evt.point) });
+
+            let point = evt.point;
+            self.sweep_line.update(span_idx, |span| { span.mut_edge(side).lower = point; });
             self.intersections.push(e1);
             if let Some(e2) = e2 {
                 self.intersections.push(e2);
@@ -996,22 +1546,74 @@ impl FillTessellator {
         output: &mut Output,
     ) {
         let vec2_position = to_f32_point(position);
-        {
-            let tess = &mut self.monotone_tessellators[span_idx];
-            tess.end(vec2_position, id);
-            tess.flush(output);
+        let mut span = self.sweep_line.remove(span_idx);
+        span.tess.end(vec2_position, id);
+        self.triangle_count += span.tess.triangles.len();
+        if self.delaunay {
+            for &(a, b, c) in &span.tess.triangles {
+                self.delaunay_indices.extend_from_slice(&[a.0, b.0, c.0]);
+            }
+            span.tess.triangles.clear();
+        } else {
+            #[cfg(feature = "tracing")]
+            for &(a, b, c) in &span.tess.triangles {
+                tess_trace!(self, TraceEvent::Triangle(a, b, c));
+            }
+            span.tess.flush(output);
+        }
+        tess_trace!(self, TraceEvent::EdgeRemoved {
+            upper: to_f32_point(span.left.upper),
+            lower: to_f32_point(span.left.lower),
+        });
+        tess_trace!(self, TraceEvent::EdgeRemoved {
+            upper: to_f32_point(span.right.upper),
+            lower: to_f32_point(span.right.lower),
+        });
+        self.monotone_pool.push(span.tess);
+    }
+
+    // Returns a monotone tessellator from the pool if any, or allocates a new one.
+    fn begin_monotone_tessellator(&mut self, pos: Point, id: VertexId) -> MonotoneTessellator {
+        if let Some(mut tess) = self.monotone_pool.pop() {
+            tess.restart(pos, id);
+            return tess;
         }
-        self.sweep_line.remove(span_idx);
-        self.monotone_tessellators.remove(span_idx);
+
+        return MonotoneTessellator::begin(pos, id);
+    }
+
+    fn take_snapshot(&mut self, position: TessPoint) {
+        fn edge(e: &SpanEdge) -> SweepEdge {
+            SweepEdge {
+                upper: to_f32_point(e.upper),
+                lower: to_f32_point(e.lower),
+                merge: e.merge,
+            }
+        }
+
+        let snapshot = SweepSnapshot {
+            position: to_f32_point(position),
+            spans: self.sweep_line.iter().map(|span| {
+                SweepSpan {
+                    left: edge(&span.left),
+                    right: edge(&span.right),
+                }
+            }).collect(),
+            pending_intersections: self.intersections.iter().map(|e| to_f32_point(e.upper)).collect(),
+            triangle_count: self.triangle_count,
+        };
+
+        self.snapshots.push(snapshot);
     }
 
     fn error(&mut self, err: FillError) {
         tess_log!(self, " !! FillTessellator Error {:?}", err);
+        tess_trace!(self, TraceEvent::Error(err.clone()));
         self.error = Some(err);
     }
 
     fn debug_check_sl(&self, current: TessPoint) {
-        for span in &self.sweep_line {
+        for span in self.sweep_line.iter() {
             if !span.left.merge {
                 debug_assert!(
                     !is_after(current, span.left.lower),
@@ -1045,7 +1647,7 @@ impl FillTessellator {
 
     fn log_sl_ids(&self) {
         print!("\n|  sl: ");
-        for span in &self.sweep_line {
+        for span in self.sweep_line.iter() {
             let ml = if span.left.merge { "*" } else { " " };
             let mr = if span.right.merge { "*" } else { " " };
             print!(
@@ -1061,13 +1663,13 @@ impl FillTessellator {
 
     fn log_sl_points(&self) {
         print!("\n sl: [");
-        for span in &self.sweep_line {
+        for span in self.sweep_line.iter() {
             print!("| l:{:?} ", span.left.upper);
             print!(" r:{:?} |", span.right.upper);
         }
         println!("]");
         print!("     [");
-        for span in &self.sweep_line {
+        for span in self.sweep_line.iter() {
             if span.left.merge {
                 print!("| l:   <merge>           ");
             } else {
@@ -1084,7 +1686,7 @@ impl FillTessellator {
 
     fn log_sl_points_at(&self, y: FixedPoint32) {
         print!("\nat y={:?}  sl: [", y);
-        for span in &self.sweep_line {
+        for span in self.sweep_line.iter() {
             if span.left.merge {
                 print!("| l:<merge> ");
             } else {
@@ -1149,6 +1751,10 @@ fn x_aabb_test(a1: FixedPoint32, b1: FixedPoint32, a2: FixedPoint32, b2: FixedPo
     min1 < max2 && max1 > min2
 }
 
+// Computes the intersection of two edges using only integer arithmetic, so that the
+// classification of the intersection is exact and agrees with `compare_directions` and
+// `test_span_side`. The intersection is rounded to the nearest point of the fixed point grid.
+//
 // TODO[optim]: This function shows up pretty high in the profiles.
 fn segment_intersection(
     a1: TessPoint,
@@ -1156,61 +1762,66 @@ fn segment_intersection(
     a2: TessPoint,
     b2: TessPoint, // An already inserted edge.
 ) -> Option<TessPoint> {
-
-    // This early-out test gives a noticeable performance improvement.
     if !x_aabb_test(a1.x, b1.x, a2.x, b2.x) {
         return None;
     }
 
-    fn tess_point(x: f64, y: f64) -> TessPoint {
-        TessPoint::new(FixedPoint32::from_f64(x), FixedPoint32::from_f64(y))
-    }
-
-    // TODO: moving this down after the v1_cross_v2 == 0.0 branch fixes test
-    // test_colinear_touching_squares2_failing
     if a1 == b2 || a1 == a2 || b1 == a2 || b1 == b2 {
         return None;
     }
 
-    let a1 = F64Point::new(a1.x.to_f64(), a1.y.to_f64());
-    let b1 = F64Point::new(b1.x.to_f64(), b1.y.to_f64());
-    let a2 = F64Point::new(a2.x.to_f64(), a2.y.to_f64());
-    let b2 = F64Point::new(b2.x.to_f64(), b2.y.to_f64());
-
-    let v1 = b1 - a1;
-    let v2 = b2 - a2;
-
-    debug_assert!(v2.x != 0.0 || v2.y != 0.0, "zero-length edge");
+    // The coordinates are 32 bits integers so the cross products fit in 64 bits
+    // and the products below in 96 bits.
+    let (a1x, a1y) = (a1.x.raw() as i128, a1.y.raw() as i128);
+    let (v1x, v1y) = ((b1.x - a1.x).raw() as i128, (b1.y - a1.y).raw() as i128);
+    let (v2x, v2y) = ((b2.x - a2.x).raw() as i128, (b2.y - a2.y).raw() as i128);
+    let (dx, dy) = ((a2.x - a1.x).raw() as i128, (a2.y - a1.y).raw() as i128);
 
-    let v1_cross_v2 = v1.cross(v2);
-    let a2_a1_cross_v1 = (a2 - a1).cross(v1);
-
-    if v1_cross_v2 == 0.0 {
+    let v1_cross_v2 = v1x * v2y - v1y * v2x;
+    if v1_cross_v2 == 0 {
         return None;
     }
 
-    let sign_v1_cross_v2 = if v1_cross_v2 > 0.0 { 1.0 } else { -1.0 };
-    let abs_v1_cross_v2 = v1_cross_v2 * sign_v1_cross_v2;
-
-    // t and u should be divided by v1_cross_v2, but we postpone that to not lose precision.
-    // We have to respect the sign of v1_cross_v2 (and therefore t and u) so we apply it now and
-    // will use the absolute value of v1_cross_v2 afterwards.
-    let t = (a2 - a1).cross(v2) * sign_v1_cross_v2;
-    let u = a2_a1_cross_v1 * sign_v1_cross_v2;
+    let sign = if v1_cross_v2 > 0 { 1 } else { -1 };
+    let denom = v1_cross_v2 * sign;
+    let t = (dx * v2y - dy * v2x) * sign;
+    let u = (dx * v1y - dy * v1x) * sign;
 
-    if t > 0.0 && t < abs_v1_cross_v2 && u > 0.0 && u <= abs_v1_cross_v2 {
-
-        let res = a1 + (v1 * t) / abs_v1_cross_v2;
-        debug_assert!(res.y <= b1.y && res.y <= b2.y);
+    if t > 0 && t < denom && u > 0 && u <= denom {
+        let res = TessPoint::new(
+            FixedPoint32::from_raw((a1x + div_round(v1x * t, denom)) as i32),
+            FixedPoint32::from_raw((a1y + div_round(v1y * t, denom)) as i32),
+        );
 
         if res != a1 && res != b1 && res != a2 && res != b2 {
-            return Some(tess_point(res.x, res.y));
+            return Some(res);
         }
     }
 
     return None;
 }
 
+// Integer division rounding to the nearest integer (the denominator must be positive).
+fn div_round(num: i128, denom: i128) -> i128 {
+    if num >= 0 {
+        (num + denom / 2) / denom
+    } else {
+        (num - denom / 2) / denom
+    }
+}
+
+// Exact sign of the cross product of two vectors of the fixed point grid. The coordinates
+// are 32 bits integers so the products can't overflow in 128 bits.
+fn cross_sign(a: TessVec2, b: TessVec2) -> Ordering {
+    let cross = a.x.raw() as i128 * b.y.raw() as i128 - a.y.raw() as i128 * b.x.raw() as i128;
+    return cross.cmp(&0);
+}
+
+// Orders directions pointing downward (or horizontally to the right) from left to right.
+fn compare_directions(a: TessVec2, b: TessVec2) -> Ordering {
+    return cross_sign(a, b);
+}
+
 // Returns true if the position is on the right side of the edge.
 fn test_span_side(span_edge: &SpanEdge, position: TessPoint) -> bool {
     if span_edge.merge {
@@ -1225,17 +1836,15 @@ fn test_span_side(span_edge: &SpanEdge, position: TessPoint) -> bool {
     let from = span_edge.upper;
     let to = span_edge.lower;
 
-    let vx = (to.x - from.x).raw() as i64;
-    let vy = (to.y - from.y).raw() as i64;
-    if vy == 0 {
+    let v = to - from;
+    if v.y.raw() == 0 {
         // If the segment is horizontal, pick the biggest x value (the right-most point).
         // That's arbitrary, not sure it is the right thing to do.
         return cmp::max(position.x.raw(), to.x.raw()) > position.x.raw();
     }
-    // shuffled around from:
-    // edge_from.x + (point.y - edge_from.y) * vx / vy > point.x
-    // in order to remove the division.
-    return (position.y - from.y).raw() as i64 * vx > (position.x - from.x).raw() as i64 * vy;
+    // Same as `edge_from.x + (point.y - edge_from.y) * vx / vy > point.x` without the
+    // division.
+    return cross_sign(v, position - from) == Ordering::Greater;
 }
 
 fn test_span_touches(span_edge: &SpanEdge, position: TessPoint) -> bool {
@@ -1256,6 +1865,8 @@ fn test_span_touches(span_edge: &SpanEdge, position: TessPoint) -> bool {
 struct Span {
     left: SpanEdge,
     right: SpanEdge,
+    // Triangulates the monotone polygon between the two edges of the span.
+    tess: MonotoneTessellator,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -1267,7 +1878,13 @@ struct SpanEdge {
 }
 
 impl Span {
-    fn begin(current: TessPoint, id: VertexId, left: TessPoint, right: TessPoint) -> Span {
+    fn begin(
+        current: TessPoint,
+        id: VertexId,
+        left: TessPoint,
+        right: TessPoint,
+        tess: MonotoneTessellator,
+    ) -> Span {
         Span {
             left: SpanEdge {
                 upper: current,
@@ -1281,6 +1898,7 @@ impl Span {
                 upper_id: id,
                 merge: false,
             },
+            tess: tess,
         }
     }
 
@@ -1291,6 +1909,9 @@ impl Span {
 
     fn merge_vertex(&mut self, vertex: TessPoint, id: VertexId, side: Side) {
         self.set_upper_vertex(vertex, id, side);
+        // The edge stays degenerate until the merge vertex is connected to a vertex below,
+        // even when a Steiner point replaces a merge vertex that is not connected yet.
+        self.mut_edge(side).lower = vertex;
         self.mut_edge(side).merge = true;
     }
 
@@ -1305,6 +1926,26 @@ impl Span {
         edge.merge = false;
     }
 
+    /// The smallest x coordinate of the span's edges.
+    #[inline]
+    fn min_x(&self) -> FixedPoint32 {
+        self.left.upper.x.min(self.left.lower.x).min(self.right.upper.x.min(self.right.lower.x))
+    }
+
+    /// The largest x coordinate of the span's edges.
+    #[inline]
+    fn max_x(&self) -> FixedPoint32 {
+        self.left.upper.x.max(self.left.lower.x).max(self.right.upper.x.max(self.right.lower.x))
+    }
+
+    #[inline]
+    fn side_edge(&self, side: Side) -> &SpanEdge {
+        return match side {
+                   Side::Left => &self.left,
+                   Side::Right => &self.right,
+               };
+    }
+
     #[inline]
     fn mut_edge(&mut self, side: Side) -> &mut SpanEdge {
         return match side {
@@ -1314,6 +1955,10 @@ impl Span {
     }
 }
 
+impl XExtent for Span {
+    fn x_extent(&self) -> (FixedPoint32, FixedPoint32) { (self.min_x(), self.max_x()) }
+}
+
 
 /// Defines an ordering between two points
 ///
@@ -1330,14 +1975,6 @@ pub fn is_after<T: PartialOrd, U>(a: TypedPoint2D<T, U>, b: TypedPoint2D<T, U>)
 fn to_internal(v: Point) -> TessPoint { TessPoint::new(fixed(v.x), fixed(v.y)) }
 #[inline]
 fn to_f32_point(v: TessPoint) -> Point { point(v.x.to_f32(), v.y.to_f32()) }
-#[inline]
-fn to_f32_vec2(v: TessVec2) -> Vec2 { vec2(v.x.to_f32(), v.y.to_f32()) }
-
-#[inline]
-fn compute_angle(v: TessVec2) -> f32 {
-    // TODO: compute directed angles using fixed point vectors.
-    -directed_angle(vec2(1.0, 0.0), to_f32_vec2(v))
-}
 
 /// A sequence of edges sorted from top to bottom, to be used as the tessellator's input.
 pub struct FillEvents {
@@ -1363,19 +2000,121 @@ impl FillEvents {
     }
 
     pub fn set_path_iter<Iter: Iterator<Item = FlattenedEvent>>(&mut self, it: Iter) {
-        self.clear();
-        let mut tmp = FillEvents::new();
+        self.set_path_iter_on_grid(it, FRACTIONAL_BITS);
+    }
+
+    /// Add points that must appear as vertices of the tessellation.
+    ///
+    /// Steiner points are useful to attach data to the interior of a shape (elevation samples,
+    /// for example) or to avoid very long and thin triangles. Points that are on an edge split
+    /// the edge. Points that are outside of the shape are added to the geometry but aren't
+    /// referenced by any triangle.
+    pub fn add_steiner_points(&mut self, points: &[Point]) {
+        if points.is_empty() {
+            return;
+        }
+        self.vertices.extend(points.iter().map(|p| to_internal(*p)));
+        self.vertices.sort_unstable_by(|a, b| compare_positions(*a, *b));
+    }
+
+    /// Adds the events of another path, as if the two paths were built together.
+    ///
+    /// Both lists of events are already sorted, so they are merged in linear time instead of
+    /// being sorted again.
+    pub fn append(&mut self, other: &FillEvents) {
+        let edges = replace(&mut self.edges, Vec::with_capacity(0));
+        self.edges = merge_sorted(&edges, &other.edges, |a, b| {
+            compare_positions(a.upper, b.upper).then(compare_positions(a.lower, b.lower))
+        });
+        let vertices = replace(&mut self.vertices, Vec::with_capacity(0));
+        self.vertices = merge_sorted(&vertices, &other.vertices, |a, b| compare_positions(*a, *b));
+    }
+
+    /// Returns true if there is no edge.
+    pub fn is_empty(&self) -> bool { self.edges.is_empty() }
+
+    /// The smallest rectangle containing the edges.
+    pub fn bounding_rect(&self) -> Rect {
+        if self.edges.is_empty() {
+            return Rect::zero();
+        }
+
+        let first = self.edges[0].upper;
+        let (mut min, mut max) = (first, first);
+        for edge in &self.edges {
+            for p in &[edge.upper, edge.lower] {
+                min = TessPoint::new(cmp::min(min.x, p.x), cmp::min(min.y, p.y));
+                max = TessPoint::new(cmp::max(max.x, p.x), cmp::max(max.y, p.y));
+            }
+        }
+        let (min, max) = (to_f32_point(min), to_f32_point(max));
+
+        return rect(min.x, min.y, max.x - min.x, max.y - min.y);
+    }
+
+    /// Same as `set_path_iter`, snapping the points to a grid of `1 / 2^fractional_bits` units.
+    ///
+    /// Panics if `fractional_bits` is larger than `MAX_FIXED_POINT_GRID`.
+    pub fn set_path_iter_on_grid<Iter: Iterator<Item = FlattenedEvent>>(
+        &mut self,
+        it: Iter,
+        fractional_bits: u32,
+    ) {
+        assert!(fractional_bits <= FRACTIONAL_BITS);
+        self.clear();
+        let mut tmp = FillEvents::new();
         ::std::mem::swap(self, &mut tmp);
         let mut builder = EventsBuilder::new();
         builder.recycle(tmp);
+        builder.grid_shift = FRACTIONAL_BITS - fractional_bits;
         let mut tmp = builder.build(it);
         ::std::mem::swap(self, &mut tmp);
     }
 }
 
+fn merge_sorted<T: Copy, F>(a: &[T], b: &[T], cmp: F) -> Vec<T>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut result = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if cmp(&b[j], &a[i]) == Ordering::Less {
+            result.push(b[j]);
+            j += 1;
+        } else {
+            result.push(a[i]);
+            i += 1;
+        }
+    }
+    result.extend_from_slice(&a[i..]);
+    result.extend_from_slice(&b[j..]);
+
+    return result;
+}
+
+fn set_path_events<Iter>(events: &mut FillEvents, it: Iter, options: &FillOptions)
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    if let Some(fractional_bits) = options.fixed_point_grid {
+        events.set_path_iter_on_grid(it, fractional_bits);
+    } else {
+        events.set_path_iter(it);
+    }
+}
+
+/// Number of fractional bits of the tessellator's internal fixed point coordinates.
+const FRACTIONAL_BITS: u32 = 16;
+
+/// The largest supported value of `FillOptions::fixed_point_grid`, the grid can't be finer
+/// than the tessellator's fixed point numbers.
+pub const MAX_FIXED_POINT_GRID: u32 = FRACTIONAL_BITS;
+
 struct EventsBuilder {
     edges: Vec<Edge>,
     vertices: Vec<TessPoint>,
+    grid_shift: u32,
 }
 
 impl EventsBuilder {
@@ -1383,9 +2122,29 @@ impl EventsBuilder {
         EventsBuilder {
             edges: Vec::new(),
             vertices: Vec::new(),
+            grid_shift: 0,
         }
     }
 
+    fn snap(&self, v: Point) -> TessPoint {
+        let p = to_internal(v);
+        if self.grid_shift == 0 {
+            return p;
+        }
+
+        let shift = self.grid_shift;
+        // Rounding up can go past the largest representable value, in which case the value is
+        // rounded down to the last point of the grid instead.
+        let max = (FixedPoint32::max_val().raw() >> shift) << shift;
+        let round = |val: FixedPoint32| {
+            let half = 1i64 << (shift - 1);
+            let rounded = ((val.raw() as i64 + half) >> shift) << shift;
+            FixedPoint32::from_raw(cmp::min(rounded, max as i64) as i32)
+        };
+
+        return TessPoint::new(round(p.x), round(p.y));
+    }
+
     fn recycle(&mut self, events: FillEvents) {
         self.edges = events.edges;
         self.vertices = events.vertices;
@@ -1400,7 +2159,7 @@ impl EventsBuilder {
         for evt in inputs {
             match evt {
                 FlattenedEvent::LineTo(next) => {
-                    let next = to_internal(next);
+                    let next = self.snap(next);
                     if next == current {
                         continue;
                     }
@@ -1433,7 +2192,7 @@ impl EventsBuilder {
                     current = first;
                 }
                 FlattenedEvent::MoveTo(next) => {
-                    let next = to_internal(next);
+                    let next = self.snap(next);
                     if nth > 1 {
                         self.add_edge(current, first);
                         self.vertex(previous, current, first);
@@ -1446,8 +2205,12 @@ impl EventsBuilder {
             }
         }
 
-        self.edges.sort_by(|a, b| compare_positions(a.upper, b.upper));
-        self.vertices.sort_by(|a, b| compare_positions(*a, *b));
+        // Unstable sorts don't allocate. Edges that start at the same position are ordered by
+        // their lower end so that the order doesn't depend on the sorting algorithm.
+        self.edges.sort_unstable_by(|a, b| {
+            compare_positions(a.upper, b.upper).then(compare_positions(a.lower, b.lower))
+        });
+        self.vertices.sort_unstable_by(|a, b| compare_positions(*a, *b));
 
         return FillEvents {
                    edges: self.edges,
@@ -1498,16 +2261,22 @@ fn test_iter_builder() {
     tess.tessellate_events(&events, &FillOptions::default(), &mut vertex_builder).unwrap();
 }
 
-/// The fill rule defines how to determine what is inside and what is outside of the shape.
-///
-/// See the SVG specification.
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum FillRule {
-    EvenOdd,
-    NonZero,
+pub use core::FillRule;
+
+/// The triangulation algorithms of the fill tessellator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FillAlgorithm {
+    /// The sweep line algorithm described in the module documentation.
+    Sweep,
+    /// The [ear clipping](../ear_clipping/index.html) algorithm, faster for small polygons.
+    ///
+    /// It only supports the even-odd fill rule and polygons that don't intersect themselves.
+    /// Steiner points and the `fixed_point_grid`, `delaunay` and `refine` options are ignored.
+    EarClipping,
 }
 
 /// Parameters for the tessellator.
+#[derive(Clone)]
 pub struct FillOptions {
     /// Maximum allowed distance to the path when building an approximation.
     pub tolerance: f32,
@@ -1523,6 +2292,39 @@ pub struct FillOptions {
     /// Not implemented yet!
     pub vertex_aa: bool,
 
+    /// If set, the input is snapped to a grid of `1 / 2^n` units before the tessellation,
+    /// which merges the vertices that are closer than the grid's resolution. The tessellation
+    /// fails with `FillError::InvalidFixedPointGrid` if `n` is larger than
+    /// `MAX_FIXED_POINT_GRID` (16).
+    ///
+    /// When tessellating pre-built `FillEvents`, the events must be built with
+    /// `FillEvents::set_path_iter_on_grid` for the snapping to apply.
+    pub fixed_point_grid: Option<u32>,
+
+    /// If true, the edges of the output are flipped until the triangulation is a constrained
+    /// Delaunay triangulation, which avoids most long and thin triangles.
+    ///
+    /// The triangles are sent to the geometry builder at the end of the tessellation instead
+    /// of as soon as they are produced. See the `delaunay` module.
+    pub delaunay: bool,
+
+    /// If set, vertices are added to the output until the triangles meet the quality
+    /// bounds. This implies `delaunay`.
+    pub refine: Option<RefineOptions>,
+
+    /// If true, the coordinates of the path events are checked before the tessellation and
+    /// the first event with a NaN or infinite coordinate, or a coordinate that doesn't fit in
+    /// the tessellator's fixed point numbers, is reported as a `FillError::InvalidInput`.
+    ///
+    /// Pre-built `FillEvents` and Steiner points are not checked.
+    pub validate_input: bool,
+
+    /// The triangulation algorithm, `FillAlgorithm::Sweep` by default.
+    ///
+    /// Only used when tessellating a path iterator, pre-built `FillEvents` are always
+    /// tessellated with the sweep line algorithm.
+    pub algorithm: FillAlgorithm,
+
     // To be able to add fields without making it a breaking change, add an empty private field
     // which makes it impossible to create a FillOptions without the calling constructor.
     _private: (),
@@ -1536,6 +2338,11 @@ impl FillOptions {
             tolerance: 0.1,
             fill_rule: FillRule::EvenOdd,
             vertex_aa: false,
+            fixed_point_grid: None,
+            delaunay: false,
+            refine: None,
+            validate_input: false,
+            algorithm: FillAlgorithm::Sweep,
             _private: (),
         }
     }
@@ -1555,6 +2362,37 @@ impl FillOptions {
         self.vertex_aa = true;
         return self;
     }
+
+    /// Snap the input to a grid of `1 / 2^fractional_bits` units, at most
+    /// `MAX_FIXED_POINT_GRID`. For example 8 fractional bits correspond to a 1/256 units grid.
+    pub fn with_fixed_point_grid(mut self, fractional_bits: u32) -> FillOptions {
+        self.fixed_point_grid = Some(fractional_bits);
+        return self;
+    }
+
+    /// Produce a constrained Delaunay triangulation.
+    pub fn with_delaunay(mut self) -> FillOptions {
+        self.delaunay = true;
+        return self;
+    }
+
+    /// Subdivide the triangles until their area is at most `max_area` and their angles
+    /// (in radians) at least `min_angle`.
+    pub fn with_refinement(mut self, max_area: f32, min_angle: f32) -> FillOptions {
+        self.refine = Some(RefineOptions::new(max_area, min_angle));
+        return self;
+    }
+
+    /// Check the coordinates of the input, see `validate_input`.
+    pub fn with_input_validation(mut self) -> FillOptions {
+        self.validate_input = true;
+        return self;
+    }
+
+    pub fn with_algorithm(mut self, algorithm: FillAlgorithm) -> FillOptions {
+        self.algorithm = algorithm;
+        return self;
+    }
 }
 
 impl Side {
@@ -1604,6 +2442,19 @@ impl MonotoneTessellator {
         return tess;
     }
 
+    /// Start a new monotone polygon, reusing the allocations of the previous one.
+    pub fn restart(&mut self, pos: Point, id: VertexId) {
+        debug_assert!(self.triangles.is_empty());
+        let first = MonotoneVertex {
+            pos: pos,
+            id: id,
+            side: Side::Left,
+        };
+        self.stack.clear();
+        self.stack.push(first);
+        self.previous = first;
+    }
+
     pub fn vertex(&mut self, pos: Point, id: VertexId, side: Side) {
         let current = MonotoneVertex {
             pos: pos,
@@ -1640,7 +2491,7 @@ impl MonotoneTessellator {
                     swap(&mut a, &mut b);
                 }
 
-                if directed_angle2(b.pos, current.pos, a.pos) <= PI {
+                if is_convex_turn(b.pos, current.pos, a.pos) {
                     self.push_triangle(&a, &b, &current);
                     last_popped = self.stack.pop();
                 } else {
@@ -1667,7 +2518,7 @@ impl MonotoneTessellator {
     fn push_triangle(&mut self, a: &MonotoneVertex, b: &MonotoneVertex, c: &MonotoneVertex) {
         //println!(" #### triangle {} {} {}", a.id.offset(), b.id.offset(), c.id.offset());
 
-        if directed_angle2(b.pos, c.pos, a.pos) <= PI {
+        if is_convex_turn(b.pos, c.pos, a.pos) {
             self.triangles.push((a.id, b.id, c.id));
         } else {
             self.triangles.push((b.id, a.id, c.id));
@@ -1682,6 +2533,90 @@ impl MonotoneTessellator {
     }
 }
 
+/// Tessellates a y-monotone polygon without going through the fill tessellator's sweep.
+///
+/// A polygon is y-monotone if every horizontal line crosses its outline at most twice. This
+/// is the case for the pieces that the fill tessellator itself decomposes paths into, and
+/// for the output of most trapezoidal or monotone decompositions, in which case this is much
+/// cheaper than tessellating the polygon with a [FillTessellator](struct.FillTessellator.html).
+///
+/// The points are the vertices of the polygon in order, clockwise or counter-clockwise,
+/// without repeating the first point at the end. They are added to the output in the same
+/// order.
+///
+/// Preconditions (not checked in release builds):
+///
+/// - The polygon is y-monotone and doesn't intersect itself.
+/// - Consecutive points are different.
+///
+/// The triangles produced from a polygon that doesn't satisfy them are unspecified.
+pub fn tessellate_monotone_polygon<Output>(polygon: &[Point], output: &mut Output) -> Count
+where
+    Output: GeometryBuilder<Vertex>,
+{
+    output.begin_geometry();
+
+    let n = polygon.len();
+    if n < 3 {
+        return output.end_geometry();
+    }
+
+    let mut ids = Vec::with_capacity(n);
+    let mut top = 0;
+    let mut bottom = 0;
+    let mut double_area = 0.0;
+    for i in 0..n {
+        let p = polygon[i];
+        ids.push(output.add_vertex(Vertex { position: p, normal: vec2(0.0, 0.0) }));
+        if is_after(polygon[top], p) {
+            top = i;
+        }
+        if is_after(p, polygon[bottom]) {
+            bottom = i;
+        }
+        let next = polygon[(i + 1) % n];
+        double_area += p.x * next.y - next.x * p.y;
+    }
+
+    if top == bottom {
+        return output.end_geometry();
+    }
+
+    // With the y axis pointing down, the vertices that follow the top one are on the left
+    // side of the polygon if it is counter-clockwise.
+    let (forward_side, backward_side) = if double_area < 0.0 {
+        (Side::Left, Side::Right)
+    } else {
+        (Side::Right, Side::Left)
+    };
+
+    let mut tess = MonotoneTessellator::begin(polygon[top], ids[top]);
+    let mut forward = (top + 1) % n;
+    let mut backward = (top + n - 1) % n;
+    while forward != bottom || backward != bottom {
+        let take_forward = backward == bottom ||
+            (forward != bottom && !is_after(polygon[forward], polygon[backward]));
+        if take_forward {
+            tess.vertex(polygon[forward], ids[forward], forward_side);
+            forward = (forward + 1) % n;
+        } else {
+            tess.vertex(polygon[backward], ids[backward], backward_side);
+            backward = (backward + n - 1) % n;
+        }
+    }
+    tess.end(polygon[bottom], ids[bottom]);
+    tess.flush(output);
+
+    return output.end_geometry();
+}
+
+// Equivalent to `directed_angle2(center, a, b) <= PI` without computing angles, which
+// would make the result depend on the platform's `atan2`.
+#[inline]
+fn is_convex_turn(center: Point, a: Point, b: Point) -> bool {
+    (a - center).cross(b - center) >= 0.0
+}
+
 #[test]
 fn test_monotone_tess() {
     println!(" ------------ ");
@@ -1726,6 +2661,50 @@ fn test_monotone_tess() {
     println!(" ------------ ");
 }
 
+#[test]
+fn test_tessellate_monotone_polygon() {
+    let polygon = [
+        point(0.0, 0.0),
+        point(2.0, 1.0),
+        point(3.0, 3.0),
+        point(1.0, 5.0),
+        point(-1.0, 4.0),
+        point(-2.0, 2.0),
+    ];
+    let mut reversed = polygon.to_vec();
+    reversed.reverse();
+
+    for points in &[&polygon[..], &reversed[..]] {
+        let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+        let count = tessellate_monotone_polygon(points, &mut simple_builder(&mut buffers));
+        assert_eq!(count, Count { vertices: 6, indices: 12 });
+        for i in 0..6 {
+            assert_eq!(buffers.vertices[i].position, points[i]);
+        }
+
+        let mut area = 0.0;
+        let mut sign = 0.0;
+        for triangle in buffers.indices.chunks(3) {
+            let a = buffers.vertices[triangle[0] as usize].position;
+            let b = buffers.vertices[triangle[1] as usize].position;
+            let c = buffers.vertices[triangle[2] as usize].position;
+            let signed_area = (b - a).cross(c - a) * 0.5;
+            assert!(signed_area != 0.0);
+            if sign == 0.0 {
+                sign = signed_area.signum();
+            }
+            // All triangles have the same orientation.
+            assert_eq!(signed_area.signum(), sign);
+            area += signed_area.abs();
+        }
+        assert_eq!(area, 15.0);
+    }
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    let count = tessellate_monotone_polygon(&polygon[..2], &mut simple_builder(&mut buffers));
+    assert_eq!(count, Count { vertices: 0, indices: 0 });
+}
+
 #[cfg(test)]
 fn tessellate(path: PathSlice, log: bool) -> Result<usize, FillError> {
     let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
@@ -1840,6 +2819,344 @@ fn test_simple_merge_split() {
     test_path_with_rotations(path.build(), 0.001, Some(4));
 }
 
+#[test]
+fn test_reuse_tessellator() {
+    // Tessellating several times with the same tessellator recycles the monotone
+    // tessellators and must produce the same result each time.
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(1.0, 1.0));
+    path.line_to(point(2.0, 0.0));
+    path.line_to(point(2.0, 3.0));
+    path.line_to(point(1.0, 2.0));
+    path.line_to(point(0.0, 3.0));
+    path.close();
+    let path = path.build();
+
+    let mut tess = FillTessellator::new();
+    for _ in 0..3 {
+        let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+        tess.tessellate_path(
+            path.path_iter().flattened(0.05),
+            &FillOptions::default(),
+            &mut simple_builder(&mut buffers),
+        ).unwrap();
+        assert_eq!(buffers.indices.len(), 12);
+    }
+    assert!(!tess.monotone_pool.is_empty());
+    assert!(tess.sweep_line.is_empty());
+}
+
+#[test]
+fn test_allocations() {
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(2.0, 1.0));
+    path.line_to(point(2.0, 3.0));
+    path.line_to(point(1.0, 2.0));
+    path.line_to(point(0.0, 3.0));
+    path.close();
+    let path = path.build();
+
+    let mut allocations = FillTessellatorAllocations::new();
+    allocations.reserve(64, 4);
+    for _ in 0..3 {
+        let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+        let mut tess = FillTessellator::with_allocations(allocations);
+        tess.tessellate_path(
+            path.path_iter().flattened(0.05),
+            &FillOptions::default(),
+            &mut simple_builder(&mut buffers),
+        ).unwrap();
+        assert_eq!(buffers.indices.len(), 9);
+        allocations = tess.into_allocations();
+        assert!(allocations.monotone_pool.len() >= 4);
+    }
+}
+
+// A comb with many teeth, which leads to a large number of spans on the sweep line
+// at the same time.
+#[cfg(test)]
+fn comb(n: usize) -> Path {
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 10.0));
+    for i in 0..(n + 1) {
+        path.line_to(point(2.0 * i as f32, 0.0));
+        path.line_to(point(2.0 * i as f32 + 1.0, 5.0));
+    }
+    path.line_to(point(2.0 * n as f32 + 1.0, 10.0));
+    path.close();
+
+    return path.build();
+}
+
+#[test]
+fn test_many_spans() {
+    let n = 500;
+    test_path(comb(n).as_slice(), Some(2 * n + 2));
+}
+
+#[test]
+fn test_many_spans_scaling() {
+    // The number of spans visited while looking for the ones that are relevant to each
+    // vertex should grow as n * log(n). Scanning the whole sweep line for each vertex would
+    // visit 64 times more spans with 8 times more teeth.
+    let visits = |n: usize| -> usize {
+        let path = comb(n);
+        let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+        let mut tess = FillTessellator::new();
+        tess.tessellate_path(
+            path.path_iter().flattened(0.05),
+            &FillOptions::default(),
+            &mut simple_builder(&mut buffers),
+        ).unwrap();
+        assert_eq!(buffers.indices.len() / 3, 2 * n + 2);
+        return tess.sweep_line.visits();
+    };
+
+    let small = visits(500);
+    let large = visits(4000);
+    assert!(large < small * 16, "{} visits with 500 teeth, {} with 4000", small, large);
+}
+
+#[test]
+fn test_snapshots() {
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(2.0, 1.0));
+    path.line_to(point(2.0, 3.0));
+    path.line_to(point(1.0, 2.0));
+    path.line_to(point(0.0, 3.0));
+    path.close();
+    let path = path.build();
+
+    let mut tess = FillTessellator::new();
+    tess.record_snapshots(true);
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    tess.tessellate_path(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    let snapshots = tess.snapshots();
+    assert_eq!(snapshots.len(), 5);
+    assert_eq!(snapshots[0].position, point(0.0, 0.0));
+    assert_eq!(snapshots[0].spans.len(), 1);
+    // The split vertex at (1, 2) splits the span in two.
+    assert_eq!(snapshots[2].position, point(1.0, 2.0));
+    assert_eq!(snapshots[2].spans.len(), 2);
+    assert!(snapshots.last().unwrap().spans.is_empty());
+    assert_eq!(snapshots.last().unwrap().triangle_count, buffers.indices.len() / 3);
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing() {
+    use std::sync::Mutex;
+
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(2.0, 1.0));
+    path.line_to(point(2.0, 3.0));
+    path.line_to(point(1.0, 2.0));
+    path.line_to(point(0.0, 3.0));
+    path.close();
+    let path = path.build();
+
+    let trace = Arc::new(Mutex::new(Vec::new()));
+    let trace_clone = trace.clone();
+
+    let mut tess = FillTessellator::new();
+    tess.set_tracer(Some(Box::new(move |evt: &TraceEvent| {
+        trace_clone.lock().unwrap().push(evt.clone());
+    })));
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    tess.tessellate_path(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    let trace = trace.lock().unwrap();
+    let vertices = trace.iter().filter(|evt| match **evt {
+        TraceEvent::Vertex { .. } => true,
+        _ => false,
+    }).count();
+    let triangles = trace.iter().filter(|evt| match **evt {
+        TraceEvent::Triangle(..) => true,
+        _ => false,
+    }).count();
+    assert_eq!(vertices, 5);
+    assert_eq!(triangles, buffers.indices.len() / 3);
+    assert_eq!(trace[1], TraceEvent::VertexEvent {
+        position: point(0.0, 0.0),
+        event_type: VertexEventType::Start,
+    });
+    assert!(trace.contains(&TraceEvent::VertexEvent {
+        position: point(1.0, 2.0),
+        event_type: VertexEventType::Split,
+    }));
+}
+
+#[test]
+fn test_reference_rasterizer() {
+    use extra::scanline_rasterizer::{rasterize_path, rasterize_triangles, compare};
+
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(20.0, 10.0));
+    path.line_to(point(20.0, 30.0));
+    path.line_to(point(10.0, 20.0));
+    path.line_to(point(0.0, 30.0));
+    path.close();
+    path.move_to(point(3.0, 5.0));
+    path.line_to(point(5.0, 7.0));
+    path.line_to(point(3.0, 9.0));
+    path.close();
+    let path = path.build();
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    let positions: Vec<Point> = buffers.vertices.iter().map(|v| v.position).collect();
+    let reference = rasterize_path(path.path_iter().flattened(0.05), FillRule::EvenOdd, 32, 32, 4);
+    let result = rasterize_triangles(&positions, &buffers.indices, 32, 32, 4);
+
+    // Allow a sample to fall on the wrong side of an edge shared by two triangles.
+    let diff = compare(&reference, &result);
+    assert!(diff.max_difference <= 1.0 / 16.0, "{}", result.to_ascii());
+}
+
+#[cfg(test)]
+fn tessellate_with_steiner_points(path: &Path, points: &[Point]) -> VertexBuffers<Vertex> {
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path_with_steiner_points(
+        path.path_iter().flattened(0.05),
+        points,
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    return buffers;
+}
+
+#[test]
+fn test_steiner_points() {
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(10.0, 0.0));
+    path.line_to(point(10.0, 10.0));
+    path.line_to(point(0.0, 10.0));
+    path.close();
+    let path = path.build();
+
+    // A triangulation of a polygon with n vertices and k interior points has n - 2 + 2k
+    // triangles.
+    let buffers = tessellate_with_steiner_points(&path, &[point(5.0, 5.0)]);
+    assert_eq!(buffers.indices.len() / 3, 4);
+    let steiner = buffers.vertices.iter().position(|v| v.position == point(5.0, 5.0)).unwrap();
+    assert!(buffers.indices.contains(&(steiner as u16)));
+
+    let buffers = tessellate_with_steiner_points(
+        &path,
+        &[point(3.0, 7.0), point(5.0, 5.0), point(8.0, 2.0)],
+    );
+    assert_eq!(buffers.indices.len() / 3, 8);
+
+    // On an edge.
+    let buffers = tessellate_with_steiner_points(&path, &[point(10.0, 5.0)]);
+    assert_eq!(buffers.indices.len() / 3, 3);
+
+    // Outside of the shape.
+    let buffers = tessellate_with_steiner_points(&path, &[point(20.0, 5.0)]);
+    assert_eq!(buffers.indices.len() / 3, 2);
+}
+
+#[test]
+fn test_steiner_point_below_merge() {
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(1.0, 1.0));
+    path.line_to(point(2.0, 0.0));
+    path.line_to(point(2.0, 3.0));
+    path.line_to(point(1.0, 2.0));
+    path.line_to(point(0.0, 3.0));
+    path.close();
+    let path = path.build();
+
+    // The merge vertex at (1.0, 1.0) gets connected to the steiner point.
+    let buffers = tessellate_with_steiner_points(&path, &[point(1.0, 1.5)]);
+    assert_eq!(buffers.indices.len() / 3, 6);
+}
+
+#[test]
+fn test_delaunay_option() {
+    // A thin kite, the sweep line splits it along its long diagonal.
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(4.0, -1.0));
+    path.line_to(point(8.0, 0.0));
+    path.line_to(point(4.0, 1.0));
+    path.close();
+    let path = path.build();
+
+    let has_long_diagonal = |buffers: &VertexBuffers<Vertex>| {
+        buffers.indices.chunks(3).any(|t| {
+            let xs: Vec<f32> = t.iter().map(|&i| buffers.vertices[i as usize].position.x).collect();
+            xs.contains(&0.0) && xs.contains(&8.0)
+        })
+    };
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+    assert!(has_long_diagonal(&buffers));
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default().with_delaunay(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+    assert_eq!(buffers.indices.len(), 6);
+    assert!(!has_long_diagonal(&buffers));
+}
+
+#[test]
+fn test_refinement_option() {
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(10.0, 0.0));
+    path.line_to(point(10.0, 10.0));
+    path.line_to(point(0.0, 10.0));
+    path.close();
+    let path = path.build();
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default().with_refinement(10.0, 0.0),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    assert!(buffers.vertices.len() > 4);
+    assert!(buffers.indices.len() / 3 >= 10);
+    for t in buffers.indices.chunks(3) {
+        let a = buffers.vertices[t[0] as usize].position;
+        let b = buffers.vertices[t[1] as usize].position;
+        let c = buffers.vertices[t[2] as usize].position;
+        assert!(((b - a).cross(c - a) * 0.5).abs() <= 10.0);
+    }
+}
+
 #[test]
 fn test_simple_aligned() {
     let mut path = Path::builder();
@@ -1943,6 +3260,136 @@ fn test_auto_intersection_type1() {
     test_path(path.as_slice(), Some(2));
 }
 
+#[test]
+fn test_auto_intersection_fixed_point_grid() {
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(2.0, 1.0));
+    path.line_to(point(0.0, 2.0));
+    path.line_to(point(2.0, 3.0));
+    path.close();
+    let path = path.build();
+
+    let options = FillOptions::default().with_fixed_point_grid(8);
+    let mut results = Vec::new();
+    for _ in 0..2 {
+        let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+        FillTessellator::new().tessellate_path(
+            path.path_iter().flattened(0.05),
+            &options,
+            &mut simple_builder(&mut buffers),
+        ).unwrap();
+        assert_eq!(buffers.indices.len(), 6);
+        results.push(buffers);
+    }
+
+    assert_eq!(results[0].indices, results[1].indices);
+    for (a, b) in results[0].vertices.iter().zip(results[1].vertices.iter()) {
+        assert_eq!(a.position, b.position);
+    }
+}
+
+#[test]
+fn test_invalid_fixed_point_grid() {
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(1.0, 0.0));
+    path.line_to(point(1.0, 1.0));
+    path.close();
+    let path = path.build();
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    let result = FillTessellator::new().tessellate_path(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default().with_fixed_point_grid(MAX_FIXED_POINT_GRID + 1),
+        &mut simple_builder(&mut buffers),
+    );
+    assert_eq!(result, Err(FillError::InvalidFixedPointGrid(MAX_FIXED_POINT_GRID + 1)));
+    assert!(buffers.indices.is_empty());
+}
+
+#[test]
+fn test_tessellate_path_quantized() {
+    use geometry_builder::Quantized;
+
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(2.01, 1.0));
+    path.line_to(point(0.0, 2.0));
+    path.line_to(point(2.0, 3.0));
+    path.close();
+    let path = path.build();
+
+    let mut buffers: VertexBuffers<Quantized<Vertex>> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path_quantized(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default(),
+        4,
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+    assert_eq!(buffers.indices.len(), 6);
+
+    // The input was snapped before the tessellation, and the intersection is on the grid.
+    for v in &buffers.vertices {
+        assert_eq!(v.position[0] as f32, v.vertex.position.x * 16.0);
+        assert_eq!(v.position[1] as f32, v.vertex.position.y * 16.0);
+    }
+    let positions: Vec<[i16; 2]> = buffers.vertices.iter().map(|v| v.position).collect();
+    assert!(positions.contains(&[32, 16]));
+    assert!(positions.contains(&[16, 24]));
+}
+
+#[test]
+fn test_segment_intersection() {
+    let p = |x: f32, y: f32| TessPoint::new(fixed(x), fixed(y));
+    assert_eq!(
+        segment_intersection(p(0.0, 0.0), p(2.0, 2.0), p(2.0, 0.0), p(0.0, 2.0)),
+        Some(p(1.0, 1.0))
+    );
+    assert_eq!(
+        segment_intersection(p(0.0, 0.0), p(1.0, 1.0), p(2.0, 0.0), p(3.0, 1.0)),
+        None
+    );
+    // Touching at an endpoint is not an intersection.
+    assert_eq!(
+        segment_intersection(p(0.0, 0.0), p(1.0, 1.0), p(1.0, 1.0), p(2.0, 3.0)),
+        None
+    );
+}
+
+#[test]
+fn test_vertex_on_edge() {
+    // The vertex at (3, 3) lies exactly on the diagonal of the first triangle and the
+    // second triangle is very thin. The sweep line must classify them consistently.
+    let mut path = Path::builder();
+    path.move_to(point(0.0, 0.0));
+    path.line_to(point(10.0, 10.0));
+    path.line_to(point(0.0, 10.0));
+    path.close();
+    path.move_to(point(3.0, 3.0));
+    path.line_to(point(9.0, 4.0));
+    path.line_to(point(9.0, 4.001));
+    path.close();
+    path.move_to(point(1.0, 1.0));
+    path.line_to(point(5.0, 5.0));
+    path.line_to(point(2.0, 5.0));
+    path.close();
+
+    test_path(path.build().as_slice(), None);
+}
+
+#[test]
+fn test_snap_to_grid() {
+    let mut builder = EventsBuilder::new();
+    builder.grid_shift = FRACTIONAL_BITS - 2;
+    assert_eq!(builder.snap(point(0.3, -0.3)), TessPoint::new(fixed(0.25), fixed(-0.25)));
+    assert_eq!(builder.snap(point(1.9, 2.0)), TessPoint::new(fixed(2.0), fixed(2.0)));
+
+    // Values that would round past the largest fixed point number don't overflow.
+    builder.grid_shift = FRACTIONAL_BITS;
+    assert_eq!(builder.snap(point(32767.9, -32768.0)), TessPoint::new(fixed(32767.0), fixed(-32768.0)));
+}
+
 #[test]
 fn test_auto_intersection_type2() {
     //  o
@@ -2587,3 +4034,370 @@ fn test_close_at_first_position() {
 
     test_path(builder.build().as_slice(), None);
 }
+
+#[test]
+fn test_input_validation() {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.line_to(point(1.0, 1.0));
+    builder.close();
+    builder.move_to(point(10.0, 0.0));
+    builder.line_to(point(40000.0, 0.0));
+    builder.line_to(point(10.0, 1.0));
+    builder.close();
+    let path = builder.build();
+
+    let options = FillOptions::default().with_input_validation();
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    let mut tess = FillTessellator::new();
+    let result = tess.tessellate_path(path.path_iter().flattened(0.05), &options, &mut simple_builder(&mut buffers));
+    assert_eq!(
+        result,
+        Err(FillError::InvalidInput(InputError {
+            event: 5,
+            kind: ::core::input_validation::InvalidCoordinate::TooLarge,
+        }))
+    );
+    assert!(buffers.indices.is_empty());
+
+    // The tessellator can be used again.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.line_to(point(1.0, 1.0));
+    builder.close();
+    let path = builder.build();
+    let result = tess.tessellate_path(path.path_iter().flattened(0.05), &options, &mut simple_builder(&mut buffers));
+    assert_eq!(result, Ok(Count { vertices: 3, indices: 3 }));
+}
+
+#[test]
+fn test_normalized_path() {
+    use path::NormalizedPath;
+    use path_builder::PathBuilder;
+
+    // A tile with a square and a curved shape, tessellated without converting the whole
+    // path to floating point coordinates first.
+    let mut builder = NormalizedPath::builder(rect(0.0, 0.0, 4096.0, 4096.0));
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1024.0, 0.0));
+    builder.line_to(point(1024.0, 1024.0));
+    builder.line_to(point(0.0, 1024.0));
+    builder.close();
+    builder.move_to(point(2048.0, 2048.0));
+    builder.quadratic_bezier_to(point(4096.0, 2048.0), point(4096.0, 4096.0));
+    builder.line_to(point(2048.0, 4096.0));
+    builder.close();
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    let mut tess = FillTessellator::new();
+    tess.tessellate_path(
+        path.path_iter().flattened(1.0),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    assert!(buffers.indices.len() > 6);
+    for v in &buffers.vertices {
+        assert!(v.position.x >= 0.0 && v.position.x <= 4096.0);
+        assert!(v.position.y >= 0.0 && v.position.y <= 4096.0);
+    }
+}
+
+// FNV-1a hash of the bits of the output, to compare tessellations without storing them.
+#[cfg(test)]
+fn output_hash(buffers: &VertexBuffers<Vertex>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    {
+        let mut add = |value: u32| {
+            for i in 0..4 {
+                hash ^= ((value >> (i * 8)) & 0xff) as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        };
+        for v in &buffers.vertices {
+            add(v.position.x.to_bits());
+            add(v.position.y.to_bits());
+            add(v.normal.x.to_bits());
+            add(v.normal.y.to_bits());
+        }
+        for &i in &buffers.indices {
+            add(i as u32);
+        }
+    }
+
+    return hash;
+}
+
+#[cfg(test)]
+fn deterministic_test_cases() -> Vec<(&'static str, Path, FillOptions)> {
+    let mut cases = Vec::new();
+
+    let mut builder = Path::builder().with_svg();
+    build_logo_path(&mut builder);
+    let logo = builder.build();
+    cases.push(("logo", logo.clone(), FillOptions::default().with_tolerance(0.05)));
+    cases.push(("logo_delaunay", logo.clone(), FillOptions::default().with_tolerance(0.05).with_delaunay()));
+    cases.push(("logo_refined", logo, FillOptions::default().with_tolerance(0.05).with_refinement(20.0, 0.3)));
+
+    // Self-intersections, colinear and overlapping edges, edges starting at the same point.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 7.5));
+    builder.line_to(point(0.0, 10.0));
+    builder.line_to(point(10.0, 2.5));
+    builder.close();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(5.0, 5.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    builder.move_to(point(2.0, 0.1));
+    builder.line_to(point(8.3, 0.1));
+    builder.line_to(point(8.3, 9.7));
+    builder.line_to(point(4.1, 9.7));
+    builder.line_to(point(4.1, 0.1));
+    builder.close();
+    cases.push(("intersections", builder.build(), FillOptions::default()));
+
+    return cases;
+}
+
+#[cfg(test)]
+fn tessellate_deterministic(path: &Path, options: &FillOptions, tess: &mut FillTessellator) -> VertexBuffers<Vertex> {
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    tess.tessellate_path(
+        path.path_iter().flattened(options.tolerance),
+        options,
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    return buffers;
+}
+
+#[test]
+fn test_deterministic_repeatable() {
+    let mut reused = FillTessellator::new();
+    for (name, path, options) in deterministic_test_cases() {
+        let a = tessellate_deterministic(&path, &options, &mut FillTessellator::new());
+        let b = tessellate_deterministic(&path, &options, &mut reused);
+        let c = tessellate_deterministic(&path, &options, &mut reused);
+        assert!(!a.indices.is_empty(), "{}", name);
+        assert_eq!(a.indices, b.indices, "{}", name);
+        assert_eq!(a.indices, c.indices, "{}", name);
+        assert_eq!(output_hash(&a), output_hash(&b), "{}", name);
+        assert_eq!(output_hash(&a), output_hash(&c), "{}", name);
+    }
+}
+
+// Compares the output with the hashes recorded in
+// `golden/deterministic_fill.txt`, which must be the same on all platforms.
+//
+// A missing or different hash is a failure. Set the LYON_BLESS environment variable to
+// record the new hashes after an intentional change of the output or a new test case.
+#[test]
+fn test_deterministic_golden() {
+    use std::fs;
+    use std::io::{Read, Write};
+
+    let file_name = concat!(env!("CARGO_MANIFEST_DIR"), "/golden/deterministic_fill.txt");
+    let bless = ::std::env::var("LYON_BLESS").is_ok();
+
+    let mut golden = String::new();
+    if let Ok(mut file) = fs::File::open(file_name) {
+        file.read_to_string(&mut golden).unwrap();
+    }
+    let recorded: Vec<(String, String)> = golden.lines().filter_map(|line| {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some(name), Some(hash)) => Some((name.to_string(), hash.to_string())),
+            _ => None,
+        }
+    }).collect();
+
+    let mut output = String::new();
+    let mut mismatches = Vec::new();
+    for (name, path, options) in deterministic_test_cases() {
+        let hash = format!("{:016x}", output_hash(&tessellate_deterministic(&path, &options, &mut FillTessellator::new())));
+        match recorded.iter().find(|&&(ref n, _)| n == name) {
+            Some(&(_, ref expected)) if *expected == hash => {}
+            Some(&(_, ref expected)) => {
+                mismatches.push(format!("{}: expected {}, got {}", name, expected, hash));
+            }
+            None => {
+                mismatches.push(format!("{}: not recorded, got {}", name, hash));
+            }
+        }
+        output.push_str(&format!("{} {}\n", name, hash));
+    }
+
+    if bless {
+        let mut file = fs::File::create(file_name).unwrap();
+        file.write_all(output.as_bytes()).unwrap();
+        return;
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "the deterministic output changed (set LYON_BLESS to record it):\n{}",
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn test_ear_clipping_algorithm() {
+    // Differential test: both algorithms cover the same area.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(5.0, 1.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(9.0, 6.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(3.0, 8.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    builder.move_to(point(2.0, 2.0));
+    builder.line_to(point(6.0, 3.0));
+    builder.line_to(point(4.0, 6.0));
+    builder.close();
+    let path = builder.build();
+
+    let area = |buffers: &VertexBuffers<Vertex>| {
+        let mut area = 0.0;
+        for triangle in buffers.indices.chunks(3) {
+            let a = buffers.vertices[triangle[0] as usize].position;
+            let b = buffers.vertices[triangle[1] as usize].position;
+            let c = buffers.vertices[triangle[2] as usize].position;
+            area += ((b - a).cross(c - a) * 0.5).abs();
+        }
+        area
+    };
+
+    let mut tess = FillTessellator::new();
+    let mut sweep: VertexBuffers<Vertex> = VertexBuffers::new();
+    tess.tessellate_path(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default(),
+        &mut simple_builder(&mut sweep),
+    ).unwrap();
+    let mut ear_clipping: VertexBuffers<Vertex> = VertexBuffers::new();
+    let options = FillOptions::default().with_algorithm(FillAlgorithm::EarClipping);
+    let count = tess.tessellate_path(
+        path.path_iter().flattened(0.05),
+        &options,
+        &mut simple_builder(&mut ear_clipping),
+    ).unwrap();
+
+    assert_eq!(count.vertices, 10);
+    assert!((area(&sweep) - area(&ear_clipping)).abs() < 0.01);
+
+    // The path builder doesn't accept NaN, the events are passed directly.
+    let invalid = vec![
+        FlattenedEvent::MoveTo(point(0.0, 0.0)),
+        FlattenedEvent::LineTo(point(::std::f32::NAN, 0.0)),
+        FlattenedEvent::LineTo(point(0.0, 1.0)),
+        FlattenedEvent::Close,
+    ];
+    let result = tess.tessellate_path(
+        invalid.into_iter(),
+        &options.with_input_validation(),
+        &mut simple_builder(&mut ear_clipping),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_cancellation() {
+    use std::sync::atomic::AtomicUsize;
+
+    // The callbacks are Send so that the tessellator can be moved to another thread.
+    fn assert_send<T: Send>() {}
+    assert_send::<FillTessellator>();
+
+    // A star with enough vertices for the progress to be reported a few times.
+    let mut builder = Path::builder();
+    let n = 1000;
+    for i in 0..n {
+        let angle = i as f32 * 2.0 * PI / n as f32;
+        let radius = if i % 2 == 0 { 100.0 } else { 80.0 };
+        let p = point(angle.cos() * radius, angle.sin() * radius);
+        if i == 0 {
+            builder.move_to(p);
+        } else {
+            builder.line_to(p);
+        }
+    }
+    builder.close();
+    let path = builder.build();
+
+    let mut tess = FillTessellator::new();
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_clone = calls.clone();
+    tess.set_progress_callback(Some(Box::new(move |progress: &FillProgress| {
+        assert!(progress.edges <= progress.total_edges);
+        calls_clone.fetch_add(1, AtomicOrdering::Relaxed);
+        true
+    })));
+    assert!(tess.tessellate_path(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).is_ok());
+    assert_eq!(calls.load(AtomicOrdering::Relaxed), n / PROGRESS_INTERVAL);
+
+    // Aborted by the progress callback.
+    buffers.vertices.clear();
+    buffers.indices.clear();
+    tess.set_progress_callback(Some(Box::new(|_: &FillProgress| false)));
+    assert_eq!(
+        tess.tessellate_path(
+            path.path_iter().flattened(0.05),
+            &FillOptions::default(),
+            &mut simple_builder(&mut buffers),
+        ),
+        Err(FillError::Cancelled)
+    );
+    assert!(buffers.vertices.is_empty());
+    assert!(buffers.indices.is_empty());
+    tess.set_progress_callback(None);
+
+    // Aborted by a token.
+    let token = CancellationToken::new();
+    tess.set_cancellation_token(Some(token.clone()));
+    assert!(tess.tessellate_path(
+        path.path_iter().flattened(0.05),
+        &FillOptions::default(),
+        &mut simple_builder(&mut buffers),
+    ).is_ok());
+    buffers.vertices.clear();
+    buffers.indices.clear();
+    token.cancel();
+    assert_eq!(
+        tess.tessellate_path(
+            path.path_iter().flattened(0.05),
+            &FillOptions::default(),
+            &mut simple_builder(&mut buffers),
+        ),
+        Err(FillError::Cancelled)
+    );
+    assert!(buffers.indices.is_empty());
+
+    // The token is checked even when there are fewer vertices than the progress interval.
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(1.0, 0.0));
+    builder.line_to(point(1.0, 1.0));
+    builder.close();
+    assert_eq!(
+        tess.tessellate_path(
+            builder.build().path_iter().flattened(0.05),
+            &FillOptions::default(),
+            &mut simple_builder(&mut buffers),
+        ),
+        Err(FillError::Cancelled)
+    );
+}
@@ -13,6 +13,19 @@
 //! is shaded once no matter how many times the path overlaps with itself at this
 //! location.
 //!
+//! The line joins, caps and miter limit of the options follow the SVG specification. A
+//! [dash pattern](struct.DashPattern.html) can be given to
+//! [tessellate_dashed](struct.StrokeTessellator.html#method.tessellate_dashed), it is
+//! applied while the segments are added, each dash being stroked as an open sub-path.
+//!
+//! The width and the dash pattern can also change along the path, for example to render the
+//! sections of a route differently, with [style breaks](struct.StyleBreak.html). The joins
+//! at the breaks connect the sides of the stroke before and after the break.
+//!
+//! The triangles of the joins and of the round caps can be told apart from the ones of the
+//! edges with [tessellate_with_primitives](struct.StrokeTessellator.html#method.tessellate_with_primitives),
+//! for example to skip the round joins and draw them as circles in the fragment shader.
+//!
 //! The main interface is the [StrokeTessellator](struct.StrokeTessellator.html),
 //! which exposes a similar interface to its
 //! [fill equivalent](../path_fill/struct.FillTessellator.html).
@@ -80,12 +93,16 @@
 
 use math::*;
 use core::FlattenedEvent;
+use bezier::{Arc, arc_step_count};
 use geometry_builder::{VertexId, GeometryBuilder, Count};
-use math_utils::{tangent, line_intersection};
+use math_utils::{tangent, directed_angle, ellipse_point_from_angle};
 use path_builder::BaseBuilder;
 use StrokeVertex as Vertex;
 use Side;
 
+use std::f32::consts::PI;
+use std::ops::Range;
+
 pub type StrokeResult = Result<Count, ()>;
 
 /// A Context object that can tessellate stroke operations for complex paths.
@@ -113,19 +130,223 @@ impl StrokeTessellator {
 
         return stroker.build();
     }
+
+    /// Tessellates a dashed stroke.
+    pub fn tessellate_dashed<Input, Output>(
+        &mut self,
+        input: Input,
+        options: &StrokeOptions,
+        dash_pattern: &DashPattern,
+        builder: &mut Output,
+    ) -> StrokeResult
+    where
+        Input: Iterator<Item = FlattenedEvent>,
+        Output: GeometryBuilder<Vertex>,
+    {
+        builder.begin_geometry();
+        let mut stroker = StrokeBuilder::new(options, builder);
+        stroker.set_dash_pattern(dash_pattern);
+
+        for evt in input {
+            stroker.flat_event(evt);
+        }
+
+        return stroker.build();
+    }
+
+    /// Tessellates a stroke whose width and dash pattern change along the path.
+    ///
+    /// The stroke is solid with the width of the options until the first break, and the style
+    /// of each break
+    /// applies until the next one. The breaks don't need to be sorted. As usual, the normals
+    /// of the vertices are relative to the `line_width` of the options: they are scaled by the
+    /// ratio between the width of the break and the width of the options so that extruding
+    /// them by the latter gives the right width everywhere.
+    pub fn tessellate_with_style_breaks<Input, Output>(
+        &mut self,
+        input: Input,
+        options: &StrokeOptions,
+        style_breaks: &[StyleBreak],
+        builder: &mut Output,
+    ) -> StrokeResult
+    where
+        Input: Iterator<Item = FlattenedEvent>,
+        Output: GeometryBuilder<Vertex>,
+    {
+        builder.begin_geometry();
+        let mut stroker = StrokeBuilder::new(options, builder);
+        stroker.set_style_breaks(style_breaks);
+
+        for evt in input {
+            stroker.flat_event(evt);
+        }
+
+        return stroker.build();
+    }
+
+    /// Tessellates a stroke and records which part of the stroke each range of triangles
+    /// belongs to.
+    ///
+    /// The ranges are added to `primitives` in the order of the triangles, and are counted in
+    /// triangles from the start of the geometry: with a `simple_builder`, the indices of a range
+    /// are `indices[3 * start..3 * end]`. See [StrokePrimitive](enum.StrokePrimitive.html).
+    pub fn tessellate_with_primitives<Input, Output>(
+        &mut self,
+        input: Input,
+        options: &StrokeOptions,
+        primitives: &mut Vec<StrokePrimitiveRange>,
+        builder: &mut Output,
+    ) -> StrokeResult
+    where
+        Input: Iterator<Item = FlattenedEvent>,
+        Output: GeometryBuilder<Vertex>,
+    {
+        builder.begin_geometry();
+        let mut stroker = StrokeBuilder::new(options, builder);
+        stroker.set_primitive_ranges(primitives);
+
+        for evt in input {
+            stroker.flat_event(evt);
+        }
+
+        return stroker.build();
+    }
+}
+
+/// The part of a stroke that triangles belong to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StrokePrimitive {
+    /// The quads along the edges of the path, including the extent of the square caps.
+    Edges,
+    /// A join at the given point of the path. Miter joins within the limit don't have any
+    /// triangle since the sides of the edges meet.
+    Join(Point),
+    /// A round cap at the given point of the path, or the cap of a sub-path without any edge.
+    Cap(Point),
+}
+
+/// A range of triangles of a stroke and the part of the stroke they belong to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrokePrimitiveRange {
+    pub primitive: StrokePrimitive,
+    /// The triangles, counted from the start of the geometry.
+    pub triangles: Range<u32>,
+}
+
+/// A change of the style of a stroke at a given distance along the path.
+///
+/// See [tessellate_with_style_breaks](struct.StrokeTessellator.html#method.tessellate_with_style_breaks).
+#[derive(Clone, Debug, PartialEq)]
+pub struct StyleBreak {
+    /// The distance along the path at which the style changes. The sub-paths are measured one
+    /// after the other, the gap between the end of a sub-path and the start of the next one
+    /// does not count.
+    ///
+    /// Breaks with a NaN distance are ignored.
+    pub distance: f32,
+    /// The width of the stroke after the break.
+    ///
+    /// Breaks with a negative or NaN width are ignored. A width of zero produces degenerate
+    /// triangles. If the `line_width` of the options isn't positive, the normals can't be
+    /// scaled relatively to it and the widths of the breaks have no effect.
+    pub line_width: f32,
+    /// The dash pattern after the break, see `DashPattern::dash_array`.
+    pub dash_array: Vec<f32>,
+    /// The distance into the dash pattern at the break.
+    pub dash_offset: f32,
+}
+
+impl StyleBreak {
+    /// A break to a solid stroke.
+    pub fn new(distance: f32, line_width: f32) -> StyleBreak {
+        StyleBreak {
+            distance: distance,
+            line_width: line_width,
+            dash_array: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+
+    pub fn with_dash_pattern(mut self, dash_array: &[f32], dash_offset: f32) -> StyleBreak {
+        self.dash_array = dash_array.to_vec();
+        self.dash_offset = dash_offset;
+        return self;
+    }
+}
+
+/// The dashes and gaps of a stroke.
+///
+/// See [tessellate_dashed](struct.StrokeTessellator.html#method.tessellate_dashed).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DashPattern {
+    /// The lengths of the alternating dashes and gaps, in the units of the path. See the SVG
+    /// specification.
+    ///
+    /// The stroke is solid if this is empty, if it contains a negative or non-finite value, or
+    /// if its sum is below the tolerance of the options.
+    pub dash_array: Vec<f32>,
+    /// The distance into the dash pattern at which each sub-path starts.
+    pub dash_offset: f32,
+}
+
+impl DashPattern {
+    pub fn new(dash_array: &[f32], dash_offset: f32) -> DashPattern {
+        DashPattern {
+            dash_array: dash_array.to_vec(),
+            dash_offset: dash_offset,
+        }
+    }
 }
 
 /// A builder that tessellates a stroke directly without allocating any intermediate data structure.
+///
+/// The dash pattern is applied to the segments as they are added. Each dash is stroked as an
+/// open sub-path with its own caps, including in closed sub-paths.
 pub struct StrokeBuilder<'l, Output: 'l> {
     first: Point,
     previous: Point,
     current: Point,
     second: Point,
-    previous_a_id: VertexId,
-    previous_b_id: VertexId,
-    second_a_id: VertexId,
-    second_b_id: VertexId,
+    // The vertices at the start of the edge from previous to current.
+    previous_left_id: VertexId,
+    previous_right_id: VertexId,
+    // The vertices at the end of the first edge.
+    second_left_id: VertexId,
+    second_right_id: VertexId,
     nth: u32,
+    // Half of the width of the new edges, of the edge from previous to current and of the
+    // first edge, relative to the width of the options.
+    half_width: f32,
+    edge_half_width: f32,
+    first_half_width: f32,
+    // Whether a sub-path was started with a move_to (or a dash), in which case it has caps
+    // even if it doesn't have any edge.
+    sub_path: bool,
+    // The current position in the input path, which is not the current position of the
+    // stroke in the gaps of the dash pattern.
+    position: Point,
+    sub_path_start: Point,
+    // Whether the stroke goes uninterrupted from the start of the sub-path, in which case it
+    // can be closed with a join.
+    continuous: bool,
+    // The distance along the input path and the style changes that are not reached yet.
+    distance: f32,
+    style_breaks: Vec<StyleBreak>,
+    next_break: usize,
+    // The pattern each sub-path starts with, and the current one which can be changed by the
+    // style breaks.
+    dash_pattern: DashPattern,
+    dash_array: Vec<f32>,
+    dash_offset: f32,
+    dashes: bool,
+    dash_index: usize,
+    dash_remaining: f32,
+    dash_on: bool,
+    // The part of the stroke of the triangles being added, and where their ranges are
+    // recorded if requested.
+    primitive: StrokePrimitive,
+    num_triangles: u32,
+    primitive_ranges: Option<&'l mut Vec<StrokePrimitiveRange>>,
     options: StrokeOptions,
     output: &'l mut Output,
 }
@@ -136,27 +357,32 @@ impl<'l, Output: 'l + GeometryBuilder<Vertex>> BaseBuilder for StrokeBuilder<'l,
     fn move_to(&mut self, to: Point) {
         self.finish();
 
-        self.first = to;
-        self.current = to;
-        self.nth = 0;
+        self.position = to;
+        self.sub_path_start = to;
+        self.start_sub_path();
     }
 
-    fn line_to(&mut self, to: Point) { self.edge_to(to); }
+    fn line_to(&mut self, to: Point) {
+        self.styled_edge_to(to);
+        self.position = to;
+    }
 
     fn close(&mut self) {
-        let first = self.first;
-        self.edge_to(first);
-        if self.nth > 1 {
-            let second = self.second;
-            self.edge_to(second);
-            self.output.add_triangle(self.previous_b_id, self.previous_a_id, self.second_b_id);
-            self.output.add_triangle(self.previous_a_id, self.second_a_id, self.second_b_id);
+        let start = self.sub_path_start;
+        self.styled_edge_to(start);
+        if self.dashes || !self.continuous {
+            self.finish();
+        } else {
+            self.close_sub_path();
         }
-        self.nth = 0;
-        self.current = self.first;
+
+        self.position = start;
+        self.start_sub_path();
+        // Unlike after a move_to, there is nothing to draw if no segment follows.
+        self.sub_path = false;
     }
 
-    fn current_position(&self) -> Point { self.current }
+    fn current_position(&self) -> Point { self.position }
 
     fn build(mut self) -> StrokeResult {
         self.finish();
@@ -164,11 +390,23 @@ impl<'l, Output: 'l + GeometryBuilder<Vertex>> BaseBuilder for StrokeBuilder<'l,
     }
 
     fn build_and_reset(&mut self) -> StrokeResult {
+        self.finish();
         self.first = Point::new(0.0, 0.0);
         self.previous = Point::new(0.0, 0.0);
         self.current = Point::new(0.0, 0.0);
         self.second = Point::new(0.0, 0.0);
+        self.position = Point::new(0.0, 0.0);
+        self.sub_path_start = Point::new(0.0, 0.0);
         self.nth = 0;
+        self.num_triangles = 0;
+        self.distance = 0.0;
+        self.next_break = 0;
+        self.half_width = 0.5;
+        self.dash_array = self.dash_pattern.dash_array.clone();
+        self.dash_offset = self.dash_pattern.dash_offset;
+        self.dashes = has_dashes(&self.dash_array, self.options.tolerance);
+        self.start_sub_path();
+        self.sub_path = false;
         return Ok(self.output.end_geometry());
     }
 }
@@ -176,116 +414,288 @@ impl<'l, Output: 'l + GeometryBuilder<Vertex>> BaseBuilder for StrokeBuilder<'l,
 impl<'l, Output: 'l + GeometryBuilder<Vertex>> StrokeBuilder<'l, Output> {
     pub fn new(options: &StrokeOptions, builder: &'l mut Output) -> Self {
         let zero = Point::new(0.0, 0.0);
-        return StrokeBuilder {
-                   first: zero,
-                   second: zero,
-                   previous: zero,
-                   current: zero,
-                   previous_a_id: VertexId(0),
-                   previous_b_id: VertexId(0),
-                   second_a_id: VertexId(0),
-                   second_b_id: VertexId(0),
-                   nth: 0,
-                   options: *options,
-                   output: builder,
-               };
-    }
-
-    pub fn set_options(&mut self, options: &StrokeOptions) { self.options = *options; }
+        let mut stroker = StrokeBuilder {
+            first: zero,
+            second: zero,
+            previous: zero,
+            current: zero,
+            previous_left_id: VertexId(0),
+            previous_right_id: VertexId(0),
+            second_left_id: VertexId(0),
+            second_right_id: VertexId(0),
+            nth: 0,
+            half_width: 0.5,
+            edge_half_width: 0.5,
+            first_half_width: 0.5,
+            sub_path: false,
+            position: zero,
+            sub_path_start: zero,
+            continuous: false,
+            distance: 0.0,
+            style_breaks: Vec::new(),
+            next_break: 0,
+            dash_pattern: DashPattern::new(&[], 0.0),
+            dash_array: Vec::new(),
+            dash_offset: 0.0,
+            dashes: false,
+            dash_index: 0,
+            dash_remaining: 0.0,
+            dash_on: true,
+            primitive: StrokePrimitive::Edges,
+            num_triangles: 0,
+            primitive_ranges: None,
+            options: *options,
+            output: builder,
+        };
+        stroker.start_sub_path();
+        stroker.sub_path = false;
 
-    fn finish(&mut self) {
-        match self.options.line_cap {
-            LineCap::Butt | LineCap::Square => {}
-            _ => {
-                println!(
-                    "[StrokeTessellator] umimplemented {:?} line cap, defaulting to LineCap::Butt.",
-                    self.options.line_cap
-                );
+        return stroker;
+    }
+
+    pub fn set_options(&mut self, options: &StrokeOptions) {
+        self.options = *options;
+        self.dashes = has_dashes(&self.dash_array, self.options.tolerance);
+    }
+
+    /// Sets the dash pattern of the stroke, which restarts at the beginning of each sub-path.
+    ///
+    /// It applies from the next sub-path, or from the current one if nothing was added to it
+    /// yet.
+    pub fn set_dash_pattern(&mut self, dash_pattern: &DashPattern) {
+        self.dash_pattern = dash_pattern.clone();
+        self.dash_array = dash_pattern.dash_array.clone();
+        self.dash_offset = dash_pattern.dash_offset;
+        self.dashes = has_dashes(&self.dash_array, self.options.tolerance);
+        if self.nth == 0 {
+            let sub_path = self.sub_path;
+            self.start_sub_path();
+            self.sub_path = sub_path;
+        }
+    }
+
+    /// Changes the style of the stroke at the given distances along the path, see
+    /// [StyleBreak](struct.StyleBreak.html).
+    ///
+    /// The distances are measured from the first event added after this call. Breaks with a
+    /// NaN distance, or a negative or NaN width, are ignored.
+    pub fn set_style_breaks(&mut self, style_breaks: &[StyleBreak]) {
+        self.style_breaks = style_breaks.iter().filter(|b| {
+            !b.distance.is_nan() && b.line_width >= 0.0
+        }).cloned().collect();
+        self.style_breaks.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
+        self.next_break = 0;
+        self.distance = 0.0;
+    }
+
+    /// Records which part of the stroke each range of the following triangles belongs to, see
+    /// [tessellate_with_primitives](struct.StrokeTessellator.html#method.tessellate_with_primitives).
+    pub fn set_primitive_ranges(&mut self, ranges: &'l mut Vec<StrokePrimitiveRange>) {
+        self.primitive_ranges = Some(ranges);
+    }
+
+    // Starts a sub-path of the stroke at the current position of the input, and restarts the
+    // dash pattern.
+    fn start_sub_path(&mut self) {
+        let start = self.position;
+        self.continuous = false;
+        if self.dashes {
+            self.restart_dashes();
+            if !self.dash_on {
+                return;
             }
         }
 
-        let hw = 0.5;
+        self.continuous = true;
+        self.begin(start);
+    }
 
-        if self.options.line_cap == LineCap::Square && self.nth == 0 {
-            // Even if there is no edge, if we are using square caps we have to place a square
-            // at the current position.
-            let a = self.output.add_vertex(
-                Vertex {
-                    position: self.current,
-                    normal: vec2(-hw, -hw),
-                    side: Side::Left,
-                }
-            );
-            let b = self.output.add_vertex(
-                Vertex {
-                    position: self.current,
-                    normal: vec2(hw, -hw),
-                    side: Side::Left,
-                }
-            );
-            let c = self.output.add_vertex(
-                Vertex {
-                    position: self.current,
-                    normal: vec2(hw, hw),
-                    side: Side::Right,
-                }
-            );
-            let d = self.output.add_vertex(
-                Vertex {
-                    position: self.current,
-                    normal: vec2(-hw, hw),
-                    side: Side::Right,
-                }
-            );
-            self.output.add_triangle(a, b, c);
-            self.output.add_triangle(a, c, d);
-        }
-
-        // last edge
-        if self.nth > 0 {
-            let current = self.current;
-            let d = self.current - self.previous;
-            if self.options.line_cap == LineCap::Square {
-                // The easiest way to implement square caps is to lie about the current position
-                // and move it slightly to accommodate for the width/2 extra length.
-                self.current = self.current + d.normalize() * hw;
+    fn restart_dashes(&mut self) {
+        let pattern = &self.dash_array;
+        let mut period: f32 = pattern.iter().sum();
+        if pattern.len() % 2 == 1 {
+            // Odd patterns are repeated to get an even number of values.
+            period *= 2.0;
+        }
+        let mut offset = self.dash_offset % period;
+        if offset < 0.0 {
+            offset += period;
+        }
+
+        self.dash_index = 0;
+        self.dash_on = true;
+        // A zero-length dash at the start is kept, since it is drawn with square or round
+        // caps.
+        while offset >= pattern[self.dash_index] && offset > 0.0 {
+            offset -= pattern[self.dash_index];
+            self.dash_index = (self.dash_index + 1) % pattern.len();
+            self.dash_on = !self.dash_on;
+        }
+        self.dash_remaining = pattern[self.dash_index] - offset;
+    }
+
+    // Splits the segment from the current position of the input to `to` at the style breaks
+    // and applies them.
+    fn styled_edge_to(&mut self, to: Point) {
+        let end = self.distance + (to - self.position).length();
+        while self.next_break < self.style_breaks.len() &&
+            self.style_breaks[self.next_break].distance <= end {
+            let distance = self.style_breaks[self.next_break].distance;
+            if distance > self.distance {
+                let p = self.position.lerp(to, (distance - self.distance) / (end - self.distance));
+                self.unstyled_edge_to(p);
+                self.position = p;
+                self.distance = distance;
             }
-            let p = self.current + d;
-            self.edge_to(p);
-            // Restore the real current position.
-            self.current = current;
+            self.next_break += 1;
+            let idx = self.next_break - 1;
+            self.apply_style_break(idx);
+        }
+
+        self.unstyled_edge_to(to);
+        self.distance = end;
+    }
+
+    fn unstyled_edge_to(&mut self, to: Point) {
+        if self.dashes {
+            self.dashed_edge_to(to);
+        } else {
+            self.edge_to(to);
+        }
+    }
+
+    // Changes the style at the current position of the input. The stroke goes on with a join
+    // unless the new dash pattern starts or ends a dash here.
+    fn apply_style_break(&mut self, idx: usize) {
+        let was_on = !self.dashes || self.dash_on;
+        {
+            let style = &self.style_breaks[idx];
+            self.half_width = if self.options.line_width > 0.0 {
+                0.5 * style.line_width / self.options.line_width
+            } else {
+                0.5
+            };
+            self.dash_array = style.dash_array.clone();
+            self.dash_offset = style.dash_offset;
+        }
+        self.dashes = has_dashes(&self.dash_array, self.options.tolerance);
+        if self.dashes {
+            self.restart_dashes();
+        }
+        let on = !self.dashes || self.dash_on;
+
+        if was_on && !on {
+            self.finish();
+            self.continuous = false;
+        } else if !was_on && on {
+            let position = self.position;
+            self.begin(position);
+            self.continuous = false;
+        }
+    }
+
+    fn begin(&mut self, to: Point) {
+        self.first = to;
+        self.current = to;
+        self.nth = 0;
+        self.sub_path = true;
+    }
+
+    // Splits the segment from the current position of the input to `to` according to the
+    // dash pattern.
+    fn dashed_edge_to(&mut self, to: Point) {
+        let from = self.position;
+        let v = to - from;
+        let length = v.length();
+
+        // With too many dashes for the precision of the segment, the distance along it would
+        // stop increasing. The segment is stroked solid and the pattern restarts after it.
+        let period: f32 = self.dash_array.iter().sum();
+        if length > period * MAX_DASHES_PER_SEGMENT {
+            if !self.dash_on {
+                self.begin(from);
+                self.continuous = false;
+            }
+            self.edge_to(to);
+            self.dash_index = 0;
+            self.dash_on = true;
+            self.dash_remaining = self.dash_array[0];
+            return;
+        }
+
+        let mut distance = 0.0;
+        while length - distance > self.dash_remaining {
+            distance += self.dash_remaining;
+            let p = from + v * (distance / length);
+            if self.dash_on {
+                self.edge_to(p);
+                self.finish();
+            } else {
+                self.begin(p);
+            }
+            self.continuous = false;
+            self.dash_on = !self.dash_on;
+            self.dash_index = (self.dash_index + 1) % self.dash_array.len();
+            self.dash_remaining = self.dash_array[self.dash_index];
+        }
+        self.dash_remaining -= length - distance;
+
+        if self.dash_on {
+            self.edge_to(to);
         }
+    }
 
-        // first edge
+    fn close_sub_path(&mut self) {
+        let first = self.first;
+        self.edge_to(first);
         if self.nth > 1 {
-            let mut first = self.first;
-            let d = first - self.second;
+            // The join connects the last edge to the first one, with the width of the latter.
+            let second = self.second;
+            let half_width = self.half_width;
+            self.half_width = self.first_half_width;
+            self.edge_to(second);
+            self.half_width = half_width;
+            let (left, right) = (self.previous_left_id, self.previous_right_id);
+            let (second_left, second_right) = (self.second_left_id, self.second_right_id);
+            self.add_edge(left, right, second_left, second_right);
+        }
+        self.nth = 0;
+        self.current = self.first;
+    }
 
-            if self.options.line_cap == LineCap::Square {
-                first = first + d.normalize() * hw;
+    fn finish(&mut self) {
+        if self.nth == 0 {
+            if self.sub_path {
+                self.add_zero_length_cap();
             }
+            self.sub_path = false;
+            return;
+        }
 
-            let n2 = tangent(d) * 0.5;
-            let n1 = -n2;
+        // The end of the last edge.
+        let direction = (self.current - self.previous).normalize();
+        let current = self.current;
+        let hw = self.edge_half_width;
+        let (end_left, end_right) = self.add_cap(current, direction, hw);
 
-            let first_a_id = self.output.add_vertex(
-                Vertex {
-                    position: first,
-                    normal: n1,
-                    side: Side::Left,
-                }
-            );
-            let first_b_id = self.output.add_vertex(
-                Vertex {
-                    position: first,
-                    normal: n2,
-                    side: Side::Right,
-                }
-            );
+        // The start of the first edge.
+        let second = if self.nth == 1 { self.current } else { self.second };
+        let direction = (second - self.first).normalize();
+        let first = self.first;
+        let hw = self.first_half_width;
+        let (start_left, start_right) = self.add_cap(first, direction, -hw);
 
-            self.output.add_triangle(first_b_id, first_a_id, self.second_b_id);
-            self.output.add_triangle(first_a_id, self.second_a_id, self.second_b_id);
+        if self.nth == 1 {
+            self.add_edge(start_left, start_right, end_left, end_right);
+        } else {
+            let (left, right) = (self.previous_left_id, self.previous_right_id);
+            self.add_edge(left, right, end_left, end_right);
+            let (second_left, second_right) = (self.second_left_id, self.second_right_id);
+            self.add_edge(start_left, start_right, second_left, second_right);
         }
+
+        self.nth = 0;
+        self.sub_path = false;
     }
 
     fn edge_to(&mut self, to: Point) {
@@ -293,124 +703,311 @@ impl<'l, Output: 'l + GeometryBuilder<Vertex>> StrokeBuilder<'l, Output> {
             return;
         }
         if self.nth == 0 {
-            // We don't have enough information to compute a and b yet.
+            // We don't have enough information to compute the vertices yet.
             self.previous = self.first;
             self.current = to;
             self.nth += 1;
+            self.first_half_width = self.half_width;
+            self.edge_half_width = self.half_width;
             return;
         }
-        let (na, nb, maybe_nc) = get_angle_info(self.previous, self.current, to);
-        let a_id = self.output.add_vertex(
-            Vertex {
-                position: self.current,
-                normal: na,
-                side: Side::Left,
-            }
-        );
-        let b_id = self.output.add_vertex(
-            Vertex {
-                position: self.current,
-                normal: nb,
-                side: Side::Right,
+
+        let (previous, current) = (self.previous, self.current);
+        let (hw_in, hw_out) = (self.edge_half_width, self.half_width);
+        let ((in_left, in_right), (out_left, out_right)) = self.add_join(previous, current, to, hw_in, hw_out);
+
+        if self.nth > 1 {
+            let (left, right) = (self.previous_left_id, self.previous_right_id);
+            self.add_edge(left, right, in_left, in_right);
+        } else {
+            self.second = current;
+            self.second_left_id = in_left;
+            self.second_right_id = in_right;
+        }
+
+        self.previous = current;
+        self.previous_left_id = out_left;
+        self.previous_right_id = out_right;
+        self.current = to;
+        self.nth += 1;
+        self.edge_half_width = hw_out;
+    }
+
+    fn add_edge(&mut self, left: VertexId, right: VertexId, next_left: VertexId, next_right: VertexId) {
+        self.primitive = StrokePrimitive::Edges;
+        self.add_triangle(right, left, next_right);
+        self.add_triangle(left, next_left, next_right);
+    }
+
+    // Adds a triangle of the current primitive, extending its range if it is the same as the
+    // one of the previous triangle.
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.output.add_triangle(a, b, c);
+        let idx = self.num_triangles;
+        self.num_triangles += 1;
+
+        if let Some(ref mut ranges) = self.primitive_ranges {
+            if let Some(last) = ranges.last_mut() {
+                if last.primitive == self.primitive && last.triangles.end == idx {
+                    last.triangles.end = idx + 1;
+                    return;
+                }
             }
-        );
+            ranges.push(StrokePrimitiveRange {
+                primitive: self.primitive,
+                triangles: idx..(idx + 1),
+            });
+        }
+    }
+
+    // Adds the vertices and triangles of the join at `current`, `hw_in` and `hw_out` being the
+    // half widths of the incoming and outgoing edges. Returns the left and right vertices at
+    // the end of the incoming edge and at the start of the outgoing one.
+    fn add_join(
+        &mut self,
+        previous: Point,
+        current: Point,
+        next: Point,
+        hw_in: f32,
+        hw_out: f32,
+    ) -> ((VertexId, VertexId), (VertexId, VertexId)) {
+        self.primitive = StrokePrimitive::Join(current);
+        let d1 = (current - previous).normalize();
+        let d2 = (next - current).normalize();
+        let n1 = tangent(d1);
+        let n2 = tangent(d2);
+        let cross = d1.cross(d2);
 
-        let (nc, c_id) = if let Some(n) = maybe_nc {
+        // The miter length divided by the line width is 1 / sin(theta / 2) where theta is the
+        // angle between the two edges.
+        let half_angle_sin = ((1.0 + d1.dot(d2)) * 0.5).max(0.0).sqrt();
+        let bisector = if half_angle_sin > 0.0001 { (n1 + n2).normalize() } else { d1 };
+        // Where the left sides and the right sides of the two edges intersect.
+        let (left_miter, right_miter) = if hw_in == hw_out {
+            let miter = if half_angle_sin > 0.0001 { bisector * (hw_in / half_angle_sin) } else { vec2(0.0, 0.0) };
+            (miter, -miter)
+        } else {
             (
-                n,
-                self.output.add_vertex(
-                    Vertex {
-                        position: self.current,
-                        normal: n,
-                        side: Side::Left, // TODO
-                    }
-                )
+                line_intersection(n1 * hw_in, d1, n2 * hw_out, d2),
+                line_intersection(-n1 * hw_in, d1, -n2 * hw_out, d2),
             )
+        };
+
+        let straight = cross.abs() < 0.0001 && d1.dot(d2) > 0.0;
+        if straight && hw_in != hw_out {
+            // The width changes along a straight line.
+            let in_left = self.output.add_vertex(Vertex { position: current, normal: n1 * hw_in, side: Side::Left });
+            let in_right = self.output.add_vertex(Vertex { position: current, normal: -n1 * hw_in, side: Side::Right });
+            let out_left = self.output.add_vertex(Vertex { position: current, normal: n2 * hw_out, side: Side::Left });
+            let out_right = self.output.add_vertex(Vertex { position: current, normal: -n2 * hw_out, side: Side::Right });
+            return ((in_left, in_right), (out_left, out_right));
+        }
+
+        // The inner side of the turn has a single vertex where the sides of the edges
+        // intersect, and the outer side is filled with a fan of triangles from it.
+        let (outer_side, inner_side, sign) = if cross > 0.0 {
+            (Side::Right, Side::Left, -1.0)
         } else {
-            (nb, b_id)
+            (Side::Left, Side::Right, 1.0)
         };
+        let (outer_miter, inner_miter) = if sign > 0.0 {
+            (left_miter, right_miter)
+        } else {
+            (right_miter, left_miter)
+        };
+        let hw = hw_in.max(hw_out);
 
-        if self.nth > 1 {
-            self.output.add_triangle(self.previous_b_id, self.previous_a_id, b_id);
-            self.output.add_triangle(self.previous_a_id, a_id, b_id);
+        let within_limit = if hw_in == hw_out {
+            half_angle_sin * self.options.miter_limit >= 1.0
+        } else {
+            half_angle_sin > 0.0001 && outer_miter.length() <= self.options.miter_limit * hw
+        };
+        let miter_join = match self.options.line_join {
+            LineJoin::Miter | LineJoin::MiterClip => within_limit,
+            LineJoin::Round | LineJoin::Bevel => false,
+        };
+        if straight || miter_join {
+            let left = self.output.add_vertex(Vertex { position: current, normal: left_miter, side: Side::Left });
+            let right = self.output.add_vertex(Vertex { position: current, normal: right_miter, side: Side::Right });
+            return ((left, right), (left, right));
         }
 
-        self.previous = self.current;
-        self.previous_a_id = a_id;
-        self.previous_b_id = c_id;
-        self.current = to;
+        let outer_in = n1 * (hw_in * sign);
+        let outer_out = n2 * (hw_out * sign);
 
-        if self.nth == 1 {
-            self.second = self.previous;
-            self.second_a_id = a_id;
-            self.second_b_id = c_id;
+        let inner_id = self.output.add_vertex(Vertex { position: current, normal: inner_miter, side: inner_side });
+        let in_id = self.output.add_vertex(Vertex { position: current, normal: outer_in, side: outer_side });
+        let out_id = self.output.add_vertex(Vertex { position: current, normal: outer_out, side: outer_side });
+
+        match self.options.line_join {
+            LineJoin::Round => {
+                let mut angle = directed_angle(d1, d2);
+                if angle > PI {
+                    angle = 2.0 * PI - angle;
+                }
+                self.add_round_fan(current, inner_id, (in_id, outer_in), (out_id, outer_out), -sign * angle, outer_side);
+            }
+            LineJoin::MiterClip => {
+                // The miter is clipped by a line perpendicular to the bisector, at a distance of
+                // miter_limit * line_width / 2 from the vertex.
+                let outer_bisector = bisector * sign;
+                let clip_distance = self.options.miter_limit * hw;
+                let t1 = (clip_distance - outer_in.dot(outer_bisector)) / d1.dot(outer_bisector);
+                let t2 = (clip_distance - outer_out.dot(outer_bisector)) / -d2.dot(outer_bisector);
+                if t1 > 0.0 && t2 > 0.0 {
+                    let c1 = self.output.add_vertex(Vertex { position: current, normal: outer_in + d1 * t1, side: outer_side });
+                    let c2 = self.output.add_vertex(Vertex { position: current, normal: outer_out - d2 * t2, side: outer_side });
+                    self.add_triangle(inner_id, in_id, c1);
+                    self.add_triangle(inner_id, c1, c2);
+                    self.add_triangle(inner_id, c2, out_id);
+                } else {
+                    self.add_triangle(inner_id, in_id, out_id);
+                }
+            }
+            LineJoin::Miter | LineJoin::Bevel => {
+                self.add_triangle(inner_id, in_id, out_id);
+            }
         }
 
-        if maybe_nc.is_some() {
-            let current = self.current;
-            self.tessellate_angle(current, na, a_id, nb, b_id, nc, c_id);
+        return if outer_side == Side::Left {
+            ((in_id, inner_id), (out_id, inner_id))
+        } else {
+            ((inner_id, in_id), (inner_id, out_id))
+        };
+    }
+
+    // Adds the left and right vertices of a cap. `extent` is the normal of the square cap along
+    // the direction of the edge: the half width of the edge, positive at the end of a sub-path
+    // and negative at its start.
+    fn add_cap(&mut self, position: Point, direction: Vec2, extent: f32) -> (VertexId, VertexId) {
+        self.primitive = StrokePrimitive::Cap(position);
+        let n = tangent(direction) * extent.abs();
+        let square = if self.options.line_cap == LineCap::Square {
+            direction * extent
+        } else {
+            vec2(0.0, 0.0)
+        };
+
+        let left = self.output.add_vertex(Vertex { position: position, normal: n + square, side: Side::Left });
+        let right = self.output.add_vertex(Vertex { position: position, normal: -n + square, side: Side::Right });
+
+        if self.options.line_cap == LineCap::Round {
+            // A half circle from the left side to the right side, going around the end.
+            let sweep = if extent > 0.0 { -PI } else { PI };
+            self.add_round_fan(position, left, (left, n), (right, -n), sweep, Side::Left);
         }
 
-        self.nth += 1;
+        return (left, right);
     }
 
-    fn tessellate_angle(
+    // Even if there is no edge, if we are using square or round caps we have to place a
+    // square or a circle at the current position.
+    fn add_zero_length_cap(&mut self) {
+        let hw = self.half_width;
+        let position = self.current;
+        self.primitive = StrokePrimitive::Cap(position);
+        match self.options.line_cap {
+            LineCap::Butt => {}
+            LineCap::Square => {
+                let a = self.output.add_vertex(Vertex { position: position, normal: vec2(-hw, -hw), side: Side::Left });
+                let b = self.output.add_vertex(Vertex { position: position, normal: vec2(hw, -hw), side: Side::Left });
+                let c = self.output.add_vertex(Vertex { position: position, normal: vec2(hw, hw), side: Side::Right });
+                let d = self.output.add_vertex(Vertex { position: position, normal: vec2(-hw, hw), side: Side::Right });
+                self.add_triangle(a, b, c);
+                self.add_triangle(a, c, d);
+            }
+            LineCap::Round => {
+                let normal = vec2(hw, 0.0);
+                let first = self.output.add_vertex(Vertex { position: position, normal: normal, side: Side::Left });
+                self.add_round_fan(position, first, (first, normal), (first, normal), 2.0 * PI, Side::Left);
+            }
+        }
+    }
+
+    // Adds a fan of triangles from `origin` to the vertices of an arc of normals that starts
+    // with the normal of `from` and rotates by `sweep` radians to the normal of `to`, the
+    // length of the normals going from the one of `from` to the one of `to`. The number of
+    // vertices depends on the tolerance and on the width of the stroke.
+    fn add_round_fan(
         &mut self,
-        _position: Point,
-        _na: Vec2,
-        a_id: VertexId,
-        _nb: Vec2,
-        b_id: VertexId,
-        _nc: Vec2,
-        c_id: VertexId,
+        position: Point,
+        origin: VertexId,
+        from: (VertexId, Vec2),
+        to: (VertexId, Vec2),
+        sweep: f32,
+        side: Side,
     ) {
-        // TODO: Properly support all types of angles.
-        self.output.add_triangle(b_id, a_id, c_id);
-    }
-}
+        let (from_radius, to_radius) = (from.1.length(), to.1.length());
+        let num_steps = arc_step_count(
+            self.options.line_width * from_radius.max(to_radius),
+            sweep,
+            self.options.tolerance,
+        );
+        let start_angle = directed_angle(vec2(1.0, 0.0), from.1);
+        let to = to.0;
 
-fn get_angle_info(previous: Point, current: Point, next: Point) -> (Vec2, Vec2, Option<Vec2>) {
-    let amount = 0.5;
-    let n1 = tangent(current - previous) * amount;
-    let n2 = tangent(next - current) * amount;
-
-    // Segment P1-->PX
-    let pn1 = previous + n1; // prev extruded along the tangent n1
-    let pn1x = current + n1; // px extruded along the tangent n1
-    // Segment PX-->P2
-    let pn2 = next + n2;
-    let pn2x = current + n2;
-
-    let inter = match line_intersection(pn1, pn1x, pn2x, pn2) {
-        Some(v) => v,
-        None => {
-            if (n1 - n2).square_length() < 0.000001 {
-                pn1x
-            } else {
-                println!("[StrokeTessellator] unimplemented narrow angle."); // TODO
-                current + (current - previous) * amount / (current - previous).length()
+        let mut previous_id = from.0;
+        for i in 1..num_steps {
+            let t = i as f32 / num_steps as f32;
+            let angle = start_angle + sweep * t;
+            let radius = from_radius + (to_radius - from_radius) * t;
+            let radii = vec2(radius, radius);
+            let normal = ellipse_point_from_angle(point(0.0, 0.0), radii, angle).to_vector();
+            let id = self.output.add_vertex(Vertex { position: position, normal: normal, side: side });
+            if previous_id != origin {
+                self.add_triangle(origin, previous_id, id);
             }
+            previous_id = id;
         }
-    };
-    return (inter - current, current - inter, None);
+        if previous_id != origin && to != origin {
+            self.add_triangle(origin, previous_id, to);
+        }
+    }
 }
 
+// Where the line going through `a` along `d1` intersects the one going through `b` along `d2`,
+// or the middle of `a` and `b` if they are parallel.
+fn line_intersection(a: Vec2, d1: Vec2, b: Vec2, d2: Vec2) -> Vec2 {
+    let cross = d1.cross(d2);
+    if cross.abs() < 0.0001 {
+        return (a + b) * 0.5;
+    }
+
+    return a + d1 * ((b - a).cross(d2) / cross);
+}
+
+// Dash patterns with negative or non-finite values are ignored, as well as the ones that are
+// shorter than the tolerance, which can't be represented and would produce huge numbers of
+// dashes.
+fn has_dashes(pattern: &[f32], tolerance: f32) -> bool {
+    let period: f32 = pattern.iter().sum();
+    return !pattern.is_empty() && period > 0.0 && period >= tolerance &&
+        pattern.iter().all(|&d| d >= 0.0 && d.is_finite());
+}
+
+const MAX_DASHES_PER_SEGMENT: f32 = 100000.0;
+
 /// Parameters for the tessellator.
+///
+/// The dash pattern is not part of the options so that they stay `Copy`, see
+/// [DashPattern](struct.DashPattern.html).
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct StrokeOptions {
     /// See the SVG secification.
     pub line_cap: LineCap,
 
     /// See the SVG secification.
-    ///
-    /// Not implemented yet!
     pub line_join: LineJoin,
 
     /// See the SVG secification.
-    ///
-    /// Not implemented yet!
     pub miter_limit: f32,
 
+    /// The width of the stroke.
+    ///
+    /// The normals of the vertices are always relative to a width of 1.0. This is only used to
+    /// pick the number of vertices of the round joins and caps for the tolerance.
+    pub line_width: f32,
+
     /// Maximum allowed distance to the path when building an approximation.
     pub tolerance: f32,
 
@@ -431,6 +1028,7 @@ impl StrokeOptions {
             line_cap: LineCap::Butt,
             line_join: LineJoin::Miter,
             miter_limit: 10.0,
+            line_width: 1.0,
             tolerance: 0.1,
             vertex_aa: false,
             _private: (),
@@ -457,6 +1055,11 @@ impl StrokeOptions {
         return self;
     }
 
+    pub fn with_line_width(mut self, width: f32) -> StrokeOptions {
+        self.line_width = width;
+        return self;
+    }
+
     pub fn with_vertex_aa(mut self) -> StrokeOptions {
         self.vertex_aa = true;
         return self;
@@ -464,64 +1067,670 @@ impl StrokeOptions {
 }
 
 
-/// Line cap as defined by the SVG specification.
+pub use core::{LineCap, LineJoin};
+
+/// Tessellates the stroke of a circular arc without flattening it first.
 ///
-/// See: https://svgwg.org/specs/strokes/#StrokeLinecapProperty
+/// The sides of the stroke of an arc are arcs as well, so the vertices are placed along the
+/// radii of the arc and the number of segments is chosen for the outer side to be within the
+/// tolerance, instead of the arc itself. Like with `StrokeTessellator`, the vertices are on
+/// the arc and must be extruded along their normal multiplied by `line_width` (the normals
+/// are 0.5 long). When the stroke is wider than the diameter of the arc, the normals of the
+/// inner side are shortened so that it stops at the center rather than crossing it.
 ///
-/// <svg viewBox="0 0 400 399.99998" height="400" width="400">
-///   <g transform="translate(0,-652.36229)">
-///     <path style="opacity:1;fill:#80b3ff;stroke:#000000;stroke-width:1;stroke-linejoin:round;" d="m 240,983 a 30,30 0 0 1 -25,-15 30,30 0 0 1 0,-30.00001 30,30 0 0 1 25.98076,-15 l 0,30 z"/>
-///     <path style="fill:#80b3ff;stroke:#000000;stroke-width:1px;stroke-linecap:butt;" d="m 390,782.6 -150,0 0,-60 150,0.5"/>
-///     <circle style="opacity:1;fill:#ff7f2a;stroke:#000000;stroke-width:1;stroke-linejoin:round;" r="10" cy="752.89227" cx="240.86813"/>
-///     <path style="fill:none;stroke:#000000;stroke-width:1px;stroke-linejoin:round;" d="m 240,722.6 150,60"/>
-///     <path style="fill:#80b3ff;stroke:#000000;stroke-width:1px;stroke-linecap:butt;" d="m 390,882 -180,0 0,-60 180,0.4"/>
-///     <circle style="opacity:1;fill:#ff7f2a;stroke:#000000;stroke-width:1;stroke-linejoin:round;" cx="239.86813" cy="852.20868" r="10" />
-///     <path style="fill:none;stroke:#000000;stroke-width:1px;stroke-linejoin:round;" d="m 210.1,822.3 180,60"/>
-///     <path style="fill:#80b3ff;stroke:#000000;stroke-width:1px;stroke-linecap:butt;" d="m 390,983 -150,0 0,-60 150,0.4"/>
-///     <circle style="opacity:1;fill:#ff7f2a;stroke:#000000;stroke-width:1;stroke-linejoin:round;" cx="239.86813" cy="953.39734" r="10" />
-///     <path style="fill:none;stroke:#000000;stroke-width:1px;stroke-linejoin:round;" d="m 390,983 -150,-60 L 210,953 l 30,30 -21.5,-9.5 L 210,953 218.3,932.5 240,923.4"/>
-///     <text y="757.61273" x="183.65314" style="font-style:normal;font-weight:normal;font-size:20px;line-height:125%;font-family:Sans;text-align:end;text-anchor:end;fill:#000000;stroke:none;">
-///        <tspan y="757.61273" x="183.65314">LineCap::Butt</tspan>
-///        <tspan y="857.61273" x="183.65314">LineCap::Square</tspan>
-///        <tspan y="957.61273" x="183.65314">LineCap::Round</tspan>
-///      </text>
-///   </g>
-/// </svg>
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum LineCap {
-    /// The stroke for each subpath does not extend beyond its two endpoints.
-    /// A zero length subpath will therefore not have any stroke.
-    Butt,
-    /// At the end of each subpath, the shape representing the stroke will be
-    /// extended by a rectangle with the same width as the stroke width and
-    /// whose length is half of the stroke width. If a subpath has zero length,
-    /// then the resulting effect is that the stroke for that subpath consists
-    /// solely of a square with side length equal to the stroke width, centered
-    /// at the subpath's point.
-    Square,
-    /// [Not implemented] At each end of each subpath, the shape representing
-    /// the stroke will be extended by a half circle with a radius equal to the
-    /// stroke width. If a subpath has zero length, then the resulting effect is
-    /// that the stroke for that subpath consists solely of a full circle centered
-    /// at the subpath's point.
-    Round,
+/// Only butt and square caps are supported.
+pub fn stroke_arc<Output>(
+    arc: &Arc,
+    line_width: f32,
+    options: &StrokeOptions,
+    output: &mut Output,
+) -> StrokeResult
+where
+    Output: GeometryBuilder<Vertex>,
+{
+    output.begin_geometry();
+
+    let sweep = arc.sweep_angle.get();
+    if sweep == 0.0 || arc.radius <= 0.0 {
+        return Ok(output.end_geometry());
+    }
+
+    let hw = 0.5;
+    let num_steps = arc_step_count(arc.radius + line_width * 0.5, sweep, options.tolerance);
+    let inner_length = if line_width > arc.radius * 2.0 { arc.radius / line_width } else { hw };
+
+    let mut vertices = Vec::with_capacity(num_steps as usize + 3);
+    {
+        let mut add_vertices = |t: f32, cap: Vec2| {
+            let direction = arc.sample_direction(t);
+            // The left side is towards the center of counter-clockwise arcs.
+            let (left, right) = if sweep > 0.0 {
+                (-direction * inner_length, direction * hw)
+            } else {
+                (direction * hw, -direction * inner_length)
+            };
+            let position = arc.sample(t);
+            let a = output.add_vertex(Vertex { position: position, normal: left + cap, side: Side::Left });
+            let b = output.add_vertex(Vertex { position: position, normal: right + cap, side: Side::Right });
+            vertices.push((a, b));
+        };
+
+        let square_caps = options.line_cap == LineCap::Square;
+        if square_caps {
+            add_vertices(0.0, -arc.sample_tangent(0.0) * hw);
+        }
+        for i in 0..(num_steps + 1) {
+            add_vertices(i as f32 / num_steps as f32, vec2(0.0, 0.0));
+        }
+        if square_caps {
+            add_vertices(1.0, arc.sample_tangent(1.0) * hw);
+        }
+    }
+
+    for i in 1..vertices.len() {
+        let (previous_a, previous_b) = vertices[i - 1];
+        let (a, b) = vertices[i];
+        output.add_triangle(previous_b, previous_a, b);
+        output.add_triangle(previous_a, a, b);
+    }
+
+    return Ok(output.end_geometry());
 }
 
-/// Line join as defined by the SVG specification.
+/// Computes the bounding rectangle of the stroke of a path without tessellating it.
 ///
-/// See: https://svgwg.org/specs/strokes/#StrokeLinejoinProperty
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum LineJoin {
-    /// A sharp corner is to be used to join path segments.
-    Miter,
-    /// [Not implemented] Same as a miter join, but if the miter limit is exceeded,
-    /// the miter is clipped at a miter length equal to the miter limit value
-    /// multiplied by the stroke width.
-    MiterClip,
-    /// [Not implemented] A round corner is to be used to join path segments.
-    Round,
-    /// [Not implemented] A bevelled corner is to be used to join path segments.
-    /// The bevel shape is a triangle that fills the area between the two stroked
-    /// segments.
-    Bevel,
+/// The stroke is `line_width` wide, and its caps, joins and miter limit are the ones of
+/// `options`, following the SVG specification. This is useful to compute the region affected
+/// by a stroke, since generating the stroke mesh for that is wasteful.
+pub fn stroke_bounding_rect<Iter>(path: Iter, line_width: f32, options: &StrokeOptions) -> Rect
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let mut bounds = StrokeBounds {
+        min: point(::std::f32::MAX, ::std::f32::MAX),
+        max: point(::std::f32::MIN, ::std::f32::MIN),
+        half_width: line_width * 0.5,
+        options: *options,
+    };
+
+    let mut points: Vec<Point> = Vec::new();
+    // Where a sub-path starts if there is no move_to after a close.
+    let mut restart = point(0.0, 0.0);
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                bounds.add_sub_path(&points, false);
+                points.clear();
+                points.push(to);
+                restart = to;
+            }
+            FlattenedEvent::LineTo(to) => {
+                if points.is_empty() {
+                    points.push(restart);
+                }
+                if points.last() != Some(&to) {
+                    points.push(to);
+                }
+            }
+            FlattenedEvent::Close => {
+                if points.len() > 1 && points.first() == points.last() {
+                    points.pop();
+                }
+                bounds.add_sub_path(&points, true);
+                points.clear();
+            }
+        }
+    }
+    bounds.add_sub_path(&points, false);
+
+    if bounds.min.x > bounds.max.x {
+        return Rect::zero();
+    }
+
+    return rect(bounds.min.x, bounds.min.y, bounds.max.x - bounds.min.x, bounds.max.y - bounds.min.y);
+}
+
+struct StrokeBounds {
+    min: Point,
+    max: Point,
+    half_width: f32,
+    options: StrokeOptions,
+}
+
+impl StrokeBounds {
+    fn add_point(&mut self, p: Point) {
+        self.min = point(self.min.x.min(p.x), self.min.y.min(p.y));
+        self.max = point(self.max.x.max(p.x), self.max.y.max(p.y));
+    }
+
+    fn add_square(&mut self, center: Point) {
+        let hw = self.half_width;
+        self.add_point(center - vec2(hw, hw));
+        self.add_point(center + vec2(hw, hw));
+    }
+
+    fn add_sub_path(&mut self, points: &[Point], closed: bool) {
+        let n = points.len();
+        if n == 0 {
+            return;
+        }
+
+        if n == 1 {
+            // A zero-length sub-path only has its caps.
+            match self.options.line_cap {
+                LineCap::Square | LineCap::Round => { self.add_square(points[0]); }
+                LineCap::Butt => {}
+            }
+            return;
+        }
+
+        let hw = self.half_width;
+        let num_edges = if closed { n } else { n - 1 };
+        for i in 0..num_edges {
+            let (from, to) = (points[i], points[(i + 1) % n]);
+            let normal = tangent(to - from) * hw;
+            self.add_point(from + normal);
+            self.add_point(from - normal);
+            self.add_point(to + normal);
+            self.add_point(to - normal);
+        }
+
+        if closed {
+            for i in 0..n {
+                self.add_join(points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+            }
+        } else {
+            for i in 1..(n - 1) {
+                self.add_join(points[i - 1], points[i], points[i + 1]);
+            }
+            self.add_cap(points[0], points[1]);
+            self.add_cap(points[n - 1], points[n - 2]);
+        }
+    }
+
+    fn add_cap(&mut self, position: Point, previous: Point) {
+        match self.options.line_cap {
+            LineCap::Butt => {}
+            LineCap::Square => {
+                let hw = self.half_width;
+                let d = (position - previous).normalize() * hw;
+                let normal = tangent(d) * hw;
+                self.add_point(position + d + normal);
+                self.add_point(position + d - normal);
+            }
+            LineCap::Round => { self.add_square(position); }
+        }
+    }
+
+    fn add_join(&mut self, previous: Point, current: Point, next: Point) {
+        let clip = match self.options.line_join {
+            LineJoin::Bevel => { return; }
+            LineJoin::Round => {
+                self.add_square(current);
+                return;
+            }
+            LineJoin::Miter => false,
+            LineJoin::MiterClip => true,
+        };
+
+        let hw = self.half_width;
+        let d1 = (current - previous).normalize();
+        let d2 = (next - current).normalize();
+
+        // The normals on the outer side of the turn.
+        let (n1, n2) = if d1.cross(d2) > 0.0 {
+            (-tangent(d1), -tangent(d2))
+        } else {
+            (tangent(d1), tangent(d2))
+        };
+
+        // The miter length divided by the line width is 1 / sin(theta / 2) where theta is the
+        // angle between the two edges.
+        let half_angle_sin = ((1.0 + d1.dot(d2)) * 0.5).max(0.0).sqrt();
+        let bisector = if half_angle_sin > 0.0001 { (n1 + n2).normalize() } else { d1 };
+
+        if half_angle_sin * self.options.miter_limit >= 1.0 {
+            self.add_point(current + bisector * (hw / half_angle_sin));
+            return;
+        }
+
+        if !clip {
+            // Falls back to a bevel join.
+            return;
+        }
+
+        // The miter is clipped by a line perpendicular to the bisector, at a distance of
+        // miter_limit * line_width / 2 from the vertex.
+        let clip_distance = self.options.miter_limit * hw;
+        let t1 = (clip_distance - hw * n1.dot(bisector)) / d1.dot(bisector);
+        let t2 = (clip_distance - hw * n2.dot(bisector)) / -d2.dot(bisector);
+        if t1 > 0.0 && t2 > 0.0 {
+            self.add_point(current + n1 * hw + d1 * t1);
+            self.add_point(current + n2 * hw - d2 * t2);
+        }
+    }
+}
+
+#[cfg(test)]
+fn line_strip(points: &[Point], close: bool) -> Vec<FlattenedEvent> {
+    let mut events = vec![FlattenedEvent::MoveTo(points[0])];
+    for p in &points[1..] {
+        events.push(FlattenedEvent::LineTo(*p));
+    }
+    if close {
+        events.push(FlattenedEvent::Close);
+    }
+
+    return events;
+}
+
+#[test]
+fn test_stroke_bounding_rect_caps() {
+    let line = line_strip(&[point(0.0, 0.0), point(10.0, 0.0)], false);
+
+    let options = StrokeOptions::default();
+    assert_eq!(stroke_bounding_rect(line.iter().cloned(), 2.0, &options), rect(0.0, -1.0, 10.0, 2.0));
+
+    let options = StrokeOptions::default().with_line_cap(LineCap::Square);
+    assert_eq!(stroke_bounding_rect(line.iter().cloned(), 2.0, &options), rect(-1.0, -1.0, 12.0, 2.0));
+
+    let options = StrokeOptions::default().with_line_cap(LineCap::Round);
+    assert_eq!(stroke_bounding_rect(line.iter().cloned(), 2.0, &options), rect(-1.0, -1.0, 12.0, 2.0));
+
+    // Zero-length sub-paths.
+    let dot = vec![FlattenedEvent::MoveTo(point(5.0, 5.0))];
+    let options = StrokeOptions::default().with_line_cap(LineCap::Square);
+    assert_eq!(stroke_bounding_rect(dot.iter().cloned(), 2.0, &options), rect(4.0, 4.0, 2.0, 2.0));
+    let options = StrokeOptions::default();
+    assert_eq!(stroke_bounding_rect(dot.iter().cloned(), 2.0, &options), Rect::zero());
+
+    // Closed sub-paths don't have caps.
+    let square = line_strip(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)], true);
+    let options = StrokeOptions::default().with_line_cap(LineCap::Square);
+    assert_eq!(stroke_bounding_rect(square.iter().cloned(), 2.0, &options), rect(-1.0, -1.0, 12.0, 12.0));
+}
+
+#[test]
+fn test_stroke_bounding_rect_joins() {
+    // A sharp turn: the miter is about 10.15 times as long as the line width.
+    let spike = line_strip(&[point(0.0, 0.0), point(10.0, 0.0), point(0.0, 2.0)], false);
+
+    let options = StrokeOptions::default().with_miter_limit(20.0);
+    let r = stroke_bounding_rect(spike.iter().cloned(), 2.0, &options);
+    assert!(r.max_x() > 20.0 && r.max_x() < 20.3);
+
+    // Over the miter limit, miter joins become bevel joins.
+    let options = StrokeOptions::default();
+    let r = stroke_bounding_rect(spike.iter().cloned(), 2.0, &options);
+    assert!(r.max_x() > 10.0 && r.max_x() < 10.5);
+    let options = StrokeOptions::default().with_line_join(LineJoin::Bevel).with_miter_limit(20.0);
+    assert_eq!(stroke_bounding_rect(spike.iter().cloned(), 2.0, &options), r);
+
+    let options = StrokeOptions::default().with_line_join(LineJoin::MiterClip).with_miter_limit(5.0);
+    let r = stroke_bounding_rect(spike.iter().cloned(), 2.0, &options);
+    assert!(r.max_x() > 14.5 && r.max_x() < 15.1);
+
+    let options = StrokeOptions::default().with_line_join(LineJoin::Round);
+    let r = stroke_bounding_rect(spike.iter().cloned(), 2.0, &options);
+    assert_eq!(r.max_x(), 11.0);
+}
+
+#[test]
+fn test_stroke_arc() {
+    use geometry_builder::{VertexBuffers, simple_builder};
+
+    let arc = Arc {
+        center: point(1.0, 2.0),
+        radius: 10.0,
+        start_angle: Radians::new(0.0),
+        sweep_angle: Radians::new(PI * 0.5),
+    };
+    let stroke = |arc: &Arc, line_width: f32, options: &StrokeOptions| {
+        let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+        stroke_arc(arc, line_width, options, &mut simple_builder(&mut buffers)).unwrap();
+        buffers
+    };
+
+    let buffers = stroke(&arc, 2.0, &StrokeOptions::default());
+    assert_eq!(buffers.indices.len(), (buffers.vertices.len() / 2 - 1) * 6);
+    for v in &buffers.vertices {
+        let extruded = v.position + v.normal * 2.0;
+        let expected_radius = if v.side == Side::Left { 9.0 } else { 11.0 };
+        assert!(((extruded - arc.center).length() - expected_radius).abs() < 0.001);
+    }
+
+    // The number of segments depends on the width of the stroke.
+    let wide = stroke(&arc, 30.0, &StrokeOptions::default());
+    assert!(wide.vertices.len() > buffers.vertices.len());
+
+    // The inner side doesn't cross the center.
+    for v in &wide.vertices {
+        if v.side == Side::Left {
+            assert!((v.position + v.normal * 30.0 - arc.center).length() < 0.001);
+        }
+    }
+
+    // Clockwise arcs have the center on their right side.
+    let flipped = stroke(&arc.flip(), 2.0, &StrokeOptions::default());
+    for v in &flipped.vertices {
+        let extruded = v.position + v.normal * 2.0;
+        let expected_radius = if v.side == Side::Right { 9.0 } else { 11.0 };
+        assert!(((extruded - arc.center).length() - expected_radius).abs() < 0.001);
+    }
+
+    // Square caps extend the ends by half of the width.
+    let capped = stroke(&arc, 2.0, &StrokeOptions::default().with_line_cap(LineCap::Square));
+    assert_eq!(capped.vertices.len(), buffers.vertices.len() + 4);
+    // The outer vertex of the start cap.
+    let first = capped.vertices[1];
+    assert!((first.position + first.normal * 2.0 - point(12.0, 1.0)).length() < 0.001);
+}
+
+#[cfg(test)]
+fn tessellate_stroke(events: &[FlattenedEvent], options: &StrokeOptions) -> ::geometry_builder::VertexBuffers<Vertex> {
+    use geometry_builder::{VertexBuffers, simple_builder};
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate(
+        events.iter().cloned(),
+        options,
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    return buffers;
+}
+
+#[cfg(test)]
+fn has_vertex(vertices: &[Vertex], position: Point, normal: Vec2, side: Side) -> bool {
+    vertices.iter().any(|v| {
+        v.position == position && (v.normal - normal).length() < 0.0001 && v.side == side
+    })
+}
+
+#[test]
+fn test_stroke_joins() {
+    let corner = line_strip(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0)], false);
+    let join_vertices = |buffers: &::geometry_builder::VertexBuffers<Vertex>| -> Vec<Vertex> {
+        buffers.vertices.iter().filter(|v| v.position == point(10.0, 0.0)).cloned().collect()
+    };
+
+    // Two edges and their miter join.
+    let miter = tessellate_stroke(&corner, &StrokeOptions::default());
+    assert_eq!(miter.vertices.len(), 6);
+    assert_eq!(miter.indices.len(), 12);
+    let join = join_vertices(&miter);
+    assert_eq!(join.len(), 2);
+    assert!(has_vertex(&join, point(10.0, 0.0), vec2(0.5, -0.5), Side::Right));
+    assert!(has_vertex(&join, point(10.0, 0.0), vec2(-0.5, 0.5), Side::Left));
+
+    // The outer side of a bevel join has two vertices with the normals of the edges.
+    let bevel = tessellate_stroke(&corner, &StrokeOptions::default().with_line_join(LineJoin::Bevel));
+    assert_eq!(bevel.vertices.len(), 7);
+    assert_eq!(bevel.indices.len(), 15);
+    let join = join_vertices(&bevel);
+    assert!(has_vertex(&join, point(10.0, 0.0), vec2(0.0, -0.5), Side::Right));
+    assert!(has_vertex(&join, point(10.0, 0.0), vec2(0.5, 0.0), Side::Right));
+    assert!(has_vertex(&join, point(10.0, 0.0), vec2(-0.5, 0.5), Side::Left));
+
+    // The outer side of a round join is an arc.
+    let options = StrokeOptions::default()
+        .with_line_join(LineJoin::Round)
+        .with_line_width(10.0)
+        .with_tolerance(0.01);
+    let round = tessellate_stroke(&corner, &options);
+    let join = join_vertices(&round);
+    assert!(join.len() > 4);
+    assert_eq!(round.indices.len(), 12 + (join.len() - 2) * 3);
+    for v in &join {
+        if v.side == Side::Right {
+            assert!((v.normal.length() - 0.5).abs() < 0.001);
+            assert!(v.normal.x >= -0.001 && v.normal.y <= 0.001);
+        }
+    }
+
+    // Over the miter limit, miter joins become bevel joins.
+    let spike = line_strip(&[point(0.0, 0.0), point(10.0, 0.0), point(0.0, 2.0)], false);
+    assert_eq!(tessellate_stroke(&spike, &StrokeOptions::default()).vertices.len(), 7);
+    assert_eq!(tessellate_stroke(&spike, &StrokeOptions::default().with_miter_limit(20.0)).vertices.len(), 6);
+    let options = StrokeOptions::default().with_line_join(LineJoin::MiterClip).with_miter_limit(5.0);
+    let clipped = tessellate_stroke(&spike, &options);
+    assert_eq!(clipped.vertices.len(), 9);
+    // The outer miter is clipped at 5 times the half line width from the vertex.
+    let outer: Vec<Vertex> = clipped.vertices.iter().filter(|v| v.side == Side::Right).cloned().collect();
+    assert!(outer.iter().all(|v| v.normal.length() < 2.6));
+    assert!(outer.iter().any(|v| v.normal.length() > 2.4));
+
+    // A closed square has a join at each corner and no cap.
+    let square = line_strip(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)], true);
+    let closed = tessellate_stroke(&square, &StrokeOptions::default().with_line_cap(LineCap::Round));
+    assert_eq!(closed.vertices.len(), 8);
+    assert_eq!(closed.indices.len(), 24);
+}
+
+#[test]
+fn test_stroke_caps() {
+    let line = line_strip(&[point(0.0, 0.0), point(10.0, 0.0)], false);
+
+    let butt = tessellate_stroke(&line, &StrokeOptions::default());
+    assert_eq!(butt.vertices.len(), 4);
+    assert_eq!(butt.indices.len(), 6);
+
+    let square = tessellate_stroke(&line, &StrokeOptions::default().with_line_cap(LineCap::Square));
+    assert_eq!(square.vertices.len(), 4);
+    assert!(has_vertex(&square.vertices, point(0.0, 0.0), vec2(-0.5, 0.5), Side::Left));
+    assert!(has_vertex(&square.vertices, point(10.0, 0.0), vec2(0.5, -0.5), Side::Right));
+
+    let options = StrokeOptions::default().with_line_cap(LineCap::Round).with_line_width(10.0);
+    let round = tessellate_stroke(&line, &options);
+    assert!(round.vertices.len() > 8);
+    // Each cap is a fan of triangles, and the edge has two.
+    assert_eq!(round.indices.len(), (round.vertices.len() - 4 + 2) * 3);
+    for v in &round.vertices {
+        assert!((v.normal.length() - 0.5).abs() < 0.001);
+        // Around the ends, not over the edge.
+        if v.position == point(0.0, 0.0) {
+            assert!(v.normal.x <= 0.001);
+        } else {
+            assert!(v.normal.x >= -0.001);
+        }
+    }
+
+    // Zero-length sub-paths are a square or a circle.
+    let dot = vec![FlattenedEvent::MoveTo(point(5.0, 5.0))];
+    assert_eq!(tessellate_stroke(&dot, &StrokeOptions::default()).vertices.len(), 0);
+    let square = tessellate_stroke(&dot, &StrokeOptions::default().with_line_cap(LineCap::Square));
+    assert_eq!(square.indices.len(), 6);
+    let circle = tessellate_stroke(&dot, &options);
+    assert_eq!(circle.indices.len(), (circle.vertices.len() - 2) * 3);
+    assert!(circle.vertices.len() > 4);
+
+    // Closing a sub-path doesn't start a new one.
+    let mut closed = line_strip(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0)], true);
+    closed.push(FlattenedEvent::Close);
+    let square = tessellate_stroke(&closed, &StrokeOptions::default().with_line_cap(LineCap::Square));
+    assert_eq!(square.vertices.len(), 6);
+}
+
+#[cfg(test)]
+fn tessellate_dashed_stroke(
+    events: &[FlattenedEvent],
+    options: &StrokeOptions,
+    dash_array: &[f32],
+    dash_offset: f32,
+) -> ::geometry_builder::VertexBuffers<Vertex> {
+    use geometry_builder::{VertexBuffers, simple_builder};
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_dashed(
+        events.iter().cloned(),
+        options,
+        &DashPattern::new(dash_array, dash_offset),
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    return buffers;
+}
+
+#[test]
+fn test_stroke_dashes() {
+    let line = line_strip(&[point(0.0, 0.0), point(10.0, 0.0)], false);
+    let dash_ends = |dash_array: &[f32], dash_offset: f32| -> Vec<f32> {
+        let buffers = tessellate_dashed_stroke(&line, &StrokeOptions::default(), dash_array, dash_offset);
+        assert_eq!(buffers.indices.len(), buffers.vertices.len() / 4 * 6);
+        let mut x: Vec<f32> = buffers.vertices.iter().map(|v| v.position.x.round()).collect();
+        x.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        x.dedup();
+        x
+    };
+
+    assert_eq!(dash_ends(&[2.0, 3.0], 0.0), vec![0.0, 2.0, 5.0, 7.0]);
+    assert_eq!(dash_ends(&[2.0, 3.0], 1.0), vec![0.0, 1.0, 4.0, 6.0, 9.0, 10.0]);
+    assert_eq!(dash_ends(&[2.0, 3.0], -2.0), vec![2.0, 4.0, 7.0, 9.0]);
+    // Odd patterns are repeated.
+    assert_eq!(dash_ends(&[4.0], 0.0), vec![0.0, 4.0, 8.0, 10.0]);
+    // Invalid patterns are ignored.
+    assert_eq!(dash_ends(&[2.0, -1.0], 0.0), vec![0.0, 10.0]);
+    assert_eq!(dash_ends(&[0.0, 0.0], 0.0), vec![0.0, 10.0]);
+    assert_eq!(dash_ends(&[1.0, ::std::f32::NAN], 0.0), vec![0.0, 10.0]);
+    // Patterns shorter than the tolerance are drawn solid.
+    assert_eq!(dash_ends(&[1.0e-6, 1.0e-6], 0.0), vec![0.0, 10.0]);
+
+    // Too many dashes for a segment, the segment is drawn solid instead of the loop getting
+    // stuck when the dashes are below the precision of the distance along it.
+    let long_line = line_strip(&[point(0.0, 0.0), point(100.0, 0.0)], false);
+    let options = StrokeOptions::default().with_tolerance(1.0e-7);
+    let solid = tessellate_dashed_stroke(&long_line, &options, &[1.0e-6, 1.0e-6], 0.0);
+    assert_eq!(solid.vertices.len(), 4);
+
+    // Zero-length dashes are dots when the caps are round or square, here at 0, 5 and 10.
+    let options = StrokeOptions::default().with_line_cap(LineCap::Square);
+    let longer_line = line_strip(&[point(0.0, 0.0), point(12.0, 0.0)], false);
+    let dots = tessellate_dashed_stroke(&longer_line, &options, &[0.0, 5.0], 0.0);
+    assert_eq!(dots.vertices.len(), 12);
+
+    // The dashes go around the corners of closed sub-paths.
+    let square = line_strip(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)], true);
+    let dashed = tessellate_dashed_stroke(&square, &StrokeOptions::default(), &[15.0, 5.0], 0.0);
+    // Two dashes with a join each.
+    assert_eq!(dashed.vertices.len(), 12);
+    assert_eq!(dashed.indices.len(), 24);
+}
+
+#[cfg(test)]
+fn tessellate_styled_stroke(
+    events: &[FlattenedEvent],
+    options: &StrokeOptions,
+    style_breaks: &[StyleBreak],
+) -> ::geometry_builder::VertexBuffers<Vertex> {
+    use geometry_builder::{VertexBuffers, simple_builder};
+
+    let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+    StrokeTessellator::new().tessellate_with_style_breaks(
+        events.iter().cloned(),
+        options,
+        style_breaks,
+        &mut simple_builder(&mut buffers),
+    ).unwrap();
+
+    return buffers;
+}
+
+#[test]
+fn test_stroke_style_breaks() {
+    let options = StrokeOptions::default().with_line_width(2.0);
+
+    // The stroke gets twice as wide in the middle of a line.
+    let line = line_strip(&[point(0.0, 0.0), point(20.0, 0.0)], false);
+    let wider = tessellate_styled_stroke(&line, &options, &[StyleBreak::new(10.0, 4.0)]);
+    assert_eq!(wider.vertices.len(), 8);
+    assert_eq!(wider.indices.len(), 12);
+    assert!(has_vertex(&wider.vertices, point(0.0, 0.0), vec2(0.0, 0.5), Side::Left));
+    assert!(has_vertex(&wider.vertices, point(10.0, 0.0), vec2(0.0, 0.5), Side::Left));
+    assert!(has_vertex(&wider.vertices, point(10.0, 0.0), vec2(0.0, 1.0), Side::Left));
+    assert!(has_vertex(&wider.vertices, point(10.0, 0.0), vec2(0.0, -1.0), Side::Right));
+    assert!(has_vertex(&wider.vertices, point(20.0, 0.0), vec2(0.0, -1.0), Side::Right));
+
+    // The miter join at a break connects the sides of both widths.
+    let corner = line_strip(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0)], false);
+    let joined = tessellate_styled_stroke(&corner, &options, &[StyleBreak::new(10.0, 4.0)]);
+    assert_eq!(joined.vertices.len(), 6);
+    assert!(has_vertex(&joined.vertices, point(10.0, 0.0), vec2(-1.0, 0.5), Side::Left));
+    assert!(has_vertex(&joined.vertices, point(10.0, 0.0), vec2(1.0, -0.5), Side::Right));
+    assert!(has_vertex(&joined.vertices, point(10.0, 10.0), vec2(-1.0, 0.0), Side::Left));
+
+    // Dashes from the middle of the line, the solid part ends with the first dash.
+    let dashed = tessellate_styled_stroke(
+        &line,
+        &options,
+        &[StyleBreak::new(10.0, 2.0).with_dash_pattern(&[2.0, 3.0], 0.0)],
+    );
+    assert_eq!(dashed.vertices.len(), 10);
+    let mut x: Vec<f32> = dashed.vertices.iter().map(|v| v.position.x.round()).collect();
+    x.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    x.dedup();
+    assert_eq!(x, vec![0.0, 10.0, 12.0, 15.0, 17.0]);
+
+    // Without breaks, the result is the same as with the regular tessellation.
+    let square = line_strip(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0), point(0.0, 10.0)], true);
+    let styled = tessellate_styled_stroke(&square, &options, &[]);
+    let regular = tessellate_stroke(&square, &options);
+    assert_eq!(styled.vertices, regular.vertices);
+    assert_eq!(styled.indices, regular.indices);
+
+    // Invalid breaks are ignored instead of panicking.
+    let nan = ::std::f32::NAN;
+    let invalid = [StyleBreak::new(nan, 4.0), StyleBreak::new(5.0, nan), StyleBreak::new(5.0, -1.0)];
+    let styled = tessellate_styled_stroke(&square, &options, &invalid);
+    assert_eq!(styled.vertices, regular.vertices);
+    assert_eq!(styled.indices, regular.indices);
+}
+
+#[test]
+fn test_stroke_primitives() {
+    use geometry_builder::{VertexBuffers, simple_builder};
+
+    let corner = line_strip(&[point(0.0, 0.0), point(10.0, 0.0), point(10.0, 10.0)], false);
+    let tessellate = |options: &StrokeOptions| -> (VertexBuffers<Vertex>, Vec<StrokePrimitiveRange>) {
+        let mut buffers: VertexBuffers<Vertex> = VertexBuffers::new();
+        let mut primitives = Vec::new();
+        StrokeTessellator::new().tessellate_with_primitives(
+            corner.iter().cloned(),
+            options,
+            &mut primitives,
+            &mut simple_builder(&mut buffers),
+        ).unwrap();
+
+        // The ranges cover all of the triangles, in order.
+        let mut end = 0;
+        for range in &primitives {
+            assert_eq!(range.triangles.start, end);
+            assert!(range.triangles.end > range.triangles.start);
+            end = range.triangles.end;
+        }
+        assert_eq!(end as usize * 3, buffers.indices.len());
+
+        (buffers, primitives)
+    };
+
+    let round = StrokeOptions::default()
+        .with_line_join(LineJoin::Round)
+        .with_line_cap(LineCap::Round)
+        .with_line_width(10.0);
+    let (buffers, primitives) = tessellate(&round);
+    assert_eq!(primitives.len(), 4);
+    assert_eq!(primitives[0].primitive, StrokePrimitive::Join(point(10.0, 0.0)));
+    assert_eq!(primitives[1].primitive, StrokePrimitive::Cap(point(10.0, 10.0)));
+    assert_eq!(primitives[2].primitive, StrokePrimitive::Cap(point(0.0, 0.0)));
+    assert_eq!(primitives[3].primitive, StrokePrimitive::Edges);
+    assert_eq!(primitives[3].triangles.end - primitives[3].triangles.start, 4);
+
+    // The same tessellation as without the ranges.
+    let regular = tessellate_stroke(&corner, &round);
+    assert_eq!(buffers.vertices, regular.vertices);
+    assert_eq!(buffers.indices, regular.indices);
+
+    // Miter joins and butt caps don't have triangles of their own.
+    let (_, primitives) = tessellate(&StrokeOptions::default());
+    assert_eq!(primitives.len(), 1);
+    assert_eq!(primitives[0].primitive, StrokePrimitive::Edges);
 }
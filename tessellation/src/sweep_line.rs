@@ -0,0 +1,451 @@
+//! An ordered sequence of spans for the sweep line of the fill tessellator.
+//!
+//! The spans are stored in a balanced tree (a treap keyed by the position of the spans
+//! on the sweep line) so that inserting or removing a span anywhere on the sweep line
+//! doesn't move the spans after it.
+//!
+//! Each node also stores the horizontal extent of the spans in its subtree, which makes it
+//! possible to look for the spans that overlap a given range of x coordinates without
+//! testing all of the spans of the sweep line. This is what keeps the tessellation of
+//! paths with a large number of spans on the sweep line at the same time from being
+//! quadratic.
+
+use math::FixedPoint32;
+
+#[cfg(test)]
+use std::cell::Cell;
+
+const NIL: usize = ::std::usize::MAX;
+
+/// The values stored in the sweep line.
+pub trait XExtent {
+    /// The smallest and largest x coordinates of the value.
+    fn x_extent(&self) -> (FixedPoint32, FixedPoint32);
+}
+
+struct Node<T> {
+    // None for the nodes of the free list.
+    value: Option<T>,
+    left: usize,
+    right: usize,
+    priority: u32,
+    // Number of values in the subtree.
+    size: usize,
+    // Extent of the node's value.
+    min_x: FixedPoint32,
+    max_x: FixedPoint32,
+    // Extent of the subtree.
+    subtree_min_x: FixedPoint32,
+    subtree_max_x: FixedPoint32,
+}
+
+/// A sequence of values that can be accessed, inserted and removed by index in
+/// logarithmic time, and searched by x extent.
+pub struct SweepLine<T> {
+    nodes: Vec<Node<T>>,
+    free_list: Vec<usize>,
+    root: usize,
+    // State of the pseudo-random generator for the priorities of the nodes. It is not
+    // seeded so that the shape of the tree is the same from one run to another.
+    rng: u32,
+    path: Vec<usize>,
+    #[cfg(test)]
+    visits: Cell<usize>,
+}
+
+impl<T: XExtent> SweepLine<T> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        SweepLine {
+            nodes: Vec::with_capacity(capacity),
+            free_list: Vec::new(),
+            root: NIL,
+            rng: 0x9E37_79B9,
+            path: Vec::new(),
+            #[cfg(test)]
+            visits: Cell::new(0),
+        }
+    }
+
+    pub fn reserve(&mut self, additional: usize) { self.nodes.reserve(additional); }
+
+    pub fn len(&self) -> usize { self.size(self.root) }
+
+    pub fn is_empty(&self) -> bool { self.root == NIL }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free_list.clear();
+        self.root = NIL;
+    }
+
+    pub fn get(&self, index: usize) -> &T {
+        assert!(index < self.len());
+        let node = self.node_at(index);
+        return self.nodes[node].value.as_ref().unwrap();
+    }
+
+    /// Modify the value at a given index.
+    ///
+    /// The extents stored in the tree are updated after the modification.
+    pub fn update<R, F>(&mut self, index: usize, callback: F) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        assert!(index < self.len());
+
+        // Walk down to the node, recording the path to update the extents of the
+        // subtrees on the way back.
+        self.path.clear();
+        let mut node = self.root;
+        let mut index = index;
+        loop {
+            let left = self.nodes[node].left;
+            let left_size = self.size(left);
+            if index == left_size {
+                break;
+            }
+            self.path.push(node);
+            if index < left_size {
+                node = left;
+            } else {
+                index -= left_size + 1;
+                node = self.nodes[node].right;
+            }
+        }
+
+        let result = {
+            let node = &mut self.nodes[node];
+            let result = callback(node.value.as_mut().unwrap());
+            let (min_x, max_x) = node.value.as_ref().unwrap().x_extent();
+            node.min_x = min_x;
+            node.max_x = max_x;
+            result
+        };
+
+        self.update_node(node);
+        while let Some(parent) = self.path.pop() {
+            self.update_node(parent);
+        }
+
+        return result;
+    }
+
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len());
+        let (min_x, max_x) = value.x_extent();
+        let priority = self.next_priority();
+        let node = Node {
+            value: Some(value),
+            left: NIL,
+            right: NIL,
+            priority: priority,
+            size: 1,
+            min_x: min_x,
+            max_x: max_x,
+            subtree_min_x: min_x,
+            subtree_max_x: max_x,
+        };
+        let node = match self.free_list.pop() {
+            Some(idx) => {
+                self.nodes[idx] = node;
+                idx
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+
+        let root = self.root;
+        let (before, after) = self.split(root, index);
+        let before = self.merge(before, node);
+        self.root = self.merge(before, after);
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len());
+        let root = self.root;
+        let (before, rest) = self.split(root, index);
+        let (node, after) = self.split(rest, 1);
+        self.root = self.merge(before, after);
+        self.free_list.push(node);
+        return self.nodes[node].value.take().unwrap();
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let last = self.len() - 1;
+        return Some(self.remove(last));
+    }
+
+    /// Returns the index of the first value at or after `start` for which `predicate`
+    /// returns true when called with the value's extent.
+    ///
+    /// The predicate is also used to skip entire subtrees, so it must return false for
+    /// any extent that contains only extents for which it returns false.
+    pub fn find<F>(&self, start: usize, predicate: &F) -> Option<usize>
+    where
+        F: Fn(FixedPoint32, FixedPoint32) -> bool,
+    {
+        let root = self.root;
+        return self.find_in_subtree(root, 0, start, predicate);
+    }
+
+    pub fn iter(&self) -> Iter<T> {
+        let mut iter = Iter {
+            sweep_line: self,
+            stack: Vec::new(),
+        };
+        iter.push_left_branch(self.root);
+        return iter;
+    }
+
+    /// The number of nodes visited by the searches so far, to check the complexity in
+    /// the tests.
+    #[cfg(test)]
+    pub fn visits(&self) -> usize { self.visits.get() }
+
+    fn find_in_subtree<F>(&self, node: usize, offset: usize, start: usize, predicate: &F) -> Option<usize>
+    where
+        F: Fn(FixedPoint32, FixedPoint32) -> bool,
+    {
+        if node == NIL {
+            return None;
+        }
+        self.count_visit();
+
+        let n = &self.nodes[node];
+        if offset + n.size <= start || !predicate(n.subtree_min_x, n.subtree_max_x) {
+            return None;
+        }
+
+        if let Some(index) = self.find_in_subtree(n.left, offset, start, predicate) {
+            return Some(index);
+        }
+
+        let index = offset + self.size(n.left);
+        if index >= start && predicate(n.min_x, n.max_x) {
+            return Some(index);
+        }
+
+        return self.find_in_subtree(n.right, index + 1, start, predicate);
+    }
+
+    fn node_at(&self, index: usize) -> usize {
+        let mut node = self.root;
+        let mut index = index;
+        loop {
+            self.count_visit();
+            let left = self.nodes[node].left;
+            let left_size = self.size(left);
+            if index == left_size {
+                return node;
+            }
+            if index < left_size {
+                node = left;
+            } else {
+                index -= left_size + 1;
+                node = self.nodes[node].right;
+            }
+        }
+    }
+
+    // Splits the subtree into the first `count` values and the remaining ones.
+    fn split(&mut self, node: usize, count: usize) -> (usize, usize) {
+        if node == NIL {
+            return (NIL, NIL);
+        }
+
+        let left = self.nodes[node].left;
+        let right = self.nodes[node].right;
+        let left_size = self.size(left);
+        if count <= left_size {
+            let (a, b) = self.split(left, count);
+            self.nodes[node].left = b;
+            self.update_node(node);
+            return (a, node);
+        }
+
+        let (a, b) = self.split(right, count - left_size - 1);
+        self.nodes[node].right = a;
+        self.update_node(node);
+        return (node, b);
+    }
+
+    // Concatenates two subtrees.
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+
+        if self.nodes[a].priority > self.nodes[b].priority {
+            let right = self.nodes[a].right;
+            let merged = self.merge(right, b);
+            self.nodes[a].right = merged;
+            self.update_node(a);
+            return a;
+        }
+
+        let left = self.nodes[b].left;
+        let merged = self.merge(a, left);
+        self.nodes[b].left = merged;
+        self.update_node(b);
+        return b;
+    }
+
+    // Recomputes the size and extent of a subtree from its children.
+    fn update_node(&mut self, node: usize) {
+        let (left, right) = (self.nodes[node].left, self.nodes[node].right);
+        let mut size = 1;
+        let mut min_x = self.nodes[node].min_x;
+        let mut max_x = self.nodes[node].max_x;
+        for &child in &[left, right] {
+            if child != NIL {
+                size += self.nodes[child].size;
+                min_x = min_x.min(self.nodes[child].subtree_min_x);
+                max_x = max_x.max(self.nodes[child].subtree_max_x);
+            }
+        }
+        let n = &mut self.nodes[node];
+        n.size = size;
+        n.subtree_min_x = min_x;
+        n.subtree_max_x = max_x;
+    }
+
+    fn size(&self, node: usize) -> usize {
+        if node == NIL {
+            return 0;
+        }
+        return self.nodes[node].size;
+    }
+
+    // Xorshift, good enough for the priorities of a treap.
+    fn next_priority(&mut self) -> u32 {
+        let mut x = self.rng;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng = x;
+        return x;
+    }
+
+    #[cfg(test)]
+    fn count_visit(&self) { self.visits.set(self.visits.get() + 1); }
+
+    #[cfg(not(test))]
+    #[inline]
+    fn count_visit(&self) {}
+}
+
+/// Iterates over the values of the sweep line from left to right.
+pub struct Iter<'l, T: 'l> {
+    sweep_line: &'l SweepLine<T>,
+    stack: Vec<usize>,
+}
+
+impl<'l, T: 'l> Iter<'l, T> {
+    fn push_left_branch(&mut self, node: usize) {
+        let mut node = node;
+        while node != NIL {
+            self.stack.push(node);
+            node = self.sweep_line.nodes[node].left;
+        }
+    }
+}
+
+impl<'l, T: 'l> Iterator for Iter<'l, T> {
+    type Item = &'l T;
+
+    fn next(&mut self) -> Option<&'l T> {
+        let node = match self.stack.pop() {
+            Some(node) => node,
+            None => { return None; }
+        };
+        let right = self.sweep_line.nodes[node].right;
+        self.push_left_branch(right);
+        return self.sweep_line.nodes[node].value.as_ref();
+    }
+}
+
+#[cfg(test)]
+struct TestSpan(i32, i32);
+
+#[cfg(test)]
+impl XExtent for TestSpan {
+    fn x_extent(&self) -> (FixedPoint32, FixedPoint32) {
+        (FixedPoint32::from_raw(self.0), FixedPoint32::from_raw(self.1))
+    }
+}
+
+#[cfg(test)]
+fn check_same(sweep_line: &SweepLine<TestSpan>, reference: &[(i32, i32)]) {
+    assert_eq!(sweep_line.len(), reference.len());
+    let values: Vec<(i32, i32)> = sweep_line.iter().map(|s| (s.0, s.1)).collect();
+    assert_eq!(&values[..], reference);
+    for (i, r) in reference.iter().enumerate() {
+        let s = sweep_line.get(i);
+        assert_eq!((s.0, s.1), *r);
+    }
+}
+
+#[test]
+fn test_sweep_line_insert_remove() {
+    let mut sweep_line = SweepLine::with_capacity(16);
+    let mut reference = Vec::new();
+
+    // Insert and remove at pseudo-random positions and compare against a vector.
+    let mut x: u32 = 12345;
+    for i in 0..500 {
+        x = x.wrapping_mul(1103515245).wrapping_add(12345);
+        let r = (x >> 8) as usize;
+        if reference.is_empty() || r % 3 != 0 {
+            let idx = r % (reference.len() + 1);
+            sweep_line.insert(idx, TestSpan(i, i + 10));
+            reference.insert(idx, (i, i + 10));
+        } else {
+            let idx = r % reference.len();
+            let removed = sweep_line.remove(idx);
+            assert_eq!((removed.0, removed.1), reference.remove(idx));
+        }
+    }
+    check_same(&sweep_line, &reference);
+
+    sweep_line.update(3, |s| { s.1 = 1000; });
+    reference[3].1 = 1000;
+    check_same(&sweep_line, &reference);
+
+    while let Some(s) = sweep_line.pop() {
+        assert_eq!((s.0, s.1), reference.pop().unwrap());
+    }
+    assert!(sweep_line.is_empty());
+}
+
+#[test]
+fn test_sweep_line_find() {
+    let mut sweep_line = SweepLine::with_capacity(16);
+    for i in 0..100 {
+        sweep_line.insert(i as usize, TestSpan(i * 10, i * 10 + 5));
+    }
+
+    let contains = |x: i32| move |min: FixedPoint32, max: FixedPoint32| {
+        min.raw() <= x && max.raw() >= x
+    };
+    assert_eq!(sweep_line.find(0, &contains(42)), Some(4));
+    assert_eq!(sweep_line.find(5, &contains(42)), None);
+    assert_eq!(sweep_line.find(0, &contains(47)), None);
+    assert_eq!(sweep_line.find(0, &contains(995)), Some(99));
+
+    // The extents are updated when the values change.
+    sweep_line.update(10, |s| { s.1 = 500; });
+    assert_eq!(sweep_line.find(0, &contains(420)), Some(10));
+    assert_eq!(sweep_line.find(11, &contains(420)), Some(42));
+
+    let after = |x: i32| move |_: FixedPoint32, max: FixedPoint32| max.raw() >= x;
+    assert_eq!(sweep_line.find(0, &after(333)), Some(10));
+    assert_eq!(sweep_line.find(11, &after(333)), Some(33));
+}
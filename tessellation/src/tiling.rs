@@ -0,0 +1,317 @@
+//! Tessellating a set of paths into a grid of tiles.
+//!
+//! Vector tile renderers split the geometry into tiles so that each tile can be uploaded,
+//! cached and culled independently. [tessellate_tiles](fn.tessellate_tiles.html) clips
+//! the paths against each tile of a [TileGrid](struct.TileGrid.html) (so that the
+//! geometry doesn't bleed across tiles), fill-tessellates each tile's content into its own
+//! buffers and records which range of the buffers belongs to which path.
+//!
+//! With the `parallel` feature enabled, the tiles are tessellated in parallel using rayon.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::tiling::*;
+//! # fn upload(_x: u32, _y: u32, _geometry: &VertexBuffers<Point>) {}
+//! # fn set_color(_color: [f32; 4]) {}
+//! # fn draw(_indices: std::ops::Range<u32>) {}
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let paths = vec![path];
+//! # let colors = vec![[1.0, 0.0, 0.0, 1.0]];
+//! let grid = TileGrid::new(point(0.0, 0.0), size(256.0, 256.0), 4, 4);
+//! let tiles = tessellate_tiles(&paths, &grid, &FillOptions::default()).unwrap();
+//! for tile in &tiles {
+//!     upload(tile.x, tile.y, &tile.geometry);
+//!     for range in &tile.ranges {
+//!         set_color(colors[range.path]);
+//!         draw(range.indices.clone());
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::ops::Range;
+
+use math::*;
+use path::Path;
+use path_iterator::PathIterator;
+use path_builder::BaseBuilder;
+use algorithms::clip::{clip_path_to_rect, ClipMode};
+use geometry_builder::{VertexBuffers, BuffersBuilder, TypedPositions};
+use path_fill::{FillTessellator, FillOptions, FillError};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// A regular grid of tiles.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TileGrid {
+    /// Position of the top-left corner of the first tile.
+    pub origin: Point,
+    pub tile_size: Size,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl TileGrid {
+    pub fn new(origin: Point, tile_size: Size, columns: u32, rows: u32) -> Self {
+        TileGrid {
+            origin: origin,
+            tile_size: tile_size,
+            columns: columns,
+            rows: rows,
+        }
+    }
+
+    /// The area covered by a tile.
+    pub fn tile_rect(&self, x: u32, y: u32) -> Rect {
+        Rect::new(
+            point(
+                self.origin.x + x as f32 * self.tile_size.width,
+                self.origin.y + y as f32 * self.tile_size.height,
+            ),
+            self.tile_size,
+        )
+    }
+
+    /// The range of columns and rows of the tiles that intersect a rectangle.
+    pub fn tiles_in_rect(&self, rect: &Rect) -> (Range<u32>, Range<u32>) {
+        let column = |x: f32| {
+            let c = ((x - self.origin.x) / self.tile_size.width).floor();
+            c.max(0.0).min(self.columns as f32) as u32
+        };
+        let row = |y: f32| {
+            let r = ((y - self.origin.y) / self.tile_size.height).floor();
+            r.max(0.0).min(self.rows as f32) as u32
+        };
+        // The clamping above would otherwise put the rectangles that are entirely to the
+        // left of or above the grid in the first column or row.
+        let x0 = column(rect.min_x());
+        let x1 = if rect.max_x() < self.origin.x {
+            0
+        } else {
+            (column(rect.max_x()) + 1).min(self.columns)
+        };
+        let y0 = row(rect.min_y());
+        let y1 = if rect.max_y() < self.origin.y {
+            0
+        } else {
+            (row(rect.max_y()) + 1).min(self.rows)
+        };
+
+        (x0..x1, y0..y1)
+    }
+}
+
+/// The part of a tile's geometry that belongs to a path.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathRange {
+    /// Index of the path in the slice passed to `tessellate_tiles`.
+    pub path: usize,
+    pub vertices: Range<u32>,
+    pub indices: Range<u32>,
+}
+
+/// The tessellated content of a tile.
+#[derive(Clone, Debug)]
+pub struct TessellatedTile {
+    pub x: u32,
+    pub y: u32,
+    /// The vertices are in the same coordinate space as the input paths.
+    pub geometry: VertexBuffers<Point>,
+    /// The paths that intersect the tile, in the order of the input.
+    pub ranges: Vec<PathRange>,
+}
+
+/// Clips and fill-tessellates paths into each tile of a grid.
+///
+/// Only the tiles that contain some geometry are part of the result, sorted by row and
+/// column.
+pub fn tessellate_tiles(
+    paths: &[Path],
+    grid: &TileGrid,
+    options: &FillOptions,
+) -> Result<Vec<TessellatedTile>, FillError> {
+    let bounds: Vec<Option<Rect>> = paths.iter().map(|path| path_bounds(path)).collect();
+
+    let mut tiles = Vec::new();
+    for y in 0..grid.rows {
+        for x in 0..grid.columns {
+            tiles.push((x, y));
+        }
+    }
+
+    let tessellate = |&(x, y): &(u32, u32)| tessellate_tile(paths, &bounds, grid, x, y, options);
+
+    #[cfg(feature = "parallel")]
+    let results: Vec<_> = tiles.par_iter().map(tessellate).collect();
+    #[cfg(not(feature = "parallel"))]
+    let results: Vec<_> = tiles.iter().map(tessellate).collect();
+
+    let mut output = Vec::new();
+    for result in results {
+        if let Some(tile) = try!{ result } {
+            output.push(tile);
+        }
+    }
+
+    return Ok(output);
+}
+
+fn tessellate_tile(
+    paths: &[Path],
+    bounds: &[Option<Rect>],
+    grid: &TileGrid,
+    x: u32,
+    y: u32,
+    options: &FillOptions,
+) -> Result<Option<TessellatedTile>, FillError> {
+    let tile_rect = grid.tile_rect(x, y);
+    let mut tessellator = FillTessellator::new();
+    let mut tile = TessellatedTile {
+        x: x,
+        y: y,
+        geometry: VertexBuffers::new(),
+        ranges: Vec::new(),
+    };
+
+    for (i, path) in paths.iter().enumerate() {
+        match bounds[i] {
+            Some(ref rect) if overlaps(rect, &tile_rect) => {}
+            _ => { continue; }
+        }
+
+        let mut builder = Path::builder();
+        clip_path_to_rect(
+            path.path_iter().flattened(options.tolerance),
+            &tile_rect,
+            ClipMode::Fill,
+            &mut builder,
+        );
+        let clipped = builder.build();
+        if clipped.points().is_empty() {
+            continue;
+        }
+
+        let first_vertex = tile.geometry.vertices.len() as u32;
+        let first_index = tile.geometry.indices.len() as u32;
+        try!{
+            tessellator.tessellate_path(
+                clipped.path_iter().flattened(options.tolerance),
+                options,
+                &mut BuffersBuilder::new(&mut tile.geometry, TypedPositions::new())
+            )
+        };
+
+        let last_index = tile.geometry.indices.len() as u32;
+        if last_index > first_index {
+            tile.ranges.push(PathRange {
+                path: i,
+                vertices: first_vertex..tile.geometry.vertices.len() as u32,
+                indices: first_index..last_index,
+            });
+        }
+    }
+
+    if tile.ranges.is_empty() {
+        return Ok(None);
+    }
+
+    return Ok(Some(tile));
+}
+
+// Unlike Rect::intersects, works with empty rectangles and rectangles that only share an edge.
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.min_x() <= b.max_x() && b.min_x() <= a.max_x() &&
+        a.min_y() <= b.max_y() && b.min_y() <= a.max_y()
+}
+
+// A conservative bounding rectangle (includes the control points).
+fn path_bounds(path: &Path) -> Option<Rect> {
+    let points = path.points();
+    if points.is_empty() {
+        return None;
+    }
+
+    let mut min = points[0];
+    let mut max = points[0];
+    for p in &points[1..] {
+        min.x = min.x.min(p.x);
+        min.y = min.y.min(p.y);
+        max.x = max.x.max(p.x);
+        max.y = max.y.max(p.y);
+    }
+
+    return Some(Rect::new(min, (max - min).to_size()));
+}
+
+#[test]
+fn test_tessellate_tiles() {
+    // A square overlapping four tiles of a 3x2 grid, and a triangle in the first tile.
+    let mut builder = Path::builder();
+    builder.move_to(point(5.0, 5.0));
+    builder.line_to(point(15.0, 5.0));
+    builder.line_to(point(15.0, 15.0));
+    builder.line_to(point(5.0, 15.0));
+    builder.close();
+    let square = builder.build();
+
+    let mut builder = Path::builder();
+    builder.move_to(point(1.0, 1.0));
+    builder.line_to(point(4.0, 1.0));
+    builder.line_to(point(1.0, 4.0));
+    builder.close();
+    let triangle = builder.build();
+
+    let grid = TileGrid::new(point(0.0, 0.0), size(10.0, 10.0), 3, 2);
+    let tiles = tessellate_tiles(&[square, triangle], &grid, &FillOptions::default()).unwrap();
+
+    // The third column is empty.
+    assert_eq!(tiles.len(), 4);
+    assert_eq!((tiles[0].x, tiles[0].y), (0, 0));
+    assert_eq!(tiles[0].ranges.len(), 2);
+    assert_eq!(tiles[0].ranges[1].path, 1);
+    assert_eq!(tiles[0].ranges[1].indices.end as usize, tiles[0].geometry.indices.len());
+    for tile in &tiles[1..] {
+        assert_eq!(tile.ranges.len(), 1);
+        assert_eq!(tile.ranges[0].path, 0);
+    }
+
+    for tile in &tiles {
+        let rect = grid.tile_rect(tile.x, tile.y);
+        for v in &tile.geometry.vertices {
+            assert!(v.x >= rect.min_x() && v.x <= rect.max_x());
+            assert!(v.y >= rect.min_y() && v.y <= rect.max_y());
+        }
+    }
+}
+
+#[test]
+fn test_tiles_in_rect() {
+    let grid = TileGrid::new(point(0.0, 0.0), size(10.0, 10.0), 4, 4);
+    assert_eq!(grid.tiles_in_rect(&rect(5.0, 5.0, 10.0, 2.0)), (0..2, 0..1));
+    assert_eq!(grid.tiles_in_rect(&rect(-50.0, 35.0, 100.0, 100.0)), (0..4, 3..4));
+    // Outside of the grid.
+    assert!(grid.tiles_in_rect(&rect(-30.0, 5.0, 10.0, 2.0)).0.is_empty());
+    assert!(grid.tiles_in_rect(&rect(5.0, -30.0, 2.0, 10.0)).1.is_empty());
+    assert!(grid.tiles_in_rect(&rect(50.0, 5.0, 10.0, 2.0)).0.is_empty());
+    assert!(grid.tiles_in_rect(&rect(5.0, 50.0, 2.0, 10.0)).1.is_empty());
+}
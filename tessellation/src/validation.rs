@@ -0,0 +1,428 @@
+//! Checking the output of the fill tessellator.
+//!
+//! [validate](fn.validate.html) looks for the kind of problems a tessellation bug typically
+//! produces: indices pointing outside of the vertex buffer, NaN positions, degenerate or
+//! flipped triangles, and triangles that don't cover the same area as the path.
+//!
+//! The checks are expensive (the area of the path is computed exactly, which is quadratic
+//! in the number of edges) and are meant to run in debug builds and tests.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate lyon_tessellation;
+//! # extern crate lyon_core;
+//! # extern crate lyon_path;
+//! # extern crate lyon_path_builder;
+//! # extern crate lyon_path_iterator;
+//! # use lyon_path::Path;
+//! # use lyon_path_builder::*;
+//! # use lyon_path_iterator::*;
+//! # use lyon_core::math::*;
+//! # use lyon_tessellation::*;
+//! # use lyon_tessellation::geometry_builder::*;
+//! # use lyon_tessellation::validation::*;
+//! # fn main() {
+//! # let mut builder = Path::builder();
+//! # builder.move_to(point(0.0, 0.0));
+//! # builder.line_to(point(10.0, 0.0));
+//! # builder.line_to(point(10.0, 10.0));
+//! # builder.line_to(point(0.0, 10.0));
+//! # builder.close();
+//! # let path = builder.build();
+//! # let mut tessellator = FillTessellator::new();
+//! # let options = FillOptions::default();
+//! let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+//! tessellator.tessellate_path(
+//!     path.path_iter().flattened(0.05),
+//!     &options,
+//!     &mut BuffersBuilder::new(&mut buffers, TypedPositions::new()),
+//! ).unwrap();
+//!
+//! debug_assert_eq!(
+//!     validate(&buffers, path.path_iter().flattened(0.05), &ValidationOptions::default()),
+//!     Ok(())
+//! );
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+
+use math::*;
+use core::FlattenedEvent;
+use geometry_builder::VertexBuffers;
+use path_fill::FillRule;
+
+/// A problem found by [validate](fn.validate.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    /// The number of indices is not a multiple of three.
+    IncompleteTriangle,
+    /// A vertex position is NaN or infinite.
+    InvalidVertex { vertex: usize },
+    /// A triangle refers to a vertex that is not in the vertex buffer.
+    IndexOutOfRange { triangle: usize, index: usize },
+    /// The area of a triangle is below the degenerate threshold.
+    DegenerateTriangle { triangle: usize },
+    /// A triangle doesn't have the same orientation as the first non-degenerate one.
+    InconsistentOrientation { triangle: usize },
+    /// The triangles don't cover the same area as the path.
+    AreaMismatch { expected: f32, actual: f32 },
+}
+
+/// Parameters for [validate](fn.validate.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ValidationOptions {
+    /// The fill rule used to compute the area of the path.
+    pub fill_rule: FillRule,
+
+    /// Triangles with an area smaller than this value are considered degenerate.
+    pub degenerate_area: f32,
+
+    /// Whether to report degenerate triangles.
+    pub check_degenerate: bool,
+
+    /// Whether to check that all triangles have the same orientation.
+    pub check_orientation: bool,
+
+    /// Maximum allowed difference between the area of the path and the total area of the
+    /// triangles, relative to the area of the path. Set to `None` to skip the area check.
+    pub area_tolerance: Option<f32>,
+}
+
+impl ValidationOptions {
+    pub fn default() -> ValidationOptions {
+        ValidationOptions {
+            fill_rule: FillRule::EvenOdd,
+            degenerate_area: 1e-6,
+            check_degenerate: true,
+            check_orientation: true,
+            area_tolerance: Some(0.001),
+        }
+    }
+
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> ValidationOptions {
+        self.fill_rule = fill_rule;
+        return self;
+    }
+
+    pub fn with_area_tolerance(mut self, tolerance: Option<f32>) -> ValidationOptions {
+        self.area_tolerance = tolerance;
+        return self;
+    }
+
+    pub fn with_degenerate_area(mut self, area: f32) -> ValidationOptions {
+        self.degenerate_area = area;
+        return self;
+    }
+
+    pub fn with_degenerate_check(mut self, check: bool) -> ValidationOptions {
+        self.check_degenerate = check;
+        return self;
+    }
+
+    pub fn with_orientation_check(mut self, check: bool) -> ValidationOptions {
+        self.check_orientation = check;
+        return self;
+    }
+}
+
+/// Checks the result of a fill tessellation against the path that was tessellated.
+///
+/// The path must be the same sequence of flattened events that was given to the
+/// tessellator (flattened with the same tolerance), otherwise the area check fails.
+///
+/// Returns the first problem that was found.
+pub fn validate<Iter>(
+    buffers: &VertexBuffers<Point>,
+    path: Iter,
+    options: &ValidationOptions,
+) -> Result<(), ValidationError>
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    if buffers.indices.len() % 3 != 0 {
+        return Err(ValidationError::IncompleteTriangle);
+    }
+
+    for (i, v) in buffers.vertices.iter().enumerate() {
+        if !v.x.is_finite() || !v.y.is_finite() {
+            return Err(ValidationError::InvalidVertex { vertex: i });
+        }
+    }
+
+    let num_vertices = buffers.vertices.len();
+    let mut orientation = 0.0;
+    let mut triangles_area = 0.0;
+    for (triangle, indices) in buffers.indices.chunks(3).enumerate() {
+        for &index in indices {
+            if index as usize >= num_vertices {
+                return Err(ValidationError::IndexOutOfRange {
+                    triangle: triangle,
+                    index: index as usize,
+                });
+            }
+        }
+
+        let area = triangle_signed_area(
+            buffers.vertices[indices[0] as usize],
+            buffers.vertices[indices[1] as usize],
+            buffers.vertices[indices[2] as usize],
+        );
+        triangles_area += area.abs();
+
+        if area.abs() <= options.degenerate_area as f64 {
+            if options.check_degenerate {
+                return Err(ValidationError::DegenerateTriangle { triangle: triangle });
+            }
+            continue;
+        }
+
+        if options.check_orientation {
+            if orientation == 0.0 {
+                orientation = area.signum();
+            } else if area.signum() != orientation {
+                return Err(ValidationError::InconsistentOrientation { triangle: triangle });
+            }
+        }
+    }
+
+    if let Some(tolerance) = options.area_tolerance {
+        let path_area = fill_area(path, options.fill_rule);
+        let max_error = (path_area * tolerance as f64).max(options.degenerate_area as f64);
+        if (path_area - triangles_area).abs() > max_error {
+            return Err(ValidationError::AreaMismatch {
+                expected: path_area as f32,
+                actual: triangles_area as f32,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Computes the area of the region covered by a flattened path according to a fill rule.
+///
+/// Sub-paths are implicitly closed. Self-intersections and overlapping sub-paths are taken
+/// into account: the plane is split in horizontal slabs at every endpoint and intersection
+/// so that no edges cross within a slab, and the area of each slab is the sum of the
+/// trapezoids between the edges where the winding number is inside of the shape.
+pub fn fill_area<Iter>(path: Iter, fill_rule: FillRule) -> f64
+where
+    Iter: Iterator<Item = FlattenedEvent>,
+{
+    let edges = collect_edges(path);
+
+    let mut slabs = Vec::with_capacity(edges.len() * 2);
+    for (i, e1) in edges.iter().enumerate() {
+        slabs.push(e1.from.y);
+        slabs.push(e1.to.y);
+        for e2 in &edges[i + 1..] {
+            if let Some(y) = edge_intersection_y(e1, e2) {
+                slabs.push(y);
+            }
+        }
+    }
+    slabs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    slabs.dedup();
+
+    let mut area = 0.0;
+    let mut crossings: Vec<(f64, f64, f64, i32)> = Vec::new();
+    for slab in slabs.windows(2) {
+        let (y0, y1) = (slab[0], slab[1]);
+        if y1 <= y0 {
+            continue;
+        }
+
+        crossings.clear();
+        for edge in &edges {
+            let (min_y, max_y, winding) = if edge.from.y < edge.to.y {
+                (edge.from.y, edge.to.y, 1)
+            } else {
+                (edge.to.y, edge.from.y, -1)
+            };
+            if min_y > y0 || max_y < y1 {
+                continue;
+            }
+            let x0 = edge.x_at(y0);
+            let x1 = edge.x_at(y1);
+            crossings.push(((x0 + x1) * 0.5, x0, x1, winding));
+        }
+        crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let mut winding = 0;
+        for pair in crossings.windows(2) {
+            winding += pair[0].3;
+            let inside = match fill_rule {
+                FillRule::EvenOdd => winding % 2 != 0,
+                FillRule::NonZero => winding != 0,
+            };
+            if inside {
+                let w0 = pair[1].1 - pair[0].1;
+                let w1 = pair[1].2 - pair[0].2;
+                area += (w0 + w1) * 0.5 * (y1 - y0);
+            }
+        }
+    }
+
+    area
+}
+
+struct Edge {
+    from: F64Point,
+    to: F64Point,
+}
+
+impl Edge {
+    fn x_at(&self, y: f64) -> f64 {
+        let dy = self.to.y - self.from.y;
+        if dy == 0.0 {
+            return self.from.x;
+        }
+        let t = ((y - self.from.y) / dy).max(0.0).min(1.0);
+        self.from.x + (self.to.x - self.from.x) * t
+    }
+}
+
+fn collect_edges<Iter: Iterator<Item = FlattenedEvent>>(path: Iter) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    let mut first = F64Point::new(0.0, 0.0);
+    let mut current = first;
+    let to_f64 = |p: Point| F64Point::new(p.x as f64, p.y as f64);
+    for evt in path {
+        match evt {
+            FlattenedEvent::MoveTo(to) => {
+                if current.y != first.y {
+                    edges.push(Edge { from: current, to: first });
+                }
+                first = to_f64(to);
+                current = first;
+            }
+            FlattenedEvent::LineTo(to) => {
+                let to = to_f64(to);
+                // Horizontal edges don't contribute to the area.
+                if to.y != current.y {
+                    edges.push(Edge { from: current, to: to });
+                }
+                current = to;
+            }
+            FlattenedEvent::Close => {
+                if current.y != first.y {
+                    edges.push(Edge { from: current, to: first });
+                }
+                current = first;
+            }
+        }
+    }
+    if current.y != first.y {
+        edges.push(Edge { from: current, to: first });
+    }
+
+    edges
+}
+
+fn edge_intersection_y(e1: &Edge, e2: &Edge) -> Option<f64> {
+    let d1 = e1.to - e1.from;
+    let d2 = e2.to - e2.from;
+    let denom = d1.x * d2.y - d1.y * d2.x;
+    if denom == 0.0 {
+        return None;
+    }
+    let diff = e2.from - e1.from;
+    let t = (diff.x * d2.y - diff.y * d2.x) / denom;
+    let u = (diff.x * d1.y - diff.y * d1.x) / denom;
+    if t <= 0.0 || t >= 1.0 || u <= 0.0 || u >= 1.0 {
+        return None;
+    }
+
+    Some(e1.from.y + d1.y * t)
+}
+
+fn triangle_signed_area(a: Point, b: Point, c: Point) -> f64 {
+    let (ax, ay) = (a.x as f64, a.y as f64);
+    let (bx, by) = (b.x as f64, b.y as f64);
+    let (cx, cy) = (c.x as f64, c.y as f64);
+    ((bx - ax) * (cy - ay) - (cx - ax) * (by - ay)) * 0.5
+}
+
+#[cfg(test)]
+use path::Path;
+#[cfg(test)]
+use path_builder::BaseBuilder;
+#[cfg(test)]
+use path_iterator::PathIterator;
+#[cfg(test)]
+use path_fill::{FillTessellator, FillOptions};
+#[cfg(test)]
+use geometry_builder::{BuffersBuilder, TypedPositions};
+
+#[cfg(test)]
+fn square_with_hole() -> Path {
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    builder.move_to(point(2.0, 2.0));
+    builder.line_to(point(8.0, 2.0));
+    builder.line_to(point(8.0, 8.0));
+    builder.line_to(point(2.0, 8.0));
+    builder.close();
+    return builder.build();
+}
+
+#[test]
+fn test_fill_area() {
+    let path = square_with_hole();
+    // Both sub-paths have the same winding, so the hole is only a hole with even-odd.
+    assert_eq!(fill_area(path.path_iter().flattened(0.1), FillRule::EvenOdd), 64.0);
+    assert_eq!(fill_area(path.path_iter().flattened(0.1), FillRule::NonZero), 100.0);
+
+    // A bow tie: two triangles of area 25 meeting at (5, 5).
+    let mut builder = Path::builder();
+    builder.move_to(point(0.0, 0.0));
+    builder.line_to(point(10.0, 10.0));
+    builder.line_to(point(10.0, 0.0));
+    builder.line_to(point(0.0, 10.0));
+    builder.close();
+    let path = builder.build();
+    assert_eq!(fill_area(path.path_iter().flattened(0.1), FillRule::EvenOdd), 50.0);
+}
+
+#[test]
+fn test_validate() {
+    let path = square_with_hole();
+    let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+    FillTessellator::new().tessellate_path(
+        path.path_iter().flattened(0.1),
+        &FillOptions::default(),
+        &mut BuffersBuilder::new(&mut buffers, TypedPositions::new())
+    ).unwrap();
+
+    let options = ValidationOptions::default();
+    assert_eq!(validate(&buffers, path.path_iter().flattened(0.1), &options), Ok(()));
+
+    let mut broken = buffers.clone();
+    broken.indices.truncate(broken.indices.len() - 3);
+    match validate(&broken, path.path_iter().flattened(0.1), &options) {
+        Err(ValidationError::AreaMismatch { .. }) => {}
+        err => panic!("unexpected result {:?}", err),
+    }
+
+    let mut broken = buffers.clone();
+    broken.indices.push(0);
+    broken.indices.push(1);
+    broken.indices.push(1000);
+    assert_eq!(
+        validate(&broken, path.path_iter().flattened(0.1), &options),
+        Err(ValidationError::IndexOutOfRange { triangle: buffers.indices.len() / 3, index: 1000 })
+    );
+
+    let mut broken = buffers.clone();
+    broken.indices.swap(0, 1);
+    assert_eq!(
+        validate(&broken, path.path_iter().flattened(0.1), &options),
+        Err(ValidationError::InconsistentOrientation { triangle: 1 })
+    );
+}
@@ -0,0 +1,123 @@
+//! # Lyon WebAssembly bindings
+//!
+//! Exposes the fill and stroke tessellators to JavaScript through wasm-bindgen.
+//!
+//! Each function parses an SVG path string and returns the resulting triangles as a flat
+//! `Float32Array` of vertex positions (`[x0, y0, x1, y1, x2, y2, ...]`), which can be
+//! uploaded as is and drawn without an index buffer.
+
+extern crate lyon_core as core;
+extern crate lyon_path as path;
+extern crate lyon_path_builder as path_builder;
+extern crate lyon_path_iterator as path_iterator;
+extern crate lyon_svg as svg;
+extern crate lyon_tessellation as tessellation;
+extern crate wasm_bindgen;
+
+use wasm_bindgen::prelude::*;
+
+use core::math::*;
+use path::Path;
+use path_builder::*;
+use path_iterator::*;
+use svg::parser::path::PathTokenizer;
+use tessellation::geometry_builder::{VertexBuffers, BuffersBuilder, VertexConstructor};
+use tessellation::path_fill::{FillTessellator, FillOptions};
+use tessellation::path_stroke::{StrokeTessellator, StrokeOptions};
+use tessellation::{FillVertex, StrokeVertex};
+
+/// Tessellates the fill of an SVG path string.
+#[wasm_bindgen]
+pub fn fill_path(path: &str, tolerance: f32) -> Result<Vec<f32>, JsValue> {
+    let path = try!{ parse_path(path) };
+
+    let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+    let result = FillTessellator::new().tessellate_path(
+        path.path_iter().flattened(tolerance),
+        &FillOptions::default().with_tolerance(tolerance),
+        &mut BuffersBuilder::new(&mut buffers, Positions)
+    );
+
+    if result.is_err() {
+        return Err(JsValue::from_str("Fill tessellation failed."));
+    }
+
+    return Ok(to_triangle_list(&buffers));
+}
+
+/// Tessellates the stroke of an SVG path string.
+#[wasm_bindgen]
+pub fn stroke_path(path: &str, line_width: f32, tolerance: f32) -> Result<Vec<f32>, JsValue> {
+    let path = try!{ parse_path(path) };
+
+    let mut buffers: VertexBuffers<Point> = VertexBuffers::new();
+    let result = StrokeTessellator::new().tessellate(
+        path.path_iter().flattened(tolerance),
+        &StrokeOptions::default().with_tolerance(tolerance).with_line_width(line_width),
+        &mut BuffersBuilder::new(&mut buffers, StrokeWidth(line_width))
+    );
+
+    if result.is_err() {
+        return Err(JsValue::from_str("Stroke tessellation failed."));
+    }
+
+    return Ok(to_triangle_list(&buffers));
+}
+
+fn parse_path(text: &str) -> Result<Path, JsValue> {
+    let mut builder = SvgPathBuilder::new(Path::builder());
+
+    for item in PathTokenizer::new(text) {
+        match item {
+            Ok(event) => { builder.svg_event(event); }
+            Err(_) => { return Err(JsValue::from_str("Invalid SVG path.")); }
+        }
+    }
+
+    return Ok(builder.build());
+}
+
+/// Expands the indexed geometry into a list of triangles.
+fn to_triangle_list(buffers: &VertexBuffers<Point>) -> Vec<f32> {
+    let mut output = Vec::with_capacity(buffers.indices.len() * 2);
+    for idx in &buffers.indices {
+        let vertex = buffers.vertices[*idx as usize];
+        output.push(vertex.x);
+        output.push(vertex.y);
+    }
+
+    return output;
+}
+
+struct Positions;
+
+impl VertexConstructor<FillVertex, Point> for Positions {
+    fn new_vertex(&mut self, vertex: FillVertex) -> Point { vertex.position }
+}
+
+// The normals of the stroke vertices are relative to a width of 1.0.
+struct StrokeWidth(f32);
+
+impl VertexConstructor<StrokeVertex, Point> for StrokeWidth {
+    fn new_vertex(&mut self, vertex: StrokeVertex) -> Point {
+        vertex.position + vertex.normal * self.0
+    }
+}
+
+#[test]
+fn test_fill_square() {
+    let triangles = fill_path("M 0 0 L 10 0 L 10 10 L 0 10 Z", 0.1).unwrap();
+    // Two triangles, three vertices each, two floats per vertex.
+    assert_eq!(triangles.len(), 12);
+}
+
+#[test]
+fn test_stroke_line() {
+    let triangles = stroke_path("M 0 0 L 10 0", 2.0, 0.1).unwrap();
+    assert!(triangles.len() > 0);
+    assert_eq!(triangles.len() % 6, 0);
+    // The sides of the stroke are one unit away from the line.
+    for y in triangles.iter().skip(1).step_by(2) {
+        assert!((y.abs() - 1.0).abs() < 0.0001);
+    }
+}